@@ -59,9 +59,9 @@ fn main() -> anyhow::Result<()> {
     let file = cli.file.or(cli.positional_file);
 
     if cli.interactive {
-        run_repl()?;
+        run_repl(&cli.define)?;
     } else if let Some(file) = file {
-        run_file(file, cli.args)?;
+        run_file(file, cli.args, &cli.define)?;
     } else {
         // If no arguments, show help
         use clap::CommandFactory;
@@ -71,6 +71,17 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Apply `-d name[=value]` entries to the request's ini settings
+fn apply_ini_defines(vm: &mut VM, defines: &[String]) {
+    for define in defines {
+        let (name, value) = define.split_once('=').unwrap_or((define.as_str(), "1"));
+        vm.context
+            .config
+            .ini_settings
+            .insert(name.trim().to_string(), value.trim().to_string());
+    }
+}
+
 fn create_engine() -> anyhow::Result<Arc<EngineContext>> {
     let builder = EngineBuilder::new();
 
@@ -238,7 +249,7 @@ fn save_readline_history(path: &Path, editor: &DefaultEditor) -> anyhow::Result<
     Ok(())
 }
 
-fn run_repl() -> anyhow::Result<()> {
+fn run_repl(ini_defines: &[String]) -> anyhow::Result<()> {
     let mut rl = DefaultEditor::new()?;
 
     // Use ~/.php_history for REPL history (compatible with PHP's readline format)
@@ -256,6 +267,7 @@ fn run_repl() -> anyhow::Result<()> {
 
     let engine_context = create_engine()?;
     let mut vm = VM::new_with_sapi(engine_context, php_rs::sapi::SapiMode::Cli);
+    apply_ini_defines(&mut vm, ini_defines);
     let output_state = Rc::new(RefCell::new(ReplOutputState::default()));
     let output_writer = TrackingOutputWriter::new(StdoutWriter::default(), output_state.clone());
     vm.set_output_writer(Box::new(output_writer));
@@ -317,7 +329,7 @@ fn run_repl() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn run_file(path: PathBuf, args: Vec<String>) -> anyhow::Result<()> {
+fn run_file(path: PathBuf, args: Vec<String>, ini_defines: &[String]) -> anyhow::Result<()> {
     let source = fs::read_to_string(&path)?;
     let script_name = path.to_string_lossy().into_owned();
     let canonical_path = path.canonicalize().unwrap_or_else(|_| path.clone());
@@ -329,6 +341,7 @@ fn run_file(path: PathBuf, args: Vec<String>) -> anyhow::Result<()> {
 
     let engine_context = create_engine()?;
     let mut vm = VM::new_with_sapi(engine_context, php_rs::sapi::SapiMode::Cli);
+    apply_ini_defines(&mut vm, ini_defines);
 
     populate_env_superglobals(&mut vm);
 
@@ -522,14 +535,18 @@ fn ensure_superglobal_key(vm: &mut VM, sym: Symbol, key: &[u8], value: &[u8]) {
 fn execute_source(source: &str, file_path: Option<&Path>, vm: &mut VM) -> Result<(), VmError> {
     let source_bytes = source.as_bytes();
     let arena = Bump::new();
-    let lexer = Lexer::new(source_bytes);
+    let lexer = Lexer::new(source_bytes).with_short_open_tag(vm.context.config.short_open_tag());
     let mut parser = PhpParser::new(lexer, &arena);
 
     let program = parser.parse_program();
 
     if !program.errors.is_empty() {
+        let path = file_path.map(|path| path.to_string_lossy());
         for error in program.errors {
-            println!("{}", error.to_human_readable(source_bytes));
+            println!(
+                "{}",
+                error.to_human_readable_with_path(source_bytes, path.as_deref())
+            );
         }
         return Ok(());
     }
@@ -548,6 +565,10 @@ fn execute_source(source: &str, file_path: Option<&Path>, vm: &mut VM) -> Result
             eprintln!("Runtime error in {} on line {}: {:?}", file, line, err);
         }
         vm.reset_after_error();
+        // Output produced before the error still goes out, and any
+        // zlib.output_compression stream gets its trailer
+        let _ = php_rs::builtins::output_control::flush_all_output_buffers(vm);
+        let _ = vm.flush_output();
         return Err(err);
     }
 
@@ -1,5 +1,5 @@
 use crate::core::value::{ArrayData, ArrayKey, ConstArrayKey, Handle, Val};
-use crate::vm::engine::VM;
+use crate::vm::engine::{ErrorLevel, VM};
 use indexmap::IndexMap;
 use smallvec::smallvec;
 use std::collections::HashSet;
@@ -567,7 +567,7 @@ pub fn php_array_key_exists(vm: &mut VM, args: &[Handle]) -> Result<Handle, Stri
     if let Val::Array(arr_rc) = &arr_val.value {
         let key = match key_val {
             Val::Int(i) => ArrayKey::Int(i),
-            Val::String(s) => ArrayKey::Str(s.into()),
+            Val::String(s) => ArrayKey::from_string(s),
             Val::Float(f) => ArrayKey::Int(f as i64),
             Val::Bool(b) => ArrayKey::Int(if b { 1 } else { 0 }),
             Val::Null => ArrayKey::Str(vec![].into()),
@@ -581,7 +581,11 @@ pub fn php_array_key_exists(vm: &mut VM, args: &[Handle]) -> Result<Handle, Stri
         let exists = arr_rc.map.contains_key(&key);
         Ok(vm.arena.alloc(Val::Bool(exists)))
     } else {
-        Err("array_key_exists(): Argument #2 ($array) must be of type array".into())
+        // PHP 8 no longer looks at object properties here; use property_exists()
+        Err(format!(
+            "array_key_exists(): Argument #2 ($array) must be of type array, {} given",
+            vm.get_type_name(args[1])
+        ))
     }
 }
 
@@ -660,7 +664,7 @@ pub fn php_array_fill_keys(vm: &mut VM, args: &[Handle]) -> Result<Handle, Strin
         let key_val = vm.arena.get(key_handle).value.clone();
         let key = match key_val {
             Val::Int(i) => ArrayKey::Int(i),
-            Val::String(s) => ArrayKey::Str(s.into()),
+            Val::String(s) => ArrayKey::from_string(s),
             Val::Float(f) => ArrayKey::Int(f as i64),
             Val::Bool(b) => ArrayKey::Int(if b { 1 } else { 0 }),
             Val::Null => ArrayKey::Str(vec![].into()),
@@ -899,7 +903,7 @@ pub fn php_array_flip(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         let val_val = vm.arena.get(val_handle).value.clone();
         let new_key = match val_val {
             Val::Int(i) => ArrayKey::Int(i),
-            Val::String(s) => ArrayKey::Str(s.into()),
+            Val::String(s) => ArrayKey::from_string(s),
             Val::Float(f) => ArrayKey::Int(f as i64),
             Val::Bool(b) => ArrayKey::Int(if b { 1 } else { 0 }),
             Val::Null => ArrayKey::Str(vec![].into()),
@@ -916,7 +920,7 @@ pub fn php_array_flip(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 
         if let ArrayKey::Int(i) = new_key {
             if i >= next_free {
-                next_free = i + 1;
+                next_free = i.saturating_add(1);
             }
         }
         map.insert(new_key, vm.arena.alloc(new_val));
@@ -1000,7 +1004,7 @@ pub fn php_array_column(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
     let column_key = match column_key_val {
         Val::Null => None,
         Val::Int(i) => Some(ArrayKey::Int(i)),
-        Val::String(s) => Some(ArrayKey::Str(s.into())),
+        Val::String(s) => Some(ArrayKey::from_string(s)),
         _ => return Err("array_column(): Column key must be int, string or null".into()),
     };
 
@@ -1009,7 +1013,7 @@ pub fn php_array_column(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
         match index_key_val {
             Val::Null => None,
             Val::Int(i) => Some(ArrayKey::Int(i)),
-            Val::String(s) => Some(ArrayKey::Str(s.into())),
+            Val::String(s) => Some(ArrayKey::from_string(s)),
             _ => return Err("array_column(): Index key must be int, string or null".into()),
         }
     } else {
@@ -1042,7 +1046,7 @@ pub fn php_array_column(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
                     let kv = vm.arena.get(kh).value.clone();
                     match kv {
                         Val::Int(i) => ArrayKey::Int(i),
-                        Val::String(s) => ArrayKey::Str(s.into()),
+                        Val::String(s) => ArrayKey::from_string(s),
                         Val::Float(f) => ArrayKey::Int(f as i64),
                         Val::Bool(b) => ArrayKey::Int(if b { 1 } else { 0 }),
                         Val::Null => ArrayKey::Str(vec![].into()),
@@ -1202,7 +1206,7 @@ pub fn php_array_combine(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
         let key_val = vm.arena.get(key_handle).value.clone();
         let key = match key_val {
             Val::Int(i) => ArrayKey::Int(i),
-            Val::String(s) => ArrayKey::Str(s.into()),
+            Val::String(s) => ArrayKey::from_string(s),
             Val::Float(f) => ArrayKey::Int(f as i64),
             Val::Bool(b) => ArrayKey::Int(if b { 1 } else { 0 }),
             Val::Null => ArrayKey::Str(vec![].into()),
@@ -1244,7 +1248,7 @@ pub fn php_array_count_values(vm: &mut VM, args: &[Handle]) -> Result<Handle, St
         let v = vm.arena.get(val_handle).value.clone();
         let key = match v {
             Val::Int(i) => ArrayKey::Int(i),
-            Val::String(s) => ArrayKey::Str(s.into()),
+            Val::String(s) => ArrayKey::from_string(s),
             _ => continue, // PHP only counts strings and integers
         };
 
@@ -1439,86 +1443,129 @@ pub fn php_array_pad(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 }
 
 pub fn php_array_sum(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    array_binop(
+        vm,
+        args,
+        "array_sum",
+        "Addition",
+        0,
+        i64::checked_add,
+        |a, b| a + b,
+    )
+}
+
+pub fn php_array_product(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    array_binop(
+        vm,
+        args,
+        "array_product",
+        "Multiplication",
+        1,
+        i64::checked_mul,
+        |a, b| a * b,
+    )
+}
+
+/// Fold an array with `+` or `*` the way php_array_binop does: integer results
+/// that overflow continue as floats, and arrays and objects are skipped with a
+/// warning.
+/// Reference: $PHP_SRC_PATH/ext/standard/array.c - php_array_binop
+fn array_binop(
+    vm: &mut VM,
+    args: &[Handle],
+    func: &str,
+    op_name: &str,
+    initial: i64,
+    int_op: fn(i64, i64) -> Option<i64>,
+    float_op: fn(f64, f64) -> f64,
+) -> Result<Handle, String> {
     if args.len() != 1 {
-        return Err("array_sum() expects exactly 1 parameter".into());
+        return Err(format!("{}() expects exactly 1 parameter", func));
     }
 
-    let val = vm.arena.get(args[0]);
-    let arr = match &val.value {
-        Val::Array(arr) => arr,
-        _ => return Err("array_sum(): Argument #1 ($array) must be of type array".into()),
+    let arr = match &vm.arena.get(args[0]).value {
+        Val::Array(arr) => arr.clone(),
+        _ => {
+            return Err(format!(
+                "{}(): Argument #1 ($array) must be of type array",
+                func
+            ));
+        }
     };
 
-    let mut sum_f = 0.0;
-    let mut is_float = false;
-
-    for (_, &val_handle) in &arr.map {
-        let v = vm.arena.get(val_handle).value.clone();
-        match &v {
-            Val::Int(i) => sum_f += *i as f64,
-            Val::Float(f) => {
-                sum_f += *f;
-                is_float = true;
+    let mut acc = Val::Int(initial);
+    for &val_handle in arr.map.values() {
+        let operand = match &vm.arena.get(val_handle).value {
+            Val::Array(_) | Val::ConstArray(_) => Err("array".to_string()),
+            Val::Object(payload) => Err(vm.describe_object_class(*payload)),
+            v => Ok(scalar_to_number(v)),
+        };
+        let operand = match operand {
+            Ok(operand) => operand,
+            Err(type_name) => {
+                vm.report_error(
+                    ErrorLevel::Warning,
+                    &format!(
+                        "{}(): {} is not supported on type {}",
+                        func, op_name, type_name
+                    ),
+                );
+                continue;
             }
-            Val::String(s) => {
-                let (i, f) = Val::parse_numeric_string(s);
-                if f {
-                    sum_f += v.to_float();
-                    is_float = true;
-                } else {
-                    sum_f += i as f64;
-                }
+        };
+
+        acc = match (&acc, &operand) {
+            (Val::Int(a), Val::Int(b)) => {
+                int_op(*a, *b).map_or_else(|| Val::Float(float_op(*a as f64, *b as f64)), Val::Int)
             }
-            _ => sum_f += v.to_float(),
-        }
+            _ => Val::Float(float_op(acc.to_float(), operand.to_float())),
+        };
     }
 
-    if is_float {
-        Ok(vm.arena.alloc(Val::Float(sum_f)))
-    } else {
-        Ok(vm.arena.alloc(Val::Int(sum_f as i64)))
-    }
+    Ok(vm.arena.alloc(acc))
 }
 
-pub fn php_array_product(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
-    if args.len() != 1 {
-        return Err("array_product() expects exactly 1 parameter".into());
-    }
-
-    let val = vm.arena.get(args[0]);
-    let arr = match &val.value {
-        Val::Array(arr) => arr,
-        _ => return Err("array_product(): Argument #1 ($array) must be of type array".into()),
+/// Numeric value of a scalar: numeric and leading-numeric strings become an
+/// int or float, other strings count as 0.
+/// Reference: $PHP_SRC_PATH/Zend/zend_operators.c - convert_scalar_to_number
+fn scalar_to_number(val: &Val) -> Val {
+    let s = match val {
+        Val::Float(f) => return Val::Float(*f),
+        Val::String(s) => s.trim_ascii_start(),
+        other => return Val::Int(other.to_int()),
     };
 
-    if arr.map.is_empty() {
-        return Ok(vm.arena.alloc(Val::Int(1)));
-    }
-
-    let mut prod_f = 1.0;
+    let sign = usize::from(matches!(s.first(), Some(b'+' | b'-')));
+    let digits = |from: usize| {
+        s[from.min(s.len())..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+    let int_digits = digits(sign);
+    let mut end = sign + int_digits;
     let mut is_float = false;
 
-    for (_, &val_handle) in &arr.map {
-        let v = vm.arena.get(val_handle).value.clone();
-        match v {
-            Val::Int(i) => prod_f *= i as f64,
-            Val::Float(f) => {
-                prod_f *= f;
-                is_float = true;
-            }
-            _ => {
-                prod_f *= v.to_float();
-                if matches!(v, Val::Float(_)) {
-                    is_float = true;
-                }
-            }
+    if s.get(end) == Some(&b'.') && (int_digits > 0 || digits(end + 1) > 0) {
+        end += 1 + digits(end + 1);
+        is_float = true;
+    }
+    if end > sign && matches!(s.get(end), Some(b'e' | b'E')) {
+        let exp_sign = usize::from(matches!(s.get(end + 1), Some(b'+' | b'-')));
+        let exp_digits = digits(end + 1 + exp_sign);
+        if exp_digits > 0 {
+            end += 1 + exp_sign + exp_digits;
+            is_float = true;
         }
     }
+    if end == sign {
+        return Val::Int(0);
+    }
 
-    if is_float {
-        Ok(vm.arena.alloc(Val::Float(prod_f)))
-    } else {
-        Ok(vm.arena.alloc(Val::Int(prod_f as i64)))
+    let prefix = std::str::from_utf8(&s[..end]).unwrap_or("0");
+    match prefix.parse::<i64>() {
+        Ok(i) if !is_float => Val::Int(i),
+        _ => Val::Float(prefix.parse().unwrap_or(0.0)),
     }
 }
 
@@ -2079,13 +2126,18 @@ pub fn php_array_diff(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     };
 
     let mut other_values = HashSet::new();
-    for &arg in &args[1..] {
+    for (idx, &arg) in args.iter().enumerate().skip(1) {
         let val = vm.arena.get(arg);
-        if let Val::Array(arr) = &val.value {
-            for (_, &vh) in &arr.map {
-                let v = vm.arena.get(vh).value.clone();
-                other_values.insert(v.to_php_string_bytes());
-            }
+        let Val::Array(arr) = &val.value else {
+            return Err(format!(
+                "array_diff(): Argument #{} must be of type array, {} given",
+                idx + 1,
+                vm.get_type_name(arg)
+            ));
+        };
+        for (_, &vh) in &arr.map {
+            let v = vm.arena.get(vh).value.clone();
+            other_values.insert(v.to_php_string_bytes());
         }
     }
 
@@ -2126,16 +2178,21 @@ pub fn php_array_intersect(vm: &mut VM, args: &[Handle]) -> Result<Handle, Strin
     };
 
     let mut intersect_sets = Vec::new();
-    for &arg in &args[1..] {
+    for (idx, &arg) in args.iter().enumerate().skip(1) {
         let val = vm.arena.get(arg);
-        if let Val::Array(arr) = &val.value {
-            let mut set = HashSet::new();
-            for (_, &vh) in &arr.map {
-                let v = vm.arena.get(vh).value.clone();
-                set.insert(v.to_php_string_bytes());
-            }
-            intersect_sets.push(set);
+        let Val::Array(arr) = &val.value else {
+            return Err(format!(
+                "array_intersect(): Argument #{} must be of type array, {} given",
+                idx + 1,
+                vm.get_type_name(arg)
+            ));
+        };
+        let mut set = HashSet::new();
+        for (_, &vh) in &arr.map {
+            let v = vm.arena.get(vh).value.clone();
+            set.insert(v.to_php_string_bytes());
         }
+        intersect_sets.push(set);
     }
 
     let mut result_map = IndexMap::new();
@@ -0,0 +1,400 @@
+use crate::core::value::{ArrayData, ArrayKey, Handle, Val};
+use crate::vm::engine::{ErrorLevel, VM};
+use bzip2::read::BzDecoder;
+use bzip2::write::BzEncoder;
+use bzip2::{Action, Compress, Compression, Decompress, Status};
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::rc::Rc;
+
+// libbzip2 return codes, as reported by bzcompress()/bzdecompress() and bzerrno()
+const BZ_OK: i64 = 0;
+const BZ_SEQUENCE_ERROR: i64 = -1;
+const BZ_PARAM_ERROR: i64 = -2;
+const BZ_DATA_ERROR: i64 = -4;
+const BZ_DATA_ERROR_MAGIC: i64 = -5;
+const BZ_IO_ERROR: i64 = -6;
+const BZ_UNEXPECTED_EOF: i64 = -7;
+
+pub struct BzFile {
+    stream: RefCell<BzStream>,
+    errno: Cell<i64>,
+}
+
+enum BzStream {
+    Reader(BzDecoder<File>),
+    Writer(BzEncoder<File>),
+    Closed,
+}
+
+fn error_code(err: bzip2::Error) -> i64 {
+    match err {
+        bzip2::Error::Sequence => BZ_SEQUENCE_ERROR,
+        bzip2::Error::Data => BZ_DATA_ERROR,
+        bzip2::Error::DataMagic => BZ_DATA_ERROR_MAGIC,
+        bzip2::Error::Param => BZ_PARAM_ERROR,
+    }
+}
+
+fn io_error_code(err: &std::io::Error) -> i64 {
+    if err.kind() == std::io::ErrorKind::UnexpectedEof {
+        return BZ_UNEXPECTED_EOF;
+    }
+    err.get_ref()
+        .and_then(|inner| inner.downcast_ref::<bzip2::Error>())
+        .map(|e| error_code(*e))
+        .unwrap_or(BZ_IO_ERROR)
+}
+
+fn error_str(errno: i64) -> &'static str {
+    match errno {
+        BZ_OK => "OK",
+        BZ_SEQUENCE_ERROR => "SEQUENCE_ERROR",
+        BZ_PARAM_ERROR => "PARAM_ERROR",
+        -3 => "MEM_ERROR",
+        BZ_DATA_ERROR => "DATA_ERROR",
+        BZ_DATA_ERROR_MAGIC => "DATA_ERROR_MAGIC",
+        BZ_IO_ERROR => "IO_ERROR",
+        BZ_UNEXPECTED_EOF => "UNEXPECTED_EOF",
+        -8 => "OUTBUFF_FULL",
+        -9 => "CONFIG_ERROR",
+        _ => "???",
+    }
+}
+
+/// Report a recoverable stream failure as a warning and return false
+fn warn_false(vm: &mut VM, func: &str, err: impl std::fmt::Display) -> Result<Handle, String> {
+    vm.report_error(ErrorLevel::Warning, &format!("{}(): {}", func, err));
+    Ok(vm.arena.alloc(Val::Bool(false)))
+}
+
+fn bz_file(vm: &VM, func: &str, handle: Handle) -> Result<Rc<dyn std::any::Any>, String> {
+    let resource = match &vm.arena.get(handle).value {
+        Val::Resource(r) => r.clone(),
+        _ => {
+            return Err(format!(
+                "{}(): Argument #1 ($bz) must be of type resource",
+                func
+            ));
+        }
+    };
+    if resource.downcast_ref::<BzFile>().is_none() {
+        return Err(format!("{}(): Invalid resource", func));
+    }
+    Ok(resource)
+}
+
+/// bzcompress(string $data, int $block_size = 4, int $work_factor = 0): string|int
+pub fn php_bzcompress(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() || args.len() > 3 {
+        return Err("bzcompress() expects 1 to 3 parameters".into());
+    }
+
+    let data = match &vm.arena.get(args[0]).value {
+        Val::String(s) => s.clone(),
+        _ => return Err("bzcompress(): Argument #1 ($data) must be of type string".into()),
+    };
+
+    let block_size = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => *i,
+        Some(_) => {
+            return Err("bzcompress(): Argument #2 ($block_size) must be of type int".into());
+        }
+        None => 4,
+    };
+    if !(1..=9).contains(&block_size) {
+        return Err("bzcompress(): Argument #2 ($block_size) must be between 1 and 9".into());
+    }
+
+    let work_factor = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => *i,
+        Some(_) => {
+            return Err("bzcompress(): Argument #3 ($work_factor) must be of type int".into());
+        }
+        None => 0,
+    };
+    if !(0..=250).contains(&work_factor) {
+        return Err("bzcompress(): Argument #3 ($work_factor) must be between 0 and 250".into());
+    }
+
+    let mut compress = Compress::new(Compression::new(block_size as u32), work_factor as u32);
+    // Same initial estimate as libbzip2's documented worst case
+    let mut buffer = Vec::with_capacity(data.len() + data.len() / 100 + 600);
+    loop {
+        let consumed = compress.total_in() as usize;
+        match compress.compress_vec(&data[consumed..], &mut buffer, Action::Finish) {
+            Ok(Status::StreamEnd) => break,
+            Ok(_) => buffer.reserve(buffer.capacity().max(4096)),
+            Err(e) => return Ok(vm.arena.alloc(Val::Int(error_code(e)))),
+        }
+    }
+
+    Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
+}
+
+/// bzdecompress(string $data, bool $use_less_memory = false): string|int|false
+pub fn php_bzdecompress(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() || args.len() > 2 {
+        return Err("bzdecompress() expects 1 or 2 parameters".into());
+    }
+
+    let data = match &vm.arena.get(args[0]).value {
+        Val::String(s) => s.clone(),
+        _ => return Err("bzdecompress(): Argument #1 ($data) must be of type string".into()),
+    };
+
+    let use_less_memory = args
+        .get(1)
+        .map(|h| vm.arena.get(*h).value.to_bool())
+        .unwrap_or(false);
+
+    let mut decompress = Decompress::new(use_less_memory);
+    let mut buffer = Vec::with_capacity(data.len() * 4);
+    loop {
+        let consumed = decompress.total_in() as usize;
+        let produced = buffer.len();
+        match decompress.decompress_vec(&data[consumed..], &mut buffer) {
+            Ok(Status::StreamEnd) => break,
+            Ok(_) if buffer.len() == buffer.capacity() => {
+                buffer.reserve(buffer.capacity().max(4096));
+            }
+            // Truncated input yields whatever was decoded so far, as in PHP
+            Ok(_) if decompress.total_in() as usize == consumed && buffer.len() == produced => {
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => return Ok(vm.arena.alloc(Val::Int(error_code(e)))),
+        }
+    }
+
+    Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
+}
+
+/// bzopen(string $file, string $mode): resource|false
+pub fn php_bzopen(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() != 2 {
+        return Err("bzopen() expects exactly 2 parameters".into());
+    }
+
+    let filename = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => return Err("bzopen(): Argument #1 ($file) must be of type string".into()),
+    };
+
+    let mode = match &vm.arena.get(args[1]).value {
+        Val::String(s) => s.clone(),
+        _ => return Err("bzopen(): Argument #2 ($mode) must be of type string".into()),
+    };
+
+    if filename.is_empty() {
+        return Err("bzopen(): Argument #1 ($file) cannot be empty".into());
+    }
+
+    let stream = match mode.as_slice() {
+        b"r" => match File::open(&filename) {
+            Ok(f) => BzStream::Reader(BzDecoder::new(f)),
+            Err(e) => return warn_false(vm, "bzopen", format!("{}: {}", filename, e)),
+        },
+        b"w" => match File::create(&filename) {
+            Ok(f) => BzStream::Writer(BzEncoder::new(f, Compression::best())),
+            Err(e) => return warn_false(vm, "bzopen", format!("{}: {}", filename, e)),
+        },
+        _ => {
+            return Err("bzopen(): Argument #2 ($mode) must be either \"r\" or \"w\"".into());
+        }
+    };
+
+    let file = BzFile {
+        stream: RefCell::new(stream),
+        errno: Cell::new(BZ_OK),
+    };
+    Ok(vm.arena.alloc(Val::Resource(Rc::new(file))))
+}
+
+/// bzread(resource $bz, int $length = 1024): string|false
+pub fn php_bzread(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() || args.len() > 2 {
+        return Err("bzread() expects 1 or 2 parameters".into());
+    }
+
+    let length = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => *i,
+        Some(_) => return Err("bzread(): Argument #2 ($length) must be of type int".into()),
+        None => 1024,
+    };
+    if length < 0 {
+        return Err("bzread(): Argument #2 ($length) must be greater than or equal to 0".into());
+    }
+
+    let resource = bz_file(vm, "bzread", args[0])?;
+    let bz = resource.downcast_ref::<BzFile>().unwrap();
+
+    let mut buffer = vec![0u8; length as usize];
+    let mut filled = 0;
+    let result = match &mut *bz.stream.borrow_mut() {
+        BzStream::Reader(decoder) => loop {
+            if filled == buffer.len() {
+                break Ok(());
+            }
+            match decoder.read(&mut buffer[filled..]) {
+                Ok(0) => break Ok(()),
+                Ok(n) => filled += n,
+                Err(e) => break Err(e),
+            }
+        },
+        BzStream::Writer(_) => {
+            return warn_false(vm, "bzread", "Stream was not opened for reading");
+        }
+        BzStream::Closed => {
+            return Err("bzread(): supplied resource is not a valid stream resource".into());
+        }
+    };
+
+    if let Err(e) = result {
+        bz.errno.set(io_error_code(&e));
+        return warn_false(vm, "bzread", "could not read valid bz2 data from stream");
+    }
+    buffer.truncate(filled);
+
+    Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
+}
+
+/// bzwrite(resource $bz, string $data, ?int $length = null): int|false
+pub fn php_bzwrite(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err("bzwrite() expects 2 or 3 parameters".into());
+    }
+
+    let data = match &vm.arena.get(args[1]).value {
+        Val::String(s) => s.clone(),
+        _ => return Err("bzwrite(): Argument #2 ($data) must be of type string".into()),
+    };
+
+    let length = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) if *i < 0 => {
+            return Err(
+                "bzwrite(): Argument #3 ($length) must be greater than or equal to 0".into(),
+            );
+        }
+        Some(Val::Int(i)) => (*i as usize).min(data.len()),
+        Some(Val::Null) | None => data.len(),
+        Some(_) => {
+            return Err("bzwrite(): Argument #3 ($length) must be of type ?int".into());
+        }
+    };
+
+    let resource = bz_file(vm, "bzwrite", args[0])?;
+    let bz = resource.downcast_ref::<BzFile>().unwrap();
+
+    let written = match &mut *bz.stream.borrow_mut() {
+        BzStream::Writer(encoder) => encoder.write_all(&data[..length]),
+        BzStream::Reader(_) => {
+            return warn_false(vm, "bzwrite", "Stream was not opened for writing");
+        }
+        BzStream::Closed => {
+            return Err("bzwrite(): supplied resource is not a valid stream resource".into());
+        }
+    };
+
+    if let Err(e) = written {
+        bz.errno.set(io_error_code(&e));
+        return warn_false(vm, "bzwrite", e);
+    }
+
+    Ok(vm.arena.alloc(Val::Int(length as i64)))
+}
+
+/// bzflush(resource $bz): bool
+pub fn php_bzflush(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() != 1 {
+        return Err("bzflush() expects exactly 1 parameter".into());
+    }
+
+    let resource = bz_file(vm, "bzflush", args[0])?;
+    let bz = resource.downcast_ref::<BzFile>().unwrap();
+
+    // libbzip2's BZ2_bzflush is a no-op; only the underlying file is flushed
+    // so a half-written block is not forced out early
+    let flushed = match &mut *bz.stream.borrow_mut() {
+        BzStream::Writer(encoder) => encoder.get_mut().flush(),
+        BzStream::Reader(_) => Ok(()),
+        BzStream::Closed => {
+            return Err("bzflush(): supplied resource is not a valid stream resource".into());
+        }
+    };
+
+    Ok(vm.arena.alloc(Val::Bool(flushed.is_ok())))
+}
+
+/// bzclose(resource $bz): bool
+pub fn php_bzclose(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() != 1 {
+        return Err("bzclose() expects exactly 1 parameter".into());
+    }
+
+    let resource = bz_file(vm, "bzclose", args[0])?;
+    let bz = resource.downcast_ref::<BzFile>().unwrap();
+
+    let stream = std::mem::replace(&mut *bz.stream.borrow_mut(), BzStream::Closed);
+    match stream {
+        BzStream::Writer(encoder) => {
+            if let Err(e) = encoder.finish() {
+                bz.errno.set(io_error_code(&e));
+                return warn_false(vm, "bzclose", e);
+            }
+        }
+        BzStream::Reader(_) => {}
+        BzStream::Closed => {
+            return Err("bzclose(): supplied resource is not a valid stream resource".into());
+        }
+    }
+
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+/// bzerrno(resource $bz): int
+pub fn php_bzerrno(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() != 1 {
+        return Err("bzerrno() expects exactly 1 parameter".into());
+    }
+
+    let resource = bz_file(vm, "bzerrno", args[0])?;
+    let errno = resource.downcast_ref::<BzFile>().unwrap().errno.get();
+
+    Ok(vm.arena.alloc(Val::Int(errno)))
+}
+
+/// bzerrstr(resource $bz): string
+pub fn php_bzerrstr(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() != 1 {
+        return Err("bzerrstr() expects exactly 1 parameter".into());
+    }
+
+    let resource = bz_file(vm, "bzerrstr", args[0])?;
+    let errno = resource.downcast_ref::<BzFile>().unwrap().errno.get();
+
+    Ok(vm
+        .arena
+        .alloc(Val::String(Rc::new(error_str(errno).as_bytes().to_vec()))))
+}
+
+/// bzerror(resource $bz): array
+pub fn php_bzerror(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() != 1 {
+        return Err("bzerror() expects exactly 1 parameter".into());
+    }
+
+    let resource = bz_file(vm, "bzerror", args[0])?;
+    let errno = resource.downcast_ref::<BzFile>().unwrap().errno.get();
+
+    let errno_handle = vm.arena.alloc(Val::Int(errno));
+    let errstr_handle = vm
+        .arena
+        .alloc(Val::String(Rc::new(error_str(errno).as_bytes().to_vec())));
+    let mut result = ArrayData::new();
+    result.insert(ArrayKey::Str(Rc::new(b"errno".to_vec())), errno_handle);
+    result.insert(ArrayKey::Str(Rc::new(b"errstr".to_vec())), errstr_handle);
+
+    Ok(vm.arena.alloc(Val::Array(Rc::new(result))))
+}
@@ -636,10 +636,7 @@ pub fn php_method_exists(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
         return Err("method_exists() expects exactly 2 parameters".into());
     }
 
-    let object_or_class = vm.arena.get(args[0]);
-    let method_name_val = vm.arena.get(args[1]);
-
-    let class_sym = match &object_or_class.value {
+    let class_sym = match &vm.arena.get(args[0]).value {
         Val::Object(h) => {
             let obj_zval = vm.arena.get(*h);
             if let Val::ObjPayload(obj_data) = &obj_zval.value {
@@ -648,28 +645,17 @@ pub fn php_method_exists(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
                 return Ok(vm.arena.alloc(Val::Bool(false)));
             }
         }
-        Val::String(s) => {
-            if let Some(sym) = vm.context.interner.find(s) {
-                sym
-            } else {
-                return Ok(vm.arena.alloc(Val::Bool(false)));
-            }
-        }
+        Val::String(s) => vm.context.interner.intern(s),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
-    let method_sym = match &method_name_val.value {
-        Val::String(s) => {
-            if let Some(sym) = vm.context.interner.find(s) {
-                sym
-            } else {
-                return Ok(vm.arena.alloc(Val::Bool(false)));
-            }
-        }
+    let method_sym = match &vm.arena.get(args[1]).value {
+        Val::String(s) => vm.context.interner.intern(s),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
-    let exists = vm.find_method(class_sym, method_sym).is_some();
+    // Method names are case-insensitive; find_method compares them that way
+    let exists = vm.class_has_method(class_sym, method_sym);
     Ok(vm.arena.alloc(Val::Bool(exists)))
 }
 
@@ -678,19 +664,11 @@ pub fn php_property_exists(vm: &mut VM, args: &[Handle]) -> Result<Handle, Strin
         return Err("property_exists() expects exactly 2 parameters".into());
     }
 
-    let object_or_class = vm.arena.get(args[0]);
-    let prop_name_val = vm.arena.get(args[1]);
-
-    let prop_sym = match &prop_name_val.value {
-        Val::String(s) => {
-            if let Some(sym) = vm.context.interner.find(s) {
-                sym
-            } else {
-                return Ok(vm.arena.alloc(Val::Bool(false)));
-            }
-        }
+    let prop_sym = match &vm.arena.get(args[1]).value {
+        Val::String(s) => vm.context.interner.intern(s),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
+    let object_or_class = vm.arena.get(args[0]);
 
     match &object_or_class.value {
         Val::Object(h) => {
@@ -706,10 +684,9 @@ pub fn php_property_exists(vm: &mut VM, args: &[Handle]) -> Result<Handle, Strin
             }
         }
         Val::String(s) => {
-            if let Some(class_sym) = vm.context.interner.find(s) {
-                let exists = vm.has_property(class_sym, prop_sym);
-                return Ok(vm.arena.alloc(Val::Bool(exists)));
-            }
+            let class_sym = vm.context.interner.intern(s);
+            let exists = vm.has_property(class_sym, prop_sym);
+            return Ok(vm.arena.alloc(Val::Bool(exists)));
         }
         _ => {}
     }
@@ -712,56 +712,595 @@ pub fn php_datetime_diff(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
     Ok(interval_handle)
 }
 
-fn convert_php_to_chrono_format(php_format: &str) -> String {
-    let mut chrono_format = String::new();
-    let mut chars = php_format.chars().peekable();
-    while let Some(ch) = chars.next() {
-        match ch {
-            'Y' => chrono_format.push_str("%Y"),
-            'y' => chrono_format.push_str("%y"),
-            'm' => chrono_format.push_str("%m"),
-            'd' => chrono_format.push_str("%d"),
-            'H' => chrono_format.push_str("%H"),
-            'i' => chrono_format.push_str("%M"),
-            's' => chrono_format.push_str("%S"),
-            'v' => chrono_format.push_str("%3f"),
-            'u' => chrono_format.push_str("%6f"),
-            _ => chrono_format.push(ch),
+// ============================================================================
+// createFromFormat() Parser
+// ============================================================================
+
+/// Errors and warnings from the most recent createFromFormat() call, keyed by
+/// the byte offset in the input where they occurred
+/// Reference: $PHP_SRC_PATH/ext/date/php_date.c - DATEG(last_errors)
+#[derive(Debug, Default)]
+pub struct DateLastErrors {
+    pub parsed: bool,
+    pub warnings: Vec<(usize, String)>,
+    pub errors: Vec<(usize, String)>,
+}
+
+/// Timezone found in the input of createFromFormat()
+#[derive(Debug, Clone, Copy)]
+enum ParsedZone {
+    Named(Tz),
+    /// UTC offset in seconds
+    Fixed(i32),
+}
+
+/// Fields collected by `parse_from_format`; `None` means the format did not
+/// provide the field and it is taken from the current time
+#[derive(Debug, Default)]
+struct ParsedDate {
+    year: Option<i64>,
+    month: Option<i64>,
+    day: Option<i64>,
+    day_of_year: Option<i64>,
+    hour: Option<i64>,
+    minute: Option<i64>,
+    second: Option<i64>,
+    micro: Option<i64>,
+    zone: Option<ParsedZone>,
+}
+
+impl ParsedDate {
+    /// Fill every unset field with its Unix epoch value ('|'), or all of them ('!')
+    fn reset_to_epoch(&mut self, only_unset: bool) {
+        let fields: [(&mut Option<i64>, i64); 7] = [
+            (&mut self.year, 1970),
+            (&mut self.month, 1),
+            (&mut self.day, 1),
+            (&mut self.hour, 0),
+            (&mut self.minute, 0),
+            (&mut self.second, 0),
+            (&mut self.micro, 0),
+        ];
+        for (field, epoch) in fields {
+            if !only_unset || field.is_none() {
+                *field = Some(epoch);
+            }
+        }
+        if !only_unset {
+            self.day_of_year = None;
+            self.zone = None;
         }
     }
-    chrono_format
 }
 
-pub fn php_datetime_create_from_format(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
-    if args.len() < 2 {
-        return Err("DateTime::createFromFormat() expects at least 2 parameters".into());
+const WEEKDAY_NAMES: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+const MONTH_NAMES: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Read up to `max_len` ASCII digits, returning the value and digit count
+fn read_number(input: &[u8], pos: usize, max_len: usize) -> Option<(i64, usize)> {
+    let len = input[pos.min(input.len())..]
+        .iter()
+        .take(max_len)
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    if len == 0 {
+        return None;
+    }
+    let digits = std::str::from_utf8(&input[pos..pos + len]).ok()?;
+    Some((digits.parse().ok()?, len))
+}
+
+/// Read a run of ASCII letters
+fn read_word(input: &[u8], pos: usize) -> &[u8] {
+    let len = input[pos.min(input.len())..]
+        .iter()
+        .take_while(|b| b.is_ascii_alphabetic())
+        .count();
+    &input[pos..pos + len]
+}
+
+/// Match a full or three-letter abbreviated name, returning its index
+fn match_name(word: &[u8], names: &[&str]) -> Option<usize> {
+    let word = String::from_utf8_lossy(word).to_ascii_lowercase();
+    names
+        .iter()
+        .position(|name| word == *name || (word.len() == 3 && name.starts_with(&word)))
+}
+
+/// Parse a timezone identifier, abbreviation or UTC offset, returning the zone
+/// and the number of bytes consumed
+fn read_timezone(input: &[u8], pos: usize) -> Option<(ParsedZone, usize)> {
+    let rest = &input[pos..];
+    match rest.first()? {
+        sign @ (b'+' | b'-') => {
+            let (hours, mut len) = match read_number(rest, 1, 2)? {
+                (h, 1) => (h, 1),
+                // "+0530" carries hours and minutes without a separator
+                (h, _) => (h, 2),
+            };
+            len += 1;
+            let mut minutes = 0;
+            let colon = usize::from(rest.get(len) == Some(&b':'));
+            if let Some((m, 2)) = read_number(rest, len + colon, 2) {
+                minutes = m;
+                len += colon + 2;
+            }
+            let secs = (hours * 3600 + minutes * 60) as i32;
+            Some((
+                ParsedZone::Fixed(if *sign == b'-' { -secs } else { secs }),
+                len,
+            ))
+        }
+        _ => {
+            let len = rest
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'/' | b'_' | b'-' | b'+'))
+                .count();
+            let name = std::str::from_utf8(&rest[..len]).ok()?;
+            if name.eq_ignore_ascii_case("z") {
+                return Some((ParsedZone::Fixed(0), len));
+            }
+            let tz = Tz::from_str(name)
+                .or_else(|_| Tz::from_str(&name.to_ascii_uppercase()))
+                .ok()?;
+            Some((ParsedZone::Named(tz), len))
+        }
     }
+}
 
-    let format = String::from_utf8_lossy(&get_string_arg(vm, args[0])?).to_string();
-    let datetime_str = String::from_utf8_lossy(&get_string_arg(vm, args[1])?).to_string();
+/// Parse `input` according to a date() style `format`, the inverse of format()
+/// Reference: $PHP_SRC_PATH/ext/date/lib/parse_date.re - timelib_parse_from_format
+fn parse_from_format(format: &[u8], input: &[u8], errors: &mut DateLastErrors) -> ParsedDate {
+    let mut parsed = ParsedDate::default();
+    let mut pos = 0;
+    let mut fpos = 0;
+    let mut allow_trailing = false;
+    let mut meridian: Option<bool> = None;
 
-    let chrono_format = convert_php_to_chrono_format(&format);
+    macro_rules! fail {
+        ($msg:expr) => {{
+            errors.errors.push((pos, $msg.to_string()));
+            return parsed;
+        }};
+    }
 
-    if let Ok(naive) = NaiveDateTime::parse_from_str(&datetime_str, &chrono_format) {
-        let tz: Tz = vm.context.config.timezone.parse().unwrap_or(Tz::UTC);
-        let dt = tz.from_utc_datetime(&naive);
+    while fpos < format.len() {
+        let fc = format[fpos];
+        fpos += 1;
 
-        let datetime_sym = vm.context.interner.intern(b"DateTime");
-        let obj_handle = vm.instantiate_class(datetime_sym, &[])?;
+        // Format characters that need no input
+        match fc {
+            b'!' => {
+                parsed.reset_to_epoch(false);
+                continue;
+            }
+            b'|' => {
+                parsed.reset_to_epoch(true);
+                continue;
+            }
+            b'+' => {
+                allow_trailing = true;
+                continue;
+            }
+            b'*' => {
+                pos += input[pos..]
+                    .iter()
+                    .take_while(|b| !b.is_ascii_digit() && !b" ,;:/.-()".contains(b))
+                    .count();
+                continue;
+            }
+            b' ' => {
+                pos += input[pos..]
+                    .iter()
+                    .take_while(|b| **b == b' ' || **b == b'\t')
+                    .count();
+                continue;
+            }
+            _ => {}
+        }
 
-        let payload_handle = match &vm.arena.get(obj_handle).value {
-            Val::Object(h) => *h,
-            _ => return Err("Failed to create DateTime".into()),
-        };
+        if pos >= input.len() {
+            fail!("Not enough data available to satisfy format");
+        }
 
-        if let Val::ObjPayload(obj_data) = &mut vm.arena.get_mut(payload_handle).value {
-            obj_data.internal = Some(Rc::new(DateTimeData { dt }));
+        match fc {
+            b'd' | b'j' => match read_number(input, pos, 2) {
+                Some((day, len)) => {
+                    parsed.day = Some(day);
+                    pos += len;
+                }
+                None => fail!("A two digit day could not be found"),
+            },
+            b'D' | b'l' => {
+                let word = read_word(input, pos);
+                if match_name(word, &WEEKDAY_NAMES).is_none() {
+                    fail!("A textual day could not be found");
+                }
+                pos += word.len();
+            }
+            b'S' => {
+                let suffix = &input[pos..(pos + 2).min(input.len())];
+                if [&b"st"[..], b"nd", b"rd", b"th"]
+                    .iter()
+                    .any(|s| suffix.eq_ignore_ascii_case(s))
+                {
+                    pos += 2;
+                }
+            }
+            b'z' => match read_number(input, pos, 3) {
+                Some((day, len)) if parsed.year.is_some() => {
+                    parsed.day_of_year = Some(day);
+                    pos += len;
+                }
+                Some(_) => fail!("A 'day of year' can only come after a year has been found"),
+                None => fail!("A three digit day-of-year could not be found"),
+            },
+            b'm' | b'n' => match read_number(input, pos, 2) {
+                Some((month, len)) => {
+                    parsed.month = Some(month);
+                    pos += len;
+                }
+                None => fail!("A two digit month could not be found"),
+            },
+            b'M' | b'F' => {
+                let word = read_word(input, pos);
+                match match_name(word, &MONTH_NAMES) {
+                    Some(index) => {
+                        parsed.month = Some(index as i64 + 1);
+                        pos += word.len();
+                    }
+                    None => fail!("A textual month could not be found"),
+                }
+            }
+            b'y' => match read_number(input, pos, 2) {
+                Some((year, len)) => {
+                    parsed.year = Some(if year < 70 { year + 2000 } else { year + 1900 });
+                    pos += len;
+                }
+                None => fail!("A two digit year could not be found"),
+            },
+            b'Y' => match read_number(input, pos, 4) {
+                Some((year, len)) => {
+                    parsed.year = Some(year);
+                    pos += len;
+                }
+                None => fail!("A four digit year could not be found"),
+            },
+            b'a' | b'A' => {
+                let word = &input[pos..(pos + 2).min(input.len())];
+                if word.eq_ignore_ascii_case(b"am") {
+                    meridian = Some(false);
+                } else if word.eq_ignore_ascii_case(b"pm") {
+                    meridian = Some(true);
+                } else {
+                    fail!("A meridian could not be found");
+                }
+                match parsed.hour {
+                    None => fail!("Meridian can only come after an hour has been found"),
+                    Some(hour) if hour > 12 => fail!("Hour cannot be higher than 12"),
+                    Some(_) => {}
+                }
+                pos += 2;
+            }
+            b'g' | b'h' | b'G' | b'H' => match read_number(input, pos, 2) {
+                Some((hour, _)) if matches!(fc, b'g' | b'h') && hour > 12 => {
+                    fail!("Hour cannot be higher than 12")
+                }
+                Some((hour, len)) => {
+                    parsed.hour = Some(hour);
+                    pos += len;
+                }
+                None => fail!("A two digit hour could not be found"),
+            },
+            b'i' => match read_number(input, pos, 2) {
+                Some((minute, 2)) => {
+                    parsed.minute = Some(minute);
+                    pos += 2;
+                }
+                _ => fail!("A two digit minute could not be found"),
+            },
+            b's' => match read_number(input, pos, 2) {
+                Some((second, 2)) => {
+                    parsed.second = Some(second);
+                    pos += 2;
+                }
+                _ => fail!("A two digit second could not be found"),
+            },
+            b'v' => match read_number(input, pos, 3) {
+                Some((millis, 3)) => {
+                    parsed.micro = Some(millis * 1000);
+                    pos += 3;
+                }
+                _ => fail!("A three digit millisecond could not be found"),
+            },
+            b'u' => match read_number(input, pos, 6) {
+                Some((micro, len)) => {
+                    parsed.micro = Some(micro * 10_i64.pow(6 - len as u32));
+                    pos += len;
+                }
+                None => fail!("A six digit microsecond could not be found"),
+            },
+            b'U' => {
+                let negative = input[pos] == b'-';
+                let start = pos + usize::from(negative || input[pos] == b'+');
+                match read_number(input, start, 19) {
+                    Some((ts, len)) => {
+                        let ts = if negative { -ts } else { ts };
+                        let Some(dt) = ChronoDateTime::from_timestamp(ts, 0) else {
+                            fail!("A unix timestamp could not be found");
+                        };
+                        parsed.year = Some(dt.year() as i64);
+                        parsed.month = Some(dt.month() as i64);
+                        parsed.day = Some(dt.day() as i64);
+                        parsed.hour = Some(dt.hour() as i64);
+                        parsed.minute = Some(dt.minute() as i64);
+                        parsed.second = Some(dt.second() as i64);
+                        parsed.zone = Some(ParsedZone::Fixed(0));
+                        pos = start + len;
+                    }
+                    None => fail!("A unix timestamp could not be found"),
+                }
+            }
+            b'e' | b'T' | b'O' | b'P' | b'p' => match read_timezone(input, pos) {
+                Some((zone, len)) => {
+                    parsed.zone = Some(zone);
+                    pos += len;
+                }
+                None => fail!("The timezone could not be found in the database"),
+            },
+            b'#' => {
+                if !b";:/.,-()".contains(&input[pos]) {
+                    fail!("The separation symbol ([;:/.,-]) could not be found");
+                }
+                pos += 1;
+            }
+            b';' | b':' | b'/' | b'.' | b',' | b'-' | b'(' | b')' => {
+                if input[pos] != fc {
+                    fail!("The separation symbol could not be found");
+                }
+                pos += 1;
+            }
+            b'?' => pos += 1,
+            b'\\' => {
+                if format.get(fpos) != Some(&input[pos]) {
+                    fail!("The escaped character could not be found");
+                }
+                fpos += 1;
+                pos += 1;
+            }
+            _ => {
+                if input[pos] != fc {
+                    fail!("The format separator does not match");
+                }
+                pos += 1;
+            }
+        }
+    }
+
+    if pos < input.len() {
+        if allow_trailing {
+            errors.warnings.push((pos, "Trailing data".to_string()));
+        } else {
+            fail!("Trailing data");
+        }
+    }
+
+    if let (Some(pm), Some(hour)) = (meridian, parsed.hour) {
+        parsed.hour = Some(match (pm, hour) {
+            (false, 12) => 0,
+            (true, h) if h != 12 => h + 12,
+            (_, h) => h,
+        });
+    }
+
+    parsed
+}
+
+/// Express a fixed UTC offset as a zone; chrono-tz only has whole-hour
+/// Etc/GMT zones, so other offsets fall back to UTC
+fn fixed_offset_zone(secs: i32) -> Tz {
+    if secs % 3600 != 0 {
+        return Tz::UTC;
+    }
+    match secs / 3600 {
+        0 => Tz::UTC,
+        // Etc/GMT zones use POSIX sign conventions, inverted from ISO 8601
+        hours => Tz::from_str(&format!("Etc/GMT{:+}", -hours)).unwrap_or(Tz::UTC),
+    }
+}
+
+/// Combine parsed fields with the current time in `tz`, rolling out-of-range
+/// values over the way PHP does and recording a warning when that happens
+fn resolve_parsed_date(
+    parsed: ParsedDate,
+    tz: Tz,
+    input_len: usize,
+    errors: &mut DateLastErrors,
+) -> Option<ChronoDateTime<Tz>> {
+    let tz = match parsed.zone {
+        Some(ParsedZone::Named(zone)) => zone,
+        _ => tz,
+    };
+    let now = Utc::now().with_timezone(&tz);
+
+    // Giving any time field zeroes the others instead of using the current time
+    let has_time = parsed.hour.is_some()
+        || parsed.minute.is_some()
+        || parsed.second.is_some()
+        || parsed.micro.is_some();
+    let time_default = |field: Option<i64>, current: u32| {
+        field.unwrap_or(if has_time { 0 } else { current as i64 })
+    };
+    let hour = time_default(parsed.hour, now.hour());
+    let minute = time_default(parsed.minute, now.minute());
+    let second = time_default(parsed.second, now.second());
+    let micro = time_default(parsed.micro, now.timestamp_subsec_micros());
+
+    let year = parsed.year.unwrap_or(now.year() as i64);
+    let (month, day) = match parsed.day_of_year {
+        Some(day_of_year) => (1, day_of_year + 1),
+        None => (
+            parsed.month.unwrap_or(now.month() as i64),
+            parsed.day.unwrap_or(now.day() as i64),
+        ),
+    };
+
+    let date_valid = parsed.day_of_year.is_some()
+        || (1..=12).contains(&month)
+            && u32::try_from(day)
+                .is_ok_and(|day| NaiveDate::from_ymd_opt(year as i32, month as u32, day).is_some());
+    if !date_valid {
+        errors
+            .warnings
+            .push((input_len, "The parsed date was invalid".to_string()));
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        errors
+            .warnings
+            .push((input_len, "The parsed time was invalid".to_string()));
+    }
+
+    let date = NaiveDate::from_ymd_opt(year as i32, 1, 1)?;
+    let months_from_jan = month - 1 + (year - date.year() as i64) * 12;
+    let date = if months_from_jan >= 0 {
+        date.checked_add_months(chrono::Months::new(months_from_jan as u32))?
+    } else {
+        date.checked_sub_months(chrono::Months::new((-months_from_jan) as u32))?
+    };
+    let local = date.and_hms_opt(0, 0, 0)?
+        + chrono::Duration::days(day - 1)
+        + chrono::Duration::hours(hour)
+        + chrono::Duration::minutes(minute)
+        + chrono::Duration::seconds(second)
+        + chrono::Duration::microseconds(micro);
+
+    match parsed.zone {
+        Some(ParsedZone::Fixed(secs)) => {
+            let offset = chrono::FixedOffset::east_opt(secs)?;
+            let dt = offset.from_local_datetime(&local).single()?;
+            Some(dt.with_timezone(&fixed_offset_zone(secs)))
         }
+        _ => tz
+            .from_local_datetime(&local)
+            .earliest()
+            .or_else(|| Some(tz.from_utc_datetime(&local))),
+    }
+}
+
+/// Shared implementation of DateTime::createFromFormat() and
+/// date_create_immutable_from_format(); records the outcome for getLastErrors()
+fn create_from_format(vm: &mut VM, args: &[Handle], class_name: &[u8]) -> Result<Handle, String> {
+    let format = get_string_arg(vm, args[0])?;
+    let datetime = get_string_arg(vm, args[1])?;
+
+    let tz: Tz = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        None | Some(Val::Null) => vm.context.config.timezone.parse().unwrap_or(Tz::UTC),
+        Some(_) => get_internal_data::<DateTimeZoneData>(vm, args[2])?.tz,
+    };
 
-        Ok(obj_handle)
+    let mut errors = DateLastErrors {
+        parsed: true,
+        ..Default::default()
+    };
+    let parsed = parse_from_format(&format, &datetime, &mut errors);
+    let dt = if errors.errors.is_empty() {
+        resolve_parsed_date(parsed, tz, datetime.len(), &mut errors)
     } else {
-        Ok(vm.arena.alloc(Val::Bool(false)))
+        None
+    };
+    if dt.is_none() && errors.errors.is_empty() {
+        errors
+            .errors
+            .push((datetime.len(), "The parsed date was invalid".to_string()));
+    }
+    *vm.context
+        .get_or_init_extension_data(DateLastErrors::default) = errors;
+
+    let Some(dt) = dt else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    let class_sym = vm.context.interner.intern(class_name);
+    let obj_handle = vm.instantiate_class(class_sym, &[])?;
+
+    let payload_handle = match &vm.arena.get(obj_handle).value {
+        Val::Object(h) => *h,
+        _ => return Err("Failed to create DateTime".into()),
+    };
+
+    if let Val::ObjPayload(obj_data) = &mut vm.arena.get_mut(payload_handle).value {
+        obj_data.internal = Some(Rc::new(DateTimeData { dt }));
+    }
+
+    Ok(obj_handle)
+}
+
+/// DateTime::createFromFormat(string $format, string $datetime, ?DateTimeZone $timezone = null): DateTime|false
+pub fn php_datetime_create_from_format(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("DateTime::createFromFormat() expects at least 2 parameters".into());
     }
+    create_from_format(vm, args, b"DateTime")
+}
+
+/// DateTime::getLastErrors(): array|false
+pub fn php_datetime_get_last_errors(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let last = vm
+        .context
+        .get_or_init_extension_data(DateLastErrors::default);
+    if !last.parsed || (last.warnings.is_empty() && last.errors.is_empty()) {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+    let warnings = last.warnings.clone();
+    let errors = last.errors.clone();
+
+    let mut map = IndexMap::new();
+    for (key, entries) in [("warning", warnings), ("error", errors)] {
+        let count = entries.len() as i64;
+        let mut list = crate::core::value::ArrayData::new();
+        for (pos, message) in entries {
+            let message = vm.arena.alloc(Val::String(Rc::new(message.into_bytes())));
+            list.insert(ArrayKey::Int(pos as i64), message);
+        }
+        map.insert(
+            make_array_key(&format!("{}_count", key)),
+            vm.arena.alloc(Val::Int(count)),
+        );
+        map.insert(
+            make_array_key(&format!("{}s", key)),
+            vm.arena.alloc(Val::Array(Rc::new(list))),
+        );
+    }
+
+    Ok(vm
+        .arena
+        .alloc(Val::Array(Rc::new(crate::core::value::ArrayData {
+            map,
+            next_free: 0,
+            internal_ptr: 0,
+        }))))
 }
 
 /// DateTime::getTimestamp(): int
@@ -3122,41 +3661,7 @@ pub fn php_date_create_immutable_from_format(
         return Err("date_create_immutable_from_format() expects at least 2 parameters".into());
     }
 
-    let format = String::from_utf8_lossy(&get_string_arg(vm, args[0])?).to_string();
-    let datetime_str = String::from_utf8_lossy(&get_string_arg(vm, args[1])?).to_string();
-
-    let chrono_format = convert_php_to_chrono_format(&format);
-
-    let tz: Tz = if args.len() > 2 {
-        let tz_data = get_internal_data::<DateTimeZoneData>(vm, args[2])?;
-        tz_data.tz
-    } else {
-        vm.context.config.timezone.parse().unwrap_or(Tz::UTC)
-    };
-
-    // Try parsing as NaiveDateTime first, if that fails try NaiveDate
-    let dt = if let Ok(naive) = NaiveDateTime::parse_from_str(&datetime_str, &chrono_format) {
-        tz.from_utc_datetime(&naive)
-    } else if let Ok(naive_date) = NaiveDate::parse_from_str(&datetime_str, &chrono_format) {
-        let naive = naive_date.and_hms_opt(0, 0, 0).unwrap();
-        tz.from_utc_datetime(&naive)
-    } else {
-        return Ok(vm.arena.alloc(Val::Bool(false)));
-    };
-
-    let datetime_sym = vm.context.interner.intern(b"DateTimeImmutable");
-    let obj_handle = vm.instantiate_class(datetime_sym, &[])?;
-
-    let payload_handle = match &vm.arena.get(obj_handle).value {
-        Val::Object(h) => *h,
-        _ => return Err("Failed to create DateTimeImmutable".into()),
-    };
-
-    if let Val::ObjPayload(obj_data) = &mut vm.arena.get_mut(payload_handle).value {
-        obj_data.internal = Some(Rc::new(DateTimeData { dt }));
-    }
-
-    Ok(obj_handle)
+    create_from_format(vm, args, b"DateTimeImmutable")
 }
 
 /// date_timestamp_get(DateTimeInterface $object): int
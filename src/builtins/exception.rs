@@ -1,7 +1,30 @@
-use crate::core::value::{Handle, Val};
-use crate::vm::engine::VM;
+use crate::core::value::{Handle, ObjectData, Val};
+use crate::vm::engine::{PropertyCollectionMode, VM};
+use std::collections::HashSet;
 use std::rc::Rc;
 
+/// Build an exception object of `class_name` carrying `message`, for native
+/// code to raise with `VM::throw_exception`
+pub fn create_exception(vm: &mut VM, class_name: &[u8], message: &str) -> Handle {
+    let class_sym = vm.context.interner.intern(class_name);
+    let mut properties = vm.collect_properties(class_sym, PropertyCollectionMode::All);
+    let message_sym = vm.context.interner.intern(b"message");
+    let code_sym = vm.context.interner.intern(b"code");
+    let message_handle = vm
+        .arena
+        .alloc(Val::String(Rc::new(message.as_bytes().to_vec())));
+    properties.insert(message_sym, message_handle);
+    properties.insert(code_sym, vm.arena.alloc(Val::Int(0)));
+
+    let payload = vm.arena.alloc(Val::ObjPayload(ObjectData {
+        class: class_sym,
+        properties,
+        internal: None,
+        dynamic_properties: HashSet::new(),
+    }));
+    vm.arena.alloc(Val::Object(payload))
+}
+
 /// Exception::__construct($message = "", $code = 0, Throwable $previous = null)
 pub fn exception_construct(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     // Get $this from current frame
@@ -1,8 +1,10 @@
 use crate::builtins::exec::{PipeKind, PipeResource};
+use crate::builtins::zip::ZipEntryStream;
 use crate::core::value::{ArrayData, ArrayKey, Handle, Val};
 use crate::vm::engine::VM;
 use glob::{MatchOptions, Pattern, glob_with};
 use indexmap::IndexMap;
+use std::any::Any;
 use std::cell::RefCell;
 use std::ffi::CString;
 use std::fs::{self, File, Metadata, OpenOptions};
@@ -230,7 +232,10 @@ pub fn php_fclose(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         let val = vm.arena.get(args[0]);
         match &val.value {
             Val::Resource(rc) => {
-                rc.is::<FileHandle>() || rc.is::<PipeResource>() || rc.is::<MemoryStream>()
+                rc.is::<FileHandle>()
+                    || rc.is::<PipeResource>()
+                    || rc.is::<MemoryStream>()
+                    || rc.is::<ZipEntryStream>()
             }
             _ => false,
         }
@@ -330,6 +335,26 @@ pub fn php_fread(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         return Ok(vm.arena.alloc(Val::String(Rc::new(result))));
     }
 
+    if let Some(zs) = resource_rc.downcast_ref::<ZipEntryStream>() {
+        let mut buffer = vec![0u8; length];
+        let mut filled = 0;
+        // Decompressing readers may return short reads before the end
+        while filled < length {
+            let n = zs
+                .reader
+                .borrow_mut()
+                .read(&mut buffer[filled..])
+                .map_err(|e| format!("fread(): {}", e))?;
+            if n == 0 {
+                *zs.eof.borrow_mut() = true;
+                break;
+            }
+            filled += n;
+        }
+        buffer.truncate(filled);
+        return Ok(vm.arena.alloc(Val::String(Rc::new(buffer))));
+    }
+
     Err("fread(): supplied argument is not a valid stream resource".into())
 }
 
@@ -370,65 +395,51 @@ pub fn php_fwrite(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         }
     };
 
-    if let Some(fh) = resource_rc.downcast_ref::<FileHandle>() {
-        let write_data = if let Some(max) = max_len {
-            &data[..data.len().min(max)]
-        } else {
-            &data
-        };
-
-        let bytes_written = fh
-            .file
-            .borrow_mut()
-            .write(write_data)
-            .map_err(|e| format!("fwrite(): {}", e))?;
+    let write_data = if let Some(max) = max_len {
+        &data[..data.len().min(max)]
+    } else {
+        &data
+    };
 
-        return Ok(vm.arena.alloc(Val::Int(bytes_written as i64)));
-    }
+    let bytes_written =
+        stream_write(&resource_rc, write_data).map_err(|e| format!("fwrite(): {}", e))?;
+    Ok(vm.arena.alloc(Val::Int(bytes_written as i64)))
+}
 
-    if let Some(pr) = resource_rc.downcast_ref::<PipeResource>() {
-        let mut pipe = pr.pipe.borrow_mut();
-        if let PipeKind::Stdin(stdin) = &mut *pipe {
-            let write_data = if let Some(max) = max_len {
-                &data[..data.len().min(max)]
-            } else {
-                &data
-            };
-            let bytes_written = stdin
-                .write(write_data)
-                .map_err(|e| format!("fwrite(): {}", e))?;
-            return Ok(vm.arena.alloc(Val::Int(bytes_written as i64)));
-        } else {
-            return Err("fwrite(): cannot write to this pipe".into());
-        }
+/// Write `data` to a writable stream resource, returning the number of
+/// bytes written
+pub fn stream_write(resource: &Rc<dyn Any>, data: &[u8]) -> Result<usize, String> {
+    if let Some(fh) = resource.downcast_ref::<FileHandle>() {
+        return fh.file.borrow_mut().write(data).map_err(|e| e.to_string());
     }
 
-    if let Some(ms) = resource_rc.downcast_ref::<MemoryStream>() {
-        let write_data = if let Some(max) = max_len {
-            &data[..data.len().min(max)]
-        } else {
-            &data
+    if let Some(pr) = resource.downcast_ref::<PipeResource>() {
+        return match &mut *pr.pipe.borrow_mut() {
+            PipeKind::Stdin(stdin) => stdin.write(data).map_err(|e| e.to_string()),
+            _ => Err("cannot write to this pipe".into()),
         };
+    }
 
+    if let Some(ms) = resource.downcast_ref::<MemoryStream>() {
         let mut buffer = ms.buffer.borrow_mut();
         let mut pos = ms.position.borrow_mut();
 
         // Extend buffer if needed
-        if *pos + write_data.len() > buffer.len() {
-            buffer.resize(*pos + write_data.len(), 0);
+        if *pos + data.len() > buffer.len() {
+            buffer.resize(*pos + data.len(), 0);
         }
 
         // Write data at current position
-        buffer[*pos..*pos + write_data.len()].copy_from_slice(write_data);
-        *pos += write_data.len();
+        buffer[*pos..*pos + data.len()].copy_from_slice(data);
+        *pos += data.len();
 
-        return Ok(vm.arena.alloc(Val::Int(write_data.len() as i64)));
+        return Ok(data.len());
     }
 
-    Err("fwrite(): supplied argument is not a valid stream resource".into())
+    Err("supplied argument is not a valid stream resource".into())
 }
 
-/// file_get_contents(filename) - Read entire file into string
+/// file_get_contents(filename, use_include_path) - Read entire file into string
 /// Reference: $PHP_SRC_PATH/ext/standard/file.c - PHP_FUNCTION(file_get_contents)
 pub fn php_file_get_contents(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
@@ -436,7 +447,14 @@ pub fn php_file_get_contents(vm: &mut VM, args: &[Handle]) -> Result<Handle, Str
     }
 
     let path_bytes = handle_to_path(vm, args[0])?;
-    let path = bytes_to_path(&path_bytes)?;
+    let mut path = bytes_to_path(&path_bytes)?;
+
+    let use_include_path = args
+        .get(1)
+        .is_some_and(|h| vm.arena.get(*h).value.to_bool());
+    if use_include_path && let Some(resolved) = vm.resolve_include_path(&path.to_string_lossy()) {
+        path = resolved;
+    }
 
     match fs::read(&path) {
         Ok(contents) => Ok(vm.arena.alloc(Val::String(Rc::new(contents)))),
@@ -1370,6 +1388,11 @@ pub fn php_feof(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
             let eof = *fh.eof.borrow();
             return Ok(vm.arena.alloc(Val::Bool(eof)));
         }
+
+        if let Some(zs) = rc.downcast_ref::<ZipEntryStream>() {
+            let eof = *zs.eof.borrow();
+            return Ok(vm.arena.alloc(Val::Bool(eof)));
+        }
     }
 
     Err("feof(): supplied argument is not a valid stream resource".into())
@@ -1433,6 +1456,34 @@ pub fn php_fgets(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 
             return Ok(vm.arena.alloc(Val::String(Rc::new(line))));
         }
+
+        if let Some(zs) = rc.downcast_ref::<ZipEntryStream>() {
+            let mut line = Vec::new();
+            let mut buf = [0u8; 1];
+
+            loop {
+                let n = zs
+                    .reader
+                    .borrow_mut()
+                    .read(&mut buf)
+                    .map_err(|e| format!("fgets(): {}", e))?;
+                if n == 0 {
+                    *zs.eof.borrow_mut() = true;
+                    break;
+                }
+
+                line.push(buf[0]);
+                if buf[0] == b'\n' || max_len.is_some_and(|max| line.len() >= max - 1) {
+                    break;
+                }
+            }
+
+            if line.is_empty() {
+                return Ok(vm.arena.alloc(Val::Bool(false)));
+            }
+
+            return Ok(vm.arena.alloc(Val::String(Rc::new(line))));
+        }
     }
 
     Err("fgets(): supplied argument is not a valid stream resource".into())
@@ -1463,6 +1514,22 @@ pub fn php_fgetc(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 
             return Ok(vm.arena.alloc(Val::String(Rc::new(vec![buf[0]]))));
         }
+
+        if let Some(zs) = rc.downcast_ref::<ZipEntryStream>() {
+            let mut buf = [0u8; 1];
+            let bytes_read = zs
+                .reader
+                .borrow_mut()
+                .read(&mut buf)
+                .map_err(|e| format!("fgetc(): {}", e))?;
+
+            if bytes_read == 0 {
+                *zs.eof.borrow_mut() = true;
+                return Ok(vm.arena.alloc(Val::Bool(false)));
+            }
+
+            return Ok(vm.arena.alloc(Val::String(Rc::new(vec![buf[0]]))));
+        }
     }
 
     Err("fgetc(): supplied argument is not a valid stream resource".into())
@@ -2314,6 +2381,25 @@ pub fn php_stream_get_contents(vm: &mut VM, args: &[Handle]) -> Result<Handle, S
         return Ok(vm.arena.alloc(Val::String(Rc::new(result))));
     }
 
+    if let Some(zs) = resource_rc.downcast_ref::<ZipEntryStream>() {
+        let mut result = Vec::new();
+        let mut reader = zs.reader.borrow_mut();
+        match max_length {
+            Some(max) => reader
+                .by_ref()
+                .take(max as u64)
+                .read_to_end(&mut result)
+                .map_err(|e| format!("stream_get_contents(): {}", e))?,
+            None => reader
+                .read_to_end(&mut result)
+                .map_err(|e| format!("stream_get_contents(): {}", e))?,
+        };
+        if max_length.is_none_or(|max| result.len() < max) {
+            *zs.eof.borrow_mut() = true;
+        }
+        return Ok(vm.arena.alloc(Val::String(Rc::new(result))));
+    }
+
     if let Some(ms) = resource_rc.downcast_ref::<MemoryStream>() {
         let buffer = ms.buffer.borrow();
         let pos = *ms.position.borrow();
@@ -1,3 +1,4 @@
+use crate::compiler::chunk::ClosureData;
 use crate::core::value::{ArrayData, ArrayKey, Handle, Val};
 use crate::runtime::context::ShutdownFunction;
 use crate::vm::engine::{ErrorLevel, VM};
@@ -109,6 +110,12 @@ pub fn php_function_exists(vm: &mut VM, args: &[Handle]) -> Result<Handle, Strin
 }
 
 /// is_callable() - Verify that the contents of a variable can be called as a function
+///
+/// PHP Reference: https://www.php.net/manual/en/function.is-callable.php
+///
+/// With `$syntax_only` only the shape of the value is checked. The optional
+/// third argument receives the callable name ("Class::method" for method
+/// callables and invokable objects).
 pub fn php_is_callable(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
         return Err("is_callable() expects at least 1 parameter, 0 given".to_string());
@@ -126,69 +133,92 @@ pub fn php_is_callable(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .map(|handle| vm.arena.get(*handle).value.to_bool())
         .unwrap_or(false);
 
-    let target = vm.arena.get(args[0]);
-    let callable = match &target.value {
+    let (callable, callable_name) = match vm.arena.get(args[0]).value.clone() {
         Val::String(name) => {
-            if syntax_only {
-                !name.is_empty()
+            let callable = if syntax_only {
+                true
+            } else if let Some(pos) = name.windows(2).position(|w| w == b"::") {
+                class_method_exists(vm, &name[..pos], &name[pos + 2..])
             } else {
                 function_exists_case_insensitive(vm, name.as_slice())
-            }
+            };
+            (callable, name.to_vec())
         }
         Val::Array(map) => {
-            let first = map.map.get(&ArrayKey::Int(0));
-            let second = map.map.get(&ArrayKey::Int(1));
-            match (first, second) {
-                (Some(&target_handle), Some(&method_handle)) => {
-                    let method_name = match &vm.arena.get(method_handle).value {
-                        Val::String(s) => s.as_slice(),
-                        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+            let first = map.map.get(&ArrayKey::Int(0)).copied();
+            let second = map.map.get(&ArrayKey::Int(1)).copied();
+            let target = first.map(|h| vm.arena.get(h).value.clone());
+            let method = second.map(|h| vm.arena.get(h).value.clone());
+            match (map.map.len(), target, method) {
+                (2, Some(Val::Object(obj_handle)), Some(Val::String(method))) => {
+                    let class = match &vm.arena.get(obj_handle).value {
+                        Val::ObjPayload(obj_data) => Some(obj_data.class),
+                        _ => None,
                     };
-                    if syntax_only {
-                        return Ok(vm.arena.alloc(Val::Bool(true)));
-                    }
-
-                    let method_sym = vm.context.interner.intern(method_name);
-                    match &vm.arena.get(target_handle).value {
-                        Val::Object(obj_handle) => {
-                            if let Val::ObjPayload(obj_data) = &vm.arena.get(*obj_handle).value {
-                                vm.find_method(obj_data.class, method_sym).is_some()
-                                    || vm.find_native_method(obj_data.class, method_sym).is_some()
-                            } else {
-                                false
-                            }
-                        }
-                        Val::String(class_name) => {
-                            let class_sym = vm.context.interner.intern(class_name.as_slice());
-                            let class_sym = vm.lookup_class_symbol(class_sym);
-                            if let Some(class_sym) = class_sym {
-                                vm.find_method(class_sym, method_sym).is_some()
-                                    || vm.find_native_method(class_sym, method_sym).is_some()
-                            } else {
-                                false
-                            }
-                        }
-                        _ => false,
-                    }
+                    let class_name = class
+                        .and_then(|class| vm.context.interner.lookup(class))
+                        .unwrap_or_default()
+                        .to_vec();
+                    let callable = syntax_only
+                        || class.is_some_and(|class| {
+                            let method_sym = vm.context.interner.intern(&method);
+                            vm.find_method(class, method_sym).is_some()
+                                || vm.find_native_method(class, method_sym).is_some()
+                        });
+                    (callable, [class_name.as_slice(), b"::", &method].concat())
                 }
-                _ => false,
+                (2, Some(Val::String(class_name)), Some(Val::String(method))) => {
+                    let callable = syntax_only || class_method_exists(vm, &class_name, &method);
+                    (callable, [class_name.as_slice(), b"::", &method].concat())
+                }
+                _ => (false, b"Array".to_vec()),
             }
         }
         Val::Object(obj_handle) => {
+            let (class, is_closure) = match &vm.arena.get(obj_handle).value {
+                Val::ObjPayload(obj_data) => (
+                    Some(obj_data.class),
+                    obj_data
+                        .internal
+                        .as_ref()
+                        .is_some_and(|internal| internal.is::<ClosureData>()),
+                ),
+                _ => (None, false),
+            };
             let method_sym = vm.context.interner.intern(b"__invoke");
-            if let Val::ObjPayload(obj_data) = &vm.arena.get(*obj_handle).value {
-                vm.find_method(obj_data.class, method_sym).is_some()
-                    || vm.find_native_method(obj_data.class, method_sym).is_some()
-            } else {
-                false
-            }
+            let callable = is_closure
+                || class.is_some_and(|class| {
+                    vm.find_method(class, method_sym).is_some()
+                        || vm.find_native_method(class, method_sym).is_some()
+                });
+            let class_name = class
+                .and_then(|class| vm.context.interner.lookup(class))
+                .unwrap_or_default();
+            (callable, [class_name, b"::__invoke"].concat())
         }
-        _ => false,
+        other => (false, other.to_php_string_bytes()),
     };
 
+    if let Some(&name_handle) = args.get(2)
+        && vm.arena.get(name_handle).is_ref
+    {
+        vm.arena.get_mut(name_handle).value = Val::String(callable_name.into());
+    }
+
     Ok(vm.arena.alloc(Val::Bool(callable)))
 }
 
+/// Whether `class_name` resolves to a class declaring or inheriting `method_name`
+fn class_method_exists(vm: &mut VM, class_name: &[u8], method_name: &[u8]) -> bool {
+    let class_sym = vm.context.interner.intern(class_name);
+    let Some(class_sym) = vm.lookup_class_symbol(class_sym) else {
+        return false;
+    };
+    let method_sym = vm.context.interner.intern(method_name);
+    vm.find_method(class_sym, method_sym).is_some()
+        || vm.find_native_method(class_sym, method_sym).is_some()
+}
+
 fn function_exists_case_insensitive(vm: &VM, name_bytes: &[u8]) -> bool {
     let stripped = if name_bytes.starts_with(b"\\") {
         &name_bytes[1..]
@@ -22,7 +22,7 @@ pub mod hmac;
 pub mod kdf;
 
 use crate::builtins::exec::{PipeKind, PipeResource};
-use crate::builtins::filesystem::FileHandle;
+use crate::builtins::filesystem::{FileHandle, MemoryStream};
 use crate::builtins::zlib::GzFile;
 use crate::core::value::{ArrayData, ArrayKey, Handle, ObjectData, Val};
 use crate::vm::engine::VM;
@@ -220,105 +220,106 @@ pub fn php_hash(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 
 const HASH_HMAC: i64 = 1;
 
+/// Hasher state behind a HashContext object, taken by hash_final()
+pub struct HashContext {
+    state: RefCell<Option<Box<dyn HashState>>>,
+}
+
+fn new_hash_context(vm: &mut VM, state: Box<dyn HashState>) -> Handle {
+    let obj = ObjectData {
+        class: vm.context.interner.intern(b"HashContext"),
+        properties: indexmap::IndexMap::new(),
+        internal: Some(Rc::new(HashContext {
+            state: RefCell::new(Some(state)),
+        })),
+        dynamic_properties: std::collections::HashSet::new(),
+    };
+    let payload_handle = vm.arena.alloc(Val::ObjPayload(obj));
+    vm.arena.alloc(Val::Object(payload_handle))
+}
+
+/// The HashContext behind `handle`, as the object's internal value
+fn hash_context(vm: &VM, func: &str, handle: Handle) -> Result<Rc<dyn std::any::Any>, String> {
+    let internal = match &vm.arena.get(handle).value {
+        Val::Object(payload) => match &vm.arena.get(*payload).value {
+            Val::ObjPayload(obj) => obj.internal.clone(),
+            _ => None,
+        },
+        _ => None,
+    };
+    match internal {
+        Some(internal) if internal.is::<HashContext>() => Ok(internal),
+        _ => Err(format!(
+            "{}(): Argument #1 ($context) must be of type HashContext",
+            func
+        )),
+    }
+}
+
+/// Run `f` on the context's hasher, failing once the context is finalized
+fn with_hash_state<R>(
+    vm: &VM,
+    func: &str,
+    handle: Handle,
+    f: impl FnOnce(&mut Box<dyn HashState>) -> Result<R, String>,
+) -> Result<R, String> {
+    let internal = hash_context(vm, func, handle)?;
+    let context = internal.downcast_ref::<HashContext>().unwrap();
+    let mut state = context.state.borrow_mut();
+    let state = state.as_mut().ok_or_else(|| {
+        format!(
+            "{}(): Argument #1 ($context) must be a valid, non-finalized HashContext",
+            func
+        )
+    })?;
+    f(state)
+}
+
 /// hash_init(string $algo, int $flags = 0, string $key = ""): HashContext
 pub fn php_hash_init(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() || args.len() > 3 {
         return Err("hash_init() expects 1 to 3 parameters".into());
     }
 
-    // Extract algorithm name
     let algo_name = match &vm.arena.get(args[0]).value {
         Val::String(s) => String::from_utf8_lossy(s).to_lowercase(),
         _ => return Err("hash_init(): Argument #1 ($algo) must be of type string".into()),
     };
 
-    // Extract flags (optional, default 0)
-    let flags = if args.len() >= 2 {
-        match &vm.arena.get(args[1]).value {
-            Val::Int(i) => *i,
-            _ => 0,
-        }
-    } else {
-        0
+    let flags = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => *i,
+        _ => 0,
     };
 
-    // Extract HMAC key (optional)
-    let hmac_key = if args.len() >= 3 {
-        match &vm.arena.get(args[2]).value {
-            Val::String(s) => {
-                if !s.is_empty() {
-                    Some(s.as_ref().to_vec())
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        }
-    } else {
-        None
+    let key = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::String(s)) => s.as_ref().to_vec(),
+        _ => Vec::new(),
     };
 
-    // Check if HMAC flag is set
     let state = if (flags & HASH_HMAC) != 0 {
-        let key = hmac_key.ok_or("hash_init(): HMAC key required when HASH_HMAC flag is set")?;
+        if key.is_empty() {
+            return Err(
+                "hash_init(): Argument #3 ($key) cannot be empty when HMAC is requested".into(),
+            );
+        }
         hmac::new_hmac_state(&algo_name, &key)?
     } else {
-        // Get algorithm from registry
         let hash_data = vm
             .context
             .get_extension_data::<crate::runtime::hash_extension::HashExtensionData>()
             .ok_or("Hash extension not initialized")?;
 
-        let algo = hash_data
-            .registry
-            .get(&algo_name)
-            .ok_or_else(|| format!("hash_init(): Unknown hashing algorithm: {}", algo_name))?;
+        let algo = hash_data.registry.get(&algo_name).ok_or_else(|| {
+            format!(
+                "hash_init(): Argument #1 ($algo) must be a valid hashing algorithm: {}",
+                algo_name
+            )
+        })?;
 
         algo.new_hasher()
     };
 
-    // Get or define HashContext class
-    let class_name = vm.context.interner.intern(b"HashContext");
-
-    // Create hash state (boxed for storage in Val::Resource)
-    let resource_id = vm.context.next_resource_id;
-    vm.context.next_resource_id += 1;
-
-    // Store state as a "resource" internally, wrapped in Rc<dyn Any>
-    let state_handle = vm.arena.alloc(Val::Resource(Rc::new(resource_id)));
-
-    // Store algorithm name and state as properties
-    let algo_prop = vm.context.interner.intern(b"__algorithm");
-    let algo_val = vm
-        .arena
-        .alloc(Val::String(Rc::new(algo_name.as_bytes().to_vec())));
-
-    let state_prop = vm.context.interner.intern(b"__state");
-
-    let finalized_prop = vm.context.interner.intern(b"__finalized");
-
-    // Store the BoxedHashState in the ResourceManager
-    vm.context
-        .resource_manager
-        .register(resource_id, Rc::new(RefCell::new(state)));
-
-    // Create HashContext object
-    use indexmap::IndexMap;
-    use std::collections::HashSet;
-    let mut properties = IndexMap::new();
-    properties.insert(algo_prop, algo_val);
-    properties.insert(state_prop, state_handle);
-    properties.insert(finalized_prop, vm.arena.alloc(Val::Bool(false)));
-
-    let obj = ObjectData {
-        class: class_name,
-        properties,
-        internal: None,
-        dynamic_properties: HashSet::new(),
-    };
-
-    let payload_handle = vm.arena.alloc(Val::ObjPayload(obj));
-    Ok(vm.arena.alloc(Val::Object(payload_handle)))
+    Ok(new_hash_context(vm, state))
 }
 
 /// hash_update(HashContext $context, string $data): bool
@@ -327,58 +328,17 @@ pub fn php_hash_update(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         return Err("hash_update() expects exactly 2 parameters".into());
     }
 
-    // Extract object
-    let obj_handle = match &vm.arena.get(args[0]).value {
-        Val::Object(h) => *h,
-        _ => {
-            return Err("hash_update(): Argument #1 ($context) must be of type HashContext".into());
-        }
-    };
-
-    // Extract data
     let data = match &vm.arena.get(args[1]).value {
         Val::String(s) => s.clone(),
         _ => return Err("hash_update(): Argument #2 ($data) must be of type string".into()),
     };
 
-    // Get object payload
-    let obj = match &vm.arena.get(obj_handle).value {
-        Val::ObjPayload(o) => o,
-        _ => return Err("hash_update(): Invalid HashContext object".into()),
-    };
-
-    // Check if finalized
-    let finalized_prop = vm.context.interner.intern(b"__finalized");
-    if let Some(&finalized_handle) = obj.properties.get(&finalized_prop) {
-        if let Val::Bool(true) = vm.arena.get(finalized_handle).value {
-            return Err("hash_update(): Supplied HashContext has already been finalized".into());
-        }
-    }
-
-    // Get state resource ID
-    let state_prop = vm.context.interner.intern(b"__state");
-    let resource_id = match obj.properties.get(&state_prop) {
-        Some(&handle) => match &vm.arena.get(handle).value {
-            Val::Resource(rc) => *rc
-                .downcast_ref::<u64>()
-                .ok_or("hash_update(): Invalid resource type")?,
-            _ => return Err("hash_update(): Invalid hash state".into()),
-        },
-        None => return Err("hash_update(): Invalid hash state".into()),
-    };
+    with_hash_state(vm, "hash_update", args[0], |state| {
+        state.update(&data);
+        Ok(())
+    })?;
 
-    // Update the hash
-    if let Some(state_rc) = vm
-        .context
-        .resource_manager
-        .get::<Box<dyn HashState>>(resource_id)
-    {
-        println!("DEBUG: hash_update data = {:?}", data);
-        state_rc.borrow_mut().update(&data);
-        Ok(vm.arena.alloc(Val::Bool(true)))
-    } else {
-        Err("hash_update(): Invalid hash context state".into())
-    }
+    Ok(vm.arena.alloc(Val::Bool(true)))
 }
 
 /// hash_update_file(HashContext $context, string $filename, ?resource $stream_context = null): bool
@@ -387,17 +347,6 @@ pub fn php_hash_update_file(vm: &mut VM, args: &[Handle]) -> Result<Handle, Stri
         return Err("hash_update_file() expects 2 or 3 parameters".into());
     }
 
-    // Extract object
-    let obj_handle = match &vm.arena.get(args[0]).value {
-        Val::Object(h) => *h,
-        _ => {
-            return Err(
-                "hash_update_file(): Argument #1 ($context) must be of type HashContext".into(),
-            );
-        }
-    };
-
-    // Extract filename
     let filename = match &vm.arena.get(args[1]).value {
         Val::String(s) => String::from_utf8_lossy(s).to_string(),
         _ => {
@@ -407,49 +356,47 @@ pub fn php_hash_update_file(vm: &mut VM, args: &[Handle]) -> Result<Handle, Stri
         }
     };
 
-    // Get object payload
-    let obj = match &vm.arena.get(obj_handle).value {
-        Val::ObjPayload(o) => o,
-        _ => return Err("hash_update_file(): Invalid HashContext object".into()),
-    };
-
-    // Check if finalized
-    let finalized_prop = vm.context.interner.intern(b"__finalized");
-    if let Some(&finalized_handle) = obj.properties.get(&finalized_prop) {
-        if let Val::Bool(true) = vm.arena.get(finalized_handle).value {
-            return Err(
-                "hash_update_file(): Supplied HashContext has already been finalized".into(),
-            );
+    with_hash_state(vm, "hash_update_file", args[0], |state| {
+        let mut file = std::fs::File::open(&filename)
+            .map_err(|e| format!("hash_update_file(): Failed to open '{}': {}", filename, e))?;
+        let mut buffer = vec![0u8; 8192];
+        loop {
+            let n = file
+                .read(&mut buffer)
+                .map_err(|e| format!("hash_update_file(): {}", e))?;
+            if n == 0 {
+                return Ok(());
+            }
+            state.update(&buffer[..n]);
         }
-    }
-
-    // Get state resource ID
-    let state_prop = vm.context.interner.intern(b"__state");
-    let resource_id = match obj.properties.get(&state_prop) {
-        Some(&handle) => match &vm.arena.get(handle).value {
-            Val::Resource(rc) => *rc
-                .downcast_ref::<u64>()
-                .ok_or("hash_update_file(): Invalid resource type")?,
-            _ => return Err("hash_update_file(): Invalid hash state".into()),
-        },
-        None => return Err("hash_update_file(): Invalid hash state".into()),
-    };
+    })?;
 
-    // Read file contents
-    let data = std::fs::read(&filename)
-        .map_err(|e| format!("hash_update_file(): Failed to open '{}': {}", filename, e))?;
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
 
-    // Update the hash
-    if let Some(state_rc) = vm
-        .context
-        .resource_manager
-        .get::<Box<dyn HashState>>(resource_id)
-    {
-        state_rc.borrow_mut().update(&data);
-        Ok(vm.arena.alloc(Val::Bool(true)))
+/// Read up to `buffer.len()` bytes from a stream resource
+fn read_stream(stream: &Rc<dyn std::any::Any>, buffer: &mut [u8]) -> Result<usize, String> {
+    let read = if let Some(fh) = stream.downcast_ref::<FileHandle>() {
+        fh.file.borrow_mut().read(buffer)
+    } else if let Some(pr) = stream.downcast_ref::<PipeResource>() {
+        match &mut *pr.pipe.borrow_mut() {
+            PipeKind::Stdout(stdout) => stdout.read(buffer),
+            PipeKind::Stderr(stderr) => stderr.read(buffer),
+            _ => return Err("hash_update_stream(): Cannot read from stdin pipe".into()),
+        }
+    } else if let Some(gz) = stream.downcast_ref::<GzFile>() {
+        gz.inner.borrow_mut().read(buffer)
+    } else if let Some(mem) = stream.downcast_ref::<MemoryStream>() {
+        let data = mem.buffer.borrow();
+        let mut pos = mem.position.borrow_mut();
+        let n = data.len().saturating_sub(*pos).min(buffer.len());
+        buffer[..n].copy_from_slice(&data[*pos..*pos + n]);
+        *pos += n;
+        Ok(n)
     } else {
-        Err("hash_update_file(): Invalid hash context state".into())
-    }
+        return Err("hash_update_stream(): Unsupported resource type".into());
+    };
+    read.map_err(|e| format!("hash_update_stream(): {}", e))
 }
 
 /// hash_update_stream(HashContext $context, resource $stream, int $length = -1): int
@@ -458,18 +405,7 @@ pub fn php_hash_update_stream(vm: &mut VM, args: &[Handle]) -> Result<Handle, St
         return Err("hash_update_stream() expects 2 or 3 parameters".into());
     }
 
-    // Extract object
-    let obj_handle = match &vm.arena.get(args[0]).value {
-        Val::Object(h) => *h,
-        _ => {
-            return Err(
-                "hash_update_stream(): Argument #1 ($context) must be of type HashContext".into(),
-            );
-        }
-    };
-
-    // Extract stream resource
-    let stream_rc = match &vm.arena.get(args[1]).value {
+    let stream = match &vm.arena.get(args[1]).value {
         Val::Resource(rc) => rc.clone(),
         _ => {
             return Err(
@@ -478,55 +414,14 @@ pub fn php_hash_update_stream(vm: &mut VM, args: &[Handle]) -> Result<Handle, St
         }
     };
 
-    // Extract length (optional, default -1)
-    let length = if args.len() >= 3 {
-        match &vm.arena.get(args[2]).value {
-            Val::Int(i) => *i,
-            _ => -1,
-        }
-    } else {
-        -1
-    };
-
-    // Get object payload
-    let obj = match &vm.arena.get(obj_handle).value {
-        Val::ObjPayload(o) => o,
-        _ => return Err("hash_update_stream(): Invalid HashContext object".into()),
-    };
-
-    // Check if finalized
-    let finalized_prop = vm.context.interner.intern(b"__finalized");
-    if let Some(&finalized_handle) = obj.properties.get(&finalized_prop) {
-        if let Val::Bool(true) = vm.arena.get(finalized_handle).value {
-            return Err(
-                "hash_update_stream(): Supplied HashContext has already been finalized".into(),
-            );
-        }
-    }
-
-    // Get state resource ID
-    let state_prop = vm.context.interner.intern(b"__state");
-    let resource_id = match obj.properties.get(&state_prop) {
-        Some(&handle) => match &vm.arena.get(handle).value {
-            Val::Resource(rc) => *rc
-                .downcast_ref::<u64>()
-                .ok_or("hash_update_stream(): Invalid resource type")?,
-            _ => return Err("hash_update_stream(): Invalid hash state".into()),
-        },
-        None => return Err("hash_update_stream(): Invalid hash state".into()),
+    let length = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => *i,
+        _ => -1,
     };
 
-    // Read from stream and update hash
-    let mut total_read = 0;
-    let mut buffer = vec![0u8; 8192];
-
-    // Get hash state from ResourceManager
-    if let Some(state_rc) = vm
-        .context
-        .resource_manager
-        .get::<Box<dyn HashState>>(resource_id)
-    {
-        let mut state = state_rc.borrow_mut();
+    let total_read = with_hash_state(vm, "hash_update_stream", args[0], |state| {
+        let mut total_read = 0;
+        let mut buffer = vec![0u8; 8192];
         loop {
             let to_read = if length < 0 {
                 buffer.len()
@@ -535,46 +430,20 @@ pub fn php_hash_update_stream(vm: &mut VM, args: &[Handle]) -> Result<Handle, St
                 if remaining == 0 {
                     break;
                 }
-                std::cmp::min(buffer.len(), remaining)
-            };
-
-            let bytes_read = if let Some(fh) = stream_rc.downcast_ref::<FileHandle>() {
-                fh.file
-                    .borrow_mut()
-                    .read(&mut buffer[..to_read])
-                    .map_err(|e| format!("hash_update_stream(): {}", e))?
-            } else if let Some(pr) = stream_rc.downcast_ref::<PipeResource>() {
-                let mut pipe = pr.pipe.borrow_mut();
-                match &mut *pipe {
-                    PipeKind::Stdout(stdout) => stdout
-                        .read(&mut buffer[..to_read])
-                        .map_err(|e| format!("hash_update_stream(): {}", e))?,
-                    PipeKind::Stderr(stderr) => stderr
-                        .read(&mut buffer[..to_read])
-                        .map_err(|e| format!("hash_update_stream(): {}", e))?,
-                    _ => return Err("hash_update_stream(): Cannot read from stdin pipe".into()),
-                }
-            } else if let Some(gz) = stream_rc.downcast_ref::<GzFile>() {
-                gz.inner
-                    .borrow_mut()
-                    .read(&mut buffer[..to_read])
-                    .map_err(|e| format!("hash_update_stream(): {}", e))?
-            } else {
-                return Err("hash_update_stream(): Unsupported resource type".into());
+                remaining.min(buffer.len())
             };
 
-            if bytes_read == 0 {
+            let n = read_stream(&stream, &mut buffer[..to_read])?;
+            if n == 0 {
                 break;
             }
-
-            state.update(&buffer[..bytes_read]);
-            total_read += bytes_read;
+            state.update(&buffer[..n]);
+            total_read += n;
         }
+        Ok(total_read)
+    })?;
 
-        Ok(vm.arena.alloc(Val::Int(total_read as i64)))
-    } else {
-        Err("hash_update_stream(): Invalid hash context state".into())
-    }
+    Ok(vm.arena.alloc(Val::Int(total_read as i64)))
 }
 
 /// hash_final(HashContext $context, bool $binary = false): string
@@ -583,73 +452,20 @@ pub fn php_hash_final(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         return Err("hash_final() expects 1 or 2 parameters".into());
     }
 
-    // Extract object
-    let obj_handle = match &vm.arena.get(args[0]).value {
-        Val::Object(h) => *h,
-        _ => return Err("hash_final(): Argument #1 ($context) must be of type HashContext".into()),
-    };
-
-    // Extract binary flag (optional)
-    let binary = if args.len() >= 2 {
-        match &vm.arena.get(args[1]).value {
-            Val::Bool(b) => *b,
-            Val::Int(i) => *i != 0,
-            _ => false,
-        }
-    } else {
-        false
-    };
-
-    // Get object payload (need mutable access to update finalized flag)
-    let obj = match &vm.arena.get(obj_handle).value {
-        Val::ObjPayload(o) => o.clone(),
-        _ => return Err("hash_final(): Invalid HashContext object".into()),
-    };
-
-    // Check if already finalized
-    let finalized_prop = vm.context.interner.intern(b"__finalized");
-    if let Some(&finalized_handle) = obj.properties.get(&finalized_prop) {
-        if let Val::Bool(true) = vm.arena.get(finalized_handle).value {
-            return Err("hash_final(): Supplied HashContext has already been finalized".into());
-        }
-    }
-
-    // Get state resource ID
-    let state_prop = vm.context.interner.intern(b"__state");
-    let resource_id = match obj.properties.get(&state_prop) {
-        Some(&handle) => match &vm.arena.get(handle).value {
-            Val::Resource(rc) => *rc
-                .downcast_ref::<u64>()
-                .ok_or("hash_final(): Invalid resource type")?,
-            _ => return Err("hash_final(): Invalid hash state".into()),
-        },
-        None => return Err("hash_final(): Invalid hash state".into()),
-    };
+    let binary = args
+        .get(1)
+        .is_some_and(|h| vm.arena.get(*h).value.to_bool());
 
-    // Remove and finalize the hash
-    let digest = if let Some(state_rc) = vm
-        .context
-        .resource_manager
-        .remove::<Box<dyn HashState>>(resource_id)
-    {
-        // Take the state out of Rc<RefCell<>>
-        let state = Rc::try_unwrap(state_rc)
-            .map_err(|_| "hash_final(): Failed to take ownership of hash state")?
-            .into_inner();
-        state.finalize()
-    } else {
-        return Err("hash_final(): Invalid hash context state".into());
-    };
-
-    // Mark as finalized
-    if let Val::ObjPayload(mut obj_data) = vm.arena.get(obj_handle).value.clone() {
-        obj_data
-            .properties
-            .insert(finalized_prop, vm.arena.alloc(Val::Bool(true)));
-        vm.arena.get_mut(obj_handle).value = Val::ObjPayload(obj_data);
-    }
+    let internal = hash_context(vm, "hash_final", args[0])?;
+    let state = internal
+        .downcast_ref::<HashContext>()
+        .unwrap()
+        .state
+        .borrow_mut()
+        .take()
+        .ok_or("hash_final(): Argument #1 ($context) must be a valid, non-finalized HashContext")?;
+    let digest = state.finalize();
 
-    // Format output
     let result = if binary {
         digest
     } else {
@@ -665,91 +481,9 @@ pub fn php_hash_copy(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         return Err("hash_copy() expects exactly 1 parameter".into());
     }
 
-    // Extract object
-    let obj_handle = match &vm.arena.get(args[0]).value {
-        Val::Object(h) => *h,
-        _ => return Err("hash_copy(): Argument #1 ($context) must be of type HashContext".into()),
-    };
-
-    // Get object payload
-    let obj = match &vm.arena.get(obj_handle).value {
-        Val::ObjPayload(o) => o,
-        _ => return Err("hash_copy(): Invalid HashContext object".into()),
-    };
-
-    // Check if finalized
-    let finalized_prop = vm.context.interner.intern(b"__finalized");
-    if let Some(&finalized_handle) = obj.properties.get(&finalized_prop) {
-        if let Val::Bool(true) = vm.arena.get(finalized_handle).value {
-            return Err("hash_copy(): Supplied HashContext has already been finalized".into());
-        }
-    }
-
-    // Get algorithm name
-    let algo_prop = vm.context.interner.intern(b"__algorithm");
-    let algo_name = match obj.properties.get(&algo_prop) {
-        Some(&handle) => match &vm.arena.get(handle).value {
-            Val::String(s) => String::from_utf8_lossy(s).to_string(),
-            _ => return Err("hash_copy(): Invalid algorithm property".into()),
-        },
-        None => return Err("hash_copy(): Invalid algorithm property".into()),
-    };
-
-    // Get state resource ID
-    let state_prop = vm.context.interner.intern(b"__state");
-    let resource_id = match obj.properties.get(&state_prop) {
-        Some(&handle) => match &vm.arena.get(handle).value {
-            Val::Resource(rc) => *rc
-                .downcast_ref::<u64>()
-                .ok_or("hash_copy(): Invalid resource type")?,
-            _ => return Err("hash_copy(): Invalid hash state".into()),
-        },
-        None => return Err("hash_copy(): Invalid hash state".into()),
-    };
-
-    // Clone the state
-    let new_state = if let Some(state_rc) = vm
-        .context
-        .resource_manager
-        .get::<Box<dyn HashState>>(resource_id)
-    {
-        state_rc.borrow().clone_state()
-    } else {
-        return Err("hash_copy(): Invalid hash context state".into());
-    };
-
-    // Create new resource ID and store cloned state
-    let new_resource_id = vm.context.next_resource_id;
-    vm.context.next_resource_id += 1;
-
-    let new_state_handle = vm.arena.alloc(Val::Resource(Rc::new(new_resource_id)));
-
-    vm.context
-        .resource_manager
-        .register(new_resource_id, Rc::new(RefCell::new(new_state)));
-
-    // Create new HashContext object
-    use indexmap::IndexMap;
-    use std::collections::HashSet;
-    let class_name = vm.context.interner.intern(b"HashContext");
-    let mut properties = IndexMap::new();
-
-    let algo_val = vm
-        .arena
-        .alloc(Val::String(Rc::new(algo_name.as_bytes().to_vec())));
-    properties.insert(algo_prop, algo_val);
-    properties.insert(state_prop, new_state_handle);
-    properties.insert(finalized_prop, vm.arena.alloc(Val::Bool(false)));
-
-    let new_obj = ObjectData {
-        class: class_name,
-        properties,
-        internal: None,
-        dynamic_properties: HashSet::new(),
-    };
+    let state = with_hash_state(vm, "hash_copy", args[0], |state| Ok(state.clone_state()))?;
 
-    let new_payload_handle = vm.arena.alloc(Val::ObjPayload(new_obj));
-    Ok(vm.arena.alloc(Val::Object(new_payload_handle)))
+    Ok(new_hash_context(vm, state))
 }
 
 /// hash_algos(): array
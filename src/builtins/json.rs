@@ -2,6 +2,7 @@
 //!
 //! This module implements PHP's JSON extension with the following functions:
 //! - json_encode() - Serialize PHP values to JSON strings
+//! - json_encode_stream() - Serialize PHP values to JSON directly into a stream
 //! - json_decode() - Parse JSON strings into PHP values
 //! - json_last_error() - Get last JSON error code
 //! - json_last_error_msg() - Get last JSON error message
@@ -21,9 +22,13 @@
 //! - Zend Encoder: $PHP_SRC_PATH/ext/json/json_encoder.c
 //! - Zend Parser: $PHP_SRC_PATH/ext/json/json_parser.y
 
-use crate::core::value::{ArrayData, ArrayKey, Handle, ObjectData, Val};
-use crate::vm::engine::VM;
+use crate::builtins::filesystem::stream_write;
+use crate::core::value::{
+    ArrayData, ArrayKey, Handle, ObjectData, Symbol, Val, format_float_serialize,
+};
+use crate::vm::engine::{ErrorLevel, VM, VmError};
 use indexmap::IndexMap;
+use std::any::Any;
 use std::collections::HashSet;
 use std::rc::Rc;
 
@@ -142,16 +147,21 @@ impl JsonDecodeOptions {
 /// Encoding context with recursion tracking
 /// Reference: $PHP_SRC_PATH/ext/json/json_encoder.c - php_json_encode_ex
 struct EncodeContext<'a> {
-    vm: &'a VM,
+    vm: &'a mut VM,
     depth: usize,
     max_depth: usize,
     visited: HashSet<Handle>,
     options: JsonEncodeOptions,
     indent_level: usize,
+    /// Walk Traversable objects element by element instead of encoding
+    /// their properties
+    iterate_traversables: bool,
+    /// Exception raised by PHP code while iterating a Traversable
+    iteration_error: Option<VmError>,
 }
 
 impl<'a> EncodeContext<'a> {
-    fn new(vm: &'a VM, options: JsonEncodeOptions, max_depth: usize) -> Self {
+    fn new(vm: &'a mut VM, options: JsonEncodeOptions, max_depth: usize) -> Self {
         Self {
             vm,
             depth: 0,
@@ -159,66 +169,87 @@ impl<'a> EncodeContext<'a> {
             visited: HashSet::new(),
             options,
             indent_level: 0,
+            iterate_traversables: false,
+            iteration_error: None,
         }
     }
 
-    /// Main recursive encoding entry point
+    /// Encode a value into a single string
     fn encode_value(&mut self, handle: Handle) -> Result<String, JsonError> {
+        let mut result = String::new();
+        self.encode_value_to(handle, &mut |piece| result.push_str(piece))?;
+        Ok(result)
+    }
+
+    /// Main recursive encoding entry point. Output is handed to `emit` piece
+    /// by piece, so arrays and objects never need to exist as one string.
+    fn encode_value_to(
+        &mut self,
+        handle: Handle,
+        emit: &mut dyn FnMut(&str),
+    ) -> Result<(), JsonError> {
         // Check depth limit
         if self.depth >= self.max_depth {
             return Err(JsonError::Depth);
         }
 
-        let val = &self.vm.arena.get(handle).value;
+        let is_composite = matches!(
+            self.vm.arena.get(handle).value,
+            Val::Array(_) | Val::Object(_)
+        );
 
         // Check for circular references on composite types
-        match val {
-            Val::Array(_) | Val::Object(_) => {
-                if !self.visited.insert(handle) {
-                    return Err(JsonError::Recursion);
-                }
-            }
-            _ => {}
+        if is_composite && !self.visited.insert(handle) {
+            return Err(JsonError::Recursion);
         }
 
         self.depth += 1;
-        let result = self.encode_value_internal(handle);
+        let result = self.encode_value_internal(handle, emit);
         self.depth -= 1;
 
         // Remove from visited set after processing
-        match val {
-            Val::Array(_) | Val::Object(_) => {
-                self.visited.remove(&handle);
-            }
-            _ => {}
+        if is_composite {
+            self.visited.remove(&handle);
         }
 
         result
     }
 
-    fn encode_value_internal(&mut self, handle: Handle) -> Result<String, JsonError> {
+    fn encode_value_internal(
+        &mut self,
+        handle: Handle,
+        emit: &mut dyn FnMut(&str),
+    ) -> Result<(), JsonError> {
         let val = &self.vm.arena.get(handle).value;
 
-        match val {
-            Val::Null => Ok("null".to_string()),
-            Val::Bool(b) => Ok(if *b { "true" } else { "false" }.to_string()),
-            Val::Int(i) => Ok(i.to_string()),
-            Val::Float(f) => self.encode_float(*f),
-            Val::String(s) => self.encode_string(s),
-            Val::Array(arr) => self.encode_array(arr),
-            Val::Object(payload_handle) => self.encode_object(*payload_handle),
-            Val::Resource(_) => Err(JsonError::UnsupportedType),
+        let scalar = match val {
+            Val::Null => "null".to_string(),
+            Val::Bool(b) => if *b { "true" } else { "false" }.to_string(),
+            Val::Int(i) => i.to_string(),
+            Val::Float(f) => self.encode_float(*f)?,
+            Val::String(s) => self.encode_string(s)?,
+            Val::Array(arr) => {
+                let arr = arr.clone();
+                return self.encode_array(&arr, emit);
+            }
+            Val::Object(payload_handle) => {
+                let payload_handle = *payload_handle;
+                return self.encode_object(handle, payload_handle, emit);
+            }
+            Val::Resource(_) => return Err(JsonError::UnsupportedType),
             Val::ObjPayload(_) => {
                 // Should not be called directly on payload
-                Err(JsonError::UnsupportedType)
+                return Err(JsonError::UnsupportedType);
             }
             Val::ConstArray(_) => {
                 // Compile-time arrays shouldn't appear during runtime encoding
-                Err(JsonError::UnsupportedType)
+                return Err(JsonError::UnsupportedType);
             }
-            Val::AppendPlaceholder => Err(JsonError::UnsupportedType),
-            Val::Uninitialized => Err(JsonError::UnsupportedType),
-        }
+            Val::AppendPlaceholder => return Err(JsonError::UnsupportedType),
+            Val::Uninitialized => return Err(JsonError::UnsupportedType),
+        };
+        emit(&scalar);
+        Ok(())
     }
 
     fn encode_float(&self, f: f64) -> Result<String, JsonError> {
@@ -226,11 +257,12 @@ impl<'a> EncodeContext<'a> {
             return Err(JsonError::InfOrNan);
         }
 
-        if self.options.preserve_zero_fraction && f.fract() == 0.0 {
-            Ok(format!("{:.1}", f))
-        } else {
-            Ok(f.to_string())
+        let precision = crate::builtins::variable::serialize_precision(self.vm);
+        let mut repr = format_float_serialize(f, precision, 'e');
+        if self.options.preserve_zero_fraction && !repr.contains('.') {
+            repr.push_str(".0");
         }
+        Ok(repr)
     }
 
     fn encode_string(&self, bytes: &Rc<Vec<u8>>) -> Result<String, JsonError> {
@@ -269,15 +301,19 @@ impl<'a> EncodeContext<'a> {
         Ok(result)
     }
 
-    fn encode_array(&mut self, arr: &Rc<ArrayData>) -> Result<String, JsonError> {
+    fn encode_array(
+        &mut self,
+        arr: &Rc<ArrayData>,
+        emit: &mut dyn FnMut(&str),
+    ) -> Result<(), JsonError> {
         // Determine if this is a JSON array (sequential int keys starting at 0)
         // or a JSON object (associative)
         let is_list = !self.options.force_object && self.is_sequential_array(&arr.map);
 
         if is_list {
-            self.encode_array_as_list(arr)
+            self.encode_array_as_list(arr, emit)
         } else {
-            self.encode_array_as_object(arr)
+            self.encode_array_as_object(arr, emit)
         }
     }
 
@@ -298,8 +334,33 @@ impl<'a> EncodeContext<'a> {
         true
     }
 
-    fn encode_array_as_list(&mut self, arr: &Rc<ArrayData>) -> Result<String, JsonError> {
-        let mut result = String::from("[");
+    /// Emit the separator and pretty-print indentation before an element
+    fn begin_element(&self, first: bool, emit: &mut dyn FnMut(&str)) {
+        if !first {
+            emit(",");
+        }
+        if self.options.pretty_print {
+            emit("\n");
+            emit(&"    ".repeat(self.indent_level));
+        }
+    }
+
+    /// Emit the closing indentation and bracket of a container
+    fn end_container(&mut self, is_empty: bool, close: &str, emit: &mut dyn FnMut(&str)) {
+        if self.options.pretty_print && !is_empty {
+            self.indent_level -= 1;
+            emit("\n");
+            emit(&"    ".repeat(self.indent_level));
+        }
+        emit(close);
+    }
+
+    fn encode_array_as_list(
+        &mut self,
+        arr: &Rc<ArrayData>,
+        emit: &mut dyn FnMut(&str),
+    ) -> Result<(), JsonError> {
+        emit("[");
 
         if self.options.pretty_print && !arr.map.is_empty() {
             self.indent_level += 1;
@@ -307,31 +368,22 @@ impl<'a> EncodeContext<'a> {
 
         let mut first = true;
         for (_, value_handle) in arr.map.iter() {
-            if !first {
-                result.push(',');
-            }
+            self.begin_element(first, emit);
             first = false;
 
-            if self.options.pretty_print {
-                result.push('\n');
-                result.push_str(&"    ".repeat(self.indent_level));
-            }
-
-            result.push_str(&self.encode_value(*value_handle)?);
+            self.encode_value_to(*value_handle, emit)?;
         }
 
-        if self.options.pretty_print && !arr.map.is_empty() {
-            self.indent_level -= 1;
-            result.push('\n');
-            result.push_str(&"    ".repeat(self.indent_level));
-        }
-
-        result.push(']');
-        Ok(result)
+        self.end_container(arr.map.is_empty(), "]", emit);
+        Ok(())
     }
 
-    fn encode_array_as_object(&mut self, arr: &Rc<ArrayData>) -> Result<String, JsonError> {
-        let mut result = String::from("{");
+    fn encode_array_as_object(
+        &mut self,
+        arr: &Rc<ArrayData>,
+        emit: &mut dyn FnMut(&str),
+    ) -> Result<(), JsonError> {
+        emit("{");
 
         if self.options.pretty_print && !arr.map.is_empty() {
             self.indent_level += 1;
@@ -339,16 +391,9 @@ impl<'a> EncodeContext<'a> {
 
         let mut first = true;
         for (key, value_handle) in arr.map.iter() {
-            if !first {
-                result.push(',');
-            }
+            self.begin_element(first, emit);
             first = false;
 
-            if self.options.pretty_print {
-                result.push('\n');
-                result.push_str(&"    ".repeat(self.indent_level));
-            }
-
             // Encode key as string
             let key_str = match key {
                 ArrayKey::Int(i) => i.to_string(),
@@ -357,46 +402,51 @@ impl<'a> EncodeContext<'a> {
                     .to_string(),
             };
 
-            result.push('"');
-            result.push_str(&key_str);
-            result.push('"');
-            result.push(':');
-
-            if self.options.pretty_print {
-                result.push(' ');
-            }
-
-            result.push_str(&self.encode_value(*value_handle)?);
-        }
+            emit("\"");
+            emit(&key_str);
+            emit(if self.options.pretty_print {
+                "\": "
+            } else {
+                "\":"
+            });
 
-        if self.options.pretty_print && !arr.map.is_empty() {
-            self.indent_level -= 1;
-            result.push('\n');
-            result.push_str(&"    ".repeat(self.indent_level));
+            self.encode_value_to(*value_handle, emit)?;
         }
 
-        result.push('}');
-        Ok(result)
+        self.end_container(arr.map.is_empty(), "}", emit);
+        Ok(())
     }
 
-    fn encode_object(&mut self, payload_handle: Handle) -> Result<String, JsonError> {
-        let payload_val = &self.vm.arena.get(payload_handle).value;
-        let obj_data = match payload_val {
-            Val::ObjPayload(data) => data,
+    fn encode_object(
+        &mut self,
+        obj_handle: Handle,
+        payload_handle: Handle,
+        emit: &mut dyn FnMut(&str),
+    ) -> Result<(), JsonError> {
+        if self.iterate_traversables {
+            match self.vm.traversable_iterator(obj_handle) {
+                Ok(Some(iterator)) => return self.encode_traversable(iterator, emit),
+                Ok(None) => {}
+                Err(e) => return Err(self.fail_iteration(e)),
+            }
+        }
+
+        let properties: Vec<(Symbol, Handle)> = match &self.vm.arena.get(payload_handle).value {
+            Val::ObjPayload(data) => data.properties.iter().map(|(k, v)| (*k, *v)).collect(),
             _ => return Err(JsonError::UnsupportedType),
         };
 
         // TODO: Check for JsonSerializable interface
         // If implemented, call $obj->jsonSerialize() and encode its return value
 
-        let mut result = String::from("{");
+        emit("{");
 
-        if self.options.pretty_print && !obj_data.properties.is_empty() {
+        if self.options.pretty_print && !properties.is_empty() {
             self.indent_level += 1;
         }
 
         let mut first = true;
-        for (prop_sym, prop_handle) in obj_data.properties.iter() {
+        for (prop_sym, prop_handle) in properties.iter() {
             // Get property name
             let prop_name = self
                 .vm
@@ -407,36 +457,88 @@ impl<'a> EncodeContext<'a> {
             let prop_str =
                 std::str::from_utf8(prop_name).map_err(|_| JsonError::InvalidPropertyName)?;
 
-            if !first {
-                result.push(',');
-            }
+            self.begin_element(first, emit);
             first = false;
 
-            if self.options.pretty_print {
-                result.push('\n');
-                result.push_str(&"    ".repeat(self.indent_level));
-            }
+            emit("\"");
+            emit(prop_str);
+            emit(if self.options.pretty_print {
+                "\": "
+            } else {
+                "\":"
+            });
 
-            result.push('"');
-            result.push_str(prop_str);
-            result.push('"');
-            result.push(':');
+            self.encode_value_to(*prop_handle, emit)?;
+        }
 
-            if self.options.pretty_print {
-                result.push(' ');
-            }
+        self.end_container(properties.is_empty(), "}", emit);
+        Ok(())
+    }
+
+    /// Encode a Traversable one element at a time as it is iterated. Nothing
+    /// already emitted can be revised, so the first key decides the shape: a
+    /// list when it is 0, an object otherwise. Later keys of a list are
+    /// dropped, as the values are still written in iteration order.
+    fn encode_traversable(
+        &mut self,
+        iterator: Handle,
+        emit: &mut dyn FnMut(&str),
+    ) -> Result<(), JsonError> {
+        let mut entry = self.next_entry(iterator, false)?;
+        let is_list = !self.options.force_object
+            && entry.is_none_or(|(key, _)| matches!(self.vm.arena.get(key).value, Val::Int(0)));
+        let (open, close) = if is_list { ("[", "]") } else { ("{", "}") };
 
-            result.push_str(&self.encode_value(*prop_handle)?);
+        emit(open);
+        if self.options.pretty_print && entry.is_some() {
+            self.indent_level += 1;
         }
 
-        if self.options.pretty_print && !obj_data.properties.is_empty() {
-            self.indent_level -= 1;
-            result.push('\n');
-            result.push_str(&"    ".repeat(self.indent_level));
+        let mut first = true;
+        while let Some((key, value)) = entry {
+            self.begin_element(first, emit);
+            first = false;
+
+            if !is_list {
+                let key_str = match &self.vm.arena.get(key).value {
+                    Val::Int(i) => i.to_string(),
+                    Val::String(s) => std::str::from_utf8(s)
+                        .map_err(|_| JsonError::Utf8)?
+                        .to_string(),
+                    _ => return Err(JsonError::InvalidPropertyName),
+                };
+                emit("\"");
+                emit(&key_str);
+                emit(if self.options.pretty_print {
+                    "\": "
+                } else {
+                    "\":"
+                });
+            }
+
+            self.encode_value_to(value, emit)?;
+            entry = self.next_entry(iterator, true)?;
         }
 
-        result.push('}');
-        Ok(result)
+        self.end_container(first, close, emit);
+        Ok(())
+    }
+
+    fn next_entry(
+        &mut self,
+        iterator: Handle,
+        started: bool,
+    ) -> Result<Option<(Handle, Handle)>, JsonError> {
+        self.vm
+            .traversable_step(iterator, started)
+            .map_err(|e| self.fail_iteration(e))
+    }
+
+    /// Keep an error raised by PHP code during iteration for the caller to
+    /// rethrow; encoding stops with a generic error code
+    fn fail_iteration(&mut self, error: VmError) -> JsonError {
+        self.iteration_error = Some(error);
+        JsonError::UnsupportedType
     }
 }
 
@@ -509,6 +611,121 @@ pub fn php_json_encode(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     }
 }
 
+/// Buffers encoder output and writes it to a stream resource in chunks
+struct StreamSink {
+    stream: Rc<dyn Any>,
+    pending: String,
+    written: usize,
+    error: Option<String>,
+}
+
+impl StreamSink {
+    const CHUNK_SIZE: usize = 8192;
+
+    fn push(&mut self, piece: &str) {
+        self.pending.push_str(piece);
+        if self.pending.len() >= Self::CHUNK_SIZE {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.error.is_none() && !self.pending.is_empty() {
+            match stream_write(&self.stream, self.pending.as_bytes()) {
+                Ok(n) => self.written += n,
+                Err(e) => self.error = Some(e),
+            }
+        }
+        self.pending.clear();
+    }
+}
+
+/// json_encode_stream(resource $stream, mixed $value, int $flags = 0, int $depth = 512): int|false
+///
+/// Writes the JSON representation of a value to a stream as it is encoded,
+/// so the output never has to be held in memory as one string. Generators
+/// and other Traversables are iterated as they are written, so their
+/// elements are never collected either.
+///
+/// # Arguments
+/// * `args[0]` - The stream resource to write to
+/// * `args[1]` - The value to encode
+/// * `args[2]` - (Optional) Bitmask of JSON_* constants (default: 0)
+/// * `args[3]` - (Optional) Maximum depth (default: 512)
+///
+/// # Returns
+/// * Number of bytes written, `false` on error. Output written before an
+///   encoding error is left in the stream.
+pub fn php_json_encode_stream(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err(format!(
+            "json_encode_stream() expects at least 2 parameters, {} given",
+            args.len()
+        ));
+    }
+
+    // Reset error state
+    vm.context
+        .get_or_init_extension_data(JsonExtensionData::default)
+        .last_error = JsonError::None;
+
+    let stream = match &vm.arena.get(args[0]).value {
+        Val::Resource(rc) => rc.clone(),
+        _ => {
+            return Err(
+                "json_encode_stream(): Argument #1 ($stream) must be of type resource".into(),
+            );
+        }
+    };
+
+    let options = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(flags)) => JsonEncodeOptions::from_flags(*flags),
+        _ => JsonEncodeOptions::default(),
+    };
+
+    let max_depth = match args.get(3).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) if *i > 0 => *i as usize,
+        _ => 512,
+    };
+
+    let mut sink = StreamSink {
+        stream,
+        pending: String::with_capacity(StreamSink::CHUNK_SIZE),
+        written: 0,
+        error: None,
+    };
+    let mut ctx = EncodeContext::new(vm, options, max_depth);
+    ctx.iterate_traversables = true;
+    let result = ctx.encode_value_to(args[1], &mut |piece| sink.push(piece));
+    let iteration_error = ctx.iteration_error.take();
+    sink.flush();
+
+    match iteration_error {
+        Some(VmError::Exception(ex_handle)) => return Err(vm.throw_exception(ex_handle)),
+        Some(e) => return Err(e.to_string()),
+        None => {}
+    }
+
+    if let Some(e) = sink.error {
+        vm.report_error(ErrorLevel::Warning, &format!("json_encode_stream(): {}", e));
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    match result {
+        Ok(()) => Ok(vm.arena.alloc(Val::Int(sink.written as i64))),
+        Err(err) => {
+            vm.context
+                .get_or_init_extension_data(JsonExtensionData::default)
+                .last_error = err;
+            if options.throw_on_error {
+                Err(format!("json_encode_stream error: {}", err.message()))
+            } else {
+                Ok(vm.arena.alloc(Val::Bool(false)))
+            }
+        }
+    }
+}
+
 /// json_decode(string $json, bool $assoc = false, int $depth = 512, int $flags = 0): mixed
 ///
 /// Decodes a JSON string
@@ -670,7 +887,10 @@ fn decode_json_value(
                 for (key, val) in entries.iter() {
                     let handle =
                         decode_json_value(vm, val, object_as_array, options, depth + 1, max_depth)?;
-                    map.insert(ArrayKey::Str(key.as_bytes().to_vec().into()), handle);
+                    map.insert(
+                        ArrayKey::from_string(key.as_bytes().to_vec().into()),
+                        handle,
+                    );
                 }
                 let array_val = Val::Array(Rc::new(ArrayData::from(map)));
                 Ok(vm.arena.alloc(array_val))
@@ -1,5 +1,7 @@
 use crate::core::value::{Handle, Val};
 use crate::vm::engine::VM;
+use rand::RngCore;
+use std::cell::RefCell;
 
 pub fn php_abs(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
@@ -8,7 +10,10 @@ pub fn php_abs(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 
     let val = vm.arena.get(args[0]);
     match &val.value {
-        Val::Int(i) => Ok(vm.arena.alloc(Val::Int(i.abs()))),
+        // abs(PHP_INT_MIN) does not fit in an int and becomes a float
+        Val::Int(i) => Ok(vm
+            .arena
+            .alloc(i.checked_abs().map_or(Val::Float(-(*i as f64)), Val::Int))),
         Val::Float(f) => Ok(vm.arena.alloc(Val::Float(f.abs()))),
         Val::String(s) => {
             // String coercion: only in weak mode
@@ -18,7 +23,9 @@ pub fn php_abs(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
             // Weak mode: try to parse as number
             let s_str = String::from_utf8_lossy(s);
             if let Ok(i) = s_str.parse::<i64>() {
-                Ok(vm.arena.alloc(Val::Int(i.abs())))
+                Ok(vm
+                    .arena
+                    .alloc(i.checked_abs().map_or(Val::Float(-(i as f64)), Val::Int)))
             } else if let Ok(f) = s_str.parse::<f64>() {
                 Ok(vm.arena.alloc(Val::Float(f.abs())))
             } else {
@@ -212,3 +219,196 @@ pub fn php_ceil(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 
     Ok(vm.arena.alloc(Val::Float(num.ceil())))
 }
+
+/// intdiv(int $num1, int $num2): int
+/// Integer division, truncating toward zero
+/// Reference: $PHP_SRC_PATH/ext/standard/math.c - PHP_FUNCTION(intdiv)
+pub fn php_intdiv(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() != 2 {
+        return Err("intdiv() expects exactly 2 parameters".into());
+    }
+
+    let num1 = vm.arena.get(args[0]).value.to_int();
+    let num2 = vm.arena.get(args[1]).value.to_int();
+
+    if num2 == 0 {
+        let ex = crate::builtins::exception::create_exception(
+            vm,
+            b"DivisionByZeroError",
+            "Division by zero",
+        );
+        return Err(vm.throw_exception(ex));
+    }
+    match num1.checked_div(num2) {
+        Some(quotient) => Ok(vm.arena.alloc(Val::Int(quotient))),
+        None => {
+            let ex = crate::builtins::exception::create_exception(
+                vm,
+                b"ArithmeticError",
+                "Division of PHP_INT_MIN by -1 is not an integer",
+            );
+            Err(vm.throw_exception(ex))
+        }
+    }
+}
+
+thread_local! {
+    /// Replacement for the OS CSPRNG installed with `set_secure_rng`, so tests
+    /// can make random_bytes(), random_int() and openssl_random_pseudo_bytes()
+    /// deterministic or failing
+    static SECURE_RNG: RefCell<Option<Box<dyn RngCore>>> = const { RefCell::new(None) };
+}
+
+/// Route `fill_secure_random` on this thread through `rng`, or back to the OS
+/// CSPRNG when `None`. Returns the previously installed source.
+pub fn set_secure_rng(rng: Option<Box<dyn RngCore>>) -> Option<Box<dyn RngCore>> {
+    SECURE_RNG.with(|current| current.replace(rng))
+}
+
+/// Fill `buf` from the operating system's CSPRNG, or from the source
+/// installed with `set_secure_rng`
+/// Reference: $PHP_SRC_PATH/ext/random/csprng.c - php_random_bytes
+pub fn fill_secure_random(buf: &mut [u8]) -> Result<(), String> {
+    SECURE_RNG
+        .with(|current| match current.borrow_mut().as_mut() {
+            Some(rng) => rng.try_fill_bytes(buf),
+            None => rand::rngs::OsRng.try_fill_bytes(buf),
+        })
+        .map_err(|e| format!("Could not gather sufficient random data: {}", e))
+}
+
+/// Why `secure_random_bytes` produced no bytes
+#[derive(Debug)]
+pub enum SecureRandomError {
+    /// The random source failed; callers turn this into an `Exception`
+    Source(String),
+    /// The buffer could not be allocated; fatal like any failed allocation
+    Memory(String),
+}
+
+/// Generate `length` bytes with `fill_secure_random`. Bytes are produced in
+/// fixed-size chunks and the buffer grows as they arrive, so memory is only
+/// taken for bytes actually generated and a length that cannot be allocated
+/// fails with an error instead of aborting the process.
+/// Reference: $PHP_SRC_PATH/ext/random/csprng.c - php_random_bytes
+pub fn secure_random_bytes(vm: &VM, length: usize) -> Result<Vec<u8>, SecureRandomError> {
+    const CHUNK_SIZE: usize = 8192;
+    // Size of the zend_string header PHP allocates in front of the bytes
+    const STRING_HEADER: usize = 32;
+
+    if length > isize::MAX as usize - STRING_HEADER {
+        return Err(SecureRandomError::Memory(format!(
+            "Possible integer overflow in memory allocation (1 * {} + {})",
+            length, STRING_HEADER
+        )));
+    }
+    if vm.memory_limit > 0 && length > vm.memory_limit {
+        return Err(SecureRandomError::Memory(format!(
+            "Allowed memory size of {} bytes exhausted (tried to allocate {} bytes)",
+            vm.memory_limit, length
+        )));
+    }
+
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; CHUNK_SIZE];
+    while buf.len() < length {
+        let take = (length - buf.len()).min(CHUNK_SIZE);
+        fill_secure_random(&mut chunk[..take]).map_err(SecureRandomError::Source)?;
+        if buf.try_reserve(take).is_err() {
+            return Err(SecureRandomError::Memory(format!(
+                "Out of memory (tried to allocate {} bytes)",
+                length
+            )));
+        }
+        buf.extend_from_slice(&chunk[..take]);
+    }
+    Ok(buf)
+}
+
+/// Throw the Exception random_bytes() and random_int() raise when the random
+/// source fails
+fn throw_random_source_error(vm: &mut VM) -> String {
+    let ex = crate::builtins::exception::create_exception(
+        vm,
+        b"Exception",
+        "Could not gather sufficient random data",
+    );
+    vm.throw_exception(ex)
+}
+
+/// random_bytes(int $length): string
+/// Get cryptographically secure random bytes
+/// Reference: $PHP_SRC_PATH/ext/random/random.c - PHP_FUNCTION(random_bytes)
+pub fn php_random_bytes(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() != 1 {
+        return Err("random_bytes() expects exactly 1 parameter".into());
+    }
+
+    let length = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i,
+        _ => return Err("random_bytes(): Argument #1 ($length) must be of type int".into()),
+    };
+    if length < 1 {
+        let ex = crate::builtins::exception::create_exception(
+            vm,
+            b"ValueError",
+            "random_bytes(): Argument #1 ($length) must be greater than 0",
+        );
+        return Err(vm.throw_exception(ex));
+    }
+
+    match secure_random_bytes(vm, length as usize) {
+        Ok(buf) => Ok(vm.arena.alloc(Val::String(buf.into()))),
+        Err(SecureRandomError::Source(_)) => Err(throw_random_source_error(vm)),
+        Err(SecureRandomError::Memory(message)) => Err(message),
+    }
+}
+
+/// random_int(int $min, int $max): int
+/// Get a cryptographically secure, uniformly selected integer
+/// Reference: $PHP_SRC_PATH/ext/random/csprng.c - php_random_int
+pub fn php_random_int(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() != 2 {
+        return Err("random_int() expects exactly 2 parameters".into());
+    }
+
+    let min = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i,
+        _ => return Err("random_int(): Argument #1 ($min) must be of type int".into()),
+    };
+    let max = match &vm.arena.get(args[1]).value {
+        Val::Int(i) => *i,
+        _ => return Err("random_int(): Argument #2 ($max) must be of type int".into()),
+    };
+    if min > max {
+        let ex = crate::builtins::exception::create_exception(
+            vm,
+            b"ValueError",
+            "random_int(): Argument #1 ($min) must be less than or equal to argument #2 ($max)",
+        );
+        return Err(vm.throw_exception(ex));
+    }
+
+    let umax = max.wrapping_sub(min) as u64;
+    let next = |vm: &mut VM| -> Result<u64, String> {
+        let mut buf = [0u8; 8];
+        fill_secure_random(&mut buf).map_err(|_| throw_random_source_error(vm))?;
+        Ok(u64::from_ne_bytes(buf))
+    };
+    let mut result = next(vm)?;
+    if umax == u64::MAX {
+        return Ok(vm.arena.alloc(Val::Int(result as i64)));
+    }
+    let range = umax + 1;
+    if range.is_power_of_two() {
+        result &= umax;
+    } else {
+        // Reject values from the incomplete last block to avoid modulo bias
+        let ceiling = u64::MAX - (u64::MAX % range) - 1;
+        while result > ceiling {
+            result = next(vm)?;
+        }
+        result %= range;
+    }
+    Ok(vm.arena.alloc(Val::Int(min.wrapping_add(result as i64))))
+}
@@ -16,7 +16,7 @@ pub fn php_mb_internal_encoding(vm: &mut VM, args: &[Handle]) -> Result<Handle,
         return Ok(vm.arena.alloc(Val::Null));
     }
 
-    if args.is_empty() {
+    if args.is_empty() || matches!(vm.arena.get(args[0]).value, Val::Null) {
         let state = vm
             .context
             .get_or_init_extension_data(MbStringState::default);
@@ -26,10 +26,17 @@ pub fn php_mb_internal_encoding(vm: &mut VM, args: &[Handle]) -> Result<Handle,
     }
 
     let enc = vm.check_builtin_param_string(args[0], 1, "mb_internal_encoding")?;
+    let name = String::from_utf8_lossy(&enc).to_string();
+    let Some(canonical) = crate::runtime::mb::encoding::canonical_label(&name) else {
+        return Err(format!(
+            "mb_internal_encoding(): Argument #1 ($encoding) must be a valid encoding, \"{}\" given",
+            name
+        ));
+    };
     let state = vm
         .context
         .get_or_init_extension_data(MbStringState::default);
-    state.internal_encoding = String::from_utf8_lossy(&enc).to_string();
+    state.internal_encoding = canonical.to_string();
 
     Ok(vm.arena.alloc(Val::Bool(true)))
 }
@@ -621,10 +628,11 @@ pub fn php_mb_strrpos(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 }
 
 fn resolve_encoding_arg(vm: &mut VM, handle: Option<&Handle>) -> String {
-    if let Some(handle) = handle {
-        if let Ok(enc) = vm.check_builtin_param_string(*handle, 1, "mbstring") {
-            return String::from_utf8_lossy(&enc).to_string();
-        }
+    if let Some(handle) = handle
+        && !matches!(vm.arena.get(*handle).value, Val::Null)
+        && let Ok(enc) = vm.check_builtin_param_string(*handle, 1, "mbstring")
+    {
+        return String::from_utf8_lossy(&enc).to_string();
     }
     let state = vm
         .context
@@ -1,5 +1,6 @@
 pub mod array;
 pub mod bcmath;
+pub mod bz2;
 pub mod class;
 pub mod datetime;
 pub mod exception;
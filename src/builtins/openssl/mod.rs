@@ -1,17 +1,30 @@
+use crate::builtins::filesystem::MemoryStream;
+use crate::builtins::math::{SecureRandomError, secure_random_bytes};
 use crate::core::value::{ArrayData, ArrayKey, Handle, ObjectData, Val};
-use crate::vm::engine::VM;
+use crate::vm::engine::{ErrorLevel, VM};
 use indexmap::IndexMap;
+use openssl::asn1::Asn1ObjectRef;
+use openssl::bn::{BigNum, BigNumContext};
 use openssl::cms::{CMSOptions, CmsContentInfo};
+use openssl::dh::Dh;
+use openssl::ec::EcGroup;
 use openssl::encrypt::{Decrypter, Encrypter};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
 use openssl::nid::Nid;
 use openssl::pkcs7::{Pkcs7, Pkcs7Flags};
-use openssl::pkey::{PKey, Private, Public};
-use openssl::sign::{Signer, Verifier};
-use openssl::symm::{Cipher, decrypt, encrypt};
-use openssl::x509::{X509, X509Req};
+use openssl::pkey::{HasParams, HasPublic, PKey, PKeyRef, Private, Public};
+use openssl::rsa::Padding;
+use openssl::sign::{RsaPssSaltlen, Signer, Verifier};
+use openssl::ssl::SslFiletype;
+use openssl::stack::StackRef;
+use openssl::symm::{Cipher, Crypter, Mode};
+use openssl::x509::store::{X509Lookup, X509StoreBuilder};
+use openssl::x509::{X509, X509NameRef, X509PurposeId, X509Req, X509StoreContext};
 use std::any::Any;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::rc::Rc;
+use std::sync::OnceLock;
 
 // X509 Purpose checking flags
 pub const X509_PURPOSE_SSL_CLIENT: i64 = 1;
@@ -27,6 +40,7 @@ pub const OPENSSL_PKCS1_PADDING: i64 = 1;
 pub const OPENSSL_SSLV23_PADDING: i64 = 2;
 pub const OPENSSL_NO_PADDING: i64 = 3;
 pub const OPENSSL_PKCS1_OAEP_PADDING: i64 = 4;
+pub const OPENSSL_PKCS1_PSS_PADDING: i64 = 6;
 
 // Key types
 pub const OPENSSL_KEYTYPE_RSA: i64 = 0;
@@ -85,8 +99,8 @@ pub const OPENSSL_CIPHER_AES_256_CBC: i64 = 7;
 
 // Other Constants
 pub const OPENSSL_RAW_DATA: i64 = 1;
-pub const OPENSSL_DONT_ZERO_PAD_KEY: i64 = 2;
-pub const OPENSSL_ZERO_PADDING: i64 = 3;
+pub const OPENSSL_ZERO_PADDING: i64 = 2;
+pub const OPENSSL_DONT_ZERO_PAD_KEY: i64 = 4;
 pub const OPENSSL_ENCODING_SMIME: i64 = 1;
 pub const OPENSSL_ENCODING_DER: i64 = 2;
 pub const OPENSSL_ENCODING_PEM: i64 = 3;
@@ -114,10 +128,12 @@ pub fn openssl_pkey_get_private(vm: &mut VM, args: &[Handle]) -> Result<Handle,
         None
     };
 
+    // Without a passphrase an encrypted key must fail rather than let
+    // OpenSSL prompt for one on the terminal
     let pkey = if let Some(pass) = passphrase {
         PKey::private_key_from_pem_passphrase(&key_data, &pass)
     } else {
-        PKey::private_key_from_pem(&key_data)
+        PKey::private_key_from_pem_callback(&key_data, |_| Ok(0))
     };
 
     match pkey {
@@ -131,7 +147,10 @@ pub fn openssl_pkey_get_private(vm: &mut VM, args: &[Handle]) -> Result<Handle,
             };
             Ok(vm.arena.alloc(Val::ObjPayload(obj)))
         }
-        Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            Ok(vm.arena.alloc(Val::Bool(false)))
+        }
     }
 }
 
@@ -194,30 +213,32 @@ pub fn openssl_public_encrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, St
         Err(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
-    let padding = if args.len() > 3 {
-        match &vm.arena.get(args[3]).value {
-            Val::Int(i) => *i,
-            _ => OPENSSL_PKCS1_PADDING,
-        }
-    } else {
-        OPENSSL_PKCS1_PADDING
+    let Some(options) = rsa_crypt_options(vm, args.get(3), args.get(4)) else {
+        vm.report_error(
+            ErrorLevel::Warning,
+            "openssl_public_encrypt(): Unknown padding type or digest",
+        );
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let mut encrypter = Encrypter::new(&pkey).map_err(|e| e.to_string())?;
-    let p = match padding {
-        OPENSSL_PKCS1_PADDING => openssl::rsa::Padding::PKCS1,
-        OPENSSL_NO_PADDING => openssl::rsa::Padding::NONE,
-        OPENSSL_PKCS1_OAEP_PADDING => openssl::rsa::Padding::PKCS1_OAEP,
-        _ => openssl::rsa::Padding::PKCS1,
+    let encrypted = Encrypter::new(&pkey).and_then(|mut encrypter| {
+        encrypter.set_rsa_padding(options.padding)?;
+        if options.padding == Padding::PKCS1_OAEP {
+            encrypter.set_rsa_oaep_md(options.oaep_md)?;
+            encrypter.set_rsa_mgf1_md(options.mgf1_md)?;
+        }
+        let mut encrypted = vec![0u8; encrypter.encrypt_len(data)?];
+        let encrypted_len = encrypter.encrypt(data, &mut encrypted)?;
+        encrypted.truncate(encrypted_len);
+        Ok(encrypted)
+    });
+    let encrypted = match encrypted {
+        Ok(encrypted) => encrypted,
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
     };
-    encrypter.set_rsa_padding(p).map_err(|e| e.to_string())?;
-
-    let buffer_len = encrypter.encrypt_len(data).map_err(|e| e.to_string())?;
-    let mut encrypted = vec![0u8; buffer_len];
-    let encrypted_len = encrypter
-        .encrypt(data, &mut encrypted)
-        .map_err(|e| e.to_string())?;
-    encrypted.truncate(encrypted_len);
 
     set_ref_value(vm, args[1], Val::String(Rc::new(encrypted)));
 
@@ -238,30 +259,32 @@ pub fn openssl_private_decrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, S
         Ok(pkey) => pkey,
         Err(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
-    let padding = if args.len() > 3 {
-        match &vm.arena.get(args[3]).value {
-            Val::Int(i) => *i,
-            _ => OPENSSL_PKCS1_PADDING,
-        }
-    } else {
-        OPENSSL_PKCS1_PADDING
+    let Some(options) = rsa_crypt_options(vm, args.get(3), args.get(4)) else {
+        vm.report_error(
+            ErrorLevel::Warning,
+            "openssl_private_decrypt(): Unknown padding type or digest",
+        );
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let mut decrypter = Decrypter::new(&pkey).map_err(|e| e.to_string())?;
-    let p = match padding {
-        OPENSSL_PKCS1_PADDING => openssl::rsa::Padding::PKCS1,
-        OPENSSL_NO_PADDING => openssl::rsa::Padding::NONE,
-        OPENSSL_PKCS1_OAEP_PADDING => openssl::rsa::Padding::PKCS1_OAEP,
-        _ => openssl::rsa::Padding::PKCS1,
+    let decrypted = Decrypter::new(&pkey).and_then(|mut decrypter| {
+        decrypter.set_rsa_padding(options.padding)?;
+        if options.padding == Padding::PKCS1_OAEP {
+            decrypter.set_rsa_oaep_md(options.oaep_md)?;
+            decrypter.set_rsa_mgf1_md(options.mgf1_md)?;
+        }
+        let mut decrypted = vec![0u8; decrypter.decrypt_len(data)?];
+        let decrypted_len = decrypter.decrypt(data, &mut decrypted)?;
+        decrypted.truncate(decrypted_len);
+        Ok(decrypted)
+    });
+    let decrypted = match decrypted {
+        Ok(decrypted) => decrypted,
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
     };
-    decrypter.set_rsa_padding(p).map_err(|e| e.to_string())?;
-
-    let buffer_len = decrypter.decrypt_len(data).map_err(|e| e.to_string())?;
-    let mut decrypted = vec![0u8; buffer_len];
-    let decrypted_len = decrypter
-        .decrypt(data, &mut decrypted)
-        .map_err(|e| e.to_string())?;
-    decrypted.truncate(decrypted_len);
 
     set_ref_value(vm, args[1], Val::String(Rc::new(decrypted)));
 
@@ -273,68 +296,127 @@ pub fn openssl_pkey_export(vm: &mut VM, args: &[Handle]) -> Result<Handle, Strin
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
-    let pkey = {
-        let val = &vm.arena.get(args[0]).value;
-        match val {
-            Val::ObjPayload(obj) => {
-                if let Some(internal) = &obj.internal {
-                    if let Some(pkey) = internal.downcast_ref::<PKey<Private>>() {
-                        pkey.clone()
-                    } else {
-                        return Ok(vm.arena.alloc(Val::Bool(false)));
-                    }
-                } else {
-                    return Ok(vm.arena.alloc(Val::Bool(false)));
-                }
-            }
-            _ => return Ok(vm.arena.alloc(Val::Bool(false))),
-        }
+    let Some(pem) = private_key_pem(vm, "openssl_pkey_export", args[0], &args[2..])? else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let passphrase = if args.len() > 2 {
-        match &vm.arena.get(args[2]).value {
-            Val::String(s) => Some(s.clone()),
-            _ => None,
-        }
-    } else {
-        None
+    set_ref_value(vm, args[1], Val::String(Rc::new(pem)));
+
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+/// PKCS#8 PEM for the key openssl_pkey_export() and
+/// openssl_pkey_export_to_file() write, given their trailing
+/// `$passphrase` and `$options` arguments. A passphrase encrypts the key
+/// with `$options['encrypt_key_cipher']` (AES-256-CBC by default). Returns
+/// `None` when the key is unusable or the cipher is unknown.
+fn private_key_pem(
+    vm: &mut VM,
+    func: &str,
+    key: Handle,
+    extra: &[Handle],
+) -> Result<Option<Vec<u8>>, String> {
+    let pkey = match &vm.arena.get(key).value {
+        Val::ObjPayload(obj) => match obj
+            .internal
+            .as_ref()
+            .and_then(|internal| internal.downcast_ref::<PKey<Private>>())
+        {
+            Some(pkey) => pkey.clone(),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let passphrase = match extra.first().map(|h| &vm.arena.get(*h).value) {
+        Some(Val::String(s)) => Some(s.clone()),
+        _ => None,
+    };
+
+    // $options['encrypt_key_cipher'] selects the PEM encryption cipher
+    let cipher_option = match extra.get(1).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Array(options)) => options
+            .map
+            .get(&ArrayKey::Str(Rc::new(b"encrypt_key_cipher".to_vec())))
+            .map(|h| vm.arena.get(*h).value.to_int()),
+        _ => None,
+    };
+    let cipher = match cipher_option {
+        None => Cipher::aes_256_cbc(),
+        Some(algo) => match cipher_from_constant(algo) {
+            Some(cipher) => cipher,
+            None => {
+                vm.report_error(
+                    ErrorLevel::Warning,
+                    &format!("{}(): Unknown cipher algorithm for private key", func),
+                );
+                return Ok(None);
+            }
+        },
     };
 
     let pem = if let Some(pass) = passphrase {
-        pkey.private_key_to_pem_pkcs8_passphrase(openssl::symm::Cipher::aes_256_cbc(), &pass)
-            .map_err(|e| e.to_string())?
+        pkey.private_key_to_pem_pkcs8_passphrase(cipher, &pass)
     } else {
-        pkey.private_key_to_pem_pkcs8().map_err(|e| e.to_string())?
+        pkey.private_key_to_pem_pkcs8()
     };
-
-    set_ref_value(vm, args[1], Val::String(Rc::new(pem)));
-
-    Ok(vm.arena.alloc(Val::Bool(true)))
+    pem.map(Some).map_err(|e| e.to_string())
 }
 
+/// openssl_random_pseudo_bytes(int $length, &$strong_result = null): string
+///
+/// Bytes come from `math::secure_random_bytes`, the same CSPRNG that backs
+/// random_bytes() and random_int(). `$strong_result` is reset to false on entry
+/// and set to true once the bytes have been generated.
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - PHP_FUNCTION(openssl_random_pseudo_bytes)
 pub fn openssl_random_pseudo_bytes(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
-        return Ok(vm.arena.alloc(Val::Bool(false)));
+        return Err("openssl_random_pseudo_bytes() expects at least 1 argument, 0 given".into());
+    }
+    if args.len() > 1 {
+        set_ref_value(vm, args[1], Val::Bool(false));
     }
     let length = match &vm.arena.get(args[0]).value {
-        Val::Int(l) if *l > 0 => *l as usize,
+        Val::Int(l) => *l,
         _ => {
-            if args.len() > 1 {
-                set_ref_value(vm, args[1], Val::Bool(false));
-            }
-            return Ok(vm.arena.alloc(Val::Bool(false)));
+            return Err(
+                "openssl_random_pseudo_bytes(): Argument #1 ($length) must be of type int".into(),
+            );
         }
     };
-    let mut buf = vec![0u8; length];
-    let success = openssl::rand::rand_bytes(&mut buf).is_ok();
-    if args.len() > 1 {
-        set_ref_value(vm, args[1], Val::Bool(success));
+    if length < 1 {
+        let ex = crate::builtins::exception::create_exception(
+            vm,
+            b"ValueError",
+            "openssl_random_pseudo_bytes(): Argument #1 ($length) must be greater than 0",
+        );
+        return Err(vm.throw_exception(ex));
     }
-    if success {
-        Ok(vm.arena.alloc(Val::String(Rc::new(buf))))
-    } else {
-        Ok(vm.arena.alloc(Val::Bool(false)))
+    if length > i32::MAX as i64 {
+        let ex = crate::builtins::exception::create_exception(
+            vm,
+            b"ValueError",
+            "openssl_random_pseudo_bytes(): Argument #1 ($length) must be less than or equal to 2147483647",
+        );
+        return Err(vm.throw_exception(ex));
+    }
+    let buf = match secure_random_bytes(vm, length as usize) {
+        Ok(buf) => buf,
+        Err(SecureRandomError::Source(_)) => {
+            let ex = crate::builtins::exception::create_exception(
+                vm,
+                b"Exception",
+                "Error reading from source device",
+            );
+            return Err(vm.throw_exception(ex));
+        }
+        Err(SecureRandomError::Memory(message)) => return Err(message),
+    };
+
+    if args.len() > 1 {
+        set_ref_value(vm, args[1], Val::Bool(true));
     }
+    Ok(vm.arena.alloc(Val::String(Rc::new(buf))))
 }
 
 pub fn openssl_cipher_iv_length(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
@@ -351,6 +433,10 @@ pub fn openssl_cipher_iv_length(vm: &mut VM, args: &[Handle]) -> Result<Handle,
             .arena
             .alloc(Val::Int(cipher.iv_len().unwrap_or(0) as i64)));
     }
+    vm.report_error(
+        ErrorLevel::Warning,
+        "openssl_cipher_iv_length(): Unknown cipher algorithm",
+    );
     Ok(vm.arena.alloc(Val::Bool(false)))
 }
 
@@ -363,13 +449,20 @@ pub fn openssl_cipher_key_length(vm: &mut VM, args: &[Handle]) -> Result<Handle,
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
+    // Variable-length ciphers such as RC4 report their default key length,
+    // as EVP_CIPHER_key_length() does for PHP
     if let Some(cipher) = map_cipher(cipher_name) {
         Ok(vm.arena.alloc(Val::Int(cipher.key_len() as i64)))
     } else {
+        vm.report_error(
+            ErrorLevel::Warning,
+            "openssl_cipher_key_length(): Unknown cipher algorithm",
+        );
         Ok(vm.arena.alloc(Val::Bool(false)))
     }
 }
 
+/// openssl_digest(string $data, string $digest_algo, bool $binary = false, int $length = 0): string|false
 pub fn openssl_digest(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() < 2 {
         return Ok(vm.arena.alloc(Val::Bool(false)));
@@ -385,8 +478,6 @@ pub fn openssl_digest(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
-    let _algo = String::from_utf8_lossy(algo_bytes);
-
     let binary = if args.len() > 2 {
         match &vm.arena.get(args[2]).value {
             Val::Bool(b) => *b,
@@ -396,10 +487,28 @@ pub fn openssl_digest(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         false
     };
 
+    // Output length in bytes, only honoured by the SHAKE (XOF) digests
+    let length = match args.get(3).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(len)) if *len > 0 => Some(*len as usize),
+        _ => None,
+    };
+
     if let Some(md) = map_digest(algo_bytes) {
-        let hash = openssl::hash::hash(md, data).map_err(|e| e.to_string())?;
+        let is_xof = md.type_() == Nid::SHAKE128 || md.type_() == Nid::SHAKE256;
+        let hash = match length {
+            Some(len) if is_xof => {
+                let mut hasher = openssl::hash::Hasher::new(md).map_err(|e| e.to_string())?;
+                hasher.update(data).map_err(|e| e.to_string())?;
+                let mut out = vec![0u8; len];
+                hasher.finish_xof(&mut out).map_err(|e| e.to_string())?;
+                out
+            }
+            _ => openssl::hash::hash(md, data)
+                .map_err(|e| e.to_string())?
+                .to_vec(),
+        };
         if binary {
-            Ok(vm.arena.alloc(Val::String(Rc::new(hash.to_vec()))))
+            Ok(vm.arena.alloc(Val::String(Rc::new(hash))))
         } else {
             let hex = hash
                 .iter()
@@ -419,17 +528,17 @@ pub fn openssl_encrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     }
 
     let data = match &vm.arena.get(args[0]).value {
-        Val::String(s) => s,
+        Val::String(s) => s.clone(),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
     let cipher_name = match &vm.arena.get(args[1]).value {
-        Val::String(s) => s,
+        Val::String(s) => s.clone(),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
     let passphrase = match &vm.arena.get(args[2]).value {
-        Val::String(s) => s,
+        Val::String(s) => s.clone(),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
@@ -444,19 +553,23 @@ pub fn openssl_encrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 
     let iv = if args.len() > 4 {
         match &vm.arena.get(args[4]).value {
-            Val::String(s) => s,
+            Val::String(s) => s.clone(),
             _ => return Ok(vm.arena.alloc(Val::Bool(false))),
         }
     } else {
-        &Rc::new(vec![])
+        Rc::new(vec![])
     };
 
-    if let Some(cipher) = map_cipher(cipher_name) {
+    if let Some(cipher) = map_cipher(&cipher_name) {
+        let iv = adjust_iv(vm, "openssl_encrypt", cipher, &iv);
+        if !zero_padding_len_ok(vm, "openssl_encrypt", cipher, data.len(), options) {
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
         // PHP's openssl_encrypt handles key derivation if passphrase is shorter than key length
         // For now, we assume passphrase is the key
-        let key = passphrase;
+        let key = &passphrase;
 
-        match encrypt(cipher, key, Some(iv), data) {
+        match symmetric_crypt(cipher, Mode::Encrypt, key, &iv, &data, options) {
             Ok(encrypted) => {
                 if (options & OPENSSL_RAW_DATA) != 0 {
                     Ok(vm.arena.alloc(Val::String(Rc::new(encrypted))))
@@ -466,7 +579,10 @@ pub fn openssl_encrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
                     Ok(vm.arena.alloc(Val::String(Rc::new(b64.into_bytes()))))
                 }
             }
-            Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
+            Err(e) => {
+                push_openssl_errors(vm, &e);
+                Ok(vm.arena.alloc(Val::Bool(false)))
+            }
         }
     } else {
         Ok(vm.arena.alloc(Val::Bool(false)))
@@ -479,17 +595,17 @@ pub fn openssl_decrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     }
 
     let data = match &vm.arena.get(args[0]).value {
-        Val::String(s) => s,
+        Val::String(s) => s.clone(),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
     let cipher_name = match &vm.arena.get(args[1]).value {
-        Val::String(s) => s,
+        Val::String(s) => s.clone(),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
     let passphrase = match &vm.arena.get(args[2]).value {
-        Val::String(s) => s,
+        Val::String(s) => s.clone(),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
@@ -504,11 +620,11 @@ pub fn openssl_decrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
 
     let iv = if args.len() > 4 {
         match &vm.arena.get(args[4]).value {
-            Val::String(s) => s,
+            Val::String(s) => s.clone(),
             _ => return Ok(vm.arena.alloc(Val::Bool(false))),
         }
     } else {
-        &Rc::new(vec![])
+        Rc::new(vec![])
     };
 
     let decoded_data = if (options & OPENSSL_RAW_DATA) != 0 {
@@ -521,18 +637,98 @@ pub fn openssl_decrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         }
     };
 
-    if let Some(cipher) = map_cipher(cipher_name) {
-        let key = passphrase;
+    if let Some(cipher) = map_cipher(&cipher_name) {
+        let iv = adjust_iv(vm, "openssl_decrypt", cipher, &iv);
+        if !zero_padding_len_ok(vm, "openssl_decrypt", cipher, decoded_data.len(), options) {
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+        let key = &passphrase;
 
-        match decrypt(cipher, key, Some(iv), &decoded_data) {
+        match symmetric_crypt(cipher, Mode::Decrypt, key, &iv, &decoded_data, options) {
             Ok(decrypted) => Ok(vm.arena.alloc(Val::String(Rc::new(decrypted)))),
-            Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
+            Err(e) => {
+                push_openssl_errors(vm, &e);
+                Ok(vm.arena.alloc(Val::Bool(false)))
+            }
         }
     } else {
         Ok(vm.arena.alloc(Val::Bool(false)))
     }
 }
 
+/// Padding and digests for openssl_public_encrypt()/openssl_private_decrypt()
+struct RsaCryptOptions {
+    padding: Padding,
+    oaep_md: MessageDigest,
+    mgf1_md: MessageDigest,
+}
+
+/// Resolve the $padding argument of the RSA encrypt/decrypt functions. It is
+/// either an OPENSSL_*_PADDING constant or an options array with "padding",
+/// "oaep_digest" and "mgf1_digest" keys; `digest_algo` (the fifth argument in
+/// PHP 8.5) also names the OAEP digest. Both digests default to SHA-1 and the
+/// MGF1 digest follows the OAEP one unless given. Returns None for an
+/// unsupported padding or digest.
+fn rsa_crypt_options(
+    vm: &VM,
+    padding: Option<&Handle>,
+    digest_algo: Option<&Handle>,
+) -> Option<RsaCryptOptions> {
+    let digest = |val: Option<&Val>| match val {
+        None | Some(Val::Null) => Some(None),
+        Some(Val::String(name)) => map_digest(name).map(Some),
+        Some(_) => None,
+    };
+
+    let (mode, mut oaep_md, mgf1_md) = match padding.map(|h| &vm.arena.get(*h).value) {
+        None | Some(Val::Null) => (OPENSSL_PKCS1_PADDING, None, None),
+        Some(Val::Int(mode)) => (*mode, None, None),
+        Some(Val::Array(options)) => {
+            let option = |key: &[u8]| {
+                options
+                    .map
+                    .get(&ArrayKey::Str(Rc::new(key.to_vec())))
+                    .map(|h| &vm.arena.get(*h).value)
+            };
+            (
+                option(b"padding").map_or(OPENSSL_PKCS1_PADDING, Val::to_int),
+                digest(option(b"oaep_digest"))?,
+                digest(option(b"mgf1_digest"))?,
+            )
+        }
+        Some(_) => return None,
+    };
+    if let Some(md) = digest(digest_algo.map(|h| &vm.arena.get(*h).value))? {
+        oaep_md = Some(md);
+    }
+
+    let padding = match mode {
+        OPENSSL_PKCS1_PADDING => Padding::PKCS1,
+        OPENSSL_NO_PADDING => Padding::NONE,
+        OPENSSL_PKCS1_OAEP_PADDING => Padding::PKCS1_OAEP,
+        _ => return None,
+    };
+    let oaep_md = oaep_md.unwrap_or_else(MessageDigest::sha1);
+    Some(RsaCryptOptions {
+        padding,
+        oaep_md,
+        mgf1_md: mgf1_md.unwrap_or(oaep_md),
+    })
+}
+
+/// Resolve the $padding argument of openssl_private_encrypt() and
+/// openssl_public_decrypt(), warning and returning None for OAEP (which only
+/// applies to public key encryption) or an unknown padding
+fn rsa_private_key_padding(vm: &mut VM, func: &str, padding: Option<&Handle>) -> Option<Padding> {
+    let message = match rsa_crypt_options(vm, padding, None) {
+        Some(options) if options.padding != Padding::PKCS1_OAEP => return Some(options.padding),
+        Some(_) => "OAEP padding can only be used for public key encryption",
+        None => "Unknown padding type",
+    };
+    vm.report_error(ErrorLevel::Warning, &format!("{}(): {}", func, message));
+    None
+}
+
 pub fn openssl_private_encrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() < 3 {
         return Ok(vm.arena.alloc(Val::Bool(false)));
@@ -544,17 +740,8 @@ pub fn openssl_private_encrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, S
     };
 
     let pkey = get_pkey(vm, args[2])?;
-    let padding = if args.len() > 3 {
-        match &vm.arena.get(args[3]).value {
-            Val::Int(i) => match *i {
-                OPENSSL_PKCS1_PADDING => openssl::rsa::Padding::PKCS1,
-                OPENSSL_NO_PADDING => openssl::rsa::Padding::NONE,
-                _ => openssl::rsa::Padding::PKCS1,
-            },
-            _ => openssl::rsa::Padding::PKCS1,
-        }
-    } else {
-        openssl::rsa::Padding::PKCS1
+    let Some(padding) = rsa_private_key_padding(vm, "openssl_private_encrypt", args.get(3)) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
     let rsa = pkey.rsa().map_err(|e| e.to_string())?;
@@ -578,17 +765,8 @@ pub fn openssl_public_decrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, St
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
-    let padding = if args.len() > 3 {
-        match &vm.arena.get(args[3]).value {
-            Val::Int(i) => match *i {
-                OPENSSL_PKCS1_PADDING => openssl::rsa::Padding::PKCS1,
-                OPENSSL_NO_PADDING => openssl::rsa::Padding::NONE,
-                _ => openssl::rsa::Padding::PKCS1,
-            },
-            _ => openssl::rsa::Padding::PKCS1,
-        }
-    } else {
-        openssl::rsa::Padding::PKCS1
+    let Some(padding) = rsa_private_key_padding(vm, "openssl_public_decrypt", args.get(3)) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
     let pkey = match get_public_key(vm, args[2]) {
@@ -658,22 +836,151 @@ pub fn openssl_pkey_derive(vm: &mut VM, args: &[Handle]) -> Result<Handle, Strin
     Ok(vm.arena.alloc(Val::String(Rc::new(secret))))
 }
 
+/// openssl_dh_compute_key($public_key, $private_key) - Shared secret from a peer's DH public value
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - PHP_FUNCTION(openssl_dh_compute_key)
+pub fn openssl_dh_compute_key(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    let peer_public = match &vm.arena.get(args[0]).value {
+        Val::String(s) => s.clone(),
+        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    };
+    let pkey = get_pkey(vm, args[1])?;
+    if !matches!(pkey.id(), openssl::pkey::Id::DH | openssl::pkey::Id::DHX) {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    // The peer value is a bare big-endian integer, so build a public key
+    // over the local key's group parameters
+    let derive = || -> Result<Vec<u8>, ErrorStack> {
+        let dh = pkey.dh()?;
+        let q = dh.prime_q().map(|q| q.to_owned()).transpose()?;
+        let params = Dh::from_pqg(dh.prime_p().to_owned()?, q, dh.generator().to_owned()?)?;
+        let peer = PKey::from_dh(params.set_public_key(BigNum::from_slice(&peer_public)?)?)?;
+        let mut deriver = openssl::derive::Deriver::new(&pkey)?;
+        deriver.set_peer(&peer)?;
+        deriver.derive_to_vec()
+    };
+
+    match derive() {
+        Ok(secret) => Ok(vm.arena.alloc(Val::String(Rc::new(secret)))),
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            Ok(vm.arena.alloc(Val::Bool(false)))
+        }
+    }
+}
+
+/// Named big-endian key components, as exposed in the `rsa`/`ec`/`dsa`/`dh`
+/// sub-arrays of openssl_pkey_get_details().
+type KeyComponents = Vec<(&'static [u8], Vec<u8>)>;
+
+/// Collect the public components of a key.
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - php_openssl_pkey_get_details
+fn public_key_components<T: HasPublic + HasParams>(
+    pkey: &PKeyRef<T>,
+) -> Result<Option<(&'static [u8], KeyComponents)>, ErrorStack> {
+    let mut parts: KeyComponents = Vec::new();
+    let section: &'static [u8] = match pkey.id() {
+        openssl::pkey::Id::RSA => {
+            let rsa = pkey.rsa()?;
+            parts.push((b"n", rsa.n().to_vec()));
+            parts.push((b"e", rsa.e().to_vec()));
+            b"rsa"
+        }
+        openssl::pkey::Id::DSA => {
+            let dsa = pkey.dsa()?;
+            parts.push((b"p", dsa.p().to_vec()));
+            parts.push((b"q", dsa.q().to_vec()));
+            parts.push((b"g", dsa.g().to_vec()));
+            parts.push((b"pub_key", dsa.pub_key().to_vec()));
+            b"dsa"
+        }
+        openssl::pkey::Id::DH => {
+            let dh = pkey.dh()?;
+            parts.push((b"p", dh.prime_p().to_vec()));
+            parts.push((b"g", dh.generator().to_vec()));
+            parts.push((b"pub_key", dh.public_key().to_vec()));
+            b"dh"
+        }
+        openssl::pkey::Id::EC => {
+            let ec = pkey.ec_key()?;
+            let group = ec.group();
+            if let Some(name) = group.curve_name().and_then(|nid| nid.short_name().ok()) {
+                parts.push((b"curve_name", name.as_bytes().to_vec()));
+            }
+            let mut ctx = BigNumContext::new()?;
+            let mut x = BigNum::new()?;
+            let mut y = BigNum::new()?;
+            ec.public_key()
+                .affine_coordinates(group, &mut x, &mut y, &mut ctx)?;
+            parts.push((b"x", x.to_vec()));
+            parts.push((b"y", y.to_vec()));
+            b"ec"
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some((section, parts)))
+}
+
+/// Collect the public and private components of a private key.
+fn private_key_components(
+    pkey: &PKey<Private>,
+) -> Result<Option<(&'static [u8], KeyComponents)>, ErrorStack> {
+    let Some((section, mut parts)) = public_key_components(pkey)? else {
+        return Ok(None);
+    };
+    match pkey.id() {
+        openssl::pkey::Id::RSA => {
+            let rsa = pkey.rsa()?;
+            parts.push((b"d", rsa.d().to_vec()));
+            let optional = [
+                (b"p" as &'static [u8], rsa.p()),
+                (b"q", rsa.q()),
+                (b"dmp1", rsa.dmp1()),
+                (b"dmq1", rsa.dmq1()),
+                (b"iqmp", rsa.iqmp()),
+            ];
+            for (name, value) in optional {
+                if let Some(value) = value {
+                    parts.push((name, value.to_vec()));
+                }
+            }
+        }
+        openssl::pkey::Id::DSA => {
+            parts.push((b"priv_key", pkey.dsa()?.priv_key().to_vec()));
+        }
+        openssl::pkey::Id::DH => {
+            parts.push((b"priv_key", pkey.dh()?.private_key().to_vec()));
+        }
+        openssl::pkey::Id::EC => {
+            parts.push((b"d", pkey.ec_key()?.private_key().to_vec()));
+        }
+        _ => {}
+    }
+    Ok(Some((section, parts)))
+}
+
 pub fn openssl_pkey_get_details(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
-    let (bits, id, public_pem) = {
+    let (bits, id, public_pem, components) = {
         let val = &vm.arena.get(args[0]).value;
         match val {
             Val::ObjPayload(obj) => {
                 if let Some(internal) = &obj.internal {
                     if let Some(pkey) = internal.downcast_ref::<PKey<Private>>() {
                         let pem = pkey.public_key_to_pem().map_err(|e| e.to_string())?;
-                        (pkey.bits() as i64, pkey.id(), pem)
+                        let components = private_key_components(pkey).map_err(|e| e.to_string())?;
+                        (pkey.bits() as i64, pkey.id(), pem, components)
                     } else if let Some(pkey) = internal.downcast_ref::<PKey<Public>>() {
                         let pem = pkey.public_key_to_pem().map_err(|e| e.to_string())?;
-                        (pkey.bits() as i64, pkey.id(), pem)
+                        let components = public_key_components(pkey).map_err(|e| e.to_string())?;
+                        (pkey.bits() as i64, pkey.id(), pem, components)
                     } else {
                         return Ok(vm.arena.alloc(Val::Bool(false)));
                     }
@@ -697,12 +1004,22 @@ pub fn openssl_pkey_get_details(vm: &mut VM, args: &[Handle]) -> Result<Handle,
     let bits_val = vm.arena.alloc(Val::Int(bits));
     details.insert(ArrayKey::Str(Rc::new(b"bits".to_vec())), bits_val);
 
-    let type_val = vm.arena.alloc(Val::Int(key_type));
-    details.insert(ArrayKey::Str(Rc::new(b"type".to_vec())), type_val);
-
     let key_val = vm.arena.alloc(Val::String(Rc::new(public_pem)));
     details.insert(ArrayKey::Str(Rc::new(b"key".to_vec())), key_val);
 
+    if let Some((section, parts)) = components {
+        let mut sub = ArrayData::new();
+        for (name, value) in parts {
+            let value_handle = vm.arena.alloc(Val::String(Rc::new(value)));
+            sub.insert(ArrayKey::Str(Rc::new(name.to_vec())), value_handle);
+        }
+        let sub_handle = vm.arena.alloc(Val::Array(Rc::new(sub)));
+        details.insert(ArrayKey::Str(Rc::new(section.to_vec())), sub_handle);
+    }
+
+    let type_val = vm.arena.alloc(Val::Int(key_type));
+    details.insert(ArrayKey::Str(Rc::new(b"type".to_vec())), type_val);
+
     Ok(vm.arena.alloc(Val::Array(Rc::new(details))))
 }
 
@@ -739,18 +1056,31 @@ pub fn openssl_x509_read(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
     }
 }
 
+/// Whether the `$no_text` argument of the x509/CSR export functions (default
+/// true) asks for the human-readable dump ahead of the PEM block
+fn export_with_text(vm: &VM, no_text: Option<&Handle>) -> bool {
+    no_text.is_some_and(|h| !vm.arena.get(*h).value.to_bool())
+}
+
 pub fn openssl_x509_export(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() < 2 {
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
+    let with_text = export_with_text(vm, args.get(2));
     let pem = {
         let val = &vm.arena.get(args[0]).value;
         match val {
             Val::ObjPayload(obj) => {
                 if let Some(internal) = &obj.internal {
                     if let Some(cert) = internal.downcast_ref::<X509>() {
-                        cert.to_pem().map_err(|e| e.to_string())?
+                        let mut out = if with_text {
+                            cert.to_text().map_err(|e| e.to_string())?
+                        } else {
+                            Vec::new()
+                        };
+                        out.extend(cert.to_pem().map_err(|e| e.to_string())?);
+                        out
                     } else {
                         return Ok(vm.arena.alloc(Val::Bool(false)));
                     }
@@ -822,6 +1152,55 @@ pub fn openssl_x509_fingerprint(vm: &mut VM, args: &[Handle]) -> Result<Handle,
     }
 }
 
+/// Key for a distinguished name component: its short name (CN, O, OU, ...) or
+/// long name (commonName, ...), falling back to the dotted OID for unknown objects
+fn name_entry_key(object: &Asn1ObjectRef, short_names: bool) -> String {
+    let nid = object.nid();
+    let name = if short_names {
+        nid.short_name()
+    } else {
+        nid.long_name()
+    };
+    match name {
+        Ok(name) if nid != Nid::UNDEF => name.to_string(),
+        _ => object.to_string(),
+    }
+}
+
+/// Convert an X509 name to PHP's array form. A component that appears more
+/// than once maps to a list of its values.
+fn name_to_array(vm: &mut VM, name: &X509NameRef, short_names: bool) -> Handle {
+    let mut array = ArrayData::new();
+    for entry in name.entries() {
+        let key = ArrayKey::Str(Rc::new(
+            name_entry_key(entry.object(), short_names).into_bytes(),
+        ));
+        let val_handle = vm
+            .arena
+            .alloc(Val::String(Rc::new(entry.data().as_slice().to_vec())));
+        let Some(&existing) = array.map.get(&key) else {
+            array.insert(key, val_handle);
+            continue;
+        };
+        let list = match &vm.arena.get(existing).value {
+            Val::Array(list) => {
+                let mut list = (**list).clone();
+                list.push(val_handle);
+                list
+            }
+            _ => {
+                let mut list = ArrayData::new();
+                list.push(existing);
+                list.push(val_handle);
+                list
+            }
+        };
+        let list_handle = vm.arena.alloc(Val::Array(Rc::new(list)));
+        array.insert(key, list_handle);
+    }
+    vm.arena.alloc(Val::Array(Rc::new(array)))
+}
+
 pub fn openssl_x509_parse(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
         return Ok(vm.arena.alloc(Val::Bool(false)));
@@ -852,34 +1231,19 @@ pub fn openssl_x509_parse(vm: &mut VM, args: &[Handle]) -> Result<Handle, String
     };
 
     let cert = cert_rc.downcast_ref::<X509>().unwrap();
+    let short_names = args.get(1).is_none_or(|&h| vm.arena.get(h).value.to_bool());
 
     let mut array = ArrayData::new();
 
     // subject
-    let subject = cert.subject_name();
-    let mut subject_arr = ArrayData::new();
-    for entry in subject.entries() {
-        let key = entry.object().to_string();
-        let val = entry.data().as_slice();
-        let val_handle = vm.arena.alloc(Val::String(Rc::new(val.to_vec())));
-        subject_arr.insert(ArrayKey::Str(Rc::new(key.into_bytes())), val_handle);
-    }
-    let subject_arr_handle = vm.arena.alloc(Val::Array(Rc::new(subject_arr)));
+    let subject_arr_handle = name_to_array(vm, cert.subject_name(), short_names);
     array.insert(
         ArrayKey::Str(Rc::new(b"subject".to_vec())),
         subject_arr_handle,
     );
 
     // issuer
-    let issuer = cert.issuer_name();
-    let mut issuer_arr = ArrayData::new();
-    for entry in issuer.entries() {
-        let key = entry.object().to_string();
-        let val = entry.data().as_slice();
-        let val_handle = vm.arena.alloc(Val::String(Rc::new(val.to_vec())));
-        issuer_arr.insert(ArrayKey::Str(Rc::new(key.into_bytes())), val_handle);
-    }
-    let issuer_arr_handle = vm.arena.alloc(Val::Array(Rc::new(issuer_arr)));
+    let issuer_arr_handle = name_to_array(vm, cert.issuer_name(), short_names);
     array.insert(
         ArrayKey::Str(Rc::new(b"issuer".to_vec())),
         issuer_arr_handle,
@@ -919,6 +1283,55 @@ pub fn openssl_x509_parse(vm: &mut VM, args: &[Handle]) -> Result<Handle, String
     let valid_to_handle = vm.arena.alloc(Val::String(Rc::new(valid_to.into_bytes())));
     array.insert(ArrayKey::Str(Rc::new(b"validTo".to_vec())), valid_to_handle);
 
+    // signatureTypeSN / signatureTypeLN / signatureTypeNID
+    let sig_nid = cert.signature_algorithm().object().nid();
+    let sig_sn = sig_nid.short_name().unwrap_or("UNDEF");
+    let sig_ln = sig_nid.long_name().unwrap_or("undefined");
+    let sig_sn_handle = vm
+        .arena
+        .alloc(Val::String(Rc::new(sig_sn.as_bytes().to_vec())));
+    array.insert(
+        ArrayKey::Str(Rc::new(b"signatureTypeSN".to_vec())),
+        sig_sn_handle,
+    );
+    let sig_ln_handle = vm
+        .arena
+        .alloc(Val::String(Rc::new(sig_ln.as_bytes().to_vec())));
+    array.insert(
+        ArrayKey::Str(Rc::new(b"signatureTypeLN".to_vec())),
+        sig_ln_handle,
+    );
+    let sig_nid_handle = vm.arena.alloc(Val::Int(sig_nid.as_raw() as i64));
+    array.insert(
+        ArrayKey::Str(Rc::new(b"signatureTypeNID".to_vec())),
+        sig_nid_handle,
+    );
+
+    // extensions: key identifiers, printed the way OpenSSL 3 prints them
+    let mut extensions = ArrayData::new();
+    let key_ids = [
+        (&b"subjectKeyIdentifier"[..], cert.subject_key_id()),
+        (&b"authorityKeyIdentifier"[..], cert.authority_key_id()),
+    ];
+    for (name, key_id) in key_ids {
+        let Some(key_id) = key_id else {
+            continue;
+        };
+        let hex = key_id
+            .as_slice()
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(":");
+        let hex_handle = vm.arena.alloc(Val::String(Rc::new(hex.into_bytes())));
+        extensions.insert(ArrayKey::Str(Rc::new(name.to_vec())), hex_handle);
+    }
+    let extensions_handle = vm.arena.alloc(Val::Array(Rc::new(extensions)));
+    array.insert(
+        ArrayKey::Str(Rc::new(b"extensions".to_vec())),
+        extensions_handle,
+    );
+
     Ok(vm.arena.alloc(Val::Array(Rc::new(array))))
 }
 
@@ -1049,13 +1462,20 @@ pub fn openssl_csr_export(vm: &mut VM, args: &[Handle]) -> Result<Handle, String
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
+    let with_text = export_with_text(vm, args.get(2));
     let pem = {
         let val = &vm.arena.get(args[0]).value;
         match val {
             Val::ObjPayload(obj) => {
                 if let Some(internal) = &obj.internal {
                     if let Some(csr) = internal.downcast_ref::<X509Req>() {
-                        csr.to_pem().map_err(|e| e.to_string())?
+                        let mut out = if with_text {
+                            csr.to_text().map_err(|e| e.to_string())?
+                        } else {
+                            Vec::new()
+                        };
+                        out.extend(csr.to_pem().map_err(|e| e.to_string())?);
+                        out
                     } else {
                         return Ok(vm.arena.alloc(Val::Bool(false)));
                     }
@@ -1102,17 +1522,9 @@ pub fn openssl_csr_get_subject(vm: &mut VM, args: &[Handle]) -> Result<Handle, S
     };
 
     let csr = csr_rc.downcast_ref::<X509Req>().unwrap();
+    let short_names = args.get(1).is_none_or(|&h| vm.arena.get(h).value.to_bool());
 
-    let mut array = ArrayData::new();
-    let subject = csr.subject_name();
-    for entry in subject.entries() {
-        let key = entry.object().to_string();
-        let val = entry.data().as_slice();
-        let val_handle = vm.arena.alloc(Val::String(Rc::new(val.to_vec())));
-        array.insert(ArrayKey::Str(Rc::new(key.into_bytes())), val_handle);
-    }
-
-    Ok(vm.arena.alloc(Val::Array(Rc::new(array))))
+    Ok(name_to_array(vm, csr.subject_name(), short_names))
 }
 
 pub fn openssl_csr_sign(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
@@ -1135,15 +1547,21 @@ pub fn openssl_csr_sign(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
     let mut x509_builder = openssl::x509::X509::builder().map_err(|e| e.to_string())?;
     x509_builder.set_version(2).map_err(|e| e.to_string())?;
 
-    let serial = if args.len() > 5 {
-        match &vm.arena.get(args[5]).value {
-            Val::Int(i) => *i,
-            _ => 0,
-        }
+    // openssl_csr_sign($csr, $ca_certificate, $private_key, $days, $options, $serial, $serial_hex)
+    let serial_hex = match args.get(6).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::String(s)) => Some(String::from_utf8_lossy(s).to_string()),
+        _ => None,
+    };
+    let serial_bn = if let Some(hex) = serial_hex {
+        BigNum::from_hex_str(&hex).map_err(|e| e.to_string())?
     } else {
-        0
+        let serial = match args.get(5).map(|h| &vm.arena.get(*h).value) {
+            Some(Val::Int(i)) => i.to_string(),
+            Some(Val::String(s)) => String::from_utf8_lossy(s).trim().to_string(),
+            _ => "0".to_string(),
+        };
+        BigNum::from_dec_str(&serial).map_err(|e| e.to_string())?
     };
-    let serial_bn = openssl::bn::BigNum::from_u32(serial as u32).map_err(|e| e.to_string())?;
     let serial_asn1 = openssl::asn1::Asn1Integer::from_bn(&serial_bn).map_err(|e| e.to_string())?;
     x509_builder
         .set_serial_number(&serial_asn1)
@@ -1234,64 +1652,267 @@ pub fn openssl_csr_get_public_key(vm: &mut VM, args: &[Handle]) -> Result<Handle
     Ok(vm.arena.alloc(Val::ObjPayload(obj)))
 }
 
-fn map_cipher(name: &[u8]) -> Option<Cipher> {
-    let name_str = std::str::from_utf8(name).ok()?.to_lowercase();
-    match name_str.as_str() {
-        "aes-128-cbc" => Some(Cipher::aes_128_cbc()),
-        "aes-128-ecb" => Some(Cipher::aes_128_ecb()),
-        "aes-128-cfb" | "aes-128-cfb128" => Some(Cipher::aes_128_cfb128()),
-        "aes-128-cfb1" => Some(Cipher::aes_128_cfb1()),
-        "aes-128-cfb8" => Some(Cipher::aes_128_cfb8()),
-        "aes-128-ctr" => Some(Cipher::aes_128_ctr()),
-        "aes-128-ofb" => Some(Cipher::aes_128_ofb()),
-        "aes-128-gcm" => Some(Cipher::aes_128_gcm()),
-        "aes-192-cbc" => Some(Cipher::aes_192_cbc()),
-        "aes-192-ecb" => Some(Cipher::aes_192_ecb()),
-        "aes-192-cfb" | "aes-192-cfb128" => Some(Cipher::aes_192_cfb128()),
-        "aes-192-ctr" => Some(Cipher::aes_192_ctr()),
-        "aes-192-ofb" => Some(Cipher::aes_192_ofb()),
-        "aes-192-gcm" => Some(Cipher::aes_192_gcm()),
-        "aes-256-cbc" => Some(Cipher::aes_256_cbc()),
-        "aes-256-ecb" => Some(Cipher::aes_256_ecb()),
-        "aes-256-cfb" | "aes-256-cfb128" => Some(Cipher::aes_256_cfb128()),
-        "aes-256-ctr" => Some(Cipher::aes_256_ctr()),
-        "aes-256-ofb" => Some(Cipher::aes_256_ofb()),
-        "aes-256-gcm" => Some(Cipher::aes_256_gcm()),
-        "aes-128-xts" => Some(Cipher::aes_128_xts()),
-        "aes-256-xts" => Some(Cipher::aes_256_xts()),
-        "des-cbc" => Some(Cipher::des_cbc()),
-        "des-ecb" => Some(Cipher::des_ecb()),
-        "des-ede3-cbc" => Some(Cipher::des_ede3_cbc()),
-        "des-ede3-ecb" => Some(Cipher::des_ede3_ecb()),
-        "bf-cbc" => Some(Cipher::bf_cbc()),
-        "bf-ecb" => Some(Cipher::bf_ecb()),
-        "bf-cfb" => Some(Cipher::bf_cfb64()),
-        "bf-ofb" => Some(Cipher::bf_ofb()),
-        "cast5-cbc" => Some(Cipher::cast5_cbc()),
-        "cast5-ecb" => Some(Cipher::cast5_ecb()),
-        "cast5-cfb" => Some(Cipher::cast5_cfb64()),
-        "cast5-ofb" => Some(Cipher::cast5_ofb()),
-        "idea-cbc" => Cipher::from_nid(Nid::IDEA_CBC),
-        "idea-ecb" => Cipher::from_nid(Nid::IDEA_ECB),
-        "idea-cfb" => Cipher::from_nid(Nid::IDEA_CFB64),
-        "idea-ofb" => Cipher::from_nid(Nid::IDEA_OFB64),
-        "rc2-cbc" => Some(Cipher::rc2_cbc()),
-        "rc4" => Some(Cipher::rc4()),
+/// Run a symmetric cipher over `data`. OPENSSL_ZERO_PADDING turns off PKCS#7
+/// padding, so the input must then be a whole number of blocks.
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - php_openssl_cipher_init
+/// Zero-pad or truncate `iv` to the length the cipher expects, warning the
+/// way PHP does when the caller's IV has the wrong size
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - php_openssl_validate_iv
+fn adjust_iv(vm: &mut VM, func: &str, cipher: Cipher, iv: &[u8]) -> Vec<u8> {
+    let expected = match cipher.iv_len() {
+        Some(len) if len != iv.len() => len,
+        _ => return iv.to_vec(),
+    };
+
+    let message = if iv.is_empty() {
+        format!(
+            "{}(): Using an empty Initialization Vector (iv) is potentially insecure and not recommended",
+            func
+        )
+    } else if iv.len() < expected {
+        format!(
+            "{}(): IV passed is only {} bytes long, cipher expects an IV of precisely {} bytes, padding with \\0",
+            func,
+            iv.len(),
+            expected
+        )
+    } else {
+        format!(
+            "{}(): IV passed is {} bytes long which is longer than the {} expected by selected cipher, truncating",
+            func,
+            iv.len(),
+            expected
+        )
+    };
+    vm.report_error(ErrorLevel::Warning, &message);
+
+    let mut adjusted = iv[..iv.len().min(expected)].to_vec();
+    adjusted.resize(expected, 0);
+    adjusted
+}
+
+/// With OPENSSL_ZERO_PADDING the data is not padded, so for block ciphers it
+/// has to fill whole blocks; warn and report false otherwise
+fn zero_padding_len_ok(vm: &mut VM, func: &str, cipher: Cipher, len: usize, options: i64) -> bool {
+    let block_size = cipher.block_size();
+    if (options & OPENSSL_ZERO_PADDING) == 0 || block_size <= 1 || len.is_multiple_of(block_size) {
+        return true;
+    }
+    vm.report_error(
+        ErrorLevel::Warning,
+        &format!(
+            "{}(): Data length {} is not a multiple of the {} byte block size, which OPENSSL_ZERO_PADDING requires",
+            func, len, block_size
+        ),
+    );
+    false
+}
+
+fn symmetric_crypt(
+    cipher: Cipher,
+    mode: Mode,
+    key: &[u8],
+    iv: &[u8],
+    data: &[u8],
+    options: i64,
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut crypter = Crypter::new(cipher, mode, key, Some(iv))?;
+    crypter.pad((options & OPENSSL_ZERO_PADDING) == 0);
+    let mut out = vec![0; data.len() + cipher.block_size()];
+    let mut count = crypter.update(data, &mut out)?;
+    count += crypter.finalize(&mut out[count..])?;
+    out.truncate(count);
+    Ok(out)
+}
+
+/// Map an OPENSSL_CIPHER_* constant to its cipher
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - php_openssl_get_evp_cipher_from_algo
+fn cipher_from_constant(algo: i64) -> Option<Cipher> {
+    match algo {
+        OPENSSL_CIPHER_RC2_40 => Some(Cipher::rc2_40_cbc()),
+        OPENSSL_CIPHER_RC2_128 => Some(Cipher::rc2_cbc()),
+        OPENSSL_CIPHER_RC2_64 => Cipher::from_nid(Nid::RC2_64_CBC),
+        OPENSSL_CIPHER_DES => Some(Cipher::des_cbc()),
+        OPENSSL_CIPHER_3DES => Some(Cipher::des_ede3_cbc()),
+        OPENSSL_CIPHER_AES_128_CBC => Some(Cipher::aes_128_cbc()),
+        OPENSSL_CIPHER_AES_192_CBC => Some(Cipher::aes_192_cbc()),
+        OPENSSL_CIPHER_AES_256_CBC => Some(Cipher::aes_256_cbc()),
         _ => None,
     }
 }
 
-fn map_digest(name: &[u8]) -> Option<openssl::hash::MessageDigest> {
+type CipherCtor = fn() -> Option<Cipher>;
+
+/// Ciphers resolvable by map_cipher(), keyed by lowercase name and including
+/// OpenSSL's short aliases such as `aes128` or `des3`
+const CIPHERS: &[(&str, CipherCtor)] = &[
+    ("aes-128-cbc", || Some(Cipher::aes_128_cbc())),
+    ("aes128", || Some(Cipher::aes_128_cbc())),
+    ("aes-128-ecb", || Some(Cipher::aes_128_ecb())),
+    ("aes-128-cfb", || Some(Cipher::aes_128_cfb128())),
+    ("aes-128-cfb128", || Some(Cipher::aes_128_cfb128())),
+    ("aes-128-cfb1", || Some(Cipher::aes_128_cfb1())),
+    ("aes-128-cfb8", || Some(Cipher::aes_128_cfb8())),
+    ("aes-128-ctr", || Some(Cipher::aes_128_ctr())),
+    ("aes-128-ofb", || Some(Cipher::aes_128_ofb())),
+    ("aes-128-gcm", || Some(Cipher::aes_128_gcm())),
+    ("aes-192-cbc", || Some(Cipher::aes_192_cbc())),
+    ("aes192", || Some(Cipher::aes_192_cbc())),
+    ("aes-192-ecb", || Some(Cipher::aes_192_ecb())),
+    ("aes-192-cfb", || Some(Cipher::aes_192_cfb128())),
+    ("aes-192-cfb128", || Some(Cipher::aes_192_cfb128())),
+    ("aes-192-ctr", || Some(Cipher::aes_192_ctr())),
+    ("aes-192-ofb", || Some(Cipher::aes_192_ofb())),
+    ("aes-192-gcm", || Some(Cipher::aes_192_gcm())),
+    ("aes-256-cbc", || Some(Cipher::aes_256_cbc())),
+    ("aes256", || Some(Cipher::aes_256_cbc())),
+    ("aes-256-ecb", || Some(Cipher::aes_256_ecb())),
+    ("aes-256-cfb", || Some(Cipher::aes_256_cfb128())),
+    ("aes-256-cfb128", || Some(Cipher::aes_256_cfb128())),
+    ("aes-256-ctr", || Some(Cipher::aes_256_ctr())),
+    ("aes-256-ofb", || Some(Cipher::aes_256_ofb())),
+    ("aes-256-gcm", || Some(Cipher::aes_256_gcm())),
+    ("aes-128-xts", || Some(Cipher::aes_128_xts())),
+    ("aes-256-xts", || Some(Cipher::aes_256_xts())),
+    ("des-cbc", || Some(Cipher::des_cbc())),
+    ("des", || Some(Cipher::des_cbc())),
+    ("des-ecb", || Some(Cipher::des_ecb())),
+    ("des-ede3-cbc", || Some(Cipher::des_ede3_cbc())),
+    ("des3", || Some(Cipher::des_ede3_cbc())),
+    ("des-ede3-ecb", || Some(Cipher::des_ede3_ecb())),
+    ("bf-cbc", || Some(Cipher::bf_cbc())),
+    ("bf", || Some(Cipher::bf_cbc())),
+    ("blowfish", || Some(Cipher::bf_cbc())),
+    ("bf-ecb", || Some(Cipher::bf_ecb())),
+    ("bf-cfb", || Some(Cipher::bf_cfb64())),
+    ("bf-ofb", || Some(Cipher::bf_ofb())),
+    ("cast5-cbc", || Some(Cipher::cast5_cbc())),
+    ("cast", || Some(Cipher::cast5_cbc())),
+    ("cast-cbc", || Some(Cipher::cast5_cbc())),
+    ("cast5-ecb", || Some(Cipher::cast5_ecb())),
+    ("cast5-cfb", || Some(Cipher::cast5_cfb64())),
+    ("cast5-ofb", || Some(Cipher::cast5_ofb())),
+    ("idea-cbc", || Cipher::from_nid(Nid::IDEA_CBC)),
+    ("idea", || Cipher::from_nid(Nid::IDEA_CBC)),
+    ("idea-ecb", || Cipher::from_nid(Nid::IDEA_ECB)),
+    ("idea-cfb", || Cipher::from_nid(Nid::IDEA_CFB64)),
+    ("idea-ofb", || Cipher::from_nid(Nid::IDEA_OFB64)),
+    ("rc2-cbc", || Some(Cipher::rc2_cbc())),
+    ("rc2", || Some(Cipher::rc2_cbc())),
+    ("rc4", || Some(Cipher::rc4())),
+    ("rc4-40", || Cipher::from_nid(Nid::RC4_40)),
+];
+
+/// Longest name in CIPHERS; anything longer cannot match
+const MAX_CIPHER_NAME_LEN: usize = 14;
+
+/// Resolve a cipher name case-insensitively. The lookup table is built once
+/// and the name is lowercased on the stack, so the encrypt/decrypt hot path
+/// does not allocate.
+fn map_cipher(name: &[u8]) -> Option<Cipher> {
+    static TABLE: OnceLock<HashMap<&'static str, CipherCtor>> = OnceLock::new();
+
+    if name.len() > MAX_CIPHER_NAME_LEN {
+        return None;
+    }
+    let mut buf = [0u8; MAX_CIPHER_NAME_LEN];
+    let lower = &mut buf[..name.len()];
+    lower.copy_from_slice(name);
+    lower.make_ascii_lowercase();
+    let lower = std::str::from_utf8(lower).ok()?;
+
+    let table = TABLE.get_or_init(|| CIPHERS.iter().copied().collect());
+    table.get(lower).and_then(|cipher| cipher())
+}
+
+type DigestCtor = fn() -> Option<MessageDigest>;
+
+/// Digests resolvable by name, in the order openssl_get_md_methods() lists
+/// them. Entries built with `from_name` are only present when the linked
+/// OpenSSL provides them (e.g. md4 needs the legacy provider).
+const DIGESTS: &[(&str, DigestCtor)] = &[
+    ("md4", || MessageDigest::from_name("md4")),
+    ("md5", || Some(MessageDigest::md5())),
+    ("sha1", || Some(MessageDigest::sha1())),
+    ("sha224", || Some(MessageDigest::sha224())),
+    ("sha256", || Some(MessageDigest::sha256())),
+    ("sha384", || Some(MessageDigest::sha384())),
+    ("sha512", || Some(MessageDigest::sha512())),
+    ("sha512-224", || MessageDigest::from_name("sha512-224")),
+    ("sha512-256", || MessageDigest::from_name("sha512-256")),
+    ("sha3-224", || Some(MessageDigest::sha3_224())),
+    ("sha3-256", || Some(MessageDigest::sha3_256())),
+    ("sha3-384", || Some(MessageDigest::sha3_384())),
+    ("sha3-512", || Some(MessageDigest::sha3_512())),
+    ("shake128", || Some(MessageDigest::shake_128())),
+    ("shake256", || Some(MessageDigest::shake_256())),
+    ("ripemd160", || Some(MessageDigest::ripemd160())),
+    ("sm3", || Some(MessageDigest::sm3())),
+];
+
+fn map_digest(name: &[u8]) -> Option<MessageDigest> {
     let name = String::from_utf8_lossy(name).to_lowercase();
-    match name.as_str() {
-        "md5" => Some(openssl::hash::MessageDigest::md5()),
-        "sha1" => Some(openssl::hash::MessageDigest::sha1()),
-        "sha224" => Some(openssl::hash::MessageDigest::sha224()),
-        "sha256" => Some(openssl::hash::MessageDigest::sha256()),
-        "sha384" => Some(openssl::hash::MessageDigest::sha384()),
-        "sha512" => Some(openssl::hash::MessageDigest::sha512()),
-        "ripemd160" => Some(openssl::hash::MessageDigest::ripemd160()),
-        "sm3" => Some(openssl::hash::MessageDigest::sm3()),
+    DIGESTS
+        .iter()
+        .find(|(digest_name, _)| *digest_name == name)
+        .and_then(|(_, digest)| digest())
+}
+
+/// Digest name for an OPENSSL_ALGO_* constant
+fn algo_digest_name(algo: i64) -> Option<&'static str> {
+    match algo {
+        OPENSSL_ALGO_DSS1 | OPENSSL_ALGO_SHA1 => Some("sha1"),
+        OPENSSL_ALGO_SHA224 => Some("sha224"),
+        OPENSSL_ALGO_SHA256 => Some("sha256"),
+        OPENSSL_ALGO_SHA384 => Some("sha384"),
+        OPENSSL_ALGO_SHA512 => Some("sha512"),
+        OPENSSL_ALGO_RMD160 => Some("ripemd160"),
+        OPENSSL_ALGO_MD5 => Some("md5"),
+        OPENSSL_ALGO_MD4 => Some("md4"),
+        OPENSSL_ALGO_MD2 => Some("md2"),
+        _ => None,
+    }
+}
+
+/// Resolve the $algorithm argument of openssl_sign()/openssl_verify(), which
+/// is either an OPENSSL_ALGO_* constant or a digest name (default SHA-1)
+fn signature_digest(vm: &VM, algo: Option<&Handle>) -> Option<MessageDigest> {
+    let name = match algo.map(|h| &vm.arena.get(*h).value) {
+        None => "sha1".to_string(),
+        Some(Val::Int(i)) => algo_digest_name(*i)?.to_string(),
+        Some(Val::String(s)) => String::from_utf8_lossy(s).to_lowercase(),
+        Some(_) => return None,
+    };
+    // Aliases such as "RSA-SHA256" resolve like EVP_get_digestbyname()
+    map_digest(name.as_bytes()).or_else(|| MessageDigest::from_name(&name))
+}
+
+/// Resolve the optional $padding argument of openssl_sign()/openssl_verify().
+/// It is either an OPENSSL_*_PADDING constant or an options array with
+/// "padding" and "salt_length" keys. Returns `Some(None)` for the default
+/// PKCS#1 v1.5 padding and `None` for an unsupported value.
+fn signature_pss_salt_len(vm: &VM, padding: Option<&Handle>) -> Option<Option<RsaPssSaltlen>> {
+    let (mode, salt_len) = match padding.map(|h| &vm.arena.get(*h).value) {
+        None | Some(Val::Null) => (0, None),
+        Some(Val::Int(mode)) => (*mode, None),
+        Some(Val::Array(options)) => {
+            let option = |key: &[u8]| {
+                options
+                    .map
+                    .get(&ArrayKey::Str(Rc::new(key.to_vec())))
+                    .map(|h| vm.arena.get(*h).value.to_int())
+            };
+            (option(b"padding").unwrap_or(0), option(b"salt_length"))
+        }
+        Some(_) => return None,
+    };
+
+    match mode {
+        0 | OPENSSL_PKCS1_PADDING => Some(None),
+        OPENSSL_PKCS1_PSS_PADDING => Some(Some(match salt_len {
+            Some(len) => RsaPssSaltlen::custom(i32::try_from(len).ok()?),
+            None => RsaPssSaltlen::DIGEST_LENGTH,
+        })),
         _ => None,
     }
 }
@@ -1311,29 +1932,24 @@ pub fn openssl_sign(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         Err(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
-    let algo = if args.len() > 3 {
-        match &vm.arena.get(args[3]).value {
-            Val::String(s) => String::from_utf8_lossy(s).to_string(),
-            Val::Int(i) => match *i {
-                1 => "sha1".to_string(),
-                2 => "md5".to_string(),
-                3 => "md4".to_string(),
-                4 => "sha224".to_string(),
-                5 => "sha256".to_string(),
-                6 => "sha384".to_string(),
-                7 => "sha512".to_string(),
-                8 => "ripemd160".to_string(),
-                _ => "sha1".to_string(),
-            },
-            _ => "sha1".to_string(),
-        }
-    } else {
-        "sha1".to_string()
+    let Some(md) = signature_digest(vm, args.get(3)) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let md = map_digest(algo.as_bytes()).unwrap_or_else(|| openssl::hash::MessageDigest::sha1());
+    let Some(pss_salt_len) = signature_pss_salt_len(vm, args.get(4)) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
 
     let mut signer = Signer::new(md, &pkey).map_err(|e| e.to_string())?;
+    if let Some(salt_len) = pss_salt_len {
+        let configured = signer
+            .set_rsa_padding(openssl::rsa::Padding::PKCS1_PSS)
+            .and_then(|_| signer.set_rsa_pss_saltlen(salt_len));
+        if let Err(e) = configured {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    }
     signer.update(&data).map_err(|e| e.to_string())?;
     let signature = signer.sign_to_vec().map_err(|e| e.to_string())?;
 
@@ -1362,29 +1978,24 @@ pub fn openssl_verify(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         Err(_) => return Ok(vm.arena.alloc(Val::Int(-1))),
     };
 
-    let algo = if args.len() > 3 {
-        match &vm.arena.get(args[3]).value {
-            Val::String(s) => String::from_utf8_lossy(s).to_string(),
-            Val::Int(i) => match *i {
-                1 => "sha1".to_string(),
-                2 => "md5".to_string(),
-                3 => "md4".to_string(),
-                4 => "sha224".to_string(),
-                5 => "sha256".to_string(),
-                6 => "sha384".to_string(),
-                7 => "sha512".to_string(),
-                8 => "ripemd160".to_string(),
-                _ => "sha1".to_string(),
-            },
-            _ => "sha1".to_string(),
-        }
-    } else {
-        "sha1".to_string()
+    let Some(md) = signature_digest(vm, args.get(3)) else {
+        return Ok(vm.arena.alloc(Val::Int(-1)));
     };
 
-    let md = map_digest(algo.as_bytes()).unwrap_or_else(|| openssl::hash::MessageDigest::sha1());
+    let Some(pss_salt_len) = signature_pss_salt_len(vm, args.get(4)) else {
+        return Ok(vm.arena.alloc(Val::Int(-1)));
+    };
 
     let mut verifier = Verifier::new(md, &pkey).map_err(|e| e.to_string())?;
+    if let Some(salt_len) = pss_salt_len {
+        let configured = verifier
+            .set_rsa_padding(openssl::rsa::Padding::PKCS1_PSS)
+            .and_then(|_| verifier.set_rsa_pss_saltlen(salt_len));
+        if let Err(e) = configured {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Int(-1)));
+        }
+    }
     verifier.update(&data).map_err(|e| e.to_string())?;
     let result = verifier.verify(&signature).map_err(|e| e.to_string())?;
 
@@ -1439,14 +2050,7 @@ pub fn openssl_pkcs7_encrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, Str
 
     let cipher = if args.len() > 5 {
         match &vm.arena.get(args[5]).value {
-            Val::Int(i) => match *i {
-                OPENSSL_CIPHER_AES_128_CBC => Cipher::aes_128_cbc(),
-                OPENSSL_CIPHER_AES_192_CBC => Cipher::aes_192_cbc(),
-                OPENSSL_CIPHER_AES_256_CBC => Cipher::aes_256_cbc(),
-                OPENSSL_CIPHER_DES => Cipher::des_cbc(),
-                OPENSSL_CIPHER_3DES => Cipher::des_ede3_cbc(),
-                _ => Cipher::aes_128_cbc(),
-            },
+            Val::Int(i) => cipher_from_constant(*i).unwrap_or_else(Cipher::aes_128_cbc),
             _ => Cipher::aes_128_cbc(),
         }
     } else {
@@ -1591,10 +2195,30 @@ pub fn openssl_pkcs7_sign(vm: &mut VM, args: &[Handle]) -> Result<Handle, String
     };
 
     let input_data = std::fs::read(&in_file).map_err(|e| e.to_string())?;
-    let empty_stack = openssl::stack::Stack::<X509>::new().map_err(|e| e.to_string())?;
+
+    // Extra certificates to embed alongside the signer's
+    let mut extra_certs = openssl::stack::Stack::<X509>::new().map_err(|e| e.to_string())?;
+    if let Some(Val::String(extra_file)) = args.get(6).map(|h| &vm.arena.get(*h).value) {
+        let extra_file = String::from_utf8_lossy(extra_file).to_string();
+        let pem = match std::fs::read(&extra_file) {
+            Ok(pem) => pem,
+            Err(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
+        };
+        match X509::stack_from_pem(&pem) {
+            Ok(certs) => {
+                for extra in certs {
+                    extra_certs.push(extra).map_err(|e| e.to_string())?;
+                }
+            }
+            Err(e) => {
+                push_openssl_errors(vm, &e);
+                return Ok(vm.arena.alloc(Val::Bool(false)));
+            }
+        }
+    }
 
     let pkcs7 =
-        Pkcs7::sign(&cert, &pkey, &empty_stack, &input_data, flags).map_err(|e| e.to_string())?;
+        Pkcs7::sign(&cert, &pkey, &extra_certs, &input_data, flags).map_err(|e| e.to_string())?;
 
     let pem = pkcs7.to_pem().map_err(|e| e.to_string())?;
     std::fs::write(&out_file, pem).map_err(|e| e.to_string())?;
@@ -1602,6 +2226,116 @@ pub fn openssl_pkcs7_sign(vm: &mut VM, args: &[Handle]) -> Result<Handle, String
     Ok(vm.arena.alloc(Val::Bool(true)))
 }
 
+/// openssl_pkcs7_read(string $data, &$certificates): bool
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - PHP_FUNCTION(openssl_pkcs7_read)
+pub fn openssl_pkcs7_read(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    let data = match &vm.arena.get(args[0]).value {
+        Val::String(s) => s.clone(),
+        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    };
+
+    // PHP also accepts the bare base64 body, without the PEM armor
+    let pkcs7 = if data.starts_with(b"-----BEGIN") {
+        Pkcs7::from_pem(&data)
+    } else {
+        let mut pem = b"-----BEGIN PKCS7-----\n".to_vec();
+        pem.extend_from_slice(&data);
+        pem.extend_from_slice(b"\n-----END PKCS7-----\n");
+        Pkcs7::from_pem(&pem).or_else(|_| Pkcs7::from_der(&data))
+    };
+    let pkcs7 = match pkcs7 {
+        Ok(pkcs7) => pkcs7,
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    };
+
+    // Only signed and signed-and-enveloped structures carry certificates
+    let Some(certs) = pkcs7.signed().and_then(|signed| signed.certificates()) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    let certs_array = certificate_objects(vm, certs);
+    set_ref_value(vm, args[1], Val::Array(Rc::new(certs_array)));
+
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+/// Wraps each certificate of a stack in an OpenSSLCertificate object
+fn certificate_objects(vm: &mut VM, certs: &StackRef<X509>) -> ArrayData {
+    let cert_class = vm.context.interner.intern(b"OpenSSLCertificate");
+    let mut certs_array = ArrayData::new();
+    for (i, cert) in certs.iter().enumerate() {
+        let cert_obj = ObjectData {
+            class: cert_class,
+            properties: IndexMap::new(),
+            internal: Some(Rc::new(cert.to_owned())),
+            dynamic_properties: HashSet::new(),
+        };
+        certs_array.insert(
+            ArrayKey::Int(i as i64),
+            vm.arena.alloc(Val::ObjPayload(cert_obj)),
+        );
+    }
+    certs_array
+}
+
+/// Build a trust store from a `$ca_info` argument. Entries may be
+/// certificate objects, PEM bundles, or paths to a PEM file or a hashed
+/// certificate directory; entries that cannot be loaded are reported and
+/// skipped.
+fn build_ca_store(
+    vm: &mut VM,
+    func: &str,
+    ca_info: Option<&Handle>,
+) -> Result<X509StoreBuilder, String> {
+    let mut builder = X509StoreBuilder::new().map_err(|e| e.to_string())?;
+    let entries: Vec<Handle> = match ca_info.map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Array(arr)) => arr.map.values().copied().collect(),
+        _ => return Ok(builder),
+    };
+    for entry in entries {
+        let certs = match &vm.arena.get(entry).value {
+            Val::ObjPayload(_) => get_cert(vm, entry).map(|cert| vec![cert]),
+            Val::String(s) if s.starts_with(b"-----BEGIN") => {
+                X509::stack_from_pem(s).map_err(|e| e.to_string())
+            }
+            Val::String(s) => {
+                let path = String::from_utf8_lossy(s).to_string();
+                if std::path::Path::new(&path).is_dir() {
+                    let loaded = builder
+                        .add_lookup(X509Lookup::hash_dir())
+                        .and_then(|lookup| lookup.add_dir(&path, SslFiletype::PEM));
+                    if let Err(e) = loaded {
+                        push_openssl_errors(vm, &e);
+                    }
+                    continue;
+                }
+                std::fs::read(&path)
+                    .map_err(|_| format!("Unable to read \"{}\"", path))
+                    .and_then(|pem| X509::stack_from_pem(&pem).map_err(|e| e.to_string()))
+            }
+            _ => Err("Expected OpenSSLCertificate".to_string()),
+        };
+        match certs {
+            Ok(certs) => {
+                for cert in certs {
+                    builder.add_cert(cert).map_err(|e| e.to_string())?;
+                }
+            }
+            Err(message) => {
+                vm.report_error(ErrorLevel::Warning, &format!("{}(): {}", func, message));
+            }
+        }
+    }
+    Ok(builder)
+}
+
 pub fn openssl_pkcs7_verify(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() < 2 {
         return Ok(vm.arena.alloc(Val::Bool(false)));
@@ -1623,9 +2357,7 @@ pub fn openssl_pkcs7_verify(vm: &mut VM, args: &[Handle]) -> Result<Handle, Stri
         .map_err(|e| e.to_string())?;
 
     let empty_stack = openssl::stack::Stack::<X509>::new().map_err(|e| e.to_string())?;
-    let store = openssl::x509::store::X509StoreBuilder::new()
-        .map_err(|e| e.to_string())?
-        .build();
+    let store = build_ca_store(vm, "openssl_pkcs7_verify", args.get(3))?.build();
 
     let mut out_data = Vec::new();
     let res = pkcs7.verify(&empty_stack, &store, None, Some(&mut out_data), flags);
@@ -1640,241 +2372,367 @@ pub fn openssl_pkcs7_verify(vm: &mut VM, args: &[Handle]) -> Result<Handle, Stri
             }
             Ok(vm.arena.alloc(Val::Bool(true)))
         }
-        Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            Ok(vm.arena.alloc(Val::Bool(false)))
+        }
+    }
+}
+
+/// Source or destination of a CMS operation. Besides filenames, the CMS
+/// functions accept in-memory streams (php://memory, php://temp) so callers
+/// don't need temporary files.
+enum CmsTarget {
+    File(String),
+    Memory(Rc<MemoryStream>),
+}
+
+impl CmsTarget {
+    fn from_handle(vm: &VM, handle: Handle) -> Option<Self> {
+        match &vm.arena.get(handle).value {
+            Val::String(s) => Some(CmsTarget::File(String::from_utf8_lossy(s).to_string())),
+            Val::Resource(rc) => rc
+                .clone()
+                .downcast::<MemoryStream>()
+                .ok()
+                .map(CmsTarget::Memory),
+            _ => None,
+        }
+    }
+
+    /// Reads everything from the stream position to the end
+    fn read(&self) -> std::io::Result<Vec<u8>> {
+        match self {
+            CmsTarget::File(path) => std::fs::read(path),
+            CmsTarget::Memory(stream) => {
+                let buffer = stream.buffer.borrow();
+                let mut pos = stream.position.borrow_mut();
+                let data = buffer.get(*pos..).unwrap_or_default().to_vec();
+                *pos = buffer.len();
+                Ok(data)
+            }
+        }
+    }
+
+    /// Writes at the stream position, replacing files like PHP's fopen(.., "w")
+    fn write(&self, data: &[u8]) -> std::io::Result<()> {
+        match self {
+            CmsTarget::File(path) => std::fs::write(path, data),
+            CmsTarget::Memory(stream) => {
+                let mut buffer = stream.buffer.borrow_mut();
+                let mut pos = stream.position.borrow_mut();
+                if *pos + data.len() > buffer.len() {
+                    buffer.resize(*pos + data.len(), 0);
+                }
+                buffer[*pos..*pos + data.len()].copy_from_slice(data);
+                *pos += data.len();
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Reads an OPENSSL_ENCODING_* argument; None for unknown encodings
+fn cms_encoding(vm: &VM, handle: Option<&Handle>) -> Option<i64> {
+    match handle.map(|h| &vm.arena.get(*h).value) {
+        None | Some(Val::Null) => Some(OPENSSL_ENCODING_SMIME),
+        Some(Val::Int(
+            encoding @ (OPENSSL_ENCODING_SMIME | OPENSSL_ENCODING_DER | OPENSSL_ENCODING_PEM),
+        )) => Some(*encoding),
+        _ => None,
+    }
+}
+
+/// Formats the $headers array as "name: value" lines for S/MIME output
+fn cms_headers(vm: &VM, handle: Option<&Handle>) -> Vec<u8> {
+    let mut out = Vec::new();
+    if let Some(Val::Array(headers)) = handle.map(|h| &vm.arena.get(*h).value) {
+        for (key, value) in headers.map.iter() {
+            let Val::String(value) = &vm.arena.get(*value).value else {
+                continue;
+            };
+            if let ArrayKey::Str(name) = key {
+                out.extend_from_slice(name);
+                out.extend_from_slice(b": ");
+            }
+            out.extend_from_slice(value);
+            out.push(b'\n');
+        }
+    }
+    out
+}
+
+/// Serializes a CMS structure in the requested encoding. S/MIME output goes
+/// through PKCS#7, whose signed and enveloped data share the CMS layout.
+fn cms_encode(
+    cms: &CmsContentInfo,
+    encoding: i64,
+    content: &[u8],
+    flags: CMSOptions,
+    headers: Vec<u8>,
+) -> Result<Vec<u8>, ErrorStack> {
+    match encoding {
+        OPENSSL_ENCODING_DER => cms.to_der(),
+        OPENSSL_ENCODING_PEM => cms.to_pem(),
+        _ => {
+            let pkcs7 = Pkcs7::from_der(&cms.to_der()?)?;
+            let smime =
+                pkcs7.to_smime(content, Pkcs7Flags::from_bits_truncate(flags.bits() as i32))?;
+            let mut out = headers;
+            out.extend_from_slice(&smime);
+            Ok(out)
+        }
+    }
+}
+
+/// Parses a CMS structure, returning the detached content of multipart S/MIME
+fn cms_decode(data: &[u8], encoding: i64) -> Result<(CmsContentInfo, Option<Vec<u8>>), ErrorStack> {
+    match encoding {
+        OPENSSL_ENCODING_DER => Ok((CmsContentInfo::from_der(data)?, None)),
+        OPENSSL_ENCODING_PEM => Ok((CmsContentInfo::from_pem(data)?, None)),
+        _ => match Pkcs7::from_smime(data) {
+            Ok((pkcs7, content)) => Ok((CmsContentInfo::from_der(&pkcs7.to_der()?)?, content)),
+            Err(_) => Ok((CmsContentInfo::smime_read_cms(data)?, None)),
+        },
     }
 }
 
+/// openssl_cms_read(string $input_filename, &$certificates): bool
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - PHP_FUNCTION(openssl_cms_read)
+pub fn openssl_cms_read(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    let Some(input) = CmsTarget::from_handle(vm, args[0]) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+    let Ok(data) = input.read() else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    // Besides PHP's PEM, accept DER and S/MIME so any openssl_cms_sign output can be read
+    let encoding = if data.starts_with(b"-----BEGIN") {
+        OPENSSL_ENCODING_PEM
+    } else if data.first() == Some(&0x30) {
+        OPENSSL_ENCODING_DER
+    } else {
+        OPENSSL_ENCODING_SMIME
+    };
+    // Signed CMS shares the PKCS#7 layout, which exposes the certificate stack
+    let pkcs7 = cms_decode(&data, encoding).and_then(|(cms, _)| Pkcs7::from_der(&cms.to_der()?));
+    let pkcs7 = match pkcs7 {
+        Ok(pkcs7) => pkcs7,
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    };
+
+    let Some(certs) = pkcs7.signed().and_then(|signed| signed.certificates()) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    let certs_array = certificate_objects(vm, certs);
+    set_ref_value(vm, args[1], Val::Array(Rc::new(certs_array)));
+
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+/// openssl_cms_encrypt(string $input_filename, string $output_filename, $certificate,
+///     ?array $headers, int $flags = 0, int $encoding = OPENSSL_ENCODING_SMIME,
+///     int $cipher_algo = OPENSSL_CIPHER_AES_128_CBC): bool
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - PHP_FUNCTION(openssl_cms_encrypt)
 pub fn openssl_cms_encrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() < 3 {
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
-    let in_file = match &vm.arena.get(args[0]).value {
-        Val::String(s) => String::from_utf8_lossy(s).to_string(),
-        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    let (Some(input), Some(output)) = (
+        CmsTarget::from_handle(vm, args[0]),
+        CmsTarget::from_handle(vm, args[1]),
+    ) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let out_file = match &vm.arena.get(args[1]).value {
-        Val::String(s) => String::from_utf8_lossy(s).to_string(),
-        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    let Ok(cert) = get_cert(vm, args[2]) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let cert = {
-        let val = &vm.arena.get(args[2]).value;
-        match val {
-            Val::ObjPayload(obj) => {
-                if let Some(internal) = &obj.internal {
-                    if let Some(x509) = internal.downcast_ref::<X509>() {
-                        x509.clone()
-                    } else {
-                        return Ok(vm.arena.alloc(Val::Bool(false)));
-                    }
-                } else {
-                    return Ok(vm.arena.alloc(Val::Bool(false)));
-                }
-            }
-            _ => return Ok(vm.arena.alloc(Val::Bool(false))),
-        }
+    let headers = cms_headers(vm, args.get(3));
+
+    let flags = match args.get(4).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => CMSOptions::from_bits_truncate(*i as u32),
+        _ => CMSOptions::empty(),
     };
 
-    let flags = if args.len() > 4 {
-        match &vm.arena.get(args[4]).value {
-            Val::Int(i) => CMSOptions::from_bits_truncate(*i as u32),
-            _ => CMSOptions::empty(),
-        }
-    } else {
-        CMSOptions::empty()
+    let Some(encoding) = cms_encoding(vm, args.get(5)) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let cipher = if args.len() > 5 {
-        match &vm.arena.get(args[5]).value {
-            Val::Int(i) => match *i {
-                OPENSSL_CIPHER_AES_128_CBC => Cipher::aes_128_cbc(),
-                OPENSSL_CIPHER_AES_192_CBC => Cipher::aes_192_cbc(),
-                OPENSSL_CIPHER_AES_256_CBC => Cipher::aes_256_cbc(),
-                OPENSSL_CIPHER_DES => Cipher::des_cbc(),
-                OPENSSL_CIPHER_3DES => Cipher::des_ede3_cbc(),
-                _ => Cipher::aes_128_cbc(),
-            },
-            _ => Cipher::aes_128_cbc(),
-        }
-    } else {
-        Cipher::aes_128_cbc()
+    let cipher = match args.get(6).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => cipher_from_constant(*i).unwrap_or_else(Cipher::aes_128_cbc),
+        _ => Cipher::aes_128_cbc(),
     };
 
-    let input_data = std::fs::read(&in_file).map_err(|e| e.to_string())?;
+    let Ok(input_data) = input.read() else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
     let mut certs = openssl::stack::Stack::<X509>::new().map_err(|e| e.to_string())?;
     certs.push(cert).map_err(|e| e.to_string())?;
 
-    let cms =
-        CmsContentInfo::encrypt(&certs, &input_data, cipher, flags).map_err(|e| e.to_string())?;
-
-    let pem = cms.to_pem().map_err(|e| e.to_string())?;
-    std::fs::write(&out_file, pem).map_err(|e| e.to_string())?;
+    let encoded = CmsContentInfo::encrypt(&certs, &input_data, cipher, flags)
+        .and_then(|cms| cms_encode(&cms, encoding, &input_data, flags, headers));
+    let encoded = match encoded {
+        Ok(encoded) => encoded,
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    };
 
-    Ok(vm.arena.alloc(Val::Bool(true)))
+    let written = output.write(&encoded).is_ok();
+    Ok(vm.arena.alloc(Val::Bool(written)))
 }
 
+/// openssl_cms_decrypt(string $input_filename, string $output_filename, $certificate,
+///     $private_key = null, int $encoding = OPENSSL_ENCODING_SMIME): bool
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - PHP_FUNCTION(openssl_cms_decrypt)
 pub fn openssl_cms_decrypt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() < 4 {
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
-    let in_file = match &vm.arena.get(args[0]).value {
-        Val::String(s) => String::from_utf8_lossy(s).to_string(),
-        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    let (Some(input), Some(output)) = (
+        CmsTarget::from_handle(vm, args[0]),
+        CmsTarget::from_handle(vm, args[1]),
+    ) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let out_file = match &vm.arena.get(args[1]).value {
-        Val::String(s) => String::from_utf8_lossy(s).to_string(),
-        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    let (Ok(cert), Ok(pkey)) = (get_cert(vm, args[2]), get_pkey(vm, args[3])) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let cert = {
-        let val = &vm.arena.get(args[2]).value;
-        match val {
-            Val::ObjPayload(obj) => {
-                if let Some(internal) = &obj.internal {
-                    if let Some(x509) = internal.downcast_ref::<X509>() {
-                        x509.clone()
-                    } else {
-                        return Ok(vm.arena.alloc(Val::Bool(false)));
-                    }
-                } else {
-                    return Ok(vm.arena.alloc(Val::Bool(false)));
-                }
-            }
-            _ => return Ok(vm.arena.alloc(Val::Bool(false))),
-        }
+    let Some(encoding) = cms_encoding(vm, args.get(4)) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let pkey = {
-        let val = &vm.arena.get(args[3]).value;
-        match val {
-            Val::ObjPayload(obj) => {
-                if let Some(internal) = &obj.internal {
-                    if let Some(pkey) = internal.downcast_ref::<PKey<Private>>() {
-                        pkey.clone()
-                    } else {
-                        return Ok(vm.arena.alloc(Val::Bool(false)));
-                    }
-                } else {
-                    return Ok(vm.arena.alloc(Val::Bool(false)));
-                }
-            }
-            _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    let Ok(input_data) = input.read() else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+    let decrypted =
+        cms_decode(&input_data, encoding).and_then(|(cms, _)| cms.decrypt(&pkey, &cert));
+    let out_data = match decrypted {
+        Ok(out_data) => out_data,
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
         }
     };
 
-    let input_data = std::fs::read(&in_file).map_err(|e| e.to_string())?;
-    let cms = CmsContentInfo::from_pem(&input_data)
-        .or_else(|_| CmsContentInfo::from_der(&input_data))
-        .map_err(|e| e.to_string())?;
-
-    let out_data = cms.decrypt(&pkey, &cert).map_err(|e| e.to_string())?;
-
-    std::fs::write(&out_file, out_data).map_err(|e| e.to_string())?;
-
-    Ok(vm.arena.alloc(Val::Bool(true)))
+    let written = output.write(&out_data).is_ok();
+    Ok(vm.arena.alloc(Val::Bool(written)))
 }
 
+/// openssl_cms_sign(string $input_filename, string $output_filename, $certificate,
+///     $private_key, ?array $headers, int $flags = 0, int $encoding = OPENSSL_ENCODING_SMIME,
+///     ?string $untrusted_certificates_filename = null): bool
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - PHP_FUNCTION(openssl_cms_sign)
 pub fn openssl_cms_sign(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() < 4 {
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
-    let in_file = match &vm.arena.get(args[0]).value {
-        Val::String(s) => String::from_utf8_lossy(s).to_string(),
-        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    let (Some(input), Some(output)) = (
+        CmsTarget::from_handle(vm, args[0]),
+        CmsTarget::from_handle(vm, args[1]),
+    ) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let out_file = match &vm.arena.get(args[1]).value {
-        Val::String(s) => String::from_utf8_lossy(s).to_string(),
-        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    let (Ok(cert), Ok(pkey)) = (get_cert(vm, args[2]), get_pkey(vm, args[3])) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let cert = {
-        let val = &vm.arena.get(args[2]).value;
-        match val {
-            Val::ObjPayload(obj) => {
-                if let Some(internal) = &obj.internal {
-                    if let Some(x509) = internal.downcast_ref::<X509>() {
-                        x509.clone()
-                    } else {
-                        return Ok(vm.arena.alloc(Val::Bool(false)));
-                    }
-                } else {
-                    return Ok(vm.arena.alloc(Val::Bool(false)));
-                }
-            }
-            _ => return Ok(vm.arena.alloc(Val::Bool(false))),
-        }
-    };
+    let headers = cms_headers(vm, args.get(4));
 
-    let pkey = {
-        let val = &vm.arena.get(args[3]).value;
-        match val {
-            Val::ObjPayload(obj) => {
-                if let Some(internal) = &obj.internal {
-                    if let Some(pkey) = internal.downcast_ref::<PKey<Private>>() {
-                        pkey.clone()
-                    } else {
-                        return Ok(vm.arena.alloc(Val::Bool(false)));
-                    }
-                } else {
-                    return Ok(vm.arena.alloc(Val::Bool(false)));
-                }
-            }
-            _ => return Ok(vm.arena.alloc(Val::Bool(false))),
-        }
+    let flags = match args.get(5).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => CMSOptions::from_bits_truncate(*i as u32),
+        _ => CMSOptions::DETACHED,
     };
 
-    let flags = if args.len() > 5 {
-        match &vm.arena.get(args[5]).value {
-            Val::Int(i) => CMSOptions::from_bits_truncate(*i as u32),
-            _ => CMSOptions::from_bits_truncate(64), // DETACHED
-        }
-    } else {
-        CMSOptions::from_bits_truncate(64) // DETACHED
+    let Some(encoding) = cms_encoding(vm, args.get(6)) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let input_data = std::fs::read(&in_file).map_err(|e| e.to_string())?;
+    let Ok(input_data) = input.read() else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
     let empty_stack = openssl::stack::Stack::<X509>::new().map_err(|e| e.to_string())?;
 
-    let cms = CmsContentInfo::sign(
+    let encoded = CmsContentInfo::sign(
         Some(&cert),
         Some(&pkey),
         Some(&empty_stack),
         Some(&input_data),
         flags,
     )
-    .map_err(|e| e.to_string())?;
-
-    let pem = cms.to_pem().map_err(|e| e.to_string())?;
-    std::fs::write(&out_file, pem).map_err(|e| e.to_string())?;
+    .and_then(|cms| cms_encode(&cms, encoding, &input_data, flags, headers));
+    let encoded = match encoded {
+        Ok(encoded) => encoded,
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    };
 
-    Ok(vm.arena.alloc(Val::Bool(true)))
+    let written = output.write(&encoded).is_ok();
+    Ok(vm.arena.alloc(Val::Bool(written)))
 }
 
+/// openssl_cms_verify(string $input_filename, int $flags = 0, ?string $certificates = null,
+///     array $ca_info = [], ?string $untrusted_certificates_filename = null,
+///     ?string $content = null, ?string $pk7 = null, ?string $sigfile = null,
+///     int $encoding = OPENSSL_ENCODING_SMIME): bool
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - PHP_FUNCTION(openssl_cms_verify)
 pub fn openssl_cms_verify(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
-    if args.len() < 2 {
+    if args.is_empty() {
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
-    let filename = match &vm.arena.get(args[0]).value {
-        Val::String(s) => String::from_utf8_lossy(s).to_string(),
-        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    let Some(input) = CmsTarget::from_handle(vm, args[0]) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
 
-    let flags = match &vm.arena.get(args[1]).value {
-        Val::Int(i) => CMSOptions::from_bits_truncate(*i as u32),
+    let flags = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => CMSOptions::from_bits_truncate(*i as u32),
         _ => CMSOptions::empty(),
     };
 
-    let data = std::fs::read(&filename).map_err(|e| e.to_string())?;
-    let mut cms = CmsContentInfo::from_pem(&data)
-        .or_else(|_| CmsContentInfo::from_der(&data))
-        .map_err(|e| e.to_string())?;
+    let Some(encoding) = cms_encoding(vm, args.get(8)) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    let Ok(data) = input.read() else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    // With $sigfile the input is the signed content and the signature lives apart
+    let (signature, content) = match args.get(7).and_then(|h| CmsTarget::from_handle(vm, *h)) {
+        Some(sigfile) => match sigfile.read() {
+            Ok(signature) => (signature, Some(data)),
+            Err(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
+        },
+        None => (data, None),
+    };
+    let (mut cms, detached) = match cms_decode(&signature, encoding) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    };
 
     let empty_stack = openssl::stack::Stack::<X509>::new().map_err(|e| e.to_string())?;
     let store = openssl::x509::store::X509StoreBuilder::new()
@@ -1885,23 +2743,29 @@ pub fn openssl_cms_verify(vm: &mut VM, args: &[Handle]) -> Result<Handle, String
     let res = cms.verify(
         Some(&empty_stack),
         Some(&store),
-        None,
+        content.or(detached).as_deref(),
         Some(&mut out_data),
         flags,
     );
 
-    match res {
-        Ok(_) => {
-            if args.len() > 6 {
-                if let Val::String(out_filename) = &vm.arena.get(args[6]).value {
-                    let out_filename = String::from_utf8_lossy(out_filename).to_string();
-                    std::fs::write(out_filename, out_data).map_err(|e| e.to_string())?;
-                }
-            }
-            Ok(vm.arena.alloc(Val::Bool(true)))
+    if let Err(e) = res {
+        push_openssl_errors(vm, &e);
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    if let Some(content) = args.get(5).and_then(|h| CmsTarget::from_handle(vm, *h))
+        && content.write(&out_data).is_err()
+    {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+    if let Some(pk7) = args.get(6).and_then(|h| CmsTarget::from_handle(vm, *h)) {
+        let pem = cms.to_pem().map_err(|e| e.to_string())?;
+        if pk7.write(&pem).is_err() {
+            return Ok(vm.arena.alloc(Val::Bool(false)));
         }
-        Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
     }
+
+    Ok(vm.arena.alloc(Val::Bool(true)))
 }
 
 pub fn openssl_spki_new(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
@@ -1943,12 +2807,17 @@ pub fn openssl_pkcs12_export(vm: &mut VM, args: &[Handle]) -> Result<Handle, Str
         _ => "".to_string(),
     };
 
-    let pkcs12 = openssl::pkcs12::Pkcs12::builder()
-        .name("PHP OpenSSL")
-        .pkey(&pkey)
-        .cert(&cert)
-        .build2(&pass)
-        .map_err(|e| e.to_string())?;
+    let Some(pkcs12) = build_pkcs12(
+        vm,
+        "openssl_pkcs12_export",
+        &cert,
+        &pkey,
+        &pass,
+        args.get(4).copied(),
+    )?
+    else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
     let der = pkcs12.to_der().map_err(|e| e.to_string())?;
 
     // Set the output reference (args[1])
@@ -1957,6 +2826,61 @@ pub fn openssl_pkcs12_export(vm: &mut VM, args: &[Handle]) -> Result<Handle, Str
     Ok(vm.arena.alloc(Val::Bool(true)))
 }
 
+/// Build a PKCS#12 bundle for the export functions, taking `friendly_name`
+/// and `extracerts` from their `$options` array. Returns `None` after warning
+/// when an extra certificate cannot be read.
+fn build_pkcs12(
+    vm: &mut VM,
+    func: &str,
+    cert: &X509,
+    pkey: &PKey<Private>,
+    pass: &str,
+    options: Option<Handle>,
+) -> Result<Option<openssl::pkcs12::Pkcs12>, String> {
+    let options = match options.map(|h| &vm.arena.get(h).value) {
+        Some(Val::Array(options)) => Some(options.clone()),
+        _ => None,
+    };
+    let option = |key: &[u8]| {
+        options
+            .as_ref()
+            .and_then(|options| options.map.get(&ArrayKey::Str(Rc::new(key.to_vec()))))
+            .copied()
+    };
+
+    let mut builder = openssl::pkcs12::Pkcs12::builder();
+    if let Some(name) = option(b"friendly_name") {
+        let name = vm.arena.get(name).value.to_php_string_bytes();
+        builder.name(&String::from_utf8_lossy(&name));
+    }
+    if let Some(extra) = option(b"extracerts") {
+        // A single certificate or an array of them
+        let handles: Vec<Handle> = match &vm.arena.get(extra).value {
+            Val::Array(certs) => certs.map.values().copied().collect(),
+            _ => vec![extra],
+        };
+        let mut ca = openssl::stack::Stack::<X509>::new().map_err(|e| e.to_string())?;
+        for handle in handles {
+            let Ok(extra_cert) = get_cert(vm, handle) else {
+                vm.report_error(
+                    ErrorLevel::Warning,
+                    &format!("{}(): Cannot get certificate from array item", func),
+                );
+                return Ok(None);
+            };
+            ca.push(extra_cert).map_err(|e| e.to_string())?;
+        }
+        builder.ca(ca);
+    }
+
+    builder
+        .pkey(pkey)
+        .cert(cert)
+        .build2(pass)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
 pub fn openssl_pkcs12_read(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() < 3 {
         return Ok(vm.arena.alloc(Val::Bool(false)));
@@ -2032,6 +2956,9 @@ fn get_cert(vm: &VM, handle: Handle) -> Result<X509, String> {
                 }
             }
         }
+        Val::String(s) => {
+            return X509::from_pem(s).map_err(|e| e.to_string());
+        }
         _ => {}
     }
     Err("Expected OpenSSLCertificate".to_string())
@@ -2095,8 +3022,8 @@ fn get_csr(vm: &VM, handle: Handle) -> Result<Rc<X509Req>, String> {
     match val {
         Val::ObjPayload(obj) => {
             if let Some(internal) = &obj.internal {
-                if let Some(csr) = internal.downcast_ref::<Rc<X509Req>>() {
-                    return Ok(csr.clone());
+                if let Ok(csr) = internal.clone().downcast::<X509Req>() {
+                    return Ok(csr);
                 }
             }
         }
@@ -2105,93 +3032,220 @@ fn get_csr(vm: &VM, handle: Handle) -> Result<Rc<X509Req>, String> {
     Err("Expected OpenSSLCertificateSigningRequest".to_string())
 }
 
+/// Errors captured from failed OpenSSL calls, drained one at a time by
+/// `openssl_error_string()`.
+/// Reference: $PHP_SRC_PATH/ext/openssl/openssl.c - php_openssl_store_errors
+#[derive(Debug, Default)]
+pub struct OpenSslErrorQueue {
+    pub errors: VecDeque<String>,
+}
+
+/// Format an error the way ERR_error_string_n() does: `error:CODE:lib:func:reason`
+fn format_openssl_error(err: &openssl::error::Error) -> String {
+    format!(
+        "error:{:08X}:{}:{}:{}",
+        err.code(),
+        err.library().unwrap_or(""),
+        err.function().unwrap_or(""),
+        err.reason().unwrap_or("")
+    )
+}
+
+fn push_openssl_errors(vm: &mut VM, stack: &ErrorStack) {
+    let queue = vm
+        .context
+        .get_or_init_extension_data(OpenSslErrorQueue::default);
+    queue
+        .errors
+        .extend(stack.errors().iter().map(format_openssl_error));
+}
+
 pub fn openssl_error_string(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
-    let err = openssl::error::Error::get();
-    if let Some(err) = err {
-        Ok(vm
-            .arena
-            .alloc(Val::String(Rc::new(err.to_string().into_bytes()))))
-    } else {
-        Ok(vm.arena.alloc(Val::Bool(false)))
+    // Pick up anything left on the thread's error stack by calls that did not
+    // surface an ErrorStack themselves.
+    let pending = ErrorStack::get();
+    push_openssl_errors(vm, &pending);
+
+    let queue = vm
+        .context
+        .get_or_init_extension_data(OpenSslErrorQueue::default);
+    match queue.errors.pop_front() {
+        Some(err) => Ok(vm.arena.alloc(Val::String(Rc::new(err.into_bytes())))),
+        None => Ok(vm.arena.alloc(Val::Bool(false))),
     }
 }
 
+/// Check an openssl_pbkdf2() count, which OpenSSL takes as a C int; this
+/// also bounds the key allocation. Warns and returns None when out of range.
+fn positive_c_int(vm: &mut VM, value: i64, arg: &str) -> Option<usize> {
+    let problem = if value > i64::from(i32::MAX) {
+        "is too long"
+    } else if value <= 0 {
+        "must be greater than 0"
+    } else {
+        return Some(value as usize);
+    };
+    vm.report_error(
+        ErrorLevel::Warning,
+        &format!("openssl_pbkdf2(): Argument {} {}", arg, problem),
+    );
+    None
+}
+
 pub fn openssl_pbkdf2(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
-    if args.len() < 5 {
-        return Err("openssl_pbkdf2() expects at least 5 parameters".into());
+    if args.len() < 4 {
+        return Err("openssl_pbkdf2() expects at least 4 parameters".into());
     }
 
     let password = match &vm.arena.get(args[0]).value {
-        Val::String(s) => s,
+        Val::String(s) => s.clone(),
         _ => return Err("password must be a string".into()),
     };
 
     let salt = match &vm.arena.get(args[1]).value {
-        Val::String(s) => s,
+        Val::String(s) => s.clone(),
         _ => return Err("salt must be a string".into()),
     };
 
     let key_length = match &vm.arena.get(args[2]).value {
-        Val::Int(i) => *i as usize,
+        Val::Int(i) => *i,
         _ => return Err("key_length must be an integer".into()),
     };
 
     let iterations = match &vm.arena.get(args[3]).value {
-        Val::Int(i) => *i as usize,
+        Val::Int(i) => *i,
         _ => return Err("iterations must be an integer".into()),
     };
 
-    let digest_name = match &vm.arena.get(args[4]).value {
-        Val::String(s) => s,
-        _ => return Err("digest_name must be a string".into()),
+    let digest_name = match args.get(4).map(|&h| &vm.arena.get(h).value) {
+        None => b"sha1".to_vec(),
+        Some(Val::String(s)) => s.to_vec(),
+        Some(_) => return Err("digest_name must be a string".into()),
     };
 
-    let digest = map_digest(digest_name).ok_or_else(|| {
-        format!(
-            "Unknown digest algorithm: {}",
-            String::from_utf8_lossy(digest_name)
-        )
-    })?;
+    let Some(key_length) = positive_c_int(vm, key_length, "#3 ($key_length)") else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+    let Some(iterations) = positive_c_int(vm, iterations, "#4 ($iterations)") else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    let Some(digest) = map_digest(&digest_name) else {
+        vm.report_error(
+            ErrorLevel::Warning,
+            "openssl_pbkdf2(): Unknown digest algorithm",
+        );
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
 
     let mut key = vec![0u8; key_length];
-    openssl::pkcs5::pbkdf2_hmac(password, salt, iterations, digest, &mut key)
-        .map_err(|e| e.to_string())?;
+    if openssl::pkcs5::pbkdf2_hmac(&password, &salt, iterations, digest, &mut key).is_err() {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
 
     Ok(vm.arena.alloc(Val::String(Rc::new(key))))
 }
 
+/// Named curves probed by openssl_get_curve_names(), in OpenSSL's builtin order
+const KNOWN_CURVES: &[Nid] = &[
+    Nid::SECP112R1,
+    Nid::SECP112R2,
+    Nid::SECP128R1,
+    Nid::SECP128R2,
+    Nid::SECP160K1,
+    Nid::SECP160R1,
+    Nid::SECP160R2,
+    Nid::SECP192K1,
+    Nid::SECP224K1,
+    Nid::SECP224R1,
+    Nid::SECP256K1,
+    Nid::SECP384R1,
+    Nid::SECP521R1,
+    Nid::X9_62_PRIME192V1,
+    Nid::X9_62_PRIME192V2,
+    Nid::X9_62_PRIME192V3,
+    Nid::X9_62_PRIME239V1,
+    Nid::X9_62_PRIME239V2,
+    Nid::X9_62_PRIME239V3,
+    Nid::X9_62_PRIME256V1,
+    Nid::SECT113R1,
+    Nid::SECT113R2,
+    Nid::SECT131R1,
+    Nid::SECT131R2,
+    Nid::SECT163K1,
+    Nid::SECT163R1,
+    Nid::SECT163R2,
+    Nid::SECT193R1,
+    Nid::SECT193R2,
+    Nid::SECT233K1,
+    Nid::SECT233R1,
+    Nid::SECT239K1,
+    Nid::SECT283K1,
+    Nid::SECT283R1,
+    Nid::SECT409K1,
+    Nid::SECT409R1,
+    Nid::SECT571K1,
+    Nid::SECT571R1,
+    Nid::X9_62_C2PNB163V1,
+    Nid::X9_62_C2PNB163V2,
+    Nid::X9_62_C2PNB163V3,
+    Nid::X9_62_C2PNB176V1,
+    Nid::X9_62_C2TNB191V1,
+    Nid::X9_62_C2TNB191V2,
+    Nid::X9_62_C2TNB191V3,
+    Nid::X9_62_C2PNB208W1,
+    Nid::X9_62_C2TNB239V1,
+    Nid::X9_62_C2TNB239V2,
+    Nid::X9_62_C2TNB239V3,
+    Nid::X9_62_C2PNB272W1,
+    Nid::X9_62_C2PNB304W1,
+    Nid::X9_62_C2TNB359V1,
+    Nid::X9_62_C2PNB368W1,
+    Nid::X9_62_C2TNB431R1,
+    Nid::WAP_WSG_IDM_ECID_WTLS1,
+    Nid::WAP_WSG_IDM_ECID_WTLS3,
+    Nid::WAP_WSG_IDM_ECID_WTLS4,
+    Nid::WAP_WSG_IDM_ECID_WTLS5,
+    Nid::WAP_WSG_IDM_ECID_WTLS6,
+    Nid::WAP_WSG_IDM_ECID_WTLS7,
+    Nid::WAP_WSG_IDM_ECID_WTLS8,
+    Nid::WAP_WSG_IDM_ECID_WTLS9,
+    Nid::WAP_WSG_IDM_ECID_WTLS10,
+    Nid::WAP_WSG_IDM_ECID_WTLS11,
+    Nid::WAP_WSG_IDM_ECID_WTLS12,
+    Nid::BRAINPOOL_P256R1,
+    Nid::BRAINPOOL_P320R1,
+    Nid::BRAINPOOL_P384R1,
+    Nid::BRAINPOOL_P512R1,
+    Nid::SM2,
+];
+
 pub fn openssl_get_curve_names(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
-    // For now return a few common ones as openssl crate doesn't expose the full list easily
-    let curves = vec!["prime256v1", "secp384r1"];
+    // Only report curves the linked OpenSSL can actually build a group for
     let mut array = ArrayData::new();
-    for curve in curves {
-        array.push(
-            vm.arena
-                .alloc(Val::String(Rc::new(curve.as_bytes().to_vec()))),
-        );
+    for nid in KNOWN_CURVES {
+        if EcGroup::from_curve_name(*nid).is_err() {
+            continue;
+        }
+        if let Ok(name) = nid.short_name() {
+            array.push(
+                vm.arena
+                    .alloc(Val::String(Rc::new(name.as_bytes().to_vec()))),
+            );
+        }
     }
     Ok(vm.arena.alloc(Val::Array(Rc::new(array))))
 }
 
 pub fn openssl_get_md_methods(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
     let mut methods = ArrayData::new();
-    let md_list = vec![
-        "md4",
-        "md5",
-        "sha1",
-        "sha224",
-        "sha256",
-        "sha384",
-        "sha512",
-        "ripemd160",
-        "sha3-224",
-        "sha3-256",
-        "sha384",
-        "sha512",
-    ];
-    for md in md_list {
-        let val = vm.arena.alloc(Val::String(Rc::new(md.as_bytes().to_vec())));
-        methods.push(val);
+    for (name, digest) in DIGESTS {
+        if digest().is_some() {
+            let val = vm
+                .arena
+                .alloc(Val::String(Rc::new(name.as_bytes().to_vec())));
+            methods.push(val);
+        }
     }
     Ok(vm.arena.alloc(Val::Array(Rc::new(methods))))
 }
@@ -2289,7 +3343,12 @@ pub fn openssl_x509_export_to_file(vm: &mut VM, args: &[Handle]) -> Result<Handl
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
-    let pem = cert.to_pem().map_err(|e| e.to_string())?;
+    let mut pem = if export_with_text(vm, args.get(2)) {
+        cert.to_text().map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+    pem.extend(cert.to_pem().map_err(|e| e.to_string())?);
     std::fs::write(filename, pem).map_err(|e| e.to_string())?;
 
     Ok(vm.arena.alloc(Val::Bool(true)))
@@ -2300,30 +3359,14 @@ pub fn openssl_pkey_export_to_file(vm: &mut VM, args: &[Handle]) -> Result<Handl
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
-    let pkey = {
-        let val = &vm.arena.get(args[0]).value;
-        match val {
-            Val::ObjPayload(obj) => {
-                if let Some(internal) = &obj.internal {
-                    if let Some(pkey) = internal.downcast_ref::<PKey<Private>>() {
-                        pkey.clone()
-                    } else {
-                        return Ok(vm.arena.alloc(Val::Bool(false)));
-                    }
-                } else {
-                    return Ok(vm.arena.alloc(Val::Bool(false)));
-                }
-            }
-            _ => return Ok(vm.arena.alloc(Val::Bool(false))),
-        }
-    };
-
     let filename = match &vm.arena.get(args[1]).value {
         Val::String(s) => String::from_utf8_lossy(s).to_string(),
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
-    let pem = pkey.private_key_to_pem_pkcs8().map_err(|e| e.to_string())?;
+    let Some(pem) = private_key_pem(vm, "openssl_pkey_export_to_file", args[0], &args[2..])? else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
     std::fs::write(filename, pem).map_err(|e| e.to_string())?;
 
     Ok(vm.arena.alloc(Val::Bool(true)))
@@ -2358,7 +3401,12 @@ pub fn openssl_csr_export_to_file(vm: &mut VM, args: &[Handle]) -> Result<Handle
         _ => return Ok(vm.arena.alloc(Val::Bool(false))),
     };
 
-    let pem = csr.to_pem().map_err(|e| e.to_string())?;
+    let mut pem = if export_with_text(vm, args.get(2)) {
+        csr.to_text().map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+    pem.extend(csr.to_pem().map_err(|e| e.to_string())?);
     std::fs::write(filename, pem).map_err(|e| e.to_string())?;
 
     Ok(vm.arena.alloc(Val::Bool(true)))
@@ -2380,12 +3428,17 @@ pub fn openssl_pkcs12_export_to_file(vm: &mut VM, args: &[Handle]) -> Result<Han
         _ => "".to_string(),
     };
 
-    let pkcs12 = openssl::pkcs12::Pkcs12::builder()
-        .name("PHP OpenSSL")
-        .pkey(&pkey)
-        .cert(&cert)
-        .build2(&pass)
-        .map_err(|e| e.to_string())?;
+    let Some(pkcs12) = build_pkcs12(
+        vm,
+        "openssl_pkcs12_export_to_file",
+        &cert,
+        &pkey,
+        &pass,
+        args.get(4).copied(),
+    )?
+    else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
     let der = pkcs12.to_der().map_err(|e| e.to_string())?;
 
     std::fs::write(filename, der).map_err(|e| e.to_string())?;
@@ -2435,6 +3488,73 @@ pub fn openssl_x509_verify(vm: &mut VM, args: &[Handle]) -> Result<Handle, Strin
     Ok(vm.arena.alloc(Val::Bool(res)))
 }
 
+/// openssl_x509_verify_chain(cert, ca_info, purpose = null, &error = null)
+///
+/// Build the chain from `$cert` to one of the CAs in `$ca_info` and verify
+/// it, optionally for one of the X509_PURPOSE_* purposes. On failure
+/// `$error` receives the verify error code, its message and the depth in
+/// the chain where it occurred.
+pub fn openssl_x509_verify_chain(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+    let cert = get_cert(vm, args[0])?;
+    let mut builder = build_ca_store(vm, "openssl_x509_verify_chain", args.get(1))?;
+    if let Some(Val::Int(purpose)) = args.get(2).map(|h| &vm.arena.get(*h).value) {
+        if !(X509_PURPOSE_SSL_CLIENT..=X509_PURPOSE_ANY).contains(purpose) {
+            vm.report_error(
+                ErrorLevel::Warning,
+                "openssl_x509_verify_chain(): Invalid purpose",
+            );
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+        builder
+            .set_purpose(X509PurposeId::from_raw(*purpose as i32))
+            .map_err(|e| e.to_string())?;
+    }
+    let store = builder.build();
+
+    let untrusted = openssl::stack::Stack::<X509>::new().map_err(|e| e.to_string())?;
+    let mut context = X509StoreContext::new().map_err(|e| e.to_string())?;
+    let outcome = context.init(&store, &cert, &untrusted, |ctx| {
+        let verified = ctx.verify_cert()?;
+        Ok((verified, ctx.error(), ctx.error_depth()))
+    });
+    let (verified, error, depth) = match outcome {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            push_openssl_errors(vm, &e);
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    };
+
+    if let Some(&error_handle) = args.get(3) {
+        let value = if verified {
+            Val::Null
+        } else {
+            let mut details = ArrayData::new();
+            details.insert(
+                ArrayKey::Str(Rc::new(b"code".to_vec())),
+                vm.arena.alloc(Val::Int(error.as_raw() as i64)),
+            );
+            details.insert(
+                ArrayKey::Str(Rc::new(b"message".to_vec())),
+                vm.arena.alloc(Val::String(Rc::new(
+                    error.error_string().as_bytes().to_vec(),
+                ))),
+            );
+            details.insert(
+                ArrayKey::Str(Rc::new(b"depth".to_vec())),
+                vm.arena.alloc(Val::Int(depth as i64)),
+            );
+            Val::Array(Rc::new(details))
+        };
+        set_ref_value(vm, error_handle, value);
+    }
+
+    Ok(vm.arena.alloc(Val::Bool(verified)))
+}
+
 pub fn openssl_x509_free(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
     Ok(vm.arena.alloc(Val::Null))
 }
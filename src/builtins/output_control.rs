@@ -115,6 +115,24 @@ pub fn php_ob_start(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         PHP_OUTPUT_HANDLER_STDFLAGS
     };
 
+    // zlib.output_compression and ob_gzhandler must not both compress
+    // Reference: $PHP_SRC_PATH/ext/zlib/zlib.c - php_zlib_output_conflict_check
+    if let Some(handler) = handler
+        && let Val::String(name) = &vm.arena.get(handler).value
+        && name.eq_ignore_ascii_case(b"ob_gzhandler")
+        && vm.output_compression_active()
+    {
+        vm.trigger_error(
+            crate::vm::engine::ErrorLevel::Warning,
+            "ob_start(): Output handler 'ob_gzhandler' conflicts with 'zlib output compression'",
+        );
+        vm.trigger_error(
+            crate::vm::engine::ErrorLevel::Notice,
+            "ob_start(): Failed to create buffer",
+        );
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
     let buffer = OutputBuffer::new(handler, chunk_size, flags);
     vm.output_buffers.push(buffer);
 
@@ -483,7 +501,8 @@ pub fn flush_all_output_buffers(vm: &mut VM) -> Result<(), String> {
         vm.output_buffers.pop();
     }
 
-    Ok(())
+    vm.finish_output_compression()
+        .map_err(|e| format!("{:?}", e))
 }
 
 /// Add URL rewriter values
@@ -476,7 +476,7 @@ pub fn preg_quote(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     Ok(vm.arena.alloc(Val::String(str_val)))
 }
 
-fn parse_php_pattern(pattern: &[u8]) -> Result<(Vec<u8>, String), String> {
+pub(crate) fn parse_php_pattern(pattern: &[u8]) -> Result<(Vec<u8>, String), String> {
     if pattern.len() < 2 {
         return Err("Empty regex".into());
     }
@@ -11,6 +11,10 @@ use super::types::{
 use crate::core::value::Handle;
 use std::fmt::Debug;
 
+/// Body of a user-defined SQL function: receives the SQL argument values and
+/// returns the result, or a message that fails the running statement
+pub type SqlFunction = Box<dyn FnMut(Vec<PdoValue>) -> Result<PdoValue, String> + Send>;
+
 /// PDO driver trait - unified interface for all database drivers
 /// Reference: pdo_driver_t structure
 pub trait PdoDriver: Debug + Send + Sync {
@@ -39,9 +43,11 @@ pub trait PdoConnection: Debug + Send {
     /// Reference: pdo_dbh_do_func
     fn exec(&mut self, sql: &str) -> Result<i64, PdoError>;
 
-    /// Quote a string for safe SQL inclusion
+    /// Quote a string for safe SQL inclusion, returning the complete literal
+    /// (including surrounding quotes). Input is raw bytes so binary data
+    /// round-trips; drivers that cannot represent a value return an error.
     /// Reference: pdo_dbh_quote_func
-    fn quote(&self, value: &str, param_type: ParamType) -> String;
+    fn quote(&self, value: &[u8], param_type: ParamType) -> Result<Vec<u8>, PdoError>;
 
     /// Begin transaction
     /// Reference: pdo_dbh_txn_func (beginTransaction)
@@ -70,6 +76,26 @@ pub trait PdoConnection: Debug + Send {
     /// Get attribute
     fn get_attribute(&self, attr: Attribute) -> Option<Handle>;
 
+    /// Server version reported as PDO::ATTR_SERVER_VERSION
+    fn server_version(&self) -> Option<String> {
+        None
+    }
+
+    /// Register a scalar SQL function; `num_args` of -1 accepts any number of
+    /// arguments. Drivers without user-defined functions return InvalidContext.
+    /// Reference: pdo_sqlite_create_function_internal in $PHP_SRC_PATH/ext/pdo_sqlite/sqlite_driver.c
+    fn create_function(
+        &mut self,
+        _name: &str,
+        _num_args: i32,
+        _deterministic: bool,
+        _function: SqlFunction,
+    ) -> Result<(), PdoError> {
+        Err(PdoError::InvalidContext(
+            "driver does not support user-defined functions".into(),
+        ))
+    }
+
     /// Get SQLSTATE error code
     fn error_code(&self) -> String;
 
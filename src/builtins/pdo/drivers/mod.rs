@@ -4,6 +4,7 @@
 
 pub mod mysql;
 pub mod oci;
+#[cfg(feature = "pdo-pgsql")]
 pub mod pgsql;
 pub mod sqlite;
 
@@ -24,17 +25,20 @@ pub(crate) fn strip_driver_prefix<'a>(dsn: &'a str, driver: &str) -> &'a str {
     dsn
 }
 
-pub(crate) fn parse_semicolon_kv(s: &str) -> impl Iterator<Item = (&str, &str)> {
-    s.split(';').filter_map(|part| {
-        let mut it = part.splitn(2, '=');
-        let key = it.next()?.trim();
-        let value = it.next()?.trim();
-        if key.is_empty() {
-            None
-        } else {
-            Some((key, value))
+/// Standard SQL string literal: wrap in single quotes and double any embedded
+/// single quote.
+/// Reference: $PHP_SRC_PATH/ext/pdo_sqlite/sqlite_driver.c - sqlite_handle_quoter
+pub(crate) fn quote_doubling_single_quotes(value: &[u8]) -> Vec<u8> {
+    let mut quoted = Vec::with_capacity(value.len() + 2);
+    quoted.push(b'\'');
+    for &b in value {
+        if b == b'\'' {
+            quoted.push(b'\'');
         }
-    })
+        quoted.push(b);
+    }
+    quoted.push(b'\'');
+    quoted
 }
 
 /// Registry of PDO drivers
@@ -53,6 +57,7 @@ impl DriverRegistry {
         // Register built-in drivers
         registry.register(Box::new(sqlite::SqliteDriver));
         registry.register(Box::new(mysql::MysqlDriver));
+        #[cfg(feature = "pdo-pgsql")]
         registry.register(Box::new(pgsql::PgsqlDriver));
         registry.register(Box::new(oci::OciDriver));
 
@@ -99,27 +104,13 @@ impl DriverRegistry {
     /// Parse a DSN string into driver name and connection string
     /// Format: "driver:connection_string"
     pub fn parse_dsn(dsn: &str) -> Result<(&str, &str), PdoError> {
-        let dsn = dsn.trim();
-        if dsn.is_empty() {
-            return Err(PdoError::InvalidParameter(
-                "Invalid DSN format: empty DSN".to_string(),
-            ));
-        }
-
-        if let Some(colon_pos) = dsn.find(':') {
-            let driver = dsn[..colon_pos].trim();
-            if driver.is_empty() {
-                return Err(PdoError::InvalidParameter(
-                    "Invalid DSN format: empty driver name".to_string(),
-                ));
-            }
-            let connection_str = &dsn[colon_pos + 1..];
-            Ok((driver, connection_str))
-        } else {
-            Err(PdoError::InvalidParameter(
-                "Invalid DSN format: expected 'driver:connection_string'".to_string(),
-            ))
+        let invalid = || PdoError::Error(super::dsn::INVALID_DSN.to_string());
+        let (driver, connection_str) = dsn.split_once(':').ok_or_else(invalid)?;
+        let driver = driver.trim();
+        if driver.is_empty() {
+            return Err(invalid());
         }
+        Ok((driver, connection_str))
     }
 }
 
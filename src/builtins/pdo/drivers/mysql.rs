@@ -5,6 +5,7 @@
 //! Reference: $PHP_SRC_PATH/ext/pdo_mysql/mysql_driver.c
 
 use crate::builtins::pdo::driver::{PdoConnection, PdoDriver, PdoStatement};
+use crate::builtins::pdo::dsn::parse_params;
 use crate::builtins::pdo::types::{
     Attribute, ColumnMeta, FetchMode, FetchedRow, ParamIdentifier, ParamType, PdoError, PdoValue,
 };
@@ -33,9 +34,12 @@ impl PdoDriver for MysqlDriver {
         let connection_str = super::strip_driver_prefix(dsn, self.name());
 
         let mut builder = OptsBuilder::new();
+        let mut charset = None;
+        let mut dsn_user = None;
+        let mut dsn_password = None;
 
         // Parse "key=value;key=value"
-        for (key, value) in super::parse_semicolon_kv(connection_str) {
+        for (key, value) in parse_params(connection_str)? {
             if key.eq_ignore_ascii_case("host") {
                 builder = builder.ip_or_hostname(Some(value));
             } else if key.eq_ignore_ascii_case("port") {
@@ -45,19 +49,35 @@ impl PdoDriver for MysqlDriver {
             } else if key.eq_ignore_ascii_case("dbname") {
                 builder = builder.db_name(Some(value));
             } else if key.eq_ignore_ascii_case("charset") {
-                // charset handling if needed
+                // Only accept plain charset names; the value is spliced into SET NAMES
+                if !value.is_empty()
+                    && value
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+                {
+                    charset = Some(value.to_ascii_lowercase());
+                }
             } else if key.eq_ignore_ascii_case("unix_socket") {
                 builder = builder.socket(Some(value));
+            } else if key.eq_ignore_ascii_case("user") {
+                dsn_user = Some(value);
+            } else if key.eq_ignore_ascii_case("password") {
+                dsn_password = Some(value);
             }
         }
 
-        if let Some(user) = username {
+        // Credentials passed to the constructor win over those in the DSN
+        if let Some(user) = username.map(str::to_string).or(dsn_user) {
             builder = builder.user(Some(user));
         }
-        if let Some(pass) = password {
+        if let Some(pass) = password.map(str::to_string).or(dsn_password) {
             builder = builder.pass(Some(pass));
         }
 
+        if let Some(charset) = &charset {
+            builder = builder.init(vec![format!("SET NAMES {}", charset)]);
+        }
+
         let conn = Conn::new(builder).map_err(|e| PdoError::ConnectionFailed(e.to_string()))?;
 
         Ok(Box::new(MysqlConnection {
@@ -65,6 +85,7 @@ impl PdoDriver for MysqlDriver {
             in_transaction: false,
             last_error: None,
             attributes: HashMap::new(),
+            charset,
         }))
     }
 }
@@ -77,6 +98,8 @@ struct MysqlConnection {
     last_error: Option<(String, Option<i64>, Option<String>)>,
     #[allow(dead_code)]
     attributes: HashMap<Attribute, Handle>,
+    /// Connection charset from the DSN, used to keep escaping multibyte-safe
+    charset: Option<String>,
 }
 
 impl PdoConnection for MysqlConnection {
@@ -108,9 +131,8 @@ impl PdoConnection for MysqlConnection {
         Ok(conn.affected_rows() as i64)
     }
 
-    fn quote(&self, value: &str, _param_type: ParamType) -> String {
-        // Very basic escaping for MySQL
-        format!("'{}'", value.replace('\'', "''"))
+    fn quote(&self, value: &[u8], _param_type: ParamType) -> Result<Vec<u8>, PdoError> {
+        Ok(escape_string(value, self.charset.as_deref()))
     }
 
     fn begin_transaction(&mut self) -> Result<(), PdoError> {
@@ -177,6 +199,42 @@ impl PdoConnection for MysqlConnection {
     }
 }
 
+/// Charsets whose multibyte sequences may contain a trail byte in the ASCII
+/// range (notably 0x5C, the backslash). Such pairs are copied verbatim so a
+/// lone trail byte is never escaped into a string terminator.
+const ASCII_TRAIL_CHARSETS: &[&str] = &["big5", "cp932", "gb18030", "gbk", "sjis"];
+
+/// Escape a value the way mysql_real_escape_string() does and wrap it in quotes.
+/// Reference: $PHP_SRC_PATH/ext/mysqlnd/mysqlnd_charset.c - mysqlnd_cset_escape_slashes
+fn escape_string(value: &[u8], charset: Option<&str>) -> Vec<u8> {
+    let multibyte = charset.is_some_and(|c| ASCII_TRAIL_CHARSETS.contains(&c));
+    let mut quoted = Vec::with_capacity(value.len() + 2);
+    quoted.push(b'\'');
+    let mut i = 0;
+    while i < value.len() {
+        let b = value[i];
+        if multibyte && b >= 0x81 && b != 0xFF && i + 1 < value.len() {
+            quoted.extend_from_slice(&value[i..i + 2]);
+            i += 2;
+            continue;
+        }
+        match b {
+            0 => quoted.extend_from_slice(b"\\0"),
+            b'\n' => quoted.extend_from_slice(b"\\n"),
+            b'\r' => quoted.extend_from_slice(b"\\r"),
+            0x1A => quoted.extend_from_slice(b"\\Z"),
+            b'\\' | b'\'' | b'"' => {
+                quoted.push(b'\\');
+                quoted.push(b);
+            }
+            _ => quoted.push(b),
+        }
+        i += 1;
+    }
+    quoted.push(b'\'');
+    quoted
+}
+
 /// MySQL statement implementation
 #[derive(Debug)]
 struct MysqlStatement {
@@ -457,4 +515,13 @@ mod tests {
         );
         assert_eq!(order, vec!["id"]);
     }
+
+    #[test]
+    fn test_escape_string() {
+        assert_eq!(escape_string(b"it's", None), b"'it\\'s'");
+        assert_eq!(escape_string(b"a\\b\0c\n\"", None), b"'a\\\\b\\0c\\n\\\"'");
+        // 0xBF5C is a single GBK character; its trail byte must not be escaped
+        assert_eq!(escape_string(b"\xbf\x5c'", Some("gbk")), b"'\xbf\x5c\\''");
+        assert_eq!(escape_string(b"\xbf\x5c'", None), b"'\xbf\\\x5c\\''");
+    }
 }
@@ -5,6 +5,7 @@
 //! Reference: $PHP_SRC_PATH/ext/pdo_oci/oci_driver.c
 
 use crate::builtins::pdo::driver::{PdoConnection, PdoDriver, PdoStatement};
+use crate::builtins::pdo::dsn::parse_params;
 use crate::builtins::pdo::types::{
     Attribute, ColumnMeta, FetchMode, FetchedRow, ParamIdentifier, ParamType, PdoError, PdoValue,
 };
@@ -33,13 +34,11 @@ impl PdoDriver for OciDriver {
         let connection_str = super::strip_driver_prefix(dsn, self.name());
 
         // Extract dbname from DSN (e.g., dbname=//localhost:1521/xe)
-        let mut dbname = "";
-        for (key, value) in super::parse_semicolon_kv(connection_str) {
-            if key.eq_ignore_ascii_case("dbname") {
-                dbname = value;
-                break;
-            }
-        }
+        let params = parse_params(connection_str)?;
+        let mut dbname = params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("dbname"))
+            .map_or("", |(_, value)| value.as_str());
 
         if dbname.is_empty() {
             dbname = connection_str; // Fallback to whole string if no dbname= found
@@ -114,8 +113,8 @@ impl PdoConnection for OciConnection {
         Ok(stmt.row_count().map(|r| r as i64).unwrap_or(0))
     }
 
-    fn quote(&self, value: &str, _param_type: ParamType) -> String {
-        format!("'{}'", value.replace('\'', "''"))
+    fn quote(&self, value: &[u8], _param_type: ParamType) -> Result<Vec<u8>, PdoError> {
+        Ok(super::quote_doubling_single_quotes(value))
     }
 
     fn begin_transaction(&mut self) -> Result<(), PdoError> {
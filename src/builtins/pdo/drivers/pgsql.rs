@@ -2,18 +2,31 @@
 //!
 //! Implements the PDO driver interface for PostgreSQL databases using the postgres crate.
 //!
+//! The postgres crate exchanges every parameter and column in binary format,
+//! so values are encoded for the type the server inferred for each placeholder
+//! and decoded back into the scalars and strings pdo_pgsql hands to PHP.
+//!
 //! Reference: $PHP_SRC_PATH/ext/pdo_pgsql/pgsql_driver.c
+//! Reference: $PHP_SRC_PATH/ext/pdo_pgsql/pgsql_statement.c
 
 use crate::builtins::pdo::driver::{PdoConnection, PdoDriver, PdoStatement};
+use crate::builtins::pdo::dsn::parse_params;
 use crate::builtins::pdo::types::{
     Attribute, ColumnMeta, FetchMode, FetchedRow, ParamIdentifier, ParamType, PdoError, PdoValue,
 };
 use crate::core::value::Handle;
+use bytes::{BufMut, BytesMut};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use indexmap::IndexMap;
+use postgres::types::{FromSql, IsNull, ToSql, Type, to_sql_checked};
 use postgres::{Client, NoTls};
 use std::collections::HashMap;
+use std::error::Error;
 use std::sync::{Arc, Mutex};
 
+type ErrorInfo = (String, Option<i64>, Option<String>);
+type BoxError = Box<dyn Error + Sync + Send>;
+
 /// PostgreSQL driver implementation
 #[derive(Debug)]
 pub struct PgsqlDriver;
@@ -32,13 +45,11 @@ impl PdoDriver for PgsqlDriver {
     ) -> Result<Box<dyn PdoConnection>, PdoError> {
         let connection_str = super::strip_driver_prefix(dsn, self.name());
 
-        // PostgreSQL connection string uses spaces instead of semicolons
-        let pg_conn_str = connection_str.replace(';', " ");
-
-        let mut config = pg_conn_str
+        let mut config = conninfo(&parse_params(connection_str)?)
             .parse::<postgres::Config>()
             .map_err(|e| PdoError::ConnectionFailed(e.to_string()))?;
 
+        // Constructor credentials override the ones in the DSN
         if let Some(user) = username {
             config.user(user);
         }
@@ -46,24 +57,64 @@ impl PdoDriver for PgsqlDriver {
             config.password(pass);
         }
 
-        let client = config
+        let mut client = config
             .connect(NoTls)
             .map_err(|e| PdoError::ConnectionFailed(e.to_string()))?;
 
+        let server_version = client
+            .query_one("SHOW server_version", &[])
+            .ok()
+            .and_then(|row| row.try_get::<_, String>(0).ok());
+
         Ok(Box::new(PgsqlConnection {
             client: Arc::new(Mutex::new(client)),
             in_transaction: false,
             last_error: None,
+            server_version,
             attributes: HashMap::new(),
         }))
     }
 }
 
+/// Turn DSN params into a libpq-style conninfo string of `key='value'` pairs
+fn conninfo(params: &[(String, String)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{}='{}'",
+                key,
+                value.replace('\\', "\\\\").replace('\'', "\\'")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Record a failed call the way pdo_pgsql fills errorInfo: the server's
+/// SQLSTATE, driver code 7 (PGRES_FATAL_ERROR) and "SEVERITY:  message".
+/// Client-side failures have no SQLSTATE and report HY000.
+/// Reference: $PHP_SRC_PATH/ext/pdo_pgsql/pgsql_driver.c - _pdo_pgsql_error
+fn record_error(slot: &mut Option<ErrorInfo>, e: postgres::Error) -> PdoError {
+    let info = match e.as_db_error() {
+        Some(db) => (
+            db.code().code().to_string(),
+            Some(7),
+            Some(format!("{}:  {}", db.severity(), db.message())),
+        ),
+        None => ("HY000".to_string(), None, Some(e.to_string())),
+    };
+    let message = info.2.clone().unwrap_or_default();
+    *slot = Some(info);
+    PdoError::ExecutionFailed(message)
+}
+
 /// PostgreSQL connection implementation
 struct PgsqlConnection {
     client: Arc<Mutex<Client>>,
     in_transaction: bool,
-    last_error: Option<(String, Option<i64>, Option<String>)>,
+    last_error: Option<ErrorInfo>,
+    server_version: Option<String>,
     #[allow(dead_code)]
     attributes: HashMap<Attribute, Handle>,
 }
@@ -73,10 +124,21 @@ impl std::fmt::Debug for PgsqlConnection {
         f.debug_struct("PgsqlConnection")
             .field("in_transaction", &self.in_transaction)
             .field("last_error", &self.last_error)
+            .field("server_version", &self.server_version)
             .finish()
     }
 }
 
+impl PgsqlConnection {
+    fn run(&mut self, sql: &str) -> Result<(), PdoError> {
+        self.last_error = None;
+        let mut client = self.client.lock().unwrap();
+        client
+            .batch_execute(sql)
+            .map_err(|e| record_error(&mut self.last_error, e))
+    }
+}
+
 impl PdoConnection for PgsqlConnection {
     fn prepare(&mut self, sql: &str) -> Result<Box<dyn PdoStatement>, PdoError> {
         Ok(Box::new(PgsqlStatement {
@@ -85,36 +147,30 @@ impl PdoConnection for PgsqlConnection {
             bound_params: HashMap::new(),
             last_error: None,
             row_count: 0,
-            column_count: 0,
+            columns: Vec::new(),
             results: None,
-            column_names: Vec::new(),
             current_row: 0,
         }))
     }
 
     fn exec(&mut self, sql: &str) -> Result<i64, PdoError> {
+        self.last_error = None;
         let mut client = self.client.lock().unwrap();
-        let affected = client.execute(sql, &[]).map_err(|e| {
-            let error = PdoError::ExecutionFailed(e.to_string());
-            self.last_error = Some(("HY000".to_string(), None, Some(e.to_string())));
-            error
-        })?;
+        let affected = client
+            .execute(sql, &[])
+            .map_err(|e| record_error(&mut self.last_error, e))?;
         Ok(affected as i64)
     }
 
-    fn quote(&self, value: &str, _param_type: ParamType) -> String {
-        // Basic escaping for PostgreSQL
-        format!("'{}'", value.replace('\'', "''"))
+    fn quote(&self, value: &[u8], _param_type: ParamType) -> Result<Vec<u8>, PdoError> {
+        Ok(super::quote_doubling_single_quotes(value))
     }
 
     fn begin_transaction(&mut self) -> Result<(), PdoError> {
         if self.in_transaction {
             return Err(PdoError::Error("Already in transaction".into()));
         }
-        let mut client = self.client.lock().unwrap();
-        client
-            .execute("BEGIN", &[])
-            .map_err(|e| PdoError::Error(e.to_string()))?;
+        self.run("BEGIN")?;
         self.in_transaction = true;
         Ok(())
     }
@@ -123,41 +179,38 @@ impl PdoConnection for PgsqlConnection {
         if !self.in_transaction {
             return Err(PdoError::Error("Not in transaction".into()));
         }
-        let mut client = self.client.lock().unwrap();
-        client
-            .execute("COMMIT", &[])
-            .map_err(|e| PdoError::Error(e.to_string()))?;
         self.in_transaction = false;
-        Ok(())
+        self.run("COMMIT")
     }
 
     fn rollback(&mut self) -> Result<(), PdoError> {
         if !self.in_transaction {
             return Err(PdoError::Error("Not in transaction".into()));
         }
-        let mut client = self.client.lock().unwrap();
-        client
-            .execute("ROLLBACK", &[])
-            .map_err(|e| PdoError::Error(e.to_string()))?;
         self.in_transaction = false;
-        Ok(())
+        self.run("ROLLBACK")
     }
 
     fn in_transaction(&self) -> bool {
         self.in_transaction
     }
 
+    /// currval() of the named sequence, or lastval() without a name
+    /// Reference: $PHP_SRC_PATH/ext/pdo_pgsql/pgsql_driver.c - pdo_pgsql_last_insert_id
     fn last_insert_id(&mut self, name: Option<&str>) -> Result<String, PdoError> {
-        let mut client = self.client.lock().unwrap();
-        let sql = if let Some(seq_name) = name {
-            format!("SELECT currval('{}')", seq_name)
-        } else {
-            "SELECT lastval()".to_string()
+        let sql = match name {
+            Some(seq_name) => format!(
+                "SELECT currval({})",
+                String::from_utf8_lossy(&super::quote_doubling_single_quotes(seq_name.as_bytes()))
+            ),
+            None => "SELECT lastval()".to_string(),
         };
 
+        self.last_error = None;
+        let mut client = self.client.lock().unwrap();
         let row = client
             .query_one(&sql, &[])
-            .map_err(|e| PdoError::Error(e.to_string()))?;
+            .map_err(|e| record_error(&mut self.last_error, e))?;
         let id: i64 = row.get(0);
         Ok(id.to_string())
     }
@@ -170,6 +223,10 @@ impl PdoConnection for PgsqlConnection {
         None
     }
 
+    fn server_version(&self) -> Option<String> {
+        self.server_version.clone()
+    }
+
     fn error_code(&self) -> String {
         self.last_error
             .as_ref()
@@ -189,11 +246,11 @@ struct PgsqlStatement {
     client: Arc<Mutex<Client>>,
     sql: String,
     bound_params: HashMap<ParamIdentifier, (PdoValue, ParamType)>,
-    last_error: Option<(String, Option<i64>, Option<String>)>,
+    last_error: Option<ErrorInfo>,
     row_count: i64,
-    column_count: usize,
+    /// Result column names and native type names
+    columns: Vec<(String, String)>,
     results: Option<Vec<Vec<PdoValue>>>,
-    column_names: Vec<String>,
     current_row: usize,
 }
 
@@ -204,12 +261,56 @@ impl std::fmt::Debug for PgsqlStatement {
             .field("bound_params", &self.bound_params)
             .field("last_error", &self.last_error)
             .field("row_count", &self.row_count)
-            .field("column_count", &self.column_count)
+            .field("columns", &self.columns)
             .field("current_row", &self.current_row)
             .finish()
     }
 }
 
+impl PgsqlStatement {
+    /// Values for each placeholder, in the order they appear in the SQL
+    fn placeholder_values(
+        &self,
+        params: Option<&[(ParamIdentifier, PdoValue)]>,
+        placeholders: &[Option<String>],
+    ) -> Vec<PdoValue> {
+        let mut all_params: HashMap<ParamIdentifier, PdoValue> = self
+            .bound_params
+            .iter()
+            .map(|(id, (val, _))| (id.clone(), val.clone()))
+            .collect();
+        if let Some(p) = params {
+            for (id, val) in p {
+                all_params.insert(id.clone(), val.clone());
+            }
+        }
+
+        if placeholders.is_empty() {
+            // Native $n placeholders bind by position
+            return (1..)
+                .map_while(|i| all_params.get(&ParamIdentifier::Position(i)).cloned())
+                .collect();
+        }
+
+        let mut position = 0;
+        placeholders
+            .iter()
+            .map(|name| {
+                let value = match name {
+                    Some(name) => all_params
+                        .get(&ParamIdentifier::Name(name.clone()))
+                        .or_else(|| all_params.get(&ParamIdentifier::Name(format!(":{}", name)))),
+                    None => {
+                        position += 1;
+                        all_params.get(&ParamIdentifier::Position(position))
+                    }
+                };
+                value.cloned().unwrap_or(PdoValue::Null)
+            })
+            .collect()
+    }
+}
+
 impl PdoStatement for PgsqlStatement {
     fn bind_param(
         &mut self,
@@ -225,83 +326,57 @@ impl PdoStatement for PgsqlStatement {
         &mut self,
         params: Option<&[(ParamIdentifier, PdoValue)]>,
     ) -> Result<bool, PdoError> {
-        let mut client = self.client.lock().unwrap();
-
-        let mut all_params = self.bound_params.clone();
-        if let Some(p) = params {
-            for (id, val) in p {
-                all_params.insert(id.clone(), (val.clone(), ParamType::Str));
-            }
+        self.last_error = None;
+        let (processed_sql, placeholders) = preprocess_sql(&self.sql);
+        let mut values = self.placeholder_values(params, &placeholders);
+
+        let client = self.client.clone();
+        let mut client = client.lock().unwrap();
+        let mut statement = client
+            .prepare(&processed_sql)
+            .map_err(|e| record_error(&mut self.last_error, e))?;
+
+        // Parameters of types without a binary encoding here are sent as
+        // literals for the server to parse
+        let inline: Vec<bool> = statement
+            .params()
+            .iter()
+            .map(|ty| !binds_natively(ty))
+            .collect();
+        if !placeholders.is_empty() && inline.contains(&true) {
+            let (sql, kept) = inline_literals(&self.sql, &values, &inline);
+            statement = client
+                .prepare(&sql)
+                .map_err(|e| record_error(&mut self.last_error, e))?;
+            values = kept;
         }
 
-        let (processed_sql, param_order) = preprocess_sql(&self.sql);
+        let pg_params: Vec<PgParam> = values.into_iter().map(PgParam).collect();
+        let params_refs: Vec<&(dyn ToSql + Sync)> =
+            pg_params.iter().map(|p| p as &(dyn ToSql + Sync)).collect();
 
-        let mut pg_params: Vec<Box<dyn postgres::types::ToSql + Sync>> = Vec::new();
-        if param_order.is_empty() {
-            // Positional parameters (?)
-            let mut i = 1;
-            while let Some((val, _)) = all_params.get(&ParamIdentifier::Position(i)) {
-                pg_params.push(pdo_to_pg(val.clone()));
-                i += 1;
-            }
-        } else {
-            // Named parameters (:name)
-            for param_name in param_order {
-                if let Some((val, _)) = all_params.get(&ParamIdentifier::Name(param_name.clone())) {
-                    pg_params.push(pdo_to_pg(val.clone()));
-                } else if let Some((val, _)) =
-                    all_params.get(&ParamIdentifier::Name(format!(":{}", param_name)))
-                {
-                    pg_params.push(pdo_to_pg(val.clone()));
-                } else {
-                    pg_params.push(Box::new(None::<String>));
-                }
-            }
-        }
+        self.columns = statement
+            .columns()
+            .iter()
+            .map(|c| (c.name().to_string(), c.type_().name().to_string()))
+            .collect();
 
-        // Convert Vec<Box<dyn ToSql>> to &[&dyn ToSql]
-        let params_refs: Vec<&(dyn postgres::types::ToSql + Sync)> =
-            pg_params.iter().map(|b| b.as_ref()).collect();
-
-        // Check if it's a query (returns rows) or an execution
-        if self.sql.trim_start().to_uppercase().starts_with("SELECT")
-            || self.sql.trim_start().to_uppercase().contains("RETURNING")
-        {
-            let rows = client.query(&processed_sql, &params_refs).map_err(|e| {
-                let err = PdoError::ExecutionFailed(e.to_string());
-                self.last_error = Some(("HY000".to_string(), None, Some(e.to_string())));
-                err
-            })?;
-
-            if !rows.is_empty() {
-                let columns = rows[0].columns();
-                self.column_count = columns.len();
-                self.column_names = columns.iter().map(|c| c.name().to_string()).collect();
-
-                let mut pdo_rows = Vec::new();
-                for row in rows {
-                    let mut pdo_row = Vec::new();
-                    for i in 0..self.column_count {
-                        pdo_row.push(pg_to_pdo(&row, i));
-                    }
-                    pdo_rows.push(pdo_row);
-                }
-                self.row_count = pdo_rows.len() as i64;
-                self.results = Some(pdo_rows);
-            } else {
-                self.column_count = 0;
-                self.row_count = 0;
-                self.results = Some(Vec::new());
-            }
-        } else {
-            let affected = client.execute(&processed_sql, &params_refs).map_err(|e| {
-                let err = PdoError::ExecutionFailed(e.to_string());
-                self.last_error = Some(("HY000".to_string(), None, Some(e.to_string())));
-                err
-            })?;
+        if self.columns.is_empty() {
+            let affected = client
+                .execute(&statement, &params_refs)
+                .map_err(|e| record_error(&mut self.last_error, e))?;
             self.row_count = affected as i64;
-            self.column_count = 0;
             self.results = None;
+        } else {
+            let rows = client
+                .query(&statement, &params_refs)
+                .map_err(|e| record_error(&mut self.last_error, e))?;
+            let pdo_rows: Vec<Vec<PdoValue>> = rows
+                .iter()
+                .map(|row| (0..row.len()).map(|i| pg_to_pdo(row, i)).collect())
+                .collect();
+            self.row_count = pdo_rows.len() as i64;
+            self.results = Some(pdo_rows);
         }
 
         self.current_row = 0;
@@ -321,22 +396,19 @@ impl PdoStatement for PgsqlStatement {
         let row_values = &results[self.current_row];
         self.current_row += 1;
 
-        match fetch_mode {
-            FetchMode::Assoc => {
-                let mut map = IndexMap::new();
-                for (i, name) in self.column_names.iter().enumerate() {
-                    map.insert(name.clone(), row_values[i].clone());
-                }
-                Ok(Some(FetchedRow::Assoc(map)))
+        let named = || {
+            let mut map = IndexMap::new();
+            for (i, (name, _)) in self.columns.iter().enumerate() {
+                map.insert(name.clone(), row_values[i].clone());
             }
+            map
+        };
+
+        match fetch_mode {
+            FetchMode::Assoc => Ok(Some(FetchedRow::Assoc(named()))),
             FetchMode::Num => Ok(Some(FetchedRow::Num(row_values.clone()))),
-            FetchMode::Both => {
-                let mut map = IndexMap::new();
-                for (i, name) in self.column_names.iter().enumerate() {
-                    map.insert(name.clone(), row_values[i].clone());
-                }
-                Ok(Some(FetchedRow::Both(map, row_values.clone())))
-            }
+            FetchMode::Both => Ok(Some(FetchedRow::Both(named(), row_values.clone()))),
+            FetchMode::Obj => Ok(Some(FetchedRow::Obj(named()))),
             _ => Err(PdoError::Error("Unsupported fetch mode".into())),
         }
     }
@@ -349,10 +421,16 @@ impl PdoStatement for PgsqlStatement {
         Ok(rows)
     }
 
-    fn column_meta(&self, _column: usize) -> Result<ColumnMeta, PdoError> {
+    fn column_meta(&self, column: usize) -> Result<ColumnMeta, PdoError> {
+        let (name, native_type) = self
+            .columns
+            .get(column)
+            .cloned()
+            .ok_or_else(|| PdoError::Error(format!("Column {} does not exist", column)))?;
+
         Ok(ColumnMeta {
-            name: "".to_string(),
-            native_type: "".to_string(),
+            name,
+            native_type,
             precision: None,
             scale: None,
         })
@@ -363,7 +441,7 @@ impl PdoStatement for PgsqlStatement {
     }
 
     fn column_count(&self) -> usize {
-        self.column_count
+        self.columns.len()
     }
 
     fn error_code(&self) -> String {
@@ -380,120 +458,393 @@ impl PdoStatement for PgsqlStatement {
     }
 }
 
-fn pdo_to_pg(val: PdoValue) -> Box<dyn postgres::types::ToSql + Sync> {
-    match val {
-        PdoValue::Null => Box::new(None::<String>),
-        PdoValue::Bool(b) => Box::new(b),
-        PdoValue::Int(i) => Box::new(i),
-        PdoValue::Float(f) => Box::new(f),
-        PdoValue::String(s) => Box::new(String::from_utf8_lossy(&s).to_string()),
+/// Parameter types PgParam can encode in binary format
+fn binds_natively(ty: &Type) -> bool {
+    matches!(
+        *ty,
+        Type::BOOL
+            | Type::INT2
+            | Type::INT4
+            | Type::INT8
+            | Type::OID
+            | Type::FLOAT4
+            | Type::FLOAT8
+            | Type::BYTEA
+            | Type::TEXT
+            | Type::VARCHAR
+            | Type::BPCHAR
+            | Type::NAME
+            | Type::UNKNOWN
+            | Type::JSON
+            | Type::JSONB
+            | Type::XML
+    )
+}
+
+/// Text form of a bound value, as PHP would send it to libpq
+fn pdo_text(value: &PdoValue) -> Vec<u8> {
+    match value {
+        PdoValue::Null => Vec::new(),
+        PdoValue::Bool(b) => {
+            if *b {
+                b"1".to_vec()
+            } else {
+                b"0".to_vec()
+            }
+        }
+        PdoValue::Int(i) => i.to_string().into_bytes(),
+        PdoValue::Float(f) => f.to_string().into_bytes(),
+        PdoValue::String(s) => s.clone(),
     }
 }
 
-fn pg_to_pdo(row: &postgres::Row, i: usize) -> PdoValue {
-    let column = &row.columns()[i];
-    let type_name = column.type_().name();
+/// A bound PDO value, encoded for whichever type the server inferred
+#[derive(Debug)]
+struct PgParam(PdoValue);
+
+impl PgParam {
+    fn as_bool(&self) -> Result<bool, BoxError> {
+        match &self.0 {
+            PdoValue::Bool(b) => Ok(*b),
+            PdoValue::Int(i) => Ok(*i != 0),
+            PdoValue::Float(f) => Ok(*f != 0.0),
+            PdoValue::String(s) => match String::from_utf8_lossy(s)
+                .trim()
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "t" | "true" | "y" | "yes" | "on" | "1" => Ok(true),
+                "f" | "false" | "n" | "no" | "off" | "0" | "" => Ok(false),
+                other => {
+                    Err(format!("invalid input syntax for type boolean: \"{}\"", other).into())
+                }
+            },
+            PdoValue::Null => Ok(false),
+        }
+    }
 
-    match type_name {
-        "bool" => {
-            let val: Option<bool> = row.get(i);
-            val.map(PdoValue::Bool).unwrap_or(PdoValue::Null)
+    fn as_int(&self) -> Result<i64, BoxError> {
+        match &self.0 {
+            PdoValue::Int(i) => Ok(*i),
+            PdoValue::Bool(b) => Ok(*b as i64),
+            PdoValue::Float(f) if f.fract() == 0.0 => Ok(*f as i64),
+            other => {
+                let text = String::from_utf8_lossy(&pdo_text(other)).into_owned();
+                text.trim().parse().map_err(|_| {
+                    format!("invalid input syntax for type integer: \"{}\"", text).into()
+                })
+            }
         }
-        "int2" | "int4" | "int8" => {
-            if type_name == "int2" {
-                let val: Option<i16> = row.get(i);
-                val.map(|v| PdoValue::Int(v as i64))
-                    .unwrap_or(PdoValue::Null)
-            } else if type_name == "int4" {
-                let val: Option<i32> = row.get(i);
-                val.map(|v| PdoValue::Int(v as i64))
-                    .unwrap_or(PdoValue::Null)
-            } else {
-                let val: Option<i64> = row.get(i);
-                val.map(PdoValue::Int).unwrap_or(PdoValue::Null)
+    }
+
+    fn as_float(&self) -> Result<f64, BoxError> {
+        match &self.0 {
+            PdoValue::Float(f) => Ok(*f),
+            PdoValue::Int(i) => Ok(*i as f64),
+            PdoValue::Bool(b) => Ok(*b as i64 as f64),
+            other => {
+                let text = String::from_utf8_lossy(&pdo_text(other)).into_owned();
+                text.trim().parse().map_err(|_| {
+                    format!(
+                        "invalid input syntax for type double precision: \"{}\"",
+                        text
+                    )
+                    .into()
+                })
             }
         }
-        "float4" | "float8" => {
-            if type_name == "float4" {
-                let val: Option<f32> = row.get(i);
-                val.map(|v| PdoValue::Float(v as f64))
-                    .unwrap_or(PdoValue::Null)
-            } else {
-                let val: Option<f64> = row.get(i);
-                val.map(PdoValue::Float).unwrap_or(PdoValue::Null)
+    }
+}
+
+impl ToSql for PgParam {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+        if matches!(self.0, PdoValue::Null) {
+            return Ok(IsNull::Yes);
+        }
+        match *ty {
+            Type::BOOL => self.as_bool()?.to_sql(ty, out),
+            Type::INT2 => i16::try_from(self.as_int()?)?.to_sql(ty, out),
+            Type::INT4 => i32::try_from(self.as_int()?)?.to_sql(ty, out),
+            Type::INT8 => self.as_int()?.to_sql(ty, out),
+            Type::OID => u32::try_from(self.as_int()?)?.to_sql(ty, out),
+            Type::FLOAT4 => (self.as_float()? as f32).to_sql(ty, out),
+            Type::FLOAT8 => self.as_float()?.to_sql(ty, out),
+            Type::JSONB => {
+                // jsonb's binary format is a version byte followed by the text
+                out.put_u8(1);
+                out.extend_from_slice(&pdo_text(&self.0));
+                Ok(IsNull::No)
+            }
+            _ => {
+                out.extend_from_slice(&pdo_text(&self.0));
+                Ok(IsNull::No)
+            }
+        }
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+/// A column value rendered as the text libpq would hand to PHP
+struct PgText(Vec<u8>);
+
+impl<'a> FromSql<'a> for PgText {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+        let text = match *ty {
+            Type::NUMERIC => numeric_to_text(raw)?.into_bytes(),
+            Type::UUID => uuid_to_text(raw)?.into_bytes(),
+            Type::DATE => NaiveDate::from_sql(ty, raw)?.to_string().into_bytes(),
+            Type::TIME => NaiveTime::from_sql(ty, raw)?.to_string().into_bytes(),
+            Type::TIMESTAMP => NaiveDateTime::from_sql(ty, raw)?.to_string().into_bytes(),
+            Type::TIMESTAMPTZ => {
+                format!("{}+00", DateTime::<Utc>::from_sql(ty, raw)?.naive_utc()).into_bytes()
             }
+            Type::JSONB => raw.get(1..).unwrap_or_default().to_vec(),
+            // Text, json, xml, enums and the like are sent as their text
+            _ => raw.to_vec(),
+        };
+        Ok(PgText(text))
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+}
+
+/// Render a binary NUMERIC (base-10000 digit groups) as decimal text
+fn numeric_to_text(raw: &[u8]) -> Result<String, BoxError> {
+    let field = |i: usize| -> Result<i16, BoxError> {
+        raw.get(i * 2..i * 2 + 2)
+            .map(|b| i16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(|| "invalid numeric value".into())
+    };
+    let ndigits = field(0)? as usize;
+    let weight = field(1)? as i32;
+    let sign = field(2)? as u16;
+    let dscale = field(3)? as usize;
+
+    match sign {
+        0xC000 => return Ok("NaN".to_string()),
+        0xD000 => return Ok("Infinity".to_string()),
+        0xF000 => return Ok("-Infinity".to_string()),
+        _ => {}
+    }
+
+    let digits = (0..ndigits)
+        .map(|i| field(4 + i))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Digit group whose value is multiplied by 10000^power
+    let group = |power: i32| {
+        usize::try_from(weight - power)
+            .ok()
+            .and_then(|i| digits.get(i).copied())
+            .unwrap_or(0)
+    };
+
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&group(weight).to_string());
+        for power in (0..weight).rev() {
+            text.push_str(&format!("{:04}", group(power)));
         }
-        _ => {
-            // Default to string for other types
-            let val: Option<String> = row.get(i);
-            val.map(|s| PdoValue::String(s.into_bytes()))
-                .unwrap_or(PdoValue::Null)
+    }
+    if dscale > 0 {
+        let mut fraction = String::new();
+        let mut power = -1;
+        while fraction.len() < dscale {
+            fraction.push_str(&format!("{:04}", group(power)));
+            power -= 1;
         }
+        fraction.truncate(dscale);
+        text.push('.');
+        text.push_str(&fraction);
     }
+    Ok(text)
 }
 
-/// Preprocess SQL to convert named parameters (:name) or positional (?) to PostgreSQL positional ($1, $2, ...)
-fn preprocess_sql(sql: &str) -> (String, Vec<String>) {
-    let mut processed = String::new();
-    let mut param_order = Vec::new();
-    let mut chars = sql.chars().peekable();
-    let mut in_quote: Option<char> = None;
-    let mut param_index = 1;
-
-    while let Some(c) = chars.next() {
-        match c {
-            '\'' | '"' | '`' => {
-                if let Some(q) = in_quote {
-                    if q == c {
-                        in_quote = None;
-                    }
-                } else {
-                    in_quote = Some(c);
+fn uuid_to_text(raw: &[u8]) -> Result<String, BoxError> {
+    if raw.len() != 16 {
+        return Err("invalid uuid value".into());
+    }
+    let hex: String = raw.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Convert a result column to the value pdo_pgsql returns: integers, floats
+/// and booleans natively, bytea as raw bytes and everything else as text
+fn pg_to_pdo(row: &postgres::Row, i: usize) -> PdoValue {
+    let value = match *row.columns()[i].type_() {
+        Type::BOOL => row
+            .try_get::<_, Option<bool>>(i)
+            .map(|v| v.map(PdoValue::Bool)),
+        Type::INT2 => row
+            .try_get::<_, Option<i16>>(i)
+            .map(|v| v.map(|n| PdoValue::Int(n.into()))),
+        Type::INT4 => row
+            .try_get::<_, Option<i32>>(i)
+            .map(|v| v.map(|n| PdoValue::Int(n.into()))),
+        Type::INT8 => row
+            .try_get::<_, Option<i64>>(i)
+            .map(|v| v.map(PdoValue::Int)),
+        Type::OID => row
+            .try_get::<_, Option<u32>>(i)
+            .map(|v| v.map(|n| PdoValue::Int(n.into()))),
+        Type::FLOAT4 => row
+            .try_get::<_, Option<f32>>(i)
+            .map(|v| v.map(|n| PdoValue::Float(n.into()))),
+        Type::FLOAT8 => row
+            .try_get::<_, Option<f64>>(i)
+            .map(|v| v.map(PdoValue::Float)),
+        Type::BYTEA => row
+            .try_get::<_, Option<Vec<u8>>>(i)
+            .map(|v| v.map(PdoValue::String)),
+        _ => row
+            .try_get::<_, Option<PgText>>(i)
+            .map(|v| v.map(|t| PdoValue::String(t.0))),
+    };
+    value.ok().flatten().unwrap_or(PdoValue::Null)
+}
+
+/// Walk SQL outside string literals, quoted identifiers, dollar-quoted
+/// bodies and comments, replacing each `?` or `:name` placeholder with what
+/// `replace` returns for its zero-based index and name. As in pdo_pgsql,
+/// `??` is a literal `?` (for the jsonb operators) and `::` a cast.
+fn rewrite_placeholders(
+    sql: &str,
+    mut replace: impl FnMut(usize, Option<&str>) -> String,
+) -> String {
+    let bytes = sql.as_bytes();
+    let ident_len = |from: usize| {
+        bytes[from.min(bytes.len())..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+            .count()
+    };
+    let mut out = String::with_capacity(sql.len());
+    let mut index = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        match bytes[i] {
+            quote @ (b'\'' | b'"') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
                 }
-                processed.push(c);
+                i = (i + 1).min(bytes.len());
             }
-            '?' if in_quote.is_none() => {
-                processed.push('$');
-                processed.push_str(&param_index.to_string());
-                param_index += 1;
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = sql[i..].find('\n').map_or(bytes.len(), |n| i + n);
             }
-            ':' if in_quote.is_none() => {
-                let mut name = String::new();
-                while let Some(&next_c) = chars.peek() {
-                    if next_c.is_alphanumeric() || next_c == '_' {
-                        name.push(chars.next().unwrap());
-                    } else {
-                        break;
-                    }
-                }
-                if name.is_empty() {
-                    processed.push(':');
-                } else {
-                    processed.push('$');
-                    processed.push_str(&param_index.to_string());
-                    param_index += 1;
-                    param_order.push(name);
-                }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = sql[i + 2..].find("*/").map_or(bytes.len(), |n| i + n + 4);
+            }
+            b'$' if !bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+                && bytes.get(i + 1 + ident_len(i + 1)) == Some(&b'$') =>
+            {
+                let tag = &sql[i..i + ident_len(i + 1) + 2];
+                let body = i + tag.len();
+                i = sql[body..]
+                    .find(tag)
+                    .map_or(bytes.len(), |n| body + n + tag.len());
+            }
+            b'?' if bytes.get(i + 1) == Some(&b'?') => {
+                out.push('?');
+                i += 2;
+                continue;
+            }
+            b'?' => {
+                out.push_str(&replace(index, None));
+                index += 1;
+                i += 1;
+                continue;
+            }
+            b':' if bytes.get(i + 1) == Some(&b':') => {
+                i += 2;
+            }
+            b':' if ident_len(i + 1) > 0 => {
+                let len = ident_len(i + 1);
+                out.push_str(&replace(index, Some(&sql[i + 1..i + 1 + len])));
+                index += 1;
+                i += 1 + len;
+                continue;
             }
             _ => {
-                processed.push(c);
+                i += 1;
+                while i < bytes.len() && !b"'\"-/$?:".contains(&bytes[i]) {
+                    i += 1;
+                }
             }
         }
+        out.push_str(&sql[start..i]);
     }
 
-    (processed, param_order)
+    out
+}
+
+/// Rewrite `?` and `:name` placeholders to PostgreSQL's `$1, $2, ...`,
+/// returning the name of each placeholder in order (None for `?`)
+fn preprocess_sql(sql: &str) -> (String, Vec<Option<String>>) {
+    let mut placeholders = Vec::new();
+    let processed = rewrite_placeholders(sql, |index, name| {
+        placeholders.push(name.map(str::to_string));
+        format!("${}", index + 1)
+    });
+    (processed, placeholders)
+}
+
+/// Rewrite placeholders flagged in `inline` into quoted literals and
+/// renumber the rest, returning the SQL with the values still to bind
+fn inline_literals(sql: &str, values: &[PdoValue], inline: &[bool]) -> (String, Vec<PdoValue>) {
+    let mut kept = Vec::new();
+    let rewritten = rewrite_placeholders(sql, |index, _| {
+        let value = values.get(index).cloned().unwrap_or(PdoValue::Null);
+        if !inline.get(index).copied().unwrap_or(false) {
+            kept.push(value);
+            return format!("${}", kept.len());
+        }
+        match value {
+            PdoValue::Null => "NULL".to_string(),
+            value => {
+                String::from_utf8_lossy(&super::quote_doubling_single_quotes(&pdo_text(&value)))
+                    .into_owned()
+            }
+        }
+    });
+    (rewritten, kept)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn names(placeholders: &[Option<String>]) -> Vec<Option<&str>> {
+        placeholders.iter().map(|n| n.as_deref()).collect()
+    }
+
     #[test]
     fn test_preprocess_sql_pgsql() {
         let sql = "SELECT * FROM users WHERE id = :id AND name = :name";
         let (processed, order) = preprocess_sql(sql);
         assert_eq!(processed, "SELECT * FROM users WHERE id = $1 AND name = $2");
-        assert_eq!(order, vec!["id", "name"]);
+        assert_eq!(names(&order), vec![Some("id"), Some("name")]);
 
         let sql_q = "SELECT * FROM users WHERE id = ? AND status = ?";
         let (processed, order) = preprocess_sql(sql_q);
@@ -501,7 +852,7 @@ mod tests {
             processed,
             "SELECT * FROM users WHERE id = $1 AND status = $2"
         );
-        assert!(order.is_empty());
+        assert_eq!(names(&order), vec![None, None]);
 
         let sql_mixed = "SELECT * FROM users WHERE email = ':not_a_param' AND id = :id";
         let (processed, order) = preprocess_sql(sql_mixed);
@@ -509,6 +860,73 @@ mod tests {
             processed,
             "SELECT * FROM users WHERE email = ':not_a_param' AND id = $1"
         );
-        assert_eq!(order, vec!["id"]);
+        assert_eq!(names(&order), vec![Some("id")]);
+    }
+
+    #[test]
+    fn test_preprocess_sql_skips_casts_comments_and_dollar_quotes() {
+        let (processed, order) =
+            preprocess_sql("SELECT :v::int, data ?? 'k', '?' -- what?\n FROM t WHERE x = ?");
+        assert_eq!(
+            processed,
+            "SELECT $1::int, data ? 'k', '?' -- what?\n FROM t WHERE x = $2"
+        );
+        assert_eq!(names(&order), vec![Some("v"), None]);
+
+        let (processed, order) =
+            preprocess_sql("SELECT $fn$ body ? :x $fn$, $$ ? $$, /* :c */ $1, :é");
+        assert_eq!(
+            processed,
+            "SELECT $fn$ body ? :x $fn$, $$ ? $$, /* :c */ $1, :é"
+        );
+        assert!(order.is_empty());
+    }
+
+    #[test]
+    fn test_inline_literals_renumbers_remaining_placeholders() {
+        let values = vec![
+            PdoValue::String(b"2024-01-02".to_vec()),
+            PdoValue::Int(7),
+            PdoValue::String(b"it's".to_vec()),
+        ];
+        let (sql, kept) = inline_literals(
+            "INSERT INTO t VALUES (:d, :n, :s)",
+            &values,
+            &[true, false, true],
+        );
+        assert_eq!(sql, "INSERT INTO t VALUES ('2024-01-02', $1, 'it''s')");
+        assert_eq!(kept, vec![PdoValue::Int(7)]);
+    }
+
+    #[test]
+    fn test_conninfo_quotes_values() {
+        let params =
+            parse_params("host=localhost;port=5432;dbname=app;user=me;password=\"p w'd\"").unwrap();
+        assert_eq!(
+            conninfo(&params),
+            "host='localhost' port='5432' dbname='app' user='me' password='p w\\'d'"
+        );
+        let config = conninfo(&params).parse::<postgres::Config>().unwrap();
+        assert_eq!(config.get_dbname(), Some("app"));
+        assert_eq!(config.get_ports(), &[5432]);
+        assert_eq!(config.get_password(), Some(&b"p w'd"[..]));
+    }
+
+    #[test]
+    fn test_numeric_to_text() {
+        let numeric = |weight: i16, sign: u16, dscale: i16, digits: &[i16]| {
+            let mut raw = Vec::new();
+            for field in [digits.len() as i16, weight, sign as i16, dscale]
+                .into_iter()
+                .chain(digits.iter().copied())
+            {
+                raw.extend_from_slice(&field.to_be_bytes());
+            }
+            numeric_to_text(&raw).unwrap()
+        };
+        assert_eq!(numeric(1, 0, 3, &[1, 2345, 6780]), "12345.678");
+        assert_eq!(numeric(-2, 0x4000, 6, &[5000]), "-0.000050");
+        assert_eq!(numeric(2, 0, 0, &[3]), "300000000");
+        assert_eq!(numeric(0, 0xC000, 0, &[]), "NaN");
     }
 }
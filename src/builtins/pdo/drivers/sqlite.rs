@@ -4,13 +4,14 @@
 //!
 //! Reference: $PHP_SRC_PATH/ext/pdo_sqlite/sqlite_driver.c
 
-use crate::builtins::pdo::driver::{PdoConnection, PdoDriver, PdoStatement};
+use crate::builtins::pdo::driver::{PdoConnection, PdoDriver, PdoStatement, SqlFunction};
 use crate::builtins::pdo::types::{
     Attribute, ColumnMeta, FetchMode, FetchedRow, ParamIdentifier, ParamType, PdoError, PdoValue,
 };
 use crate::core::value::Handle;
 use indexmap::IndexMap;
 use rusqlite::Connection;
+use rusqlite::functions::FunctionFlags;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
@@ -32,7 +33,13 @@ impl PdoDriver for SqliteDriver {
     ) -> Result<Box<dyn PdoConnection>, PdoError> {
         let path = super::strip_driver_prefix(dsn, self.name());
 
-        let conn = Connection::open(path).map_err(|e| PdoError::ConnectionFailed(e.to_string()))?;
+        // ":memory:" is a private in-memory database and "" a private temporary
+        // one that SQLite deletes on close
+        let conn = match path {
+            ":memory:" => Connection::open_in_memory(),
+            _ => Connection::open(path),
+        }
+        .map_err(|e| PdoError::ConnectionFailed(e.to_string()))?;
 
         Ok(Box::new(SqliteConnection {
             conn: Arc::new(Mutex::new(conn)),
@@ -55,9 +62,11 @@ struct SqliteConnection {
 impl PdoConnection for SqliteConnection {
     fn prepare(&mut self, sql: &str) -> Result<Box<dyn PdoStatement>, PdoError> {
         // Validate SQL syntax by preparing it
+        self.last_error = None;
         self.conn.lock().unwrap().prepare(sql).map_err(|e| {
-            let error = PdoError::SyntaxError("HY000".to_string(), Some(e.to_string()));
-            self.last_error = Some(("HY000".to_string(), None, Some(e.to_string())));
+            let info = sqlite_error_info(&e);
+            let error = PdoError::SyntaxError(info.0.clone(), info.2.clone());
+            self.last_error = Some(info);
             error
         })?;
 
@@ -76,6 +85,7 @@ impl PdoConnection for SqliteConnection {
     }
 
     fn exec(&mut self, sql: &str) -> Result<i64, PdoError> {
+        self.last_error = None;
         self.conn
             .lock()
             .unwrap()
@@ -83,7 +93,7 @@ impl PdoConnection for SqliteConnection {
             .map(|n| n as i64)
             .map_err(|e| {
                 let error = PdoError::ExecutionFailed(e.to_string());
-                self.last_error = Some(("HY000".to_string(), None, Some(e.to_string())));
+                self.last_error = Some(sqlite_error_info(&e));
                 error
             })
     }
@@ -150,9 +160,57 @@ impl PdoConnection for SqliteConnection {
         self.attributes.get(&attr).copied()
     }
 
-    fn quote(&self, s: &str, _type: ParamType) -> String {
-        // Basic SQLite quoting
-        format!("'{}'", s.replace('\'', "''"))
+    fn server_version(&self) -> Option<String> {
+        Some(rusqlite::version().to_string())
+    }
+
+    fn quote(&self, s: &[u8], _type: ParamType) -> Result<Vec<u8>, PdoError> {
+        // SQL text cannot carry NUL bytes, so such values become a blob literal
+        if s.contains(&0) {
+            let mut quoted = Vec::with_capacity(s.len() * 2 + 3);
+            quoted.extend_from_slice(b"x'");
+            for b in s {
+                quoted.extend_from_slice(format!("{:02x}", b).as_bytes());
+            }
+            quoted.push(b'\'');
+            return Ok(quoted);
+        }
+        Ok(super::quote_doubling_single_quotes(s))
+    }
+
+    fn create_function(
+        &mut self,
+        name: &str,
+        num_args: i32,
+        deterministic: bool,
+        function: SqlFunction,
+    ) -> Result<(), PdoError> {
+        let mut flags = FunctionFlags::SQLITE_UTF8;
+        if deterministic {
+            flags |= FunctionFlags::SQLITE_DETERMINISTIC;
+        }
+
+        // The function only runs while a statement on this connection executes,
+        // so unwinding out of it cannot leave shared state half-updated
+        let mut function = std::panic::AssertUnwindSafe(function);
+
+        self.last_error = None;
+        self.conn
+            .lock()
+            .unwrap()
+            .create_scalar_function(name, num_args, flags, move |ctx| {
+                let args = (0..ctx.len())
+                    .map(|i| rusqlite_to_pdo(ctx.get_raw(i).into()))
+                    .collect();
+                (*function)(args)
+                    .map(|result| pdo_to_rusqlite(&result))
+                    .map_err(|msg| rusqlite::Error::UserFunctionError(msg.into()))
+            })
+            .map_err(|e| {
+                let error = PdoError::Error(e.to_string());
+                self.last_error = Some(sqlite_error_info(&e));
+                error
+            })
     }
 
     fn error_code(&self) -> String {
@@ -183,25 +241,11 @@ struct SqliteStatement {
     current_row: usize,
 }
 
-impl PdoStatement for SqliteStatement {
-    fn bind_param(
-        &mut self,
-        param: ParamIdentifier,
-        value: PdoValue,
-        param_type: ParamType,
-    ) -> Result<(), PdoError> {
-        self.bound_params.insert(param, (value, param_type));
-        Ok(())
-    }
-
-    fn execute(
-        &mut self,
-        params: Option<&[(ParamIdentifier, PdoValue)]>,
-    ) -> Result<bool, PdoError> {
+impl SqliteStatement {
+    /// Run the statement, buffering any result rows.
+    fn run(&mut self, params: Option<&[(ParamIdentifier, PdoValue)]>) -> rusqlite::Result<()> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn
-            .prepare(&self.sql)
-            .map_err(|e| PdoError::ExecutionFailed(e.to_string()))?;
+        let mut stmt = conn.prepare(&self.sql)?;
 
         // Combine bound_params and provided params
         let mut all_params = self.bound_params.clone();
@@ -246,8 +290,7 @@ impl PdoStatement for SqliteStatement {
             } else {
                 let params: Vec<_> = rusqlite_params.into_iter().map(|(_, v)| v).collect();
                 stmt.execute(rusqlite::params_from_iter(params))
-            }
-            .map_err(|e| PdoError::ExecutionFailed(e.to_string()))?;
+            }?;
 
             self.row_count = affected as i64;
             self.results = None;
@@ -257,18 +300,12 @@ impl PdoStatement for SqliteStatement {
             } else {
                 let params: Vec<_> = rusqlite_params.into_iter().map(|(_, v)| v).collect();
                 stmt.query(rusqlite::params_from_iter(params))
-            }
-            .map_err(|e| PdoError::ExecutionFailed(e.to_string()))?;
+            }?;
 
-            while let Some(row) = query_result
-                .next()
-                .map_err(|e| PdoError::ExecutionFailed(e.to_string()))?
-            {
+            while let Some(row) = query_result.next()? {
                 let mut pdo_row = Vec::new();
                 for i in 0..self.column_count {
-                    let val: rusqlite::types::Value = row
-                        .get(i)
-                        .map_err(|e| PdoError::ExecutionFailed(e.to_string()))?;
+                    let val: rusqlite::types::Value = row.get(i)?;
                     pdo_row.push(rusqlite_to_pdo(val));
                 }
                 rows.push(pdo_row);
@@ -277,6 +314,30 @@ impl PdoStatement for SqliteStatement {
             self.results = Some(rows);
         }
         self.current_row = 0;
+        Ok(())
+    }
+}
+
+impl PdoStatement for SqliteStatement {
+    fn bind_param(
+        &mut self,
+        param: ParamIdentifier,
+        value: PdoValue,
+        param_type: ParamType,
+    ) -> Result<(), PdoError> {
+        self.bound_params.insert(param, (value, param_type));
+        Ok(())
+    }
+
+    fn execute(
+        &mut self,
+        params: Option<&[(ParamIdentifier, PdoValue)]>,
+    ) -> Result<bool, PdoError> {
+        self.last_error = None;
+        self.run(params).map_err(|e| {
+            self.last_error = Some(sqlite_error_info(&e));
+            PdoError::ExecutionFailed(e.to_string())
+        })?;
         Ok(true)
     }
 
@@ -365,6 +426,20 @@ impl PdoStatement for SqliteStatement {
     }
 }
 
+/// Build the (SQLSTATE, driver code, message) triple for a SQLite failure.
+/// Reference: pdo_sqlite_error in $PHP_SRC_PATH/ext/pdo_sqlite/sqlite_driver.c
+fn sqlite_error_info(e: &rusqlite::Error) -> (String, Option<i64>, Option<String>) {
+    let code = match e {
+        rusqlite::Error::SqliteFailure(err, _) => Some((err.extended_code & 0xff) as i64),
+        _ => None,
+    };
+    let state = match code {
+        Some(19) => "23000", // SQLITE_CONSTRAINT
+        _ => "HY000",
+    };
+    (state.to_string(), code, Some(e.to_string()))
+}
+
 /// Helper to convert PdoValue to rusqlite Value
 fn pdo_to_rusqlite(val: &PdoValue) -> rusqlite::types::Value {
     match val {
@@ -420,11 +495,13 @@ mod tests {
         let driver = SqliteDriver;
         let conn = driver.connect("sqlite::memory:", None, None, &[]).unwrap();
 
-        assert_eq!(conn.quote("hello", ParamType::Str), "'hello'");
+        assert_eq!(conn.quote(b"hello", ParamType::Str).unwrap(), b"'hello'");
         assert_eq!(
-            conn.quote("'; DROP TABLE test; --", ParamType::Str),
-            "'''; DROP TABLE test; --'"
+            conn.quote(b"'; DROP TABLE test; --", ParamType::Str)
+                .unwrap(),
+            b"'''; DROP TABLE test; --'"
         );
+        assert_eq!(conn.quote(b"a\0b", ParamType::Str).unwrap(), b"x'610062'");
     }
 
     #[test]
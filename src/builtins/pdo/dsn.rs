@@ -0,0 +1,207 @@
+//! PDO data source name parsing
+//!
+//! Resolves `pdo.dsn.*` aliases and `uri:` indirection down to a
+//! "driver:params" DSN, and splits driver params into key/value pairs.
+//!
+//! Reference: $PHP_SRC_PATH/ext/pdo/pdo_dbh.c - PHP_METHOD(PDO, __construct)
+//! Reference: $PHP_SRC_PATH/ext/pdo/pdo.c - php_pdo_parse_data_source
+
+use super::drivers::DriverRegistry;
+use super::types::PdoError;
+
+pub(crate) const INVALID_DSN: &str = "invalid data source name";
+
+fn invalid_dsn() -> PdoError {
+    PdoError::Error(INVALID_DSN.to_string())
+}
+
+/// Resolve a DSN passed to PDO::__construct into (driver, params).
+///
+/// A DSN without a colon names an alias looked up as the `pdo.dsn.<name>`
+/// ini setting; `uri:<location>` reads the real DSN from the first line of a
+/// file.
+pub fn resolve_dsn(
+    dsn: &str,
+    ini: impl Fn(&str) -> Option<String>,
+) -> Result<(String, String), PdoError> {
+    let mut dsn = dsn.to_string();
+
+    if !dsn.contains(':') {
+        dsn = ini(&format!("pdo.dsn.{}", dsn)).ok_or_else(invalid_dsn)?;
+        if !dsn.contains(':') {
+            return Err(PdoError::Error(format!(
+                "{} (via INI: {})",
+                INVALID_DSN, dsn
+            )));
+        }
+    }
+
+    if let Some(uri) = dsn.strip_prefix("uri:") {
+        dsn = dsn_from_uri(uri)
+            .ok_or_else(|| PdoError::Error("invalid data source URI".to_string()))?;
+        if !dsn.contains(':') {
+            return Err(PdoError::Error(format!("{} (via URI)", INVALID_DSN)));
+        }
+    }
+
+    let (driver, params) = DriverRegistry::parse_dsn(&dsn)?;
+    Ok((driver.to_string(), params.to_string()))
+}
+
+/// Read the first line of the file a `uri:` DSN points at
+fn dsn_from_uri(uri: &str) -> Option<String> {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let contents = std::fs::read(path).ok()?;
+    let line = contents.split(|b| *b == b'\n').next()?;
+    Some(
+        String::from_utf8_lossy(line)
+            .trim_end_matches('\r')
+            .to_string(),
+    )
+}
+
+/// Split "key=value;key=value" driver params.
+///
+/// Keys and unquoted values are trimmed, and `;;` inside an unquoted value is
+/// a literal semicolon. Values wrapped in single or double quotes may contain
+/// semicolons as-is. Segments without `=` are ignored, as in PHP.
+pub fn parse_params(params: &str) -> Result<Vec<(String, String)>, PdoError> {
+    let mut pairs = Vec::new();
+    let mut rest = params;
+
+    while !rest.is_empty() {
+        let Some(pos) = rest.find(['=', ';']) else {
+            break;
+        };
+        if rest.as_bytes()[pos] == b';' {
+            rest = &rest[pos + 1..];
+            continue;
+        }
+
+        let key = rest[..pos].trim();
+        let (value, remaining) = parse_value(&rest[pos + 1..])?;
+        if !key.is_empty() {
+            pairs.push((key.to_string(), value));
+        }
+        rest = remaining;
+    }
+
+    Ok(pairs)
+}
+
+/// Read one value, returning it with the input left after its terminating `;`
+fn parse_value(input: &str) -> Result<(String, &str), PdoError> {
+    let trimmed = input.trim_start();
+    if let Some(quote) = trimmed.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let body = &trimmed[1..];
+        let end = body.find(quote).ok_or_else(invalid_dsn)?;
+        let after = body[end + 1..].trim_start();
+        let rest = match after.strip_prefix(';') {
+            Some(rest) => rest,
+            None if after.is_empty() => after,
+            None => return Err(invalid_dsn()),
+        };
+        return Ok((body[..end].to_string(), rest));
+    }
+
+    let mut value = String::new();
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == ';' {
+            if input[i + 1..].starts_with(';') {
+                value.push(';');
+                chars.next();
+                continue;
+            }
+            return Ok((value.trim().to_string(), &input[i + 1..]));
+        }
+        value.push(c);
+    }
+    Ok((value.trim().to_string(), ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(params: &str) -> Vec<(String, String)> {
+        parse_params(params).unwrap()
+    }
+
+    #[test]
+    fn test_parse_params_plain_pairs() {
+        assert_eq!(
+            pairs(" host = localhost ;port=3306;dbname=test;"),
+            vec![
+                ("host".to_string(), "localhost".to_string()),
+                ("port".to_string(), "3306".to_string()),
+                ("dbname".to_string(), "test".to_string()),
+            ]
+        );
+        assert_eq!(
+            pairs("flag;charset=utf8mb4"),
+            vec![("charset".to_string(), "utf8mb4".to_string())]
+        );
+        assert!(pairs("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_params_semicolons_in_values() {
+        assert_eq!(
+            pairs("password=a;;b;user=root"),
+            vec![
+                ("password".to_string(), "a;b".to_string()),
+                ("user".to_string(), "root".to_string()),
+            ]
+        );
+        assert_eq!(
+            pairs("password=\"x;y=z\";unix_socket='/tmp/my sql.sock'"),
+            vec![
+                ("password".to_string(), "x;y=z".to_string()),
+                ("unix_socket".to_string(), "/tmp/my sql.sock".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_params_rejects_unterminated_quotes() {
+        assert!(parse_params("password=\"open;dbname=x").is_err());
+        assert!(parse_params("password=\"a\"b;dbname=x").is_err());
+    }
+
+    #[test]
+    fn test_resolve_dsn_aliases() {
+        let ini = |name: &str| (name == "pdo.dsn.main").then(|| "sqlite::memory:".to_string());
+        assert_eq!(
+            resolve_dsn("main", ini).unwrap(),
+            ("sqlite".to_string(), ":memory:".to_string())
+        );
+        assert_eq!(
+            resolve_dsn("missing", ini).unwrap_err().to_string(),
+            INVALID_DSN
+        );
+        assert_eq!(
+            resolve_dsn(":memory:", ini).unwrap_err().to_string(),
+            INVALID_DSN
+        );
+    }
+
+    #[test]
+    fn test_resolve_dsn_uri() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dsn.txt");
+        std::fs::write(&path, "mysql:host=db;port=3307\nignored\n").unwrap();
+
+        let uri = format!("uri:file://{}", path.display());
+        assert_eq!(
+            resolve_dsn(&uri, |_| None).unwrap(),
+            ("mysql".to_string(), "host=db;port=3307".to_string())
+        );
+        assert_eq!(
+            resolve_dsn("uri:file:///nonexistent/dsn", |_| None)
+                .unwrap_err()
+                .to_string(),
+            "invalid data source URI"
+        );
+    }
+}
@@ -23,17 +23,25 @@
 
 pub mod driver;
 pub mod drivers;
+pub mod dsn;
 #[cfg(test)]
 mod tests;
 pub mod types;
 
-use crate::core::value::{ArrayData, ArrayKey, Handle, ObjectData, Val, Visibility};
+use crate::builtins::exception::create_exception;
+use crate::core::value::{ArrayData, ArrayKey, Handle, ObjectData, Symbol, Val, Visibility};
+use crate::runtime::pdo_extension::PdoExtensionData;
 use crate::runtime::registry::{ExtensionRegistry, NativeClassDef, NativeMethodEntry};
-use crate::vm::engine::{PropertyCollectionMode, VM};
-use drivers::DriverRegistry;
+use crate::vm::engine::ErrorLevel;
+use crate::vm::engine::{PropertyCollectionMode, VM, VmError};
+use indexmap::IndexMap;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
-use types::{Attribute, ParamIdentifier, ParamType, PdoValue};
+use types::{
+    Attribute, ErrorMode, FetchConversion, NullHandling, ParamIdentifier, ParamType, PdoError,
+    PdoValue,
+};
 
 /// Register the PDO extension components to the registry
 pub fn register_pdo_extension_to_registry(registry: &mut ExtensionRegistry) {
@@ -70,6 +78,26 @@ pub fn register_pdo_extension_to_registry(registry: &mut ExtensionRegistry) {
         },
     );
 
+    pdo_methods.insert(
+        b"quote".to_vec(),
+        NativeMethodEntry {
+            handler: php_pdo_quote,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    pdo_methods.insert(
+        b"sqliteCreateFunction".to_vec(),
+        NativeMethodEntry {
+            handler: php_pdo_sqlite_create_function,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
     pdo_methods.insert(
         b"beginTransaction".to_vec(),
         NativeMethodEntry {
@@ -185,6 +213,18 @@ pub fn register_pdo_extension_to_registry(registry: &mut ExtensionRegistry) {
     pdo_constants.insert(b"FETCH_BOUND".to_vec(), (Val::Int(6), Visibility::Public));
     pdo_constants.insert(b"FETCH_COLUMN".to_vec(), (Val::Int(7), Visibility::Public));
     pdo_constants.insert(b"FETCH_CLASS".to_vec(), (Val::Int(8), Visibility::Public));
+    pdo_constants.insert(
+        b"FETCH_KEY_PAIR".to_vec(),
+        (Val::Int(12), Visibility::Public),
+    );
+    pdo_constants.insert(
+        b"FETCH_GROUP".to_vec(),
+        (Val::Int(types::FETCH_GROUP), Visibility::Public),
+    );
+    pdo_constants.insert(
+        b"FETCH_UNIQUE".to_vec(),
+        (Val::Int(types::FETCH_UNIQUE), Visibility::Public),
+    );
 
     pdo_constants.insert(
         b"ERRMODE_SILENT".to_vec(),
@@ -212,10 +252,18 @@ pub fn register_pdo_extension_to_registry(registry: &mut ExtensionRegistry) {
         b"ATTR_SERVER_VERSION".to_vec(),
         (Val::Int(4), Visibility::Public),
     );
+    pdo_constants.insert(
+        b"ATTR_ORACLE_NULLS".to_vec(),
+        (Val::Int(11), Visibility::Public),
+    );
     pdo_constants.insert(
         b"ATTR_STATEMENT_CLASS".to_vec(),
         (Val::Int(13), Visibility::Public),
     );
+    pdo_constants.insert(
+        b"ATTR_STRINGIFY_FETCHES".to_vec(),
+        (Val::Int(17), Visibility::Public),
+    );
     pdo_constants.insert(
         b"ATTR_DEFAULT_FETCH_MODE".to_vec(),
         (Val::Int(19), Visibility::Public),
@@ -225,6 +273,23 @@ pub fn register_pdo_extension_to_registry(registry: &mut ExtensionRegistry) {
         (Val::Int(20), Visibility::Public),
     );
 
+    // ATTR_ORACLE_NULLS values
+    pdo_constants.insert(b"NULL_NATURAL".to_vec(), (Val::Int(0), Visibility::Public));
+    pdo_constants.insert(
+        b"NULL_EMPTY_STRING".to_vec(),
+        (Val::Int(1), Visibility::Public),
+    );
+    pdo_constants.insert(
+        b"NULL_TO_STRING".to_vec(),
+        (Val::Int(2), Visibility::Public),
+    );
+
+    // pdo_sqlite's sqliteCreateFunction() flag
+    pdo_constants.insert(
+        b"SQLITE_DETERMINISTIC".to_vec(),
+        (Val::Int(SQLITE_DETERMINISTIC), Visibility::Public),
+    );
+
     registry.register_class(NativeClassDef {
         name: b"PDO".to_vec(),
         parent: None,
@@ -351,30 +416,116 @@ pub fn register_pdo_extension_to_registry(registry: &mut ExtensionRegistry) {
         },
     );
 
+    st_methods.insert(
+        b"setFetchMode".to_vec(),
+        NativeMethodEntry {
+            handler: php_pdo_stmt_set_fetch_mode,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    // IteratorAggregate, so foreach walks the result set
+    st_methods.insert(
+        b"getIterator".to_vec(),
+        NativeMethodEntry {
+            handler: php_pdo_stmt_get_iterator,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
     registry.register_class(NativeClassDef {
         name: b"PDOStatement".to_vec(),
         parent: None,
         is_interface: false,
         is_trait: false,
         is_final: false,
-        interfaces: vec![b"Iterator".to_vec()],
+        interfaces: vec![b"IteratorAggregate".to_vec()],
         methods: st_methods,
         constants: HashMap::new(),
         constructor: None,
         extension_name: None,
     });
 
+    // The iterator PDOStatement::getIterator() returns
+    let mut iterator_methods = HashMap::new();
+    iterator_methods.insert(
+        b"rewind".to_vec(),
+        NativeMethodEntry {
+            handler: php_pdo_iterator_rewind,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    iterator_methods.insert(
+        b"valid".to_vec(),
+        NativeMethodEntry {
+            handler: php_pdo_iterator_valid,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    iterator_methods.insert(
+        b"current".to_vec(),
+        NativeMethodEntry {
+            handler: php_pdo_iterator_current,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    iterator_methods.insert(
+        b"key".to_vec(),
+        NativeMethodEntry {
+            handler: php_pdo_iterator_key,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    iterator_methods.insert(
+        b"next".to_vec(),
+        NativeMethodEntry {
+            handler: php_pdo_iterator_next,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    registry.register_class(NativeClassDef {
+        name: b"InternalIterator".to_vec(),
+        parent: None,
+        is_interface: false,
+        is_trait: false,
+        is_final: true,
+        interfaces: vec![b"Iterator".to_vec()],
+        methods: iterator_methods,
+        constants: HashMap::new(),
+        constructor: None,
+        extension_name: None,
+    });
+
     // 3. Register PDOException Class
     registry.register_class(NativeClassDef {
         name: b"PDOException".to_vec(),
-        parent: Some(b"Exception".to_vec()),
+        parent: Some(b"RuntimeException".to_vec()),
         is_interface: false,
         is_trait: false,
         is_final: false,
         interfaces: Vec::new(),
         methods: HashMap::new(),
         constants: HashMap::new(),
-        constructor: None,
+        constructor: Some(crate::builtins::exception::exception_construct),
         extension_name: None,
     });
 
@@ -382,6 +533,9 @@ pub fn register_pdo_extension_to_registry(registry: &mut ExtensionRegistry) {
     register_pdo_constants(registry);
 }
 
+/// PDO::SQLITE_DETERMINISTIC, SQLite's own SQLITE_DETERMINISTIC bit
+const SQLITE_DETERMINISTIC: i64 = 0x800;
+
 /// Helper to get connection ID from PDO object
 fn get_pdo_connection_id(vm: &VM, handle: Handle) -> Result<u64, String> {
     let obj_handle = match &vm.arena.get(handle).value {
@@ -440,6 +594,254 @@ fn get_pdo_statement_id(vm: &VM, handle: Handle) -> Result<u64, String> {
     }
 }
 
+/// Driver error triple: (SQLSTATE, driver error code, driver message)
+type ErrorInfo = (String, Option<i64>, Option<String>);
+
+/// PDO::ATTR_ERRMODE of a connection; PHP 8 defaults to ERRMODE_EXCEPTION
+fn connection_error_mode(vm: &VM, conn_id: u64) -> ErrorMode {
+    vm.context
+        .get_extension_data::<PdoExtensionData>()
+        .and_then(|ext| ext.error_modes.get(&conn_id).copied())
+        .unwrap_or(ErrorMode::Exception)
+}
+
+/// Prefer the error the driver recorded, falling back to the returned error
+/// for drivers that do not track one.
+fn driver_error_info(recorded: ErrorInfo, err: &PdoError) -> ErrorInfo {
+    if recorded.0 != "00000" {
+        return recorded;
+    }
+    match err {
+        PdoError::SyntaxError(state, msg) => (state.clone(), None, msg.clone()),
+        other => ("HY000".to_string(), None, Some(other.to_string())),
+    }
+}
+
+/// Human-readable description of a SQLSTATE class
+/// Reference: $PHP_SRC_PATH/ext/pdo/pdo_sqlstate.c
+fn sqlstate_description(state: &str) -> &'static str {
+    match state {
+        "00000" => "No error",
+        "01000" => "Warning",
+        "08001" => "SQL client unable to establish SQL connection",
+        "08003" => "Connection does not exist",
+        "08006" => "Connection failure",
+        "21S01" => "Insert value list does not match column list",
+        "22001" => "String data, right truncated",
+        "22003" => "Numeric value out of range",
+        "22012" => "Division by zero",
+        "23000" => "Integrity constraint violation",
+        "25000" => "Invalid transaction state",
+        "28000" => "Invalid authorization specification",
+        "22P02" => "Invalid text representation",
+        "23502" => "Not null violation",
+        "23505" => "Unique violation",
+        "42000" => "Syntax error or access violation",
+        "42601" => "Syntax error",
+        "42703" => "Undefined column",
+        "42P01" => "Undefined table",
+        "42S01" => "Base table or view already exists",
+        "42S02" => "Base table or view not found",
+        "42S22" => "Column not found",
+        "HY000" => "General error",
+        "HY093" => "Invalid parameter number",
+        "IM001" => "Driver does not support this function",
+        _ => "<<Unknown error>>",
+    }
+}
+
+fn error_info_to_array(vm: &mut VM, info: ErrorInfo) -> Handle {
+    let (state, code, msg) = info;
+    let mut arr = ArrayData::new();
+    arr.push(vm.arena.alloc(Val::String(Rc::new(state.into_bytes()))));
+    arr.push(vm.arena.alloc(code.map(Val::Int).unwrap_or(Val::Null)));
+    arr.push(
+        vm.arena.alloc(
+            msg.map(|s| Val::String(Rc::new(s.into_bytes())))
+                .unwrap_or(Val::Null),
+        ),
+    );
+    vm.arena.alloc(Val::Array(Rc::new(arr)))
+}
+
+/// Report a failed driver call according to the connection's error mode:
+/// SILENT returns false, WARNING also raises E_WARNING, and EXCEPTION throws
+/// a PDOException whose code is the SQLSTATE.
+/// Reference: pdo_handle_error in $PHP_SRC_PATH/ext/pdo/pdo_dbh.c
+fn pdo_handle_error(
+    vm: &mut VM,
+    conn_id: u64,
+    func: &str,
+    info: ErrorInfo,
+) -> Result<Handle, String> {
+    let message = match (&info.1, &info.2) {
+        (Some(code), msg) => format!(
+            "SQLSTATE[{}]: {}: {} {}",
+            info.0,
+            sqlstate_description(&info.0),
+            code,
+            msg.as_deref().unwrap_or("")
+        ),
+        (None, Some(msg)) => format!(
+            "SQLSTATE[{}]: {}: {}",
+            info.0,
+            sqlstate_description(&info.0),
+            msg
+        ),
+        (None, None) => format!("SQLSTATE[{}]: {}", info.0, sqlstate_description(&info.0)),
+    };
+
+    match connection_error_mode(vm, conn_id) {
+        ErrorMode::Silent => Ok(vm.arena.alloc(Val::Bool(false))),
+        ErrorMode::Warning => {
+            vm.trigger_error(ErrorLevel::Warning, &format!("{}: {}", func, message));
+            Ok(vm.arena.alloc(Val::Bool(false)))
+        }
+        ErrorMode::Exception => {
+            let class_sym = vm.context.interner.intern(b"PDOException");
+            let mut properties = vm.collect_properties(class_sym, PropertyCollectionMode::All);
+            let message_sym = vm.context.interner.intern(b"message");
+            let code_sym = vm.context.interner.intern(b"code");
+            let error_info_sym = vm.context.interner.intern(b"errorInfo");
+            properties.insert(
+                message_sym,
+                vm.arena
+                    .alloc(Val::String(Rc::new(message.clone().into_bytes()))),
+            );
+            properties.insert(
+                code_sym,
+                vm.arena
+                    .alloc(Val::String(Rc::new(info.0.clone().into_bytes()))),
+            );
+            properties.insert(error_info_sym, error_info_to_array(vm, info));
+
+            let payload_handle = vm.arena.alloc(Val::ObjPayload(ObjectData {
+                class: class_sym,
+                properties,
+                internal: None,
+                dynamic_properties: HashSet::new(),
+            }));
+            let ex_handle = vm.arena.alloc(Val::Object(payload_handle));
+
+            vm.throw_exception(ex_handle);
+            Err(message)
+        }
+    }
+}
+
+/// Throw a PDOException with code 0, as PDO::__construct does for bad DSNs
+/// and failed connections
+fn throw_pdo_exception(vm: &mut VM, message: String) -> String {
+    let ex_handle = create_exception(vm, b"PDOException", &message);
+    vm.throw_exception(ex_handle);
+    message
+}
+
+thread_local! {
+    /// VM running the current driver call. SQLite evaluates functions registered
+    /// with PDO::sqliteCreateFunction from inside that call, and they reach the
+    /// VM through this pointer.
+    static CALLBACK_VM: Cell<*mut VM> = const { Cell::new(std::ptr::null_mut()) };
+    /// Exception thrown by such a function, rethrown once the driver call returns
+    static CALLBACK_EXCEPTION: Cell<Option<Handle>> = const { Cell::new(None) };
+}
+
+/// Run a driver call that may invoke user-defined SQL functions. If one of
+/// them threw, its exception is rethrown in place of the driver's result.
+fn run_driver_call<T>(vm: &mut VM, call: impl FnOnce() -> T) -> Result<T, String> {
+    let previous = CALLBACK_VM.with(|current| current.replace(vm as *mut VM));
+    let result = call();
+    CALLBACK_VM.with(|current| current.set(previous));
+
+    match CALLBACK_EXCEPTION.with(Cell::take) {
+        Some(ex_handle) => Err(vm.throw_exception(ex_handle)),
+        None => Ok(result),
+    }
+}
+
+/// Body of a function registered with PDO::sqliteCreateFunction: call the PHP
+/// callback with the SQL arguments and hand its return value back to SQLite
+fn call_sql_function(callback: Handle, values: Vec<PdoValue>) -> Result<PdoValue, String> {
+    let vm_ptr = CALLBACK_VM.with(Cell::get);
+    if vm_ptr.is_null() {
+        return Err("user-defined function called outside of a PDO call".into());
+    }
+    // SAFETY: the pointer is set by run_driver_call, which holds the VM
+    // exclusively and does not touch it until the driver call returns
+    let vm = unsafe { &mut *vm_ptr };
+
+    let args = values
+        .into_iter()
+        .map(|value| pdo_val_to_handle(vm, FetchConversion::default(), value))
+        .collect();
+    match vm.call_callable(callback, args) {
+        Ok(result) => Ok(handle_to_pdo_val(vm, result)),
+        Err(VmError::Exception(ex_handle)) => {
+            CALLBACK_EXCEPTION.with(|pending| pending.set(Some(ex_handle)));
+            Err("An error occurred while invoking the callback".into())
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Validate a PDO::ATTR_ERRMODE value
+fn error_mode_from_handle(vm: &VM, handle: Handle) -> Result<ErrorMode, String> {
+    match &vm.arena.get(handle).value {
+        Val::Int(i) => ErrorMode::from_i64(*i).ok_or_else(|| {
+            "PDO::setAttribute(): Argument #2 ($value) must be one of the PDO::ERRMODE_* constants"
+                .to_string()
+        }),
+        _ => Err("PDO::setAttribute(): Argument #2 ($value) must be of type int".to_string()),
+    }
+}
+
+/// Connection that owns a statement, for error-mode lookups
+fn statement_connection_id(vm: &VM, stmt_id: u64) -> Option<u64> {
+    vm.context
+        .get_extension_data::<PdoExtensionData>()
+        .and_then(|ext| ext.statement_connections.get(&stmt_id).copied())
+}
+
+/// Apply a PDO::ATTR_STRINGIFY_FETCHES or PDO::ATTR_ORACLE_NULLS value
+fn update_fetch_conversion(
+    vm: &VM,
+    conversion: &mut FetchConversion,
+    attr: Attribute,
+    handle: Handle,
+) -> Result<(), String> {
+    let value = &vm.arena.get(handle).value;
+    match attr {
+        Attribute::StringifyFetches => conversion.stringify = value.to_bool(),
+        Attribute::OracleNulls => {
+            let Val::Int(i) = value else {
+                return Err(
+                    "PDO::setAttribute(): Argument #2 ($value) must be of type int".to_string(),
+                );
+            };
+            conversion.nulls = NullHandling::from_i64(*i).ok_or_else(|| {
+                "PDO::setAttribute(): Argument #2 ($value) must be one of the PDO::NULL_* constants"
+                    .to_string()
+            })?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn connection_fetch_conversion(vm: &VM, conn_id: u64) -> FetchConversion {
+    vm.context
+        .get_extension_data::<PdoExtensionData>()
+        .and_then(|ext| ext.fetch_conversions.get(&conn_id).copied())
+        .unwrap_or_default()
+}
+
+/// Conversions configured on the connection that owns a statement
+fn statement_fetch_conversion(vm: &VM, stmt_id: u64) -> FetchConversion {
+    statement_connection_id(vm, stmt_id)
+        .map(|conn_id| connection_fetch_conversion(vm, conn_id))
+        .unwrap_or_default()
+}
+
 // --- PDO Native Methods ---
 
 /// PDO::__construct(string $dsn, ?string $username = null, ?string $password = null, ?array $options = null)
@@ -492,23 +894,42 @@ pub fn php_pdo_construct(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
         }
     }
 
-    // Parse DSN and connect
+    // Resolve aliases and uri: indirection, then connect
+    let ini_settings = &vm.context.config.ini_settings;
     let (driver_name, conn_str) =
-        DriverRegistry::parse_dsn(&dsn).map_err(|e| format!("PDO::__construct(): {}", e))?;
+        match dsn::resolve_dsn(&dsn, |name| ini_settings.get(name).cloned()) {
+            Ok(resolved) => resolved,
+            Err(e) => return Err(throw_pdo_exception(vm, e.to_string())),
+        };
 
     let pdo_ext = vm
         .context
         .get_extension_data::<crate::runtime::pdo_extension::PdoExtensionData>()
         .ok_or("PDO extension not initialized")?;
-    let registry = pdo_ext.driver_registry.as_ref();
 
-    let driver = registry
-        .get(driver_name)
-        .ok_or_else(|| format!("PDO::__construct(): Driver '{}' not found", driver_name))?;
+    let Some(driver) = pdo_ext.driver_registry.get_arc(&driver_name) else {
+        return Err(throw_pdo_exception(vm, "could not find driver".to_string()));
+    };
+
+    let conn = match driver.connect(
+        &conn_str,
+        username.as_deref(),
+        password.as_deref(),
+        &options,
+    ) {
+        Ok(conn) => conn,
+        Err(e) => return Err(throw_pdo_exception(vm, e.to_string())),
+    };
 
-    let conn = driver
-        .connect(conn_str, username.as_deref(), password.as_deref(), &options)
-        .map_err(|e| format!("PDO::__construct(): Connection failed: {}", e))?;
+    let error_mode = options
+        .iter()
+        .find(|(attr, _)| *attr == Attribute::ErrorMode)
+        .map(|(_, val)| error_mode_from_handle(vm, *val))
+        .transpose()?;
+    let mut fetch_conversion = FetchConversion::default();
+    for (attr, val) in &options {
+        update_fetch_conversion(vm, &mut fetch_conversion, *attr, *val)?;
+    }
 
     // Store connection in context
     let conn_id = vm.context.next_resource_id;
@@ -516,6 +937,12 @@ pub fn php_pdo_construct(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
     vm.context
         .resource_manager
         .register(conn_id, Rc::new(std::cell::RefCell::new(conn)));
+    if let Some(ext) = vm.context.get_extension_data_mut::<PdoExtensionData>() {
+        if let Some(mode) = error_mode {
+            ext.error_modes.insert(conn_id, mode);
+        }
+        ext.fetch_conversions.insert(conn_id, fetch_conversion);
+    }
 
     // Store ID in object
     if let Some(this_handle) = vm.frames.last().and_then(|f| f.this) {
@@ -559,10 +986,14 @@ pub fn php_pdo_prepare(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .get::<Box<dyn crate::builtins::pdo::driver::PdoConnection>>(conn_id)
         .ok_or("PDO::prepare(): Invalid connection")?;
 
-    let stmt = conn_ref
-        .borrow_mut()
-        .prepare(&query)
-        .map_err(|e| format!("PDO::prepare(): {}", e))?;
+    let prepared = conn_ref.borrow_mut().prepare(&query);
+    let stmt = match prepared {
+        Ok(stmt) => stmt,
+        Err(e) => {
+            let info = driver_error_info(conn_ref.borrow().error_info(), &e);
+            return pdo_handle_error(vm, conn_id, "PDO::prepare()", info);
+        }
+    };
 
     // Create PDOStatement object
     let stmt_class_sym = vm.context.interner.intern(b"PDOStatement");
@@ -582,6 +1013,9 @@ pub fn php_pdo_prepare(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     vm.context
         .resource_manager
         .register(stmt_id, Rc::new(std::cell::RefCell::new(stmt)));
+    if let Some(ext) = vm.context.get_extension_data_mut::<PdoExtensionData>() {
+        ext.statement_connections.insert(stmt_id, conn_id);
+    }
 
     // Store ID and default fetch mode in PDOStatement object
     let id_sym = vm.context.interner.intern(b"__id");
@@ -623,12 +1057,136 @@ pub fn php_pdo_exec(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .get::<Box<dyn crate::builtins::pdo::driver::PdoConnection>>(conn_id)
         .ok_or("PDO::exec(): Invalid connection")?;
 
-    let affected = conn_ref
-        .borrow_mut()
-        .exec(&sql)
-        .map_err(|e| format!("PDO::exec(): {}", e))?;
+    let result = run_driver_call(vm, || conn_ref.borrow_mut().exec(&sql))?;
+    match result {
+        Ok(affected) => Ok(vm.arena.alloc(Val::Int(affected))),
+        Err(e) => {
+            let info = driver_error_info(conn_ref.borrow().error_info(), &e);
+            pdo_handle_error(vm, conn_id, "PDO::exec()", info)
+        }
+    }
+}
 
-    Ok(vm.arena.alloc(Val::Int(affected)))
+/// PDO::quote(string $string, int $type = PDO::PARAM_STR)
+pub fn php_pdo_quote(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in PDO::quote")?;
+    let conn_id = get_pdo_connection_id(vm, this_handle)?;
+
+    if args.is_empty() {
+        return Err("PDO::quote() expects at least 1 parameter".into());
+    }
+
+    let value = match &vm.arena.get(args[0]).value {
+        v @ (Val::String(_) | Val::Int(_) | Val::Float(_) | Val::Bool(_) | Val::Null) => {
+            v.to_php_string_bytes()
+        }
+        _ => return Err("PDO::quote(): Argument #1 ($string) must be of type string".into()),
+    };
+
+    let param_type = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => ParamType::from_i64(*i).unwrap_or(ParamType::Str),
+        _ => ParamType::Str,
+    };
+
+    // Numeric input quoted as PARAM_INT becomes a bare integer literal;
+    // anything else still goes through the driver's string escaping.
+    if matches!(param_type, ParamType::Int) {
+        let numeric = std::str::from_utf8(&value).ok().and_then(|s| {
+            let s = s.trim();
+            s.parse::<i64>().ok().or_else(|| {
+                s.parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .map(|f| f as i64)
+            })
+        });
+        if let Some(n) = numeric {
+            return Ok(vm
+                .arena
+                .alloc(Val::String(Rc::new(n.to_string().into_bytes()))));
+        }
+    }
+
+    let conn_ref = vm
+        .context
+        .resource_manager
+        .get::<Box<dyn crate::builtins::pdo::driver::PdoConnection>>(conn_id)
+        .ok_or("PDO::quote(): Invalid connection")?;
+
+    let quoted = conn_ref.borrow().quote(&value, param_type);
+    match quoted {
+        Ok(quoted) => Ok(vm.arena.alloc(Val::String(Rc::new(quoted)))),
+        Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
+}
+
+/// PDO::sqliteCreateFunction(string $function_name, callable $callback, int $num_args = -1, int $flags = 0)
+/// Reference: $PHP_SRC_PATH/ext/pdo_sqlite/sqlite_driver.c
+pub fn php_pdo_sqlite_create_function(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in PDO::sqliteCreateFunction")?;
+    let conn_id = get_pdo_connection_id(vm, this_handle)?;
+
+    if args.len() < 2 {
+        return Err("PDO::sqliteCreateFunction() expects at least 2 parameters".into());
+    }
+
+    let name = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => {
+            return Err(
+                "PDO::sqliteCreateFunction(): Argument #1 ($function_name) must be of type string"
+                    .into(),
+            );
+        }
+    };
+    let callback = args[1];
+    if !vm.is_callable(callback) {
+        return Err(
+            "PDO::sqliteCreateFunction(): Argument #2 ($callback) must be a valid callback".into(),
+        );
+    }
+    let num_args = args
+        .get(2)
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(-1);
+    let flags = args
+        .get(3)
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0);
+
+    let conn_ref = vm
+        .context
+        .resource_manager
+        .get::<Box<dyn crate::builtins::pdo::driver::PdoConnection>>(conn_id)
+        .ok_or("PDO::sqliteCreateFunction(): Invalid connection")?;
+
+    let registered = conn_ref.borrow_mut().create_function(
+        &name,
+        num_args as i32,
+        flags & SQLITE_DETERMINISTIC != 0,
+        Box::new(move |values| call_sql_function(callback, values)),
+    );
+    match registered {
+        Ok(()) => {
+            if let Some(ext) = vm.context.get_extension_data_mut::<PdoExtensionData>() {
+                ext.sql_functions.entry(conn_id).or_default().push(callback);
+            }
+            Ok(vm.arena.alloc(Val::Bool(true)))
+        }
+        // Only pdo_sqlite provides this method
+        Err(PdoError::InvalidContext(_)) => {
+            Err("Call to undefined method PDO::sqliteCreateFunction()".into())
+        }
+        Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
 }
 
 pub fn php_pdo_begin_transaction(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
@@ -726,6 +1284,25 @@ pub fn php_pdo_set_attribute(vm: &mut VM, args: &[Handle]) -> Result<Handle, Str
 
     let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
     let conn_id = get_pdo_connection_id(vm, this_handle)?;
+
+    // The error mode is PDO-level state shared by every driver
+    if attr == Attribute::ErrorMode {
+        let mode = error_mode_from_handle(vm, args[1])?;
+        if let Some(ext) = vm.context.get_extension_data_mut::<PdoExtensionData>() {
+            ext.error_modes.insert(conn_id, mode);
+        }
+        return Ok(vm.arena.alloc(Val::Bool(true)));
+    }
+
+    if matches!(attr, Attribute::StringifyFetches | Attribute::OracleNulls) {
+        let mut conversion = connection_fetch_conversion(vm, conn_id);
+        update_fetch_conversion(vm, &mut conversion, attr, args[1])?;
+        if let Some(ext) = vm.context.get_extension_data_mut::<PdoExtensionData>() {
+            ext.fetch_conversions.insert(conn_id, conversion);
+        }
+        return Ok(vm.arena.alloc(Val::Bool(true)));
+    }
+
     let conn_ref = vm
         .context
         .resource_manager
@@ -757,12 +1334,32 @@ pub fn php_pdo_get_attribute(vm: &mut VM, args: &[Handle]) -> Result<Handle, Str
 
     let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
     let conn_id = get_pdo_connection_id(vm, this_handle)?;
+
+    if attr == Attribute::ErrorMode {
+        let mode = connection_error_mode(vm, conn_id);
+        return Ok(vm.arena.alloc(Val::Int(mode as i64)));
+    }
+    if attr == Attribute::StringifyFetches {
+        let stringify = connection_fetch_conversion(vm, conn_id).stringify;
+        return Ok(vm.arena.alloc(Val::Bool(stringify)));
+    }
+    if attr == Attribute::OracleNulls {
+        let nulls = connection_fetch_conversion(vm, conn_id).nulls;
+        return Ok(vm.arena.alloc(Val::Int(nulls as i64)));
+    }
+
     let conn_ref = vm
         .context
         .resource_manager
         .get::<Box<dyn crate::builtins::pdo::driver::PdoConnection>>(conn_id)
         .ok_or("Invalid connection")?;
 
+    if attr == Attribute::ServerVersion
+        && let Some(version) = conn_ref.borrow().server_version()
+    {
+        return Ok(vm.arena.alloc(Val::String(Rc::new(version.into_bytes()))));
+    }
+
     match conn_ref.borrow().get_attribute(attr) {
         Some(handle) => Ok(handle),
         None => Ok(vm.arena.alloc(Val::Null)),
@@ -774,8 +1371,11 @@ pub fn php_pdo_query(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         return Err("PDO::query() expects at least 1 parameter".into());
     }
 
-    // 1. Prepare
+    // 1. Prepare (a failure has already been reported per the error mode)
     let stmt = php_pdo_prepare(vm, &[args[0]])?;
+    if matches!(vm.arena.get(stmt).value, Val::Bool(false)) {
+        return Ok(stmt);
+    }
 
     // 2. Execute (we need the statement ID to execute it)
     let stmt_id = get_pdo_statement_id(vm, stmt)?;
@@ -785,10 +1385,12 @@ pub fn php_pdo_query(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .get::<Box<dyn crate::builtins::pdo::driver::PdoStatement>>(stmt_id)
         .ok_or("query(): Statement vanished")?;
 
-    stmt_ref
-        .borrow_mut()
-        .execute(None)
-        .map_err(|e| format!("PDO::query(): {}", e))?;
+    let executed = run_driver_call(vm, || stmt_ref.borrow_mut().execute(None))?;
+    if let Err(e) = executed {
+        let info = driver_error_info(stmt_ref.borrow().error_info(), &e);
+        let conn_id = statement_connection_id(vm, stmt_id).unwrap_or_default();
+        return pdo_handle_error(vm, conn_id, "PDO::query()", info);
+    }
 
     Ok(stmt)
 }
@@ -813,19 +1415,8 @@ pub fn php_pdo_error_info(vm: &mut VM, _args: &[Handle]) -> Result<Handle, Strin
         .resource_manager
         .get::<Box<dyn crate::builtins::pdo::driver::PdoConnection>>(conn_id)
         .ok_or("Invalid connection")?;
-    let (state, code, msg) = conn_ref.borrow().error_info();
-
-    let mut arr = ArrayData::new();
-    arr.push(vm.arena.alloc(Val::String(Rc::new(state.into_bytes()))));
-    arr.push(vm.arena.alloc(code.map(Val::Int).unwrap_or(Val::Null)));
-    arr.push(
-        vm.arena.alloc(
-            msg.map(|s| Val::String(Rc::new(s.into_bytes())))
-                .unwrap_or(Val::Null),
-        ),
-    );
-
-    Ok(vm.arena.alloc(Val::Array(Rc::new(arr))))
+    let info = conn_ref.borrow().error_info();
+    Ok(error_info_to_array(vm, info))
 }
 
 // --- PDOStatement Native Methods ---
@@ -865,10 +1456,12 @@ pub fn php_pdo_stmt_execute(vm: &mut VM, args: &[Handle]) -> Result<Handle, Stri
         .resource_manager
         .get::<Box<dyn crate::builtins::pdo::driver::PdoStatement>>(stmt_id)
         .ok_or("Invalid statement")?;
-    stmt_ref
-        .borrow_mut()
-        .execute(params.as_deref())
-        .map_err(|e| e.to_string())?;
+    let executed = run_driver_call(vm, || stmt_ref.borrow_mut().execute(params.as_deref()))?;
+    if let Err(e) = executed {
+        let info = driver_error_info(stmt_ref.borrow().error_info(), &e);
+        let conn_id = statement_connection_id(vm, stmt_id).unwrap_or_default();
+        return pdo_handle_error(vm, conn_id, "PDOStatement::execute()", info);
+    }
 
     Ok(vm.arena.alloc(Val::Bool(true)))
 }
@@ -947,65 +1540,401 @@ pub fn php_pdo_stmt_bind_value(vm: &mut VM, args: &[Handle]) -> Result<Handle, S
 
 pub fn php_pdo_stmt_fetch(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
-    let stmt_id = get_pdo_statement_id(vm, this_handle)?;
 
-    let fetch_mode = if !args.is_empty() {
-        match &vm.arena.get(args[0]).value {
+    let mut spec = statement_fetch_spec(vm, this_handle);
+    if let Some(mode_handle) = args.first() {
+        spec.mode = match &vm.arena.get(*mode_handle).value {
             Val::Int(i) => types::FetchMode::from_i64(*i).unwrap_or(types::FetchMode::Both),
             _ => types::FetchMode::Both,
-        }
-    } else {
-        // Look for fetchMode property on the statement object
-        let fetch_mode_sym = vm.context.interner.intern(b"fetchMode");
-        let mut mode = types::FetchMode::Both;
+        };
+    }
 
-        if let Val::Object(payload_h) = &vm.arena.get(this_handle).value {
-            if let Val::ObjPayload(obj) = &vm.arena.get(*payload_h).value {
-                if let Some(val_h) = obj.properties.get(&fetch_mode_sym) {
-                    if let Val::Int(m) = &vm.arena.get(*val_h).value {
-                        mode = types::FetchMode::from_i64(*m).unwrap_or(types::FetchMode::Both);
-                    }
-                }
+    match fetch_statement_value(vm, this_handle, &spec)? {
+        Some(value) => Ok(value),
+        None => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
+}
+
+/// The statement's fetch mode with the extra arguments setFetchMode() took
+/// for it. The mode is FETCH_BOTH unless setFetchMode() changed it or
+/// PDO::ATTR_DEFAULT_FETCH_MODE was copied in when the statement was prepared.
+struct FetchSpec {
+    mode: types::FetchMode,
+    /// Column index for FETCH_COLUMN
+    column: i64,
+    /// Class for FETCH_CLASS; stdClass when none was given
+    class: Option<Symbol>,
+    /// Constructor arguments for FETCH_CLASS
+    ctor_args: Vec<Handle>,
+}
+
+fn statement_fetch_spec(vm: &mut VM, this_handle: Handle) -> FetchSpec {
+    let fetch_mode_sym = vm.context.interner.intern(b"fetchMode");
+    let fetch_column_sym = vm.context.interner.intern(b"fetchColumn");
+    let fetch_class_sym = vm.context.interner.intern(b"fetchClass");
+    let fetch_ctor_args_sym = vm.context.interner.intern(b"fetchCtorArgs");
+
+    let mut spec = FetchSpec {
+        mode: types::FetchMode::Both,
+        column: 0,
+        class: None,
+        ctor_args: Vec::new(),
+    };
+    let Val::Object(payload_h) = &vm.arena.get(this_handle).value else {
+        return spec;
+    };
+    let Val::ObjPayload(obj) = &vm.arena.get(*payload_h).value else {
+        return spec;
+    };
+    let property = |sym| obj.properties.get(&sym).map(|h| &vm.arena.get(*h).value);
+
+    if let Some(Val::Int(m)) = property(fetch_mode_sym) {
+        spec.mode = types::FetchMode::from_i64(*m).unwrap_or(types::FetchMode::Both);
+    }
+    if let Some(Val::Int(column)) = property(fetch_column_sym) {
+        spec.column = *column;
+    }
+    let class_name = match property(fetch_class_sym) {
+        Some(Val::String(name)) => Some(name.clone()),
+        _ => None,
+    };
+    if let Some(Val::Array(ctor_args)) = property(fetch_ctor_args_sym) {
+        spec.ctor_args = ctor_args.map.values().copied().collect();
+    }
+    spec.class = class_name.map(|name| vm.context.interner.intern(&name));
+    spec
+}
+
+/// Fetch the next row shaped by `spec`, or None once the result set is exhausted
+fn fetch_statement_value(
+    vm: &mut VM,
+    this_handle: Handle,
+    spec: &FetchSpec,
+) -> Result<Option<Handle>, String> {
+    let stmt_id = get_pdo_statement_id(vm, this_handle)?;
+    let conversion = statement_fetch_conversion(vm, stmt_id);
+
+    match spec.mode {
+        types::FetchMode::Column => {
+            let Some(types::FetchedRow::Num(mut values)) =
+                fetch_statement_row(vm, stmt_id, types::FetchMode::Num)?
+            else {
+                return Ok(None);
+            };
+            if spec.column < 0 || spec.column as usize >= values.len() {
+                let ex = create_exception(vm, b"ValueError", "Invalid column index");
+                return Err(vm.throw_exception(ex));
             }
+            let value = values.swap_remove(spec.column as usize);
+            Ok(Some(pdo_val_to_handle(vm, conversion, value)))
         }
-        mode
+        types::FetchMode::Class => {
+            let Some(types::FetchedRow::Assoc(row)) =
+                fetch_statement_row(vm, stmt_id, types::FetchMode::Assoc)?
+            else {
+                return Ok(None);
+            };
+            fetch_class_object(vm, spec, conversion, row).map(Some)
+        }
+        mode => {
+            let row = fetch_statement_row(vm, stmt_id, mode)?;
+            Ok(row.map(|row| fetched_row_to_val(vm, conversion, row)))
+        }
+    }
+}
+
+/// Build a FETCH_CLASS row. Columns are assigned as properties before the
+/// constructor runs, as PDO does without FETCH_PROPS_LATE.
+/// Reference: $PHP_SRC_PATH/ext/pdo/pdo_stmt.c - do_fetch (PDO_FETCH_CLASS)
+fn fetch_class_object(
+    vm: &mut VM,
+    spec: &FetchSpec,
+    conversion: FetchConversion,
+    row: IndexMap<String, PdoValue>,
+) -> Result<Handle, String> {
+    let class = spec
+        .class
+        .unwrap_or_else(|| vm.context.interner.intern(b"stdClass"));
+    let mut properties = vm.collect_properties(class, PropertyCollectionMode::All);
+    for (key, val) in row {
+        let key_sym = vm.context.interner.intern(key.as_bytes());
+        properties.insert(key_sym, pdo_val_to_handle(vm, conversion, val));
+    }
+    let obj_data = ObjectData {
+        class,
+        properties,
+        internal: None,
+        dynamic_properties: HashSet::new(),
     };
+    let payload_handle = vm.arena.alloc(Val::ObjPayload(obj_data));
+    let obj_handle = vm.arena.alloc(Val::Object(payload_handle));
+
+    let constructor = vm.context.interner.intern(b"__construct");
+    if vm.find_method(class, constructor).is_some() {
+        match vm.call_method_with_args(obj_handle, constructor, &spec.ctor_args) {
+            Ok(_) => {}
+            Err(VmError::Exception(ex_handle)) => return Err(vm.throw_exception(ex_handle)),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(obj_handle)
+}
 
+fn fetch_statement_row(
+    vm: &mut VM,
+    stmt_id: u64,
+    fetch_mode: types::FetchMode,
+) -> Result<Option<types::FetchedRow>, String> {
     let stmt_ref = vm
         .context
         .resource_manager
         .get::<Box<dyn crate::builtins::pdo::driver::PdoStatement>>(stmt_id)
         .ok_or("Invalid statement")?;
-    let row_opt = stmt_ref
+    let row = stmt_ref
         .borrow_mut()
         .fetch(fetch_mode)
         .map_err(|e| e.to_string())?;
+    Ok(row)
+}
 
-    match row_opt {
-        Some(row) => Ok(fetched_row_to_val(vm, row)),
-        None => Ok(vm.arena.alloc(Val::Bool(false))),
+/// PDOStatement::setFetchMode(int $mode, mixed ...$args): bool
+///
+/// FETCH_COLUMN takes the column index and FETCH_CLASS the class name and
+/// optional constructor arguments; they are kept with the mode for fetch()
+/// and foreach.
+pub fn php_pdo_stmt_set_fetch_mode(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
+
+    let mode = match args.first().map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) if types::FetchMode::from_i64(*i).is_some() => *i,
+        _ => {
+            return Err("PDOStatement::setFetchMode(): Argument #1 ($mode) must be a bitmask of PDO::FETCH_* constants".into());
+        }
+    };
+
+    let mut column = None;
+    let mut class_name = None;
+    let mut ctor_args = None;
+    let expected_args = match types::FetchMode::from_i64(mode) {
+        Some(types::FetchMode::Column) => {
+            if let Some(handle) = args.get(1) {
+                let Val::Int(index) = vm.arena.get(*handle).value else {
+                    let ex = create_exception(
+                        vm,
+                        b"TypeError",
+                        "PDOStatement::setFetchMode(): Argument #2 must be of type int",
+                    );
+                    return Err(vm.throw_exception(ex));
+                };
+                column = Some(index);
+            }
+            2..=2
+        }
+        Some(types::FetchMode::Class) => {
+            if let Some(handle) = args.get(1) {
+                let class = match &vm.arena.get(*handle).value {
+                    Val::String(name) => {
+                        let name = name.strip_prefix(b"\\").unwrap_or(name).to_vec();
+                        let sym = vm.context.interner.intern(&name);
+                        if !vm.class_exists(sym) {
+                            vm.trigger_autoload(sym).map_err(|e| e.to_string())?;
+                        }
+                        vm.lookup_class_symbol(sym)
+                    }
+                    _ => None,
+                };
+                let Some(class) = class else {
+                    let ex = create_exception(
+                        vm,
+                        b"TypeError",
+                        "PDOStatement::setFetchMode(): Argument #2 must be a valid class",
+                    );
+                    return Err(vm.throw_exception(ex));
+                };
+                let name = vm.context.interner.lookup(class).unwrap_or(b"").to_vec();
+                class_name = Some(vm.arena.alloc(Val::String(name.into())));
+            }
+            if let Some(handle) = args.get(2) {
+                match &vm.arena.get(*handle).value {
+                    Val::Array(_) => ctor_args = Some(*handle),
+                    Val::Null => {}
+                    _ => {
+                        let ex = create_exception(
+                            vm,
+                            b"TypeError",
+                            "PDOStatement::setFetchMode(): Argument #3 must be of type ?array",
+                        );
+                        return Err(vm.throw_exception(ex));
+                    }
+                }
+            }
+            2..=3
+        }
+        _ => 1..=1,
+    };
+    if !expected_args.contains(&args.len()) {
+        let expected = if expected_args.start() == expected_args.end() {
+            format!("exactly {}", expected_args.start())
+        } else {
+            format!("at least {}", expected_args.start())
+        };
+        let message = format!(
+            "PDOStatement::setFetchMode() expects {} argument{} for the fetch mode provided, {} given",
+            expected,
+            if *expected_args.start() == 1 { "" } else { "s" },
+            args.len()
+        );
+        let ex = create_exception(vm, b"ArgumentCountError", &message);
+        return Err(vm.throw_exception(ex));
+    }
+
+    let mode_handle = vm.arena.alloc(Val::Int(mode));
+    let column_handle = column.map(|index| vm.arena.alloc(Val::Int(index)));
+    let entries = [
+        (b"fetchMode".as_slice(), Some(mode_handle)),
+        (b"fetchColumn", column_handle),
+        (b"fetchClass", class_name),
+        (b"fetchCtorArgs", ctor_args),
+    ]
+    .map(|(name, handle)| (vm.context.interner.intern(name), handle));
+    if let Val::Object(payload_h) = vm.arena.get(this_handle).value
+        && let Val::ObjPayload(obj) = &mut vm.arena.get_mut(payload_h).value
+    {
+        for (sym, handle) in entries {
+            match handle {
+                Some(handle) => obj.properties.insert(sym, handle),
+                None => obj.properties.shift_remove(&sym),
+            };
+        }
     }
+
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+// --- PDOStatement iteration ---
+//
+// foreach over a statement asks getIterator() for an InternalIterator that
+// walks the result set with the statement's fetch mode. Like php-src, every
+// foreach starts a fresh forward-only iterator that fetches one row ahead, so
+// a consumed statement iterates as empty.
+// Reference: $PHP_SRC_PATH/ext/pdo/pdo_stmt.c - pdo_stmt_iter_get
+
+/// State of an InternalIterator over a statement's result set, kept in the
+/// iterator object's internal slot
+#[derive(Debug)]
+pub struct StatementIterator {
+    pub statement: Handle,
+    /// Fetched-ahead row, None once the result set is exhausted
+    pub current: Option<Handle>,
+    pub key: i64,
+}
+
+/// PDOStatement::getIterator(): Iterator
+pub fn php_pdo_stmt_get_iterator(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
+    let class = vm.context.interner.intern(b"InternalIterator");
+    let obj_data = ObjectData {
+        class,
+        properties: IndexMap::new(),
+        internal: Some(Rc::new(RefCell::new(StatementIterator {
+            statement: this_handle,
+            current: None,
+            key: 0,
+        }))),
+        dynamic_properties: HashSet::new(),
+    };
+    let payload_handle = vm.arena.alloc(Val::ObjPayload(obj_data));
+    Ok(vm.arena.alloc(Val::Object(payload_handle)))
+}
+
+fn statement_iterator(
+    vm: &VM,
+    this_handle: Handle,
+) -> Result<Rc<RefCell<StatementIterator>>, String> {
+    if let Val::Object(payload_h) = &vm.arena.get(this_handle).value
+        && let Val::ObjPayload(obj) = &vm.arena.get(*payload_h).value
+        && let Some(internal) = &obj.internal
+        && let Ok(iterator) = internal.clone().downcast::<RefCell<StatementIterator>>()
+    {
+        return Ok(iterator);
+    }
+    Err("InternalIterator object is uninitialized".into())
+}
+
+fn advance_statement_iterator(vm: &mut VM, this_handle: Handle, key: i64) -> Result<(), String> {
+    let iterator = statement_iterator(vm, this_handle)?;
+    let statement = iterator.borrow().statement;
+    let spec = statement_fetch_spec(vm, statement);
+    let current = fetch_statement_value(vm, statement, &spec)?;
+    let mut iterator = iterator.borrow_mut();
+    iterator.current = current;
+    iterator.key = key;
+    Ok(())
+}
+
+/// InternalIterator::rewind(): start a new pass by fetching the next row
+pub fn php_pdo_iterator_rewind(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
+    advance_statement_iterator(vm, this_handle, 0)?;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// InternalIterator::next()
+pub fn php_pdo_iterator_next(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
+    let key = statement_iterator(vm, this_handle)?.borrow().key;
+    advance_statement_iterator(vm, this_handle, key + 1)?;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// InternalIterator::valid()
+pub fn php_pdo_iterator_valid(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
+    let valid = statement_iterator(vm, this_handle)?.borrow().current.is_some();
+    Ok(vm.arena.alloc(Val::Bool(valid)))
+}
+
+/// InternalIterator::current()
+pub fn php_pdo_iterator_current(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
+    match statement_iterator(vm, this_handle)?.borrow().current {
+        Some(current) => Ok(current),
+        None => Ok(vm.arena.alloc(Val::Null)),
+    }
+}
+
+/// InternalIterator::key()
+pub fn php_pdo_iterator_key(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
+    let iterator = statement_iterator(vm, this_handle)?;
+    let iterator = iterator.borrow();
+    let key = if iterator.current.is_some() {
+        Val::Int(iterator.key)
+    } else {
+        Val::Null
+    };
+    Ok(vm.arena.alloc(key))
 }
 
 pub fn php_pdo_stmt_fetch_all(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     let this_handle = vm.frames.last().and_then(|f| f.this).ok_or("No 'this'")?;
     let stmt_id = get_pdo_statement_id(vm, this_handle)?;
 
-    let fetch_mode = if !args.is_empty() {
+    let raw_mode = if !args.is_empty() {
         match &vm.arena.get(args[0]).value {
-            Val::Int(i) => types::FetchMode::from_i64(*i).unwrap_or(types::FetchMode::Both),
-            _ => types::FetchMode::Both,
+            Val::Int(i) => *i,
+            _ => types::FetchMode::Both as i64,
         }
     } else {
         // Look for fetchMode property on the statement object
         let fetch_mode_sym = vm.context.interner.intern(b"fetchMode");
-        let mut mode = types::FetchMode::Both;
+        let mut mode = types::FetchMode::Both as i64;
 
         if let Val::Object(payload_h) = &vm.arena.get(this_handle).value {
             if let Val::ObjPayload(obj) = &vm.arena.get(*payload_h).value {
                 if let Some(val_h) = obj.properties.get(&fetch_mode_sym) {
                     if let Val::Int(m) = &vm.arena.get(*val_h).value {
-                        mode = types::FetchMode::from_i64(*m).unwrap_or(types::FetchMode::Both);
+                        mode = *m;
                     }
                 }
             }
@@ -1013,19 +1942,127 @@ pub fn php_pdo_stmt_fetch_all(vm: &mut VM, args: &[Handle]) -> Result<Handle, St
         mode
     };
 
+    let flags = raw_mode & types::FETCH_FLAGS;
+    let base_mode = types::FetchMode::from_i64(raw_mode & !types::FETCH_FLAGS)
+        .unwrap_or(types::FetchMode::Both);
+
     let stmt_ref = vm
         .context
         .resource_manager
         .get::<Box<dyn crate::builtins::pdo::driver::PdoStatement>>(stmt_id)
         .ok_or("Invalid statement")?;
+
+    let conversion = statement_fetch_conversion(vm, stmt_id);
+    let keyed = flags & types::FETCH_GROUP != 0;
+    if !keyed
+        && !matches!(
+            base_mode,
+            types::FetchMode::Column | types::FetchMode::KeyPair
+        )
+    {
+        let rows = stmt_ref
+            .borrow_mut()
+            .fetch_all(base_mode)
+            .map_err(|e| e.to_string())?;
+
+        let mut arr = ArrayData::new();
+        for row in rows {
+            arr.push(fetched_row_to_val(vm, conversion, row));
+        }
+
+        return Ok(vm.arena.alloc(Val::Array(Rc::new(arr))));
+    }
+
+    // Column-oriented and keyed modes are assembled here from FETCH_BOTH rows,
+    // mirroring pdo_stmt_fetch_all() in ext/pdo/pdo_stmt.c.
+    let column_count = stmt_ref.borrow().column_count();
     let rows = stmt_ref
         .borrow_mut()
-        .fetch_all(fetch_mode)
+        .fetch_all(types::FetchMode::Both)
         .map_err(|e| e.to_string())?;
 
+    let column_index = match base_mode {
+        types::FetchMode::Column => {
+            let index = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+                Some(Val::Int(i)) => *i,
+                _ if keyed => 1,
+                // The column setFetchMode() was given
+                None if args.is_empty() => statement_fetch_spec(vm, this_handle).column,
+                _ => 0,
+            };
+            if index < 0 || index as usize >= column_count {
+                return Err("PDOStatement::fetchAll(): Invalid column index".into());
+            }
+            Some(index as usize)
+        }
+        types::FetchMode::KeyPair => {
+            if keyed {
+                return Err("PDOStatement::fetchAll(): PDO::FETCH_KEY_PAIR cannot be combined with PDO::FETCH_GROUP or PDO::FETCH_UNIQUE".into());
+            }
+            if column_count != 2 {
+                return Err("PDOStatement::fetchAll(): PDO::FETCH_KEY_PAIR fetch mode requires the result set to contain exactly 2 columns".into());
+            }
+            None
+        }
+        _ => None,
+    };
+
     let mut arr = ArrayData::new();
+    let mut groups: IndexMap<ArrayKey, ArrayData> = IndexMap::new();
     for row in rows {
-        arr.push(fetched_row_to_val(vm, row));
+        let types::FetchedRow::Both(mut assoc, mut num) = row else {
+            continue;
+        };
+
+        if base_mode == types::FetchMode::KeyPair {
+            let mut values = num.into_iter();
+            let key = pdo_val_to_key(conversion.apply(values.next().unwrap_or(PdoValue::Null)));
+            let value = pdo_val_to_handle(vm, conversion, values.next().unwrap_or(PdoValue::Null));
+            arr.insert(key, value);
+            continue;
+        }
+
+        let group_key = if keyed {
+            let first = num.first().cloned().unwrap_or(PdoValue::Null);
+            Some(pdo_val_to_key(conversion.apply(first)))
+        } else {
+            None
+        };
+
+        let value = if let Some(index) = column_index {
+            let column = num.get(index).cloned().unwrap_or(PdoValue::Null);
+            pdo_val_to_handle(vm, conversion, column)
+        } else {
+            // The grouping column becomes the key and is dropped from the row.
+            if !num.is_empty() {
+                num.remove(0);
+            }
+            assoc.shift_remove_index(0);
+            let row = match base_mode {
+                types::FetchMode::Assoc => types::FetchedRow::Assoc(assoc),
+                types::FetchMode::Num => types::FetchedRow::Num(num),
+                types::FetchMode::Obj => types::FetchedRow::Obj(assoc),
+                _ => types::FetchedRow::Both(assoc, num),
+            };
+            fetched_row_to_val(vm, conversion, row)
+        };
+
+        match group_key {
+            None => {
+                arr.push(value);
+            }
+            Some(key) if flags & types::FETCH_UNIQUE == types::FETCH_UNIQUE => {
+                arr.insert(key, value);
+            }
+            Some(key) => {
+                groups.entry(key).or_insert_with(ArrayData::new).push(value);
+            }
+        }
+    }
+
+    for (key, group) in groups {
+        let group_handle = vm.arena.alloc(Val::Array(Rc::new(group)));
+        arr.insert(key, group_handle);
     }
 
     Ok(vm.arena.alloc(Val::Array(Rc::new(arr))))
@@ -1092,19 +2129,8 @@ pub fn php_pdo_stmt_error_info(vm: &mut VM, _args: &[Handle]) -> Result<Handle,
         .resource_manager
         .get::<Box<dyn crate::builtins::pdo::driver::PdoStatement>>(stmt_id)
         .ok_or("Invalid statement")?;
-    let (state, code, msg) = stmt_ref.borrow().error_info();
-
-    let mut arr = ArrayData::new();
-    arr.push(vm.arena.alloc(Val::String(Rc::new(state.into_bytes()))));
-    arr.push(vm.arena.alloc(code.map(Val::Int).unwrap_or(Val::Null)));
-    arr.push(
-        vm.arena.alloc(
-            msg.map(|s| Val::String(Rc::new(s.into_bytes())))
-                .unwrap_or(Val::Null),
-        ),
-    );
-
-    Ok(vm.arena.alloc(Val::Array(Rc::new(arr))))
+    let info = stmt_ref.borrow().error_info();
+    Ok(error_info_to_array(vm, info))
 }
 
 fn handle_to_pdo_val(vm: &VM, handle: Handle) -> PdoValue {
@@ -1118,8 +2144,8 @@ fn handle_to_pdo_val(vm: &VM, handle: Handle) -> PdoValue {
     }
 }
 
-fn pdo_val_to_handle(vm: &mut VM, val: PdoValue) -> Handle {
-    match val {
+fn pdo_val_to_handle(vm: &mut VM, conversion: FetchConversion, val: PdoValue) -> Handle {
+    match conversion.apply(val) {
         PdoValue::Null => vm.arena.alloc(Val::Null),
         PdoValue::Bool(b) => vm.arena.alloc(Val::Bool(b)),
         PdoValue::Int(i) => vm.arena.alloc(Val::Int(i)),
@@ -1128,14 +2154,34 @@ fn pdo_val_to_handle(vm: &mut VM, val: PdoValue) -> Handle {
     }
 }
 
-fn fetched_row_to_val(vm: &mut VM, row: types::FetchedRow) -> Handle {
+fn pdo_val_to_key(val: PdoValue) -> ArrayKey {
+    match val {
+        PdoValue::Null => ArrayKey::Str(Rc::new(Vec::new())),
+        PdoValue::Bool(b) => ArrayKey::Int(b as i64),
+        PdoValue::Int(i) => ArrayKey::Int(i),
+        PdoValue::Float(f) => ArrayKey::Int(f as i64),
+        PdoValue::String(s) => {
+            if let Some(i) = std::str::from_utf8(&s)
+                .ok()
+                .and_then(|text| text.parse::<i64>().ok())
+                .filter(|i| i.to_string().as_bytes() == s.as_slice())
+            {
+                ArrayKey::Int(i)
+            } else {
+                ArrayKey::Str(Rc::new(s))
+            }
+        }
+    }
+}
+
+fn fetched_row_to_val(vm: &mut VM, conversion: FetchConversion, row: types::FetchedRow) -> Handle {
     match row {
         types::FetchedRow::Assoc(map) => {
             let mut arr = ArrayData::new();
             for (key, val) in map {
                 arr.insert(
                     ArrayKey::Str(Rc::new(key.into_bytes())),
-                    pdo_val_to_handle(vm, val),
+                    pdo_val_to_handle(vm, conversion, val),
                 );
             }
             vm.arena.alloc(Val::Array(Rc::new(arr)))
@@ -1143,7 +2189,10 @@ fn fetched_row_to_val(vm: &mut VM, row: types::FetchedRow) -> Handle {
         types::FetchedRow::Num(vec) => {
             let mut arr = ArrayData::new();
             for (idx, val) in vec.into_iter().enumerate() {
-                arr.insert(ArrayKey::Int(idx as i64), pdo_val_to_handle(vm, val));
+                arr.insert(
+                    ArrayKey::Int(idx as i64),
+                    pdo_val_to_handle(vm, conversion, val),
+                );
             }
             vm.arena.alloc(Val::Array(Rc::new(arr)))
         }
@@ -1152,11 +2201,14 @@ fn fetched_row_to_val(vm: &mut VM, row: types::FetchedRow) -> Handle {
             for (key, val) in assoc {
                 arr.insert(
                     ArrayKey::Str(Rc::new(key.into_bytes())),
-                    pdo_val_to_handle(vm, val),
+                    pdo_val_to_handle(vm, conversion, val),
                 );
             }
             for (idx, val) in num.into_iter().enumerate() {
-                arr.insert(ArrayKey::Int(idx as i64), pdo_val_to_handle(vm, val));
+                arr.insert(
+                    ArrayKey::Int(idx as i64),
+                    pdo_val_to_handle(vm, conversion, val),
+                );
             }
             vm.arena.alloc(Val::Array(Rc::new(arr)))
         }
@@ -1166,7 +2218,7 @@ fn fetched_row_to_val(vm: &mut VM, row: types::FetchedRow) -> Handle {
             let mut properties = vm.collect_properties(std_class_sym, PropertyCollectionMode::All);
             for (key, val) in map {
                 let key_sym = vm.context.interner.intern(key.as_bytes());
-                properties.insert(key_sym, pdo_val_to_handle(vm, val));
+                properties.insert(key_sym, pdo_val_to_handle(vm, conversion, val));
             }
             let obj_data = ObjectData {
                 class: std_class_sym,
@@ -9,6 +9,7 @@
 //!
 //! Reference: $PHP_SRC_PATH/ext/pdo/php_pdo_driver.h
 
+use crate::core::value::Val;
 use indexmap::IndexMap;
 use std::fmt;
 
@@ -33,21 +34,82 @@ impl ErrorMode {
     }
 }
 
+/// PDO::ATTR_ORACLE_NULLS conversion
+/// Reference: enum pdo_null_handling in php_pdo_driver.h
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(i64)]
+pub enum NullHandling {
+    #[default]
+    Natural = 0, // PDO::NULL_NATURAL - leave values as returned
+    EmptyString = 1, // PDO::NULL_EMPTY_STRING - empty strings become NULL
+    ToString = 2,    // PDO::NULL_TO_STRING - NULLs become empty strings
+}
+
+impl NullHandling {
+    pub fn from_i64(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(NullHandling::Natural),
+            1 => Some(NullHandling::EmptyString),
+            2 => Some(NullHandling::ToString),
+            _ => None,
+        }
+    }
+}
+
+/// Per-connection conversions applied to every fetched column value
+/// Reference: fetch_value() in pdo_stmt.c
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FetchConversion {
+    /// PDO::ATTR_STRINGIFY_FETCHES
+    pub stringify: bool,
+    /// PDO::ATTR_ORACLE_NULLS
+    pub nulls: NullHandling,
+}
+
+impl FetchConversion {
+    pub fn apply(&self, val: PdoValue) -> PdoValue {
+        let val = if self.stringify {
+            match val {
+                // Drivers without a boolean type would return "0", not ""
+                PdoValue::Bool(b) => PdoValue::String(if b { b"1" } else { b"0" }.to_vec()),
+                PdoValue::Int(i) => PdoValue::String(i.to_string().into_bytes()),
+                PdoValue::Float(f) => PdoValue::String(Val::Float(f).to_php_string_bytes()),
+                other => other,
+            }
+        } else {
+            val
+        };
+        match (self.nulls, val) {
+            (NullHandling::EmptyString, PdoValue::String(s)) if s.is_empty() => PdoValue::Null,
+            (NullHandling::ToString, PdoValue::Null) => PdoValue::String(Vec::new()),
+            (_, val) => val,
+        }
+    }
+}
+
 /// PDO fetch modes
 /// Reference: enum pdo_fetch_type in php_pdo_driver.h
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i64)]
 pub enum FetchMode {
     // Note: PDO::FETCH_LAZY (1) is deprecated, we start at 2
-    Assoc = 2,  // PDO::FETCH_ASSOC - associative array
-    Num = 3,    // PDO::FETCH_NUM - numeric array
-    Both = 4,   // PDO::FETCH_BOTH - both numeric and associative
-    Obj = 5,    // PDO::FETCH_OBJ - anonymous object
-    Bound = 6,  // PDO::FETCH_BOUND - fetch into bound variables
-    Column = 7, // PDO::FETCH_COLUMN - single column
-    Class = 8,  // PDO::FETCH_CLASS - class instance
+    Assoc = 2,    // PDO::FETCH_ASSOC - associative array
+    Num = 3,      // PDO::FETCH_NUM - numeric array
+    Both = 4,     // PDO::FETCH_BOTH - both numeric and associative
+    Obj = 5,      // PDO::FETCH_OBJ - anonymous object
+    Bound = 6,    // PDO::FETCH_BOUND - fetch into bound variables
+    Column = 7,   // PDO::FETCH_COLUMN - single column
+    Class = 8,    // PDO::FETCH_CLASS - class instance
+    KeyPair = 12, // PDO::FETCH_KEY_PAIR - first column => second column
 }
 
+/// Fetch mode modifier bits (PDO_FETCH_FLAGS in php_pdo_driver.h)
+pub const FETCH_FLAGS: i64 = 0xFFFF0000;
+/// PDO::FETCH_GROUP - group rows by the first column
+pub const FETCH_GROUP: i64 = 0x10000;
+/// PDO::FETCH_UNIQUE - key rows by the first column (implies FETCH_GROUP)
+pub const FETCH_UNIQUE: i64 = 0x30000;
+
 impl FetchMode {
     pub fn from_i64(value: i64) -> Option<Self> {
         match value {
@@ -58,6 +120,7 @@ impl FetchMode {
             6 => Some(FetchMode::Bound),
             7 => Some(FetchMode::Column),
             8 => Some(FetchMode::Class),
+            12 => Some(FetchMode::KeyPair),
             _ => None,
         }
     }
@@ -155,7 +218,7 @@ pub enum ParamIdentifier {
 }
 
 /// PDO value type (handle-independent for driver safety)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PdoValue {
     Null,
     Bool(bool),
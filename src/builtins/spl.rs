@@ -1,5 +1,11 @@
-use crate::core::value::{Handle, Val};
+use crate::core::value::{ArrayData, ArrayKey, Handle, Val, Visibility};
+use crate::runtime::registry::{ExtensionRegistry, NativeClassDef, NativeMethodEntry};
 use crate::vm::engine::VM;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::rc::Rc;
 
 /// spl_autoload_register() - Register a function for autoloading classes
@@ -87,3 +93,1409 @@ pub fn php_spl_object_hash(vm: &mut VM, args: &[Handle]) -> Result<Handle, Strin
     let hash_bytes = Rc::new(hash.into_bytes());
     Ok(vm.arena.alloc(Val::String(hash_bytes)))
 }
+
+/// SplPriorityQueue::EXTR_DATA
+const EXTR_DATA: i64 = 1;
+/// SplPriorityQueue::EXTR_PRIORITY
+const EXTR_PRIORITY: i64 = 2;
+/// SplPriorityQueue::EXTR_BOTH
+const EXTR_BOTH: i64 = 3;
+
+/// One element of an SplHeap or SplPriorityQueue
+#[derive(Debug, Clone, Copy)]
+pub struct SplHeapEntry {
+    pub value: Handle,
+    /// Only set for SplPriorityQueue
+    pub priority: Option<Handle>,
+    /// Insertion order, so equal elements come out first-in first-out
+    pub seq: u64,
+}
+
+/// Backing store for SplHeap and SplPriorityQueue objects, kept in the
+/// object's internal slot
+/// Reference: $PHP_SRC_PATH/ext/spl/spl_heap.c
+#[derive(Debug)]
+pub struct SplHeapData {
+    /// Binary heap; the element with the greatest compare() result is at index 0
+    pub entries: Vec<SplHeapEntry>,
+    pub next_seq: u64,
+    pub extract_flags: i64,
+    /// Set when a compare() call failed part-way through reordering
+    pub corrupted: bool,
+}
+
+impl Default for SplHeapData {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            next_seq: 0,
+            extract_flags: EXTR_DATA,
+            corrupted: false,
+        }
+    }
+}
+
+fn spl_this(vm: &VM, method: &str) -> Result<Handle, String> {
+    vm.frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or_else(|| format!("{}() called outside object context", method))
+}
+
+/// Throw a RuntimeException and return its message for the native to fail with
+fn throw_runtime_exception(vm: &mut VM, message: &str) -> String {
+    let ex = crate::builtins::exception::create_exception(vm, b"RuntimeException", message);
+    vm.throw_exception(ex)
+}
+
+/// Fetch the heap store of `this`, creating it on first use so that user
+/// subclasses with their own constructor still work
+fn heap_data(vm: &mut VM, this: Handle) -> Result<Rc<RefCell<SplHeapData>>, String> {
+    let payload = match &vm.arena.get(this).value {
+        Val::Object(h) => *h,
+        _ => return Err("Invalid 'this' object".into()),
+    };
+    match &mut vm.arena.get_mut(payload).value {
+        Val::ObjPayload(obj) => {
+            if let Some(internal) = &obj.internal
+                && let Ok(data) = internal.clone().downcast::<RefCell<SplHeapData>>()
+            {
+                return Ok(data);
+            }
+            let data = Rc::new(RefCell::new(SplHeapData::default()));
+            obj.internal = Some(data.clone());
+            Ok(data)
+        }
+        _ => Err("Invalid object payload".into()),
+    }
+}
+
+/// Order two entries through the object's compare() method, which user
+/// subclasses may override. Ties go to the earlier insertion.
+fn heap_compare(
+    vm: &mut VM,
+    this: Handle,
+    a: &SplHeapEntry,
+    b: &SplHeapEntry,
+) -> Result<Ordering, String> {
+    let (left, right) = match (a.priority, b.priority) {
+        (Some(pa), Some(pb)) => (pa, pb),
+        _ => (a.value, b.value),
+    };
+    let compare_sym = vm.context.interner.intern(b"compare");
+    let result = vm
+        .call_method_with_args(this, compare_sym, &[left, right])
+        .map_err(|e| e.to_string())?;
+    Ok(match vm.arena.get(result).value.to_int().cmp(&0) {
+        Ordering::Equal => b.seq.cmp(&a.seq),
+        ordering => ordering,
+    })
+}
+
+fn heap_sift_up(
+    vm: &mut VM,
+    this: Handle,
+    entries: &mut [SplHeapEntry],
+    mut idx: usize,
+) -> Result<(), String> {
+    while idx > 0 {
+        let parent = (idx - 1) / 2;
+        if heap_compare(vm, this, &entries[idx], &entries[parent])? != Ordering::Greater {
+            break;
+        }
+        entries.swap(idx, parent);
+        idx = parent;
+    }
+    Ok(())
+}
+
+fn heap_sift_down(
+    vm: &mut VM,
+    this: Handle,
+    entries: &mut [SplHeapEntry],
+    mut idx: usize,
+) -> Result<(), String> {
+    loop {
+        let mut largest = idx;
+        for child in [2 * idx + 1, 2 * idx + 2] {
+            if child < entries.len()
+                && heap_compare(vm, this, &entries[child], &entries[largest])? == Ordering::Greater
+            {
+                largest = child;
+            }
+        }
+        if largest == idx {
+            return Ok(());
+        }
+        entries.swap(idx, largest);
+        idx = largest;
+    }
+}
+
+fn heap_check_corruption(data: &SplHeapData) -> Result<(), String> {
+    if data.corrupted {
+        return Err("Heap is corrupted, heap properties are no longer ensured.".into());
+    }
+    Ok(())
+}
+
+/// Insert an entry. The reordering works on a copy while the stored entries
+/// (including the new one) stay reachable for the GC during compare() calls.
+fn heap_insert(
+    vm: &mut VM,
+    this: Handle,
+    value: Handle,
+    priority: Option<Handle>,
+) -> Result<(), String> {
+    let data = heap_data(vm, this)?;
+    let mut entries = {
+        let mut data = data.borrow_mut();
+        heap_check_corruption(&data)?;
+        let seq = data.next_seq;
+        data.next_seq += 1;
+        data.entries.push(SplHeapEntry {
+            value,
+            priority,
+            seq,
+        });
+        data.entries.clone()
+    };
+
+    let last = entries.len() - 1;
+    if let Err(e) = heap_sift_up(vm, this, &mut entries, last) {
+        data.borrow_mut().corrupted = true;
+        return Err(e);
+    }
+    data.borrow_mut().entries = entries;
+    Ok(())
+}
+
+/// Remove and return the top entry, or None when the heap is empty
+fn heap_extract(vm: &mut VM, this: Handle) -> Result<Option<SplHeapEntry>, String> {
+    let data = heap_data(vm, this)?;
+    let mut entries = {
+        let data = data.borrow();
+        heap_check_corruption(&data)?;
+        if data.entries.is_empty() {
+            return Ok(None);
+        }
+        data.entries.clone()
+    };
+
+    let top = entries.swap_remove(0);
+    if !entries.is_empty()
+        && let Err(e) = heap_sift_down(vm, this, &mut entries, 0)
+    {
+        data.borrow_mut().corrupted = true;
+        return Err(e);
+    }
+    data.borrow_mut().entries = entries;
+    Ok(Some(top))
+}
+
+fn heap_top(vm: &mut VM, this: Handle) -> Result<Option<SplHeapEntry>, String> {
+    let data = heap_data(vm, this)?;
+    let data = data.borrow();
+    heap_check_corruption(&data)?;
+    Ok(data.entries.first().copied())
+}
+
+/// Shape an SplPriorityQueue entry according to its extract flags
+fn pqueue_output(vm: &mut VM, this: Handle, entry: SplHeapEntry) -> Result<Handle, String> {
+    let flags = heap_data(vm, this)?.borrow().extract_flags;
+    let priority = entry.priority.unwrap_or(entry.value);
+    match flags {
+        EXTR_PRIORITY => Ok(priority),
+        EXTR_BOTH => {
+            let mut arr = ArrayData::new();
+            arr.insert(ArrayKey::Str(Rc::new(b"data".to_vec())), entry.value);
+            arr.insert(ArrayKey::Str(Rc::new(b"priority".to_vec())), priority);
+            Ok(vm.arena.alloc(Val::Array(Rc::new(arr))))
+        }
+        _ => Ok(entry.value),
+    }
+}
+
+/// Copy an argument so later changes to the caller's variable don't leak in
+fn heap_copy_arg(vm: &mut VM, handle: Handle) -> Handle {
+    let value = vm.arena.get(handle).value.clone();
+    vm.arena.alloc(value)
+}
+
+/// SplHeap::compare() - abstract; subclasses must provide the ordering
+pub fn spl_heap_compare(_vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    Err("Cannot call abstract method SplHeap::compare()".into())
+}
+
+/// SplMinHeap::compare($value1, $value2) - positive when $value1 is smaller
+pub fn spl_min_heap_compare(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("SplMinHeap::compare() expects exactly 2 arguments".into());
+    }
+    let result = vm.compare_values(args[1], args[0]);
+    Ok(vm.arena.alloc(Val::Int(result)))
+}
+
+/// SplMaxHeap::compare($value1, $value2) - positive when $value1 is greater
+pub fn spl_max_heap_compare(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("SplMaxHeap::compare() expects exactly 2 arguments".into());
+    }
+    let result = vm.compare_values(args[0], args[1]);
+    Ok(vm.arena.alloc(Val::Int(result)))
+}
+
+/// SplPriorityQueue::compare($priority1, $priority2) - positive when $priority1 is greater
+pub fn spl_priority_queue_compare(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("SplPriorityQueue::compare() expects exactly 2 arguments".into());
+    }
+    let result = vm.compare_values(args[0], args[1]);
+    Ok(vm.arena.alloc(Val::Int(result)))
+}
+
+/// SplHeap::insert($value)
+pub fn spl_heap_insert(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::insert")?;
+    let Some(&value) = args.first() else {
+        return Err("SplHeap::insert() expects exactly 1 argument, 0 given".into());
+    };
+    let value = heap_copy_arg(vm, value);
+    heap_insert(vm, this, value, None)?;
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+/// SplPriorityQueue::insert($value, $priority)
+pub fn spl_priority_queue_insert(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplPriorityQueue::insert")?;
+    if args.len() < 2 {
+        return Err(format!(
+            "SplPriorityQueue::insert() expects exactly 2 arguments, {} given",
+            args.len()
+        ));
+    }
+    let value = heap_copy_arg(vm, args[0]);
+    let priority = heap_copy_arg(vm, args[1]);
+    heap_insert(vm, this, value, Some(priority))?;
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+/// SplHeap::extract() / SplPriorityQueue::extract()
+pub fn spl_heap_extract(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::extract")?;
+    match heap_extract(vm, this)? {
+        Some(entry) if entry.priority.is_some() => pqueue_output(vm, this, entry),
+        Some(entry) => Ok(entry.value),
+        None => Err(throw_runtime_exception(
+            vm,
+            "Can't extract from an empty heap",
+        )),
+    }
+}
+
+/// SplHeap::top() / SplPriorityQueue::top()
+pub fn spl_heap_top(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::top")?;
+    match heap_top(vm, this)? {
+        Some(entry) if entry.priority.is_some() => pqueue_output(vm, this, entry),
+        Some(entry) => Ok(entry.value),
+        None => Err(throw_runtime_exception(vm, "Can't peek at an empty heap")),
+    }
+}
+
+/// SplHeap::current() - the top element, or null once iteration is exhausted
+pub fn spl_heap_current(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::current")?;
+    match heap_top(vm, this)? {
+        Some(entry) if entry.priority.is_some() => pqueue_output(vm, this, entry),
+        Some(entry) => Ok(entry.value),
+        None => Ok(vm.arena.alloc(Val::Null)),
+    }
+}
+
+/// SplHeap::key() - number of remaining elements minus one
+pub fn spl_heap_key(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::key")?;
+    let count = heap_data(vm, this)?.borrow().entries.len() as i64;
+    Ok(vm.arena.alloc(Val::Int(count - 1)))
+}
+
+/// SplHeap::next() - iteration is destructive, so this extracts the top
+pub fn spl_heap_next(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::next")?;
+    heap_extract(vm, this)?;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplHeap::valid()
+pub fn spl_heap_valid(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::valid")?;
+    let valid = !heap_data(vm, this)?.borrow().entries.is_empty();
+    Ok(vm.arena.alloc(Val::Bool(valid)))
+}
+
+/// SplHeap::rewind() - no-op, the heap is always positioned at its top
+pub fn spl_heap_rewind(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplHeap::count()
+pub fn spl_heap_count(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::count")?;
+    let count = heap_data(vm, this)?.borrow().entries.len() as i64;
+    Ok(vm.arena.alloc(Val::Int(count)))
+}
+
+/// SplHeap::isEmpty()
+pub fn spl_heap_is_empty(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::isEmpty")?;
+    let empty = heap_data(vm, this)?.borrow().entries.is_empty();
+    Ok(vm.arena.alloc(Val::Bool(empty)))
+}
+
+/// SplHeap::isCorrupted()
+pub fn spl_heap_is_corrupted(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::isCorrupted")?;
+    let corrupted = heap_data(vm, this)?.borrow().corrupted;
+    Ok(vm.arena.alloc(Val::Bool(corrupted)))
+}
+
+/// SplHeap::recoverFromCorruption()
+pub fn spl_heap_recover_from_corruption(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplHeap::recoverFromCorruption")?;
+    heap_data(vm, this)?.borrow_mut().corrupted = false;
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+/// SplPriorityQueue::setExtractFlags($flags)
+pub fn spl_priority_queue_set_extract_flags(
+    vm: &mut VM,
+    args: &[Handle],
+) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplPriorityQueue::setExtractFlags")?;
+    let flags = args
+        .first()
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0)
+        & EXTR_BOTH;
+    if flags == 0 {
+        return Err("Must specify at least one extract flag".into());
+    }
+    heap_data(vm, this)?.borrow_mut().extract_flags = flags;
+    Ok(vm.arena.alloc(Val::Int(flags)))
+}
+
+/// SplPriorityQueue::getExtractFlags()
+pub fn spl_priority_queue_get_extract_flags(
+    vm: &mut VM,
+    _args: &[Handle],
+) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplPriorityQueue::getExtractFlags")?;
+    let flags = heap_data(vm, this)?.borrow().extract_flags;
+    Ok(vm.arena.alloc(Val::Int(flags)))
+}
+
+fn public_method(handler: crate::runtime::context::NativeHandler) -> NativeMethodEntry {
+    NativeMethodEntry {
+        handler,
+        visibility: Visibility::Public,
+        is_static: false,
+        is_final: false,
+    }
+}
+
+/// Register SplHeap, SplMinHeap, SplMaxHeap and SplPriorityQueue
+pub fn register_spl_heap_classes(registry: &mut ExtensionRegistry) {
+    let mut iteration_methods = HashMap::new();
+    for (name, handler) in [
+        (
+            &b"extract"[..],
+            spl_heap_extract as crate::runtime::context::NativeHandler,
+        ),
+        (b"top", spl_heap_top),
+        (b"current", spl_heap_current),
+        (b"key", spl_heap_key),
+        (b"next", spl_heap_next),
+        (b"valid", spl_heap_valid),
+        (b"rewind", spl_heap_rewind),
+        (b"count", spl_heap_count),
+        (b"isEmpty", spl_heap_is_empty),
+        (b"isCorrupted", spl_heap_is_corrupted),
+        (b"recoverFromCorruption", spl_heap_recover_from_corruption),
+    ] {
+        iteration_methods.insert(name.to_vec(), public_method(handler));
+    }
+
+    let compare_method = |handler| NativeMethodEntry {
+        handler,
+        visibility: Visibility::Protected,
+        is_static: false,
+        is_final: false,
+    };
+
+    let mut heap_methods = iteration_methods.clone();
+    heap_methods.insert(b"insert".to_vec(), public_method(spl_heap_insert));
+    heap_methods.insert(b"compare".to_vec(), compare_method(spl_heap_compare));
+    registry.register_class(NativeClassDef {
+        name: b"SplHeap".to_vec(),
+        parent: None,
+        is_interface: false,
+        is_trait: false,
+        is_final: false,
+        interfaces: vec![b"Iterator".to_vec(), b"Countable".to_vec()],
+        methods: heap_methods,
+        constants: HashMap::new(),
+        constructor: None,
+        extension_name: None,
+    });
+
+    for (name, compare) in [
+        (
+            &b"SplMinHeap"[..],
+            spl_min_heap_compare as crate::runtime::context::NativeHandler,
+        ),
+        (b"SplMaxHeap", spl_max_heap_compare),
+    ] {
+        let mut methods = HashMap::new();
+        methods.insert(b"compare".to_vec(), compare_method(compare));
+        registry.register_class(NativeClassDef {
+            name: name.to_vec(),
+            parent: Some(b"SplHeap".to_vec()),
+            is_interface: false,
+            is_trait: false,
+            is_final: false,
+            interfaces: vec![],
+            methods,
+            constants: HashMap::new(),
+            constructor: None,
+            extension_name: None,
+        });
+    }
+
+    let mut pqueue_methods = iteration_methods;
+    pqueue_methods.insert(b"insert".to_vec(), public_method(spl_priority_queue_insert));
+    pqueue_methods.insert(
+        b"compare".to_vec(),
+        public_method(spl_priority_queue_compare),
+    );
+    pqueue_methods.insert(
+        b"setExtractFlags".to_vec(),
+        public_method(spl_priority_queue_set_extract_flags),
+    );
+    pqueue_methods.insert(
+        b"getExtractFlags".to_vec(),
+        public_method(spl_priority_queue_get_extract_flags),
+    );
+    let mut pqueue_constants = HashMap::new();
+    for (name, value) in [
+        (&b"EXTR_DATA"[..], EXTR_DATA),
+        (b"EXTR_PRIORITY", EXTR_PRIORITY),
+        (b"EXTR_BOTH", EXTR_BOTH),
+    ] {
+        pqueue_constants.insert(name.to_vec(), (Val::Int(value), Visibility::Public));
+    }
+    registry.register_class(NativeClassDef {
+        name: b"SplPriorityQueue".to_vec(),
+        parent: None,
+        is_interface: false,
+        is_trait: false,
+        is_final: false,
+        interfaces: vec![b"Iterator".to_vec(), b"Countable".to_vec()],
+        methods: pqueue_methods,
+        constants: pqueue_constants,
+        constructor: None,
+        extension_name: None,
+    });
+}
+
+/// Backing store for SplFixedArray objects, kept in the object's internal slot
+/// Reference: $PHP_SRC_PATH/ext/spl/spl_fixedarray.c
+#[derive(Debug, Default)]
+pub struct SplFixedArrayData {
+    pub elements: Vec<Handle>,
+    /// Iterator position
+    pub position: usize,
+}
+
+/// Fetch the element store of `this`, creating an empty one on first use
+fn fixed_array_data(vm: &mut VM, this: Handle) -> Result<Rc<RefCell<SplFixedArrayData>>, String> {
+    let payload = match &vm.arena.get(this).value {
+        Val::Object(h) => *h,
+        _ => return Err("Invalid 'this' object".into()),
+    };
+    match &mut vm.arena.get_mut(payload).value {
+        Val::ObjPayload(obj) => {
+            if let Some(internal) = &obj.internal
+                && let Ok(data) = internal.clone().downcast::<RefCell<SplFixedArrayData>>()
+            {
+                return Ok(data);
+            }
+            let data = Rc::new(RefCell::new(SplFixedArrayData::default()));
+            obj.internal = Some(data.clone());
+            Ok(data)
+        }
+        _ => Err("Invalid object payload".into()),
+    }
+}
+
+/// Read a size argument, rejecting negative values like PHP's ValueError
+fn fixed_array_size_arg(vm: &mut VM, args: &[Handle], method: &str) -> Result<usize, String> {
+    let size = args
+        .first()
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0);
+    if size < 0 {
+        let message = format!(
+            "{}(): Argument #1 ($size) must be greater than or equal to 0",
+            method
+        );
+        let ex = crate::builtins::exception::create_exception(vm, b"ValueError", &message);
+        return Err(vm.throw_exception(ex));
+    }
+    Ok(size as usize)
+}
+
+/// Resize the element store, filling new slots with null
+fn fixed_array_resize(vm: &mut VM, data: &RefCell<SplFixedArrayData>, size: usize) {
+    let current = data.borrow().elements.len();
+    if size <= current {
+        data.borrow_mut().elements.truncate(size);
+        return;
+    }
+    let fill: Vec<Handle> = (current..size).map(|_| vm.arena.alloc(Val::Null)).collect();
+    data.borrow_mut().elements.extend(fill);
+}
+
+/// Convert an offset to an element index; None means out of range
+/// Reference: $PHP_SRC_PATH/ext/spl/spl_engine.c - spl_offset_convert_to_long
+fn fixed_array_offset(vm: &VM, offset: Handle, size: usize) -> Result<Option<usize>, String> {
+    let index = match &vm.arena.get(offset).value {
+        Val::Int(i) => *i,
+        Val::Float(f) => *f as i64,
+        Val::Bool(b) => *b as i64,
+        Val::String(s) => std::str::from_utf8(s)
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .ok_or("Illegal offset type")?,
+        Val::Null => return Err("[] operator not supported for SplFixedArray".into()),
+        _ => return Err("Illegal offset type".into()),
+    };
+    Ok(usize::try_from(index).ok().filter(|&i| i < size))
+}
+
+fn fixed_array_index(vm: &mut VM, this: Handle, offset: Option<&Handle>) -> Result<usize, String> {
+    let size = fixed_array_data(vm, this)?.borrow().elements.len();
+    match offset {
+        Some(offset) => match fixed_array_offset(vm, *offset, size)? {
+            Some(index) => Ok(index),
+            None => Err(throw_runtime_exception(vm, "Index invalid or out of range")),
+        },
+        None => Err(throw_runtime_exception(vm, "Index invalid or out of range")),
+    }
+}
+
+/// SplFixedArray::__construct(int $size = 0)
+pub fn spl_fixed_array_construct(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::__construct")?;
+    let size = fixed_array_size_arg(vm, args, "SplFixedArray::__construct")?;
+    let data = fixed_array_data(vm, this)?;
+    fixed_array_resize(vm, &data, size);
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplFixedArray::offsetExists($index) - false when out of range or null
+pub fn spl_fixed_array_offset_exists(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::offsetExists")?;
+    let data = fixed_array_data(vm, this)?;
+    let exists = match args.first() {
+        Some(offset) => {
+            let data = data.borrow();
+            fixed_array_offset(vm, *offset, data.elements.len())?
+                .is_some_and(|i| !matches!(vm.arena.get(data.elements[i]).value, Val::Null))
+        }
+        None => false,
+    };
+    Ok(vm.arena.alloc(Val::Bool(exists)))
+}
+
+/// SplFixedArray::offsetGet($index)
+pub fn spl_fixed_array_offset_get(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::offsetGet")?;
+    let index = fixed_array_index(vm, this, args.first())?;
+    Ok(fixed_array_data(vm, this)?.borrow().elements[index])
+}
+
+/// SplFixedArray::offsetSet($index, $value)
+pub fn spl_fixed_array_offset_set(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::offsetSet")?;
+    let index = fixed_array_index(vm, this, args.first())?;
+    let value = match args.get(1) {
+        Some(value) => vm.arena.get(*value).value.clone(),
+        None => Val::Null,
+    };
+    let value = vm.arena.alloc(value);
+    fixed_array_data(vm, this)?.borrow_mut().elements[index] = value;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplFixedArray::offsetUnset($index) - resets the slot to null
+pub fn spl_fixed_array_offset_unset(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::offsetUnset")?;
+    let index = fixed_array_index(vm, this, args.first())?;
+    let null = vm.arena.alloc(Val::Null);
+    fixed_array_data(vm, this)?.borrow_mut().elements[index] = null;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplFixedArray::count() / SplFixedArray::getSize()
+pub fn spl_fixed_array_count(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::count")?;
+    let size = fixed_array_data(vm, this)?.borrow().elements.len() as i64;
+    Ok(vm.arena.alloc(Val::Int(size)))
+}
+
+/// SplFixedArray::setSize(int $size) - grows with nulls or truncates
+pub fn spl_fixed_array_set_size(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::setSize")?;
+    let size = fixed_array_size_arg(vm, args, "SplFixedArray::setSize")?;
+    let data = fixed_array_data(vm, this)?;
+    fixed_array_resize(vm, &data, size);
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+/// SplFixedArray::toArray()
+pub fn spl_fixed_array_to_array(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::toArray")?;
+    let elements = fixed_array_data(vm, this)?.borrow().elements.clone();
+    let mut arr = ArrayData::new();
+    for (i, element) in elements.into_iter().enumerate() {
+        let value = vm.arena.get(element).value.clone();
+        arr.insert(ArrayKey::Int(i as i64), vm.arena.alloc(value));
+    }
+    Ok(vm.arena.alloc(Val::Array(Rc::new(arr))))
+}
+
+/// SplFixedArray::fromArray(array $array, bool $preserveKeys = true)
+pub fn spl_fixed_array_from_array(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let entries: Vec<(ArrayKey, Handle)> = match args.first().map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Array(arr)) => arr.map.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+        _ => {
+            return Err(
+                "SplFixedArray::fromArray(): Argument #1 ($array) must be of type array".into(),
+            );
+        }
+    };
+    let preserve_keys = args
+        .get(1)
+        .map(|h| vm.arena.get(*h).value.to_bool())
+        .unwrap_or(true);
+
+    let mut slots = Vec::with_capacity(entries.len());
+    for (position, (key, value)) in entries.into_iter().enumerate() {
+        let index = if preserve_keys {
+            match key {
+                ArrayKey::Int(i) if i >= 0 => i as usize,
+                _ => return Err("array must contain only positive integer keys".into()),
+            }
+        } else {
+            position
+        };
+        slots.push((index, value));
+    }
+    let size = slots.iter().map(|(i, _)| i + 1).max().unwrap_or(0);
+
+    let class_sym = vm.context.interner.intern(b"SplFixedArray");
+    let obj = vm.instantiate_class(class_sym, &[])?;
+    let data = fixed_array_data(vm, obj)?;
+    fixed_array_resize(vm, &data, size);
+    for (index, value) in slots {
+        let value = vm.arena.get(value).value.clone();
+        let value = vm.arena.alloc(value);
+        data.borrow_mut().elements[index] = value;
+    }
+    Ok(obj)
+}
+
+/// SplFixedArray::current()
+pub fn spl_fixed_array_current(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::current")?;
+    let data = fixed_array_data(vm, this)?;
+    let element = {
+        let data = data.borrow();
+        data.elements.get(data.position).copied()
+    };
+    Ok(element.unwrap_or_else(|| vm.arena.alloc(Val::Null)))
+}
+
+/// SplFixedArray::key()
+pub fn spl_fixed_array_key(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::key")?;
+    let position = fixed_array_data(vm, this)?.borrow().position as i64;
+    Ok(vm.arena.alloc(Val::Int(position)))
+}
+
+/// SplFixedArray::next()
+pub fn spl_fixed_array_next(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::next")?;
+    fixed_array_data(vm, this)?.borrow_mut().position += 1;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplFixedArray::valid()
+pub fn spl_fixed_array_valid(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::valid")?;
+    let data = fixed_array_data(vm, this)?;
+    let valid = {
+        let data = data.borrow();
+        data.position < data.elements.len()
+    };
+    Ok(vm.arena.alloc(Val::Bool(valid)))
+}
+
+/// SplFixedArray::rewind()
+pub fn spl_fixed_array_rewind(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFixedArray::rewind")?;
+    fixed_array_data(vm, this)?.borrow_mut().position = 0;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// Register SplFixedArray
+pub fn register_spl_fixed_array_class(registry: &mut ExtensionRegistry) {
+    let mut methods = HashMap::new();
+    for (name, handler) in [
+        (
+            &b"__construct"[..],
+            spl_fixed_array_construct as crate::runtime::context::NativeHandler,
+        ),
+        (b"offsetExists", spl_fixed_array_offset_exists),
+        (b"offsetGet", spl_fixed_array_offset_get),
+        (b"offsetSet", spl_fixed_array_offset_set),
+        (b"offsetUnset", spl_fixed_array_offset_unset),
+        (b"count", spl_fixed_array_count),
+        (b"getSize", spl_fixed_array_count),
+        (b"setSize", spl_fixed_array_set_size),
+        (b"toArray", spl_fixed_array_to_array),
+        (b"current", spl_fixed_array_current),
+        (b"key", spl_fixed_array_key),
+        (b"next", spl_fixed_array_next),
+        (b"valid", spl_fixed_array_valid),
+        (b"rewind", spl_fixed_array_rewind),
+    ] {
+        methods.insert(name.to_vec(), public_method(handler));
+    }
+    methods.insert(
+        b"fromArray".to_vec(),
+        NativeMethodEntry {
+            handler: spl_fixed_array_from_array,
+            visibility: Visibility::Public,
+            is_static: true,
+            is_final: false,
+        },
+    );
+
+    registry.register_class(NativeClassDef {
+        name: b"SplFixedArray".to_vec(),
+        parent: None,
+        is_interface: false,
+        is_trait: false,
+        is_final: false,
+        interfaces: vec![
+            b"ArrayAccess".to_vec(),
+            b"Countable".to_vec(),
+            b"Iterator".to_vec(),
+        ],
+        methods,
+        constants: HashMap::new(),
+        constructor: Some(spl_fixed_array_construct),
+        extension_name: None,
+    });
+}
+
+/// SplFileObject flag: strip the trailing line break from each line
+const SPL_FILE_DROP_NEW_LINE: i64 = 1;
+/// SplFileObject flag: read the next line on rewind()/next()
+const SPL_FILE_READ_AHEAD: i64 = 2;
+/// SplFileObject flag: skip empty lines while iterating
+const SPL_FILE_SKIP_EMPTY: i64 = 4;
+/// SplFileObject flag: return lines parsed as CSV rows
+const SPL_FILE_READ_CSV: i64 = 8;
+
+/// The line an SplFileObject iterator is positioned on
+#[derive(Debug, Clone)]
+pub enum SplFileLine {
+    Text(Vec<u8>),
+    /// A CSV row; None is the single null field of a blank line
+    Csv(Vec<Option<Vec<u8>>>),
+}
+
+/// Backing store for SplFileObject, kept in the object's internal slot
+/// Reference: $PHP_SRC_PATH/ext/spl/spl_directory.c
+#[derive(Debug)]
+pub struct SplFileObjectData {
+    /// None until __construct has opened the file
+    pub reader: Option<BufReader<File>>,
+    pub path: Vec<u8>,
+    pub flags: i64,
+    pub current: Option<SplFileLine>,
+    pub line_num: i64,
+    /// Set once a read hits the end of the file
+    pub eof: bool,
+    pub csv_separator: u8,
+    pub csv_enclosure: u8,
+    pub csv_escape: Option<u8>,
+}
+
+impl Default for SplFileObjectData {
+    fn default() -> Self {
+        Self {
+            reader: None,
+            path: Vec::new(),
+            flags: 0,
+            current: None,
+            line_num: 0,
+            eof: false,
+            csv_separator: b',',
+            csv_enclosure: b'"',
+            csv_escape: Some(b'\\'),
+        }
+    }
+}
+
+impl SplFileObjectData {
+    fn reader(&mut self) -> Result<&mut BufReader<File>, String> {
+        self.reader
+            .as_mut()
+            .ok_or_else(|| "Object not initialized".to_string())
+    }
+
+    /// Read one raw line including its line break; None at end of file
+    fn read_raw_line(&mut self) -> Result<Option<Vec<u8>>, String> {
+        if self.eof {
+            return Ok(None);
+        }
+        let mut line = Vec::new();
+        self.reader()?
+            .read_until(b'\n', &mut line)
+            .map_err(|e| e.to_string())?;
+        if !line.ends_with(b"\n") {
+            self.eof = true;
+        }
+        Ok(Some(line))
+    }
+
+    /// Read the next line as text, honouring DROP_NEW_LINE
+    fn read_text_line(&mut self) -> Result<Option<Vec<u8>>, String> {
+        let mut line = match self.read_raw_line()? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        if self.flags & SPL_FILE_DROP_NEW_LINE != 0 {
+            strip_line_break(&mut line);
+        }
+        Ok(Some(line))
+    }
+
+    /// Read the next CSV row, continuing across line breaks inside enclosures
+    fn read_csv_row(
+        &mut self,
+        separator: u8,
+        enclosure: u8,
+        escape: Option<u8>,
+    ) -> Result<Option<SplFileLine>, String> {
+        let mut record = match self.read_raw_line()? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        while record.iter().filter(|&&b| b == enclosure).count() % 2 == 1 {
+            match self.read_raw_line()? {
+                Some(more) if !more.is_empty() => record.extend_from_slice(&more),
+                _ => break,
+            }
+        }
+        strip_line_break(&mut record);
+        if record.is_empty() {
+            return Ok(Some(SplFileLine::Csv(vec![None])));
+        }
+        let fields = crate::builtins::string::parse_csv_line(&record, separator, enclosure, escape);
+        Ok(Some(SplFileLine::Csv(
+            fields.into_iter().map(Some).collect(),
+        )))
+    }
+
+    /// Load the line the iterator is positioned on, skipping empty lines when
+    /// SKIP_EMPTY is set
+    /// Reference: spl_filesystem_file_read_line in spl_directory.c
+    fn read_line(&mut self) -> Result<(), String> {
+        loop {
+            let line = if self.flags & SPL_FILE_READ_CSV != 0 {
+                self.read_csv_row(self.csv_separator, self.csv_enclosure, self.csv_escape)?
+            } else {
+                self.read_text_line()?.map(SplFileLine::Text)
+            };
+            let line = line.unwrap_or(SplFileLine::Text(Vec::new()));
+            let empty = match &line {
+                SplFileLine::Text(text) => text.is_empty(),
+                SplFileLine::Csv(fields) => matches!(fields.as_slice(), [None]),
+            };
+            if empty && !self.eof && self.flags & SPL_FILE_SKIP_EMPTY != 0 {
+                self.line_num += 1;
+                continue;
+            }
+            if !(empty && self.eof && self.flags & SPL_FILE_SKIP_EMPTY != 0) {
+                self.current = Some(line);
+            }
+            return Ok(());
+        }
+    }
+
+    /// Drop any buffered read-ahead so the OS file position matches the
+    /// logical position before seeking or writing
+    fn sync_position(&mut self) -> Result<u64, String> {
+        self.reader()?
+            .stream_position()
+            .map_err(|e| e.to_string())
+            .and_then(|pos| {
+                self.reader()?
+                    .seek(SeekFrom::Start(pos))
+                    .map_err(|e| e.to_string())
+            })
+    }
+}
+
+fn strip_line_break(line: &mut Vec<u8>) {
+    if line.ends_with(b"\n") {
+        line.pop();
+        if line.ends_with(b"\r") {
+            line.pop();
+        }
+    }
+}
+
+/// Fetch the file store of `this`, creating an unopened one on first use
+fn file_object_data(vm: &mut VM, this: Handle) -> Result<Rc<RefCell<SplFileObjectData>>, String> {
+    let payload = match &vm.arena.get(this).value {
+        Val::Object(h) => *h,
+        _ => return Err("Invalid 'this' object".into()),
+    };
+    match &mut vm.arena.get_mut(payload).value {
+        Val::ObjPayload(obj) => {
+            if let Some(internal) = &obj.internal
+                && let Ok(data) = internal.clone().downcast::<RefCell<SplFileObjectData>>()
+            {
+                return Ok(data);
+            }
+            let data = Rc::new(RefCell::new(SplFileObjectData::default()));
+            obj.internal = Some(data.clone());
+            Ok(data)
+        }
+        _ => Err("Invalid object payload".into()),
+    }
+}
+
+fn file_line_to_handle(vm: &mut VM, line: SplFileLine) -> Handle {
+    match line {
+        SplFileLine::Text(text) => vm.arena.alloc(Val::String(Rc::new(text))),
+        SplFileLine::Csv(fields) => {
+            let mut arr = ArrayData::new();
+            for field in fields {
+                let value = match field {
+                    Some(bytes) => Val::String(Rc::new(bytes)),
+                    None => Val::Null,
+                };
+                arr.push(vm.arena.alloc(value));
+            }
+            vm.arena.alloc(Val::Array(Rc::new(arr)))
+        }
+    }
+}
+
+/// Raise a RuntimeException from an SplFileObject method
+fn file_object_error(vm: &mut VM, message: String) -> String {
+    let ex = crate::builtins::exception::create_exception(vm, b"RuntimeException", &message);
+    vm.throw_exception(ex);
+    message
+}
+
+/// Read an optional single-byte CSV control argument
+fn csv_control_arg(
+    vm: &VM,
+    args: &[Handle],
+    index: usize,
+    default: Option<u8>,
+    method: &str,
+    name: &str,
+) -> Result<Option<u8>, String> {
+    let Some(handle) = args.get(index) else {
+        return Ok(default);
+    };
+    let bytes = vm.value_to_string(*handle)?;
+    match bytes.as_slice() {
+        [b] => Ok(Some(*b)),
+        [] if name == "escape" => Ok(None),
+        _ => Err(format!(
+            "{}(): Argument #{} (${}) must be a single character",
+            method,
+            index + 1,
+            name
+        )),
+    }
+}
+
+/// SplFileObject::__construct(string $filename, string $mode = "r")
+pub fn spl_file_object_construct(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::__construct")?;
+    let path = match args.first() {
+        Some(h) => vm.value_to_string(*h)?,
+        None => return Err("SplFileObject::__construct() expects at least 1 argument".into()),
+    };
+    let mode = match args.get(1) {
+        Some(h) => vm.value_to_string(*h)?,
+        None => b"r".to_vec(),
+    };
+
+    let mut options = OpenOptions::new();
+    match mode.iter().find(|b| !matches!(b, b'b' | b't')) {
+        Some(b'r') => options.read(true),
+        Some(b'w') => options.write(true).create(true).truncate(true),
+        Some(b'a') => options.append(true).create(true),
+        Some(b'x') => options.write(true).create_new(true),
+        Some(b'c') => options.write(true).create(true),
+        _ => {
+            return Err(format!(
+                "SplFileObject::__construct(): Invalid mode '{}'",
+                String::from_utf8_lossy(&mode)
+            ));
+        }
+    };
+    if mode.contains(&b'+') {
+        options.read(true).write(true);
+    }
+
+    let file = match options.open(String::from_utf8_lossy(&path).as_ref()) {
+        Ok(file) => file,
+        Err(e) => {
+            let message = format!(
+                "SplFileObject::__construct({}): Failed to open stream: {}",
+                String::from_utf8_lossy(&path),
+                e.to_string().split(" (os error").next().unwrap_or_default()
+            );
+            return Err(file_object_error(vm, message));
+        }
+    };
+
+    let data = file_object_data(vm, this)?;
+    let mut data = data.borrow_mut();
+    data.reader = Some(BufReader::new(file));
+    data.path = path;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplFileObject::fgets() - Read the next line
+pub fn spl_file_object_fgets(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::fgets")?;
+    let data = file_object_data(vm, this)?;
+    let line = {
+        let mut data = data.borrow_mut();
+        data.current = None;
+        let line = data.read_text_line()?;
+        if line.is_some() {
+            data.line_num += 1;
+        }
+        line
+    };
+    match line {
+        Some(line) => Ok(vm.arena.alloc(Val::String(Rc::new(line)))),
+        None => {
+            let path = String::from_utf8_lossy(&data.borrow().path).to_string();
+            Err(file_object_error(
+                vm,
+                format!("Cannot read from file {}", path),
+            ))
+        }
+    }
+}
+
+/// SplFileObject::fgetcsv(string $separator = ",", string $enclosure = "\"", string $escape = "\\")
+pub fn spl_file_object_fgetcsv(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::fgetcsv")?;
+    let data = file_object_data(vm, this)?;
+    let (separator, enclosure, escape) = {
+        let data = data.borrow();
+        (data.csv_separator, data.csv_enclosure, data.csv_escape)
+    };
+    let method = "SplFileObject::fgetcsv";
+    let separator = csv_control_arg(vm, args, 0, Some(separator), method, "separator")?;
+    let enclosure = csv_control_arg(vm, args, 1, Some(enclosure), method, "enclosure")?;
+    let escape = csv_control_arg(vm, args, 2, escape, method, "escape")?;
+
+    let row = {
+        let mut data = data.borrow_mut();
+        data.current = None;
+        let row =
+            data.read_csv_row(separator.unwrap_or(b','), enclosure.unwrap_or(b'"'), escape)?;
+        if row.is_some() {
+            data.line_num += 1;
+        }
+        row
+    };
+    match row {
+        Some(row) => Ok(file_line_to_handle(vm, row)),
+        None => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
+}
+
+/// SplFileObject::fread(int $length)
+pub fn spl_file_object_fread(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::fread")?;
+    let length = args
+        .first()
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0);
+    if length <= 0 {
+        return Err("SplFileObject::fread(): Argument #1 ($length) must be greater than 0".into());
+    }
+    let data = file_object_data(vm, this)?;
+    let buf = {
+        let mut data = data.borrow_mut();
+        let mut buf = Vec::with_capacity(length as usize);
+        data.reader()?
+            .take(length as u64)
+            .read_to_end(&mut buf)
+            .map_err(|e| e.to_string())?;
+        if (buf.len() as i64) < length {
+            data.eof = true;
+        }
+        buf
+    };
+    Ok(vm.arena.alloc(Val::String(Rc::new(buf))))
+}
+
+/// SplFileObject::fwrite(string $data, int $length = 0)
+pub fn spl_file_object_fwrite(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::fwrite")?;
+    let mut bytes = match args.first() {
+        Some(h) => vm.value_to_string(*h)?,
+        None => return Err("SplFileObject::fwrite() expects at least 1 argument".into()),
+    };
+    if let Some(length) = args.get(1).map(|h| vm.arena.get(*h).value.to_int())
+        && length > 0
+    {
+        bytes.truncate(length as usize);
+    }
+    let data = file_object_data(vm, this)?;
+    let written = {
+        let mut data = data.borrow_mut();
+        data.sync_position()?;
+        data.reader()?.get_mut().write(&bytes)
+    };
+    match written {
+        Ok(n) => Ok(vm.arena.alloc(Val::Int(n as i64))),
+        Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
+}
+
+/// SplFileObject::fseek(int $offset, int $whence = SEEK_SET) - 0 on success, -1 on failure
+pub fn spl_file_object_fseek(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::fseek")?;
+    let offset = args
+        .first()
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0);
+    let whence = args
+        .get(1)
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0);
+    let target = match whence {
+        0 if offset >= 0 => SeekFrom::Start(offset as u64),
+        1 => SeekFrom::Current(offset),
+        2 => SeekFrom::End(offset),
+        _ => return Ok(vm.arena.alloc(Val::Int(-1))),
+    };
+    let data = file_object_data(vm, this)?;
+    let result = {
+        let mut data = data.borrow_mut();
+        data.current = None;
+        data.eof = false;
+        data.reader()?.seek(target).is_ok()
+    };
+    Ok(vm.arena.alloc(Val::Int(if result { 0 } else { -1 })))
+}
+
+/// SplFileObject::ftell()
+pub fn spl_file_object_ftell(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::ftell")?;
+    let data = file_object_data(vm, this)?;
+    let position = data
+        .borrow_mut()
+        .reader()?
+        .stream_position()
+        .map_err(|e| e.to_string())?;
+    Ok(vm.arena.alloc(Val::Int(position as i64)))
+}
+
+/// SplFileObject::eof()
+pub fn spl_file_object_eof(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::eof")?;
+    let eof = file_object_data(vm, this)?.borrow().eof;
+    Ok(vm.arena.alloc(Val::Bool(eof)))
+}
+
+/// SplFileObject::current() - The current line, read on first access
+pub fn spl_file_object_current(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::current")?;
+    let data = file_object_data(vm, this)?;
+    let line = {
+        let mut data = data.borrow_mut();
+        if data.current.is_none() {
+            data.read_line()?;
+        }
+        data.current.clone()
+    };
+    match line {
+        Some(line) => Ok(file_line_to_handle(vm, line)),
+        None => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
+}
+
+/// SplFileObject::key() - The current line number
+pub fn spl_file_object_key(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::key")?;
+    let line_num = file_object_data(vm, this)?.borrow().line_num;
+    Ok(vm.arena.alloc(Val::Int(line_num)))
+}
+
+/// SplFileObject::next()
+pub fn spl_file_object_next(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::next")?;
+    let data = file_object_data(vm, this)?;
+    let mut data = data.borrow_mut();
+    data.current = None;
+    if data.flags & SPL_FILE_READ_AHEAD != 0 {
+        data.read_line()?;
+    }
+    data.line_num += 1;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplFileObject::valid()
+pub fn spl_file_object_valid(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::valid")?;
+    let data = file_object_data(vm, this)?;
+    let valid = {
+        let mut data = data.borrow_mut();
+        if data.flags & SPL_FILE_READ_AHEAD != 0 {
+            if data.current.is_none() && !data.eof {
+                data.read_line()?;
+            }
+            data.current.is_some()
+        } else if data.flags & SPL_FILE_SKIP_EMPTY != 0 && data.current.is_none() && !data.eof {
+            // Look ahead so trailing empty lines do not produce an extra row
+            data.read_line()?;
+            data.current.is_some()
+        } else {
+            data.current.is_some() || !data.eof
+        }
+    };
+    Ok(vm.arena.alloc(Val::Bool(valid)))
+}
+
+/// SplFileObject::rewind()
+pub fn spl_file_object_rewind(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::rewind")?;
+    let data = file_object_data(vm, this)?;
+    let mut data = data.borrow_mut();
+    data.reader()?
+        .seek(SeekFrom::Start(0))
+        .map_err(|e| e.to_string())?;
+    data.current = None;
+    data.eof = false;
+    data.line_num = 0;
+    if data.flags & SPL_FILE_READ_AHEAD != 0 {
+        data.read_line()?;
+    }
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplFileObject::setFlags(int $flags)
+pub fn spl_file_object_set_flags(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::setFlags")?;
+    let flags = args
+        .first()
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0);
+    file_object_data(vm, this)?.borrow_mut().flags = flags;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// SplFileObject::getFlags()
+pub fn spl_file_object_get_flags(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::getFlags")?;
+    let flags = file_object_data(vm, this)?.borrow().flags;
+    Ok(vm.arena.alloc(Val::Int(flags)))
+}
+
+/// SplFileObject::setCsvControl(string $separator = ",", string $enclosure = "\"", string $escape = "\\")
+pub fn spl_file_object_set_csv_control(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let this = spl_this(vm, "SplFileObject::setCsvControl")?;
+    let method = "SplFileObject::setCsvControl";
+    let separator = csv_control_arg(vm, args, 0, Some(b','), method, "separator")?;
+    let enclosure = csv_control_arg(vm, args, 1, Some(b'"'), method, "enclosure")?;
+    let escape = csv_control_arg(vm, args, 2, Some(b'\\'), method, "escape")?;
+    let data = file_object_data(vm, this)?;
+    let mut data = data.borrow_mut();
+    data.csv_separator = separator.unwrap_or(b',');
+    data.csv_enclosure = enclosure.unwrap_or(b'"');
+    data.csv_escape = escape;
+    Ok(vm.arena.alloc(Val::Null))
+}
+
+/// Register SplFileObject
+pub fn register_spl_file_object_class(registry: &mut ExtensionRegistry) {
+    let mut methods = HashMap::new();
+    for (name, handler) in [
+        (
+            &b"__construct"[..],
+            spl_file_object_construct as crate::runtime::context::NativeHandler,
+        ),
+        (b"fgets", spl_file_object_fgets),
+        (b"fgetcsv", spl_file_object_fgetcsv),
+        (b"fread", spl_file_object_fread),
+        (b"fwrite", spl_file_object_fwrite),
+        (b"fseek", spl_file_object_fseek),
+        (b"ftell", spl_file_object_ftell),
+        (b"eof", spl_file_object_eof),
+        (b"current", spl_file_object_current),
+        (b"key", spl_file_object_key),
+        (b"next", spl_file_object_next),
+        (b"valid", spl_file_object_valid),
+        (b"rewind", spl_file_object_rewind),
+        (b"setFlags", spl_file_object_set_flags),
+        (b"getFlags", spl_file_object_get_flags),
+        (b"setCsvControl", spl_file_object_set_csv_control),
+    ] {
+        methods.insert(name.to_vec(), public_method(handler));
+    }
+
+    let mut constants = HashMap::new();
+    for (name, value) in [
+        (&b"DROP_NEW_LINE"[..], SPL_FILE_DROP_NEW_LINE),
+        (b"READ_AHEAD", SPL_FILE_READ_AHEAD),
+        (b"SKIP_EMPTY", SPL_FILE_SKIP_EMPTY),
+        (b"READ_CSV", SPL_FILE_READ_CSV),
+    ] {
+        constants.insert(name.to_vec(), (Val::Int(value), Visibility::Public));
+    }
+
+    registry.register_class(NativeClassDef {
+        name: b"SplFileObject".to_vec(),
+        parent: None,
+        is_interface: false,
+        is_trait: false,
+        is_final: false,
+        interfaces: vec![b"Iterator".to_vec()],
+        methods,
+        constants,
+        constructor: Some(spl_file_object_construct),
+        extension_name: None,
+    });
+}
@@ -1296,7 +1296,12 @@ pub fn php_str_getcsv(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     )))
 }
 
-fn parse_csv_line(input: &[u8], delimiter: u8, enclosure: u8, escape: Option<u8>) -> Vec<Vec<u8>> {
+pub(crate) fn parse_csv_line(
+    input: &[u8],
+    delimiter: u8,
+    enclosure: u8,
+    escape: Option<u8>,
+) -> Vec<Vec<u8>> {
     let mut fields = Vec::new();
     let mut field = Vec::new();
     let mut in_quotes = false;
@@ -1892,7 +1897,10 @@ fn insert_parse_str_value(
 
         if is_last {
             match segment {
-                None => current_array.push(value_handle),
+                // Like php_register_variable_ex, a full array drops the value
+                None => {
+                    current_array.push(value_handle);
+                }
                 Some(name) => {
                     current_array.insert(array_key_from_bytes(name), value_handle);
                 }
@@ -1945,12 +1953,7 @@ fn ensure_array_for_key(vm: &mut VM, array: &mut ArrayData, key: ArrayKey) -> Ha
 }
 
 fn array_key_from_bytes(bytes: &[u8]) -> ArrayKey {
-    if let Ok(s) = std::str::from_utf8(bytes) {
-        if let Ok(num) = s.parse::<i64>() {
-            return ArrayKey::Int(num);
-        }
-    }
-    ArrayKey::Str(Rc::new(bytes.to_vec()))
+    ArrayKey::from_string(Rc::new(bytes.to_vec()))
 }
 
 pub fn php_strcmp(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
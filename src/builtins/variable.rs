@@ -1,67 +1,67 @@
-use crate::core::value::{ArrayData, ArrayKey, Handle, Val};
+use crate::core::value::{ArrayData, ArrayKey, Handle, Val, format_float_serialize};
 use crate::vm::engine::VM;
+use std::collections::BTreeMap;
 use std::fmt::Write as FmtWrite;
 use std::rc::Rc;
 
 pub fn php_var_dump(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     let mut output = String::new();
     for arg in args {
-        // Check for __debugInfo
-        let class_sym = if let Val::Object(obj_handle) = vm.arena.get(*arg).value {
-            if let Val::ObjPayload(obj_data) = &vm.arena.get(obj_handle).value {
-                Some((obj_handle, obj_data.class))
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        dump_value(vm, *arg, 0, &mut output)?;
+    }
+    vm.print_bytes(output.as_bytes())?;
+    Ok(vm.arena.alloc(Val::Null))
+}
 
-        if let Some((obj_handle, class)) = class_sym {
-            let debug_info_sym = vm.context.interner.intern(b"__debugInfo");
-            if let Some((method, _, _, _)) = vm.find_method(class, debug_info_sym) {
-                let mut frame = crate::vm::frame::CallFrame::new(method.chunk.clone());
-                frame.func = Some(method.clone());
-                frame.this = Some(obj_handle);
-                frame.class_scope = Some(class);
-
-                let res = vm.run_frame(frame);
-                if let Ok(res_handle) = res {
-                    let res_val = vm.arena.get(res_handle);
-                    if let Val::Array(arr) = &res_val.value {
-                        let class_name = String::from_utf8_lossy(
-                            vm.context.interner.lookup(class).unwrap_or(b""),
-                        );
-                        let _ = writeln!(output, "object({}) ({}) {{", class_name, arr.map.len());
-                        for (key, val_handle) in arr.map.iter() {
-                            match key {
-                                crate::core::value::ArrayKey::Int(i) => {
-                                    let _ = writeln!(output, "  [{}]=>", i);
-                                }
-                                crate::core::value::ArrayKey::Str(s) => {
-                                    let _ = writeln!(
-                                        output,
-                                        "  [\"{}\"]=>",
-                                        String::from_utf8_lossy(s)
-                                    );
-                                }
-                            }
-                            dump_value(vm, *val_handle, 1, &mut output);
-                        }
-                        output.push_str("}\n");
-                        continue;
-                    }
-                }
+/// The array returned by the object's __debugInfo(), or None when its class
+/// doesn't define one
+/// Reference: $PHP_SRC_PATH/Zend/zend_object_handlers.c - zend_std_get_debug_info
+fn debug_info(
+    vm: &mut VM,
+    handle: Handle,
+    class: crate::core::value::Symbol,
+) -> Result<Option<Rc<ArrayData>>, String> {
+    let debug_info_sym = vm.context.interner.intern(b"__debugInfo");
+    if vm.find_method(class, debug_info_sym).is_none() {
+        return Ok(None);
+    }
+    let res_handle = vm
+        .call_method_simple(handle, debug_info_sym)
+        .map_err(|e| e.to_string())?;
+    match &vm.arena.get(res_handle).value {
+        Val::Array(arr) => Ok(Some(arr.clone())),
+        Val::Null => Ok(Some(Rc::new(ArrayData::new()))),
+        _ => Err("__debuginfo() must return an array".into()),
+    }
+}
+
+fn dump_array_entries(
+    vm: &mut VM,
+    arr: &ArrayData,
+    depth: usize,
+    output: &mut String,
+) -> Result<(), String> {
+    let indent = "  ".repeat(depth);
+    for (key, val_handle) in arr.map.iter() {
+        match key {
+            ArrayKey::Int(i) => {
+                let _ = writeln!(output, "{}  [{}]=>", indent, i);
+            }
+            ArrayKey::Str(s) => {
+                let _ = writeln!(output, "{}  [\"{}\"]=>", indent, String::from_utf8_lossy(s));
             }
         }
-
-        dump_value(vm, *arg, 0, &mut output);
+        dump_value(vm, *val_handle, depth + 1, output)?;
     }
-    vm.print_bytes(output.as_bytes())?;
-    Ok(vm.arena.alloc(Val::Null))
+    Ok(())
 }
 
-fn dump_value(vm: &VM, handle: Handle, depth: usize, output: &mut String) {
+fn dump_value(
+    vm: &mut VM,
+    handle: Handle,
+    depth: usize,
+    output: &mut String,
+) -> Result<(), String> {
     let val = vm.arena.get(handle);
     let indent = "  ".repeat(depth);
 
@@ -79,7 +79,12 @@ fn dump_value(vm: &VM, handle: Handle, depth: usize, output: &mut String) {
             let _ = writeln!(output, "{}int({})", indent, i);
         }
         Val::Float(f) => {
-            let _ = writeln!(output, "{}float({})", indent, f);
+            let _ = writeln!(
+                output,
+                "{}float({})",
+                indent,
+                format_float_serialize(*f, serialize_precision(vm), 'E')
+            );
         }
         Val::Bool(b) => {
             let _ = writeln!(output, "{}bool({})", indent, b);
@@ -97,56 +102,57 @@ fn dump_value(vm: &VM, handle: Handle, depth: usize, output: &mut String) {
             );
         }
         Val::Array(arr) => {
+            let arr = arr.clone();
             let _ = writeln!(output, "{}array({}) {{", indent, arr.map.len());
-            for (key, val_handle) in arr.map.iter() {
-                match key {
-                    crate::core::value::ArrayKey::Int(i) => {
-                        let _ = writeln!(output, "{}  [{}]=>", indent, i);
-                    }
-                    crate::core::value::ArrayKey::Str(s) => {
-                        let _ =
-                            writeln!(output, "{}  [\"{}\"]=>", indent, String::from_utf8_lossy(s));
-                    }
-                }
-                dump_value(vm, *val_handle, depth + 1, output);
-            }
+            dump_array_entries(vm, &arr, depth, output)?;
             let _ = writeln!(output, "{}}}", indent);
         }
-        Val::Object(handle) => {
-            // Dereference the object payload
-            let payload_val = vm.arena.get(*handle);
-            if let Val::ObjPayload(obj) = &payload_val.value {
-                let class_name = vm
-                    .context
-                    .interner
-                    .lookup(obj.class)
-                    .unwrap_or(b"<unknown>");
+        Val::Object(payload_handle) => {
+            let payload_handle = *payload_handle;
+            let Val::ObjPayload(obj) = &vm.arena.get(payload_handle).value else {
+                let _ = writeln!(output, "{}object(INVALID)", indent);
+                return Ok(());
+            };
+            let class = obj.class;
+            let properties: Vec<_> = obj.properties.iter().map(|(k, v)| (*k, *v)).collect();
+            let class_name =
+                String::from_utf8_lossy(vm.context.interner.lookup(class).unwrap_or(b"<unknown>"))
+                    .into_owned();
+
+            // __debugInfo() replaces the property list
+            if let Some(info) = debug_info(vm, handle, class)? {
                 let _ = writeln!(
                     output,
                     "{}object({})#{} ({}) {{",
                     indent,
-                    String::from_utf8_lossy(class_name),
-                    handle.0,
-                    obj.properties.len()
+                    class_name,
+                    payload_handle.0,
+                    info.map.len()
                 );
-                for (prop_sym, prop_handle) in &obj.properties {
-                    let prop_name = vm
-                        .context
-                        .interner
-                        .lookup(*prop_sym)
-                        .unwrap_or(b"<unknown>");
-                    let _ = writeln!(
-                        output,
-                        "{}  [\"{}\"]=>",
-                        indent,
-                        String::from_utf8_lossy(prop_name)
-                    );
-                    dump_value(vm, *prop_handle, depth + 1, output);
-                }
+                dump_array_entries(vm, &info, depth, output)?;
                 let _ = writeln!(output, "{}}}", indent);
-            } else {
-                let _ = writeln!(output, "{}object(INVALID)", indent);
+                return Ok(());
             }
+
+            let _ = writeln!(
+                output,
+                "{}object({})#{} ({}) {{",
+                indent,
+                class_name,
+                payload_handle.0,
+                properties.len()
+            );
+            for (prop_sym, prop_handle) in properties {
+                let prop_name = vm.context.interner.lookup(prop_sym).unwrap_or(b"<unknown>");
+                let _ = writeln!(
+                    output,
+                    "{}  [\"{}\"]=>",
+                    indent,
+                    String::from_utf8_lossy(prop_name)
+                );
+                dump_value(vm, prop_handle, depth + 1, output)?;
+            }
+            let _ = writeln!(output, "{}}}", indent);
         }
         Val::ObjPayload(_) => {
             let _ = writeln!(output, "{}ObjPayload(Internal)", indent);
@@ -161,6 +167,7 @@ fn dump_value(vm: &VM, handle: Handle, depth: usize, output: &mut String) {
             let _ = writeln!(output, "{}uninitialized", indent);
         }
     }
+    Ok(())
 }
 
 pub fn php_var_export(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
@@ -208,7 +215,12 @@ fn export_value(vm: &VM, handle: Handle, depth: usize, output: &mut String) {
             output.push_str(&i.to_string());
         }
         Val::Float(f) => {
-            output.push_str(&f.to_string());
+            let repr = format_float_serialize(*f, serialize_precision(vm), 'E');
+            output.push_str(&repr);
+            // var_export keeps a zero fraction so the value reads back as float
+            if f.is_finite() && !repr.contains(['.', 'E']) {
+                output.push_str(".0");
+            }
         }
         Val::Bool(b) => {
             output.push_str(if *b { "true" } else { "false" });
@@ -497,6 +509,52 @@ pub fn php_defined(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     Ok(vm.arena.alloc(Val::Bool(exists)))
 }
 
+/// get_defined_constants(bool $categorize = false): array
+///
+/// With `$categorize`, constants are grouped by the extension that registered
+/// them, with "Core" first and user-defined constants last under "user".
+pub fn php_get_defined_constants(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let categorize = args
+        .first()
+        .is_some_and(|&h| vm.arena.get(h).value.to_bool());
+
+    // (sort rank, category) -> constants sorted by name
+    let mut categories: BTreeMap<(u8, Vec<u8>), BTreeMap<Vec<u8>, Val>> = BTreeMap::new();
+    for (sym, val) in &vm.context.constants {
+        let Some(name) = vm.context.interner.lookup(*sym) else {
+            continue;
+        };
+        let category: (u8, &[u8]) = match vm.context.engine.registry.constants().get(name) {
+            Some(entry) => match entry.extension_name.as_deref() {
+                Some(b"Core") | None => (0, b"Core"),
+                Some(extension) => (1, extension),
+            },
+            None if vm.context.builtin_constants.contains(sym) => (0, b"Core"),
+            None => (2, b"user"),
+        };
+        categories
+            .entry((category.0, category.1.to_vec()))
+            .or_default()
+            .insert(name.to_vec(), val.clone());
+    }
+
+    let mut result = ArrayData::new();
+    for ((_, category), constants) in categories {
+        let mut group = ArrayData::new();
+        let target = if categorize { &mut group } else { &mut result };
+        for (name, val) in constants {
+            let handle = vm.arena.alloc(val);
+            target.insert(ArrayKey::Str(Rc::new(name)), handle);
+        }
+        if categorize {
+            let group_handle = vm.arena.alloc(Val::Array(Rc::new(group)));
+            result.insert(ArrayKey::Str(Rc::new(category)), group_handle);
+        }
+    }
+
+    Ok(vm.arena.alloc(Val::Array(Rc::new(result))))
+}
+
 pub fn php_constant(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() != 1 {
         return Err("constant() expects exactly 1 parameter".into());
@@ -750,6 +808,16 @@ pub fn php_getopt(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     Ok(vm.arena.alloc(Val::Array(map.into())))
 }
 
+/// The serialize_precision ini setting; -1 (shortest round-trip) by default
+pub(crate) fn serialize_precision(vm: &VM) -> i64 {
+    vm.context
+        .config
+        .ini_settings
+        .get("serialize_precision")
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(-1)
+}
+
 pub fn php_ini_get(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
         return Err("ini_get() expects exactly 1 parameter".into());
@@ -775,6 +843,7 @@ pub fn php_ini_get(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         "max_execution_time" => vm.context.config.max_execution_time.to_string(),
         "upload_max_filesize" => "2M".to_string(),
         "post_max_size" => "8M".to_string(),
+        "serialize_precision" => "-1".to_string(),
         _ => "".to_string(), // Unknown settings return empty string
     };
 
@@ -978,16 +1047,9 @@ fn serialize_value(vm: &VM, handle: Handle) -> Result<Vec<u8>, String> {
         }
         Val::Float(f) => {
             result.extend_from_slice(b"d:");
-            // Format float like PHP does
-            let f_str = if f.is_infinite() {
-                if f.is_sign_positive() { "INF" } else { "-INF" }
-            } else if f.is_nan() {
-                "NAN"
-            } else {
-                // PHP uses specific formatting for floats
-                &format!("{}", f)
-            };
-            result.extend_from_slice(f_str.as_bytes());
+            result.extend_from_slice(
+                format_float_serialize(*f, serialize_precision(vm), 'E').as_bytes(),
+            );
             result.push(b';');
         }
         Val::String(s) => {
@@ -1110,14 +1172,9 @@ fn serialize_val(vm: &VM, val: &Val) -> Result<Vec<u8>, String> {
         }
         Val::Float(f) => {
             result.extend_from_slice(b"d:");
-            let f_str = if f.is_infinite() {
-                if f.is_sign_positive() { "INF" } else { "-INF" }
-            } else if f.is_nan() {
-                "NAN"
-            } else {
-                &format!("{}", f)
-            };
-            result.extend_from_slice(f_str.as_bytes());
+            result.extend_from_slice(
+                format_float_serialize(*f, serialize_precision(vm), 'E').as_bytes(),
+            );
             result.push(b';');
         }
         Val::String(s) => {
@@ -1,24 +1,44 @@
 use crate::core::value::{ArrayData, ArrayKey, Handle, Val, Visibility};
 use crate::runtime::registry::{ExtensionRegistry, NativeClassDef, NativeMethodEntry};
-use crate::vm::engine::VM;
+use crate::vm::engine::{ErrorLevel, VM};
 use indexmap::IndexMap;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 use zip::ZipArchive;
 
 #[derive(Debug)]
 pub struct ZipArchiveWrapper {
     pub path: String,
+    /// libzip error code (ZipArchive::ER_*) exposed as the `status` property
     pub last_error: i64,
-    pub status: i64,
+    /// System errno accompanying `last_error`, exposed as `statusSys`
+    pub status_sys: i64,
     #[allow(dead_code)]
     pub reader: Option<ZipArchive<File>>,
     pub password: Option<String>,
+    /// Pending archive comment set via setArchiveComment(), written on close()
+    pub comment: Option<Vec<u8>>,
     pub additions: IndexMap<String, Vec<u8>>,
     pub deletions: HashSet<String>,
+    /// Per-entry method and level requested via setCompressionName() or
+    /// setCompressionIndex(), applied when close() writes the entry
+    pub compression: HashMap<String, (zip::CompressionMethod, Option<i64>)>,
+    /// Per-entry AES mode (None for EM_NONE) and password requested via
+    /// setEncryptionName() or setEncryptionIndex(); a missing password falls
+    /// back to the one given to setPassword()
+    pub encryption: HashMap<String, (Option<zip::AesMode>, Option<String>)>,
+    /// Per-entry modification time (UNIX timestamp) set via setMtimeName() or
+    /// setMtimeIndex(), applied when close() writes the entry
+    pub mtimes: HashMap<String, i64>,
+    /// Per-entry comments set via setCommentName() or setCommentIndex(),
+    /// written on close()
+    pub comments: HashMap<String, Vec<u8>>,
+    /// Opened with ZipArchive::OVERWRITE: the old file was emptied and
+    /// close() writes a fresh archive even without pending changes
+    pub truncate: bool,
     pub current_entry_index: usize,
 }
 
@@ -27,14 +47,255 @@ impl ZipArchiveWrapper {
         Self {
             path: String::new(),
             last_error: 0,
-            status: 0,
+            status_sys: 0,
             reader: None,
             password: None,
+            comment: None,
             additions: IndexMap::new(),
             deletions: HashSet::new(),
+            compression: HashMap::new(),
+            encryption: HashMap::new(),
+            mtimes: HashMap::new(),
+            comments: HashMap::new(),
+            truncate: false,
             current_entry_index: 0,
         }
     }
+
+    /// Number of entries the archive will hold after close(): stored entries
+    /// that are neither deleted nor replaced, plus pending additions. Backs
+    /// both count() and the `numFiles` property.
+    pub fn num_entries(&self) -> usize {
+        let stored = self.reader.as_ref().map_or(0, |reader| {
+            reader
+                .file_names()
+                .filter(|name| {
+                    !self.deletions.contains(*name) && !self.additions.contains_key(*name)
+                })
+                .count()
+        });
+        stored + self.additions.len()
+    }
+
+    /// Name of the live entry at `index`: stored entries come first,
+    /// followed by pending additions
+    pub fn entry_name(&self, index: usize) -> Option<String> {
+        let reader_len = self.reader.as_ref().map_or(0, |r| r.len());
+        if index < reader_len {
+            self.reader
+                .as_ref()
+                .and_then(|r| r.name_for_index(index))
+                .filter(|name| !self.deletions.contains(*name))
+                .map(str::to_string)
+        } else {
+            self.additions
+                .get_index(index - reader_len)
+                .map(|(name, _)| name.clone())
+        }
+    }
+
+    /// Whether `name` is a live entry, stored or pending
+    pub fn has_entry(&self, name: &str) -> bool {
+        self.additions.contains_key(name)
+            || (!self.deletions.contains(name)
+                && self
+                    .reader
+                    .as_ref()
+                    .is_some_and(|reader| reader.index_for_name(name).is_some()))
+    }
+
+    /// Mark `name` for deletion, dropping any pending addition under that
+    /// name. Returns false when no live entry has that name.
+    pub fn delete_entry(&mut self, name: &str) -> bool {
+        let stored = !self.deletions.contains(name)
+            && self
+                .reader
+                .as_ref()
+                .is_some_and(|reader| reader.index_for_name(name).is_some());
+        let added = self.additions.shift_remove(name).is_some();
+        if stored {
+            self.deletions.insert(name.to_string());
+        }
+        stored || added
+    }
+}
+
+/// Entry resource returned by zip_read()
+#[derive(Debug)]
+pub struct ZipEntryResource {
+    /// Resource id of the owning zip_open() archive
+    pub zip_id: u64,
+    pub index: usize,
+    /// Read cursor for zip_entry_read()
+    pub position: usize,
+    /// Decompressed contents, loaded by zip_entry_open() or the first read
+    pub content: Option<Vec<u8>>,
+}
+
+/// Read-only stream resource returned by ZipArchive::getStream()/getStreamIndex()
+///
+/// Stored and deflated members are decompressed incrementally from the archive
+/// file; pending additions are read from their in-memory buffer.
+pub struct ZipEntryStream {
+    pub reader: RefCell<Box<dyn std::io::Read>>,
+    pub eof: RefCell<bool>,
+}
+
+impl ZipEntryStream {
+    fn new(reader: Box<dyn std::io::Read>) -> Self {
+        Self {
+            reader: RefCell::new(reader),
+            eof: RefCell::new(false),
+        }
+    }
+}
+
+/// Map a libzip error code to the message returned by zip_error_strerror()
+/// Reference: libzip lib/zip_err_str.c
+fn zip_error_string(code: i64) -> &'static str {
+    match code {
+        0 => "No error",
+        1 => "Multi-disk zip archives not supported",
+        2 => "Renaming temporary file failed",
+        3 => "Closing zip archive failed",
+        4 => "Seek error",
+        5 => "Read error",
+        6 => "Write error",
+        7 => "CRC error",
+        8 => "Containing zip archive was closed",
+        9 => "No such file",
+        10 => "File already exists",
+        11 => "Can't open file",
+        12 => "Failure to create temporary file",
+        13 => "Zlib error",
+        14 => "Malloc failure",
+        15 => "Entry has been changed",
+        16 => "Compression method not supported",
+        17 => "Premature end of file",
+        18 => "Invalid argument",
+        19 => "Not a zip archive",
+        20 => "Internal error",
+        21 => "Zip archive inconsistent",
+        22 => "Can't remove file",
+        23 => "Entry has been deleted",
+        24 => "Encryption method not supported",
+        25 => "Read-only archive",
+        26 => "No password provided",
+        27 => "Wrong password provided",
+        28 => "Operation not supported",
+        29 => "Resource still in use",
+        30 => "Tell error",
+        31 => "Compressed data invalid",
+        32 => "Operation cancelled",
+        _ => "Unknown error",
+    }
+}
+
+/// Format a full status string, appending the system error for the
+/// ER_* codes libzip classifies as ZIP_ET_SYS.
+fn zip_status_string(code: i64, sys: i64) -> String {
+    let base = zip_error_string(code);
+    // ER_RENAME, ER_CLOSE, ER_SEEK, ER_READ, ER_WRITE, ER_OPEN, ER_TMPOPEN, ER_REMOVE, ER_TELL
+    let is_sys = matches!(code, 2..=6 | 11 | 12 | 22 | 30);
+    if is_sys && sys != 0 {
+        let err = std::io::Error::from_raw_os_error(sys as i32).to_string();
+        // Drop Rust's " (os error N)" suffix to match strerror()
+        let msg = match err.rfind(" (os error ") {
+            Some(pos) => &err[..pos],
+            None => err.as_str(),
+        };
+        format!("{}: {}", base, msg)
+    } else {
+        base.to_string()
+    }
+}
+
+/// Open an existing archive for reading, mapping failures to the
+/// (ZipArchive::ER_*, errno) pair reported by open() and zip_open().
+/// ZipArchive::CHECKCONS in `flags` also verifies every central directory entry.
+fn open_zip_reader(path: &Path, flags: i64) -> Result<ZipArchive<File>, (i64, i64)> {
+    let file = File::open(path).map_err(|e| {
+        let sys = e.raw_os_error().unwrap_or(0) as i64;
+        (11, sys) // ER_OPEN
+    })?;
+    let mut archive = match ZipArchive::new(file) {
+        Ok(archive) => archive,
+        Err(zip::result::ZipError::Io(e)) => {
+            let sys = e.raw_os_error().unwrap_or(0) as i64;
+            return Err((5, sys)); // ER_READ
+        }
+        Err(_) => return Err((19, 0)), // ER_NOZIP
+    };
+
+    if flags & 4 != 0 {
+        // ZipArchive::CHECKCONS: every central directory entry must resolve
+        let consistent = (0..archive.len()).all(|i| archive.by_index_raw(i).is_ok());
+        if !consistent {
+            return Err((21, 0)); // ER_INCONS
+        }
+    }
+
+    Ok(archive)
+}
+
+/// Register `value` under a fresh resource ID and return the PHP resource
+/// for it, as the procedural zip_* functions hand out
+fn alloc_zip_resource<T: 'static>(vm: &mut VM, value: T) -> Handle {
+    let resource_id = vm.context.next_resource_id;
+    vm.context.next_resource_id += 1;
+    vm.context
+        .resource_manager
+        .register(resource_id, Rc::new(RefCell::new(value)));
+    vm.arena.alloc(Val::Resource(Rc::new(resource_id)))
+}
+
+/// Register `wrapper` as the archive backing `this_handle`
+fn attach_zip_wrapper(
+    vm: &mut VM,
+    this_handle: Handle,
+    wrapper: ZipArchiveWrapper,
+) -> Result<Rc<RefCell<ZipArchiveWrapper>>, String> {
+    let archive_id = vm.context.next_resource_id;
+    vm.context.next_resource_id += 1;
+    let wrapper_rc = Rc::new(RefCell::new(wrapper));
+    vm.context
+        .resource_manager
+        .register(archive_id, wrapper_rc.clone());
+
+    let obj_handle = match &vm.arena.get(this_handle).value {
+        Val::Object(h) => *h,
+        _ => return Err("Invalid 'this' object".into()),
+    };
+
+    let id_sym = vm.context.interner.intern(b"__id");
+    let id_val = vm.arena.alloc(Val::Int(archive_id as i64));
+
+    if let Val::ObjPayload(obj) = &mut vm.arena.get_mut(obj_handle).value {
+        obj.properties.insert(id_sym, id_val);
+    }
+
+    Ok(wrapper_rc)
+}
+
+/// Record a failed open(): the archive is left closed and the error code is
+/// kept for `status`/`statusSys` and getStatusString()
+fn fail_zip_open(vm: &mut VM, code: i64, sys: i64) -> Result<Handle, String> {
+    if let Some(this_handle) = vm.frames.last().and_then(|f| f.this) {
+        let mut wrapper = ZipArchiveWrapper::new();
+        wrapper.last_error = code;
+        wrapper.status_sys = sys;
+
+        let wrapper_rc = match get_zip_wrapper(vm, this_handle) {
+            Ok(existing) => {
+                *existing.borrow_mut() = wrapper;
+                existing
+            }
+            Err(_) => attach_zip_wrapper(vm, this_handle, wrapper)?,
+        };
+        update_zip_properties(vm, this_handle, &wrapper_rc.borrow())?;
+    }
+
+    Ok(vm.arena.alloc(Val::Int(code)))
 }
 
 fn get_zip_wrapper<'a>(
@@ -118,6 +379,36 @@ pub fn register_zip_extension_to_registry(registry: &mut ExtensionRegistry) {
         },
     );
 
+    zip_methods.insert(
+        b"addGlob".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_add_glob,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"addPattern".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_add_pattern,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"replaceFile".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_replace_file,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
     zip_methods.insert(
         b"count".to_vec(),
         NativeMethodEntry {
@@ -188,6 +479,26 @@ pub fn register_zip_extension_to_registry(registry: &mut ExtensionRegistry) {
         },
     );
 
+    zip_methods.insert(
+        b"getStream".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_get_stream,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"getStreamIndex".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_get_stream_index,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
     zip_methods.insert(
         b"getFromName".to_vec(),
         NativeMethodEntry {
@@ -218,6 +529,126 @@ pub fn register_zip_extension_to_registry(registry: &mut ExtensionRegistry) {
         },
     );
 
+    zip_methods.insert(
+        b"setCompressionName".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_set_compression_name,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"setCompressionIndex".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_set_compression_index,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"setEncryptionName".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_set_encryption_name,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"setEncryptionIndex".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_set_encryption_index,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"setMtimeName".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_set_mtime_name,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"setMtimeIndex".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_set_mtime_index,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"setArchiveComment".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_set_archive_comment,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"getArchiveComment".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_get_archive_comment,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"setCommentName".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_set_comment_name,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"setCommentIndex".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_set_comment_index,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"getCommentName".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_get_comment_name,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
+    zip_methods.insert(
+        b"getCommentIndex".to_vec(),
+        NativeMethodEntry {
+            handler: php_zip_archive_get_comment_index,
+            visibility: Visibility::Public,
+            is_static: false,
+            is_final: false,
+        },
+    );
+
     zip_methods.insert(
         b"locateName".to_vec(),
         NativeMethodEntry {
@@ -409,7 +840,7 @@ pub fn register_zip_extension_to_registry(registry: &mut ExtensionRegistry) {
         is_interface: false,
         is_trait: false,
         is_final: false,
-        interfaces: Vec::new(),
+        interfaces: vec![b"Countable".to_vec()],
         methods: zip_methods,
         constants: zip_constants,
         constructor: None,
@@ -437,24 +868,21 @@ fn update_zip_properties(
     this_handle: Handle,
     wrapper: &ZipArchiveWrapper,
 ) -> Result<(), String> {
-    let base_count = wrapper.reader.as_ref().map(|r| r.len()).unwrap_or(0);
-    let num_files = (base_count + wrapper.additions.len() - wrapper.deletions.len()) as i64;
+    let num_files = wrapper.num_entries() as i64;
     let filename = wrapper.path.clone();
-    let comment = wrapper
-        .reader
-        .as_ref()
-        .map(|r| r.comment().to_vec())
-        .unwrap_or_default();
+    let comment = archive_comment(wrapper, false);
 
     let num_files_sym = vm.context.interner.intern(b"numFiles");
     let filename_sym = vm.context.interner.intern(b"filename");
     let comment_sym = vm.context.interner.intern(b"comment");
     let status_sym = vm.context.interner.intern(b"status");
+    let status_sys_sym = vm.context.interner.intern(b"statusSys");
 
     let num_files_handle = vm.arena.alloc(Val::Int(num_files));
     let filename_handle = vm.arena.alloc(Val::String(Rc::new(filename.into_bytes())));
     let comment_handle = vm.arena.alloc(Val::String(Rc::new(comment)));
-    let status_handle = vm.arena.alloc(Val::Int(0)); // Success for now
+    let status_handle = vm.arena.alloc(Val::Int(wrapper.last_error));
+    let status_sys_handle = vm.arena.alloc(Val::Int(wrapper.status_sys));
 
     let this_val = vm.arena.get(this_handle);
     if let Val::Object(obj_handle) = &this_val.value {
@@ -465,6 +893,9 @@ fn update_zip_properties(
             obj_data.properties.insert(filename_sym, filename_handle);
             obj_data.properties.insert(comment_sym, comment_handle);
             obj_data.properties.insert(status_sym, status_handle);
+            obj_data
+                .properties
+                .insert(status_sys_sym, status_sys_handle);
         }
     }
 
@@ -495,12 +926,12 @@ pub fn php_zip_archive_open(vm: &mut VM, args: &[Handle]) -> Result<Handle, Stri
 
     if (flags & 2 != 0) && exists {
         // ZipArchive::EXCL
-        return Ok(vm.arena.alloc(Val::Int(10))); // ER_EXISTS
+        return fail_zip_open(vm, 10, 0); // ER_EXISTS
     }
 
     if !exists && (flags & 1 == 0) {
         // Not ZipArchive::CREATE
-        return Ok(vm.arena.alloc(Val::Int(9))); // ER_NOENT
+        return fail_zip_open(vm, 9, 0); // ER_NOENT
     }
 
     let mut wrapper = ZipArchiveWrapper::new();
@@ -508,41 +939,22 @@ pub fn php_zip_archive_open(vm: &mut VM, args: &[Handle]) -> Result<Handle, Stri
 
     if exists && (flags & 8 == 0) {
         // Not ZipArchive::OVERWRITE, try to open existing
-        match File::open(path) {
-            Ok(file) => match ZipArchive::new(file) {
-                Ok(archive) => {
-                    wrapper.reader = Some(archive);
-                }
-                Err(_) => {
-                    return Ok(vm.arena.alloc(Val::Int(19))); // ER_NOZIP
-                }
-            },
-            Err(_) => {
-                return Ok(vm.arena.alloc(Val::Int(11))); // ER_OPEN
-            }
+        match open_zip_reader(path, flags) {
+            Ok(archive) => wrapper.reader = Some(archive),
+            Err((code, sys)) => return fail_zip_open(vm, code, sys),
         }
+    } else if exists {
+        // ZipArchive::OVERWRITE: drop the old contents right away
+        if let Err(e) = File::create(path) {
+            let sys = e.raw_os_error().unwrap_or(0) as i64;
+            return fail_zip_open(vm, 11, sys); // ER_OPEN
+        }
+        wrapper.truncate = true;
     }
 
-    let archive_id = vm.context.next_resource_id;
-    vm.context.next_resource_id += 1;
-    let wrapper_rc = Rc::new(RefCell::new(wrapper));
-    vm.context
-        .resource_manager
-        .register(archive_id, wrapper_rc.clone());
-
     // Store ID in object
     if let Some(this_handle) = vm.frames.last().and_then(|f| f.this) {
-        let obj_handle = match &vm.arena.get(this_handle).value {
-            Val::Object(h) => *h,
-            _ => return Err("No 'this' in ZipArchive::open".into()),
-        };
-
-        let id_sym = vm.context.interner.intern(b"__id");
-        let id_val = vm.arena.alloc(Val::Int(archive_id as i64));
-
-        if let Val::ObjPayload(obj) = &mut vm.arena.get_mut(obj_handle).value {
-            obj.properties.insert(id_sym, id_val);
-        }
+        let wrapper_rc = attach_zip_wrapper(vm, this_handle, wrapper)?;
 
         // Update properties
         update_zip_properties(vm, this_handle, &wrapper_rc.borrow())?;
@@ -560,7 +972,15 @@ pub fn php_zip_archive_close(vm: &mut VM, _args: &[Handle]) -> Result<Handle, St
     let wrapper_rc = get_zip_wrapper(vm, this_handle)?;
     let mut wrapper = wrapper_rc.borrow_mut();
 
-    if wrapper.additions.is_empty() && wrapper.deletions.is_empty() {
+    if wrapper.additions.is_empty()
+        && wrapper.deletions.is_empty()
+        && wrapper.comment.is_none()
+        && wrapper.compression.is_empty()
+        && wrapper.encryption.is_empty()
+        && wrapper.mtimes.is_empty()
+        && wrapper.comments.is_empty()
+        && !wrapper.truncate
+    {
         wrapper.reader = None;
         return Ok(vm.arena.alloc(Val::Bool(true)));
     }
@@ -569,52 +989,33 @@ pub fn php_zip_archive_close(vm: &mut VM, _args: &[Handle]) -> Result<Handle, St
     let path = wrapper.path.clone();
     let temp_path = format!("{}.tmp", path);
 
-    {
-        let file = File::create(&temp_path).map_err(|e| e.to_string())?;
-        let mut writer = zip::ZipWriter::new(file);
-
-        let deletions = wrapper.deletions.clone();
-
-        // Copy old entries (if not deleted)
-        if let Some(reader) = &mut wrapper.reader {
-            for i in 0..reader.len() {
-                let mut entry = reader.by_index(i).map_err(|e| e.to_string())?;
-                let name = entry.name().to_string();
-
-                if deletions.contains(&name) {
-                    continue;
-                }
-
-                let options = zip::write::SimpleFileOptions::default()
-                    .compression_method(entry.compression())
-                    .last_modified_time(entry.last_modified().unwrap_or_default());
-
-                writer
-                    .start_file(name, options)
-                    .map_err(|e| e.to_string())?;
-                std::io::copy(&mut entry, &mut writer).map_err(|e| e.to_string())?;
-            }
-        }
-
-        // Add new entries
-        for (name, content) in &wrapper.additions {
-            let options = zip::write::SimpleFileOptions::default();
-            writer
-                .start_file(name, options)
-                .map_err(|e| e.to_string())?;
-            use std::io::Write;
-            writer.write_all(content).map_err(|e| e.to_string())?;
-        }
-
-        writer.finish().map_err(|e| e.to_string())?;
+    let written = write_zip_archive(&mut wrapper, &temp_path).and_then(|()| {
+        // Replace old file with new one
+        std::fs::rename(&temp_path, &path).map_err(|e| (2, e)) // ER_RENAME
+    });
+    if let Err((code, e)) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        wrapper.last_error = code;
+        wrapper.status_sys = e.raw_os_error().unwrap_or(0) as i64;
+        let message = zip_status_string(wrapper.last_error, wrapper.status_sys);
+        update_zip_properties(vm, this_handle, &wrapper)?;
+        drop(wrapper);
+        vm.report_error(
+            ErrorLevel::Warning,
+            &format!("ZipArchive::close(): {}", message),
+        );
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     }
 
-    // Replace old file with new one
-    std::fs::rename(temp_path, path).map_err(|e| e.to_string())?;
-
     wrapper.reader = None;
     wrapper.additions.clear();
     wrapper.deletions.clear();
+    wrapper.compression.clear();
+    wrapper.encryption.clear();
+    wrapper.mtimes.clear();
+    wrapper.comments.clear();
+    wrapper.comment = None;
+    wrapper.truncate = false;
 
     // Update properties
     update_zip_properties(vm, this_handle, &wrapper)?;
@@ -622,18 +1023,236 @@ pub fn php_zip_archive_close(vm: &mut VM, _args: &[Handle]) -> Result<Handle, St
     Ok(vm.arena.alloc(Val::Bool(true)))
 }
 
-pub fn php_zip_archive_add_file(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
-    if args.is_empty() {
-        return Err("ZipArchive::addFile() expects at least 1 parameter".into());
+/// libzip error code for a failure to decrypt an entry: ER_NOPASSWD when no
+/// password was given, ER_WRONGPASSWD when it did not match
+fn password_error_code(error: &zip::result::ZipError) -> Option<i64> {
+    match error {
+        zip::result::ZipError::UnsupportedArchive(zip::result::ZipError::PASSWORD_REQUIRED) => {
+            Some(26)
+        }
+        zip::result::ZipError::InvalidPassword => Some(27),
+        _ => None,
     }
+}
 
-    let filename = match &vm.arena.get(args[0]).value {
-        Val::String(s) => String::from_utf8_lossy(s).to_string(),
-        _ => return Err("ZipArchive::addFile(): Argument #1 (filename) must be string".into()),
+/// Write the archive with its pending changes to `temp_path`, returning the
+/// libzip error code (ER_*) and cause on failure
+fn write_zip_archive(
+    wrapper: &mut ZipArchiveWrapper,
+    temp_path: &str,
+) -> Result<(), (i64, std::io::Error)> {
+    use std::io::Write;
+
+    let zip_err = |code: i64| {
+        move |e: zip::result::ZipError| match e {
+            zip::result::ZipError::Io(e) => (code, e),
+            other => (code, std::io::Error::other(other)),
+        }
     };
 
-    let localname = if args.len() >= 2 {
-        match &vm.arena.get(args[1]).value {
+    let file = File::create(temp_path).map_err(|e| (12, e))?; // ER_TMPOPEN
+    let mut writer = zip::ZipWriter::new(file);
+
+    let deletions = wrapper.deletions.clone();
+    let replaced: HashSet<String> = wrapper.additions.keys().cloned().collect();
+    let compression = wrapper.compression.clone();
+    let password = wrapper.password.clone();
+    let mut encryption = HashMap::new();
+    for (name, (mode, entry_password)) in &wrapper.encryption {
+        let Some(mode) = mode else {
+            continue;
+        };
+        let Some(entry_password) = entry_password.clone().or_else(|| password.clone()) else {
+            return Err((26, std::io::Error::other("No password provided"))); // ER_NOPASSWD
+        };
+        encryption.insert(name.clone(), (*mode, entry_password));
+    }
+    let mtimes = wrapper.mtimes.clone();
+    // Comments of the entries carried over, overridden by setComment*() below
+    let mut comments: HashMap<String, Vec<u8>> = HashMap::new();
+    // setCompression*(), setEncryption*() and setMtime*() requests override
+    // what an entry would otherwise get
+    let entry_options = |name: &str, options: zip::write::SimpleFileOptions| {
+        let options = match mtimes.get(name) {
+            Some(&mtime) => options.last_modified_time(zip_unix_to_dos_time(mtime)),
+            None => options,
+        };
+        let options = match compression.get(name) {
+            Some(&(method, level)) => options.compression_method(method).compression_level(level),
+            None => options,
+        };
+        match encryption.get(name) {
+            Some((mode, password)) => options.with_aes_encryption(*mode, password),
+            None => options,
+        }
+    };
+
+    // Copy old entries (if not deleted)
+    if let Some(reader) = &mut wrapper.reader {
+        for i in 0..reader.len() {
+            let raw = reader.by_index_raw(i).map_err(zip_err(5))?; // ER_READ
+            let name = raw.name().to_string();
+
+            if deletions.contains(&name) || replaced.contains(&name) {
+                continue;
+            }
+            if !raw.comment().is_empty() {
+                comments.insert(name.clone(), raw.comment().as_bytes().to_vec());
+            }
+            // Encrypted entries nobody asked to change are re-encrypted the
+            // same way, which needs the password that decrypts them
+            let inherited = if raw.encrypted() && !wrapper.encryption.contains_key(&name) {
+                match zip_encryption_method(true, raw.extra_data()) {
+                    257 => Some(zip::AesMode::Aes128),
+                    258 => Some(zip::AesMode::Aes192),
+                    259 => Some(zip::AesMode::Aes256),
+                    _ => {
+                        let e = std::io::Error::other("Encryption method not supported");
+                        return Err((24, e)); // ER_ENCRNOTSUPP
+                    }
+                }
+            } else {
+                None
+            };
+            drop(raw);
+
+            let mut entry = match &password {
+                Some(password) => reader.by_index_decrypt(i, password.as_bytes()),
+                None => reader.by_index(i),
+            }
+            .map_err(|e| zip_err(password_error_code(&e).unwrap_or(5))(e))?;
+
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(entry.compression())
+                .last_modified_time(entry.last_modified().unwrap_or_default());
+            let options = entry_options(&name, options);
+            let options = match (inherited, &password) {
+                (Some(mode), Some(password)) => options.with_aes_encryption(mode, password),
+                _ => options,
+            };
+
+            writer.start_file(name, options).map_err(zip_err(6))?; // ER_WRITE
+            std::io::copy(&mut entry, &mut writer).map_err(|e| (6, e))?;
+        }
+    }
+
+    if let Some(comment) = &wrapper.comment {
+        writer.set_raw_comment(comment.clone().into_boxed_slice());
+    } else if let Some(reader) = &wrapper.reader {
+        writer.set_raw_comment(reader.comment().into());
+    }
+
+    // Add new entries, stamped with the time of close() by default
+    let now = zip_unix_to_dos_time(chrono::Local::now().timestamp());
+    for (name, content) in &wrapper.additions {
+        let options = zip::write::SimpleFileOptions::default().last_modified_time(now);
+        let options = entry_options(name, options);
+        writer.start_file(name, options).map_err(zip_err(6))?;
+        writer.write_all(content).map_err(|e| (6, e))?;
+    }
+
+    writer.finish().map_err(zip_err(6))?;
+
+    comments.extend(wrapper.comments.clone());
+    if comments.values().any(|comment| !comment.is_empty()) {
+        write_entry_comments(temp_path, &comments).map_err(|e| (6, e))?;
+    }
+    Ok(())
+}
+
+/// Store entry comments in the archive just written to `path`. ZipWriter has
+/// no per-entry comment option, so the central directory is rebuilt with the
+/// comment field of each named entry replaced.
+fn write_entry_comments(path: &str, comments: &HashMap<String, Vec<u8>>) -> std::io::Result<()> {
+    let data = std::fs::read(path)?;
+    let malformed = || std::io::Error::other("Malformed central directory");
+    let bytes = |pos: usize, len: usize| data.get(pos..pos + len).ok_or_else(malformed);
+    let u16_at = |pos: usize| bytes(pos, 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+    let u32_at = |pos: usize| bytes(pos, 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+    let u64_at = |pos: usize| bytes(pos, 8).map(|b| u64::from_le_bytes(b.try_into().unwrap()));
+
+    // End of central directory record, followed only by the archive comment
+    let eocd = (0..=data.len().saturating_sub(22))
+        .rev()
+        .find(|&pos| {
+            data[pos..].starts_with(b"PK\x05\x06")
+                && u16_at(pos + 20).is_ok_and(|len| pos + 22 + len == data.len())
+        })
+        .ok_or_else(malformed)?;
+    // A ZIP64 end of central directory record sits in front of its locator
+    let zip64 = match eocd.checked_sub(20) {
+        Some(locator) if data[locator..].starts_with(b"PK\x06\x07") => {
+            Some((locator, u64_at(locator + 8)? as usize))
+        }
+        _ => None,
+    };
+    let (cd_start, cd_size) = match zip64 {
+        Some((_, record)) => (u64_at(record + 48)? as usize, u64_at(record + 40)? as usize),
+        None => (u32_at(eocd + 16)? as usize, u32_at(eocd + 12)? as usize),
+    };
+    let cd_end = cd_start + cd_size;
+
+    let mut directory = Vec::with_capacity(cd_size);
+    let mut pos = cd_start;
+    while pos < cd_end {
+        if bytes(pos, 4)? != b"PK\x01\x02" {
+            return Err(malformed());
+        }
+        let name_len = u16_at(pos + 28)?;
+        let extra_len = u16_at(pos + 30)?;
+        let comment_len = u16_at(pos + 32)?;
+        let fixed_len = 46 + name_len + extra_len;
+        let record = bytes(pos, fixed_len + comment_len)?;
+        let name = String::from_utf8_lossy(&record[46..46 + name_len]);
+
+        let start = directory.len();
+        directory.extend_from_slice(&record[..fixed_len]);
+        match comments.get(name.as_ref()) {
+            Some(comment) => {
+                directory[start + 32..start + 34]
+                    .copy_from_slice(&(comment.len() as u16).to_le_bytes());
+                directory.extend_from_slice(comment);
+            }
+            None => directory.extend_from_slice(&record[fixed_len..]),
+        }
+        pos += fixed_len + comment_len;
+    }
+
+    let mut out = Vec::with_capacity(data.len() - cd_size + directory.len());
+    out.extend_from_slice(&data[..cd_start]);
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&data[cd_end..]);
+
+    // The records after the central directory moved with its end
+    let new_cd_end = cd_start + directory.len();
+    let shift = |pos: usize| pos - cd_end + new_cd_end;
+    if let Some((locator, record)) = zip64 {
+        let (locator, record) = (shift(locator), shift(record));
+        out[record + 40..record + 48].copy_from_slice(&(directory.len() as u64).to_le_bytes());
+        out[locator + 8..locator + 16].copy_from_slice(&(record as u64).to_le_bytes());
+    }
+    // A saturated size defers to the ZIP64 record
+    if u32_at(eocd + 12)? != u32::MAX {
+        let size = u32::try_from(directory.len()).map_err(|_| malformed())?;
+        let eocd = shift(eocd);
+        out[eocd + 12..eocd + 16].copy_from_slice(&size.to_le_bytes());
+    }
+
+    std::fs::write(path, out)
+}
+
+pub fn php_zip_archive_add_file(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::addFile() expects at least 1 parameter".into());
+    }
+
+    let filename = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => return Err("ZipArchive::addFile(): Argument #1 (filename) must be string".into()),
+    };
+
+    let localname = if args.len() >= 2 {
+        match &vm.arena.get(args[1]).value {
             Val::String(s) => String::from_utf8_lossy(s).to_string(),
             _ => filename.clone(),
         }
@@ -641,12 +1260,6 @@ pub fn php_zip_archive_add_file(vm: &mut VM, args: &[Handle]) -> Result<Handle,
         filename.clone()
     };
 
-    // Read file content
-    let content = match std::fs::read(&filename) {
-        Ok(c) => c,
-        Err(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
-    };
-
     let this_handle = vm
         .frames
         .last()
@@ -655,6 +1268,21 @@ pub fn php_zip_archive_add_file(vm: &mut VM, args: &[Handle]) -> Result<Handle,
     let wrapper = get_zip_wrapper(vm, this_handle)?;
     let mut wrapper = wrapper.borrow_mut();
 
+    // Read file content
+    let content = match std::fs::read(&filename) {
+        Ok(c) => c,
+        Err(e) => {
+            wrapper.last_error = if e.kind() == std::io::ErrorKind::NotFound {
+                9 // ER_NOENT
+            } else {
+                11 // ER_OPEN
+            };
+            wrapper.status_sys = e.raw_os_error().unwrap_or(0) as i64;
+            update_zip_properties(vm, this_handle, &wrapper)?;
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    };
+
     wrapper.additions.insert(localname, content);
 
     // Update properties
@@ -728,6 +1356,243 @@ pub fn php_zip_archive_add_from_string(vm: &mut VM, args: &[Handle]) -> Result<H
     Ok(vm.arena.alloc(Val::Bool(true)))
 }
 
+/// Path rewriting options shared by addGlob() and addPattern()
+#[derive(Default)]
+struct ZipAddPatternOptions {
+    add_path: Option<String>,
+    remove_path: Option<String>,
+    remove_all_path: bool,
+}
+
+fn zip_add_pattern_options(vm: &VM, handle: Option<&Handle>) -> ZipAddPatternOptions {
+    let mut options = ZipAddPatternOptions::default();
+    let Some(Val::Array(arr)) = handle.map(|h| &vm.arena.get(*h).value) else {
+        return options;
+    };
+    let string_option = |key: &[u8]| {
+        arr.map
+            .get(&ArrayKey::Str(Rc::new(key.to_vec())))
+            .and_then(|h| match &vm.arena.get(*h).value {
+                Val::String(s) if !s.is_empty() => Some(String::from_utf8_lossy(s).to_string()),
+                _ => None,
+            })
+    };
+    options.add_path = string_option(b"add_path");
+    // A trailing slash on remove_path is ignored, as in php_zip_parse_options
+    options.remove_path = string_option(b"remove_path").map(|path| {
+        if path.len() > 1 && path.ends_with('/') {
+            path[..path.len() - 1].to_string()
+        } else {
+            path
+        }
+    });
+    options.remove_all_path = arr
+        .map
+        .get(&ArrayKey::Str(Rc::new(b"remove_all_path".to_vec())))
+        .is_some_and(|h| vm.arena.get(*h).value.to_bool());
+    options
+}
+
+/// Archive entry name for a file found by addGlob()/addPattern()
+/// Reference: $PHP_SRC_PATH/ext/zip/php_zip.c - php_zip_add_from_pattern
+fn zip_pattern_entry_name(file: &str, options: &ZipAddPatternOptions) -> String {
+    let stripped = if options.remove_all_path {
+        file.rsplit('/').next().unwrap_or(file)
+    } else if let Some(rest) = options
+        .remove_path
+        .as_deref()
+        .and_then(|prefix| file.strip_prefix(prefix))
+    {
+        rest.strip_prefix('/').unwrap_or(rest)
+    } else {
+        file
+    };
+    match &options.add_path {
+        Some(add_path) => format!("{}{}", add_path, stripped),
+        None => stripped.to_string(),
+    }
+}
+
+/// Add regular files from `files` using the addGlob()/addPattern() naming
+/// rules; returns the list of added paths, or false if a file can't be read
+fn zip_add_matched_files(
+    vm: &mut VM,
+    method: &str,
+    files: Vec<String>,
+    options: &ZipAddPatternOptions,
+) -> Result<Handle, String> {
+    let mut added = Vec::new();
+    for file in files {
+        if !Path::new(&file).is_file() {
+            continue;
+        }
+        let Ok(content) = std::fs::read(&file) else {
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        };
+        added.push((zip_pattern_entry_name(&file, options), file, content));
+    }
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or_else(|| format!("No 'this' in ZipArchive::{}", method))?;
+    let wrapper = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper.borrow_mut();
+
+    let mut result = ArrayData::new();
+    for (entry_name, file, content) in added {
+        wrapper.additions.insert(entry_name, content);
+        result.push(vm.arena.alloc(Val::String(Rc::new(file.into_bytes()))));
+    }
+
+    // Update properties
+    update_zip_properties(vm, this_handle, &wrapper)?;
+
+    Ok(vm.arena.alloc(Val::Array(Rc::new(result))))
+}
+
+/// ZipArchive::addGlob($pattern, $flags = 0, $options = [])
+pub fn php_zip_archive_add_glob(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::addGlob() expects at least 1 parameter".into());
+    }
+    if !matches!(vm.arena.get(args[0]).value, Val::String(_)) {
+        return Err("ZipArchive::addGlob(): Argument #1 ($pattern) must be of type string".into());
+    }
+
+    let options = zip_add_pattern_options(vm, args.get(2));
+
+    // Reuse glob() so matching and GLOB_* flags behave identically
+    let glob_args: Vec<Handle> = args.iter().take(2).copied().collect();
+    let matches = crate::builtins::filesystem::php_glob(vm, &glob_args)?;
+    let files: Vec<String> = match &vm.arena.get(matches).value {
+        Val::Array(arr) => arr
+            .map
+            .values()
+            .filter_map(|h| match &vm.arena.get(*h).value {
+                Val::String(s) => Some(String::from_utf8_lossy(s).to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => return Ok(vm.arena.alloc(Val::Bool(false))),
+    };
+
+    zip_add_matched_files(vm, "addGlob", files, &options)
+}
+
+/// ZipArchive::addPattern($pattern, $path = ".", $options = [])
+///
+/// Adds the files directly inside `$path` whose names match the regex.
+pub fn php_zip_archive_add_pattern(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::addPattern() expects at least 1 parameter".into());
+    }
+
+    let pattern = match &vm.arena.get(args[0]).value {
+        Val::String(s) => s.clone(),
+        _ => {
+            return Err(
+                "ZipArchive::addPattern(): Argument #1 ($pattern) must be of type string".into(),
+            );
+        }
+    };
+    let dir = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::String(s)) if !s.is_empty() => String::from_utf8_lossy(s).to_string(),
+        _ => ".".to_string(),
+    };
+    let options = zip_add_pattern_options(vm, args.get(2));
+
+    let (regex_bytes, _flags) = crate::builtins::pcre::parse_php_pattern(&pattern)?;
+    let regex = pcre2::bytes::Regex::new(&String::from_utf8_lossy(&regex_bytes))
+        .map_err(|e| format!("Invalid regex: {}", e))?;
+
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+
+    let dir = dir.trim_end_matches('/');
+    let mut files = Vec::new();
+    for name in names {
+        if regex.is_match(name.as_bytes()).unwrap_or(false) {
+            files.push(format!("{}/{}", dir, name));
+        }
+    }
+
+    zip_add_matched_files(vm, "addPattern", files, &options)
+}
+
+/// ZipArchive::replaceFile($filepath, $index, $start = 0, $length = ZipArchive::LENGTH_TO_END)
+pub fn php_zip_archive_replace_file(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("ZipArchive::replaceFile() expects at least 2 parameters".into());
+    }
+
+    let filepath = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => {
+            return Err(
+                "ZipArchive::replaceFile(): Argument #1 ($filepath) must be of type string".into(),
+            );
+        }
+    };
+    let index = match &vm.arena.get(args[1]).value {
+        Val::Int(i) => *i,
+        _ => {
+            return Err(
+                "ZipArchive::replaceFile(): Argument #2 ($index) must be of type int".into(),
+            );
+        }
+    };
+    let start = args
+        .get(2)
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0);
+    let length = args
+        .get(3)
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0);
+    if index < 0 || start < 0 || length < 0 {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    let Ok(content) = std::fs::read(&filepath) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+    let start = (start as usize).min(content.len());
+    let end = match length {
+        0 => content.len(),
+        length => start.saturating_add(length as usize).min(content.len()),
+    };
+    let content = content[start..end].to_vec();
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in ZipArchive::replaceFile")?;
+    let wrapper = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper.borrow_mut();
+
+    let name = wrapper.entry_name(index as usize);
+    let Some(name) = name else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    // A pending addition with an existing entry's name replaces it on close()
+    wrapper.additions.insert(name, content);
+
+    // Update properties
+    update_zip_properties(vm, this_handle, &wrapper)?;
+
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
 pub fn php_zip_archive_count(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
     let this_handle = vm
         .frames
@@ -737,13 +1602,7 @@ pub fn php_zip_archive_count(vm: &mut VM, _args: &[Handle]) -> Result<Handle, St
     let wrapper = get_zip_wrapper(vm, this_handle)?;
     let wrapper = wrapper.borrow();
 
-    let count = if let Some(reader) = &wrapper.reader {
-        reader.len() as i64
-    } else {
-        0
-    };
-
-    Ok(vm.arena.alloc(Val::Int(count)))
+    Ok(vm.arena.alloc(Val::Int(wrapper.num_entries() as i64)))
 }
 
 pub fn php_zip_archive_delete_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
@@ -782,13 +1641,9 @@ pub fn php_zip_archive_delete_index(vm: &mut VM, args: &[Handle]) -> Result<Hand
         }
     }
 
-    if let Some(name) = name_to_delete {
-        if wrapper.additions.contains_key(&name) {
-            wrapper.additions.shift_remove(&name);
-        } else {
-            wrapper.deletions.insert(name);
-        }
-
+    if let Some(name) = name_to_delete
+        && wrapper.delete_entry(&name)
+    {
         // Update properties
         update_zip_properties(vm, this_handle, &wrapper)?;
 
@@ -816,7 +1671,9 @@ pub fn php_zip_archive_delete_name(vm: &mut VM, args: &[Handle]) -> Result<Handl
     let wrapper = get_zip_wrapper(vm, this_handle)?;
     let mut wrapper = wrapper.borrow_mut();
 
-    wrapper.deletions.insert(name);
+    if !wrapper.delete_entry(&name) {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
 
     // Update properties
     update_zip_properties(vm, this_handle, &wrapper)?;
@@ -824,6 +1681,32 @@ pub fn php_zip_archive_delete_name(vm: &mut VM, args: &[Handle]) -> Result<Handl
     Ok(vm.arena.alloc(Val::Bool(true)))
 }
 
+/// Resolve an entry name below `destination`, refusing absolute names and
+/// names that climb out of the destination through `..`
+fn enclosed_entry_path(destination: &Path, name: &str) -> Option<PathBuf> {
+    let mut outpath = destination.to_path_buf();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => outpath.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    if outpath.as_path() == destination {
+        return None;
+    }
+    Some(outpath)
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent()
+        && !parent.exists()
+    {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 pub fn php_zip_archive_extract_to(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
         return Err("ZipArchive::extractTo() expects at least 1 parameter".into());
@@ -836,6 +1719,33 @@ pub fn php_zip_archive_extract_to(vm: &mut VM, args: &[Handle]) -> Result<Handle
         }
     };
 
+    let files: Option<Vec<String>> = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        None | Some(Val::Null) => None,
+        Some(Val::String(s)) => Some(vec![String::from_utf8_lossy(s).to_string()]),
+        Some(Val::Array(arr)) => {
+            let mut names = Vec::with_capacity(arr.map.len());
+            for handle in arr.map.values() {
+                match &vm.arena.get(*handle).value {
+                    Val::String(s) => names.push(String::from_utf8_lossy(s).to_string()),
+                    Val::Int(i) => names.push(i.to_string()),
+                    _ => {
+                        return Err(
+                            "ZipArchive::extractTo(): Argument #2 (files) must contain only strings"
+                                .into(),
+                        );
+                    }
+                }
+            }
+            Some(names)
+        }
+        Some(_) => {
+            return Err(
+                "ZipArchive::extractTo(): Argument #2 (files) must be of type array|string|null"
+                    .into(),
+            );
+        }
+    };
+
     let this_handle = vm
         .frames
         .last()
@@ -844,30 +1754,83 @@ pub fn php_zip_archive_extract_to(vm: &mut VM, args: &[Handle]) -> Result<Handle
     let wrapper_rc = get_zip_wrapper(vm, this_handle)?;
     let mut wrapper = wrapper_rc.borrow_mut();
 
-    if let Some(reader) = &mut wrapper.reader {
+    if wrapper.reader.is_none() && wrapper.additions.is_empty() {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    // Entries as they would appear after close(): stored entries (by index)
+    // that were neither deleted nor replaced, followed by pending additions
+    let mut entries: Vec<(String, Option<usize>)> = Vec::new();
+    if let Some(reader) = &wrapper.reader {
         for i in 0..reader.len() {
-            let mut file = reader.by_index(i).map_err(|e| e.to_string())?;
-            let outpath = match file.enclosed_name() {
-                Some(path) => Path::new(&destination).join(path),
-                None => continue,
+            let Some(name) = reader.name_for_index(i) else {
+                continue;
             };
+            if wrapper.deletions.contains(name) || wrapper.additions.contains_key(name) {
+                continue;
+            }
+            entries.push((name.to_string(), Some(i)));
+        }
+    }
+    for name in wrapper.additions.keys() {
+        if !wrapper.deletions.contains(name) {
+            entries.push((name.clone(), None));
+        }
+    }
 
-            if file.is_dir() {
-                std::fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
-            } else {
-                if let Some(p) = outpath.parent() {
-                    if !p.exists() {
-                        std::fs::create_dir_all(&p).map_err(|e| e.to_string())?;
-                    }
+    if let Some(files) = &files {
+        let mut selected = Vec::with_capacity(files.len());
+        for file in files {
+            match entries.iter().find(|(name, _)| name == file) {
+                Some(entry) => selected.push(entry.clone()),
+                None => return Ok(vm.arena.alloc(Val::Bool(false))),
+            }
+        }
+        entries = selected;
+    }
+
+    // Refuse the whole extraction before writing anything if an entry would escape
+    let destination = Path::new(&destination);
+    let mut targets = Vec::with_capacity(entries.len());
+    for (name, index) in entries {
+        match enclosed_entry_path(destination, &name) {
+            Some(outpath) => targets.push((name, index, outpath)),
+            None => return Ok(vm.arena.alloc(Val::Bool(false))),
+        }
+    }
+
+    for (name, index, outpath) in targets {
+        match index {
+            Some(i) => {
+                let reader = wrapper.reader.as_mut().ok_or("ZipArchive reader missing")?;
+                let mut file = reader.by_index(i).map_err(|e| e.to_string())?;
+                if file.is_dir() {
+                    std::fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
+                    continue;
                 }
+                create_parent_dir(&outpath)?;
                 let mut outfile = std::fs::File::create(&outpath).map_err(|e| e.to_string())?;
                 std::io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
+                #[cfg(unix)]
+                if let Some(mode) = file.unix_mode() {
+                    use std::os::unix::fs::PermissionsExt;
+                    let permissions = std::fs::Permissions::from_mode(mode & 0o7777);
+                    std::fs::set_permissions(&outpath, permissions).map_err(|e| e.to_string())?;
+                }
+            }
+            None => {
+                if name.ends_with('/') {
+                    std::fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
+                } else {
+                    create_parent_dir(&outpath)?;
+                    std::fs::write(&outpath, &wrapper.additions[&name])
+                        .map_err(|e| e.to_string())?;
+                }
             }
         }
-        return Ok(vm.arena.alloc(Val::Bool(true)));
     }
 
-    Ok(vm.arena.alloc(Val::Bool(false)))
+    Ok(vm.arena.alloc(Val::Bool(true)))
 }
 
 pub fn php_zip_archive_rename_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
@@ -994,8 +1957,14 @@ pub fn php_zip_archive_get_from_name(vm: &mut VM, args: &[Handle]) -> Result<Han
     let wrapper = get_zip_wrapper(vm, this_handle)?;
     let mut wrapper = wrapper.borrow_mut();
 
+    let password = wrapper.password.clone();
+    let mut password_error = None;
     if let Some(reader) = &mut wrapper.reader {
-        match reader.by_name(&name) {
+        let file = match &password {
+            Some(password) => reader.by_name_decrypt(&name, password.as_bytes()),
+            None => reader.by_name(&name),
+        };
+        match file {
             Ok(mut file) => {
                 let mut content = Vec::new();
                 use std::io::Read;
@@ -1003,35 +1972,172 @@ pub fn php_zip_archive_get_from_name(vm: &mut VM, args: &[Handle]) -> Result<Han
                     return Ok(vm.arena.alloc(Val::String(Rc::new(content))));
                 }
             }
-            Err(_) => {}
+            Err(e) => password_error = password_error_code(&e),
         }
     }
+    if let Some(code) = password_error {
+        wrapper.last_error = code;
+    }
 
     Ok(vm.arena.alloc(Val::Bool(false)))
 }
 
-pub fn php_zip_archive_get_name_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+/// Open a reader over entry `index`, where indices past the stored entries
+/// address pending additions (as in getNameIndex)
+fn open_entry_reader(
+    wrapper: &mut ZipArchiveWrapper,
+    index: usize,
+) -> Result<Option<Box<dyn std::io::Read>>, String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let reader_len = wrapper.reader.as_ref().map(|r| r.len()).unwrap_or(0);
+    if index >= reader_len {
+        return Ok(wrapper
+            .additions
+            .get_index(index - reader_len)
+            .filter(|(name, _)| !wrapper.deletions.contains(*name))
+            .map(|(_, content)| {
+                Box::new(std::io::Cursor::new(content.clone())) as Box<dyn std::io::Read>
+            }));
+    }
+
+    let path = wrapper.path.clone();
+    let Some(reader) = wrapper.reader.as_mut() else {
+        return Ok(None);
+    };
+    let (name, compression, encrypted, data_start, compressed_size) = {
+        let raw = reader.by_index_raw(index).map_err(|e| e.to_string())?;
+        (
+            raw.name().to_string(),
+            raw.compression(),
+            raw.encrypted(),
+            raw.data_start(),
+            raw.compressed_size(),
+        )
+    };
+    if wrapper.deletions.contains(&name) {
+        return Ok(None);
+    }
+
+    if !encrypted
+        && matches!(
+            compression,
+            zip::CompressionMethod::Stored | zip::CompressionMethod::Deflated
+        )
+    {
+        let mut file = File::open(&path).map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(data_start))
+            .map_err(|e| e.to_string())?;
+        let raw = file.take(compressed_size);
+        return Ok(Some(if compression == zip::CompressionMethod::Stored {
+            Box::new(raw)
+        } else {
+            Box::new(flate2::read::DeflateDecoder::new(raw))
+        }));
+    }
+
+    // Other methods and encrypted entries are decompressed up front
+    let mut entry = match &wrapper.password {
+        Some(password) => reader.by_index_decrypt(index, password.as_bytes()),
+        None => reader.by_index(index),
+    }
+    .map_err(|e| e.to_string())?;
+    let mut content = Vec::new();
+    entry.read_to_end(&mut content).map_err(|e| e.to_string())?;
+    Ok(Some(Box::new(std::io::Cursor::new(content))))
+}
+
+pub fn php_zip_archive_get_stream(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
-        return Err("ZipArchive::getNameIndex() expects 1 parameter".into());
+        return Err("ZipArchive::getStream() expects 1 parameter".into());
     }
 
-    let index = match &vm.arena.get(args[0]).value {
-        Val::Int(i) => *i as usize,
-        _ => return Err("ZipArchive::getNameIndex(): Argument #1 (index) must be integer".into()),
+    let name = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => return Err("ZipArchive::getStream(): Argument #1 (name) must be string".into()),
     };
 
     let this_handle = vm
         .frames
         .last()
         .and_then(|f| f.this)
-        .ok_or("No 'this' in ZipArchive::getNameIndex")?;
+        .ok_or("No 'this' in ZipArchive::getStream")?;
     let wrapper_rc = get_zip_wrapper(vm, this_handle)?;
     let mut wrapper = wrapper_rc.borrow_mut();
 
+    // A pending addition shadows a stored entry of the same name
     let reader_len = wrapper.reader.as_ref().map(|r| r.len()).unwrap_or(0);
-    if index < reader_len {
-        if let Some(reader) = &mut wrapper.reader {
-            if let Ok(file) = reader.by_index(index) {
+    let index = match wrapper.additions.get_index_of(&name) {
+        Some(i) => Some(reader_len + i),
+        None => wrapper
+            .reader
+            .as_ref()
+            .and_then(|r| r.index_for_name(&name)),
+    };
+
+    let stream = match index {
+        Some(i) => open_entry_reader(&mut wrapper, i)?,
+        None => None,
+    };
+    match stream {
+        Some(reader) => Ok(vm
+            .arena
+            .alloc(Val::Resource(Rc::new(ZipEntryStream::new(reader))))),
+        None => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
+}
+
+pub fn php_zip_archive_get_stream_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::getStreamIndex() expects at least 1 parameter".into());
+    }
+
+    let index = match &vm.arena.get(args[0]).value {
+        Val::Int(i) if *i >= 0 => *i as usize,
+        Val::Int(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
+        _ => {
+            return Err("ZipArchive::getStreamIndex(): Argument #1 (index) must be integer".into());
+        }
+    };
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in ZipArchive::getStreamIndex")?;
+    let wrapper_rc = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper_rc.borrow_mut();
+
+    match open_entry_reader(&mut wrapper, index)? {
+        Some(reader) => Ok(vm
+            .arena
+            .alloc(Val::Resource(Rc::new(ZipEntryStream::new(reader))))),
+        None => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
+}
+
+pub fn php_zip_archive_get_name_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::getNameIndex() expects 1 parameter".into());
+    }
+
+    let index = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i as usize,
+        _ => return Err("ZipArchive::getNameIndex(): Argument #1 (index) must be integer".into()),
+    };
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in ZipArchive::getNameIndex")?;
+    let wrapper_rc = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper_rc.borrow_mut();
+
+    let reader_len = wrapper.reader.as_ref().map(|r| r.len()).unwrap_or(0);
+    if index < reader_len {
+        if let Some(reader) = &mut wrapper.reader {
+            if let Ok(file) = reader.by_index(index) {
                 return Ok(vm
                     .arena
                     .alloc(Val::String(Rc::new(file.name().as_bytes().to_vec()))));
@@ -1050,112 +2156,801 @@ pub fn php_zip_archive_get_name_index(vm: &mut VM, args: &[Handle]) -> Result<Ha
 }
 
 pub fn php_zip_archive_get_status_string(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
-    Ok(vm.arena.alloc(Val::String(b"No error".to_vec().into())))
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in ZipArchive::getStatusString")?;
+
+    let message = match get_zip_wrapper(vm, this_handle) {
+        Ok(wrapper) => {
+            let wrapper = wrapper.borrow();
+            zip_status_string(wrapper.last_error, wrapper.status_sys)
+        }
+        Err(_) => zip_error_string(0).to_string(),
+    };
+
+    Ok(vm.arena.alloc(Val::String(message.into_bytes().into())))
 }
 
-pub fn php_zip_archive_locate_name(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
-    if args.is_empty() {
-        return Err("ZipArchive::locateName() expects at least 1 parameter".into());
+/// Current comment of the entry `name`, or the one on disk when `unchanged`
+/// is set; pending additions have none until setComment*() gives them one
+fn entry_comment(wrapper: &mut ZipArchiveWrapper, name: &str, unchanged: bool) -> Vec<u8> {
+    if let Some(comment) = wrapper.comments.get(name).filter(|_| !unchanged) {
+        return comment.clone();
+    }
+    if wrapper.additions.contains_key(name) && !unchanged {
+        return Vec::new();
+    }
+    wrapper
+        .reader
+        .as_mut()
+        .and_then(|reader| {
+            let index = reader.index_for_name(name)?;
+            let entry = reader.by_index_raw(index).ok()?;
+            Some(entry.comment().as_bytes().to_vec())
+        })
+        .unwrap_or_default()
+}
+
+/// Current archive comment, or the one on disk when `unchanged` is set
+fn archive_comment(wrapper: &ZipArchiveWrapper, unchanged: bool) -> Vec<u8> {
+    match &wrapper.comment {
+        Some(comment) if !unchanged => comment.clone(),
+        _ => wrapper
+            .reader
+            .as_ref()
+            .map(|r| r.comment().to_vec())
+            .unwrap_or_default(),
+    }
+}
+
+pub fn php_zip_archive_set_compression_name(
+    vm: &mut VM,
+    args: &[Handle],
+) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("ZipArchive::setCompressionName() expects at least 2 parameters".into());
     }
 
     let name = match &vm.arena.get(args[0]).value {
         Val::String(s) => String::from_utf8_lossy(s).to_string(),
-        _ => return Err("ZipArchive::locateName(): Argument #1 (name) must be string".into()),
+        _ => {
+            return Err(
+                "ZipArchive::setCompressionName(): Argument #1 ($name) must be of type string"
+                    .into(),
+            );
+        }
+    };
+
+    set_entry_compression(vm, "setCompressionName", &args[1..], |_| Some(name))
+}
+
+pub fn php_zip_archive_set_compression_index(
+    vm: &mut VM,
+    args: &[Handle],
+) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("ZipArchive::setCompressionIndex() expects at least 2 parameters".into());
+    }
+
+    let index = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i,
+        _ => {
+            return Err(
+                "ZipArchive::setCompressionIndex(): Argument #1 ($index) must be of type int"
+                    .into(),
+            );
+        }
+    };
+
+    set_entry_compression(vm, "setCompressionIndex", &args[1..], |wrapper| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| wrapper.entry_name(index))
+    })
+}
+
+/// Record the compression method and level (`$method`, `$compflags`) that
+/// close() should use for the entry `resolve` names
+/// Reference: $PHP_SRC_PATH/ext/zip/php_zip.c - ZipArchive::setCompressionName
+fn set_entry_compression(
+    vm: &mut VM,
+    method_name: &str,
+    args: &[Handle],
+    resolve: impl FnOnce(&ZipArchiveWrapper) -> Option<String>,
+) -> Result<Handle, String> {
+    let method = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i,
+        _ => {
+            return Err(format!(
+                "ZipArchive::{}(): Argument #2 ($method) must be of type int",
+                method_name
+            ));
+        }
     };
+    let level = args
+        .get(1)
+        .map(|h| vm.arena.get(*h).value.to_int())
+        .unwrap_or(0);
 
     let this_handle = vm
         .frames
         .last()
         .and_then(|f| f.this)
-        .ok_or("No 'this' in ZipArchive::locateName")?;
-    let wrapper_rc = get_zip_wrapper(vm, this_handle)?;
-    let wrapper = wrapper_rc.borrow();
+        .ok_or_else(|| format!("No 'this' in ZipArchive::{}", method_name))?;
+    let wrapper = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper.borrow_mut();
 
-    if let Some(reader) = &wrapper.reader {
-        if let Some(index) = reader.index_for_name(&name) {
-            return Ok(vm.arena.alloc(Val::Int(index as i64)));
+    let Some(name) = resolve(&wrapper).filter(|name| wrapper.has_entry(name)) else {
+        wrapper.last_error = 9; // ER_NOENT
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+    let method = match method {
+        -1 | 8 => zip::CompressionMethod::Deflated, // CM_DEFAULT, CM_DEFLATE
+        0 => zip::CompressionMethod::Stored,
+        12 => zip::CompressionMethod::Bzip2,
+        _ => {
+            wrapper.last_error = 16; // ER_COMPNOTSUPP
+            return Ok(vm.arena.alloc(Val::Bool(false)));
         }
-    }
+    };
+    // $compflags is the level, 0 meaning the method's default
+    let level = match level {
+        0 => None,
+        1..=9 if method != zip::CompressionMethod::Stored => Some(level),
+        1..=9 => None,
+        _ => {
+            wrapper.last_error = 18; // ER_INVAL
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    };
 
-    let reader_len = wrapper.reader.as_ref().map(|r| r.len()).unwrap_or(0);
-    if let Some(index) = wrapper.additions.get_index_of(&name) {
-        return Ok(vm.arena.alloc(Val::Int((reader_len + index) as i64)));
+    wrapper.compression.insert(name, (method, level));
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+pub fn php_zip_archive_set_encryption_name(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("ZipArchive::setEncryptionName() expects at least 2 parameters".into());
     }
 
-    Ok(vm.arena.alloc(Val::Bool(false)))
+    let name = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => {
+            return Err(
+                "ZipArchive::setEncryptionName(): Argument #1 ($name) must be of type string"
+                    .into(),
+            );
+        }
+    };
+
+    set_entry_encryption(vm, "setEncryptionName", &args[1..], |_| Some(name))
 }
 
-pub fn php_zip_archive_stat_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
-    if args.is_empty() {
-        return Err("ZipArchive::statIndex() expects 1 parameter".into());
+pub fn php_zip_archive_set_encryption_index(
+    vm: &mut VM,
+    args: &[Handle],
+) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("ZipArchive::setEncryptionIndex() expects at least 2 parameters".into());
     }
 
     let index = match &vm.arena.get(args[0]).value {
-        Val::Int(i) => *i as usize,
-        _ => return Err("ZipArchive::statIndex(): Argument #1 (index) must be integer".into()),
+        Val::Int(i) => *i,
+        _ => {
+            return Err(
+                "ZipArchive::setEncryptionIndex(): Argument #1 ($index) must be of type int".into(),
+            );
+        }
+    };
+
+    set_entry_encryption(vm, "setEncryptionIndex", &args[1..], |wrapper| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| wrapper.entry_name(index))
+    })
+}
+
+/// Record the encryption method and password (`$method`, `$password`) that
+/// close() should use for the entry `resolve` names
+/// Reference: $PHP_SRC_PATH/ext/zip/php_zip.c - ZipArchive::setEncryptionName
+fn set_entry_encryption(
+    vm: &mut VM,
+    method_name: &str,
+    args: &[Handle],
+    resolve: impl FnOnce(&ZipArchiveWrapper) -> Option<String>,
+) -> Result<Handle, String> {
+    let method = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i,
+        _ => {
+            return Err(format!(
+                "ZipArchive::{}(): Argument #2 ($method) must be of type int",
+                method_name
+            ));
+        }
+    };
+    let password = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        None | Some(Val::Null) => None,
+        Some(Val::String(s)) => Some(String::from_utf8_lossy(s).to_string()),
+        Some(_) => {
+            return Err(format!(
+                "ZipArchive::{}(): Argument #3 ($password) must be of type ?string",
+                method_name
+            ));
+        }
     };
 
     let this_handle = vm
         .frames
         .last()
         .and_then(|f| f.this)
-        .ok_or("No 'this' in ZipArchive::statIndex")?;
+        .ok_or_else(|| format!("No 'this' in ZipArchive::{}", method_name))?;
     let wrapper = get_zip_wrapper(vm, this_handle)?;
     let mut wrapper = wrapper.borrow_mut();
 
-    if let Some(reader) = &mut wrapper.reader {
-        if let Ok(file) = reader.by_index(index) {
-            let mut map = IndexMap::new();
-            map.insert(
-                ArrayKey::Str(Rc::new(b"name".to_vec())),
-                vm.arena
-                    .alloc(Val::String(Rc::new(file.name().as_bytes().to_vec()))),
+    let Some(name) = resolve(&wrapper).filter(|name| wrapper.has_entry(name)) else {
+        wrapper.last_error = 9; // ER_NOENT
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+    let mode = match method {
+        0 => None, // EM_NONE
+        257 => Some(zip::AesMode::Aes128),
+        258 => Some(zip::AesMode::Aes192),
+        259 => Some(zip::AesMode::Aes256),
+        _ => {
+            wrapper.last_error = 24; // ER_ENCRNOTSUPP
+            return Ok(vm.arena.alloc(Val::Bool(false)));
+        }
+    };
+
+    wrapper.encryption.insert(name, (mode, password));
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+pub fn php_zip_archive_set_mtime_name(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("ZipArchive::setMtimeName() expects at least 2 parameters".into());
+    }
+
+    let name = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => {
+            return Err(
+                "ZipArchive::setMtimeName(): Argument #1 ($name) must be of type string".into(),
             );
-            map.insert(
-                ArrayKey::Str(Rc::new(b"index".to_vec())),
-                vm.arena.alloc(Val::Int(index as i64)),
+        }
+    };
+
+    set_entry_mtime(vm, "setMtimeName", &args[1..], |_| Some(name))
+}
+
+pub fn php_zip_archive_set_mtime_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("ZipArchive::setMtimeIndex() expects at least 2 parameters".into());
+    }
+
+    let index = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i,
+        _ => {
+            return Err(
+                "ZipArchive::setMtimeIndex(): Argument #1 ($index) must be of type int".into(),
             );
-            map.insert(
-                ArrayKey::Str(Rc::new(b"crc".to_vec())),
-                vm.arena.alloc(Val::Int(file.crc32() as i64)),
+        }
+    };
+
+    set_entry_mtime(vm, "setMtimeIndex", &args[1..], |wrapper| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| wrapper.entry_name(index))
+    })
+}
+
+/// Record the modification time (`$timestamp`) that close() should write for
+/// the entry `resolve` names. `$flags` is accepted for compatibility; no
+/// ZipArchive::FL_* flag changes how the time is stored.
+/// Reference: $PHP_SRC_PATH/ext/zip/php_zip.c - ZipArchive::setMtimeName
+fn set_entry_mtime(
+    vm: &mut VM,
+    method_name: &str,
+    args: &[Handle],
+    resolve: impl FnOnce(&ZipArchiveWrapper) -> Option<String>,
+) -> Result<Handle, String> {
+    let timestamp = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i,
+        _ => {
+            return Err(format!(
+                "ZipArchive::{}(): Argument #2 ($timestamp) must be of type int",
+                method_name
+            ));
+        }
+    };
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or_else(|| format!("No 'this' in ZipArchive::{}", method_name))?;
+    let wrapper = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper.borrow_mut();
+
+    let Some(name) = resolve(&wrapper).filter(|name| wrapper.has_entry(name)) else {
+        wrapper.last_error = 9; // ER_NOENT
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    wrapper.mtimes.insert(name, timestamp);
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+pub fn php_zip_archive_set_archive_comment(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::setArchiveComment() expects 1 parameter".into());
+    }
+
+    let comment = match &vm.arena.get(args[0]).value {
+        Val::String(s) => s.to_vec(),
+        _ => {
+            return Err(
+                "ZipArchive::setArchiveComment(): Argument #1 (comment) must be string".into(),
             );
-            map.insert(
-                ArrayKey::Str(Rc::new(b"size".to_vec())),
-                vm.arena.alloc(Val::Int(file.size() as i64)),
+        }
+    };
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in ZipArchive::setArchiveComment")?;
+    let wrapper = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper.borrow_mut();
+
+    // The end of central directory record stores the comment length in 16 bits
+    if comment.len() > u16::MAX as usize {
+        wrapper.last_error = 18; // ER_INVAL
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    wrapper.comment = Some(comment);
+
+    // Update properties
+    update_zip_properties(vm, this_handle, &wrapper)?;
+
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+pub fn php_zip_archive_get_archive_comment(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let flags = match args.first().map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => *i,
+        _ => 0,
+    };
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in ZipArchive::getArchiveComment")?;
+    let wrapper = get_zip_wrapper(vm, this_handle)?;
+    let wrapper = wrapper.borrow();
+
+    // ZipArchive::FL_UNCHANGED
+    let comment = archive_comment(&wrapper, flags & 8 != 0);
+
+    Ok(vm.arena.alloc(Val::String(Rc::new(comment))))
+}
+
+pub fn php_zip_archive_set_comment_name(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("ZipArchive::setCommentName() expects 2 parameters".into());
+    }
+
+    let name = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => {
+            return Err(
+                "ZipArchive::setCommentName(): Argument #1 ($name) must be of type string".into(),
             );
-            map.insert(
-                ArrayKey::Str(Rc::new(b"comp_size".to_vec())),
-                vm.arena.alloc(Val::Int(file.compressed_size() as i64)),
+        }
+    };
+
+    set_entry_comment(vm, "setCommentName", args[1], |_| Some(name))
+}
+
+pub fn php_zip_archive_set_comment_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("ZipArchive::setCommentIndex() expects 2 parameters".into());
+    }
+
+    let index = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i,
+        _ => {
+            return Err(
+                "ZipArchive::setCommentIndex(): Argument #1 ($index) must be of type int".into(),
             );
-            map.insert(
-                ArrayKey::Str(Rc::new(b"mtime".to_vec())),
-                vm.arena.alloc(Val::Int(0)),
+        }
+    };
+
+    set_entry_comment(vm, "setCommentIndex", args[1], |wrapper| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| wrapper.entry_name(index))
+    })
+}
+
+/// Record the comment close() should write for the entry `resolve` names
+fn set_entry_comment(
+    vm: &mut VM,
+    method_name: &str,
+    comment: Handle,
+    resolve: impl FnOnce(&ZipArchiveWrapper) -> Option<String>,
+) -> Result<Handle, String> {
+    let comment = match &vm.arena.get(comment).value {
+        Val::String(s) => s.to_vec(),
+        _ => {
+            return Err(format!(
+                "ZipArchive::{}(): Argument #2 ($comment) must be of type string",
+                method_name
+            ));
+        }
+    };
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or_else(|| format!("No 'this' in ZipArchive::{}", method_name))?;
+    let wrapper = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper.borrow_mut();
+
+    let Some(name) = resolve(&wrapper).filter(|name| wrapper.has_entry(name)) else {
+        wrapper.last_error = 9; // ER_NOENT
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+    // Central directory records store the comment length in 16 bits
+    if comment.len() > u16::MAX as usize {
+        wrapper.last_error = 18; // ER_INVAL
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    wrapper.comments.insert(name, comment);
+    Ok(vm.arena.alloc(Val::Bool(true)))
+}
+
+pub fn php_zip_archive_get_comment_name(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::getCommentName() expects at least 1 parameter".into());
+    }
+
+    let name = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => {
+            return Err(
+                "ZipArchive::getCommentName(): Argument #1 ($name) must be of type string".into(),
             );
-            map.insert(
-                ArrayKey::Str(Rc::new(b"comp_method".to_vec())),
-                vm.arena.alloc(Val::Int(0)),
+        }
+    };
+    let flags = zip_flags_arg(vm, args, 1);
+
+    get_entry_comment(vm, "getCommentName", flags, |_| Some(name))
+}
+
+pub fn php_zip_archive_get_comment_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::getCommentIndex() expects at least 1 parameter".into());
+    }
+
+    let index = match &vm.arena.get(args[0]).value {
+        Val::Int(i) => *i,
+        _ => {
+            return Err(
+                "ZipArchive::getCommentIndex(): Argument #1 ($index) must be of type int".into(),
             );
+        }
+    };
+    let flags = zip_flags_arg(vm, args, 1);
+
+    get_entry_comment(vm, "getCommentIndex", flags, |wrapper| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| wrapper.entry_name(index))
+    })
+}
+
+/// Comment of the entry `resolve` names; ZipArchive::FL_UNCHANGED in `flags`
+/// ignores a pending setComment*()
+fn get_entry_comment(
+    vm: &mut VM,
+    method_name: &str,
+    flags: i64,
+    resolve: impl FnOnce(&ZipArchiveWrapper) -> Option<String>,
+) -> Result<Handle, String> {
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or_else(|| format!("No 'this' in ZipArchive::{}", method_name))?;
+    let wrapper = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper.borrow_mut();
+
+    let Some(name) = resolve(&wrapper).filter(|name| wrapper.has_entry(name)) else {
+        wrapper.last_error = 9; // ER_NOENT
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    };
+
+    let comment = entry_comment(&mut wrapper, &name, flags & ZIP_FL_UNCHANGED != 0);
+    Ok(vm.arena.alloc(Val::String(Rc::new(comment))))
+}
+
+/// ZipArchive::FL_NOCASE
+const ZIP_FL_NOCASE: i64 = 1;
+/// ZipArchive::FL_NODIR
+const ZIP_FL_NODIR: i64 = 2;
+/// ZipArchive::FL_UNCHANGED
+const ZIP_FL_UNCHANGED: i64 = 8;
+
+fn zip_flags_arg(vm: &VM, args: &[Handle], position: usize) -> i64 {
+    match args.get(position).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => *i,
+        _ => 0,
+    }
+}
+
+/// Match an entry name against a lookup name, honoring FL_NOCASE and FL_NODIR
+fn zip_name_matches(entry: &str, name: &str, flags: i64) -> bool {
+    let entry = if flags & ZIP_FL_NODIR != 0 {
+        entry.rsplit('/').next().unwrap_or(entry)
+    } else {
+        entry
+    };
+    if flags & ZIP_FL_NOCASE != 0 {
+        entry.eq_ignore_ascii_case(name)
+    } else {
+        entry == name
+    }
+}
+
+/// Find the index of `name`, where indices past the stored entries address
+/// pending additions. With FL_UNCHANGED only the archive on disk is searched
+/// and pending deletions are ignored.
+fn locate_entry(wrapper: &ZipArchiveWrapper, name: &str, flags: i64) -> Option<usize> {
+    let unchanged = flags & ZIP_FL_UNCHANGED != 0;
+    let visible = |entry: &str| unchanged || !wrapper.deletions.contains(entry);
+
+    if let Some(reader) = &wrapper.reader {
+        if flags & (ZIP_FL_NOCASE | ZIP_FL_NODIR) == 0 {
+            if let Some(index) = reader.index_for_name(name)
+                && visible(name)
+            {
+                return Some(index);
+            }
+        } else if let Some(index) = (0..reader.len()).find(|&i| {
+            reader
+                .name_for_index(i)
+                .is_some_and(|entry| zip_name_matches(entry, name, flags) && visible(entry))
+        }) {
+            return Some(index);
+        }
+    }
+
+    if unchanged {
+        return None;
+    }
+    let reader_len = wrapper.reader.as_ref().map(|r| r.len()).unwrap_or(0);
+    wrapper
+        .additions
+        .keys()
+        .position(|entry| zip_name_matches(entry, name, flags) && visible(entry))
+        .map(|index| reader_len + index)
+}
 
-            return Ok(vm.arena.alloc(Val::Array(Rc::new(ArrayData {
-                map,
-                next_free: 0,
-                internal_ptr: 0,
-            }))));
+/// ZIP_EM_* value for an entry, read from its AES extra field when present
+fn zip_encryption_method(encrypted: bool, extra: Option<&[u8]>) -> i64 {
+    if !encrypted {
+        return 0; // ZIP_EM_NONE
+    }
+    let mut extra = extra.unwrap_or_default();
+    while extra.len() >= 4 {
+        let id = u16::from_le_bytes([extra[0], extra[1]]);
+        let len = u16::from_le_bytes([extra[2], extra[3]]) as usize;
+        let data = &extra[4..extra.len().min(4 + len)];
+        // WinZip AES: version (2), vendor "AE" (2), strength (1), method (2)
+        if id == 0x9901 && data.len() >= 5 && (1..=3).contains(&data[4]) {
+            return 0x0100 + data[4] as i64; // ZIP_EM_AES_128/192/256
         }
+        extra = &extra[extra.len().min(4 + len)..];
     }
+    1 // ZIP_EM_TRAD_PKWARE
+}
 
-    Ok(vm.arena.alloc(Val::Bool(false)))
+/// Convert an MS-DOS entry timestamp (local time) to a UNIX timestamp
+fn zip_dos_time_to_unix(time: zip::DateTime) -> i64 {
+    use chrono::TimeZone;
+
+    chrono::Local
+        .with_ymd_and_hms(
+            time.year() as i32,
+            time.month() as u32,
+            time.day() as u32,
+            time.hour() as u32,
+            time.minute() as u32,
+            time.second() as u32,
+        )
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .unwrap_or(0)
+}
+
+/// Convert a UNIX timestamp to an MS-DOS entry timestamp (local time), which
+/// cannot represent dates outside 1980-2107
+fn zip_unix_to_dos_time(timestamp: i64) -> zip::DateTime {
+    use chrono::{Datelike, TimeZone, Timelike};
+
+    chrono::Local
+        .timestamp_opt(timestamp, 0)
+        .earliest()
+        .and_then(|dt| {
+            zip::DateTime::from_date_and_time(
+                u16::try_from(dt.year()).ok()?,
+                dt.month() as u8,
+                dt.day() as u8,
+                dt.hour() as u8,
+                dt.minute() as u8,
+                dt.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// Build the statIndex()/statName() array for entry `index`, or None when the
+/// entry does not exist (or is pending deletion without FL_UNCHANGED)
+fn stat_entry(
+    vm: &mut VM,
+    wrapper: &mut ZipArchiveWrapper,
+    index: usize,
+    flags: i64,
+) -> Option<Handle> {
+    let unchanged = flags & ZIP_FL_UNCHANGED != 0;
+    let reader_len = wrapper.reader.as_ref().map(|r| r.len()).unwrap_or(0);
+
+    let (name, crc, size, comp_size, mtime, comp_method, encryption_method) = if index < reader_len
+    {
+        let reader = wrapper.reader.as_mut()?;
+        let file = reader.by_index_raw(index).ok()?;
+        if !unchanged && wrapper.deletions.contains(file.name()) {
+            return None;
+        }
+        #[allow(deprecated)]
+        let comp_method = file.compression().to_u16() as i64;
+        (
+            file.name().to_string(),
+            file.crc32() as i64,
+            file.size() as i64,
+            file.compressed_size() as i64,
+            file.last_modified().map(zip_dos_time_to_unix).unwrap_or(0),
+            comp_method,
+            zip_encryption_method(file.encrypted(), file.extra_data()),
+        )
+    } else {
+        if unchanged {
+            return None;
+        }
+        let (name, content) = wrapper.additions.get_index(index - reader_len)?;
+        if wrapper.deletions.contains(name) {
+            return None;
+        }
+        let mut crc = flate2::Crc::new();
+        crc.update(content);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        // Buffered additions are uncompressed until close() writes them
+        (
+            name.clone(),
+            crc.sum() as i64,
+            content.len() as i64,
+            content.len() as i64,
+            now,
+            0,
+            0,
+        )
+    };
+
+    // A pending setMtime*() shows up before close() writes it
+    let mtime = match wrapper.mtimes.get(&name) {
+        Some(&pending) if !unchanged => pending,
+        _ => mtime,
+    };
+
+    let mut map = IndexMap::new();
+    map.insert(
+        ArrayKey::Str(Rc::new(b"name".to_vec())),
+        vm.arena.alloc(Val::String(Rc::new(name.into_bytes()))),
+    );
+    for (key, value) in [
+        (&b"index"[..], index as i64),
+        (b"crc", crc),
+        (b"size", size),
+        (b"mtime", mtime),
+        (b"comp_size", comp_size),
+        (b"comp_method", comp_method),
+        (b"encryption_method", encryption_method),
+    ] {
+        map.insert(
+            ArrayKey::Str(Rc::new(key.to_vec())),
+            vm.arena.alloc(Val::Int(value)),
+        );
+    }
+
+    Some(vm.arena.alloc(Val::Array(Rc::new(ArrayData {
+        map,
+        next_free: 0,
+        internal_ptr: 0,
+    }))))
+}
+
+pub fn php_zip_archive_locate_name(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::locateName() expects at least 1 parameter".into());
+    }
+
+    let name = match &vm.arena.get(args[0]).value {
+        Val::String(s) => String::from_utf8_lossy(s).to_string(),
+        _ => return Err("ZipArchive::locateName(): Argument #1 (name) must be string".into()),
+    };
+    let flags = zip_flags_arg(vm, args, 1);
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in ZipArchive::locateName")?;
+    let wrapper_rc = get_zip_wrapper(vm, this_handle)?;
+    let wrapper = wrapper_rc.borrow();
+
+    match locate_entry(&wrapper, &name, flags) {
+        Some(index) => Ok(vm.arena.alloc(Val::Int(index as i64))),
+        None => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
+}
+
+pub fn php_zip_archive_stat_index(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("ZipArchive::statIndex() expects at least 1 parameter".into());
+    }
+
+    let index = match &vm.arena.get(args[0]).value {
+        Val::Int(i) if *i >= 0 => *i as usize,
+        Val::Int(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
+        _ => return Err("ZipArchive::statIndex(): Argument #1 (index) must be integer".into()),
+    };
+    let flags = zip_flags_arg(vm, args, 1);
+
+    let this_handle = vm
+        .frames
+        .last()
+        .and_then(|f| f.this)
+        .ok_or("No 'this' in ZipArchive::statIndex")?;
+    let wrapper = get_zip_wrapper(vm, this_handle)?;
+    let mut wrapper = wrapper.borrow_mut();
+
+    match stat_entry(vm, &mut wrapper, index, flags) {
+        Some(stat) => Ok(stat),
+        None => Ok(vm.arena.alloc(Val::Bool(false))),
+    }
 }
 
 pub fn php_zip_archive_stat_name(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
-        return Err("ZipArchive::statName() expects 1 parameter".into());
+        return Err("ZipArchive::statName() expects at least 1 parameter".into());
     }
 
     let name = match &vm.arena.get(args[0]).value {
         Val::String(s) => String::from_utf8_lossy(s).to_string(),
         _ => return Err("ZipArchive::statName(): Argument #1 (name) must be string".into()),
     };
+    let flags = zip_flags_arg(vm, args, 1);
 
     let this_handle = vm
         .frames
@@ -1165,50 +2960,12 @@ pub fn php_zip_archive_stat_name(vm: &mut VM, args: &[Handle]) -> Result<Handle,
     let wrapper = get_zip_wrapper(vm, this_handle)?;
     let mut wrapper = wrapper.borrow_mut();
 
-    if let Some(reader) = &mut wrapper.reader {
-        if let Some(index) = reader.index_for_name(&name) {
-            if let Ok(file) = reader.by_index(index) {
-                let mut map = IndexMap::new();
-                map.insert(
-                    ArrayKey::Str(Rc::new(b"name".to_vec())),
-                    vm.arena
-                        .alloc(Val::String(Rc::new(file.name().as_bytes().to_vec()))),
-                );
-                map.insert(
-                    ArrayKey::Str(Rc::new(b"index".to_vec())),
-                    vm.arena.alloc(Val::Int(index as i64)),
-                );
-                map.insert(
-                    ArrayKey::Str(Rc::new(b"crc".to_vec())),
-                    vm.arena.alloc(Val::Int(file.crc32() as i64)),
-                );
-                map.insert(
-                    ArrayKey::Str(Rc::new(b"size".to_vec())),
-                    vm.arena.alloc(Val::Int(file.size() as i64)),
-                );
-                map.insert(
-                    ArrayKey::Str(Rc::new(b"comp_size".to_vec())),
-                    vm.arena.alloc(Val::Int(file.compressed_size() as i64)),
-                );
-                map.insert(
-                    ArrayKey::Str(Rc::new(b"mtime".to_vec())),
-                    vm.arena.alloc(Val::Int(0)),
-                );
-                map.insert(
-                    ArrayKey::Str(Rc::new(b"comp_method".to_vec())),
-                    vm.arena.alloc(Val::Int(0)),
-                );
-
-                return Ok(vm.arena.alloc(Val::Array(Rc::new(ArrayData {
-                    map,
-                    next_free: 0,
-                    internal_ptr: 0,
-                }))));
-            }
-        }
+    let stat = locate_entry(&wrapper, &name, flags)
+        .and_then(|index| stat_entry(vm, &mut wrapper, index, flags));
+    match stat {
+        Some(stat) => Ok(stat),
+        None => Ok(vm.arena.alloc(Val::Bool(false))),
     }
-
-    Ok(vm.arena.alloc(Val::Bool(false)))
 }
 
 pub fn php_zip_archive_unchange_all(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
@@ -1222,6 +2979,11 @@ pub fn php_zip_archive_unchange_all(vm: &mut VM, _args: &[Handle]) -> Result<Han
 
     wrapper.additions.clear();
     wrapper.deletions.clear();
+    wrapper.compression.clear();
+    wrapper.encryption.clear();
+    wrapper.mtimes.clear();
+    wrapper.comments.clear();
+    wrapper.comment = None;
 
     // Update properties
     update_zip_properties(vm, this_handle, &wrapper)?;
@@ -1253,6 +3015,11 @@ pub fn php_zip_archive_set_password(vm: &mut VM, args: &[Handle]) -> Result<Hand
 }
 
 // Procedural functions
+
+/// zip_open($filename, $flags = 0) - returns a zip resource, or the
+/// ZipArchive::ER_* code as an int when the archive cannot be opened.
+/// The procedural API only reads, so of ZipArchive::open()'s flags just
+/// EXCL and CHECKCONS apply.
 pub fn php_zip_open(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
         return Err("zip_open() expects 1 parameter".into());
@@ -1262,33 +3029,32 @@ pub fn php_zip_open(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         Val::String(s) => String::from_utf8_lossy(s).to_string(),
         _ => return Err("zip_open(): Argument #1 (filename) must be string".into()),
     };
+    let flags = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) => *i,
+        _ => 0,
+    };
 
     let path = Path::new(&filename);
     if !path.exists() {
-        return Ok(vm.arena.alloc(Val::Bool(false)));
+        return Ok(vm.arena.alloc(Val::Int(9))); // ER_NOENT
+    }
+    if flags & 2 != 0 {
+        // ZipArchive::EXCL
+        return Ok(vm.arena.alloc(Val::Int(10))); // ER_EXISTS
     }
 
-    match File::open(path) {
-        Ok(file) => match ZipArchive::new(file) {
-            Ok(archive) => {
-                let mut wrapper = ZipArchiveWrapper::new();
-                wrapper.path = filename;
-                wrapper.reader = Some(archive);
-
-                let resource_id = vm.context.next_resource_id;
-                vm.context.next_resource_id += 1;
-                vm.context
-                    .resource_manager
-                    .register(resource_id, Rc::new(RefCell::new(wrapper)));
-
-                Ok(vm.arena.alloc(Val::Resource(Rc::new(resource_id))))
-            }
-            Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
-        },
-        Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
+    match open_zip_reader(path, flags) {
+        Ok(archive) => {
+            let mut wrapper = ZipArchiveWrapper::new();
+            wrapper.path = filename;
+            wrapper.reader = Some(archive);
+            Ok(alloc_zip_resource(vm, wrapper))
+        }
+        Err((code, _)) => Ok(vm.arena.alloc(Val::Int(code))),
     }
 }
 
+/// zip_close($zip) - false when the resource was already closed
 pub fn php_zip_close(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
         return Err("zip_close() expects 1 parameter".into());
@@ -1299,9 +3065,13 @@ pub fn php_zip_close(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         _ => return Err("zip_close(): Argument #1 must be a zip resource".into()),
     };
 
-    vm.context
+    let closed = vm
+        .context
         .resource_manager
         .remove::<ZipArchiveWrapper>(resource_id);
+    if closed.is_none() {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
 
     Ok(vm.arena.alloc(Val::Null))
 }
@@ -1328,61 +3098,128 @@ pub fn php_zip_read(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
             let entry_index = wrapper.current_entry_index;
             wrapper.current_entry_index += 1;
 
-            let entry_id = vm.context.next_resource_id;
-            vm.context.next_resource_id += 1;
-            vm.context
-                .resource_manager
-                .register(entry_id, Rc::new(RefCell::new((resource_id, entry_index))));
-
-            return Ok(vm.arena.alloc(Val::Resource(Rc::new(entry_id))));
+            drop(wrapper);
+            return Ok(alloc_zip_resource(
+                vm,
+                ZipEntryResource {
+                    zip_id: resource_id,
+                    index: entry_index,
+                    position: 0,
+                    content: None,
+                },
+            ));
         }
     }
 
     Ok(vm.arena.alloc(Val::Bool(false)))
 }
 
-pub fn php_zip_entry_open(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
-    Ok(vm.arena.alloc(Val::Bool(true)))
+fn get_zip_entry(
+    vm: &VM,
+    handle: Handle,
+    function: &str,
+) -> Result<Rc<RefCell<ZipEntryResource>>, String> {
+    let entry_id = match &vm.arena.get(handle).value {
+        Val::Resource(id) => *id.downcast_ref::<u64>().ok_or("Invalid resource type")?,
+        _ => {
+            return Err(format!(
+                "{}(): Argument #1 must be a zip entry resource",
+                function
+            ));
+        }
+    };
+    vm.context
+        .resource_manager
+        .get::<ZipEntryResource>(entry_id)
+        .ok_or_else(|| "Invalid zip entry resource".into())
+}
+
+/// Decompress an entry's contents for zip_entry_read()
+fn load_zip_entry(vm: &VM, entry: &ZipEntryResource) -> Option<Vec<u8>> {
+    use std::io::Read;
+
+    let wrapper_rc = vm
+        .context
+        .resource_manager
+        .get::<ZipArchiveWrapper>(entry.zip_id)?;
+    let mut wrapper = wrapper_rc.borrow_mut();
+    let mut file = wrapper.reader.as_mut()?.by_index(entry.index).ok()?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content).ok()?;
+    Some(content)
+}
+
+/// zip_entry_open($zip, $entry, $mode = "rb") - only read modes are supported
+pub fn php_zip_entry_open(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.len() < 2 {
+        return Err("zip_entry_open() expects at least 2 parameters".into());
+    }
+
+    let zip_id = match &vm.arena.get(args[0]).value {
+        Val::Resource(id) => *id.downcast_ref::<u64>().ok_or("Invalid resource type")?,
+        _ => return Err("zip_entry_open(): Argument #1 must be a zip resource".into()),
+    };
+    let entry_rc = get_zip_entry(vm, args[1], "zip_entry_open")?;
+    let read_mode = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::String(mode)) => mode.first() == Some(&b'r'),
+        _ => true,
+    };
+
+    let mut entry = entry_rc.borrow_mut();
+    if !read_mode || entry.zip_id != zip_id {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    entry.content = load_zip_entry(vm, &entry);
+    entry.position = 0;
+    Ok(vm.arena.alloc(Val::Bool(entry.content.is_some())))
 }
 
-pub fn php_zip_entry_close(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+/// zip_entry_close($entry) - release the buffered contents and reset the cursor
+pub fn php_zip_entry_close(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    if args.is_empty() {
+        return Err("zip_entry_close() expects 1 parameter".into());
+    }
+
+    let entry_rc = get_zip_entry(vm, args[0], "zip_entry_close")?;
+    let mut entry = entry_rc.borrow_mut();
+    entry.content = None;
+    entry.position = 0;
     Ok(vm.arena.alloc(Val::Bool(true)))
 }
 
+/// zip_entry_read($entry, $len = 1024) - read up to $len bytes from the
+/// entry's cursor; returns "" at end of entry
 pub fn php_zip_entry_read(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.is_empty() {
         return Err("zip_entry_read() expects at least 1 parameter".into());
     }
 
-    let entry_id = match &vm.arena.get(args[0]).value {
-        Val::Resource(id) => *id.downcast_ref::<u64>().ok_or("Invalid resource type")?,
-        _ => return Err("zip_entry_read(): Argument #1 must be a zip entry resource".into()),
+    let entry_rc = get_zip_entry(vm, args[0], "zip_entry_read")?;
+    let len = match args.get(1) {
+        Some(h) => vm.arena.get(*h).value.to_int(),
+        None => 1024,
     };
+    if len <= 0 {
+        return Err("zip_entry_read(): Argument #2 ($len) must be greater than 0".into());
+    }
 
-    let (resource_id, entry_index) = vm
-        .context
-        .resource_manager
-        .get::<(u64, usize)>(entry_id)
-        .map(|rc| *rc.borrow())
-        .ok_or("Invalid zip entry resource")?;
-    let wrapper_rc = vm
-        .context
-        .resource_manager
-        .get::<ZipArchiveWrapper>(resource_id)
-        .ok_or("Zip resource not found")?;
-    let mut wrapper = wrapper_rc.borrow_mut();
-
-    if let Some(reader) = &mut wrapper.reader {
-        if let Ok(mut entry) = reader.by_index(entry_index) {
-            let mut content = Vec::new();
-            use std::io::Read;
-            if entry.read_to_end(&mut content).is_ok() {
-                return Ok(vm.arena.alloc(Val::String(Rc::new(content))));
-            }
-        }
+    let mut entry = entry_rc.borrow_mut();
+    if entry.content.is_none() {
+        entry.content = load_zip_entry(vm, &entry);
     }
+    let position = entry.position;
+    let chunk = match &entry.content {
+        Some(content) => {
+            let start = position.min(content.len());
+            let end = start.saturating_add(len as usize).min(content.len());
+            content[start..end].to_vec()
+        }
+        None => return Ok(vm.arena.alloc(Val::Bool(false))),
+    };
+    entry.position += chunk.len();
 
-    Ok(vm.arena.alloc(Val::Bool(false)))
+    Ok(vm.arena.alloc(Val::String(Rc::new(chunk))))
 }
 
 pub fn php_zip_entry_name(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
@@ -1398,8 +3235,11 @@ pub fn php_zip_entry_name(vm: &mut VM, args: &[Handle]) -> Result<Handle, String
     let (resource_id, entry_index) = vm
         .context
         .resource_manager
-        .get::<(u64, usize)>(entry_id)
-        .map(|rc| *rc.borrow())
+        .get::<ZipEntryResource>(entry_id)
+        .map(|rc| {
+            let entry = rc.borrow();
+            (entry.zip_id, entry.index)
+        })
         .ok_or("Invalid zip entry resource")?;
     let wrapper_rc = vm
         .context
@@ -1432,8 +3272,11 @@ pub fn php_zip_entry_filesize(vm: &mut VM, args: &[Handle]) -> Result<Handle, St
     let (resource_id, entry_index) = vm
         .context
         .resource_manager
-        .get::<(u64, usize)>(entry_id)
-        .map(|rc| *rc.borrow())
+        .get::<ZipEntryResource>(entry_id)
+        .map(|rc| {
+            let entry = rc.borrow();
+            (entry.zip_id, entry.index)
+        })
         .ok_or("Invalid zip entry resource")?;
     let wrapper_rc = vm
         .context
@@ -1468,8 +3311,11 @@ pub fn php_zip_entry_compressionmethod(vm: &mut VM, args: &[Handle]) -> Result<H
     let (resource_id, entry_index) = vm
         .context
         .resource_manager
-        .get::<(u64, usize)>(entry_id)
-        .map(|rc| *rc.borrow())
+        .get::<ZipEntryResource>(entry_id)
+        .map(|rc| {
+            let entry = rc.borrow();
+            (entry.zip_id, entry.index)
+        })
         .ok_or("Invalid zip entry resource")?;
     let wrapper_rc = vm
         .context
@@ -1480,9 +3326,19 @@ pub fn php_zip_entry_compressionmethod(vm: &mut VM, args: &[Handle]) -> Result<H
 
     if let Some(reader) = &mut wrapper.reader {
         if let Ok(entry) = reader.by_index(entry_index) {
-            return Ok(vm.arena.alloc(Val::String(
-                format!("{:?}", entry.compression()).into_bytes().into(),
-            )));
+            #[allow(deprecated)]
+            let name: &[u8] = match entry.compression().to_u16() {
+                0 => b"stored",
+                1 => b"shrunk",
+                2..=5 => b"reduced",
+                6 => b"imploded",
+                7 => b"tokenized",
+                8 => b"deflated",
+                9 => b"deflatedX",
+                10 => b"implodedX",
+                _ => b"unknown",
+            };
+            return Ok(vm.arena.alloc(Val::String(name.to_vec().into())));
         }
     }
 
@@ -1506,8 +3362,11 @@ pub fn php_zip_entry_compressedsize(vm: &mut VM, args: &[Handle]) -> Result<Hand
     let (resource_id, entry_index) = vm
         .context
         .resource_manager
-        .get::<(u64, usize)>(entry_id)
-        .map(|rc| *rc.borrow())
+        .get::<ZipEntryResource>(entry_id)
+        .map(|rc| {
+            let entry = rc.borrow();
+            (entry.zip_id, entry.index)
+        })
         .ok_or("Invalid zip entry resource")?;
     let wrapper_rc = vm
         .context
@@ -1,8 +1,6 @@
 use crate::core::value::{ArrayData, ArrayKey, Handle, ObjectData, Val};
-use crate::vm::engine::VM;
-use flate2::read::{
-    DeflateDecoder, DeflateEncoder, GzDecoder, GzEncoder as GzReadEncoder, ZlibDecoder, ZlibEncoder,
-};
+use crate::vm::engine::{ErrorLevel, VM};
+use flate2::read::GzDecoder;
 use flate2::write::GzEncoder as GzWriteEncoder;
 use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
 use std::any::Any;
@@ -34,6 +32,10 @@ struct GzFileReader {
 
 impl GzFileInner for GzFileReader {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // An empty buffer reads nothing and must not be mistaken for EOF
+        if buf.is_empty() {
+            return Ok(0);
+        }
         let n = self.decoder.read(buf)?;
         if n == 0 {
             self.eof = true;
@@ -81,12 +83,18 @@ impl GzFileInner for GzFileReader {
             )),
         }
     }
+    /// Read a line of at most `length - 1` bytes, as fgets() does
     fn gets(&mut self, length: usize) -> std::io::Result<Vec<u8>> {
         let mut buf = Vec::new();
         let mut byte = [0u8; 1];
-        while buf.len() < length - 1 {
-            if self.read(&mut byte)? == 0 {
-                break;
+        while buf.len() < length.saturating_sub(1) {
+            match self.read(&mut byte) {
+                Ok(0) => break,
+                Ok(_) => {}
+                // Hand back what was already consumed (and counted by
+                // gztell); the error resurfaces on the next read
+                Err(_) if !buf.is_empty() => break,
+                Err(e) => return Err(e),
             }
             buf.push(byte[0]);
             if byte[0] == b'\n' {
@@ -153,6 +161,85 @@ impl GzFileInner for GzFileWriter {
 pub struct DeflateContext {
     pub compress: RefCell<Compress>,
     pub encoding: i64,
+    /// CRC-32 of the input so far for ZLIB_ENCODING_GZIP, whose header and
+    /// trailer flate2 leaves to us; None until the header has been written
+    gzip_crc: RefCell<Option<flate2::Crc>>,
+}
+
+impl DeflateContext {
+    /// Deflater for `encoding` (ZLIB_ENCODING_RAW, ZLIB_ENCODING_DEFLATE or
+    /// ZLIB_ENCODING_GZIP), or None for any other value
+    pub fn new(compression: Compression, encoding: i64) -> Option<Self> {
+        let zlib_header = match encoding {
+            15 => true,        // ZLIB_ENCODING_DEFLATE
+            31 | -15 => false, // ZLIB_ENCODING_GZIP, ZLIB_ENCODING_RAW
+            _ => return None,
+        };
+        Some(Self {
+            compress: RefCell::new(Compress::new(compression, zlib_header)),
+            encoding,
+            gzip_crc: RefCell::new(None),
+        })
+    }
+
+    /// Compress `data` with `flush`, framing gzip streams with their header
+    /// and trailer. ZLIB_FINISH ends the stream and resets the deflater, so
+    /// the next call starts a new one.
+    pub fn add(&self, data: &[u8], flush: FlushCompress) -> Result<Vec<u8>, flate2::CompressError> {
+        let mut output = Vec::new();
+        let mut gzip_crc = self.gzip_crc.borrow_mut();
+        if self.encoding == 31 {
+            let crc = gzip_crc.get_or_insert_with(|| {
+                // Magic, CM=deflate, no flags, no mtime, no extra flags, OS=Unix
+                output.extend_from_slice(&[0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 3]);
+                flate2::Crc::new()
+            });
+            crc.update(data);
+        }
+
+        let mut compress = self.compress.borrow_mut();
+        output.extend(deflate_chunk(&mut compress, data, flush)?);
+
+        if flush == FlushCompress::Finish {
+            if let Some(crc) = gzip_crc.take() {
+                output.extend_from_slice(&crc.sum().to_le_bytes());
+                output.extend_from_slice(&crc.amount().to_le_bytes());
+            }
+            compress.reset();
+        }
+        Ok(output)
+    }
+}
+
+/// gzip stream for `zlib.output_compression`, wrapping the script's
+/// unbuffered output from its first byte until the request ends
+pub struct OutputCompression {
+    deflate: DeflateContext,
+}
+
+impl OutputCompression {
+    /// Start a stream at `level` (-1 for the default), returning the state
+    /// and the gzip member header to send ahead of any compressed data
+    pub fn start(level: i32) -> (Self, Vec<u8>) {
+        let compression = if level == -1 {
+            Compression::default()
+        } else {
+            Compression::new(level as u32)
+        };
+        let deflate = DeflateContext::new(compression, 31).expect("ZLIB_ENCODING_GZIP");
+        let header = deflate.add(&[], FlushCompress::None).unwrap_or_default();
+        (Self { deflate }, header)
+    }
+
+    /// Compress `data`, returning whatever output the deflater released
+    pub fn write(&mut self, data: &[u8], flush: FlushCompress) -> Vec<u8> {
+        self.deflate.add(data, flush).unwrap_or_default()
+    }
+
+    /// End the stream: the remaining deflate output plus the gzip trailer
+    pub fn finish(mut self) -> Vec<u8> {
+        self.write(&[], FlushCompress::Finish)
+    }
 }
 
 pub struct InflateContext {
@@ -160,6 +247,9 @@ pub struct InflateContext {
     pub encoding: i64,
     pub status: RefCell<Status>,
     pub read_len: RefCell<usize>,
+    /// Input buffered until a ZLIB_ENCODING_GZIP stream's header is complete;
+    /// None once the header has been skipped (or for other encodings)
+    gzip_header: RefCell<Option<Vec<u8>>>,
 }
 
 /// gzcompress(string $data, int $level = -1, int $encoding = ZLIB_ENCODING_DEFLATE): string|false
@@ -194,13 +284,161 @@ pub fn php_gzcompress(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         Compression::new(level as u32)
     };
 
-    let mut encoder = ZlibEncoder::new(&data[..], compression);
-    let mut buffer = Vec::new();
-    if encoder.read_to_end(&mut buffer).is_err() {
-        return Ok(vm.arena.alloc(Val::Bool(false)));
+    let encoding = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        None => 15, // ZLIB_ENCODING_DEFLATE
+        Some(Val::Int(i)) => *i,
+        Some(_) => {
+            return Err("gzcompress(): Argument #3 ($encoding) must be of type int".into());
+        }
+    };
+
+    deflate_one_shot(vm, "gzcompress", &data, compression, encoding, 3)
+}
+
+/// Container around the deflate stream handled by `inflate_one_shot`
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InflateEncoding {
+    Raw,
+    Zlib,
+    Gzip,
+}
+
+impl InflateEncoding {
+    /// Sniff the container from its header, as zlib_decode() does
+    fn detect(data: &[u8]) -> Self {
+        match data {
+            [0x1f, 0x8b, ..] => Self::Gzip,
+            [cmf, flg, ..]
+                if cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 =>
+            {
+                Self::Zlib
+            }
+            _ => Self::Raw,
+        }
     }
+}
 
-    Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
+/// Decompress a whole string for gzuncompress(), gzinflate(), gzdecode() and
+/// zlib_decode(). Output stops once `max_length` bytes (0 for no limit) have
+/// been produced; otherwise a corrupt or truncated stream, or output larger
+/// than memory_limit, warns with zlib's message and returns None.
+fn inflate_one_shot(
+    vm: &mut VM,
+    func: &str,
+    data: &[u8],
+    encoding: InflateEncoding,
+    max_length: usize,
+) -> Option<Vec<u8>> {
+    match inflate_data(data, encoding, max_length, vm.memory_limit) {
+        Ok(output) => Some(output),
+        Err(message) => {
+            vm.report_error(ErrorLevel::Warning, &format!("{}(): {}", func, message));
+            None
+        }
+    }
+}
+
+fn inflate_data(
+    data: &[u8],
+    encoding: InflateEncoding,
+    max_length: usize,
+    memory_limit: usize,
+) -> Result<Vec<u8>, &'static str> {
+    const DATA_ERROR: &str = "data error";
+
+    let stream = match encoding {
+        InflateEncoding::Gzip => match gzip_header_len(data)? {
+            Some(len) => &data[len..],
+            None => return Err(DATA_ERROR),
+        },
+        _ => data,
+    };
+    let mut inflater = Decompress::new(encoding == InflateEncoding::Zlib);
+    let mut output = Vec::new();
+    let mut chunk = vec![0u8; 8192];
+
+    loop {
+        let room = match max_length {
+            0 => chunk.len(),
+            max => chunk.len().min(max - output.len()),
+        };
+        let before_in = inflater.total_in();
+        let before_out = inflater.total_out();
+        let status = inflater
+            .decompress(
+                &stream[before_in as usize..],
+                &mut chunk[..room],
+                FlushDecompress::None,
+            )
+            .map_err(|_| DATA_ERROR)?;
+        let consumed = inflater.total_in() - before_in;
+        let produced = (inflater.total_out() - before_out) as usize;
+        output.extend_from_slice(&chunk[..produced]);
+
+        if memory_limit > 0 && output.len() > memory_limit {
+            return Err("insufficient memory");
+        }
+        if status == Status::StreamEnd {
+            break;
+        }
+        if max_length > 0 && output.len() >= max_length {
+            // Stopping early is not an error, and leaves the trailer unchecked
+            return Ok(output);
+        }
+        if consumed == 0 && produced == 0 {
+            // The input ended before the stream did
+            return Err(DATA_ERROR);
+        }
+    }
+
+    if encoding == InflateEncoding::Gzip {
+        // CRC-32 and size of the uncompressed data (RFC 1952)
+        let end = inflater.total_in() as usize;
+        let trailer = stream.get(end..end + 8).ok_or(DATA_ERROR)?;
+        let mut crc = flate2::Crc::new();
+        crc.update(&output);
+        if trailer[..4] != crc.sum().to_le_bytes() || trailer[4..] != crc.amount().to_le_bytes() {
+            return Err(DATA_ERROR);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Length of the gzip member header at the start of `data`, skipping the
+/// optional fields its flags announce (RFC 1952), or None while incomplete
+fn gzip_header_len(data: &[u8]) -> Result<Option<usize>, &'static str> {
+    if !data.starts_with(&[0x1f, 0x8b, 8][..data.len().min(3)]) {
+        return Err("data error");
+    }
+    let Some(&flags) = data.get(3) else {
+        return Ok(None);
+    };
+    let mut pos = 10;
+    if flags & 0x04 != 0 {
+        // FEXTRA: length-prefixed
+        let (Some(&low), Some(&high)) = (data.get(pos), data.get(pos + 1)) else {
+            return Ok(None);
+        };
+        pos += 2 + u16::from_le_bytes([low, high]) as usize;
+    }
+    for field in [0x08, 0x10] {
+        // FNAME, FCOMMENT: zero-terminated
+        if flags & field != 0 {
+            let Some(end) = data
+                .get(pos..)
+                .and_then(|rest| rest.iter().position(|&b| b == 0))
+            else {
+                return Ok(None);
+            };
+            pos += end + 1;
+        }
+    }
+    if flags & 0x02 != 0 {
+        // FHCRC
+        pos += 2;
+    }
+    Ok((data.len() >= pos).then_some(pos))
 }
 
 /// gzuncompress(string $data, int $max_length = 0): string|false
@@ -223,18 +461,11 @@ pub fn php_gzuncompress(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
         0
     };
 
-    let mut decoder = ZlibDecoder::new(&data[..]);
-    let mut buffer = Vec::new();
-
-    let result = if max_length > 0 {
-        decoder.take(max_length as u64).read_to_end(&mut buffer)
-    } else {
-        decoder.read_to_end(&mut buffer)
-    };
-
-    if result.is_err() {
+    let Some(buffer) =
+        inflate_one_shot(vm, "gzuncompress", &data, InflateEncoding::Zlib, max_length)
+    else {
         return Ok(vm.arena.alloc(Val::Bool(false)));
-    }
+    };
 
     Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
 }
@@ -271,13 +502,15 @@ pub fn php_gzdeflate(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         Compression::new(level as u32)
     };
 
-    let mut encoder = DeflateEncoder::new(&data[..], compression);
-    let mut buffer = Vec::new();
-    if encoder.read_to_end(&mut buffer).is_err() {
-        return Ok(vm.arena.alloc(Val::Bool(false)));
-    }
+    let encoding = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        None => -15, // ZLIB_ENCODING_RAW
+        Some(Val::Int(i)) => *i,
+        Some(_) => {
+            return Err("gzdeflate(): Argument #3 ($encoding) must be of type int".into());
+        }
+    };
 
-    Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
+    deflate_one_shot(vm, "gzdeflate", &data, compression, encoding, 3)
 }
 
 /// gzinflate(string $data, int $max_length = 0): string|false
@@ -300,18 +533,10 @@ pub fn php_gzinflate(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         0
     };
 
-    let mut decoder = DeflateDecoder::new(&data[..]);
-    let mut buffer = Vec::new();
-
-    let result = if max_length > 0 {
-        decoder.take(max_length as u64).read_to_end(&mut buffer)
-    } else {
-        decoder.read_to_end(&mut buffer)
-    };
-
-    if result.is_err() {
+    let Some(buffer) = inflate_one_shot(vm, "gzinflate", &data, InflateEncoding::Raw, max_length)
+    else {
         return Ok(vm.arena.alloc(Val::Bool(false)));
-    }
+    };
 
     Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
 }
@@ -348,13 +573,15 @@ pub fn php_gzencode(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         Compression::new(level as u32)
     };
 
-    let mut encoder = GzReadEncoder::new(&data[..], compression);
-    let mut buffer = Vec::new();
-    if encoder.read_to_end(&mut buffer).is_err() {
-        return Ok(vm.arena.alloc(Val::Bool(false)));
-    }
+    let encoding = match args.get(2).map(|h| &vm.arena.get(*h).value) {
+        None => 31, // ZLIB_ENCODING_GZIP
+        Some(Val::Int(i)) => *i,
+        Some(_) => {
+            return Err("gzencode(): Argument #3 ($encoding) must be of type int".into());
+        }
+    };
 
-    Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
+    deflate_one_shot(vm, "gzencode", &data, compression, encoding, 3)
 }
 
 /// gzdecode(string $data, int $max_length = 0): string|false
@@ -377,18 +604,10 @@ pub fn php_gzdecode(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         0
     };
 
-    let mut decoder = GzDecoder::new(&data[..]);
-    let mut buffer = Vec::new();
-
-    let result = if max_length > 0 {
-        decoder.take(max_length as u64).read_to_end(&mut buffer)
-    } else {
-        decoder.read_to_end(&mut buffer)
-    };
-
-    if result.is_err() {
+    let Some(buffer) = inflate_one_shot(vm, "gzdecode", &data, InflateEncoding::Gzip, max_length)
+    else {
         return Ok(vm.arena.alloc(Val::Bool(false)));
-    }
+    };
 
     Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
 }
@@ -430,33 +649,33 @@ pub fn php_zlib_encode(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         Compression::new(level as u32)
     };
 
-    let mut buffer = Vec::new();
-    match encoding {
-        15 => {
-            // ZLIB_ENCODING_DEFLATE
-            let mut encoder = ZlibEncoder::new(&data[..], compression);
-            if encoder.read_to_end(&mut buffer).is_err() {
-                return Ok(vm.arena.alloc(Val::Bool(false)));
-            }
-        }
-        31 => {
-            // ZLIB_ENCODING_GZIP
-            let mut encoder = GzReadEncoder::new(&data[..], compression);
-            if encoder.read_to_end(&mut buffer).is_err() {
-                return Ok(vm.arena.alloc(Val::Bool(false)));
-            }
-        }
-        -1 => {
-            // ZLIB_ENCODING_RAW
-            let mut encoder = DeflateEncoder::new(&data[..], compression);
-            if encoder.read_to_end(&mut buffer).is_err() {
-                return Ok(vm.arena.alloc(Val::Bool(false)));
-            }
-        }
-        _ => return Err(format!("zlib_encode(): Unknown encoding: {}", encoding)),
+    deflate_one_shot(vm, "zlib_encode", &data, compression, encoding, 2)
+}
+
+/// Compress a whole string for gzcompress(), gzdeflate(), gzencode() and
+/// zlib_encode(); `arg` is the position of their `$encoding` parameter
+fn deflate_one_shot(
+    vm: &mut VM,
+    func: &str,
+    data: &[u8],
+    compression: Compression,
+    encoding: i64,
+    arg: usize,
+) -> Result<Handle, String> {
+    let Some(context) = DeflateContext::new(compression, encoding) else {
+        return Err(invalid_encoding_error(func, arg));
+    };
+    match context.add(data, FlushCompress::Finish) {
+        Ok(buffer) => Ok(vm.arena.alloc(Val::String(Rc::new(buffer)))),
+        Err(_) => Ok(vm.arena.alloc(Val::Bool(false))),
     }
+}
 
-    Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
+fn invalid_encoding_error(func: &str, arg: usize) -> String {
+    format!(
+        "{}(): Argument #{} ($encoding) must be one of ZLIB_ENCODING_RAW, ZLIB_ENCODING_GZIP, or ZLIB_ENCODING_DEFLATE",
+        func, arg
+    )
 }
 
 /// zlib_decode(string $data, int $max_length = 0): string|false
@@ -479,47 +698,12 @@ pub fn php_zlib_decode(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         0
     };
 
-    // zlib_decode is supposed to auto-detect the encoding.
-    // flate2 doesn't have an auto-detect decoder easily.
-    // PHP's zlib_decode supports raw, zlib, and gzip.
-
-    // Try GZIP first
-    let mut decoder = GzDecoder::new(&data[..]);
-    let mut buffer = Vec::new();
-    let result = if max_length > 0 {
-        decoder.take(max_length as u64).read_to_end(&mut buffer)
-    } else {
-        decoder.read_to_end(&mut buffer)
-    };
-    if result.is_ok() {
-        return Ok(vm.arena.alloc(Val::String(Rc::new(buffer))));
-    }
-
-    // Try ZLIB
-    buffer.clear();
-    let mut decoder = ZlibDecoder::new(&data[..]);
-    let result = if max_length > 0 {
-        decoder.take(max_length as u64).read_to_end(&mut buffer)
-    } else {
-        decoder.read_to_end(&mut buffer)
-    };
-    if result.is_ok() {
-        return Ok(vm.arena.alloc(Val::String(Rc::new(buffer))));
-    }
-
-    // Try RAW DEFLATE
-    buffer.clear();
-    let mut decoder = DeflateDecoder::new(&data[..]);
-    let result = if max_length > 0 {
-        decoder.take(max_length as u64).read_to_end(&mut buffer)
-    } else {
-        decoder.read_to_end(&mut buffer)
+    let encoding = InflateEncoding::detect(&data);
+    let Some(buffer) = inflate_one_shot(vm, "zlib_decode", &data, encoding, max_length) else {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
     };
-    if result.is_ok() {
-        return Ok(vm.arena.alloc(Val::String(Rc::new(buffer))));
-    }
 
-    Ok(vm.arena.alloc(Val::Bool(false)))
+    Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
 }
 
 /// deflate_init(int $encoding, array $options = []): DeflateContext|false
@@ -555,21 +739,8 @@ pub fn php_deflate_init(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
         Compression::new(level as u32)
     };
 
-    let zlib_header = match encoding {
-        15 => true,  // ZLIB_ENCODING_DEFLATE
-        31 => false, // ZLIB_ENCODING_GZIP (handled differently in flate2)
-        -1 => false, // ZLIB_ENCODING_RAW
-        _ => return Err(format!("deflate_init(): Unknown encoding: {}", encoding)),
-    };
-
-    // flate2::Compress::new(level, zlib_header)
-    // If encoding is GZIP, we might need a different approach or manual header.
-    // For now, let's support ZLIB and RAW.
-    let compress = Compress::new(compression, zlib_header);
-
-    let context = DeflateContext {
-        compress: RefCell::new(compress),
-        encoding,
+    let Some(context) = DeflateContext::new(compression, encoding) else {
+        return Err(invalid_encoding_error("deflate_init", 1));
     };
 
     let class_name = vm.context.interner.intern(b"DeflateContext");
@@ -625,7 +796,21 @@ pub fn php_deflate_add(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .downcast_ref::<DeflateContext>()
         .ok_or("deflate_add(): Invalid DeflateContext")?;
 
-    let mut compress = context.compress.borrow_mut();
+    let output = match context.add(&data, flush_mode) {
+        Ok(output) => output,
+        Err(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
+    };
+
+    Ok(vm.arena.alloc(Val::String(Rc::new(output))))
+}
+
+/// Feed `data` through `compress` with `flush`, collecting all output the
+/// flush mode requires
+fn deflate_chunk(
+    compress: &mut Compress,
+    data: &[u8],
+    flush: FlushCompress,
+) -> Result<Vec<u8>, flate2::CompressError> {
     let mut output = Vec::with_capacity(data.len() / 2 + 64);
 
     // Incremental compression
@@ -635,7 +820,7 @@ pub fn php_deflate_add(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         let before_out = compress.total_out();
 
         let mut temp_out = vec![0u8; 4096];
-        match compress.compress(&data[input_pos..], &mut temp_out, flush_mode) {
+        match compress.compress(&data[input_pos..], &mut temp_out, flush) {
             Ok(Status::Ok) | Ok(Status::BufError) => {
                 let consumed = (compress.total_in() - before_in) as usize;
                 let produced = (compress.total_out() - before_out) as usize;
@@ -650,19 +835,19 @@ pub fn php_deflate_add(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
                 output.extend_from_slice(&temp_out[..produced]);
                 break;
             }
-            Err(_) => return Ok(vm.arena.alloc(Val::Bool(false))),
+            Err(e) => return Err(e),
         }
     }
 
-    // If flush_mode is Finish or Sync, we might need more calls to get all data
-    if flush_mode == FlushCompress::Finish
-        || flush_mode == FlushCompress::Sync
-        || flush_mode == FlushCompress::Full
+    // If flush is Finish or Sync, we might need more calls to get all data
+    if flush == FlushCompress::Finish
+        || flush == FlushCompress::Sync
+        || flush == FlushCompress::Full
     {
         loop {
             let before_out = compress.total_out();
             let mut temp_out = vec![0u8; 4096];
-            match compress.compress(&[], &mut temp_out, flush_mode) {
+            match compress.compress(&[], &mut temp_out, flush) {
                 Ok(status) => {
                     let produced = (compress.total_out() - before_out) as usize;
                     output.extend_from_slice(&temp_out[..produced]);
@@ -675,7 +860,7 @@ pub fn php_deflate_add(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         }
     }
 
-    Ok(vm.arena.alloc(Val::String(Rc::new(output))))
+    Ok(output)
 }
 
 /// inflate_init(int $encoding, array $options = []): InflateContext|false
@@ -690,10 +875,9 @@ pub fn php_inflate_init(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
     };
 
     let zlib_header = match encoding {
-        15 => true,  // ZLIB_ENCODING_DEFLATE
-        31 => false, // ZLIB_ENCODING_GZIP
-        -1 => false, // ZLIB_ENCODING_RAW
-        _ => return Err(format!("inflate_init(): Unknown encoding: {}", encoding)),
+        15 => true,        // ZLIB_ENCODING_DEFLATE
+        31 | -15 => false, // ZLIB_ENCODING_GZIP, ZLIB_ENCODING_RAW
+        _ => return Err(invalid_encoding_error("inflate_init", 1)),
     };
 
     let decompress = Decompress::new(zlib_header);
@@ -703,6 +887,7 @@ pub fn php_inflate_init(vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
         encoding,
         status: RefCell::new(Status::Ok),
         read_len: RefCell::new(0),
+        gzip_header: RefCell::new((encoding == 31).then(Vec::new)),
     };
 
     let class_name = vm.context.interner.intern(b"InflateContext");
@@ -756,6 +941,28 @@ pub fn php_inflate_add(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .downcast_ref::<InflateContext>()
         .ok_or("inflate_add(): Invalid InflateContext")?;
 
+    // A gzip stream's header is skipped once it has arrived in full
+    let mut gzip_header = context.gzip_header.borrow_mut();
+    let data = match gzip_header.as_mut() {
+        Some(pending) => {
+            pending.extend_from_slice(&data);
+            match gzip_header_len(pending) {
+                Ok(Some(len)) => {
+                    *context.read_len.borrow_mut() += len;
+                    let body = Rc::new(pending.split_off(len));
+                    *gzip_header = None;
+                    body
+                }
+                Ok(None) => return Ok(vm.arena.alloc(Val::String(Rc::new(Vec::new())))),
+                Err(message) => {
+                    vm.report_error(ErrorLevel::Warning, &format!("inflate_add(): {}", message));
+                    return Ok(vm.arena.alloc(Val::Bool(false)));
+                }
+            }
+        }
+        None => data,
+    };
+
     let mut decompress = context.decompress.borrow_mut();
     let mut output = Vec::with_capacity(data.len() * 2);
 
@@ -845,6 +1052,13 @@ pub fn php_inflate_get_read_len(vm: &mut VM, args: &[Handle]) -> Result<Handle,
     Ok(vm.arena.alloc(Val::Int(read_len as i64)))
 }
 
+/// Report a recoverable stream failure as a warning and return false, the
+/// way PHP's gz* stream functions fail
+fn warn_false(vm: &mut VM, func: &str, err: impl std::fmt::Display) -> Result<Handle, String> {
+    vm.report_error(ErrorLevel::Warning, &format!("{}(): {}", func, err));
+    Ok(vm.arena.alloc(Val::Bool(false)))
+}
+
 /// gzopen(string $filename, string $mode, int $use_include_path = 0): resource|false
 pub fn php_gzopen(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     if args.len() < 2 || args.len() > 3 {
@@ -861,8 +1075,23 @@ pub fn php_gzopen(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         _ => return Err("gzopen(): Argument #2 ($mode) must be of type string".into()),
     };
 
+    let use_include_path = args
+        .get(2)
+        .is_some_and(|h| vm.arena.get(*h).value.to_bool());
+    let filename = if use_include_path && mode.contains('r') {
+        match vm.resolve_include_path(&filename) {
+            Some(path) => path.to_string_lossy().into_owned(),
+            None => filename,
+        }
+    } else {
+        filename
+    };
+
     let file = if mode.contains('r') {
-        let f = File::open(&filename).map_err(|e| e.to_string())?;
+        let f = match File::open(&filename) {
+            Ok(f) => f,
+            Err(e) => return warn_false(vm, "gzopen", e),
+        };
         let decoder = GzDecoder::new(f);
         GzFile {
             inner: RefCell::new(Box::new(GzFileReader {
@@ -873,7 +1102,10 @@ pub fn php_gzopen(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
             })),
         }
     } else if mode.contains('w') || mode.contains('a') {
-        let f = File::create(&filename).map_err(|e| e.to_string())?;
+        let f = match File::create(&filename) {
+            Ok(f) => f,
+            Err(e) => return warn_false(vm, "gzopen", e),
+        };
         let encoder = GzWriteEncoder::new(f, Compression::default());
         GzFile {
             inner: RefCell::new(Box::new(GzFileWriter {
@@ -895,7 +1127,8 @@ pub fn php_gzread(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     }
 
     let length = match &vm.arena.get(args[1]).value {
-        Val::Int(i) => *i as usize,
+        Val::Int(i) if *i > 0 => *i as usize,
+        Val::Int(_) => return Err("gzread(): Argument #2 ($length) must be greater than 0".into()),
         _ => return Err("gzread(): Argument #2 ($length) must be of type int".into()),
     };
 
@@ -909,11 +1142,11 @@ pub fn php_gzread(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .ok_or("gzread(): Invalid resource")?;
 
     let mut buffer = vec![0u8; length];
-    let n = gz_file
-        .inner
-        .borrow_mut()
-        .read(&mut buffer)
-        .map_err(|e| e.to_string())?;
+    let read = gz_file.inner.borrow_mut().read(&mut buffer);
+    let n = match read {
+        Ok(n) => n,
+        Err(e) => return warn_false(vm, "gzread", e),
+    };
     buffer.truncate(n);
 
     Ok(vm.arena.alloc(Val::String(Rc::new(buffer))))
@@ -959,11 +1192,11 @@ pub fn php_gzwrite(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         &data[..]
     };
 
-    let n = gz_file
-        .inner
-        .borrow_mut()
-        .write(to_write)
-        .map_err(|e| e.to_string())?;
+    let written = gz_file.inner.borrow_mut().write(to_write);
+    let n = match written {
+        Ok(n) => n,
+        Err(e) => return warn_false(vm, "gzwrite", e),
+    };
 
     Ok(vm.arena.alloc(Val::Int(n as i64)))
 }
@@ -983,11 +1216,10 @@ pub fn php_gzclose(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .downcast_ref::<GzFile>()
         .ok_or("gzclose(): Invalid resource")?;
 
-    gz_file
-        .inner
-        .borrow_mut()
-        .close()
-        .map_err(|e| e.to_string())?;
+    let closed = gz_file.inner.borrow_mut().close();
+    if let Err(e) = closed {
+        return warn_false(vm, "gzclose", e);
+    }
 
     Ok(vm.arena.alloc(Val::Bool(true)))
 }
@@ -1102,13 +1334,12 @@ pub fn php_gzgets(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         return Err("gzgets() expects 1 or 2 parameters".into());
     }
 
-    let length = if args.len() >= 2 {
-        match &vm.arena.get(args[1]).value {
-            Val::Int(i) => *i as usize,
-            _ => 1024,
+    let length = match args.get(1).map(|h| &vm.arena.get(*h).value) {
+        Some(Val::Int(i)) if *i > 0 => *i as usize,
+        Some(Val::Int(_)) => {
+            return Err("gzgets(): Argument #2 ($length) must be greater than 0".into());
         }
-    } else {
-        1024
+        _ => 1024,
     };
 
     let resource = match &vm.arena.get(args[0]).value {
@@ -1120,11 +1351,16 @@ pub fn php_gzgets(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .downcast_ref::<GzFile>()
         .ok_or("gzgets(): Invalid resource")?;
 
-    let line = gz_file
-        .inner
-        .borrow_mut()
-        .gets(length)
-        .map_err(|e| e.to_string())?;
+    // Room for the terminating NUL only: nothing can be read, as with fgets()
+    if length == 1 {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
+    let read = gz_file.inner.borrow_mut().gets(length);
+    let line = match read {
+        Ok(line) => line,
+        Err(e) => return warn_false(vm, "gzgets", e),
+    };
     if line.is_empty() && gz_file.inner.borrow_mut().eof() {
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
@@ -1147,12 +1383,16 @@ pub fn php_gzgetc(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
         .downcast_ref::<GzFile>()
         .ok_or("gzgetc(): Invalid resource")?;
 
+    if gz_file.inner.borrow_mut().eof() {
+        return Ok(vm.arena.alloc(Val::Bool(false)));
+    }
+
     let mut byte = [0u8; 1];
-    let n = gz_file
-        .inner
-        .borrow_mut()
-        .read(&mut byte)
-        .map_err(|e| e.to_string())?;
+    let read = gz_file.inner.borrow_mut().read(&mut byte);
+    let n = match read {
+        Ok(n) => n,
+        Err(e) => return warn_false(vm, "gzgetc", e),
+    };
     if n == 0 {
         return Ok(vm.arena.alloc(Val::Bool(false)));
     }
@@ -1178,11 +1418,11 @@ pub fn php_gzpassthru(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     let mut total = 0;
     let mut buf = [0u8; 8192];
     loop {
-        let n = gz_file
-            .inner
-            .borrow_mut()
-            .read(&mut buf)
-            .map_err(|e| e.to_string())?;
+        let read = gz_file.inner.borrow_mut().read(&mut buf);
+        let n = match read {
+            Ok(n) => n,
+            Err(e) => return warn_false(vm, "gzpassthru", e),
+        };
         if n == 0 {
             break;
         }
@@ -1200,7 +1440,11 @@ pub fn php_readgzfile(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     }
 
     let mode_handle = vm.arena.alloc(Val::String(Rc::new(b"rb".to_vec())));
-    let gz_handle = php_gzopen(vm, &[args[0], mode_handle])?;
+    let gz_args = match args.get(1) {
+        Some(&use_include_path) => vec![args[0], mode_handle, use_include_path],
+        None => vec![args[0], mode_handle],
+    };
+    let gz_handle = php_gzopen(vm, &gz_args)?;
 
     if let Val::Bool(false) = vm.arena.get(gz_handle).value {
         return Ok(gz_handle);
@@ -1219,7 +1463,11 @@ pub fn php_gzfile(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
     }
 
     let mode_handle = vm.arena.alloc(Val::String(Rc::new(b"rb".to_vec())));
-    let gz_handle = php_gzopen(vm, &[args[0], mode_handle])?;
+    let gz_args = match args.get(1) {
+        Some(&use_include_path) => vec![args[0], mode_handle, use_include_path],
+        None => vec![args[0], mode_handle],
+    };
+    let gz_handle = php_gzopen(vm, &gz_args)?;
 
     if let Val::Bool(false) = vm.arena.get(gz_handle).value {
         return Ok(gz_handle);
@@ -1252,5 +1500,8 @@ pub fn php_ob_gzhandler(_vm: &mut VM, args: &[Handle]) -> Result<Handle, String>
 
 /// zlib_get_coding_type(): string|false
 pub fn php_zlib_get_coding_type(vm: &mut VM, _args: &[Handle]) -> Result<Handle, String> {
+    if vm.output_compression_active() {
+        return Ok(vm.arena.alloc(Val::String(Rc::new(b"gzip".to_vec()))));
+    }
     Ok(vm.arena.alloc(Val::Bool(false)))
 }
@@ -83,4 +83,5 @@ pub struct CodeChunk {
     pub constants: Vec<Val>,       // Literals (Ints, Strings)
     pub lines: Vec<u32>,           // Line numbers for debug
     pub catch_table: Vec<CatchEntry>,
+    pub compile_warnings: Vec<(u32, String)>, // (line, message), reported when the file runs
 }
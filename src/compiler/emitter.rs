@@ -1,77 +1,89 @@
 use crate::compiler::chunk::{CatchEntry, CodeChunk, FuncParam, ReturnType, UserFunc};
 use crate::core::interner::Interner;
-use crate::core::value::{Symbol, Val, Visibility};
+use crate::core::value::{ArrayKey, Symbol, Val, Visibility};
+use crate::parser::ast::visitor::{Visitor, walk_expr};
 use crate::parser::ast::{
-    AssignOp, AttributeGroup, BinaryOp, CastKind, ClassMember, Expr, IncludeKind, MagicConstKind,
-    Name, Stmt, StmtId, TraitAdaptation, Type, UnaryOp, UseKind,
+    Arg, AssignOp, AttributeGroup, BinaryOp, CastKind, ClassMember, Expr, ExprId, IncludeKind,
+    MagicConstKind, Name, Param, Stmt, StmtId, TraitAdaptation, Type, UnaryOp, UseKind,
 };
+use crate::parser::lexer::escape::{QuoteKind, Unescaped, unescape, unescape_single_quoted};
 use crate::parser::lexer::token::{Token, TokenKind};
-use crate::parser::span::Span;
+use crate::parser::span::{LineIndex, Span};
 use crate::vm::opcode::OpCode;
-use std::cell::RefCell;
+use std::cell::{OnceCell, RefCell};
 use std::collections::HashMap;
 use std::path::Path;
 use std::rc::Rc;
 
-/// Unescape a double-quoted string, processing escape sequences like \n, \r, \t, etc.
-fn unescape_string(s: &[u8]) -> Vec<u8> {
-    let mut result = Vec::new();
-    let mut i = 0;
-    while i < s.len() {
-        if s[i] == b'\\' && i + 1 < s.len() {
-            match s[i + 1] {
-                b'n' => result.push(b'\n'),
-                b'r' => result.push(b'\r'),
-                b't' => result.push(b'\t'),
-                b'\\' => result.push(b'\\'),
-                b'$' => result.push(b'$'),
-                b'"' => result.push(b'"'),
-                b'\'' => result.push(b'\''),
-                b'v' => result.push(b'\x0B'), // vertical tab
-                b'e' => result.push(b'\x1B'), // escape
-                b'f' => result.push(b'\x0C'), // form feed
-                b'0' => result.push(b'\0'),   // null byte
-                // Hexadecimal: \xHH
-                b'x' if i + 3 < s.len() => {
-                    if let (Some(h1), Some(h2)) = (
-                        char::from(s[i + 2]).to_digit(16),
-                        char::from(s[i + 3]).to_digit(16),
-                    ) {
-                        result.push((h1 * 16 + h2) as u8);
-                        i += 2; // Skip the two hex digits
-                    } else {
-                        result.push(b'\\');
-                        result.push(s[i + 1]);
-                    }
-                }
-                // Octal: \nnn (up to 3 digits)
-                b'0'..=b'7' => {
-                    let mut octal_val = s[i + 1] - b'0';
-                    let mut consumed = 1;
-                    if i + 2 < s.len() && (b'0'..=b'7').contains(&s[i + 2]) {
-                        octal_val = octal_val * 8 + (s[i + 2] - b'0');
-                        consumed = 2;
-                        if i + 3 < s.len() && (b'0'..=b'7').contains(&s[i + 3]) {
-                            octal_val = octal_val * 8 + (s[i + 3] - b'0');
-                            consumed = 3;
-                        }
-                    }
-                    result.push(octal_val);
-                    i += consumed;
-                }
-                _ => {
-                    // Unknown escape, keep both characters
-                    result.push(b'\\');
-                    result.push(s[i + 1]);
+/// Decode the source text of a string literal token into its value bytes
+fn string_literal_value(value: &[u8]) -> Unescaped {
+    // Binary string prefix: b"..." and b'...'
+    let value = match value {
+        [b'b' | b'B', b'"' | b'\'', ..] => &value[1..],
+        _ => value,
+    };
+    let quoted = |q: u8| value.len() >= 2 && value[0] == q && value[value.len() - 1] == q;
+    if quoted(b'\'') {
+        Unescaped {
+            bytes: unescape_single_quoted(&value[1..value.len() - 1]),
+            warnings: Vec::new(),
+        }
+    } else {
+        let inner = if quoted(b'"') {
+            &value[1..value.len() - 1]
+        } else {
+            value
+        };
+        // The parser already rejected invalid \u{...} escapes
+        unescape(inner, QuoteKind::Double).unwrap_or_else(|_| Unescaped {
+            bytes: inner.to_vec(),
+            warnings: Vec::new(),
+        })
+    }
+}
+
+/// Body of a closure: a statement block, or an arrow function's expression
+#[derive(Clone, Copy)]
+enum ClosureBody<'a, 'ast> {
+    Block(&'a [StmtId<'ast>]),
+    Expr(ExprId<'ast>),
+}
+
+/// Variables an arrow function never captures from the enclosing scope
+const ARROW_UNCAPTURED: &[&[u8]] = &[
+    b"this",
+    b"GLOBALS",
+    b"_SERVER",
+    b"_GET",
+    b"_POST",
+    b"_FILES",
+    b"_COOKIE",
+    b"_SESSION",
+    b"_REQUEST",
+    b"_ENV",
+];
+
+/// Collects the variables referenced by an arrow function body. Nested
+/// closures only contribute their `use` list; their bodies have their own scope.
+struct ArrowCaptureCollector<'src> {
+    source: &'src [u8],
+    names: Vec<Span>,
+}
+
+impl<'ast> Visitor<'ast> for ArrowCaptureCollector<'_> {
+    fn visit_expr(&mut self, expr: ExprId<'ast>) {
+        match expr {
+            Expr::Variable { name, .. } => {
+                if self.source.get(name.start) == Some(&b'$') {
+                    self.names.push(*name);
                 }
             }
-            i += 2;
-        } else {
-            result.push(s[i]);
-            i += 1;
+            Expr::Closure { uses, .. } => {
+                self.names.extend(uses.iter().map(|u| u.var.span));
+            }
+            _ => walk_expr(self, expr),
         }
     }
-    result
 }
 
 struct LoopInfo {
@@ -92,6 +104,8 @@ struct TryFinallyInfo {
 pub struct Emitter<'src> {
     chunk: CodeChunk,
     source: &'src [u8],
+    /// Line starts of `source`, built on first use and shared with nested emitters
+    line_index: Rc<OnceCell<LineIndex>>,
     interner: &'src mut Interner,
     loop_stack: Vec<LoopInfo>,
     try_finally_stack: Vec<TryFinallyInfo>,
@@ -115,6 +129,7 @@ impl<'src> Emitter<'src> {
         Self {
             chunk: CodeChunk::default(),
             source,
+            line_index: Rc::default(),
             interner,
             loop_stack: Vec::new(),
             try_finally_stack: Vec::new(),
@@ -438,11 +453,25 @@ impl<'src> Emitter<'src> {
     }
 
     fn set_current_line(&mut self, span: Span) {
-        if let Some(info) = span.line_info(self.source) {
-            self.current_line = info.line as u32;
+        if span.start <= self.source.len() {
+            self.current_line = self.line_at(span.start);
         }
     }
 
+    /// 1-based line number of a byte offset in the source
+    fn line_at(&self, offset: usize) -> u32 {
+        self.line_index
+            .get_or_init(|| LineIndex::new(self.source))
+            .line(offset) as u32
+    }
+
+    fn add_compile_warnings(&mut self, warnings: Vec<String>) {
+        let line = self.current_line;
+        self.chunk
+            .compile_warnings
+            .extend(warnings.into_iter().map(|warning| (line, warning)));
+    }
+
     fn push_op(&mut self, op: OpCode) {
         self.chunk.code.push(op);
         self.chunk.lines.push(self.current_line);
@@ -489,10 +518,140 @@ impl<'src> Emitter<'src> {
         };
         self.chunk.name = chunk_name;
         self.chunk.file_path = self.file_path.clone();
+        // Hoisted declarations compile first; report in source order
+        self.chunk.compile_warnings.sort_by_key(|(line, _)| *line);
 
         (self.chunk, self.is_generator)
     }
 
+    /// Compile a closure or arrow function and emit the Closure opcode that
+    /// builds it, loading each captured variable onto the stack first
+    #[allow(clippy::too_many_arguments)]
+    fn emit_closure(
+        &mut self,
+        params: &[Param],
+        captures: &[(Span, bool)],
+        body: ClosureBody,
+        by_ref: bool,
+        is_static: bool,
+        return_type: Option<&Type>,
+        span: Span,
+        close_brace_span: Option<Span>,
+    ) {
+        // 1. Create emitter with inherited context (closures inherit context)
+        let closure_sym = self.interner.intern(b"{closure}");
+        let mut func_emitter = Emitter::new(self.source, self.interner);
+        func_emitter.line_index = Rc::clone(&self.line_index);
+        func_emitter.file_path = self.file_path.clone();
+        func_emitter.current_class = self.current_class;
+        func_emitter.current_function = Some(closure_sym);
+        func_emitter.current_namespace = self.current_namespace;
+        func_emitter.use_aliases = self.use_aliases.clone();
+        func_emitter.chunk.strict_types = self.chunk.strict_types;
+
+        // 2. Process params
+        let mut param_syms = Vec::new();
+        for (i, param) in params.iter().enumerate() {
+            let p_name = func_emitter.get_text(param.name.span);
+            if p_name.starts_with(b"$") {
+                let sym = func_emitter.interner.intern(&p_name[1..]);
+                let param_type = param.ty.and_then(|ty| func_emitter.convert_type(ty));
+                let default_value = if param.variadic {
+                    None
+                } else {
+                    param
+                        .default
+                        .map(|expr| func_emitter.eval_constant_expr(expr))
+                };
+
+                param_syms.push(FuncParam {
+                    name: sym,
+                    by_ref: param.by_ref,
+                    param_type,
+                    is_variadic: param.variadic,
+                    default_value,
+                });
+
+                if param.variadic {
+                    func_emitter.chunk.code.push(OpCode::RecvVariadic(i as u32));
+                } else if let Some(default_expr) = param.default {
+                    let val = func_emitter.eval_constant_expr(default_expr);
+                    let idx = func_emitter.add_constant(val);
+                    func_emitter
+                        .chunk
+                        .code
+                        .push(OpCode::RecvInit(i as u32, idx as u16));
+                } else {
+                    func_emitter.push_op(OpCode::Recv(i as u32));
+                }
+            }
+        }
+
+        let (mut func_chunk, is_generator) = match body {
+            ClosureBody::Block(stmts) => func_emitter.compile(stmts),
+            ClosureBody::Expr(expr) => {
+                func_emitter.emit_expr(expr);
+                func_emitter.push_op(OpCode::Return);
+                func_emitter.compile(&[])
+            }
+        };
+        func_chunk.returns_ref = by_ref;
+        self.chunk
+            .compile_warnings
+            .append(&mut func_chunk.compile_warnings);
+
+        // 3. Push the captured variables
+        let implicit = matches!(body, ClosureBody::Expr(_));
+        if implicit {
+            // Arrow functions skip outer variables that are not defined
+            self.push_op(OpCode::SuppressUndefined(true));
+        }
+        let mut use_syms = Vec::new();
+        for (var_span, by_ref) in captures {
+            let u_name = self.get_text(*var_span);
+            if u_name.starts_with(b"$") {
+                let sym = self.interner.intern(&u_name[1..]);
+                use_syms.push(sym);
+
+                if *by_ref {
+                    self.push_op(OpCode::LoadRef(sym));
+                } else {
+                    // Emit code to push the captured variable onto the stack
+                    self.push_op(OpCode::LoadVar(sym));
+                    self.push_op(OpCode::Copy);
+                }
+            }
+        }
+        if implicit {
+            self.push_op(OpCode::SuppressUndefined(false));
+        }
+
+        // Convert return type
+        let ret_type = return_type.and_then(|rt| self.convert_type(rt));
+
+        let start_line = Some(self.line_at(span.start));
+        let end_line = close_brace_span.map(|s| self.line_at(s.start));
+
+        let user_func = UserFunc {
+            params: param_syms,
+            uses: use_syms.clone(),
+            chunk: Rc::new(func_chunk),
+            is_static,
+            is_generator,
+            statics: Rc::new(RefCell::new(HashMap::new())),
+            return_type: ret_type,
+            start_line,
+            end_line,
+        };
+
+        let func_res = Val::Resource(Rc::new(user_func));
+        let const_idx = self.add_constant(func_res);
+
+        self.chunk
+            .code
+            .push(OpCode::Closure(const_idx as u32, use_syms.len() as u32));
+    }
+
     fn emit_members(&mut self, class_sym: Symbol, members: &[ClassMember]) {
         for member in members {
             match member {
@@ -536,6 +695,7 @@ impl<'src> Emitter<'src> {
 
                     // 2. Create emitter with inherited context
                     let mut method_emitter = Emitter::new(self.source, self.interner);
+                    method_emitter.line_index = Rc::clone(&self.line_index);
                     method_emitter.file_path = self.file_path.clone();
                     method_emitter.current_class = Some(class_sym);
                     method_emitter.current_namespace = self.current_namespace;
@@ -592,11 +752,13 @@ impl<'src> Emitter<'src> {
                         }
                     }
 
-                    let (method_chunk, is_generator) = method_emitter.compile(body);
+                    let (mut method_chunk, is_generator) = method_emitter.compile(body);
+                    self.chunk
+                        .compile_warnings
+                        .append(&mut method_chunk.compile_warnings);
 
-                    let start_line = span.line_info(self.source).map(|li| li.line as u32);
-                    let end_line = close_brace_span
-                        .and_then(|s| s.line_info(self.source).map(|li| li.line as u32));
+                    let start_line = Some(self.line_at(span.start));
+                    let end_line = close_brace_span.map(|s| self.line_at(s.start));
 
                     // Convert return type
                     let ret_type = return_type.and_then(|rt| self.convert_type(rt));
@@ -1248,6 +1410,7 @@ impl<'src> Emitter<'src> {
 
                 // 2. Create emitter with inherited context
                 let mut func_emitter = Emitter::new(self.source, self.interner);
+                func_emitter.line_index = Rc::clone(&self.line_index);
                 func_emitter.file_path = self.file_path.clone();
                 func_emitter.current_function = Some(func_sym);
                 func_emitter.current_namespace = self.current_namespace;
@@ -1293,13 +1456,15 @@ impl<'src> Emitter<'src> {
 
                 let (mut func_chunk, is_generator) = func_emitter.compile(body);
                 func_chunk.returns_ref = *by_ref;
+                self.chunk
+                    .compile_warnings
+                    .append(&mut func_chunk.compile_warnings);
 
                 // Convert return type
                 let ret_type = return_type.and_then(|rt| self.convert_type(rt));
 
-                let start_line = span.line_info(self.source).map(|li| li.line as u32);
-                let end_line = close_brace_span
-                    .and_then(|s| s.line_info(self.source).map(|li| li.line as u32));
+                let start_line = Some(self.line_at(span.start));
+                let end_line = close_brace_span.map(|s| self.line_at(s.start));
 
                 let user_func = UserFunc {
                     params: param_syms,
@@ -1351,12 +1516,8 @@ impl<'src> Emitter<'src> {
                     .code
                     .push(OpCode::DefClass(class_sym, parent_sym));
 
-                let start_line = name
-                    .span
-                    .line_info(self.source)
-                    .map(|info| info.line as u32);
-                let end_line = close_brace_span
-                    .and_then(|span| span.line_info(self.source).map(|info| info.line as u32));
+                let start_line = Some(self.line_at(name.span.start));
+                let end_line = close_brace_span.map(|span| self.line_at(span.start));
                 self.chunk
                     .code
                     .push(OpCode::SetClassLines(class_sym, start_line, end_line));
@@ -1409,12 +1570,8 @@ impl<'src> Emitter<'src> {
 
                 self.push_op(OpCode::DefInterface(sym));
 
-                let start_line = name
-                    .span
-                    .line_info(self.source)
-                    .map(|info| info.line as u32);
-                let end_line = close_brace_span
-                    .and_then(|span| span.line_info(self.source).map(|info| info.line as u32));
+                let start_line = Some(self.line_at(name.span.start));
+                let end_line = close_brace_span.map(|span| self.line_at(span.start));
                 self.chunk
                     .code
                     .push(OpCode::SetClassLines(sym, start_line, end_line));
@@ -1450,12 +1607,8 @@ impl<'src> Emitter<'src> {
 
                 self.push_op(OpCode::DefTrait(sym));
 
-                let start_line = name
-                    .span
-                    .line_info(self.source)
-                    .map(|info| info.line as u32);
-                let end_line = close_brace_span
-                    .and_then(|span| span.line_info(self.source).map(|info| info.line as u32));
+                let start_line = Some(self.line_at(name.span.start));
+                let end_line = close_brace_span.map(|span| self.line_at(span.start));
                 self.chunk
                     .code
                     .push(OpCode::SetClassLines(sym, start_line, end_line));
@@ -1950,42 +2103,7 @@ impl<'src> Emitter<'src> {
                 Some(Val::Int(i))
             }
             Expr::String { value, .. } => {
-                let s = if value.len() >= 2 {
-                    let first = value[0];
-                    let last = value[value.len() - 1];
-                    if first == b'"' && last == b'"' {
-                        let inner = &value[1..value.len() - 1];
-                        unescape_string(inner)
-                    } else if first == b'\'' && last == b'\'' {
-                        let inner = &value[1..value.len() - 1];
-                        let mut result = Vec::new();
-                        let mut i = 0;
-                        while i < inner.len() {
-                            if inner[i] == b'\\' && i + 1 < inner.len() {
-                                if inner[i + 1] == b'\'' || inner[i + 1] == b'\\' {
-                                    result.push(inner[i + 1]);
-                                    i += 2;
-                                } else {
-                                    result.push(inner[i]);
-                                    i += 1;
-                                }
-                            } else {
-                                result.push(inner[i]);
-                                i += 1;
-                            }
-                        }
-                        result
-                    } else {
-                        // No quotes - this is from string interpolation (EncapsedAndWhitespace)
-                        // These strings need unescaping too
-                        unescape_string(value)
-                    }
-                } else if !value.is_empty() {
-                    // Short string without quotes - also from interpolation
-                    unescape_string(value)
-                } else {
-                    value.to_vec()
-                };
+                let s = string_literal_value(value).bytes;
                 Some(Val::String(s.into()))
             }
             Expr::Boolean { value, .. } => Some(Val::Bool(*value)),
@@ -2012,7 +2130,15 @@ impl<'src> Emitter<'src> {
                                 }
                                 ConstArrayKey::Int(i)
                             }
-                            Some(Val::String(s)) => ConstArrayKey::Str(s),
+                            Some(Val::String(s)) => match ArrayKey::parse_int_key(&s) {
+                                Some(i) => {
+                                    if i >= next_index {
+                                        next_index = i.saturating_add(1);
+                                    }
+                                    ConstArrayKey::Int(i)
+                                }
+                                None => ConstArrayKey::Str(s),
+                            },
                             Some(Val::Float(f)) => {
                                 let i = f as i64;
                                 if i >= next_index {
@@ -2075,6 +2201,60 @@ impl<'src> Emitter<'src> {
         }
     }
 
+    /// Call `method` on the object on top of the stack
+    fn emit_method_call(&mut self, method: &Expr, args: &[Arg]) {
+        if args.iter().any(|arg| arg.unpack || arg.name.is_some()) {
+            // Unpacked and named arguments are bound when the pending call runs
+            match method {
+                Expr::Variable { span, .. } if !self.get_text(*span).starts_with(b"$") => {
+                    let name = self.get_text(*span).to_vec();
+                    let idx = self.add_constant(Val::String(name.into()));
+                    self.push_op(OpCode::Const(idx as u16));
+                }
+                _ => self.emit_expr(method),
+            }
+            self.push_op(OpCode::InitMethodCall);
+            for arg in args {
+                self.emit_expr(arg.value);
+                if arg.unpack {
+                    self.push_op(OpCode::SendUnpack);
+                } else if let Some(name) = arg.name {
+                    let sym = self.interner.intern(self.get_text(name.span));
+                    self.push_op(OpCode::SendNamed(sym));
+                } else {
+                    self.push_op(OpCode::SendValEx);
+                }
+            }
+            self.push_op(OpCode::DoFcall);
+            return;
+        }
+
+        if let Expr::Variable { span, .. } = method {
+            let name = self.get_text(*span);
+            if !name.starts_with(b"$") {
+                for arg in args {
+                    self.emit_expr(arg.value);
+                }
+                let sym = self.interner.intern(name);
+                self.push_op(OpCode::CallMethod(sym, args.len() as u8));
+            } else {
+                // Dynamic method call: $obj->$method()
+                self.emit_expr(method);
+                for arg in args {
+                    self.emit_expr(arg.value);
+                }
+                self.push_op(OpCode::CallMethodDynamic(args.len() as u8));
+            }
+        } else {
+            // Dynamic method call with expression: $obj->{$expr}()
+            self.emit_expr(method);
+            for arg in args {
+                self.emit_expr(arg.value);
+            }
+            self.push_op(OpCode::CallMethodDynamic(args.len() as u8));
+        }
+    }
+
     fn emit_expr(&mut self, expr: &Expr) {
         self.set_current_line(expr.span());
         match expr {
@@ -2091,54 +2271,32 @@ impl<'src> Emitter<'src> {
                 self.push_op(OpCode::Const(idx as u16));
             }
             Expr::String { value, .. } => {
-                let s = if value.len() >= 2 {
-                    let first = value[0];
-                    let last = value[value.len() - 1];
-                    if first == b'"' && last == b'"' {
-                        // Double-quoted string: unescape escape sequences
-                        let inner = &value[1..value.len() - 1];
-                        unescape_string(inner)
-                    } else if first == b'\'' && last == b'\'' {
-                        // Single-quoted string: no escape processing (except \' and \\)
-                        let inner = &value[1..value.len() - 1];
-                        let mut result = Vec::new();
-                        let mut i = 0;
-                        while i < inner.len() {
-                            if inner[i] == b'\\' && i + 1 < inner.len() {
-                                if inner[i + 1] == b'\'' || inner[i + 1] == b'\\' {
-                                    result.push(inner[i + 1]);
-                                    i += 2;
-                                } else {
-                                    result.push(inner[i]);
-                                    i += 1;
-                                }
-                            } else {
-                                result.push(inner[i]);
-                                i += 1;
-                            }
-                        }
-                        result
-                    } else {
-                        // No quotes - this is from string interpolation (EncapsedAndWhitespace)
-                        // These strings need unescaping too
-                        unescape_string(value)
-                    }
-                } else if !value.is_empty() {
-                    // Short string without quotes - also from interpolation
-                    unescape_string(value)
-                } else {
-                    value.to_vec()
-                };
+                let Unescaped { bytes: s, warnings } = string_literal_value(value);
+                self.add_compile_warnings(warnings);
                 let idx = self.add_constant(Val::String(s.into()));
                 self.push_op(OpCode::Const(idx as u16));
             }
-            Expr::InterpolatedString { parts, .. } => {
+            Expr::InterpolatedString { parts, span } => {
                 if parts.is_empty() {
                     let idx = self.add_constant(Val::String(Vec::<u8>::new().into()));
                     self.push_op(OpCode::Const(idx as u16));
                 } else {
+                    let kind = QuoteKind::of_opener(&self.source[span.start..]);
                     for (i, part) in parts.iter().enumerate() {
-                        self.emit_expr(*part);
+                        if let Expr::String { value, .. } = part {
+                            // Literal body text: decode escapes, except in nowdocs
+                            let bytes = match kind.map(|kind| unescape(value, kind)) {
+                                Some(Ok(decoded)) => {
+                                    self.add_compile_warnings(decoded.warnings);
+                                    decoded.bytes
+                                }
+                                _ => value.to_vec(),
+                            };
+                            let idx = self.add_constant(Val::String(bytes.into()));
+                            self.push_op(OpCode::Const(idx as u16));
+                        } else {
+                            self.emit_expr(part);
+                        }
                         if i > 0 {
                             self.push_op(OpCode::Concat);
                         }
@@ -2254,6 +2412,7 @@ impl<'src> Emitter<'src> {
                 self.emit_expr(condition);
 
                 let mut end_jumps = Vec::new();
+                let mut default_arm = None;
 
                 for arm in *arms {
                     if let Some(conds) = arm.conditions {
@@ -2290,16 +2449,19 @@ impl<'src> Emitter<'src> {
                         // Patch skip_body_idx to here (next arm)
                         self.patch_jump(skip_body_idx, self.chunk.code.len());
                     } else {
-                        // Default arm
-                        self.push_op(OpCode::Pop); // Pop condition
-                        self.emit_expr(arm.body);
-                        end_jumps.push(self.chunk.code.len());
-                        self.push_op(OpCode::Jmp(0));
+                        // The default arm applies only once every other arm
+                        // failed, wherever it appears
+                        default_arm = Some(arm);
                     }
                 }
 
-                // No match found
-                self.push_op(OpCode::MatchError);
+                if let Some(arm) = default_arm {
+                    self.push_op(OpCode::Pop); // Pop condition
+                    self.emit_expr(arm.body);
+                } else {
+                    // No match found
+                    self.push_op(OpCode::MatchError);
+                }
 
                 let end_label = self.chunk.code.len();
                 for idx in end_jumps {
@@ -2312,6 +2474,11 @@ impl<'src> Emitter<'src> {
                 let idx = self.add_constant(Val::Int(1));
                 self.push_op(OpCode::Const(idx as u16));
             }
+            Expr::Throw { expr, .. } => {
+                // Control never continues past the throw, so no value is pushed
+                self.emit_expr(expr);
+                self.push_op(OpCode::Throw);
+            }
             Expr::Include { expr, kind, .. } => {
                 self.emit_expr(expr);
                 let include_type = match kind {
@@ -2831,7 +2998,6 @@ impl<'src> Emitter<'src> {
                 }
             }
             Expr::Closure {
-                attributes: _,
                 params,
                 uses,
                 body,
@@ -2842,122 +3008,60 @@ impl<'src> Emitter<'src> {
                 close_brace_span,
                 ..
             } => {
-                // 1. Collect param info
-                struct ParamInfo<'a> {
-                    name_span: crate::parser::span::Span,
-                    by_ref: bool,
-                    ty: Option<&'a Type<'a>>,
-                    default: Option<&'a Expr<'a>>,
-                    variadic: bool,
-                }
-
-                let mut param_infos = Vec::new();
-                for param in *params {
-                    param_infos.push(ParamInfo {
-                        name_span: param.name.span,
-                        by_ref: param.by_ref,
-                        ty: param.ty,
-                        default: param.default.as_ref().map(|e| *e),
-                        variadic: param.variadic,
-                    });
-                }
-
-                // 2. Create emitter with inherited context (closures inherit context)
-                let closure_sym = self.interner.intern(b"{closure}");
-                let mut func_emitter = Emitter::new(self.source, self.interner);
-                func_emitter.file_path = self.file_path.clone();
-                func_emitter.current_class = self.current_class;
-                func_emitter.current_function = Some(closure_sym);
-                func_emitter.current_namespace = self.current_namespace;
-                func_emitter.use_aliases = self.use_aliases.clone();
-                func_emitter.chunk.strict_types = self.chunk.strict_types;
-
-                // 3. Process params
-                let mut param_syms = Vec::new();
-                for (i, info) in param_infos.iter().enumerate() {
-                    let p_name = func_emitter.get_text(info.name_span);
-                    if p_name.starts_with(b"$") {
-                        let sym = func_emitter.interner.intern(&p_name[1..]);
-                        let param_type = info.ty.and_then(|ty| func_emitter.convert_type(ty));
-                        let default_value = if info.variadic {
-                            None
-                        } else {
-                            info.default
-                                .map(|expr| func_emitter.eval_constant_expr(expr))
-                        };
-
-                        param_syms.push(FuncParam {
-                            name: sym,
-                            by_ref: info.by_ref,
-                            param_type,
-                            is_variadic: info.variadic,
-                            default_value,
-                        });
-
-                        if info.variadic {
-                            func_emitter.chunk.code.push(OpCode::RecvVariadic(i as u32));
-                        } else if let Some(default_expr) = info.default {
-                            let val = func_emitter.eval_constant_expr(default_expr);
-                            let idx = func_emitter.add_constant(val);
-                            func_emitter
-                                .chunk
-                                .code
-                                .push(OpCode::RecvInit(i as u32, idx as u16));
-                        } else {
-                            func_emitter.push_op(OpCode::Recv(i as u32));
-                        }
-                    }
-                }
-
-                let (mut func_chunk, is_generator) = func_emitter.compile(body);
-                func_chunk.returns_ref = *by_ref;
-
-                // Extract uses
-                let mut use_syms = Vec::new();
-                for use_var in *uses {
-                    let u_name = self.get_text(use_var.var.span);
-                    if u_name.starts_with(b"$") {
-                        let sym = self.interner.intern(&u_name[1..]);
-                        use_syms.push(sym);
-
-                        if use_var.by_ref {
-                            self.push_op(OpCode::LoadRef(sym));
-                        } else {
-                            // Emit code to push the captured variable onto the stack
-                            self.push_op(OpCode::LoadVar(sym));
-                            self.push_op(OpCode::Copy);
-                        }
+                let captures: Vec<(Span, bool)> =
+                    uses.iter().map(|u| (u.var.span, u.by_ref)).collect();
+                self.emit_closure(
+                    params,
+                    &captures,
+                    ClosureBody::Block(body),
+                    *by_ref,
+                    *is_static,
+                    *return_type,
+                    *span,
+                    *close_brace_span,
+                );
+            }
+            Expr::ArrowFunction {
+                params,
+                expr,
+                by_ref,
+                is_static,
+                return_type,
+                span,
+                ..
+            } => {
+                // Arrow functions capture every outer variable their body uses, by value
+                let mut collector = ArrowCaptureCollector {
+                    source: self.source,
+                    names: Vec::new(),
+                };
+                collector.visit_expr(expr);
+                let param_names: Vec<&[u8]> =
+                    params.iter().map(|p| self.get_text(p.name.span)).collect();
+                let mut captures: Vec<(Span, bool)> = Vec::new();
+                for span in collector.names {
+                    let name = self.get_text(span);
+                    if param_names.contains(&name)
+                        || ARROW_UNCAPTURED.contains(&&name[1..])
+                        || captures.iter().any(|(s, _)| self.get_text(*s) == name)
+                    {
+                        continue;
                     }
+                    captures.push((span, false));
                 }
-
-                // Convert return type
-                let ret_type = return_type.and_then(|rt| self.convert_type(rt));
-
-                let start_line = span.line_info(self.source).map(|li| li.line as u32);
-                let end_line = close_brace_span
-                    .and_then(|s| s.line_info(self.source).map(|li| li.line as u32));
-
-                let user_func = UserFunc {
-                    params: param_syms,
-                    uses: use_syms.clone(),
-                    chunk: Rc::new(func_chunk),
-                    is_static: *is_static,
-                    is_generator,
-                    statics: Rc::new(RefCell::new(HashMap::new())),
-                    return_type: ret_type,
-                    start_line,
-                    end_line,
-                };
-
-                let func_res = Val::Resource(Rc::new(user_func));
-                let const_idx = self.add_constant(func_res);
-
-                self.chunk
-                    .code
-                    .push(OpCode::Closure(const_idx as u32, use_syms.len() as u32));
+                self.emit_closure(
+                    params,
+                    &captures,
+                    ClosureBody::Expr(expr),
+                    *by_ref,
+                    *is_static,
+                    *return_type,
+                    *span,
+                    Some(Span::new(span.end.saturating_sub(1), span.end)),
+                );
             }
             Expr::Call { func, args, .. } => {
-                let has_unpack = args.iter().any(|arg| arg.unpack);
+                let has_unpack = args.iter().any(|arg| arg.unpack || arg.name.is_some());
 
                 match func {
                     Expr::Variable { span, .. } => {
@@ -2978,6 +3082,9 @@ impl<'src> Emitter<'src> {
                         self.emit_expr(&arg.value);
                         if arg.unpack {
                             self.push_op(OpCode::SendUnpack);
+                        } else if let Some(name) = arg.name {
+                            let sym = self.interner.intern(self.get_text(name.span));
+                            self.push_op(OpCode::SendNamed(sym));
                         } else {
                             self.push_op(OpCode::SendValEx);
                         }
@@ -3064,8 +3171,8 @@ impl<'src> Emitter<'src> {
                         .push(OpCode::DefClass(class_sym, parent_sym));
 
                     // Set class line information
-                    let start_line = span.line_info(self.source).map(|info| info.line as u32);
-                    let end_line = span.line_info(self.source).map(|info| info.line as u32);
+                    let start_line = Some(self.line_at(span.start));
+                    let end_line = Some(self.line_at(span.start));
                     self.chunk
                         .code
                         .push(OpCode::SetClassLines(class_sym, start_line, end_line));
@@ -3151,36 +3258,7 @@ impl<'src> Emitter<'src> {
                 ..
             } => {
                 self.emit_expr(target);
-                if let Expr::Variable { span, .. } = method {
-                    let name = self.get_text(*span);
-                    if !name.starts_with(b"$") {
-                        for arg in *args {
-                            self.emit_expr(arg.value);
-                        }
-                        let sym = self.interner.intern(name);
-                        self.chunk
-                            .code
-                            .push(OpCode::CallMethod(sym, args.len() as u8));
-                    } else {
-                        // Dynamic method call: $obj->$method()
-                        self.emit_expr(method);
-                        for arg in *args {
-                            self.emit_expr(arg.value);
-                        }
-                        self.chunk
-                            .code
-                            .push(OpCode::CallMethodDynamic(args.len() as u8));
-                    }
-                } else {
-                    // Dynamic method call with expression: $obj->{$expr}()
-                    self.emit_expr(method);
-                    for arg in *args {
-                        self.emit_expr(arg.value);
-                    }
-                    self.chunk
-                        .code
-                        .push(OpCode::CallMethodDynamic(args.len() as u8));
-                }
+                self.emit_method_call(method, args);
             }
             Expr::StaticCall {
                 class,
@@ -3613,17 +3691,16 @@ impl<'src> Emitter<'src> {
                             self.push_op(OpCode::LoadVar(sym));
                         }
                     }
-                    Expr::ArrayDimFetch {
-                        array: array_var,
-                        dim,
-                        ..
-                    } => {
-                        self.emit_expr(array_var);
-                        if let Some(d) = dim {
-                            self.emit_expr(d);
-                        } else {
-                            // TODO: Handle append
-                            self.push_op(OpCode::Const(0));
+                    Expr::ArrayDimFetch { .. } => {
+                        let (base, keys) = Self::flatten_dim_fetch(var);
+                        self.emit_expr(base);
+                        for key in &keys {
+                            if let Some(k) = key {
+                                self.emit_expr(k);
+                            } else {
+                                let idx = self.add_constant(Val::AppendPlaceholder);
+                                self.push_op(OpCode::Const(idx as u16));
+                            }
                         }
 
                         let mut handled = false;
@@ -3637,14 +3714,14 @@ impl<'src> Emitter<'src> {
                         }
 
                         if !handled {
-                            self.emit_expr(expr);
+                            self.emit_expr_for_write(expr);
                             self.push_op(OpCode::MakeRef);
                         }
 
-                        self.push_op(OpCode::AssignDimRef);
+                        self.push_op(OpCode::AssignDimRef(keys.len() as u8));
 
                         // Store back the updated array if target is a variable
-                        if let Expr::Variable { span, .. } = array_var {
+                        if let Expr::Variable { span, .. } = base {
                             let name = self.get_text(*span);
                             if name.starts_with(b"$") {
                                 let sym = self.interner.intern(&name[1..]);
@@ -4143,17 +4220,7 @@ impl<'src> Emitter<'src> {
                     Val::Float(0.0)
                 }
             }
-            Expr::String { value, .. } => {
-                let s = value;
-                if s.len() >= 2
-                    && ((s[0] == b'"' && s[s.len() - 1] == b'"')
-                        || (s[0] == b'\'' && s[s.len() - 1] == b'\''))
-                {
-                    Val::String(s[1..s.len() - 1].to_vec().into())
-                } else {
-                    Val::String(s.to_vec().into())
-                }
-            }
+            Expr::String { value, .. } => Val::String(string_literal_value(value).bytes.into()),
             Expr::Boolean { value, .. } => Val::Bool(*value),
             Expr::Null { .. } => Val::Null,
             Expr::Array { items, .. } => {
@@ -4184,7 +4251,15 @@ impl<'src> Emitter<'src> {
                                     }
                                     ConstArrayKey::Int(i)
                                 }
-                                Val::String(s) => ConstArrayKey::Str(s),
+                                Val::String(s) => match ArrayKey::parse_int_key(&s) {
+                                    Some(i) => {
+                                        if i >= next_index {
+                                            next_index = i.saturating_add(1);
+                                        }
+                                        ConstArrayKey::Int(i)
+                                    }
+                                    None => ConstArrayKey::Str(s),
+                                },
                                 Val::Float(f) => {
                                     let i = f as i64;
                                     if i >= next_index {
@@ -4351,16 +4426,7 @@ impl<'src> Emitter<'src> {
 
     /// Calculate line number from byte offset (1-indexed)
     fn get_line_number(&self, offset: usize) -> i64 {
-        let mut line = 1i64;
-        for (i, &byte) in self.source.iter().enumerate() {
-            if i >= offset {
-                break;
-            }
-            if byte == b'\n' {
-                line += 1;
-            }
-        }
-        line
+        self.line_at(offset) as i64
     }
 
     /// Convert AST Type to ReturnType
@@ -15,6 +15,8 @@
 //! - gc-arena crate concepts (mark-and-sweep, incremental collection)
 //! - PHP's reference counting + cycle collector: `$PHP_SRC_PATH/Zend/zend_gc.c`
 
+use crate::builtins::pdo::StatementIterator;
+use crate::builtins::spl::{SplFixedArrayData, SplHeapData};
 use crate::core::value::{Handle, Val, Zval};
 use crate::vm::frame::{GeneratorData, GeneratorState, SubIterator};
 use std::cell::RefCell;
@@ -40,6 +42,9 @@ fn trace_call_frame(frame: &crate::vm::frame::CallFrame, tracer: &mut dyn FnMut(
     for h in frame.args.iter() {
         tracer(*h);
     }
+    for (_, h) in frame.named_args.iter() {
+        tracer(*h);
+    }
 }
 
 /// Trace all handles reachable from GeneratorData (suspended frames, yielded values, etc.).
@@ -88,6 +93,26 @@ impl Traceable for Val {
                     if let Some(gen_data) = internal.downcast_ref::<RefCell<GeneratorData>>() {
                         trace_generator_data(&gen_data.borrow(), tracer);
                     }
+                    if let Some(fixed) = internal.downcast_ref::<RefCell<SplFixedArrayData>>() {
+                        for element in &fixed.borrow().elements {
+                            tracer(*element);
+                        }
+                    }
+                    if let Some(heap) = internal.downcast_ref::<RefCell<SplHeapData>>() {
+                        for entry in &heap.borrow().entries {
+                            tracer(entry.value);
+                            if let Some(priority) = entry.priority {
+                                tracer(priority);
+                            }
+                        }
+                    }
+                    if let Some(iterator) = internal.downcast_ref::<RefCell<StatementIterator>>() {
+                        let iterator = iterator.borrow();
+                        tracer(iterator.statement);
+                        if let Some(current) = iterator.current {
+                            tracer(current);
+                        }
+                    }
                 }
             }
             Val::ConstArray(arr) => {
@@ -424,6 +449,7 @@ mod tests {
             generator: None,
             discard_return: false,
             args: smallvec::SmallVec::new(),
+            named_args: Vec::new(),
             callsite_strict_types: false,
             stack_base: None,
             pending_finally: None,
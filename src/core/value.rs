@@ -35,7 +35,9 @@ impl ArrayData {
     pub fn insert(&mut self, key: ArrayKey, value: Handle) -> Option<Handle> {
         if let ArrayKey::Int(i) = &key {
             if *i >= self.next_free {
-                self.next_free = i + 1;
+                // Clamps at PHP_INT_MAX like nNextFreeElement; next_free_index()
+                // then reports no free slot while that key is occupied
+                self.next_free = i.saturating_add(1);
             }
         }
         self.map.insert(key, value)
@@ -47,11 +49,27 @@ impl ArrayData {
         self.next_free
     }
 
+    /// Get the next auto-increment index, or None when it is already occupied
+    /// (the array holds PHP_INT_MAX)
+    /// Reference: $PHP_SRC_PATH/Zend/zend_hash.c - zend_hash_next_index_insert
+    pub fn next_free_index(&self) -> Option<i64> {
+        if self.map.contains_key(&ArrayKey::Int(self.next_free)) {
+            None
+        } else {
+            Some(self.next_free)
+        }
+    }
+
     /// Append a value with auto-incremented key
-    pub fn push(&mut self, value: Handle) {
-        let key = ArrayKey::Int(self.next_free);
-        self.next_free += 1;
-        self.map.insert(key, value);
+    /// Returns false without writing when the next index is already occupied
+    pub fn push(&mut self, value: Handle) -> bool {
+        match self.next_free_index() {
+            Some(index) => {
+                self.insert(ArrayKey::Int(index), value);
+                true
+            }
+            None => false,
+        }
     }
 }
 
@@ -62,17 +80,11 @@ impl From<IndexMap<ArrayKey, Handle>> for ArrayData {
             .keys()
             .filter_map(|k| match k {
                 ArrayKey::Int(i) => Some(*i),
-                ArrayKey::Str(s) => {
-                    // PHP also considers numeric string keys
-                    if let Ok(s_str) = std::str::from_utf8(s) {
-                        s_str.parse::<i64>().ok()
-                    } else {
-                        None
-                    }
-                }
+                // PHP also considers numeric string keys
+                ArrayKey::Str(s) => ArrayKey::parse_int_key(s),
             })
             .max()
-            .map(|i| i + 1)
+            .map(|i| i.saturating_add(1))
             .unwrap_or(0);
 
         Self {
@@ -341,6 +353,75 @@ impl Val {
     }
 }
 
+/// Format a float the way PHP does for `serialize_precision`: -1 (the default)
+/// gives the shortest digit string that round-trips, any other value rounds to
+/// that many significant digits. Exponential notation (using `exp_char`) is
+/// used when the decimal exponent is below -4 or above the digit count (17 for
+/// the round-trip form).
+/// Used by var_dump, var_export, serialize and json_encode; echo and string
+/// conversion use the display precision instead.
+/// Reference: $PHP_SRC_PATH/main/snprintf.c - php_gcvt
+pub fn format_float_serialize(f: f64, serialize_precision: i64, exp_char: char) -> String {
+    // snprintf's 'H' conversion treats a precision of 0 as FLOAT_DIGITS
+    const FLOAT_DIGITS: usize = 6;
+
+    if f.is_nan() {
+        return "NAN".to_string();
+    }
+    if f.is_infinite() {
+        return if f > 0.0 { "INF" } else { "-INF" }.to_string();
+    }
+
+    let (sci, ndigit) = match serialize_precision {
+        // `{:e}` yields the shortest round-trip digits, e.g. "-1.2345e17"
+        p if p < 0 => (format!("{:e}", f.abs()), 17),
+        p => {
+            let precision = if p == 0 { FLOAT_DIGITS } else { p as usize };
+            (format!("{:.*e}", precision - 1, f.abs()), precision as i32)
+        }
+    };
+    let (mantissa, exponent) = sci.split_once('e').unwrap_or((&sci, "0"));
+    // zend_dtoa drops trailing zeros from the rounded digits
+    let mut digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    while digits.len() > 1 && digits.ends_with('0') {
+        digits.pop();
+    }
+    let decpt = exponent.parse::<i32>().unwrap_or(0) + 1;
+
+    let mut out = String::new();
+    if f.is_sign_negative() {
+        out.push('-');
+    }
+
+    if !(-3..=ndigit).contains(&decpt) {
+        out.push_str(&digits[..1]);
+        out.push('.');
+        if digits.len() > 1 {
+            out.push_str(&digits[1..]);
+        } else {
+            out.push('0');
+        }
+        out.push(exp_char);
+        out.push(if decpt > 0 { '+' } else { '-' });
+        out.push_str(&(decpt - 1).abs().to_string());
+    } else if decpt <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat((-decpt) as usize));
+        out.push_str(&digits);
+    } else {
+        let int_len = decpt as usize;
+        if digits.len() <= int_len {
+            out.push_str(&digits);
+            out.push_str(&"0".repeat(int_len - digits.len()));
+        } else {
+            out.push_str(&digits[..int_len]);
+            out.push('.');
+            out.push_str(&digits[int_len..]);
+        }
+    }
+    out
+}
+
 #[derive(Debug, Clone)]
 pub struct ObjectData {
     // Placeholder for object data
@@ -363,6 +444,33 @@ pub enum ArrayKey {
     Str(Rc<Vec<u8>>),
 }
 
+impl ArrayKey {
+    /// Key for a string offset: canonical decimal integers become int keys,
+    /// everything else stays a string key
+    pub fn from_string(s: Rc<Vec<u8>>) -> Self {
+        match Self::parse_int_key(&s) {
+            Some(i) => ArrayKey::Int(i),
+            None => ArrayKey::Str(s),
+        }
+    }
+
+    /// The integer a string offset denotes, if any. Only an optional `-`
+    /// followed by digits without a leading zero, within i64 range, counts,
+    /// so "007", "-0", "+5", " 5" and "9223372036854775808" stay strings.
+    /// Reference: $PHP_SRC_PATH/Zend/zend_hash.c - _zend_handle_numeric_str_ex
+    pub fn parse_int_key(s: &[u8]) -> Option<i64> {
+        let digits = s.strip_prefix(b"-").unwrap_or(s);
+        match digits {
+            [] => return None,
+            [b'0'] => return (digits.len() == s.len()).then_some(0),
+            [b'0', ..] => return None,
+            _ if !digits.iter().all(u8::is_ascii_digit) => return None,
+            _ => {}
+        }
+        std::str::from_utf8(s).ok()?.parse::<i64>().ok()
+    }
+}
+
 // The Container (Zval equivalent)
 #[derive(Debug, Clone)]
 pub struct Zval {
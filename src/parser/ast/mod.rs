@@ -21,6 +21,12 @@ impl ParseError {
         self.to_human_readable_with_path(source, None)
     }
 
+    /// The error as PHP reports it: `{message} in {file} on line {line}`
+    pub fn to_php_message(&self, source: &[u8], file: &str) -> String {
+        let line = self.span.line_info(source).map_or(0, |info| info.line);
+        format!("{} in {} on line {}", self.message, file, line)
+    }
+
     pub fn to_human_readable_with_path(&self, source: &[u8], path: Option<&str>) -> String {
         let Some(LineInfo {
             line,
@@ -410,6 +416,11 @@ pub enum Expr<'ast> {
         expr: ExprId<'ast>,
         span: Span,
     },
+    /// `throw` in expression position (PHP 8+)
+    Throw {
+        expr: ExprId<'ast>,
+        span: Span,
+    },
     Yield {
         key: Option<ExprId<'ast>>,
         value: Option<ExprId<'ast>>,
@@ -573,6 +584,7 @@ impl<'ast> Expr<'ast> {
             Expr::ArrowFunction { span, .. } => *span,
             Expr::Clone { span, .. } => *span,
             Expr::Print { span, .. } => *span,
+            Expr::Throw { span, .. } => *span,
             Expr::NullsafePropertyFetch { span, .. } => *span,
             Expr::NullsafeMethodCall { span, .. } => *span,
             Expr::VariadicPlaceholder { span } => *span,
@@ -997,6 +997,11 @@ impl<'a, 'ast> Visitor<'ast> for SExprFormatter<'a> {
                 self.visit_expr(expr);
                 self.write(")");
             }
+            Expr::Throw { expr, .. } => {
+                self.write("(throw ");
+                self.visit_expr(expr);
+                self.write(")");
+            }
             Expr::Include { kind, expr, .. } => {
                 self.write("(");
                 self.write(match kind {
@@ -323,6 +323,7 @@ pub fn walk_expr<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: ExprId<
         | Expr::PostInc { var: expr, .. }
         | Expr::PostDec { var: expr, .. }
         | Expr::Print { expr, .. }
+        | Expr::Throw { expr, .. }
         | Expr::Clone { expr, .. }
         | Expr::Cast { expr, .. }
         | Expr::Empty { expr, .. }
@@ -0,0 +1,168 @@
+//! Escape sequence decoding for double-quoted strings and heredocs.
+
+/// The kind of literal whose body is being decoded. It decides which quote
+/// character may be escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteKind {
+    /// `"..."`: `\"` decodes to `"`
+    Double,
+    /// Heredoc or backtick body: `\"` stays as written
+    Heredoc,
+}
+
+impl QuoteKind {
+    /// The decoding for an interpolated string, given the source text starting
+    /// at its opening token. Nowdocs decode nothing and yield `None`.
+    pub fn of_opener(source: &[u8]) -> Option<Self> {
+        let source = match source {
+            [b'b' | b'B', rest @ ..] => rest,
+            _ => source,
+        };
+        if source.starts_with(b"`") {
+            return Some(QuoteKind::Heredoc);
+        }
+        let Some(rest) = source.strip_prefix(b"<<<") else {
+            return Some(QuoteKind::Double);
+        };
+        let label = rest.trim_ascii_start();
+        if label.starts_with(b"'") {
+            None
+        } else {
+            Some(QuoteKind::Heredoc)
+        }
+    }
+}
+
+/// Error message for a malformed `\u{...}` escape
+pub const INVALID_CODEPOINT: &str = "Invalid UTF-8 codepoint escape sequence";
+/// Error message for a `\u{...}` escape above U+10FFFF
+pub const CODEPOINT_TOO_LARGE: &str =
+    "Invalid UTF-8 codepoint escape sequence: Codepoint too large";
+
+/// A decoded string body
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Unescaped {
+    pub bytes: Vec<u8>,
+    /// Warnings for octal escapes above `\377`, which wrap modulo 256
+    pub warnings: Vec<String>,
+}
+
+/// Decode the escape sequences of a double-quoted string or heredoc body the
+/// way PHP's scanner does. Unknown escapes are kept as a backslash plus the
+/// character; an invalid `\u{...}` escape is an error.
+pub fn unescape(s: &[u8], kind: QuoteKind) -> Result<Unescaped, &'static str> {
+    let mut out = Unescaped {
+        bytes: Vec::with_capacity(s.len()),
+        warnings: Vec::new(),
+    };
+    let bytes = &mut out.bytes;
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] != b'\\' || i + 1 >= s.len() {
+            bytes.push(s[i]);
+            i += 1;
+            continue;
+        }
+        let c = s[i + 1];
+        i += 2;
+        match c {
+            b'n' => bytes.push(b'\n'),
+            b't' => bytes.push(b'\t'),
+            b'r' => bytes.push(b'\r'),
+            b'v' => bytes.push(0x0B),
+            b'e' => bytes.push(0x1B),
+            b'f' => bytes.push(0x0C),
+            b'\\' | b'$' => bytes.push(c),
+            b'"' if kind == QuoteKind::Double => bytes.push(c),
+            b'x' if s.get(i).is_some_and(u8::is_ascii_hexdigit) => {
+                let mut value = hex_value(s[i]);
+                i += 1;
+                if let Some(&d) = s.get(i).filter(|d| d.is_ascii_hexdigit()) {
+                    value = value * 16 + hex_value(d);
+                    i += 1;
+                }
+                bytes.push(value);
+            }
+            b'u' if s.get(i) == Some(&b'{') => {
+                let digits_start = i + 1;
+                let mut end = digits_start;
+                while end < s.len() && s[end].is_ascii_hexdigit() {
+                    end += 1;
+                }
+                if end == digits_start || s.get(end) != Some(&b'}') {
+                    return Err(INVALID_CODEPOINT);
+                }
+                let digits = std::str::from_utf8(&s[digits_start..end]).unwrap_or_default();
+                let codepoint = match u32::from_str_radix(digits, 16) {
+                    Ok(cp) if cp <= 0x10FFFF => cp,
+                    _ => return Err(CODEPOINT_TOO_LARGE),
+                };
+                push_utf8(bytes, codepoint);
+                i = end + 1;
+            }
+            b'0'..=b'7' => {
+                let start = i - 1;
+                let mut end = i;
+                while end < s.len() && end - start < 3 && (b'0'..=b'7').contains(&s[end]) {
+                    end += 1;
+                }
+                let digits = &s[start..end];
+                let value = digits
+                    .iter()
+                    .fold(0u32, |acc, d| acc * 8 + u32::from(d - b'0'));
+                if digits.len() == 3 && digits[0] > b'3' {
+                    out.warnings.push(format!(
+                        "Octal escape sequence overflow \\{} is greater than \\377",
+                        String::from_utf8_lossy(digits)
+                    ));
+                }
+                bytes.push(value as u8);
+                i = end;
+            }
+            _ => {
+                bytes.push(b'\\');
+                bytes.push(c);
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn hex_value(d: u8) -> u8 {
+    (d as char).to_digit(16).unwrap_or(0) as u8
+}
+
+/// Encode a codepoint as UTF-8 without rejecting surrogates, as PHP does
+fn push_utf8(out: &mut Vec<u8>, cp: u32) {
+    if cp < 0x80 {
+        out.push(cp as u8);
+    } else if cp < 0x800 {
+        out.push(0xC0 | (cp >> 6) as u8);
+        out.push(0x80 | (cp & 0x3F) as u8);
+    } else if cp < 0x10000 {
+        out.push(0xE0 | (cp >> 12) as u8);
+        out.push(0x80 | ((cp >> 6) & 0x3F) as u8);
+        out.push(0x80 | (cp & 0x3F) as u8);
+    } else {
+        out.push(0xF0 | (cp >> 18) as u8);
+        out.push(0x80 | ((cp >> 12) & 0x3F) as u8);
+        out.push(0x80 | ((cp >> 6) & 0x3F) as u8);
+        out.push(0x80 | (cp & 0x3F) as u8);
+    }
+}
+
+/// Decode a single-quoted string body, where only `\\` and `\'` are escapes
+pub fn unescape_single_quoted(s: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] == b'\\' && matches!(s.get(i + 1), Some(b'\'' | b'\\')) {
+            result.push(s[i + 1]);
+            i += 2;
+        } else {
+            result.push(s[i]);
+            i += 1;
+        }
+    }
+    result
+}
@@ -1,4 +1,6 @@
+pub mod escape;
 pub mod token;
+pub mod trivia;
 
 use crate::parser::span::Span;
 use memchr::{memchr, memchr3};
@@ -131,6 +133,7 @@ pub struct Lexer<'src> {
     cursor: usize,
     state_stack: Vec<LexerState>,
     mode: LexerMode,
+    short_open_tag: bool,
 }
 
 impl<'src> Lexer<'src> {
@@ -149,9 +152,18 @@ impl<'src> Lexer<'src> {
             cursor,
             state_stack: vec![LexerState::Initial],
             mode: LexerMode::Standard,
+            short_open_tag: false,
         }
     }
 
+    /// Recognize the short open tag `<?` (the `short_open_tag` ini setting).
+    /// When off, `<?` that is not `<?php` or `<?=` is inline HTML, so `<?xml`
+    /// prologs pass through untouched.
+    pub fn with_short_open_tag(mut self, enabled: bool) -> Self {
+        self.short_open_tag = enabled;
+        self
+    }
+
     pub(crate) fn source(&self) -> &'src [u8] {
         self.input
     }
@@ -190,6 +202,12 @@ impl<'src> Lexer<'src> {
         }
     }
 
+    fn skip_blanks(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.cursor += 1;
+        }
+    }
+
     fn read_identifier(&mut self) {
         while self.cursor < self.input.len() {
             let c = self.input[self.cursor];
@@ -331,6 +349,15 @@ impl<'src> Lexer<'src> {
         TokenKind::Error // Unterminated comment
     }
 
+    /// Whether a simple interpolated variable continues with `->name`, which
+    /// is lexed as a property fetch inside the string. `?->` stays literal text.
+    fn at_interpolated_property_fetch(&self) -> bool {
+        self.input[self.cursor..]
+            .strip_prefix(b"->")
+            .and_then(|name| name.first())
+            .is_some_and(|&c| c.is_ascii_alphabetic() || c == b'_' || c >= 0x80)
+    }
+
     fn next_in_looking_for_property(&mut self) -> Option<Token> {
         let start = self.cursor;
         if self.cursor >= self.input.len() {
@@ -578,11 +605,7 @@ impl<'src> Lexer<'src> {
                         // Check for array offset [
                         if self.peek() == Some(b'[') {
                             self.state_stack.push(LexerState::VarOffset);
-                        } else if self.peek() == Some(b'-')
-                            && self.input.get(self.cursor + 1) == Some(&b'>')
-                            && let Some(next_next) = self.input.get(self.cursor + 2)
-                            && (next_next.is_ascii_alphabetic() || *next_next == b'_')
-                        {
+                        } else if self.at_interpolated_property_fetch() {
                             self.state_stack.push(LexerState::LookingForProperty);
                         }
 
@@ -1027,11 +1050,7 @@ impl<'src> Lexer<'src> {
                         // Check for array offset [
                         if self.peek() == Some(b'[') {
                             self.state_stack.push(LexerState::VarOffset);
-                        } else if self.peek() == Some(b'-')
-                            && self.input.get(self.cursor + 1) == Some(&b'>')
-                            && let Some(next_next) = self.input.get(self.cursor + 2)
-                            && (next_next.is_ascii_alphabetic() || *next_next == b'_')
-                        {
+                        } else if self.at_interpolated_property_fetch() {
                             self.state_stack.push(LexerState::LookingForProperty);
                         }
 
@@ -1161,7 +1180,10 @@ impl<'src> Iterator for Lexer<'src> {
                     }
                 }
 
-                if self.input[self.cursor..].starts_with(b"<?php") {
+                if self.input[self.cursor..]
+                    .get(..5)
+                    .is_some_and(|tag| tag.eq_ignore_ascii_case(b"<?php"))
+                {
                     if self.cursor > start {
                         return Some(Token {
                             kind: TokenKind::InlineHtml,
@@ -1198,6 +1220,21 @@ impl<'src> Iterator for Lexer<'src> {
                         kind: TokenKind::OpenTagEcho,
                         span: Span::new(tag_start, self.cursor),
                     });
+                } else if self.short_open_tag && self.input[self.cursor..].starts_with(b"<?") {
+                    if self.cursor > start {
+                        return Some(Token {
+                            kind: TokenKind::InlineHtml,
+                            span: Span::new(start, self.cursor),
+                        });
+                    }
+                    let tag_start = self.cursor;
+                    self.state_stack.pop();
+                    self.state_stack.push(LexerState::Scripting);
+                    self.advance_n(2);
+                    return Some(Token {
+                        kind: TokenKind::OpenTag,
+                        span: Span::new(tag_start, self.cursor),
+                    });
                 }
                 self.advance();
             }
@@ -1327,9 +1364,9 @@ impl<'src> Iterator for Lexer<'src> {
                 TokenKind::CloseBrace
             }
             b'(' => {
-                // Check for cast
+                // Check for cast; PHP only allows spaces and tabs inside the parens
                 let saved_cursor = self.cursor;
-                self.skip_whitespace();
+                self.skip_blanks();
 
                 let start_ident = self.cursor;
                 self.read_identifier();
@@ -1337,7 +1374,7 @@ impl<'src> Iterator for Lexer<'src> {
 
                 if ident_len > 0 {
                     let ident = &self.input[start_ident..self.cursor];
-                    self.skip_whitespace();
+                    self.skip_blanks();
                     if self.peek() == Some(b')') {
                         let cast_kind = match ident.to_ascii_lowercase().as_slice() {
                             b"int" | b"integer" => Some(TokenKind::IntCast),
@@ -1607,6 +1644,9 @@ impl<'src> Iterator for Lexer<'src> {
                             kind: self.read_double_quoted(b'"', quote_pos),
                             span: Span::new(start, self.cursor),
                         });
+                    } else if self.input[self.cursor..].starts_with(b"<<<") {
+                        self.advance_n(3); // Eat <<<
+                        return Some(self.read_heredoc_start(start));
                     }
                 }
 
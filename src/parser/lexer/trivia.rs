@@ -0,0 +1,78 @@
+//! Trivia-preserving tokenization for tools that need the full source text
+//! (formatters, linters, precise diagnostics). The parser keeps using the
+//! plain [`Lexer`] iterator, which skips whitespace.
+
+use super::Lexer;
+use super::token::{Token, TokenKind};
+use crate::parser::span::Span;
+
+/// A token with the whitespace and comments that precede it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriviaToken {
+    /// Whitespace runs, comments and a leading shebang line, in source order
+    pub leading_trivia: Vec<Span>,
+    pub token: Token,
+}
+
+/// Iterator over [`TriviaToken`]s, ending with the `Eof` token, which carries
+/// any trailing trivia. Concatenating every trivia span and token span
+/// reproduces the input byte for byte.
+#[derive(Debug, Clone)]
+pub struct TriviaTokens<'src> {
+    lexer: Lexer<'src>,
+    last_end: usize,
+    finished: bool,
+}
+
+impl<'src> TriviaTokens<'src> {
+    pub fn new(lexer: Lexer<'src>) -> Self {
+        Self {
+            lexer,
+            last_end: 0,
+            finished: false,
+        }
+    }
+}
+
+impl Iterator for TriviaTokens<'_> {
+    type Item = TriviaToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let mut leading_trivia = Vec::new();
+        loop {
+            let token = self.lexer.next()?;
+            // Anything the lexer skipped between tokens is whitespace (or
+            // the shebang line before the first token)
+            if token.span.start > self.last_end {
+                leading_trivia.push(Span::new(self.last_end, token.span.start));
+            }
+            self.last_end = self.last_end.max(token.span.end);
+            match token.kind {
+                TokenKind::Comment | TokenKind::DocComment => leading_trivia.push(token.span),
+                kind => {
+                    self.finished = kind == TokenKind::Eof;
+                    return Some(TriviaToken {
+                        leading_trivia,
+                        token,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<'src> Lexer<'src> {
+    /// Switch to trivia-preserving mode: tokens come with the whitespace
+    /// and comments before them attached as leading trivia.
+    pub fn with_trivia(self) -> TriviaTokens<'src> {
+        TriviaTokens::new(self)
+    }
+}
+
+/// Tokenize `input`, attaching whitespace and comments to the following token
+pub fn lex_with_trivia(input: &[u8]) -> TriviaTokens<'_> {
+    Lexer::new(input).with_trivia()
+}
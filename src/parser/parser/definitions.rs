@@ -802,7 +802,9 @@ impl<'src, 'ast> Parser<'src, 'ast> {
             let mut close_brace_span = None;
             let body = if self.current_token.kind == TokenKind::OpenBrace {
                 has_body_flag = true;
+                let outer_saw_yield = std::mem::take(&mut self.saw_yield);
                 let body_stmt_id = self.parse_block();
+                self.check_generator_return_type(return_type, outer_saw_yield);
                 match body_stmt_id {
                     Stmt::Block { statements, span } => {
                         close_brace_span = Some(Span::new(span.end.saturating_sub(1), span.end));
@@ -1718,7 +1720,9 @@ impl<'src, 'ast> Parser<'src, 'ast> {
         };
 
         // Body
+        let outer_saw_yield = std::mem::take(&mut self.saw_yield);
         let body_stmt_id = self.parse_stmt(); // Should be a block
+        self.check_generator_return_type(return_type, outer_saw_yield);
         let (body, close_brace_span): (&'ast [StmtId<'ast>], Option<Span>) = match body_stmt_id {
             Stmt::Block { statements, span } => (
                 statements,
@@ -3,8 +3,11 @@ use crate::parser::ast::{
     Arg, ArrayItem, AssignOp, AttributeGroup, BinaryOp, CastKind, ClosureUse, Expr, ExprId,
     IncludeKind, MagicConstKind, MatchArm, Param, ParseError, Stmt, StmtId, Type, UnaryOp,
 };
+use crate::parser::lexer::escape::{QuoteKind, unescape};
 use crate::parser::lexer::token::{Token, TokenKind};
 use crate::parser::span::Span;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 
 impl<'src, 'ast> Parser<'src, 'ast> {
     pub(super) fn parse_call_arguments(&mut self) -> (&'ast [Arg<'ast>], Span) {
@@ -172,6 +175,68 @@ impl<'src, 'ast> Parser<'src, 'ast> {
         }
     }
 
+    /// Finish a function body started with `outer_saw_yield` saved: restore
+    /// the enclosing flag and, if the body yields, check that the declared
+    /// return type can hold a Generator.
+    pub(super) fn check_generator_return_type(
+        &mut self,
+        return_type: Option<&Type<'ast>>,
+        outer_saw_yield: bool,
+    ) {
+        let is_generator = std::mem::replace(&mut self.saw_yield, outer_saw_yield);
+        if let Some(ty) = return_type
+            && is_generator
+            && !self.accepts_generator(ty)
+        {
+            let declared = self.type_to_string(ty);
+            self.errors.push(ParseError {
+                span: type_span(ty),
+                message: generator_return_type_message(declared),
+            });
+        }
+    }
+
+    /// The declared type as PHP's error messages spell it
+    fn type_to_string(&self, ty: &Type<'ast>) -> String {
+        match ty {
+            Type::Simple(token) => String::from_utf8_lossy(self.lexer.slice(token.span)).into(),
+            Type::Name(name) => String::from_utf8_lossy(self.lexer.slice(name.span)).into(),
+            Type::Union(types) => types
+                .iter()
+                .map(|ty| match ty {
+                    Type::Intersection(_) => format!("({})", self.type_to_string(ty)),
+                    _ => self.type_to_string(ty),
+                })
+                .collect::<Vec<_>>()
+                .join("|"),
+            Type::Intersection(types) => types
+                .iter()
+                .map(|ty| self.type_to_string(ty))
+                .collect::<Vec<_>>()
+                .join("&"),
+            Type::Nullable(inner) => format!("?{}", self.type_to_string(inner)),
+        }
+    }
+
+    fn accepts_generator(&self, ty: &Type<'ast>) -> bool {
+        match ty {
+            Type::Simple(token) => matches!(
+                token.kind,
+                TokenKind::TypeIterable | TokenKind::TypeMixed | TokenKind::TypeObject
+            ),
+            Type::Name(name) => name.parts.last().is_some_and(|part| {
+                let text = self.lexer.slice(part.span);
+                [b"Traversable".as_slice(), b"Iterator", b"Generator"]
+                    .iter()
+                    .any(|class| text.eq_ignore_ascii_case(class))
+            }),
+            Type::Union(types) | Type::Intersection(types) => {
+                types.iter().any(|ty| self.accepts_generator(ty))
+            }
+            Type::Nullable(inner) => self.accepts_generator(inner),
+        }
+    }
+
     pub(super) fn parse_closure_expr(
         &mut self,
         attributes: &'ast [AttributeGroup<'ast>],
@@ -199,7 +264,9 @@ impl<'src, 'ast> Parser<'src, 'ast> {
         let uses = self.parse_use_list();
         let return_type = self.parse_return_type();
 
+        let outer_saw_yield = std::mem::take(&mut self.saw_yield);
         let body_stmt_id = self.parse_block();
+        self.check_generator_return_type(return_type, outer_saw_yield);
         let (body, close_brace_span): (&'ast [StmtId<'ast>], Option<Span>) = match body_stmt_id {
             Stmt::Block { statements, span } => (
                 statements,
@@ -250,7 +317,9 @@ impl<'src, 'ast> Parser<'src, 'ast> {
         if self.current_token.kind == TokenKind::DoubleArrow {
             self.bump();
         }
+        let outer_saw_yield = std::mem::take(&mut self.saw_yield);
         let expr = self.parse_expr(0);
+        self.check_generator_return_type(return_type, outer_saw_yield);
 
         let end = expr.span().end;
         self.arena.alloc(Expr::ArrowFunction {
@@ -477,11 +546,28 @@ impl<'src, 'ast> Parser<'src, 'ast> {
 
                     let current_is_elvis = self.next_token.kind == TokenKind::Colon;
 
-                    if just_parsed_ternary && (!just_parsed_elvis || !current_is_elvis) {
-                        self.errors.push(ParseError {
+                    // PHP 8 rejects nesting in the else branch; only chained
+                    // short ternaries (`a ?: b ?: c`) are unambiguous.
+                    // Reference: $PHP_SRC_PATH/Zend/zend_compile.c - zend_compile_conditional
+                    if just_parsed_ternary {
+                        let message = match (just_parsed_elvis, current_is_elvis) {
+                            (false, false) => Some(
+                                "Unparenthesized `a ? b : c ? d : e` is not supported. Use either `(a ? b : c) ? d : e` or `a ? b : (c ? d : e)`",
+                            ),
+                            (false, true) => Some(
+                                "Unparenthesized `a ? b : c ?: d` is not supported. Use either `(a ? b : c) ?: d` or `a ? b : (c ?: d)`",
+                            ),
+                            (true, false) => Some(
+                                "Unparenthesized `a ?: b ? c : d` is not supported. Use either `(a ?: b) ? c : d` or `a ?: (b ? c : d)`",
+                            ),
+                            (true, true) => None,
+                        };
+                        if let Some(message) = message {
+                            self.errors.push(ParseError {
                                 span: self.current_token.span,
-                                message: "Unparenthesized `a ? b : c ? d : e` is not supported. Use either `(a ? b : c) ? d : e` or `a ? b : (c ? d : e)`",
+                                message,
                             });
+                        }
                     }
 
                     self.bump();
@@ -1115,6 +1201,7 @@ impl<'src, 'ast> Parser<'src, 'ast> {
             TokenKind::Yield | TokenKind::YieldFrom => {
                 let start = token.span.start;
                 self.bump();
+                self.saw_yield = true;
 
                 let mut is_from = token.kind == TokenKind::YieldFrom;
                 if !is_from && self.current_token.kind == TokenKind::Identifier {
@@ -1173,12 +1260,12 @@ impl<'src, 'ast> Parser<'src, 'ast> {
             }
 
             TokenKind::Throw => {
-                // Throw expression (PHP 8+): reuse error node to avoid a new variant
+                // Throw expression (PHP 8+)
                 let start = token.span.start;
                 self.bump();
                 let expr = self.parse_expr(0);
                 let span = Span::new(start, expr.span().end);
-                self.arena.alloc(Expr::Error { span })
+                self.arena.alloc(Expr::Throw { expr, span })
             }
 
             TokenKind::Function => {
@@ -1421,6 +1508,14 @@ impl<'src, 'ast> Parser<'src, 'ast> {
             }
             TokenKind::StringLiteral => {
                 self.bump();
+                let text = self.lexer.slice(token.span);
+                let body = match text {
+                    [b'b' | b'B', rest @ ..] => rest,
+                    _ => text,
+                };
+                if let [b'"', inner @ .., b'"'] = body {
+                    self.check_escapes(inner, QuoteKind::Double, token.span);
+                }
                 self.arena.alloc(Expr::String {
                     value: self.arena.alloc_slice_copy(self.lexer.slice(token.span)),
                     span: token.span,
@@ -1763,12 +1858,15 @@ impl<'src, 'ast> Parser<'src, 'ast> {
 
         let mut parts: bumpalo::collections::Vec<&'ast Expr<'ast>> =
             bumpalo::collections::Vec::new_in(self.arena);
+        // Indices of the literal body segments, for heredoc indentation stripping
+        let mut literal_parts = Vec::new();
 
         while self.current_token.kind != end_token && self.current_token.kind != TokenKind::Eof {
             match self.current_token.kind {
                 TokenKind::EncapsedAndWhitespace => {
                     let token = self.current_token;
                     self.bump();
+                    literal_parts.push(parts.len());
                     parts.push(self.arena.alloc(Expr::String {
                         value: self.arena.alloc_slice_copy(self.lexer.slice(token.span)),
                         span: token.span,
@@ -1904,13 +2002,24 @@ impl<'src, 'ast> Parser<'src, 'ast> {
         }
 
         let end = if self.current_token.kind == end_token {
-            let end = self.current_token.span.end;
+            let closer = self.current_token.span;
             self.bump();
-            end
+            if end_token == TokenKind::EndHeredoc {
+                self.strip_heredoc_indentation(&mut parts, &literal_parts, closer);
+            }
+            closer.end
         } else {
             self.current_token.span.start
         };
 
+        if let Some(kind) = QuoteKind::of_opener(self.lexer.slice(Span::new(start, end))) {
+            for &index in &literal_parts {
+                if let Expr::String { value, span } = *parts[index] {
+                    self.check_escapes(value, kind, span);
+                }
+            }
+        }
+
         let span = Span::new(start, end);
         let parts = parts.into_bump_slice();
 
@@ -1920,4 +2029,191 @@ impl<'src, 'ast> Parser<'src, 'ast> {
             self.arena.alloc(Expr::InterpolatedString { parts, span })
         }
     }
+
+    /// Report an invalid `\u{...}` escape in a string body as a parse error
+    fn check_escapes(&mut self, body: &[u8], kind: QuoteKind, span: Span) {
+        if let Err(message) = unescape(body, kind) {
+            self.errors.push(ParseError { span, message });
+        }
+    }
+
+    /// Drop the newline before a heredoc/nowdoc closing marker and strip the
+    /// closer's indentation from every body line (PHP 7.3 flexible heredoc).
+    /// Reference: strip_multiline_string_indentation() in zend_language_scanner.l
+    fn strip_heredoc_indentation(
+        &mut self,
+        parts: &mut [&'ast Expr<'ast>],
+        literal_parts: &[usize],
+        closer: Span,
+    ) {
+        let closer_text = self.lexer.slice(closer);
+        let indentation = closer_text
+            .iter()
+            .take_while(|&&c| c == b' ' || c == b'\t')
+            .count();
+        let using_spaces = closer_text.first() == Some(&b' ');
+        if closer_text[..indentation]
+            .iter()
+            .any(|&c| (c == b' ') != using_spaces)
+        {
+            self.errors.push(ParseError {
+                span: closer,
+                message: MIXED_INDENTATION,
+            });
+            return;
+        }
+
+        let Some(last) = parts.len().checked_sub(1) else {
+            return;
+        };
+        for &index in literal_parts {
+            let Expr::String { value, span } = *parts[index] else {
+                continue;
+            };
+            let at_end = index == last;
+            let mut value = value;
+            if at_end {
+                value = value
+                    .strip_suffix(b"\n")
+                    .map(|v| v.strip_suffix(b"\r").unwrap_or(v))
+                    .or_else(|| value.strip_suffix(b"\r"))
+                    .unwrap_or(value);
+            }
+            let stripped = if indentation > 0 {
+                match strip_line_indentation(value, indentation, using_spaces, index == 0, at_end) {
+                    Ok(stripped) => stripped,
+                    Err(message) => {
+                        self.errors.push(ParseError { span, message });
+                        return;
+                    }
+                }
+            } else {
+                value.to_vec()
+            };
+            parts[index] = self.arena.alloc(Expr::String {
+                value: self.arena.alloc_slice_copy(&stripped),
+                span,
+            });
+        }
+    }
+}
+
+const MIXED_INDENTATION: &str = "Invalid indentation - tabs and spaces cannot be mixed";
+
+/// Position and length of the next "\n", "\r\n" or "\r" at or after `from`
+fn next_newline(text: &[u8], from: usize) -> Option<(usize, usize)> {
+    let at = from
+        + text[from..]
+            .iter()
+            .position(|&c| c == b'\n' || c == b'\r')?;
+    let len = if text[at] == b'\r' && text.get(at + 1) == Some(&b'\n') {
+        2
+    } else {
+        1
+    };
+    Some((at, len))
+}
+
+/// Remove `indentation` whitespace characters from the start of every line in
+/// one literal heredoc segment. `at_line_start` is false when the segment
+/// continues a line begun before an interpolation; `at_end` is true for the
+/// segment just before the closing marker, whose last line may be shorter.
+fn strip_line_indentation(
+    text: &[u8],
+    indentation: usize,
+    using_spaces: bool,
+    at_line_start: bool,
+    at_end: bool,
+) -> Result<Vec<u8>, &'static str> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut pos = 0;
+    if !at_line_start {
+        let Some((at, len)) = next_newline(text, 0) else {
+            return Ok(text.to_vec());
+        };
+        pos = at + len;
+        out.extend_from_slice(&text[..pos]);
+    }
+
+    loop {
+        let newline = next_newline(text, pos);
+        let line_end = match newline {
+            Some((at, _)) => Some(at),
+            None if at_end => Some(text.len()),
+            None => None,
+        };
+        for _ in 0..indentation {
+            // Whitespace-only lines need not carry the full indentation
+            if Some(pos) == line_end {
+                break;
+            }
+            match text.get(pos) {
+                Some(&c) if c == b' ' || c == b'\t' => {
+                    if (c == b' ') != using_spaces {
+                        return Err(MIXED_INDENTATION);
+                    }
+                }
+                _ => return Err(body_indentation_message(indentation)),
+            }
+            pos += 1;
+        }
+        if pos == text.len() {
+            break;
+        }
+        let line_len = match newline {
+            Some((at, len)) => at + len - pos,
+            None => text.len() - pos,
+        };
+        out.extend_from_slice(&text[pos..pos + line_len]);
+        pos += line_len;
+        if newline.is_none() {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+/// The source range covered by a type's names and keywords
+fn type_span(ty: &Type<'_>) -> Span {
+    match ty {
+        Type::Simple(token) => token.span,
+        Type::Name(name) => name.span,
+        Type::Union(types) | Type::Intersection(types) => match (types.first(), types.last()) {
+            (Some(first), Some(last)) => Span::new(type_span(first).start, type_span(last).end),
+            _ => Span::default(),
+        },
+        Type::Nullable(inner) => type_span(inner),
+    }
+}
+
+fn generator_return_type_message(declared: String) -> &'static str {
+    static MESSAGES: OnceLock<Mutex<HashMap<String, &'static str>>> = OnceLock::new();
+    let mut messages = MESSAGES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    messages.entry(declared).or_insert_with_key(|declared| {
+        format!(
+            "Generator return type must be a declared supertype of Generator, {} given",
+            declared
+        )
+        .leak()
+    })
+}
+
+/// ParseError messages are `'static`, so each distinct level's message is
+/// built once and kept for the life of the process
+fn body_indentation_message(indentation: usize) -> &'static str {
+    static MESSAGES: OnceLock<Mutex<HashMap<usize, &'static str>>> = OnceLock::new();
+    let mut messages = MESSAGES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    messages.entry(indentation).or_insert_with(|| {
+        format!(
+            "Invalid body indentation level (expecting an indentation level of at least {})",
+            indentation
+        )
+        .leak()
+    })
 }
@@ -31,6 +31,8 @@ pub struct Parser<'src, 'ast> {
     pub(super) current_doc_comment: Option<Span>,
     pub(super) next_doc_comment: Option<Span>,
     pub(super) seen_non_declare_stmt: bool,
+    /// Whether the function body being parsed contains `yield`
+    pub(super) saw_yield: bool,
 }
 
 impl<'src, 'ast> Parser<'src, 'ast> {
@@ -50,6 +52,7 @@ impl<'src, 'ast> Parser<'src, 'ast> {
             current_doc_comment: None,
             next_doc_comment: None,
             seen_non_declare_stmt: false,
+            saw_yield: false,
         };
         parser.bump();
         parser.bump();
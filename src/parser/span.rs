@@ -13,6 +13,38 @@ pub struct LineInfo<'src> {
     pub line_text: &'src [u8],
 }
 
+/// Byte offsets of every line start in a source file, computed once so that
+/// spans can be converted to line and column numbers by binary search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &[u8]) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            source
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| **b == b'\n')
+                .map(|(i, _)| i + 1),
+        );
+        Self { line_starts }
+    }
+
+    /// 1-based line number of a byte offset
+    pub fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset)
+    }
+
+    /// 1-based (line, column) of a byte offset; the column counts bytes
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.line(offset);
+        (line, offset - self.line_starts[line - 1] + 1)
+    }
+}
+
 /// Execute a closure with a source code context for Span debugging.
 /// This allows Spans to print their line number and text content when Debug formatted.
 pub fn with_session_globals<F, R>(source: &[u8], f: F) -> R
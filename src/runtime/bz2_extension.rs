@@ -0,0 +1,43 @@
+use crate::builtins::bz2;
+use crate::runtime::context::RequestContext;
+use crate::runtime::extension::{Extension, ExtensionInfo, ExtensionResult};
+use crate::runtime::registry::ExtensionRegistry;
+
+pub struct Bz2Extension;
+
+impl Extension for Bz2Extension {
+    fn info(&self) -> ExtensionInfo {
+        ExtensionInfo {
+            name: "bz2",
+            version: "1.0.0",
+            dependencies: &[],
+        }
+    }
+
+    fn module_init(&self, registry: &mut ExtensionRegistry) -> ExtensionResult {
+        registry.register_function(b"bzcompress", bz2::php_bzcompress);
+        registry.register_function(b"bzdecompress", bz2::php_bzdecompress);
+        registry.register_function(b"bzopen", bz2::php_bzopen);
+        registry.register_function(b"bzread", bz2::php_bzread);
+        registry.register_function(b"bzwrite", bz2::php_bzwrite);
+        registry.register_function(b"bzflush", bz2::php_bzflush);
+        registry.register_function(b"bzclose", bz2::php_bzclose);
+        registry.register_function(b"bzerror", bz2::php_bzerror);
+        registry.register_function(b"bzerrno", bz2::php_bzerrno);
+        registry.register_function(b"bzerrstr", bz2::php_bzerrstr);
+
+        ExtensionResult::Success
+    }
+
+    fn module_shutdown(&self) -> ExtensionResult {
+        ExtensionResult::Success
+    }
+
+    fn request_init(&self, _context: &mut RequestContext) -> ExtensionResult {
+        ExtensionResult::Success
+    }
+
+    fn request_shutdown(&self, _context: &mut RequestContext) -> ExtensionResult {
+        ExtensionResult::Success
+    }
+}
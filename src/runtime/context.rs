@@ -3,7 +3,7 @@ use crate::core::interner::Interner;
 use crate::core::value::{Handle, Symbol, Val, Visibility};
 use crate::runtime::attributes::AttributeInstance;
 use crate::runtime::extension::Extension;
-use crate::runtime::registry::ExtensionRegistry;
+use crate::runtime::registry::{ExtensionRegistry, NativeClassDef};
 use crate::runtime::resource_manager::ResourceManager;
 use crate::vm::engine::VM;
 use crate::vm::memory::{MemoryApi, VmHeap};
@@ -31,6 +31,54 @@ pub struct PhpConfig {
     pub ini_settings: HashMap<String, String>,
 }
 
+impl PhpConfig {
+    /// Whether the `short_open_tag` ini setting enables `<?` as an open tag
+    pub fn short_open_tag(&self) -> bool {
+        self.ini_settings
+            .get("short_open_tag")
+            .is_some_and(|value| {
+                matches!(
+                    value.to_ascii_lowercase().as_str(),
+                    "1" | "on" | "yes" | "true"
+                )
+            })
+    }
+
+    /// Whether `zlib.output_compression` is on. Besides a boolean the setting
+    /// accepts a buffer size, so any positive number also enables it.
+    pub fn output_compression(&self) -> bool {
+        self.ini_settings
+            .get("zlib.output_compression")
+            .is_some_and(|value| {
+                let value = value.trim().to_ascii_lowercase();
+                matches!(value.as_str(), "on" | "yes" | "true")
+                    || value.parse::<i64>().is_ok_and(|n| n > 0)
+            })
+    }
+
+    /// The `zlib.output_compression_level` ini setting, -1 for zlib's default
+    pub fn output_compression_level(&self) -> i32 {
+        self.ini_settings
+            .get("zlib.output_compression_level")
+            .and_then(|value| value.trim().parse::<i32>().ok())
+            .filter(|level| (-1..=9).contains(level))
+            .unwrap_or(-1)
+    }
+
+    /// The `include_path` ini setting split into its directories, using
+    /// PHP's per-OS separator (`;` on Windows, `:` elsewhere)
+    pub fn include_paths(&self) -> Vec<PathBuf> {
+        let separator = if cfg!(windows) { ';' } else { ':' };
+        self.ini_settings
+            .get("include_path")
+            .map_or(".", String::as_str)
+            .split(separator)
+            .filter(|entry| !entry.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    }
+}
+
 impl Default for PhpConfig {
     fn default() -> Self {
         let mut ini_settings = HashMap::new();
@@ -40,6 +88,11 @@ impl Default for PhpConfig {
         ini_settings.insert("input_encoding".to_string(), "".to_string());
         ini_settings.insert("internal_encoding".to_string(), "".to_string());
         ini_settings.insert("output_encoding".to_string(), "".to_string());
+        ini_settings.insert("zlib.output_compression".to_string(), "0".to_string());
+        ini_settings.insert(
+            "zlib.output_compression_level".to_string(),
+            "-1".to_string(),
+        );
 
         Self {
             error_reporting: 32767, // E_ALL
@@ -240,6 +293,12 @@ impl EngineContext {
             .register_extension(Box::new(ZlibExtension))
             .expect("Failed to register Zlib extension");
 
+        // Register Bz2 extension
+        use crate::runtime::bz2_extension::Bz2Extension;
+        registry
+            .register_extension(Box::new(Bz2Extension))
+            .expect("Failed to register Bz2 extension");
+
         // Register MBString extension
         use crate::runtime::mb_extension::MbStringExtension;
         registry
@@ -267,6 +326,9 @@ pub struct RequestContext {
     pub config: PhpConfig,
     pub globals: HashMap<Symbol, Handle>,
     pub constants: HashMap<Symbol, Val>,
+    /// Core constants the context registers itself, as opposed to extension
+    /// or user-defined constants
+    pub builtin_constants: HashSet<Symbol>,
     pub function_attributes: HashMap<Symbol, Vec<AttributeInstance>>,
     pub user_functions: HashMap<Symbol, Rc<UserFunc>>,
     pub classes: HashMap<Symbol, ClassDef>,
@@ -305,6 +367,7 @@ impl RequestContext {
             config,
             globals: HashMap::new(),
             constants: HashMap::new(),
+            builtin_constants: HashSet::new(),
             function_attributes: HashMap::new(),
             user_functions: HashMap::new(),
             classes: HashMap::new(),
@@ -640,6 +703,7 @@ impl RequestContext {
 
     pub fn insert_builtin_constant(&mut self, name: &[u8], value: Val) {
         let sym = self.interner.intern(name);
+        self.builtin_constants.insert(sym);
         self.constants.insert(sym, value);
     }
 }
@@ -654,6 +718,9 @@ impl RequestContext {
 /// ```
 pub struct EngineBuilder {
     extensions: Vec<Box<dyn Extension>>,
+    /// Host functions and classes, registered after all extensions
+    functions: Vec<(Vec<u8>, NativeHandler)>,
+    classes: Vec<NativeClassDef>,
 }
 
 impl EngineBuilder {
@@ -661,9 +728,45 @@ impl EngineBuilder {
     pub fn new() -> Self {
         Self {
             extensions: Vec::new(),
+            functions: Vec::new(),
+            classes: Vec::new(),
         }
     }
 
+    /// Expose a host-provided native function to scripts
+    ///
+    /// The handler receives the VM and one `Handle` per argument. Read an
+    /// argument with `vm.arena.get(handle).value` (a `Val`), build the result
+    /// with `vm.arena.alloc(Val::..)` and return its handle. Returning `Err`
+    /// raises a fatal error; use `vm.report_error(ErrorLevel::Warning, ..)`
+    /// for recoverable problems.
+    ///
+    /// # Example
+    /// ```ignore
+    /// fn host_add(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    ///     let sum: i64 = args.iter().map(|&h| vm.arena.get(h).value.to_int()).sum();
+    ///     Ok(vm.arena.alloc(Val::Int(sum)))
+    /// }
+    ///
+    /// let engine = EngineBuilder::new()
+    ///     .with_core_extensions()
+    ///     .with_native_function(b"host_add", host_add)
+    ///     .build()?;
+    /// ```
+    pub fn with_native_function(mut self, name: &[u8], handler: NativeHandler) -> Self {
+        self.functions.push((name.to_vec(), handler));
+        self
+    }
+
+    /// Expose a host-provided native class to scripts
+    ///
+    /// Method handlers follow the `with_native_function` contract; instance
+    /// methods find their object in `vm.frames.last().and_then(|f| f.this)`.
+    pub fn with_native_class(mut self, class: NativeClassDef) -> Self {
+        self.classes.push(class);
+        self
+    }
+
     /// Add an extension to the builder
     pub fn with_extension<E: Extension + 'static>(mut self, ext: E) -> Self {
         self.extensions.push(Box::new(ext));
@@ -693,8 +796,12 @@ impl EngineBuilder {
             .push(Box::new(super::pthreads_extension::PthreadsExtension));
         self.extensions
             .push(Box::new(super::zlib_extension::ZlibExtension));
+        self.extensions
+            .push(Box::new(super::bz2_extension::Bz2Extension));
         self.extensions
             .push(Box::new(super::mb_extension::MbStringExtension));
+        self.extensions
+            .push(Box::new(super::zip_extension::ZipExtension));
         self.extensions
             .push(Box::new(crate::builtins::reflection::ReflectionExtension));
         self
@@ -714,6 +821,13 @@ impl EngineBuilder {
             registry.register_extension(ext)?;
         }
 
+        for (name, handler) in self.functions {
+            registry.register_function(&name, handler);
+        }
+        for class in self.classes {
+            registry.register_class(class);
+        }
+
         Ok(Arc::new(EngineContext { registry }))
     }
 }
@@ -219,6 +219,10 @@ impl Extension for CoreExtension {
         registry.register_function(b"define", variable::php_define);
         registry.register_function(b"defined", variable::php_defined);
         registry.register_function(b"constant", variable::php_constant);
+        registry.register_function(
+            b"get_defined_constants",
+            variable::php_get_defined_constants,
+        );
         registry.register_function(b"gettype", variable::php_gettype);
         registry.register_function(b"var_export", variable::php_var_export);
         registry.register_function(b"getenv", variable::php_getenv);
@@ -264,6 +268,9 @@ impl Extension for CoreExtension {
         registry.register_function(b"round", math::php_round);
         registry.register_function(b"floor", math::php_floor);
         registry.register_function(b"ceil", math::php_ceil);
+        registry.register_function(b"intdiv", math::php_intdiv);
+        registry.register_function(b"random_bytes", math::php_random_bytes);
+        registry.register_function(b"random_int", math::php_random_int);
 
         // BCMath functions
         registry.register_function(b"bcadd", bcmath::bcadd);
@@ -329,7 +336,7 @@ impl Extension for CoreExtension {
         registry.register_function(b"func_num_args", function::php_func_num_args);
         registry.register_function(b"func_get_arg", function::php_func_get_arg);
         registry.register_function(b"function_exists", function::php_function_exists);
-        registry.register_function(b"is_callable", function::php_is_callable);
+        registry.register_function_with_by_ref(b"is_callable", function::php_is_callable, vec![2]);
         registry.register_function(b"call_user_func", function::php_call_user_func);
         registry.register_function(b"call_user_func_array", function::php_call_user_func_array);
         registry.register_function(b"debug_backtrace", function::php_debug_backtrace);
@@ -351,6 +358,9 @@ impl Extension for CoreExtension {
         );
         registry.register_function(b"spl_autoload_register", spl::php_spl_autoload_register);
         registry.register_function(b"spl_object_hash", spl::php_spl_object_hash);
+        spl::register_spl_heap_classes(registry);
+        spl::register_spl_fixed_array_class(registry);
+        spl::register_spl_file_object_class(registry);
         registry.register_function(b"assert", function::php_assert);
 
         // Filesystem functions - File I/O
@@ -366,6 +376,9 @@ impl Extension for CoreExtension {
         registry.register_function(b"rewind", filesystem::php_rewind);
         registry.register_function(b"feof", filesystem::php_feof);
         registry.register_function(b"fflush", filesystem::php_fflush);
+        registry.register_constant(b"SEEK_SET", Val::Int(0));
+        registry.register_constant(b"SEEK_CUR", Val::Int(1));
+        registry.register_constant(b"SEEK_END", Val::Int(2));
 
         // Filesystem functions - File content
         registry.register_function(b"file_get_contents", filesystem::php_file_get_contents);
@@ -1289,6 +1302,20 @@ impl Extension for CoreExtension {
             extension_name: None,
         });
 
+        // ArgumentCountError
+        registry.register_class(NativeClassDef {
+            name: b"ArgumentCountError".to_vec(),
+            parent: Some(b"TypeError".to_vec()),
+            is_interface: false,
+            is_trait: false,
+            is_final: false,
+            interfaces: vec![],
+            methods: HashMap::new(),
+            constants: HashMap::new(),
+            constructor: Some(exception::exception_construct),
+            extension_name: None,
+        });
+
         // ArithmeticError
         registry.register_class(NativeClassDef {
             name: b"ArithmeticError".to_vec(),
@@ -231,6 +231,15 @@ impl Extension for DateExtension {
                 is_final: false,
             },
         );
+        datetime_methods.insert(
+            b"getLastErrors".to_vec(),
+            NativeMethodEntry {
+                handler: datetime::php_datetime_get_last_errors,
+                visibility: Visibility::Public,
+                is_static: true,
+                is_final: false,
+            },
+        );
         registry.register_class(NativeClassDef {
             name: b"DateTime".to_vec(),
             parent: None,
@@ -641,6 +650,10 @@ impl Extension for DateExtension {
             b"date_create_from_format",
             datetime::php_datetime_create_from_format,
         );
+        registry.register_function(
+            b"date_get_last_errors",
+            datetime::php_datetime_get_last_errors,
+        );
         registry.register_function(b"date_format", datetime::php_date_format);
         registry.register_function(b"date_modify", datetime::php_date_modify);
         registry.register_function(b"date_add", datetime::php_date_add);
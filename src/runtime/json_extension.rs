@@ -8,6 +8,7 @@ use std::collections::HashMap;
 ///
 /// This extension provides PHP's core JSON functionality:
 /// - `json_encode()` - Encode PHP values to JSON
+/// - `json_encode_stream()` - Encode PHP values to JSON directly into a stream
 /// - `json_decode()` - Decode JSON to PHP values
 /// - `json_last_error()` - Get last error code
 /// - `json_last_error_msg()` - Get last error message
@@ -41,6 +42,7 @@ impl Extension for JsonExtension {
     fn module_init(&self, registry: &mut ExtensionRegistry) -> ExtensionResult {
         // Register JSON functions
         registry.register_function(b"json_encode", json::php_json_encode);
+        registry.register_function(b"json_encode_stream", json::php_json_encode_stream);
         registry.register_function(b"json_decode", json::php_json_decode);
         registry.register_function(b"json_last_error", json::php_json_last_error);
         registry.register_function(b"json_last_error_msg", json::php_json_last_error_msg);
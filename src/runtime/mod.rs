@@ -1,4 +1,5 @@
 pub mod attributes;
+pub mod bz2_extension;
 pub mod context;
 pub mod core_extension;
 pub mod date_extension;
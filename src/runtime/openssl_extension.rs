@@ -56,6 +56,10 @@ impl Extension for OpenSSLExtension {
             b"OPENSSL_PKCS1_OAEP_PADDING",
             Val::Int(openssl::OPENSSL_PKCS1_OAEP_PADDING),
         );
+        registry.register_constant(
+            b"OPENSSL_PKCS1_PSS_PADDING",
+            Val::Int(openssl::OPENSSL_PKCS1_PSS_PADDING),
+        );
 
         registry.register_constant(
             b"OPENSSL_KEYTYPE_RSA",
@@ -253,6 +257,7 @@ impl Extension for OpenSSLExtension {
             openssl::openssl_pkey_export_to_file,
         );
         registry.register_function(b"openssl_pkey_derive", openssl::openssl_pkey_derive);
+        registry.register_function(b"openssl_dh_compute_key", openssl::openssl_dh_compute_key);
         registry.register_function(b"openssl_pkey_free", openssl::openssl_pkey_free);
         registry.register_function(b"openssl_get_privatekey", openssl::openssl_pkey_get_private);
         registry.register_function(b"openssl_get_publickey", openssl::openssl_pkey_get_public);
@@ -277,6 +282,11 @@ impl Extension for OpenSSLExtension {
             openssl::openssl_x509_check_private_key,
         );
         registry.register_function(b"openssl_x509_verify", openssl::openssl_x509_verify);
+        registry.register_function_with_by_ref(
+            b"openssl_x509_verify_chain",
+            openssl::openssl_x509_verify_chain,
+            vec![3],
+        );
         registry.register_function(b"openssl_x509_free", openssl::openssl_x509_free);
         registry.register_function_with_by_ref(
             b"openssl_csr_new",
@@ -306,10 +316,12 @@ impl Extension for OpenSSLExtension {
         registry.register_function(b"openssl_pkcs7_decrypt", openssl::openssl_pkcs7_decrypt);
         registry.register_function(b"openssl_pkcs7_sign", openssl::openssl_pkcs7_sign);
         registry.register_function(b"openssl_pkcs7_verify", openssl::openssl_pkcs7_verify);
+        registry.register_function(b"openssl_pkcs7_read", openssl::openssl_pkcs7_read);
         registry.register_function(b"openssl_cms_encrypt", openssl::openssl_cms_encrypt);
         registry.register_function(b"openssl_cms_decrypt", openssl::openssl_cms_decrypt);
         registry.register_function(b"openssl_cms_sign", openssl::openssl_cms_sign);
         registry.register_function(b"openssl_cms_verify", openssl::openssl_cms_verify);
+        registry.register_function(b"openssl_cms_read", openssl::openssl_cms_read);
         registry.register_function(b"openssl_get_md_methods", openssl::openssl_get_md_methods);
         registry.register_function(
             b"openssl_get_cipher_methods",
@@ -1,22 +1,40 @@
 use crate::builtins::pdo;
+use crate::builtins::pdo::types::{ErrorMode, FetchConversion};
+use crate::core::value::Handle;
 use crate::runtime::context::RequestContext;
 use crate::runtime::extension::{Extension, ExtensionInfo, ExtensionResult};
 use crate::runtime::registry::ExtensionRegistry;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Extension-specific data for PDO module
 ///
 /// Resources (connections, statements) are managed via RequestContext::resource_manager.
-/// Only the driver registry is stored here as it's module-level state, not per-resource.
+/// The driver registry is module-level state; the per-connection tables hold the
+/// driver-independent PDO::ATTR_ERRMODE, PDO::ATTR_STRINGIFY_FETCHES and
+/// PDO::ATTR_ORACLE_NULLS settings (pdo_dbh_t in php-src).
 #[derive(Debug)]
 pub struct PdoExtensionData {
     pub driver_registry: Arc<pdo::drivers::DriverRegistry>,
+    /// PDO::ATTR_ERRMODE per connection resource ID
+    pub error_modes: HashMap<u64, ErrorMode>,
+    /// Fetched-value conversions per connection resource ID
+    pub fetch_conversions: HashMap<u64, FetchConversion>,
+    /// Owning connection resource ID per statement resource ID
+    pub statement_connections: HashMap<u64, u64>,
+    /// PDO::sqliteCreateFunction callbacks per connection resource ID, kept
+    /// here so the garbage collector treats them as roots
+    pub sql_functions: HashMap<u64, Vec<Handle>>,
 }
 
 impl Default for PdoExtensionData {
     fn default() -> Self {
         Self {
             driver_registry: Arc::new(pdo::drivers::DriverRegistry::new()),
+            error_modes: HashMap::new(),
+            fetch_conversions: HashMap::new(),
+            statement_connections: HashMap::new(),
+            sql_functions: HashMap::new(),
         }
     }
 }
@@ -79,7 +79,7 @@ impl Extension for ZlibExtension {
         // Register constants
         registry.register_constant(b"FORCE_GZIP", Val::Int(31));
         registry.register_constant(b"FORCE_DEFLATE", Val::Int(15));
-        registry.register_constant(b"ZLIB_ENCODING_RAW", Val::Int(-1));
+        registry.register_constant(b"ZLIB_ENCODING_RAW", Val::Int(-15));
         registry.register_constant(b"ZLIB_ENCODING_DEFLATE", Val::Int(15));
         registry.register_constant(b"ZLIB_ENCODING_GZIP", Val::Int(31));
 
@@ -19,13 +19,19 @@ impl VM {
         let method_sym = self.context.interner.intern(method_name);
         let class_name = self.extract_object_class(obj_handle)?;
 
-        let (user_func, _, _, defined_class) =
-            self.find_method(class_name, method_sym).ok_or_else(|| {
-                VmError::RuntimeError(format!(
-                    "ArrayAccess::{} not found",
-                    String::from_utf8_lossy(method_name)
-                ))
-            })?;
+        let Some((user_func, _, _, defined_class)) = self.find_method(class_name, method_sym)
+        else {
+            // Native classes such as SplFixedArray implement ArrayAccess in Rust
+            if self.find_native_method(class_name, method_sym).is_some() {
+                return self
+                    .call_method_with_args(obj_handle, method_sym, &args)
+                    .map(Some);
+            }
+            return Err(VmError::RuntimeError(format!(
+                "ArrayAccess::{} not found",
+                String::from_utf8_lossy(method_name)
+            )));
+        };
 
         self.invoke_user_method(obj_handle, user_func, args, defined_class, class_name)?;
         Ok(self.last_return_value.take())
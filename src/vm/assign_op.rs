@@ -1,5 +1,7 @@
 use crate::core::value::Val;
 use crate::vm::engine::VmError;
+use crate::vm::opcodes::arithmetic::{int_add, int_mul, int_pow, int_sub};
+use crate::vm::opcodes::bitwise::shift_int;
 use std::rc::Rc;
 
 /// Binary assignment operation types
@@ -81,7 +83,7 @@ impl AssignOpType {
 
     fn add(left: Val, right: Val) -> Result<Val, VmError> {
         match (left, right) {
-            (Val::Int(a), Val::Int(b)) => Ok(Val::Int(a.wrapping_add(b))),
+            (Val::Int(a), Val::Int(b)) => Ok(int_add(a, b)),
             (Val::Float(a), Val::Float(b)) => Ok(Val::Float(a + b)),
             (Val::Int(a), Val::Float(b)) => Ok(Val::Float(a as f64 + b)),
             (Val::Float(a), Val::Int(b)) => Ok(Val::Float(a + b as f64)),
@@ -99,7 +101,7 @@ impl AssignOpType {
 
     fn sub(left: Val, right: Val) -> Result<Val, VmError> {
         match (left, right) {
-            (Val::Int(a), Val::Int(b)) => Ok(Val::Int(a.wrapping_sub(b))),
+            (Val::Int(a), Val::Int(b)) => Ok(int_sub(a, b)),
             (Val::Float(a), Val::Float(b)) => Ok(Val::Float(a - b)),
             (Val::Int(a), Val::Float(b)) => Ok(Val::Float(a as f64 - b)),
             (Val::Float(a), Val::Int(b)) => Ok(Val::Float(a - b as f64)),
@@ -109,7 +111,7 @@ impl AssignOpType {
 
     fn mul(left: Val, right: Val) -> Result<Val, VmError> {
         match (left, right) {
-            (Val::Int(a), Val::Int(b)) => Ok(Val::Int(a.wrapping_mul(b))),
+            (Val::Int(a), Val::Int(b)) => Ok(int_mul(a, b)),
             (Val::Float(a), Val::Float(b)) => Ok(Val::Float(a * b)),
             (Val::Int(a), Val::Float(b)) => Ok(Val::Float(a as f64 * b)),
             (Val::Float(a), Val::Int(b)) => Ok(Val::Float(a * b as f64)),
@@ -192,50 +194,15 @@ impl AssignOpType {
         }
     }
 
+    /// Negative counts are rejected by VM::check_shift_count before this runs
     fn shift_left(left: Val, right: Val) -> Result<Val, VmError> {
-        match (left, right) {
-            (Val::Int(a), Val::Int(b)) => {
-                if b < 0 || b >= 64 {
-                    Ok(Val::Int(0))
-                } else {
-                    Ok(Val::Int(a.wrapping_shl(b as u32)))
-                }
-            }
-            (Val::Float(a), Val::Int(b)) => {
-                let a_int = a as i64;
-                if b < 0 {
-                    Ok(Val::Int(0))
-                } else if b >= 64 {
-                    Ok(Val::Int(0))
-                } else {
-                    Ok(Val::Int(a_int << b))
-                }
-            }
-            _ => Ok(Val::Int(0)),
-        }
+        let count = right.to_int().max(0);
+        Ok(Val::Int(shift_int(left.to_int(), count, false)))
     }
 
     fn shift_right(left: Val, right: Val) -> Result<Val, VmError> {
-        match (left, right) {
-            (Val::Int(a), Val::Int(b)) => {
-                if b < 0 || b >= 64 {
-                    Ok(Val::Int(if a < 0 { -1 } else { 0 }))
-                } else {
-                    Ok(Val::Int(a.wrapping_shr(b as u32)))
-                }
-            }
-            (Val::Float(a), Val::Int(b)) => {
-                let a_int = a as i64;
-                if b < 0 {
-                    Ok(Val::Int(0))
-                } else if b >= 64 {
-                    Ok(Val::Int(if a_int < 0 { -1 } else { 0 }))
-                } else {
-                    Ok(Val::Int(a_int >> b))
-                }
-            }
-            _ => Ok(Val::Int(0)),
-        }
+        let count = right.to_int().max(0);
+        Ok(Val::Int(shift_int(left.to_int(), count, true)))
     }
 
     fn concat(left: Val, right: Val) -> Result<Val, VmError> {
@@ -325,20 +292,7 @@ impl AssignOpType {
 
     fn pow(left: Val, right: Val) -> Result<Val, VmError> {
         match (left, right) {
-            (Val::Int(a), Val::Int(b)) => {
-                if b < 0 {
-                    // Negative exponent returns float
-                    Ok(Val::Float((a as f64).powf(b as f64)))
-                } else if b > u32::MAX as i64 {
-                    Ok(Val::Float((a as f64).powf(b as f64)))
-                } else {
-                    // Try to compute as int, fallback to float on overflow
-                    match a.checked_pow(b as u32) {
-                        Some(result) => Ok(Val::Int(result)),
-                        None => Ok(Val::Float((a as f64).powf(b as f64))),
-                    }
-                }
-            }
+            (Val::Int(a), Val::Int(b)) => Ok(int_pow(a, b)),
             (Val::Float(a), Val::Float(b)) => Ok(Val::Float(a.powf(b))),
             (Val::Int(a), Val::Float(b)) => Ok(Val::Float((a as f64).powf(b))),
             (Val::Float(a), Val::Int(b)) => Ok(Val::Float(a.powf(b as f64))),
@@ -10,7 +10,7 @@
 /// PHP Reference: $PHP_SRC_PATH/Zend/zend_execute.c - zend_call_function, zend_call_method
 /// PHP Reference: $PHP_SRC_PATH/Zend/zend_closures.c - closure invocation
 use super::engine::VM;
-use crate::compiler::chunk::ClosureData;
+use crate::compiler::chunk::{ClosureData, UserFunc};
 use crate::core::value::{ArrayKey, Handle, ObjectData, Symbol, Val};
 use crate::vm::engine::{PendingCall, VmError};
 use crate::vm::frame::{ArgList, CallFrame, GeneratorData, GeneratorState};
@@ -32,12 +32,38 @@ impl VM {
         let PendingCall {
             func_name,
             func_handle,
-            args,
+            mut args,
+            named_args,
             is_static: call_is_static,
             class_name,
             this_handle: call_this,
         } = call;
 
+        let mut extra_named = Vec::new();
+        if !named_args.is_empty() {
+            match self.resolve_call_target(func_name, func_handle, class_name) {
+                Some(func) => {
+                    extra_named = self.bind_named_args(&func, &mut args, named_args)?;
+                }
+                // Builtins expose no parameter names yet, so a named argument
+                // cannot be bound without guessing its position. A function
+                // that does not exist at all is left to the call to report.
+                None if class_name.is_some()
+                    || func_name.is_none_or(|name| self.is_builtin_function(name)) =>
+                {
+                    let message = format!(
+                        "Unknown named parameter ${}",
+                        String::from_utf8_lossy(
+                            self.context.interner.lookup(named_args[0].0).unwrap_or(b"")
+                        )
+                    );
+                    return Err(self.throw_error(b"Error", &message));
+                }
+                None => {}
+            }
+        }
+        let depth = self.frames.len();
+
         if let Some(name) = func_name {
             if let Some(class_name) = class_name {
                 // Method call: Class::method() or $obj->method()
@@ -61,9 +87,173 @@ impl VM {
                 "Dynamic function call not supported yet".into(),
             ));
         }
+
+        // Named arguments without a matching parameter are picked up by RecvVariadic
+        if !extra_named.is_empty()
+            && self.frames.len() > depth
+            && let Some(frame) = self.frames.last_mut()
+        {
+            frame.named_args = extra_named;
+        }
         Ok(())
     }
 
+    /// Whether `name` is a function registered by an extension
+    fn is_builtin_function(&self, name: Symbol) -> bool {
+        let name_bytes = self.context.interner.lookup(name).unwrap_or(b"");
+        self.context
+            .engine
+            .registry
+            .get_function(&name_bytes.to_ascii_lowercase())
+            .is_some()
+    }
+
+    /// Resolve the user function a pending call will dispatch to, if any
+    fn resolve_call_target(
+        &mut self,
+        func_name: Option<Symbol>,
+        func_handle: Option<Handle>,
+        class_name: Option<Symbol>,
+    ) -> Option<Rc<UserFunc>> {
+        if let Some(name) = func_name {
+            if let Some(class_name) = class_name {
+                return self.find_method(class_name, name).map(|(func, ..)| func);
+            }
+            return self.context.user_functions.get(&name).cloned();
+        }
+
+        match self.arena.get(func_handle?).value.clone() {
+            Val::String(s) => {
+                let sym = self.context.interner.intern(&s);
+                self.context.user_functions.get(&sym).cloned()
+            }
+            Val::Object(payload_handle) => {
+                let Val::ObjPayload(obj_data) = &self.arena.get(payload_handle).value else {
+                    return None;
+                };
+                if let Some(internal) = &obj_data.internal
+                    && let Some(closure) = internal.downcast_ref::<ClosureData>()
+                {
+                    return Some(closure.func.clone());
+                }
+                let class = obj_data.class;
+                let invoke_sym = self.context.interner.intern(b"__invoke");
+                self.find_method(class, invoke_sym).map(|(func, ..)| func)
+            }
+            Val::Array(map) => {
+                let (target, method) = match (map.map.get_index(0), map.map.get_index(1)) {
+                    (Some((_, t)), Some((_, m))) => (*t, *m),
+                    _ => return None,
+                };
+                let Val::String(method_name) = &self.arena.get(method).value else {
+                    return None;
+                };
+                let method_sym = self.context.interner.intern(method_name);
+                let class_sym = match &self.arena.get(target).value {
+                    Val::String(class_name) => {
+                        let class_name = class_name.clone();
+                        let sym = self.context.interner.intern(&class_name);
+                        self.resolve_class_name(sym).ok()?
+                    }
+                    Val::Object(payload_handle) => match &self.arena.get(*payload_handle).value {
+                        Val::ObjPayload(obj_data) => obj_data.class,
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                self.find_method(class_sym, method_sym)
+                    .map(|(func, ..)| func)
+            }
+            _ => None,
+        }
+    }
+
+    /// Bind named arguments to parameter positions.
+    ///
+    /// Skipped optional parameters are filled with an `Uninitialized` placeholder
+    /// so that RecvInit falls back to the default value. Names that match no
+    /// parameter are returned for a variadic parameter to collect.
+    /// Reference: $PHP_SRC_PATH/Zend/zend_execute.c - zend_handle_named_arg
+    fn bind_named_args(
+        &mut self,
+        func: &UserFunc,
+        args: &mut ArgList,
+        named_args: Vec<(Symbol, Handle)>,
+    ) -> Result<Vec<(Symbol, Handle)>, VmError> {
+        let has_variadic = func.params.iter().any(|p| p.is_variadic);
+        let positional = args.len();
+        let mut extra = Vec::new();
+
+        for (name, handle) in named_args {
+            let position = func
+                .params
+                .iter()
+                .position(|p| !p.is_variadic && p.name == name);
+            match position {
+                Some(idx) => {
+                    let overwrites = idx < positional
+                        || args.get(idx).is_some_and(|h| {
+                            !matches!(self.arena.get(*h).value, Val::Uninitialized)
+                        });
+                    if overwrites {
+                        let message = format!(
+                            "Named parameter ${} overwrites previous argument",
+                            String::from_utf8_lossy(
+                                self.context.interner.lookup(name).unwrap_or(b"")
+                            )
+                        );
+                        return Err(self.throw_error(b"Error", &message));
+                    }
+                    while args.len() <= idx {
+                        args.push(self.arena.alloc(Val::Uninitialized));
+                    }
+                    args[idx] = handle;
+                }
+                None if has_variadic => {
+                    if extra.iter().any(|(n, _)| *n == name) {
+                        let message = format!(
+                            "Named parameter ${} overwrites previous argument",
+                            String::from_utf8_lossy(
+                                self.context.interner.lookup(name).unwrap_or(b"")
+                            )
+                        );
+                        return Err(self.throw_error(b"Error", &message));
+                    }
+                    extra.push((name, handle));
+                }
+                None => {
+                    let message = format!(
+                        "Unknown named parameter ${}",
+                        String::from_utf8_lossy(self.context.interner.lookup(name).unwrap_or(b""))
+                    );
+                    return Err(self.throw_error(b"Error", &message));
+                }
+            }
+        }
+
+        // A skipped parameter without a default cannot be filled in
+        for (idx, handle) in args.iter().enumerate().skip(positional) {
+            let param = &func.params[idx];
+            if param.default_value.is_none()
+                && matches!(self.arena.get(*handle).value, Val::Uninitialized)
+            {
+                let message = format!(
+                    "{}(): Argument #{} (${}) not passed",
+                    String::from_utf8_lossy(
+                        self.context.interner.lookup(func.chunk.name).unwrap_or(b"")
+                    ),
+                    idx + 1,
+                    String::from_utf8_lossy(
+                        self.context.interner.lookup(param.name).unwrap_or(b"")
+                    )
+                );
+                return Err(self.throw_error(b"ArgumentCountError", &message));
+            }
+        }
+
+        Ok(extra)
+    }
+
     /// Invoke a method by class and method symbol
     /// Reference: $PHP_SRC_PATH/Zend/zend_execute.c - ZEND_INIT_METHOD_CALL
     #[inline]
@@ -104,6 +294,28 @@ impl VM {
             // Don't bind params here - let Recv/RecvInit opcodes handle it.
             self.push_frame(frame);
             Ok(())
+        } else if let Some(native_entry) = self.find_native_method(class_name, method_name) {
+            self.check_method_visibility(
+                native_entry.declaring_class,
+                native_entry.visibility,
+                Some(method_name),
+            )?;
+
+            // Set this in current frame temporarily for native method to access
+            let saved_this = self.frames.last().and_then(|f| f.this);
+            if let Some(frame) = self.frames.last_mut() {
+                frame.this = call_this;
+            }
+
+            let result =
+                (native_entry.handler)(self, &args).map_err(|e| self.native_call_error(e))?;
+
+            if let Some(frame) = self.frames.last_mut() {
+                frame.this = saved_this;
+            }
+
+            self.operand_stack.push(result);
+            Ok(())
         } else {
             let name_str =
                 String::from_utf8_lossy(self.context.interner.lookup(method_name).unwrap_or(b""));
@@ -153,7 +365,7 @@ impl VM {
             // Set caller's strict_types mode for builtin parameter validation
             // Reference: $PHP_SRC_PATH/Zend/zend_compile.h - ZEND_ARG_USES_STRICT_TYPES()
             self.builtin_call_strict = callsite_strict_types;
-            let res = handler(self, &args).map_err(VmError::RuntimeError)?;
+            let res = handler(self, &args).map_err(|e| self.native_call_error(e))?;
             self.builtin_call_strict = false; // Reset after call
             self.operand_stack.push(res);
             return Ok(());
@@ -301,7 +513,8 @@ impl VM {
                 // Set caller's strict_types mode for builtin parameter validation
                 self.builtin_call_strict = callsite_strict_types;
                 // Call native handler
-                let result = (native_entry.handler)(self, &args).map_err(VmError::RuntimeError)?;
+                let result =
+                    (native_entry.handler)(self, &args).map_err(|e| self.native_call_error(e))?;
                 self.builtin_call_strict = false; // Reset after call
 
                 // Restore previous this
@@ -407,7 +620,8 @@ impl VM {
                 native_entry.visibility,
                 Some(method_sym),
             )?;
-            let result = (native_entry.handler)(self, &args).map_err(VmError::RuntimeError)?;
+            let result =
+                (native_entry.handler)(self, &args).map_err(|e| self.native_call_error(e))?;
             self.operand_stack.push(result);
             return Ok(());
         }
@@ -465,7 +679,8 @@ impl VM {
                 }
 
                 // Call native handler
-                let result = (native_entry.handler)(self, &args).map_err(VmError::RuntimeError)?;
+                let result =
+                    (native_entry.handler)(self, &args).map_err(|e| self.native_call_error(e))?;
 
                 // Restore previous this
                 if let Some(frame) = self.frames.last_mut() {
@@ -7,6 +7,7 @@ use crate::compiler::chunk::UserFunc;
 use crate::core::value::{Symbol, Visibility};
 use crate::runtime::context::ClassDef;
 use crate::vm::engine::{VM, VmError};
+use std::collections::HashSet;
 use std::rc::Rc;
 
 /// Result of method lookup in inheritance chain
@@ -78,6 +79,34 @@ impl VM {
         })
     }
 
+    /// Check whether a method is declared by the class, its parents, the traits
+    /// they use, or any interface they implement (the `method_exists()` view)
+    /// Reference: $PHP_SRC_PATH/Zend/zend_builtin_functions.c - method_exists
+    pub(crate) fn class_has_method(&self, class_name: Symbol, method_name: Symbol) -> bool {
+        if self.find_method(class_name, method_name).is_some() {
+            return true;
+        }
+
+        let mut pending = Vec::new();
+        self.walk_inheritance_chain(class_name, |def, _| {
+            pending.extend(def.interfaces.iter().copied());
+            None::<()>
+        });
+        let mut seen = HashSet::new();
+        while let Some(interface) = pending.pop() {
+            if !seen.insert(interface) {
+                continue;
+            }
+            if self.find_method(interface, method_name).is_some() {
+                return true;
+            }
+            if let Some(def) = self.get_class_def(interface) {
+                pending.extend(def.interfaces.iter().copied());
+            }
+        }
+        false
+    }
+
     /// Find property in class hierarchy
     /// Reference: $PHP_SRC_PATH/Zend/zend_object_handlers.c - zend_std_get_property_ptr_ptr
     pub(crate) fn lookup_property(
@@ -280,6 +280,8 @@ pub struct PendingCall {
     pub func_name: Option<Symbol>,
     pub func_handle: Option<Handle>,
     pub args: ArgList,
+    /// Named arguments (`foo(name: $v)` or string keys unpacked via `...`)
+    pub named_args: Vec<(Symbol, Handle)>,
     pub is_static: bool,
     pub class_name: Option<Symbol>,
     pub this_handle: Option<Handle>,
@@ -327,6 +329,8 @@ pub struct VM {
     pub output_writer: Box<dyn OutputWriter>,
     pub error_handler: Box<dyn ErrorHandler>,
     pub output_buffers: Vec<crate::builtins::output_control::OutputBuffer>,
+    /// Active `zlib.output_compression` stream, started on the first output
+    pub output_compression: Option<crate::builtins::zlib::OutputCompression>,
     pub implicit_flush: bool,
     pub url_rewrite_vars: HashMap<Rc<Vec<u8>>, Rc<Vec<u8>>>,
     trace_includes: bool,
@@ -363,6 +367,9 @@ pub struct VM {
     last_error_location: Option<(String, u32)>,
     /// Cache for static property handles within this request's arena
     pub(crate) static_prop_handles: HashMap<(Symbol, Symbol), Handle>,
+    /// Exception object queued by a native handler via `throw_exception`,
+    /// raised as a catchable PHP exception once the handler returns
+    pending_exception: Option<Handle>,
 }
 
 impl VM {
@@ -388,6 +395,7 @@ impl VM {
         self.last_error_location = None;
         self.suppress_undefined_notice = false;
         self.builtin_call_strict = false;
+        self.pending_exception = None;
     }
 
     /// Collect all root handles from VM state for garbage collection.
@@ -413,6 +421,7 @@ impl VM {
                 roots.push(h);
             }
             roots.extend(frame.args.iter());
+            roots.extend(frame.named_args.iter().map(|(_, h)| *h));
         }
 
         // Context globals
@@ -437,6 +446,7 @@ impl VM {
                 roots.push(h);
             }
             roots.extend(pc.args.iter());
+            roots.extend(pc.named_args.iter().map(|(_, h)| *h));
         }
 
         // Variable handle map (the keys are handles)
@@ -447,6 +457,7 @@ impl VM {
 
         // Static property handles
         roots.extend(self.static_prop_handles.values());
+        roots.extend(self.pending_exception);
 
         // Context: autoloaders, callbacks, shutdown functions, error handler
         roots.extend(&self.context.autoloaders);
@@ -473,6 +484,16 @@ impl VM {
             }
         }
 
+        // User-defined SQL functions (PDO::sqliteCreateFunction callbacks)
+        if let Some(pdo) = self
+            .context
+            .get_extension_data::<crate::runtime::pdo_extension::PdoExtensionData>()
+        {
+            for callbacks in pdo.sql_functions.values() {
+                roots.extend(callbacks);
+            }
+        }
+
         // Constants that may contain object handles
         for val in self.context.constants.values() {
             val.trace_handles(&mut |h| roots.push(h));
@@ -586,6 +607,34 @@ impl VM {
         }
     }
 
+    /// Queue `ex_handle` to be thrown as a PHP exception when the running
+    /// native handler returns. The handler should propagate the returned
+    /// message as its `Err`, which is only surfaced if nothing catches it.
+    pub fn throw_exception(&mut self, ex_handle: Handle) -> String {
+        self.pending_exception = Some(ex_handle);
+        "Uncaught exception thrown by native function".into()
+    }
+
+    /// Map a native handler error to a `VmError`, promoting an exception
+    /// queued via `throw_exception` so it reaches PHP catch blocks.
+    pub(crate) fn native_call_error(&mut self, msg: String) -> VmError {
+        match self.pending_exception.take() {
+            Some(ex_handle) => {
+                self.set_exception_location(ex_handle);
+                VmError::Exception(ex_handle)
+            }
+            None => VmError::RuntimeError(msg),
+        }
+    }
+
+    /// Build a `class_name` error thrown from an opcode handler, for the
+    /// handler to return so the exception reaches PHP catch blocks
+    pub(crate) fn throw_error(&mut self, class_name: &[u8], message: &str) -> VmError {
+        let ex_handle = crate::builtins::exception::create_exception(self, class_name, message);
+        self.set_exception_location(ex_handle);
+        VmError::Exception(ex_handle)
+    }
+
     #[inline]
     fn method_lookup_key(&self, name: Symbol) -> Option<Symbol> {
         let name_bytes = self.context.interner.lookup(name)?;
@@ -972,6 +1021,7 @@ impl VM {
             output_writer: Box::new(StdoutWriter::default()),
             error_handler: Box::new(StderrErrorHandler::default()),
             output_buffers: Vec::new(),
+            output_compression: None,
             implicit_flush: false,
             url_rewrite_vars: HashMap::new(),
             trace_includes,
@@ -995,6 +1045,7 @@ impl VM {
             disable_classes: std::collections::HashSet::new(),
             last_error_location: None,
             static_prop_handles: HashMap::new(),
+            pending_exception: None,
         };
         vm.context.bind_memory_api(vm.arena.as_mut());
         vm.initialize_superglobals();
@@ -1211,21 +1262,79 @@ impl VM {
             Ok(())
         } else {
             // No buffering, write directly - this sends headers
-            self.context.headers_sent = true;
-            self.output_writer.write(bytes)
+            self.write_output_direct(bytes)
         }
     }
 
     pub(crate) fn write_output_direct(&mut self, bytes: &[u8]) -> Result<(), VmError> {
+        if self.output_compression.is_none() && !bytes.is_empty() && self.can_start_compression() {
+            self.start_output_compression()?;
+        }
+
         // Direct output always sends headers
         self.context.headers_sent = true;
-        self.output_writer.write(bytes)
+        match &mut self.output_compression {
+            Some(compression) => {
+                let compressed = compression.write(bytes, flate2::FlushCompress::None);
+                self.output_writer.write(&compressed)
+            }
+            None => self.output_writer.write(bytes),
+        }
     }
 
     pub fn flush_output(&mut self) -> Result<(), VmError> {
+        // flush() pushes out everything compressed so far
+        if let Some(compression) = &mut self.output_compression {
+            let pending = compression.write(&[], flate2::FlushCompress::Sync);
+            self.output_writer.write(&pending)?;
+        }
         self.output_writer.flush()
     }
 
+    /// Whether `zlib.output_compression` is on and would compress the next
+    /// output: either its stream is running or no headers were sent yet
+    pub fn output_compression_active(&self) -> bool {
+        self.output_compression.is_some() || self.can_start_compression()
+    }
+
+    fn can_start_compression(&self) -> bool {
+        !self.context.headers_sent && self.context.config.output_compression()
+    }
+
+    /// Begin the gzip stream for `zlib.output_compression`, announcing it in
+    /// the response headers
+    /// Reference: $PHP_SRC_PATH/ext/zlib/zlib.c - php_zlib_output_handler
+    fn start_output_compression(&mut self) -> Result<(), VmError> {
+        let level = self.context.config.output_compression_level();
+        let (compression, header) = crate::builtins::zlib::OutputCompression::start(level);
+        self.context.headers.retain(|entry| {
+            entry.key.as_deref() != Some(b"content-encoding".as_slice())
+                && entry.key.as_deref() != Some(b"content-length".as_slice())
+        });
+        let headers: [(&[u8], &[u8]); 2] = [
+            (b"content-encoding", b"Content-Encoding: gzip"),
+            (b"vary", b"Vary: Accept-Encoding"),
+        ];
+        for (key, line) in headers {
+            self.context.headers.push(HeaderEntry {
+                key: Some(key.to_vec()),
+                line: line.to_vec(),
+            });
+        }
+        self.output_compression = Some(compression);
+        self.context.headers_sent = true;
+        self.output_writer.write(&header)
+    }
+
+    /// Emit the end of the `zlib.output_compression` stream, including the
+    /// gzip trailer. Runs once all output buffers have been flushed.
+    pub fn finish_output_compression(&mut self) -> Result<(), VmError> {
+        match self.output_compression.take() {
+            Some(compression) => self.output_writer.write(&compression.finish()),
+            None => Ok(()),
+        }
+    }
+
     pub fn finish_request(&mut self) -> Result<(), VmError> {
         crate::builtins::output_control::flush_all_output_buffers(self)
             .map_err(VmError::RuntimeError)?;
@@ -1394,26 +1503,51 @@ impl VM {
         }
     }
 
-    fn resolve_script_path(&self, raw: &str) -> Result<PathBuf, VmError> {
-        let candidate = PathBuf::from(raw);
-        if candidate.is_absolute() {
-            return Ok(candidate);
+    /// Find an existing file the way PHP's include does: absolute paths and
+    /// `./`/`../` paths as given, anything else against each `include_path`
+    /// entry and then the directory of the currently executing script.
+    pub fn resolve_include_path(&self, filename: &str) -> Option<PathBuf> {
+        let candidate = PathBuf::from(filename);
+        let explicit = candidate.is_absolute()
+            || filename.starts_with("./")
+            || filename.starts_with("../")
+            || (cfg!(windows) && (filename.starts_with(".\\") || filename.starts_with("..\\")));
+        if explicit {
+            return candidate.exists().then_some(candidate);
         }
 
-        // 1. Try relative to the directory of the currently executing script
-        if let Some(frame) = self.frames.last() {
-            if let Some(file_path) = &frame.chunk.file_path {
-                let current_dir = Path::new(file_path).parent();
-                if let Some(dir) = current_dir {
-                    let resolved = dir.join(&candidate);
-                    if resolved.exists() {
-                        return Ok(resolved);
-                    }
-                }
+        for dir in self.context.config.include_paths() {
+            let resolved = dir.join(&candidate);
+            if resolved.exists() {
+                return Some(resolved);
             }
         }
 
-        // 2. Fallback to CWD
+        let script_dir = self
+            .frames
+            .last()
+            .and_then(|frame| frame.chunk.file_path.as_ref())
+            .and_then(|file_path| Path::new(file_path).parent());
+        if let Some(dir) = script_dir {
+            let resolved = dir.join(&candidate);
+            if resolved.exists() {
+                return Some(resolved);
+            }
+        }
+
+        None
+    }
+
+    fn resolve_script_path(&self, raw: &str) -> Result<PathBuf, VmError> {
+        if let Some(resolved) = self.resolve_include_path(raw) {
+            return Ok(resolved);
+        }
+
+        // Not found: report the failure against the CWD-relative path
+        let candidate = PathBuf::from(raw);
+        if candidate.is_absolute() {
+            return Ok(candidate);
+        }
         let cwd = std::env::current_dir()
             .map_err(|e| VmError::RuntimeError(format!("Failed to resolve path {}: {}", raw, e)))?;
         Ok(cwd.join(candidate))
@@ -1558,6 +1692,17 @@ impl VM {
         &mut self,
         obj_handle: Handle,
         method_name: Symbol,
+    ) -> Result<Handle, VmError> {
+        self.call_method_with_args(obj_handle, method_name, &[])
+    }
+
+    /// Like `call_method_simple`, passing `args` to the method. Visibility is not
+    /// checked, so native code can reach protected hooks such as SplHeap::compare()
+    pub(crate) fn call_method_with_args(
+        &mut self,
+        obj_handle: Handle,
+        method_name: Symbol,
+        args: &[Handle],
     ) -> Result<Handle, VmError> {
         let class_name = if let Val::Object(h) = self.arena.get(obj_handle).value {
             if let Val::ObjPayload(data) = &self.arena.get(h).value {
@@ -1583,6 +1728,7 @@ impl VM {
             frame.this = Some(obj_handle);
             frame.class_scope = Some(declaring_class);
             frame.called_scope = Some(class_name);
+            frame.args = args.iter().copied().collect();
             frame.stack_base = Some(self.operand_stack.len());
 
             let depth = self.frames.len();
@@ -1605,7 +1751,8 @@ impl VM {
             if let Some(frame) = self.frames.last_mut() {
                 frame.this = Some(obj_handle);
             }
-            let result = (native_entry.handler)(self, &[]).map_err(VmError::RuntimeError)?;
+            let result =
+                (native_entry.handler)(self, args).map_err(|e| self.native_call_error(e))?;
             if let Some(frame) = self.frames.last_mut() {
                 frame.this = saved_this;
             }
@@ -1629,6 +1776,121 @@ impl VM {
         )))
     }
 
+    /// Resolve the Iterator or Generator that iterating `handle` walks,
+    /// following IteratorAggregate::getIterator(). Returns None when `handle`
+    /// is not Traversable.
+    pub(crate) fn traversable_iterator(
+        &mut self,
+        mut handle: Handle,
+    ) -> Result<Option<Handle>, VmError> {
+        let iterator_sym = self.context.interner.intern(b"Iterator");
+        let aggregate_sym = self.context.interner.intern(b"IteratorAggregate");
+        let get_iterator_sym = self.context.interner.intern(b"getIterator");
+        loop {
+            if self.generator_data(handle).is_some() || self.is_instance_of(handle, iterator_sym)
+            {
+                return Ok(Some(handle));
+            }
+            if !self.is_instance_of(handle, aggregate_sym) {
+                return Ok(None);
+            }
+            handle = self.call_method_simple(handle, get_iterator_sym)?;
+        }
+    }
+
+    /// Step an iterator from `traversable_iterator` the way foreach does: the
+    /// first step rewinds it (starting a generator), later steps advance it.
+    /// Returns the current key and value, or None once it is exhausted.
+    pub(crate) fn traversable_step(
+        &mut self,
+        iterator: Handle,
+        started: bool,
+    ) -> Result<Option<(Handle, Handle)>, VmError> {
+        if let Some(gen_data) = self.generator_data(iterator) {
+            let created = matches!(gen_data.borrow().state, GeneratorState::Created(_));
+            if started || created {
+                self.resume_generator(iterator, &gen_data)?;
+            }
+            let data = gen_data.borrow();
+            return Ok(match (&data.state, data.current_key, data.current_val) {
+                (GeneratorState::Finished, ..) => None,
+                (_, Some(key), Some(val)) => Some((key, val)),
+                _ => None,
+            });
+        }
+
+        let step_sym = self
+            .context
+            .interner
+            .intern(if started { b"next" } else { b"rewind" });
+        let valid_sym = self.context.interner.intern(b"valid");
+        let current_sym = self.context.interner.intern(b"current");
+        let key_sym = self.context.interner.intern(b"key");
+
+        self.call_method_simple(iterator, step_sym)?;
+        let valid = self.call_method_simple(iterator, valid_sym)?;
+        if !self.arena.get(valid).value.to_bool() {
+            return Ok(None);
+        }
+        let val = self.call_method_simple(iterator, current_sym)?;
+        let key = self.call_method_simple(iterator, key_sym)?;
+        Ok(Some((key, val)))
+    }
+
+    fn generator_data(&self, handle: Handle) -> Option<Rc<RefCell<GeneratorData>>> {
+        let Val::Object(payload_handle) = self.arena.get(handle).value else {
+            return None;
+        };
+        let Val::ObjPayload(obj_data) = &self.arena.get(payload_handle).value else {
+            return None;
+        };
+        obj_data
+            .internal
+            .clone()?
+            .downcast::<RefCell<GeneratorData>>()
+            .ok()
+    }
+
+    /// Run a generator until its next yield or return, as IterNext does for
+    /// foreach, without disturbing the caller's return value
+    fn resume_generator(
+        &mut self,
+        gen_handle: Handle,
+        gen_data: &RefCell<GeneratorData>,
+    ) -> Result<(), VmError> {
+        let mut frame = {
+            let mut data = gen_data.borrow_mut();
+            match std::mem::replace(&mut data.state, GeneratorState::Running) {
+                GeneratorState::Created(frame) => frame,
+                GeneratorState::Suspended(frame) | GeneratorState::Delegating(frame) => {
+                    data.sent_val = Some(self.arena.alloc(Val::Null));
+                    frame
+                }
+                GeneratorState::Finished => {
+                    data.state = GeneratorState::Finished;
+                    return Ok(());
+                }
+                GeneratorState::Running => {
+                    return Err(VmError::RuntimeError(
+                        "Cannot resume an already running generator".into(),
+                    ));
+                }
+            }
+        };
+        frame.generator = Some(gen_handle);
+
+        let saved_return_value = self.last_return_value.take();
+        let depth = self.frames.len();
+        self.push_frame(frame);
+        let result = self.run_loop(depth);
+        self.last_return_value = saved_return_value;
+
+        if result.is_err() {
+            gen_data.borrow_mut().state = GeneratorState::Finished;
+        }
+        result
+    }
+
     pub fn collect_methods(&self, class_name: Symbol, caller_scope: Option<Symbol>) -> Vec<Symbol> {
         // Collect methods from entire inheritance chain
         // Reference: $PHP_SRC_PATH/Zend/zend_API.c - reflection functions
@@ -1675,9 +1937,12 @@ impl VM {
         visible
     }
 
+    /// Whether the class or an ancestor declares the property, static or not
     pub fn has_property(&self, class_name: Symbol, prop_name: Symbol) -> bool {
         self.walk_inheritance_chain(class_name, |def, _cls| {
-            if def.properties.contains_key(&prop_name) {
+            if def.properties.contains_key(&prop_name)
+                || def.static_properties.contains_key(&prop_name)
+            {
                 Some(true)
             } else {
                 None
@@ -2746,12 +3011,16 @@ impl VM {
         false
     }
 
-    fn handle_exception(&mut self, ex_handle: Handle) -> bool {
+    /// Unwind to the nearest matching catch block in frames at or above
+    /// `floor`. Frames below it belong to whoever started this run loop (for
+    /// example a native function calling back into PHP), so an uncaught
+    /// exception is returned to that caller instead of jumping into them.
+    fn handle_exception(&mut self, ex_handle: Handle, floor: usize) -> bool {
         // Validate that the exception is a Throwable
         let throwable_sym = self.context.interner.intern(b"Throwable");
         if !self.is_instance_of(ex_handle, throwable_sym) {
             // Not a valid exception object - this shouldn't happen if Throw validates properly
-            self.frames.clear();
+            self.frames.truncate(floor);
             return false;
         }
 
@@ -2759,7 +3028,7 @@ impl VM {
         let mut finally_blocks = Vec::new(); // Track finally blocks to execute
 
         // Unwind stack, collecting finally blocks
-        while frame_idx > 0 {
+        while frame_idx > floor {
             frame_idx -= 1;
 
             let (ip, chunk) = {
@@ -2819,7 +3088,7 @@ impl VM {
         // In PHP, finally blocks execute from innermost to outermost
         // We've already collected them in the correct order during iteration
         self.execute_finally_blocks(&finally_blocks);
-        self.frames.clear();
+        self.frames.truncate(floor);
         false
     }
 
@@ -2983,18 +3252,24 @@ impl VM {
         // Extract return type info AND the callee's strict_types flag
         let return_type_check = {
             let frame = self.current_frame()?;
-            frame.func.as_ref().and_then(|f| {
-                f.return_type.as_ref().map(|rt| {
-                    let func_name = self
-                        .context
-                        .interner
-                        .lookup(f.chunk.name)
-                        .map(|b| String::from_utf8_lossy(b).to_string())
-                        .unwrap_or_else(|| "unknown".to_string());
-                    let callee_strict = f.chunk.strict_types;
-                    (rt.clone(), func_name, callee_strict)
+            // A generator's declared type describes the Generator object,
+            // not the value its body returns
+            frame
+                .func
+                .as_ref()
+                .filter(|f| !f.is_generator)
+                .and_then(|f| {
+                    f.return_type.as_ref().map(|rt| {
+                        let func_name = self
+                            .context
+                            .interner
+                            .lookup(f.chunk.name)
+                            .map(|b| String::from_utf8_lossy(b).to_string())
+                            .unwrap_or_else(|| "unknown".to_string());
+                        let callee_strict = f.chunk.strict_types;
+                        (rt.clone(), func_name, callee_strict)
+                    })
                 })
-            })
         };
 
         if let Some((ret_type, func_name, callee_strict)) = return_type_check {
@@ -3011,20 +3286,22 @@ impl VM {
                         let val_type = self.get_type_name(ret_val);
                         let expected_type = self.return_type_to_string(&ret_type);
 
-                        return Err(VmError::RuntimeError(format!(
+                        let message = format!(
                             "{}(): Return value must be of type {}, {} returned",
                             func_name, expected_type, val_type
-                        )));
+                        );
+                        return Err(self.throw_error(b"TypeError", &message));
                     }
                 } else {
                     // Strict mode: throw TypeError
                     let val_type = self.get_type_name(ret_val);
                     let expected_type = self.return_type_to_string(&ret_type);
 
-                    return Err(VmError::RuntimeError(format!(
+                    let message = format!(
                         "{}(): Return value must be of type {}, {} returned",
                         func_name, expected_type, val_type
-                    )));
+                    );
+                    return Err(self.throw_error(b"TypeError", &message));
                 }
             }
         }
@@ -3097,7 +3374,7 @@ impl VM {
     }
 
     pub fn run(&mut self, chunk: Rc<CodeChunk>) -> Result<(), VmError> {
-        let mut initial_frame = CallFrame::new(chunk);
+        let mut initial_frame = CallFrame::new(chunk.clone());
 
         // Inject globals into the top-level frame locals
         for (symbol, handle) in &self.context.globals {
@@ -3105,12 +3382,30 @@ impl VM {
         }
 
         self.push_frame(initial_frame);
+        self.report_compile_warnings(&chunk);
         let result = self.run_loop(0);
         self.run_header_callback();
         self.run_shutdown_functions();
         result
     }
 
+    /// Report the warnings the compiler collected for a file that is about to run
+    fn report_compile_warnings(&mut self, chunk: &CodeChunk) {
+        if chunk.compile_warnings.is_empty() {
+            return;
+        }
+        let file = chunk
+            .file_path
+            .clone()
+            .unwrap_or_else(|| "Unknown".to_string());
+        let saved_location = self.last_error_location.take();
+        for (line, warning) in &chunk.compile_warnings {
+            self.last_error_location = Some((file.clone(), *line));
+            self.report_error(ErrorLevel::Warning, warning);
+        }
+        self.last_error_location = saved_location;
+    }
+
     fn run_header_callback(&mut self) {
         // Only call the callback if headers haven't been sent yet
         if self.context.headers_sent {
@@ -3373,7 +3668,7 @@ impl VM {
             if let Err(e) = res {
                 match e {
                     VmError::Exception(h) => {
-                        if !self.handle_exception(h) {
+                        if !self.handle_exception(h, target_depth) {
                             return Err(VmError::Exception(h));
                         }
                     }
@@ -3489,6 +3784,42 @@ impl VM {
         Ok(())
     }
 
+    fn set_exception_location(&mut self, ex_handle: Handle) {
+        // Set exception properties (file, line, trace) at throw time
+        // This mimics PHP's behavior of capturing context when exception is thrown
+        let file_sym = self.context.interner.intern(b"file");
+        let line_sym = self.context.interner.intern(b"line");
+
+        // Get current file and line from frame
+        let (file_path, line_no) = if let Some(frame) = self.frames.last() {
+            let file = frame
+                .chunk
+                .file_path
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string());
+            let line = if frame.ip > 0 && frame.ip <= frame.chunk.lines.len() {
+                frame.chunk.lines[frame.ip - 1]
+            } else {
+                0
+            };
+            (file, line)
+        } else {
+            ("unknown".to_string(), 0)
+        };
+
+        // Allocate property values first
+        let file_val = self.arena.alloc(Val::String(file_path.into_bytes().into()));
+        let line_val = self.arena.alloc(Val::Int(line_no as i64));
+
+        // Now mutate the object to set file and line
+        if let Val::Object(payload_handle) = self.arena.get(ex_handle).value
+            && let Val::ObjPayload(ref mut obj_data) = self.arena.get_mut(payload_handle).value
+        {
+            obj_data.properties.insert(file_sym, file_val);
+            obj_data.properties.insert(line_sym, line_val);
+        }
+    }
+
     fn exec_throw(&mut self) -> Result<(), VmError> {
         let ex_handle = self
             .operand_stack
@@ -3533,38 +3864,7 @@ impl VM {
             )));
         }
 
-        // Set exception properties (file, line, trace) at throw time
-        // This mimics PHP's behavior of capturing context when exception is thrown
-        let file_sym = self.context.interner.intern(b"file");
-        let line_sym = self.context.interner.intern(b"line");
-
-        // Get current file and line from frame
-        let (file_path, line_no) = if let Some(frame) = self.frames.last() {
-            let file = frame
-                .chunk
-                .file_path
-                .clone()
-                .unwrap_or_else(|| "unknown".to_string());
-            let line = if frame.ip > 0 && frame.ip <= frame.chunk.lines.len() {
-                frame.chunk.lines[frame.ip - 1]
-            } else {
-                0
-            };
-            (file, line)
-        } else {
-            ("unknown".to_string(), 0)
-        };
-
-        // Allocate property values first
-        let file_val = self.arena.alloc(Val::String(file_path.into_bytes().into()));
-        let line_val = self.arena.alloc(Val::Int(line_no as i64));
-
-        // Now mutate the object to set file and line
-        let payload = self.arena.get_mut(payload_handle);
-        if let Val::ObjPayload(ref mut obj_data) = payload.value {
-            obj_data.properties.insert(file_sym, file_val);
-            obj_data.properties.insert(line_sym, line_val);
-        }
+        self.set_exception_location(ex_handle);
 
         Err(VmError::Exception(ex_handle))
     }
@@ -3595,9 +3895,7 @@ impl VM {
                     self.operand_stack.push(this_val);
                     return Ok(());
                 }
-                return Err(VmError::RuntimeError(
-                    "Using $this when not in object context".into(),
-                ));
+                return Err(self.throw_error(b"Error", "Using $this when not in object context"));
             }
         }
 
@@ -3883,6 +4181,7 @@ impl VM {
                         VmError::RuntimeError(format!("Invalid assign op: {}", op))
                     })?;
 
+                    self.check_shift_count(op_type, &val)?;
                     let res = op_type.apply(current_val, val)?;
 
                     self.arena.get_mut(var_handle).value = res.clone();
@@ -4563,7 +4862,11 @@ impl VM {
                         // Get arg_handle first
                         let has_arg = {
                             let frame = self.frames.last().unwrap();
-                            (arg_idx as usize) < frame.args.len()
+                            // Parameters skipped by named arguments hold an
+                            // Uninitialized placeholder
+                            frame.args.get(arg_idx as usize).is_some_and(|h| {
+                                !matches!(self.arena.get(*h).value, Val::Uninitialized)
+                            })
                         };
 
                         if has_arg {
@@ -4575,6 +4878,7 @@ impl VM {
                                     arg_handle,
                                     param_type,
                                     callsite_strict,
+                                    arg_idx as usize + 1,
                                     param.name,
                                     &func_name_str,
                                 )?
@@ -4618,7 +4922,11 @@ impl VM {
                         // Check if arg was supplied
                         let has_arg = {
                             let frame = self.frames.last().unwrap();
-                            (arg_idx as usize) < frame.args.len()
+                            // Parameters skipped by named arguments hold an
+                            // Uninitialized placeholder
+                            frame.args.get(arg_idx as usize).is_some_and(|h| {
+                                !matches!(self.arena.get(*h).value, Val::Uninitialized)
+                            })
                         };
 
                         if has_arg {
@@ -4630,6 +4938,7 @@ impl VM {
                                     arg_handle,
                                     param_type,
                                     callsite_strict,
+                                    arg_idx as usize + 1,
                                     param.name,
                                     &func_name_str,
                                 )?
@@ -4670,11 +4979,18 @@ impl VM {
                             .unwrap_or(b"?"),
                     )
                     .to_string();
-                    let args: Vec<Handle> = if frame.args.len() > arg_idx as usize {
-                        frame.args[arg_idx as usize..].to_vec()
-                    } else {
-                        vec![]
-                    };
+                    let mut args: Vec<(ArrayKey, Handle)> = frame
+                        .args
+                        .iter()
+                        .skip(arg_idx as usize)
+                        .enumerate()
+                        .map(|(i, h)| (ArrayKey::Int(i as i64), *h))
+                        .collect();
+                    // Unknown named arguments are collected with string keys
+                    for (name, handle) in &frame.named_args {
+                        let key = self.context.interner.lookup(*name).unwrap_or(b"");
+                        args.push((ArrayKey::Str(Rc::new(key.to_vec())), *handle));
+                    }
                     (
                         frame.func.clone(),
                         frame.callsite_strict_types,
@@ -4688,8 +5004,8 @@ impl VM {
                         let param = func.params[arg_idx as usize].clone();
                         let mut arr = IndexMap::new();
 
-                        for (i, handle) in args_to_check.iter().enumerate() {
-                            let mut arg_handle = *handle;
+                        for (i, (key, handle)) in args_to_check.into_iter().enumerate() {
+                            let mut arg_handle = handle;
 
                             // Type check each variadic argument
                             if let Some(ref param_type) = param.param_type {
@@ -4697,6 +5013,7 @@ impl VM {
                                     arg_handle,
                                     param_type,
                                     callsite_strict,
+                                    arg_idx as usize + i + 1,
                                     param.name,
                                     &func_name_str,
                                 )?;
@@ -4706,11 +5023,11 @@ impl VM {
                                 if !self.arena.get(arg_handle).is_ref {
                                     self.arena.get_mut(arg_handle).is_ref = true;
                                 }
-                                arr.insert(ArrayKey::Int(i as i64), arg_handle);
+                                arr.insert(key, arg_handle);
                             } else {
                                 let val = self.arena.get(arg_handle).value.clone();
                                 let h = self.arena.alloc(val);
-                                arr.insert(ArrayKey::Int(i as i64), h);
+                                arr.insert(key, h);
                             }
                         }
                         let arr_handle = self.arena.alloc(Val::Array(ArrayData::from(arr).into()));
@@ -5201,26 +5518,27 @@ impl VM {
                 let canonical_path = Self::canonical_path_string(&resolved_path);
 
                 let arena = bumpalo::Bump::new();
-                let lexer = crate::parser::lexer::Lexer::new(&source);
+                let lexer = crate::parser::lexer::Lexer::new(&source)
+                    .with_short_open_tag(self.context.config.short_open_tag());
                 let mut parser = crate::parser::parser::Parser::new(lexer, &arena);
                 let program = parser.parse_program();
 
-                if !program.errors.is_empty() {
+                if let Some(error) = program.errors.first() {
                     return Err(VmError::RuntimeError(format!(
-                        "Parse errors: {:?}",
-                        program.errors
+                        "Parse error: {}",
+                        error.to_php_message(&source, &canonical_path)
                     )));
                 }
 
                 let emitter =
                     crate::compiler::emitter::Emitter::new(&source, &mut self.context.interner)
                         .with_file_path(canonical_path.clone());
-                let (chunk, _) = emitter.compile(program.statements);
+                let chunk = Rc::new(emitter.compile(program.statements).0);
 
                 // PHP shares the same symbol_table between caller and included code (Zend VM ref).
                 // We clone locals, run the include, then copy them back to persist changes.
                 let caller_frame_idx = self.frames.len() - 1;
-                let mut frame = CallFrame::new(Rc::new(chunk));
+                let mut frame = CallFrame::new(chunk.clone());
 
                 // Include inherits full scope (this, class_scope, called_scope) and symbol table
                 if let Some(caller) = self.frames.get(caller_frame_idx) {
@@ -5231,6 +5549,7 @@ impl VM {
                 }
 
                 self.push_frame(frame);
+                self.report_compile_warnings(&chunk);
                 let depth = self.frames.len();
 
                 // Execute the included file (inlining run_loop to capture locals before pop)
@@ -5481,27 +5800,22 @@ impl VM {
 
             OpCode::AssignDim => self.exec_assign_dim()?,
 
-            OpCode::AssignDimRef => {
-                let val_handle = self
-                    .operand_stack
-                    .pop()
-                    .ok_or(VmError::RuntimeError("Stack underflow".into()))?;
-                let key_handle = self
+            OpCode::AssignDimRef(depth) => {
+                let ref_handle = self
                     .operand_stack
                     .pop()
                     .ok_or(VmError::RuntimeError("Stack underflow".into()))?;
+                let keys = self.pop_n_operands(depth as usize)?;
                 let array_handle = self
                     .operand_stack
                     .pop()
                     .ok_or(VmError::RuntimeError("Stack underflow".into()))?;
 
-                self.assign_dim(array_handle, key_handle, val_handle)?;
-
-                // assign_dim pushes the new array handle.
-                let new_array_handle = self.operand_stack.pop().unwrap();
+                let new_array_handle =
+                    self.assign_nested_dim_ref(array_handle, &keys, ref_handle)?;
 
-                // We want to return [Val, NewArray] so that we can StoreVar(NewArray) and leave Val.
-                self.operand_stack.push(val_handle);
+                // Leave [Ref, NewArray] so that StoreVar(NewArray) leaves the Ref
+                self.operand_stack.push(ref_handle);
                 self.operand_stack.push(new_array_handle);
             }
 
@@ -5838,10 +6152,20 @@ impl VM {
 
             OpCode::IterInit(target) => {
                 // Stack: [Array/Object]
-                let iterable_handle = self
+                let mut iterable_handle = self
                     .operand_stack
                     .peek()
                     .ok_or(VmError::RuntimeError("Stack underflow".into()))?;
+
+                // An IteratorAggregate is walked through the iterator it hands out
+                if let Val::Object(_) = self.arena.get(iterable_handle).value
+                    && let Some(iterator) = self.traversable_iterator(iterable_handle)?
+                    && iterator != iterable_handle
+                {
+                    self.operand_stack.pop();
+                    self.operand_stack.push(iterator);
+                    iterable_handle = iterator;
+                }
                 let iterable_val = &self.arena.get(iterable_handle).value;
 
                 match iterable_val {
@@ -6853,14 +7177,19 @@ impl VM {
                 }
             }
             OpCode::UseTrait(class_name, trait_name) => {
-                let trait_methods = if let Some(trait_def) = self.context.classes.get(&trait_name) {
-                    if !trait_def.is_trait {
-                        return Err(VmError::RuntimeError("Not a trait".into()));
-                    }
-                    trait_def.methods.clone()
-                } else {
-                    return Err(VmError::RuntimeError("Trait not found".into()));
-                };
+                let (trait_methods, trait_props, trait_static_props) =
+                    if let Some(trait_def) = self.context.classes.get(&trait_name) {
+                        if !trait_def.is_trait {
+                            return Err(VmError::RuntimeError("Not a trait".into()));
+                        }
+                        (
+                            trait_def.methods.clone(),
+                            trait_def.properties.clone(),
+                            trait_def.static_properties.clone(),
+                        )
+                    } else {
+                        return Err(VmError::RuntimeError("Trait not found".into()));
+                    };
 
                 // Collect information about already-used traits BEFORE the mutable borrow
                 let existing_traits_and_methods: Vec<(Symbol, Vec<Symbol>)> =
@@ -6883,6 +7212,14 @@ impl VM {
                 if let Some(class_def) = self.context.classes.get_mut(&class_name) {
                     class_def.traits.push(trait_name);
 
+                    // Trait properties are copied into the class unless it declares its own
+                    for (name, prop) in trait_props {
+                        class_def.properties.entry(name).or_insert(prop);
+                    }
+                    for (name, prop) in trait_static_props {
+                        class_def.static_properties.entry(name).or_insert(prop);
+                    }
+
                     // Track conflicts for error reporting
                     let mut conflicts = Vec::new();
 
@@ -7475,7 +7812,7 @@ impl VM {
 
                             // Call native handler
                             let _result = (native_entry.handler)(self, &args)
-                                .map_err(VmError::RuntimeError)?;
+                                .map_err(|e| self.native_call_error(e))?;
 
                             // Restore previous this
                             if let Some(frame) = self.frames.last_mut() {
@@ -8102,9 +8439,9 @@ impl VM {
                 if let Some(this_handle) = frame.this {
                     self.operand_stack.push(this_handle);
                 } else {
-                    return Err(VmError::RuntimeError(
-                        "Using $this when not in object context".into(),
-                    ));
+                    return Err(
+                        self.throw_error(b"Error", "Using $this when not in object context")
+                    );
                 }
             }
             OpCode::FetchGlobals => {
@@ -8234,6 +8571,7 @@ impl VM {
                     func_name: Some(name_sym),
                     func_handle: None,
                     args: ArgList::new(),
+                    named_args: Vec::new(),
                     is_static: false,
                     class_name: None,
                     this_handle: None,
@@ -8254,6 +8592,7 @@ impl VM {
                     func_name: Some(name_sym),
                     func_handle: None,
                     args: ArgList::new(),
+                    named_args: Vec::new(),
                     is_static: false,
                     class_name: None,
                     this_handle: None,
@@ -8272,6 +8611,7 @@ impl VM {
                             func_name: Some(sym),
                             func_handle: Some(callable_handle),
                             args: ArgList::new(),
+                            named_args: Vec::new(),
                             is_static: false,
                             class_name: None,
                             this_handle: None,
@@ -8280,14 +8620,25 @@ impl VM {
                     Val::Object(payload_handle) => {
                         let payload_val = self.arena.get(payload_handle);
                         if let Val::ObjPayload(obj_data) = &payload_val.value {
-                            let invoke = self.context.interner.intern(b"__invoke");
+                            let is_closure = obj_data
+                                .internal
+                                .as_ref()
+                                .is_some_and(|internal| internal.is::<ClosureData>());
+                            // Closures are invoked directly via invoke_callable_value
+                            let (func_name, class_name, this_handle) = if is_closure {
+                                (None, None, None)
+                            } else {
+                                let invoke = self.context.interner.intern(b"__invoke");
+                                (Some(invoke), Some(obj_data.class), Some(callable_handle))
+                            };
                             self.pending_calls.push(PendingCall {
-                                func_name: Some(invoke),
+                                func_name,
                                 func_handle: Some(callable_handle),
                                 args: ArgList::new(),
+                                named_args: Vec::new(),
                                 is_static: false,
-                                class_name: Some(obj_data.class),
-                                this_handle: Some(callable_handle),
+                                class_name,
+                                this_handle,
                             });
                         } else {
                             return Err(VmError::RuntimeError(
@@ -8300,6 +8651,7 @@ impl VM {
                             func_name: None,
                             func_handle: Some(callable_handle),
                             args: ArgList::new(),
+                            named_args: Vec::new(),
                             is_static: false,
                             class_name: None,
                             this_handle: None,
@@ -8343,20 +8695,50 @@ impl VM {
                     .operand_stack
                     .pop()
                     .ok_or(VmError::RuntimeError("Stack underflow".into()))?;
+                if self.pending_calls.is_empty() {
+                    return Err(VmError::RuntimeError("No pending call".into()));
+                }
+                let map = match &self.arena.get(array_handle).value {
+                    Val::Array(map) => map.clone(),
+                    _ => {
+                        return Err(VmError::RuntimeError(
+                            "Argument unpack expects array".into(),
+                        ));
+                    }
+                };
+                // Reference: $PHP_SRC_PATH/Zend/zend_vm_def.h - ZEND_SEND_UNPACK
+                for (key, handle) in map.map.iter() {
+                    let call = self
+                        .pending_calls
+                        .last_mut()
+                        .ok_or(VmError::RuntimeError("No pending call".into()))?;
+                    match key {
+                        ArrayKey::Int(_) => {
+                            if !call.named_args.is_empty() {
+                                return Err(self.throw_error(
+                                    b"Error",
+                                    "Cannot use positional argument after named argument during unpacking",
+                                ));
+                            }
+                            call.args.push(*handle);
+                        }
+                        ArrayKey::Str(name) => {
+                            let sym = self.context.interner.intern(name);
+                            call.named_args.push((sym, *handle));
+                        }
+                    }
+                }
+            }
+            OpCode::SendNamed(name) => {
+                let val_handle = self
+                    .operand_stack
+                    .pop()
+                    .ok_or(VmError::RuntimeError("Stack underflow".into()))?;
                 let call = self
                     .pending_calls
                     .last_mut()
                     .ok_or(VmError::RuntimeError("No pending call".into()))?;
-                let arr_val = self.arena.get(array_handle);
-                if let Val::Array(map) = &arr_val.value {
-                    for (_, handle) in map.map.iter() {
-                        call.args.push(*handle);
-                    }
-                } else {
-                    return Err(VmError::RuntimeError(
-                        "Argument unpack expects array".into(),
-                    ));
-                }
+                call.named_args.push((name, val_handle));
             }
             OpCode::DoFcall | OpCode::DoFcallByName | OpCode::DoIcall | OpCode::DoUcall => {
                 let call = self
@@ -8941,6 +9323,7 @@ impl VM {
                     func_name: Some(name_sym),
                     func_handle: None,
                     args: ArgList::new(),
+                    named_args: Vec::new(),
                     is_static: false,
                     class_name: None, // Will be resolved from object
                     this_handle: Some(obj_handle),
@@ -8988,6 +9371,7 @@ impl VM {
                     func_name: Some(name_sym),
                     func_handle: None,
                     args: ArgList::new(),
+                    named_args: Vec::new(),
                     is_static: true,
                     class_name: Some(resolved_class),
                     this_handle: None,
@@ -9190,7 +9574,7 @@ impl VM {
                 let result = if type_val == 0 {
                     // Isset
                     if let Some(h) = val_handle {
-                        !matches!(self.arena.get(h).value, Val::Null)
+                        !matches!(self.arena.get(h).value, Val::Null | Val::Uninitialized)
                     } else {
                         false
                     }
@@ -9199,7 +9583,7 @@ impl VM {
                     if let Some(h) = val_handle {
                         let val = &self.arena.get(h).value;
                         match val {
-                            Val::Null => true,
+                            Val::Null | Val::Uninitialized => true,
                             Val::Bool(b) => !b,
                             Val::Int(i) => *i == 0,
                             Val::Float(f) => *f == 0.0,
@@ -9363,7 +9747,7 @@ impl VM {
                 let result = if type_val == 0 {
                     // Isset
                     if let Some(h) = val_handle {
-                        !matches!(self.arena.get(h).value, Val::Null)
+                        !matches!(self.arena.get(h).value, Val::Null | Val::Uninitialized)
                     } else {
                         false
                     }
@@ -9372,7 +9756,7 @@ impl VM {
                     if let Some(h) = val_handle {
                         let val = &self.arena.get(h).value;
                         match val {
-                            Val::Null => true,
+                            Val::Null | Val::Uninitialized => true,
                             Val::Bool(b) => !b,
                             Val::Int(i) => *i == 0,
                             Val::Float(f) => *f == 0.0,
@@ -9490,6 +9874,7 @@ impl VM {
                 let op_type = AssignOpType::from_u8(op)
                     .ok_or_else(|| VmError::RuntimeError(format!("Invalid assign op: {}", op)))?;
 
+                self.check_shift_count(op_type, &val)?;
                 let res = op_type.apply(current_val.clone(), val)?;
 
                 if let Some(class_def) = self.context.classes.get_mut(&defining_class) {
@@ -10504,9 +10889,11 @@ impl VM {
                                 if let Some(val_handle) = obj_data.properties.get(&prop_name) {
                                     (
                                         obj_data.class,
+                                        // Declared typed properties that were never
+                                        // assigned are not set, and skip __isset
                                         Some(!matches!(
                                             self.arena.get(*val_handle).value,
-                                            Val::Null
+                                            Val::Null | Val::Uninitialized
                                         )),
                                     )
                                 } else {
@@ -11051,15 +11438,20 @@ impl VM {
         wrapped_source.extend_from_slice(code.as_bytes());
 
         let arena = bumpalo::Bump::new();
-        let lexer = crate::parser::lexer::Lexer::new(&wrapped_source);
+        let lexer = crate::parser::lexer::Lexer::new(&wrapped_source)
+            .with_short_open_tag(self.context.config.short_open_tag());
         let mut parser = crate::parser::parser::Parser::new(lexer, &arena);
         let program = parser.parse_program();
 
-        if !program.errors.is_empty() {
+        if let Some(error) = program.errors.first() {
             // Eval error: in PHP 7+ throws ParseError
+            let (caller_file, caller_line) = self
+                .compute_current_location()
+                .unwrap_or_else(|| ("Unknown".to_string(), 0));
+            let eval_file = format!("{}({}) : eval()'d code", caller_file, caller_line);
             return Err(VmError::RuntimeError(format!(
-                "Eval parse errors: {:?}",
-                program.errors
+                "Parse error: {}",
+                error.to_php_message(&wrapped_source, &eval_file)
             )));
         }
 
@@ -11073,10 +11465,10 @@ impl VM {
         let emitter =
             crate::compiler::emitter::Emitter::new(&wrapped_source, &mut self.context.interner)
                 .with_inherited_strict_types(caller_strict);
-        let (chunk, _) = emitter.compile(program.statements);
+        let chunk = Rc::new(emitter.compile(program.statements).0);
 
         let caller_frame_idx = self.frames.len() - 1;
-        let mut frame = CallFrame::new(Rc::new(chunk));
+        let mut frame = CallFrame::new(chunk.clone());
         if let Some(caller) = self.frames.get(caller_frame_idx) {
             frame.locals = caller.locals.clone();
             frame.this = caller.this;
@@ -11085,6 +11477,7 @@ impl VM {
         }
 
         self.push_frame(frame);
+        self.report_compile_warnings(&chunk);
         let depth = self.frames.len();
         let stack_before_eval = self.operand_stack.len();
 
@@ -11147,9 +11540,7 @@ impl VM {
 
         let source_res = std::fs::read(&resolved_path);
         match source_res {
-            Ok(source) => {
-                self.execute_include_file(&source, path_str, &canonical_path, inserted_once_guard)
-            }
+            Ok(source) => self.execute_include_file(&source, &canonical_path, inserted_once_guard),
             Err(e) => {
                 if inserted_once_guard {
                     self.context.included_files.remove(&canonical_path);
@@ -11171,12 +11562,12 @@ impl VM {
     fn execute_include_file(
         &mut self,
         source: &[u8],
-        path_str: &str,
         canonical_path: &str,
         inserted_once_guard: bool,
     ) -> Result<(), VmError> {
         let arena = bumpalo::Bump::new();
-        let lexer = crate::parser::lexer::Lexer::new(source);
+        let lexer = crate::parser::lexer::Lexer::new(source)
+            .with_short_open_tag(self.context.config.short_open_tag());
         let mut parser = crate::parser::parser::Parser::new(lexer, &arena);
         let program = parser.parse_program();
 
@@ -11185,17 +11576,17 @@ impl VM {
                 self.context.included_files.remove(canonical_path);
             }
             return Err(VmError::RuntimeError(format!(
-                "Parse errors in {}: {:?}",
-                path_str, program.errors
+                "Parse error: {}",
+                program.errors[0].to_php_message(source, canonical_path)
             )));
         }
 
         let emitter = crate::compiler::emitter::Emitter::new(source, &mut self.context.interner)
             .with_file_path(canonical_path.to_string());
-        let (chunk, _) = emitter.compile(program.statements);
+        let chunk = Rc::new(emitter.compile(program.statements).0);
 
         let caller_frame_idx = self.frames.len() - 1;
-        let mut frame = CallFrame::new(Rc::new(chunk));
+        let mut frame = CallFrame::new(chunk.clone());
         // Include inherits full scope
         if let Some(caller) = self.frames.get(caller_frame_idx) {
             frame.locals = caller.locals.clone();
@@ -11205,6 +11596,7 @@ impl VM {
         }
 
         self.push_frame(frame);
+        self.report_compile_warnings(&chunk);
         let depth = self.frames.len();
         let target_depth = depth - 1; // Target is caller's depth
 
@@ -11383,6 +11775,15 @@ impl VM {
         Ok(())
     }
 
+    /// Error thrown when an append finds the next index (after PHP_INT_MAX) occupied
+    /// Reference: $PHP_SRC_PATH/Zend/zend_execute.c - zend_cannot_add_element
+    fn next_element_occupied_error(&mut self) -> VmError {
+        self.throw_error(
+            b"Error",
+            "Cannot add element to the array as the next element is already occupied",
+        )
+    }
+
     /// Note: Array append now uses O(1) ArrayData::push() instead of O(n) index computation
     /// Reference: $PHP_SRC_PATH/Zend/zend_hash.c - zend_hash_next_free_element
 
@@ -11405,7 +11806,9 @@ impl VM {
 
             if let Val::Array(map) = &mut array_zval_mut.value {
                 // Use O(1) push method instead of O(n) index computation
-                Rc::make_mut(map).push(val_handle);
+                if !Rc::make_mut(map).push(val_handle) {
+                    return Err(self.next_element_occupied_error());
+                }
             } else {
                 return Err(VmError::RuntimeError(format!(
                     "Cannot use scalar as array (type: {})",
@@ -11423,7 +11826,9 @@ impl VM {
 
             if let Val::Array(ref mut map) = new_val {
                 // Use O(1) push method instead of O(n) index computation
-                Rc::make_mut(map).push(val_handle);
+                if !Rc::make_mut(map).push(val_handle) {
+                    return Err(self.next_element_occupied_error());
+                }
             } else {
                 return Err(VmError::RuntimeError(format!(
                     "Cannot use scalar as array (type: {})",
@@ -11446,11 +11851,22 @@ impl VM {
         // We need to traverse down, creating copies if necessary (COW),
         // then update the bottom, then reconstruct the path up.
 
-        let new_handle = self.assign_nested_recursive(array_handle, keys, val_handle)?;
+        let new_handle = self.assign_nested_recursive(array_handle, keys, val_handle, false)?;
         self.operand_stack.push(new_handle);
         Ok(())
     }
 
+    /// Store a reference into a nested array slot, returning the updated array
+    /// Reference: $PHP_SRC_PATH/Zend/zend_execute.c - zend_assign_to_variable_reference
+    pub(crate) fn assign_nested_dim_ref(
+        &mut self,
+        array_handle: Handle,
+        keys: &[Handle],
+        ref_handle: Handle,
+    ) -> Result<Handle, VmError> {
+        self.assign_nested_recursive(array_handle, keys, ref_handle, true)
+    }
+
     pub(crate) fn unset_nested_dim(
         &mut self,
         array_handle: Handle,
@@ -11567,11 +11983,14 @@ impl VM {
         Ok(current_handle)
     }
 
+    /// With `bind_ref` the last slot is rebound to `val_handle` (`$a[..] = &$x`)
+    /// instead of writing through a reference already stored there.
     fn assign_nested_recursive(
         &mut self,
         current_handle: Handle,
         keys: &[Handle],
         val_handle: Handle,
+        bind_ref: bool,
     ) -> Result<Handle, VmError> {
         if keys.is_empty() {
             return Ok(val_handle);
@@ -11598,8 +12017,12 @@ impl VM {
                             self.call_array_access_offset_get(current_handle, first_key)?;
 
                         // Recurse on the intermediate value
-                        let new_intermediate =
-                            self.assign_nested_recursive(intermediate, remaining_keys, val_handle)?;
+                        let new_intermediate = self.assign_nested_recursive(
+                            intermediate,
+                            remaining_keys,
+                            val_handle,
+                            bind_ref,
+                        )?;
 
                         // If the intermediate value changed, call offsetSet to update it
                         if new_intermediate != intermediate {
@@ -11652,10 +12075,13 @@ impl VM {
             let key = if let Some(k) = key {
                 k
             } else {
-                // Compute next auto-index using O(1) next_index()
+                // Compute next auto-index using O(1) next_free_index()
                 let current_zval = self.arena.get(current_handle);
                 if let Val::Array(map) = &current_zval.value {
-                    ArrayKey::Int(map.next_index())
+                    match map.next_free_index() {
+                        Some(index) => ArrayKey::Int(index),
+                        None => return Err(self.next_element_occupied_error()),
+                    }
                 } else {
                     return Err(VmError::RuntimeError(format!(
                         "Cannot use scalar as array (type: {})",
@@ -11691,7 +12117,7 @@ impl VM {
                     let current_zval = self.arena.get(current_handle);
                     if let Val::Array(map) = &current_zval.value {
                         map.map.get(&key).and_then(|&h| {
-                            if self.arena.get(h).is_ref {
+                            if self.arena.get(h).is_ref && !bind_ref {
                                 Some(h)
                             } else {
                                 None
@@ -11747,8 +12173,12 @@ impl VM {
                     empty_handle
                 };
 
-                let new_next_handle =
-                    self.assign_nested_recursive(next_handle, remaining_keys, val_handle)?;
+                let new_next_handle = self.assign_nested_recursive(
+                    next_handle,
+                    remaining_keys,
+                    val_handle,
+                    bind_ref,
+                )?;
 
                 // Only update if changed (if next_handle is a ref, it's mutated in place)
                 if new_next_handle != next_handle {
@@ -11775,8 +12205,11 @@ impl VM {
             // Resolve key
             let key_val = &self.arena.get(key_handle).value;
             let key = if let Val::AppendPlaceholder = key_val {
-                // Use O(1) next_index() instead of O(n) computation
-                ArrayKey::Int(map_mut.next_index())
+                // Use O(1) next_free_index() instead of O(n) computation
+                match map_mut.next_free_index() {
+                    Some(index) => ArrayKey::Int(index),
+                    None => return Err(self.next_element_occupied_error()),
+                }
             } else {
                 self.array_key_from_value(key_val)?
             };
@@ -11785,7 +12218,7 @@ impl VM {
                 // We are at the last key.
                 let mut updated_ref = false;
                 if let Some(existing_handle) = map_mut.map.get(&key) {
-                    if self.arena.get(*existing_handle).is_ref {
+                    if self.arena.get(*existing_handle).is_ref && !bind_ref {
                         // Update Ref value
                         let new_val = self.arena.get(val_handle).value.clone();
                         self.arena.get_mut(*existing_handle).value = new_val;
@@ -11805,8 +12238,12 @@ impl VM {
                     self.arena.alloc(Val::Array(ArrayData::new().into()))
                 };
 
-                let new_next_handle =
-                    self.assign_nested_recursive(next_handle, remaining_keys, val_handle)?;
+                let new_next_handle = self.assign_nested_recursive(
+                    next_handle,
+                    remaining_keys,
+                    val_handle,
+                    bind_ref,
+                )?;
                 map_mut.insert(key, new_next_handle);
             }
         } else {
@@ -11925,14 +12362,7 @@ impl VM {
             Val::Int(i) => Ok(ArrayKey::Int(*i)),
             Val::Bool(b) => Ok(ArrayKey::Int(if *b { 1 } else { 0 })),
             Val::Float(f) => Ok(ArrayKey::Int(*f as i64)),
-            Val::String(s) => {
-                if let Ok(text) = std::str::from_utf8(s) {
-                    if let Ok(int_val) = text.parse::<i64>() {
-                        return Ok(ArrayKey::Int(int_val));
-                    }
-                }
-                Ok(ArrayKey::Str(s.clone()))
-            }
+            Val::String(s) => Ok(ArrayKey::from_string(s.clone())),
             Val::Null => Ok(ArrayKey::Str(Rc::new(Vec::new()))),
             Val::Object(payload_handle) => Err(VmError::RuntimeError(format!(
                 "TypeError: Cannot access offset of type {} on array",
@@ -12152,6 +12582,7 @@ impl VM {
         arg_handle: Handle,
         param_type: &ReturnType,
         strict: bool,
+        arg_num: usize,
         param_name: Symbol,
         func_name: &str,
     ) -> Result<Handle, VmError> {
@@ -12162,38 +12593,24 @@ impl VM {
             return Ok(arg_handle);
         }
 
-        // Type doesn't match - decide whether to coerce or error
-        if strict {
-            // Strict mode: no coercion, throw TypeError
-            let param_name_str =
-                String::from_utf8_lossy(self.context.interner.lookup(param_name).unwrap_or(b"?"));
-            let expected = self.return_type_name(param_type);
-            let val_type = self.arena.get(arg_handle).value.type_name();
-            return Err(VmError::RuntimeError(format!(
-                "{}(): Argument #{} (${}
-) must be of type {}, {} given",
-                func_name, param_name_str, param_name_str, expected, val_type
-            )));
+        // Type doesn't match: weak mode may still coerce scalars
+        if !strict && let Some(coerced) = self.coerce_parameter_value(arg_handle, param_type)? {
+            return Ok(coerced);
         }
 
-        // Weak mode: attempt coercion for scalar types
-        let coerced = self.coerce_parameter_value(arg_handle, param_type)?;
-        if let Some(coerced_handle) = coerced {
-            Ok(coerced_handle)
-        } else {
-            // Coercion failed - emit warning and use original value
-            let param_name_str =
-                String::from_utf8_lossy(self.context.interner.lookup(param_name).unwrap_or(b"?"));
-            let expected = self.return_type_name(param_type);
-            let val_type = self.arena.get(arg_handle).value.type_name();
-            let message = format!(
-                "{}(): Argument #{} (${}
-) must be of type {}, {} given",
-                func_name, param_name_str, param_name_str, expected, val_type
-            );
-            self.trigger_error(ErrorLevel::Warning, &message);
-            Ok(arg_handle)
-        }
+        let param_name_str =
+            String::from_utf8_lossy(self.context.interner.lookup(param_name).unwrap_or(b"?"));
+        let expected = self.return_type_name(param_type);
+        // PHP names the class of an object argument
+        let val_type = match &self.arena.get(arg_handle).value {
+            Val::Object(payload) => self.describe_object_class(*payload),
+            val => val.type_name().to_string(),
+        };
+        let message = format!(
+            "{}(): Argument #{} (${}) must be of type {}, {} given",
+            func_name, arg_num, param_name_str, expected, val_type
+        );
+        Err(self.throw_error(b"TypeError", &message))
     }
 
     /// Attempt to coerce a parameter value to the expected type (weak mode only)
@@ -12587,7 +13004,8 @@ impl VM {
             }
 
             // Call native handler
-            let result = (native_entry.handler)(self, &args).map_err(VmError::RuntimeError)?;
+            let result =
+                (native_entry.handler)(self, &args).map_err(|e| self.native_call_error(e))?;
 
             // Restore previous this
             if let Some(frame) = self.frames.last_mut() {
@@ -12769,7 +13187,8 @@ impl VM {
                 }
             }
 
-            let result = (native_entry.handler)(self, &args).map_err(VmError::RuntimeError)?;
+            let result =
+                (native_entry.handler)(self, &args).map_err(|e| self.native_call_error(e))?;
 
             if let Some(frame) = self.frames.last_mut() {
                 frame.this = saved_this;
@@ -13013,6 +13432,10 @@ impl VM {
                 .unwrap_or_else(|| format!("{:?}", method_name));
 
             if !class_def.methods.contains_key(method_name) {
+                // Abstract classes may leave interface methods to their subclasses
+                if class_def.is_abstract {
+                    continue;
+                }
                 return Err(VmError::RuntimeError(format!(
                     "Class {} contains 1 abstract method and must therefore be declared abstract or implement the remaining method ({}::{})",
                     class_name_str, iface_name_str, method_name_str
@@ -13754,6 +14177,7 @@ mod tests {
             func_name: None,
             func_handle: Some(callable_handle),
             args,
+            named_args: Vec::new(),
             is_static: false,
             class_name: None,
             this_handle: None,
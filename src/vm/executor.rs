@@ -69,6 +69,8 @@ pub struct ExecutionConfig {
     pub disable_functions: std::collections::HashSet<String>,
     /// Sandboxing: disabled class names (like PHP's disable_classes ini)
     pub disable_classes: std::collections::HashSet<String>,
+    /// INI settings applied before the code is parsed (e.g. short_open_tag)
+    pub ini_settings: HashMap<String, String>,
 }
 
 impl Default for ExecutionConfig {
@@ -85,6 +87,7 @@ impl Default for ExecutionConfig {
             allowed_functions: None, // All functions allowed by default
             disable_functions: std::collections::HashSet::new(), // No functions disabled by default
             disable_classes: std::collections::HashSet::new(), // No classes disabled by default
+            ini_settings: HashMap::new(),
         }
     }
 }
@@ -127,27 +130,32 @@ pub fn execute_code_with_config(
 ) -> Result<ExecutionResult, VmError> {
     let start = std::time::Instant::now();
 
+    // Create execution context
+    let engine_context = crate::runtime::context::EngineBuilder::new()
+        .with_core_extensions()
+        .build()
+        .map_err(|e| VmError::RuntimeError(format!("Failed to build engine: {}", e)))?;
+    let mut request_context = RequestContext::new(engine_context);
+    request_context
+        .config
+        .ini_settings
+        .extend(config.ini_settings);
+
     // Parse the code
     let arena = bumpalo::Bump::new();
-    let lexer = crate::parser::lexer::Lexer::new(source.as_bytes());
+    let lexer = crate::parser::lexer::Lexer::new(source.as_bytes())
+        .with_short_open_tag(request_context.config.short_open_tag());
     let mut parser = crate::parser::parser::Parser::new(lexer, &arena);
     let program = parser.parse_program();
 
     // Check for parse errors
-    if !program.errors.is_empty() {
+    if let Some(error) = program.errors.first() {
         return Err(VmError::RuntimeError(format!(
-            "Parse errors: {:?}",
-            program.errors
+            "Parse error: {}",
+            error.to_php_message(source.as_bytes(), "Unknown")
         )));
     }
 
-    // Create execution context
-    let engine_context = crate::runtime::context::EngineBuilder::new()
-        .with_core_extensions()
-        .build()
-        .map_err(|e| VmError::RuntimeError(format!("Failed to build engine: {}", e)))?;
-    let mut request_context = RequestContext::new(engine_context);
-
     // Apply configuration - set execution timeout
     if config.timeout_ms > 0 {
         request_context.config.max_execution_time =
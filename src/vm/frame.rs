@@ -20,6 +20,9 @@ pub struct CallFrame {
     pub generator: Option<Handle>,
     pub discard_return: bool,
     pub args: ArgList,
+    /// Named arguments with no matching parameter, collected by a variadic
+    /// parameter as string keys.
+    pub named_args: Vec<(Symbol, Handle)>,
     /// Caller-side strict typing mode (declare(strict_types=1) in the *calling* file).
     /// This controls scalar parameter/return coercion.
     pub callsite_strict_types: bool,
@@ -41,6 +44,7 @@ impl CallFrame {
             generator: None,
             discard_return: false,
             args: ArgList::new(),
+            named_args: Vec::new(),
             callsite_strict_types: false,
             stack_base: None,
             pending_finally: None,
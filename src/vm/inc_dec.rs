@@ -2,6 +2,7 @@
 /// Reference: $PHP_SRC_PATH/Zend/zend_operators.c - increment_function/decrement_function
 use crate::core::value::Val;
 use crate::vm::engine::{ErrorHandler, ErrorLevel, VmError};
+use crate::vm::opcodes::arithmetic::{int_add, int_sub};
 use std::rc::Rc;
 
 /// Increment a value in-place, following PHP semantics
@@ -9,13 +10,7 @@ use std::rc::Rc;
 pub fn increment_value(val: Val, error_handler: &mut dyn ErrorHandler) -> Result<Val, VmError> {
     match val {
         // INT: increment by 1, overflow to float
-        Val::Int(i) => {
-            if i == i64::MAX {
-                Ok(Val::Float(i as f64 + 1.0))
-            } else {
-                Ok(Val::Int(i + 1))
-            }
-        }
+        Val::Int(i) => Ok(int_add(i, 1)),
 
         // FLOAT: increment by 1.0
         Val::Float(f) => Ok(Val::Float(f + 1.0)),
@@ -56,13 +51,7 @@ pub fn increment_value(val: Val, error_handler: &mut dyn ErrorHandler) -> Result
 pub fn decrement_value(val: Val, error_handler: &mut dyn ErrorHandler) -> Result<Val, VmError> {
     match val {
         // INT: decrement by 1, underflow to float
-        Val::Int(i) => {
-            if i == i64::MIN {
-                Ok(Val::Float(i as f64 - 1.0))
-            } else {
-                Ok(Val::Int(i - 1))
-            }
-        }
+        Val::Int(i) => Ok(int_sub(i, 1)),
 
         // FLOAT: decrement by 1.0
         Val::Float(f) => Ok(Val::Float(f - 1.0)),
@@ -126,11 +115,7 @@ fn increment_string(s: Rc<Vec<u8>>, error_handler: &mut dyn ErrorHandler) -> Res
 
         // Try as integer
         if let Ok(i) = trimmed.parse::<i64>() {
-            if i == i64::MAX {
-                return Ok(Val::Float(i as f64 + 1.0));
-            } else {
-                return Ok(Val::Int(i + 1));
-            }
+            return Ok(int_add(i, 1));
         }
 
         // Try as float
@@ -219,11 +204,7 @@ fn decrement_string(s: Rc<Vec<u8>>, error_handler: &mut dyn ErrorHandler) -> Res
 
         // Try as integer
         if let Ok(i) = trimmed.parse::<i64>() {
-            if i == i64::MIN {
-                return Ok(Val::Float(i as f64 - 1.0));
-            } else {
-                return Ok(Val::Int(i - 1));
-            }
+            return Ok(int_sub(i, 1));
         }
 
         // Try as float
@@ -47,7 +47,7 @@ pub enum OpCode {
     StoreVar(Symbol),   // Pop value, store in local
     StoreVarDynamic,    // [Val, Name] -> [Val] (Stores Val in Name, pushes Val)
     AssignRef(Symbol),  // Pop value (handle), mark as ref, store in local
-    AssignDimRef,       // [Array, Index, ValueRef] -> Assigns ref to array index
+    AssignDimRef(u8),   // [Array, Key_n..Key_1, Ref] -> [Ref, NewArray]. Arg is depth
     MakeVarRef(Symbol), // Convert local var to reference (COW if needed), push handle
     MakeRef,            // Convert top of stack to reference
     UnsetVar(Symbol),
@@ -296,6 +296,7 @@ pub enum OpCode {
     FastRet,
     RecvVariadic(u32),
     SendUnpack,
+    SendNamed(Symbol), // Pop value, pass as named argument
     CopyTmp,
     FuncNumArgs,
     FuncGetArgs,
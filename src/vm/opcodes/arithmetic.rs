@@ -33,6 +33,37 @@ use crate::core::value::Val;
 use crate::vm::engine::{ErrorLevel, VM, VmError};
 use std::rc::Rc;
 
+/// `$a + $b` on integers, promoted to float on overflow
+/// Reference: $PHP_SRC_PATH/Zend/zend_operators.h - fast_long_add_function
+pub(crate) fn int_add(a: i64, b: i64) -> Val {
+    a.checked_add(b)
+        .map_or_else(|| Val::Float(a as f64 + b as f64), Val::Int)
+}
+
+/// `$a - $b` on integers, promoted to float on overflow
+/// Reference: $PHP_SRC_PATH/Zend/zend_operators.h - fast_long_sub_function
+pub(crate) fn int_sub(a: i64, b: i64) -> Val {
+    a.checked_sub(b)
+        .map_or_else(|| Val::Float(a as f64 - b as f64), Val::Int)
+}
+
+/// `$a * $b` on integers, promoted to float on overflow
+/// Reference: $PHP_SRC_PATH/Zend/zend_multiply.h - ZEND_SIGNED_MULTIPLY_LONG
+pub(crate) fn int_mul(a: i64, b: i64) -> Val {
+    a.checked_mul(b)
+        .map_or_else(|| Val::Float(a as f64 * b as f64), Val::Int)
+}
+
+/// `$a ** $b` on integers: exact while the result fits, float for negative
+/// exponents and on overflow
+/// Reference: $PHP_SRC_PATH/Zend/zend_operators.c - pow_function_base
+pub(crate) fn int_pow(a: i64, b: i64) -> Val {
+    match u32::try_from(b).ok().and_then(|exp| a.checked_pow(exp)) {
+        Some(result) => Val::Int(result),
+        None => Val::Float((a as f64).powf(b as f64)),
+    }
+}
+
 /// Arithmetic operation types
 /// Reference: $PHP_SRC_PATH/Zend/zend_operators.c
 #[derive(Debug, Clone, Copy)]
@@ -46,13 +77,17 @@ enum ArithOp {
 }
 
 impl ArithOp {
-    fn apply_int(&self, a: i64, b: i64) -> Option<i64> {
+    /// Result on two integers, or None when the operation has to be done in
+    /// floats
+    fn apply_int(&self, a: i64, b: i64) -> Option<Val> {
         match self {
-            ArithOp::Add => Some(a.wrapping_add(b)),
-            ArithOp::Sub => Some(a.wrapping_sub(b)),
-            ArithOp::Mul => Some(a.wrapping_mul(b)),
-            ArithOp::Mod if b != 0 => Some(a % b),
-            _ => None, // Div/Pow always use float, Mod checks zero
+            ArithOp::Add => Some(int_add(a, b)),
+            ArithOp::Sub => Some(int_sub(a, b)),
+            ArithOp::Mul => Some(int_mul(a, b)),
+            ArithOp::Pow => Some(int_pow(a, b)),
+            // PHP_INT_MIN % -1 is 0 rather than an overflow
+            ArithOp::Mod if b != 0 => Some(Val::Int(a.wrapping_rem(b))),
+            _ => None, // Div always uses float, Mod checks zero
         }
     }
 
@@ -68,7 +103,7 @@ impl ArithOp {
     }
 
     fn always_float(&self) -> bool {
-        matches!(self, ArithOp::Div | ArithOp::Pow)
+        matches!(self, ArithOp::Div)
     }
 }
 
@@ -113,7 +148,7 @@ impl VM {
         let result = if needs_float {
             Val::Float(op.apply_float(a_val.to_float(), b_val.to_float()))
         } else if let Some(int_result) = op.apply_int(a_val.to_int(), b_val.to_int()) {
-            Val::Int(int_result)
+            int_result
         } else {
             Val::Float(op.apply_float(a_val.to_float(), b_val.to_float()))
         };
@@ -350,12 +385,8 @@ mod tests {
 
         let result = vm.operand_stack.pop().unwrap();
         let result_val = vm.arena.get(result);
-        // Power always returns float in PHP
-        if let Val::Float(f) = result_val.value {
-            assert!((f - 1024.0).abs() < 0.0001);
-        } else {
-            panic!("Expected float for power operation");
-        }
+        // Int ** non-negative int stays an int while it fits
+        assert!(matches!(result_val.value, Val::Int(1024)));
     }
 
     #[test]
@@ -432,4 +463,23 @@ mod tests {
         let result_val = vm.arena.get(result);
         assert!(matches!(result_val.value, Val::Int(-42)));
     }
+
+    #[test]
+    fn test_int_overflow_promotes_to_float() {
+        let engine = Arc::new(EngineContext::new());
+        let mut vm = VM::new(engine);
+
+        // PHP_INT_MAX * 2 no longer fits in an int
+        let left = vm.arena.alloc(Val::Int(i64::MAX));
+        let right = vm.arena.alloc(Val::Int(2));
+
+        vm.operand_stack.push(left);
+        vm.operand_stack.push(right);
+
+        vm.exec_mul().unwrap();
+
+        let result = vm.operand_stack.pop().unwrap();
+        let result_val = vm.arena.get(result);
+        assert_eq!(result_val.value, Val::Float(i64::MAX as f64 * 2.0));
+    }
 }
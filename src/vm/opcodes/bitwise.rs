@@ -7,7 +7,6 @@
 //! Bitwise operations work on integers:
 //! - Operands are converted to integers via type juggling
 //! - Results are always integers (or strings for string bitwise ops)
-//! - Shift operations use modulo for shift amounts
 //!
 //! ## Operations
 //!
@@ -22,8 +21,8 @@
 //! ## Special Cases
 //!
 //! - String bitwise operations work character-by-character
-//! - Shift amounts > 63 are reduced via modulo
-//! - Negative shift amounts cause undefined behavior in PHP
+//! - Shifting by 64 or more yields 0 (or -1 when right-shifting a negative)
+//! - Negative shift amounts throw ArithmeticError
 //!
 //! ## Performance
 //!
@@ -38,6 +37,18 @@
 use crate::core::value::Val;
 use crate::vm::engine::{VM, VmError};
 
+/// Shift by a non-negative count: 64 bits or more shift everything out,
+/// leaving 0, or -1 for an arithmetic right shift of a negative value
+/// Reference: $PHP_SRC_PATH/Zend/zend_operators.c - shift_left_function
+pub(crate) fn shift_int(value: i64, count: i64, is_shr: bool) -> i64 {
+    match (is_shr, count >= 64) {
+        (true, true) => value >> 63,
+        (false, true) => 0,
+        (true, false) => value >> count,
+        (false, false) => value << count,
+    }
+}
+
 impl VM {
     /// Generic binary bitwise operation using AssignOpType
     /// Reference: $PHP_SRC_PATH/Zend/zend_operators.c
@@ -64,25 +75,29 @@ impl VM {
         let shift_amount = b_val.to_int();
         let value = a_val.to_int();
 
-        let result = if shift_amount < 0 || shift_amount >= 64 {
-            if is_shr {
-                Val::Int(value >> 63)
-            } else {
-                Val::Int(0)
-            }
-        } else {
-            if is_shr {
-                Val::Int(value.wrapping_shr(shift_amount as u32))
-            } else {
-                Val::Int(value.wrapping_shl(shift_amount as u32))
-            }
-        };
+        if shift_amount < 0 {
+            return Err(self.throw_error(b"ArithmeticError", "Bit shift by negative number"));
+        }
 
+        let result = Val::Int(shift_int(value, shift_amount, is_shr));
         let res_handle = self.arena.alloc(result);
         self.operand_stack.push(res_handle);
         Ok(())
     }
 
+    /// Compound shifts (`<<=`, `>>=`) reject negative counts like `<<` and `>>`
+    pub(crate) fn check_shift_count(
+        &mut self,
+        op_type: crate::vm::assign_op::AssignOpType,
+        count: &Val,
+    ) -> Result<(), VmError> {
+        use crate::vm::assign_op::AssignOpType;
+        if matches!(op_type, AssignOpType::Sl | AssignOpType::Sr) && count.to_int() < 0 {
+            return Err(self.throw_error(b"ArithmeticError", "Bit shift by negative number"));
+        }
+        Ok(())
+    }
+
     /// Execute BitwiseAnd operation: $result = $left & $right
     /// Reference: $PHP_SRC_PATH/Zend/zend_operators.c - bitwise_and_function
     #[inline]
@@ -391,6 +406,43 @@ mod tests {
         assert!(matches!(result_val.value, Val::Int(1024)));
     }
 
+    #[test]
+    fn test_shift_wider_than_int() {
+        let engine = Arc::new(EngineContext::new());
+        let mut vm = VM::new(engine);
+
+        // 1 << 64 = 0, -8 >> 64 = -1
+        for (value, is_shr, expected) in [(1, false, 0), (-8, true, -1), (8, true, 0)] {
+            let left = vm.arena.alloc(Val::Int(value));
+            let right = vm.arena.alloc(Val::Int(64));
+            vm.operand_stack.push(left);
+            vm.operand_stack.push(right);
+
+            if is_shr {
+                vm.exec_shift_right().unwrap();
+            } else {
+                vm.exec_shift_left().unwrap();
+            }
+
+            let result = vm.operand_stack.pop().unwrap();
+            assert_eq!(vm.arena.get(result).value, Val::Int(expected));
+        }
+    }
+
+    #[test]
+    fn test_shift_by_negative_count_throws() {
+        let engine = Arc::new(EngineContext::new());
+        let mut vm = VM::new(engine);
+
+        let left = vm.arena.alloc(Val::Int(1));
+        let right = vm.arena.alloc(Val::Int(-1));
+
+        vm.operand_stack.push(left);
+        vm.operand_stack.push(right);
+
+        assert!(matches!(vm.exec_shift_left(), Err(VmError::Exception(_))));
+    }
+
     #[test]
     fn test_bitwise_operations_with_zero() {
         let engine = Arc::new(EngineContext::new());
@@ -36,7 +36,7 @@
 //! - Zend: `$PHP_SRC_PATH/Zend/zend_operators.c` - compare_function
 //! - PHP Manual: https://www.php.net/manual/en/language.operators.comparison.php
 
-use crate::core::value::Val;
+use crate::core::value::{Handle, Val};
 use crate::vm::engine::{VM, VmError};
 
 impl VM {
@@ -98,6 +98,11 @@ impl VM {
         self.binary_cmp(|a, b| php_compare(a, b) >= 0)
     }
 
+    /// Compare two values as `<=>` does, for native code that orders values
+    pub(crate) fn compare_values(&self, a: Handle, b: Handle) -> i64 {
+        php_compare(&self.arena.get(a).value, &self.arena.get(b).value)
+    }
+
     /// Execute Spaceship operation: $result = $left <=> $right
     /// Returns -1, 0, or 1
     /// Reference: $PHP_SRC_PATH/Zend/zend_operators.c - compare_function
@@ -365,6 +365,33 @@ fn test_array_sum_product() {
     }
 }
 
+#[test]
+fn test_array_sum_overflows_to_float() {
+    let val = run_code("<?php return array_sum([PHP_INT_MAX, 1]);");
+    assert_eq!(val, Val::Float(9223372036854775808.0));
+
+    let val = run_code("<?php return array_sum([1, '2', '3.5abc', 'abc', true, null]);");
+    assert_eq!(val, Val::Float(7.5));
+
+    let val = run_code("<?php return array_sum(['10', ' 5', '0x1A']);");
+    assert_eq!(val, Val::Int(15));
+}
+
+#[test]
+fn test_array_product_of_floats() {
+    let val = run_code("<?php return array_product([1.5, 2, '4']);");
+    assert_eq!(val, Val::Float(12.0));
+
+    let val = run_code("<?php return array_product([PHP_INT_MAX, 2]);");
+    assert_eq!(val, Val::Float(18446744073709551614.0));
+}
+
+#[test]
+fn test_array_sum_product_empty_identity() {
+    let val = run_code("<?php return array_sum([]) === 0 && array_product([]) === 1;");
+    assert_eq!(val, Val::Bool(true));
+}
+
 #[test]
 fn test_array_pad() {
     let code = r#"<?php
@@ -443,6 +470,31 @@ fn test_array_diff_intersect() {
     }
 }
 
+#[test]
+fn test_array_diff_three_arrays_preserves_keys() {
+    let code = r#"<?php
+        $a = ['a' => '1', 5 => 2, 'x' => '3', 7 => 4.0, 'k' => 'green'];
+        return json_encode(array_diff($a, [1], ['2'], [4]));
+    "#;
+
+    let val = run_code(code);
+    assert_eq!(
+        val,
+        Val::String(br#"{"x":"3","k":"green"}"#.to_vec().into())
+    );
+}
+
+#[test]
+fn test_array_intersect_three_arrays_preserves_keys() {
+    let code = r#"<?php
+        $a = [3 => '1', 'b' => '2', 'c' => 3, 9 => '4'];
+        return json_encode(array_intersect($a, ['1', '2', 3, '4'], [2, '3', 1], [1.0, '3', 'z']));
+    "#;
+
+    let val = run_code(code);
+    assert_eq!(val, Val::String(br#"{"3":"1","c":3}"#.to_vec().into()));
+}
+
 #[test]
 fn test_sorting_functions() {
     let code = r#"<?php
@@ -0,0 +1,136 @@
+mod common;
+
+use common::run_code_with_vm;
+use php_rs::core::value::{ArrayKey, Val};
+use std::rc::Rc;
+
+fn returned_keys(src: &str) -> Vec<ArrayKey> {
+    let (res, _) = run_code_with_vm(src).unwrap();
+    match res {
+        Val::Array(arr) => arr.map.keys().cloned().collect(),
+        other => panic!("Expected array, got {:?}", other),
+    }
+}
+
+fn str_key(s: &str) -> ArrayKey {
+    ArrayKey::Str(Rc::new(s.as_bytes().to_vec()))
+}
+
+fn expected_keys() -> Vec<ArrayKey> {
+    vec![
+        str_key("007"),
+        ArrayKey::Int(-5),
+        str_key("9223372036854775808"),
+        str_key("-0"),
+        ArrayKey::Int(9223372036854775807),
+        ArrayKey::Int(42),
+    ]
+}
+
+#[test]
+fn test_runtime_string_offsets_only_canonical_ints() {
+    let keys = returned_keys(
+        r#"<?php
+        $arr = [];
+        $inputs = ["007", "-5", "9223372036854775808", "-0", "9223372036854775807"];
+        foreach ($inputs as $k) {
+            $arr[$k] = true;
+        }
+        $arr[(string) 42] = true;
+        return $arr;
+    "#,
+    );
+    assert_eq!(keys, expected_keys());
+}
+
+#[test]
+fn test_literal_string_keys_only_canonical_ints() {
+    let keys = returned_keys(
+        r#"<?php
+        return [
+            "007" => 1,
+            "-5" => 2,
+            "9223372036854775808" => 3,
+            "-0" => 4,
+            "9223372036854775807" => 5,
+            "42" => 6,
+        ];
+    "#,
+    );
+    assert_eq!(keys, expected_keys());
+}
+
+#[test]
+fn test_array_functions_normalize_string_keys() {
+    let keys = returned_keys(
+        r#"<?php
+        $values = ["007", "-5", "9223372036854775808", "-0", "9223372036854775807", "42"];
+        return array_flip($values);
+    "#,
+    );
+    assert_eq!(keys, expected_keys());
+
+    let keys = returned_keys(
+        r#"<?php
+        return json_decode('{"-5": 1, "-0": 2, "007": 3}', true);
+    "#,
+    );
+    assert!(keys.contains(&ArrayKey::Int(-5)));
+    assert!(keys.contains(&str_key("-0")));
+    assert!(keys.contains(&str_key("007")));
+}
+
+#[test]
+fn test_numeric_string_lookup_finds_int_key() {
+    let (res, _) = run_code_with_vm(
+        r#"<?php
+        $arr = [5 => "five", "05" => "padded"];
+        return json_encode([
+            $arr["5"],
+            $arr["05"],
+            array_key_exists("5", $arr),
+            array_key_exists("-0", $arr),
+        ]);
+    "#,
+    )
+    .unwrap();
+    match res {
+        Val::String(s) => assert_eq!(s.as_slice(), br#"["five","padded",true,false]"#),
+        other => panic!("Expected string, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_append_after_php_int_max_throws_error() {
+    let (res, _) = run_code_with_vm(
+        r#"<?php
+        $errors = [];
+        $arr = [PHP_INT_MAX => "max"];
+        try {
+            $arr[] = "overwrite";
+        } catch (Error $e) {
+            $errors[] = $e->getMessage();
+        }
+        $ref = &$arr;
+        try {
+            $ref[] = "overwrite";
+        } catch (Error $e) {
+            $errors[] = $e->getMessage();
+        }
+        $arr["key"][] = "nested";
+        try {
+            $arr[][] = "nested";
+        } catch (Error $e) {
+            $errors[] = $e->getMessage();
+        }
+        return json_encode([$arr, $errors]);
+        "#,
+    )
+    .unwrap();
+    let message = "Cannot add element to the array as the next element is already occupied";
+    let expected = format!(
+        r#"[{{"9223372036854775807":"max","key":["nested"]}},["{0}","{0}","{0}"]]"#,
+        message
+    );
+    assert_eq!(res, Val::String(Rc::new(expected.into_bytes())));
+}
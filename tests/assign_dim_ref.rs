@@ -39,3 +39,57 @@ fn test_assign_dim_ref_modify_via_array() {
         _ => panic!("Expected int 3, got {:?}", result),
     }
 }
+
+#[test]
+fn test_ref_to_array_element_updates_array() {
+    let src = r#"<?php
+        $arr = ['k' => 1, 'n' => ['m' => 1]];
+        $ref = &$arr['k'];
+        $ref = 5;
+        $deep = &$arr['n']['m'];
+        $deep++;
+        return $arr['k'] * 10 + $arr['n']['m'];
+    "#;
+    let (result, _) = run_code(src).unwrap();
+    assert_eq!(result, Val::Int(52));
+}
+
+#[test]
+fn test_append_ref_tracks_source() {
+    let src = r#"<?php
+        $other = 1;
+        $arr = ['first'];
+        $arr[] = &$other;
+        $other = 9;
+        $seen = $arr[1];
+        $arr[1] = 4;
+        return [$seen, $other, count($arr)];
+    "#;
+    let (result, vm) = run_code(src).unwrap();
+    let Val::Array(arr) = result else {
+        panic!("Expected array, got {:?}", result);
+    };
+    let values: Vec<Val> = arr
+        .map
+        .values()
+        .map(|h| vm.arena.get(*h).value.clone())
+        .collect();
+    assert_eq!(values, [Val::Int(9), Val::Int(4), Val::Int(2)]);
+}
+
+#[test]
+fn test_nested_dim_ref_and_rebinding() {
+    let src = r#"<?php
+        $x = 1;
+        $y = 2;
+        $m = [];
+        $m['a']['b'][] = &$x;
+        $m['a']['b'][0] = 6;
+        $m['a']['c'] = &$x;
+        $m['a']['c'] = &$y;
+        $y = 3;
+        return $x * 10 + $m['a']['c'];
+    "#;
+    let (result, _) = run_code(src).unwrap();
+    assert_eq!(result, Val::Int(63));
+}
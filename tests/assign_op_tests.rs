@@ -414,10 +414,17 @@ return $a;
 fn test_negative_shift() {
     let code = r#"<?php
 $a = 16;
-$a >>= -1; // Negative shift should result in 0
+try {
+    $a >>= -1; // Negative shift throws ArithmeticError
+} catch (ArithmeticError $e) {
+    return $e->getMessage();
+}
 return $a;
 "#;
-    assert_eq!(run_code(code), Val::Int(0));
+    assert_eq!(
+        run_code(code),
+        Val::String(b"Bit shift by negative number".to_vec().into())
+    );
 }
 
 #[test]
@@ -0,0 +1,105 @@
+use php_rs::core::value::Val;
+use php_rs::runtime::context::EngineBuilder;
+use php_rs::vm::engine::VM;
+use std::rc::Rc;
+
+fn create_test_vm() -> VM {
+    let engine = EngineBuilder::new()
+        .with_extension(php_rs::runtime::bz2_extension::Bz2Extension)
+        .build()
+        .expect("Failed to build engine");
+    VM::new(engine)
+}
+
+#[test]
+fn test_bzcompress_bzdecompress() {
+    let mut vm = create_test_vm();
+    let data = b"Hello world! Hello world! Hello world! Hello world! Hello world!".repeat(20);
+    let data_handle = vm.arena.alloc(Val::String(Rc::new(data.clone())));
+
+    let compressed_handle = php_rs::builtins::bz2::php_bzcompress(&mut vm, &[data_handle]).unwrap();
+    let compressed = match &vm.arena.get(compressed_handle).value {
+        Val::String(s) => s.clone(),
+        _ => panic!("bzcompress did not return a string"),
+    };
+
+    assert!(compressed.starts_with(b"BZh4"));
+    assert!(compressed.len() < data.len());
+
+    let decompressed_handle =
+        php_rs::builtins::bz2::php_bzdecompress(&mut vm, &[compressed_handle]).unwrap();
+    let decompressed = match &vm.arena.get(decompressed_handle).value {
+        Val::String(s) => s.clone(),
+        _ => panic!("bzdecompress did not return a string"),
+    };
+
+    assert_eq!(decompressed.as_ref(), &data);
+}
+
+#[test]
+fn test_bzdecompress_invalid_data_returns_error_code() {
+    let mut vm = create_test_vm();
+    let data_handle = vm
+        .arena
+        .alloc(Val::String(Rc::new(b"not bzip2 data".to_vec())));
+
+    let result = php_rs::builtins::bz2::php_bzdecompress(&mut vm, &[data_handle]).unwrap();
+    // BZ_DATA_ERROR_MAGIC
+    assert_eq!(vm.arena.get(result).value, Val::Int(-5));
+}
+
+#[test]
+fn test_bz2_file_ops() {
+    let mut vm = create_test_vm();
+    let path = std::env::temp_dir().join(format!("php_rs_bz2_{}.bz2", std::process::id()));
+    let data = b"Hello, bzip2 file operations!\n".repeat(100);
+
+    let filename_handle = vm.arena.alloc(Val::String(Rc::new(
+        path.to_string_lossy().as_bytes().to_vec(),
+    )));
+    let mode_w_handle = vm.arena.alloc(Val::String(Rc::new(b"w".to_vec())));
+    let bz_w_handle =
+        php_rs::builtins::bz2::php_bzopen(&mut vm, &[filename_handle, mode_w_handle]).unwrap();
+    assert!(matches!(vm.arena.get(bz_w_handle).value, Val::Resource(_)));
+
+    let data_handle = vm.arena.alloc(Val::String(Rc::new(data.clone())));
+    let written_handle =
+        php_rs::builtins::bz2::php_bzwrite(&mut vm, &[bz_w_handle, data_handle]).unwrap();
+    assert_eq!(
+        vm.arena.get(written_handle).value,
+        Val::Int(data.len() as i64)
+    );
+
+    let flushed = php_rs::builtins::bz2::php_bzflush(&mut vm, &[bz_w_handle]).unwrap();
+    assert_eq!(vm.arena.get(flushed).value, Val::Bool(true));
+    let closed = php_rs::builtins::bz2::php_bzclose(&mut vm, &[bz_w_handle]).unwrap();
+    assert_eq!(vm.arena.get(closed).value, Val::Bool(true));
+
+    let mode_r_handle = vm.arena.alloc(Val::String(Rc::new(b"r".to_vec())));
+    let bz_r_handle =
+        php_rs::builtins::bz2::php_bzopen(&mut vm, &[filename_handle, mode_r_handle]).unwrap();
+    assert!(matches!(vm.arena.get(bz_r_handle).value, Val::Resource(_)));
+
+    let mut read_back = Vec::new();
+    let len_handle = vm.arena.alloc(Val::Int(1000));
+    loop {
+        let chunk = php_rs::builtins::bz2::php_bzread(&mut vm, &[bz_r_handle, len_handle]).unwrap();
+        match &vm.arena.get(chunk).value {
+            Val::String(s) if s.is_empty() => break,
+            Val::String(s) => read_back.extend_from_slice(s),
+            _ => panic!("bzread() should return string"),
+        }
+    }
+    assert_eq!(read_back, data);
+
+    let errno = php_rs::builtins::bz2::php_bzerrno(&mut vm, &[bz_r_handle]).unwrap();
+    assert_eq!(vm.arena.get(errno).value, Val::Int(0));
+    let errstr = php_rs::builtins::bz2::php_bzerrstr(&mut vm, &[bz_r_handle]).unwrap();
+    assert_eq!(
+        vm.arena.get(errstr).value,
+        Val::String(Rc::new(b"OK".to_vec()))
+    );
+
+    php_rs::builtins::bz2::php_bzclose(&mut vm, &[bz_r_handle]).unwrap();
+    let _ = std::fs::remove_file(&path);
+}
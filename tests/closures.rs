@@ -79,7 +79,6 @@ fn test_closure_this_binding() {
 }
 
 #[test]
-#[should_panic(expected = "Using $this when not in object context")]
 fn test_static_closure_no_this() {
     let code = r#"<?php
         class A {
@@ -91,7 +90,64 @@ fn test_static_closure_no_this() {
         }
         $a = new A();
         $f = $a->getClosure();
-        $f();
+        try {
+            $f();
+            return "no error";
+        } catch (Error $e) {
+            return $e->getMessage();
+        }
+    "#;
+    let result = run_code(code);
+    assert_eq!(
+        result,
+        Val::String(b"Using $this when not in object context".to_vec().into())
+    );
+}
+
+#[test]
+fn test_static_arrow_function_no_this() {
+    let code = r#"<?php
+        class A {
+            public $val = 10;
+            public function run() {
+                $isset = static fn() => isset($this);
+                $use = static fn() => $this->val;
+                try {
+                    $use();
+                    $caught = "no error";
+                } catch (Error $e) {
+                    $caught = $e->getMessage();
+                }
+                return var_export($isset(), true) . "|" . $caught;
+            }
+        }
+        return (new A())->run();
     "#;
-    run_code(code);
+    let result = run_code(code);
+    assert_eq!(
+        result,
+        Val::String(
+            b"false|Using $this when not in object context"
+                .to_vec()
+                .into()
+        )
+    );
+}
+
+#[test]
+fn test_arrow_function_binds_this_and_captures_by_value() {
+    let code = r#"<?php
+        class A {
+            public $val = 10;
+            public function run($factor) {
+                $n = 1;
+                $f = fn($x) => $this->val * $factor + $x + $n++;
+                return $f(1) + $f(1) + $n;
+            }
+        }
+        return (new A())->run(2);
+    "#;
+    let result = run_code(code);
+    // Each call sees $n == 1, and the outer $n is never incremented
+    assert_eq!(result, Val::Int(45));
 }
@@ -3,7 +3,7 @@
 //! Provides unified execution helpers that delegate to the centralized
 //! `vm::executor` API. This eliminates code duplication across test files.
 
-use php_rs::core::value::Val;
+use php_rs::core::value::{Handle, Val};
 use php_rs::vm::engine::{VM, VmError};
 use php_rs::vm::executor::{ExecutionConfig, execute_code};
 
@@ -75,6 +75,65 @@ pub fn run_code_with_vm(code: &str) -> Result<(Val, VM), VmError> {
     Ok((value, vm))
 }
 
+/// Execute code that is expected to end with an uncaught exception and return
+/// the exception's class name and message
+///
+/// Code must contain the full `<?php` opening tag.
+/// Panics if execution finishes without an uncaught exception.
+#[allow(dead_code)]
+pub fn run_code_uncaught_exception(code: &str) -> (String, String) {
+    use php_rs::compiler::emitter::Emitter;
+    use php_rs::runtime::context::{EngineBuilder, RequestContext};
+
+    let arena = bumpalo::Bump::new();
+    let lexer = php_rs::parser::lexer::Lexer::new(code.as_bytes());
+    let mut parser = php_rs::parser::parser::Parser::new(lexer, &arena);
+    let program = parser.parse_program();
+
+    if !program.errors.is_empty() {
+        panic!("Parse errors: {:?}", program.errors);
+    }
+
+    let engine_context = EngineBuilder::new()
+        .with_core_extensions()
+        .build()
+        .expect("Failed to build engine");
+    let mut request_context = RequestContext::new(engine_context);
+    let emitter = Emitter::new(code.as_bytes(), &mut request_context.interner);
+    let (chunk, _) = emitter.compile(program.statements);
+
+    let mut vm = VM::new_with_context(request_context);
+    let ex_handle = match vm.run(std::rc::Rc::new(chunk)) {
+        Err(VmError::Exception(handle)) => handle,
+        other => panic!("Expected an uncaught exception, got {:?}", other),
+    };
+
+    exception_class_and_message(&vm, ex_handle)
+}
+
+/// Class name and message of the exception object `ex_handle`
+#[allow(dead_code)]
+pub fn exception_class_and_message(vm: &VM, ex_handle: Handle) -> (String, String) {
+    let Val::Object(payload) = vm.arena.get(ex_handle).value else {
+        panic!("Exception is not an object");
+    };
+    let Val::ObjPayload(obj) = &vm.arena.get(payload).value else {
+        panic!("Exception has no object payload");
+    };
+    let class = vm.context.interner.lookup(obj.class).unwrap_or(b"");
+    let message = vm
+        .context
+        .interner
+        .find(b"message")
+        .and_then(|sym| obj.properties.get(&sym))
+        .map(|handle| match &vm.arena.get(*handle).value {
+            Val::String(s) => String::from_utf8_lossy(s).to_string(),
+            _ => String::new(),
+        })
+        .unwrap_or_default();
+    (String::from_utf8_lossy(class).to_string(), message)
+}
+
 /// Execute code and return just the VM (for inspecting internal state)
 #[allow(dead_code)]
 pub fn run_code_vm_only(code: &str) -> VM {
@@ -135,6 +194,31 @@ pub fn run_code_capture_output(code: &str) -> Result<(Val, String), VmError> {
     Ok((value, output_str))
 }
 
+/// Secure random source whose every read fails, for installing with
+/// `php_rs::builtins::math::set_secure_rng` to exercise entropy errors
+#[allow(dead_code)]
+pub struct FailingRng;
+
+impl rand::RngCore for FailingRng {
+    fn next_u32(&mut self) -> u32 {
+        unreachable!()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        unreachable!()
+    }
+
+    fn fill_bytes(&mut self, _dest: &mut [u8]) {
+        unreachable!()
+    }
+
+    fn try_fill_bytes(&mut self, _dest: &mut [u8]) -> Result<(), rand::Error> {
+        Err(rand::Error::new(std::io::Error::other(
+            "entropy source failed",
+        )))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,3 +111,39 @@ fn test_dateinterval_properties() {
     .unwrap();
     assert_eq!(output, "123456");
 }
+
+#[test]
+fn test_datetime_create_from_format() {
+    let (_, output) = run_code_capture_output(
+        r#"<?php
+    $dt = DateTime::createFromFormat("!d/m/Y", "25/12/2023");
+    echo $dt->format("Y-m-d H:i:s") . "\n";
+    $dt = DateTime::createFromFormat("Y-m-d|", "2023-02-03");
+    echo $dt->format("Y-m-d H:i:s") . "\n";
+    $dt = DateTime::createFromFormat("j-M-Y g:i a", "5-Feb-2009 3:04 pm");
+    echo $dt->format("Y-m-d H:i:s") . "\n";
+    var_dump(DateTime::getLastErrors());
+    "#,
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        "2023-12-25 00:00:00\n2023-02-03 00:00:00\n2009-02-05 15:04:00\nbool(false)\n"
+    );
+}
+
+#[test]
+fn test_datetime_create_from_format_mismatch() {
+    let (_, output) = run_code_capture_output(
+        r#"<?php
+    var_dump(DateTime::createFromFormat("d/m/Y", "2023-12-25"));
+    $errors = DateTime::getLastErrors();
+    echo $errors["error_count"] . ": " . $errors["errors"][2];
+    "#,
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        "bool(false)\n1: The separation symbol could not be found"
+    );
+}
@@ -0,0 +1,100 @@
+use php_rs::compiler::emitter::Emitter;
+use php_rs::core::value::{Handle, Val, Visibility};
+use php_rs::runtime::context::{EngineBuilder, RequestContext};
+use php_rs::runtime::registry::{NativeClassDef, NativeMethodEntry};
+use php_rs::vm::engine::VM;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+fn host_add(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let sum: i64 = args.iter().map(|&h| vm.arena.get(h).value.to_int()).sum();
+    Ok(vm.arena.alloc(Val::Int(sum)))
+}
+
+fn host_greeting(vm: &mut VM, args: &[Handle]) -> Result<Handle, String> {
+    let name = match args.first().map(|&h| &vm.arena.get(h).value) {
+        Some(Val::String(s)) => s.clone(),
+        _ => return Err("HostGreeter::greet(): Argument #1 ($name) must be of type string".into()),
+    };
+    let mut greeting = b"Hello, ".to_vec();
+    greeting.extend_from_slice(&name);
+    Ok(vm.arena.alloc(Val::String(Rc::new(greeting))))
+}
+
+fn run_with_engine(engine: EngineBuilder, code: &str) -> Val {
+    let engine = engine.build().expect("Failed to build engine");
+    let mut request_context = RequestContext::new(engine);
+
+    let arena = bumpalo::Bump::new();
+    let lexer = php_rs::parser::lexer::Lexer::new(code.as_bytes());
+    let mut parser = php_rs::parser::parser::Parser::new(lexer, &arena);
+    let program = parser.parse_program();
+    assert!(program.errors.is_empty(), "{:?}", program.errors);
+    let emitter = Emitter::new(code.as_bytes(), &mut request_context.interner);
+    let (chunk, _) = emitter.compile(program.statements);
+
+    let mut vm = VM::new_with_context(request_context);
+    vm.run(Rc::new(chunk)).expect("script should run");
+    let handle = vm.last_return_value.expect("script should return a value");
+    vm.arena.get(handle).value.clone()
+}
+
+#[test]
+fn test_host_function_callable_from_php() {
+    let engine = EngineBuilder::new()
+        .with_core_extensions()
+        .with_native_function(b"host_add", host_add);
+    let result = run_with_engine(
+        engine,
+        r#"<?php
+        return json_encode([
+            host_add(2, 3),
+            HOST_ADD(1, 2, 3, 4),
+            function_exists('host_add'),
+            array_map('host_add', [1, 2], [10, 20]),
+        ]);
+    "#,
+    );
+    assert_eq!(
+        result,
+        Val::String(Rc::new(br#"[5,10,true,[11,22]]"#.to_vec()))
+    );
+}
+
+#[test]
+fn test_host_class_callable_from_php() {
+    let mut methods = HashMap::new();
+    methods.insert(
+        b"greet".to_vec(),
+        NativeMethodEntry {
+            handler: host_greeting,
+            visibility: Visibility::Public,
+            is_static: true,
+            is_final: false,
+        },
+    );
+    let engine = EngineBuilder::new()
+        .with_core_extensions()
+        .with_native_class(NativeClassDef {
+            name: b"HostGreeter".to_vec(),
+            parent: None,
+            is_interface: false,
+            is_trait: false,
+            is_final: true,
+            interfaces: Vec::new(),
+            methods,
+            constants: HashMap::new(),
+            constructor: None,
+            extension_name: None,
+        });
+    let result = run_with_engine(
+        engine,
+        r#"<?php
+        return json_encode([class_exists('HostGreeter'), HostGreeter::greet('embedder')]);
+    "#,
+    );
+    assert_eq!(
+        result,
+        Val::String(Rc::new(br#"[true,"Hello, embedder"]"#.to_vec()))
+    );
+}
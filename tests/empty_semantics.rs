@@ -0,0 +1,86 @@
+mod common;
+use common::run_code_capture_output;
+
+fn run(code: &str) -> String {
+    let (_, output) = run_code_capture_output(code).unwrap();
+    output
+}
+
+#[test]
+fn test_empty_string_zero_but_not_float_string() {
+    let output = run(r#"<?php
+var_dump(empty("0"), empty("0.0"), empty(""), empty(" "));
+"#);
+    assert_eq!(output, "bool(true)\nbool(false)\nbool(true)\nbool(false)\n");
+}
+
+#[test]
+fn test_empty_matches_negated_bool() {
+    let output = run(r#"<?php
+$values = [0, 1, -1, 0.0, 0.5, "", "0", "a", [], [0], null, false, true, new stdClass];
+foreach ($values as $v) {
+    echo empty($v) === !$v ? "same\n" : "differs\n";
+}
+"#);
+    assert_eq!(output, "same\n".repeat(14));
+}
+
+#[test]
+fn test_empty_suppresses_undefined_warnings() {
+    let output = run(r#"<?php
+set_error_handler(function ($errno, $errstr) {
+    echo "error: $errstr\n";
+    return true;
+});
+$arr = ['x' => ['y' => 0]];
+$str = "abc";
+var_dump(empty($undefined));
+var_dump(empty($arr['missing']));
+var_dump(empty($arr['x']['missing']));
+var_dump(empty($arr['x']['y']));
+var_dump(empty($str[10]));
+"#);
+    assert_eq!(
+        output,
+        "bool(true)\nbool(true)\nbool(true)\nbool(true)\nbool(true)\n"
+    );
+}
+
+#[test]
+fn test_empty_uninitialized_typed_property() {
+    let output = run(r#"<?php
+class Point { public int $x; public ?int $y; }
+$p = new Point;
+var_dump(empty($p->x), empty($p->y));
+$p->x = 5;
+var_dump(empty($p->x));
+"#);
+    assert_eq!(output, "bool(true)\nbool(true)\nbool(false)\n");
+}
+
+#[test]
+fn test_empty_magic_property_consults_isset_then_get() {
+    let output = run(r#"<?php
+class Bag {
+    private $data = ['zero' => 0, 'name' => 'bag'];
+    public function __isset($name) {
+        echo "__isset($name)\n";
+        return isset($this->data[$name]);
+    }
+    public function __get($name) {
+        echo "__get($name)\n";
+        return $this->data[$name];
+    }
+}
+$b = new Bag;
+var_dump(empty($b->zero));
+var_dump(empty($b->name));
+var_dump(empty($b->missing));
+"#);
+    assert_eq!(
+        output,
+        "__isset(zero)\n__get(zero)\nbool(true)\n\
+         __isset(name)\n__get(name)\nbool(false)\n\
+         __isset(missing)\nbool(true)\n"
+    );
+}
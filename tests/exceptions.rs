@@ -665,3 +665,63 @@ fn test_exception_in_function() {
         panic!("Expected 'caught', got {:?}", res);
     }
 }
+
+// ============================================================================
+// Throw Expression Tests
+// ============================================================================
+
+#[test]
+fn test_throw_in_coalesce() {
+    let src = r#"<?php
+        function required($v) {
+            return $v ?? throw new RuntimeException("missing");
+        }
+
+        $res = required("present");
+        try {
+            required(null);
+            $res .= ":not reached";
+        } catch (RuntimeException $e) {
+            $res .= ":" . $e->getMessage();
+        }
+        return $res;
+    "#;
+
+    let (res, _) = run_code_with_vm(src).unwrap();
+    if let Val::String(s) = res {
+        assert_eq!(std::str::from_utf8(&s).unwrap(), "present:missing");
+    } else {
+        panic!("Expected 'present:missing', got {:?}", res);
+    }
+}
+
+#[test]
+fn test_throw_in_ternary_else() {
+    let src = r#"<?php
+        function positive($n) {
+            return $n > 0 ? $n : throw new InvalidArgumentException("not positive");
+        }
+
+        $check = fn($n) => $n !== 0 ? $n : throw new LogicException("zero");
+
+        $res = positive(4) . ":" . $check(2);
+        try {
+            positive(-1);
+        } catch (InvalidArgumentException $e) {
+            $res .= ":" . $e->getMessage();
+        }
+        try {
+            $check(0);
+        } catch (LogicException $e) {
+            $res .= ":" . $e->getMessage();
+        }
+        return $res;
+    "#;
+
+    let (res, _) = run_code_with_vm(src).unwrap();
+    if let Val::String(s) = res {
+        assert_eq!(std::str::from_utf8(&s).unwrap(), "4:2:not positive:zero");
+    } else {
+        panic!("Expected '4:2:not positive:zero', got {:?}", res);
+    }
+}
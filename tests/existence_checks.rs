@@ -295,6 +295,41 @@ fn test_property_exists_inherited() {
     }
 }
 
+#[test]
+fn test_property_exists_uninitialized_typed() {
+    let code = r#"<?php
+        class A {
+            public int $count;
+            public function __isset($name) { return true; }
+        }
+        $a = new A();
+        return json_encode([property_exists($a, 'count'), isset($a->count), empty($a->count)]);
+    "#;
+
+    let val = run_code(code);
+    assert_eq!(val, Val::String(b"[true,false,true]".to_vec().into()));
+}
+
+#[test]
+fn test_property_exists_null_value() {
+    let code = r#"<?php
+        class A {
+            public ?string $name = null;
+        }
+        $a = new A();
+        $arr = ['name' => null];
+        return json_encode([
+            property_exists($a, 'name'),
+            isset($a->name),
+            array_key_exists('name', $arr),
+            isset($arr['name']),
+        ]);
+    "#;
+
+    let val = run_code(code);
+    assert_eq!(val, Val::String(b"[true,false,true,false]".to_vec().into()));
+}
+
 #[test]
 fn test_get_class_methods() {
     let code = r#"<?php
@@ -458,3 +493,54 @@ fn test_property_exists_false() {
         panic!("Expected bool false, got {:?}", val);
     }
 }
+
+#[test]
+fn test_method_exists_traits_interfaces_and_case() {
+    let code = r#"<?php
+        interface I { function fromInterface(); }
+        trait T { function fromTrait() {} }
+        abstract class Base implements I { function inherited() {} }
+        class Child extends Base {
+            use T;
+            function fromInterface() {}
+        }
+        abstract class Pending implements I {}
+        return json_encode([
+            method_exists(new Child(), 'inherited'),
+            method_exists('Child', 'fromTrait'),
+            method_exists('child', 'FROMTRAIT'),
+            method_exists('Child', 'INHERITED'),
+            method_exists('Pending', 'fromInterface'),
+            method_exists('I', 'frominterface'),
+            method_exists('Child', 'missing'),
+        ]);
+    "#;
+
+    let val = run_code(code);
+    assert_eq!(
+        val,
+        Val::String(b"[true,true,true,true,true,true,false]".to_vec().into())
+    );
+}
+
+#[test]
+fn test_property_exists_inherited_trait_and_static() {
+    let code = r#"<?php
+        trait T { public $fromTrait = 1; }
+        class A { protected $inherited; public static $counter = 0; }
+        class B extends A { use T; }
+        return json_encode([
+            property_exists('B', 'inherited'),
+            property_exists(new B(), 'inherited'),
+            property_exists('b', 'fromTrait'),
+            property_exists('B', 'counter'),
+            property_exists('B', 'Inherited'),
+        ]);
+    "#;
+
+    let val = run_code(code);
+    assert_eq!(
+        val,
+        Val::String(b"[true,true,true,true,false]".to_vec().into())
+    );
+}
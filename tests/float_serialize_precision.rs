@@ -0,0 +1,71 @@
+mod common;
+
+use common::run_code_capture_output;
+
+#[test]
+fn test_var_dump_float_shortest_round_trip() {
+    let (_, output) = run_code_capture_output(
+        r#"<?php
+        var_dump(0.1, 0.1 + 0.2, 1/3, 1.0, 1e100, 2.5e-5, NAN, -INF);
+    "#,
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        "float(0.1)\nfloat(0.30000000000000004)\nfloat(0.3333333333333333)\nfloat(1)\nfloat(1.0E+100)\nfloat(2.5E-5)\nfloat(NAN)\nfloat(-INF)\n"
+    );
+}
+
+#[test]
+fn test_var_export_float_shortest_round_trip() {
+    let (_, output) = run_code_capture_output(
+        r#"<?php
+        var_export(0.1); echo "\n";
+        var_export(1.0); echo "\n";
+        var_export(1e25); echo "\n";
+        var_export([1.5]);
+    "#,
+    )
+    .unwrap();
+    assert_eq!(output, "0.1\n1.0\n1.0E+25\narray (\n  0 => 1.5,\n)");
+}
+
+#[test]
+fn test_json_encode_and_serialize_float() {
+    let (_, output) = run_code_capture_output(
+        r#"<?php
+        echo json_encode([0.1, 1.0, 1e25]), "\n";
+        echo json_encode(1.0, JSON_PRESERVE_ZERO_FRACTION), "\n";
+        echo serialize(0.1), serialize(1e25), "\n";
+        var_dump(unserialize(serialize(0.1)) === 0.1);
+    "#,
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        "[0.1,1,1.0e+25]\n1.0\nd:0.1;d:1.0E+25;\nbool(true)\n"
+    );
+}
+
+#[test]
+fn test_float_formatting_honors_serialize_precision_ini() {
+    let (_, output) = run_code_capture_output(
+        r#"<?php
+        ini_set('serialize_precision', 17);
+        var_dump(0.1, 1.0, 0.1 + 0.2);
+        echo json_encode([0.1, 2.5]), serialize(0.1), "\n";
+        var_export(0.1); echo "\n";
+        ini_set('serialize_precision', 5);
+        var_dump(123456.789, 0.000012345, 1.5);
+        ini_set('serialize_precision', -1);
+        var_dump(0.1);
+    "#,
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        "float(0.10000000000000001)\nfloat(1)\nfloat(0.30000000000000004)\n\
+         [0.10000000000000001,2.5]d:0.10000000000000001;\n0.10000000000000001\n\
+         float(1.2346E+5)\nfloat(1.2345E-5)\nfloat(1.5)\nfloat(0.1)\n"
+    );
+}
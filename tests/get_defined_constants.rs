@@ -0,0 +1,49 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn bytes(s: &[u8]) -> Val {
+    Val::String(s.to_vec().into())
+}
+
+#[test]
+fn user_constants_are_listed_flat_and_under_user() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+define('MY_DEFINED', 42);
+const MY_CONST = 'x';
+$all = get_defined_constants();
+$groups = get_defined_constants(true);
+return implode(',', [
+    $all['MY_DEFINED'], $all['MY_CONST'], $all['E_ALL'],
+    $groups['user']['MY_DEFINED'], $groups['user']['MY_CONST'],
+    count($groups['user']),
+    isset($groups['Core']['E_ALL']) ? 'core' : 'missing',
+    isset($groups['user']['E_ALL']) ? 'leaked' : 'clean',
+    array_key_last($groups),
+]);
+"#
+        ),
+        bytes(b"42,x,32767,42,x,2,core,clean,user")
+    );
+}
+
+#[test]
+fn extension_constants_are_grouped_by_extension() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$groups = get_defined_constants(true);
+return implode(',', [
+    $groups['openssl']['OPENSSL_RAW_DATA'],
+    isset($groups['Core']['OPENSSL_RAW_DATA']) ? 'leaked' : 'clean',
+    array_key_first($groups),
+    isset($groups['user']) ? 'user' : 'no user',
+]);
+"#
+        ),
+        bytes(b"1,clean,Core,no user")
+    );
+}
@@ -54,3 +54,83 @@ fn test_hash_update_single_chunk() {
         "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f"
     );
 }
+
+fn string_of(vm: &VM, handle: php_rs::core::value::Handle) -> Vec<u8> {
+    match &vm.arena.get(handle).value {
+        Val::String(s) => s.as_ref().clone(),
+        other => panic!("Expected String, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_hash_update_small_chunks_matches_one_shot() {
+    let mut vm = create_test_vm();
+
+    let data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    let algo_handle = vm.arena.alloc(Val::String(Rc::new(b"sha256".to_vec())));
+    let ctx_handle =
+        php_rs::builtins::hash::php_hash_init(&mut vm, &[algo_handle]).expect("hash_init failed");
+    for chunk in data.chunks(7) {
+        let chunk_handle = vm.arena.alloc(Val::String(Rc::new(chunk.to_vec())));
+        php_rs::builtins::hash::php_hash_update(&mut vm, &[ctx_handle, chunk_handle])
+            .expect("hash_update failed");
+    }
+    let incremental =
+        php_rs::builtins::hash::php_hash_final(&mut vm, &[ctx_handle]).expect("hash_final failed");
+
+    let data_handle = vm.arena.alloc(Val::String(Rc::new(data)));
+    let one_shot = php_rs::builtins::hash::php_hash(&mut vm, &[algo_handle, data_handle])
+        .expect("hash failed");
+
+    assert_eq!(string_of(&vm, incremental), string_of(&vm, one_shot));
+}
+
+#[test]
+fn test_hash_copy_forks_state() {
+    let mut vm = create_test_vm();
+
+    let algo_handle = vm.arena.alloc(Val::String(Rc::new(b"sha256".to_vec())));
+    let ctx_handle =
+        php_rs::builtins::hash::php_hash_init(&mut vm, &[algo_handle]).expect("hash_init failed");
+    let hello = vm.arena.alloc(Val::String(Rc::new(b"Hello, ".to_vec())));
+    php_rs::builtins::hash::php_hash_update(&mut vm, &[ctx_handle, hello])
+        .expect("hash_update failed");
+
+    let copy_handle =
+        php_rs::builtins::hash::php_hash_copy(&mut vm, &[ctx_handle]).expect("hash_copy failed");
+
+    let world = vm.arena.alloc(Val::String(Rc::new(b"World!".to_vec())));
+    php_rs::builtins::hash::php_hash_update(&mut vm, &[ctx_handle, world])
+        .expect("hash_update failed");
+    let there = vm.arena.alloc(Val::String(Rc::new(b"there".to_vec())));
+    php_rs::builtins::hash::php_hash_update(&mut vm, &[copy_handle, there])
+        .expect("hash_update failed");
+
+    let original =
+        php_rs::builtins::hash::php_hash_final(&mut vm, &[ctx_handle]).expect("hash_final failed");
+    let copy =
+        php_rs::builtins::hash::php_hash_final(&mut vm, &[copy_handle]).expect("hash_final failed");
+
+    assert_eq!(
+        string_of(&vm, original),
+        b"dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f"
+    );
+    assert_eq!(
+        string_of(&vm, copy),
+        b"32d4a06f866d0ca5347047d2cd8903b8613cb8581770597f76a14cc9828a06b6"
+    );
+}
+
+#[test]
+fn test_hash_final_rejects_finalized_context() {
+    let mut vm = create_test_vm();
+
+    let algo_handle = vm.arena.alloc(Val::String(Rc::new(b"md5".to_vec())));
+    let ctx_handle =
+        php_rs::builtins::hash::php_hash_init(&mut vm, &[algo_handle]).expect("hash_init failed");
+    php_rs::builtins::hash::php_hash_final(&mut vm, &[ctx_handle]).expect("hash_final failed");
+
+    let err = php_rs::builtins::hash::php_hash_final(&mut vm, &[ctx_handle]).unwrap_err();
+    assert!(err.contains("non-finalized HashContext"), "{}", err);
+}
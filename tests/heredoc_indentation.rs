@@ -0,0 +1,75 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn bytes(s: &[u8]) -> Val {
+    Val::String(s.to_vec().into())
+}
+
+fn parse_errors(source: &str) -> Vec<&'static str> {
+    let arena = bumpalo::Bump::new();
+    let lexer = php_rs::parser::lexer::Lexer::new(source.as_bytes());
+    let mut parser = php_rs::parser::parser::Parser::new(lexer, &arena);
+    let program = parser.parse_program();
+    program.errors.iter().map(|e| e.message).collect()
+}
+
+#[test]
+fn indented_heredoc_strips_closer_indentation() {
+    assert_eq!(
+        run_code(
+            "<?php\n$name = 'World';\nreturn <<<EOT\n    Hello $name\n      nested\t!\n\n    {$name}s\n    EOT;\n"
+        ),
+        bytes(b"Hello World\n  nested\t!\n\nWorlds")
+    );
+}
+
+#[test]
+fn heredoc_without_indentation_drops_final_newline() {
+    assert_eq!(
+        run_code("<?php\nreturn <<<EOT\nline one\nline two\nEOT;\n"),
+        bytes(b"line one\nline two")
+    );
+    assert_eq!(run_code("<?php\nreturn <<<EOT\nEOT;\n"), bytes(b""));
+}
+
+#[test]
+fn indented_nowdoc_strips_closer_indentation() {
+    assert_eq!(
+        run_code("<?php\nreturn <<<'EOT'\n\t\traw $name\n\t\t\tdeeper\n\t\tEOT;\n"),
+        bytes(b"raw $name\n\tdeeper")
+    );
+}
+
+#[test]
+fn under_indented_body_is_a_parse_error() {
+    assert_eq!(
+        parse_errors("<?php\n$a = <<<EOT\n  short\n    EOT;\n"),
+        vec!["Invalid body indentation level (expecting an indentation level of at least 4)"]
+    );
+    assert_eq!(
+        parse_errors("<?php\n$a = <<<EOT\n    ok\n$a\n    EOT;\n"),
+        vec!["Invalid body indentation level (expecting an indentation level of at least 4)"]
+    );
+}
+
+#[test]
+fn mixed_tabs_and_spaces_are_a_parse_error() {
+    assert_eq!(
+        parse_errors("<?php\n$a = <<<EOT\n \tbody\n \tEOT;\n"),
+        vec!["Invalid indentation - tabs and spaces cannot be mixed"]
+    );
+    assert_eq!(
+        parse_errors("<?php\n$a = <<<'EOT'\n\t  body\n    EOT;\n"),
+        vec!["Invalid indentation - tabs and spaces cannot be mixed"]
+    );
+}
+
+#[test]
+fn binary_prefixed_heredoc_and_nowdoc() {
+    assert_eq!(
+        run_code("<?php\n$v = 'x';\nreturn b<<<EOT\n  a $v\n  EOT . B<<<'EOT'\n$v\nEOT;\n"),
+        bytes(b"a x$v")
+    );
+}
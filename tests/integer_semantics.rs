@@ -0,0 +1,138 @@
+mod common;
+
+use common::run_code_capture_output;
+
+#[test]
+fn test_negative_shift_throws_arithmetic_error() {
+    let (_val, output) = run_code_capture_output(
+        r#"<?php
+        foreach (['<<', '>>', '<<=', '>>='] as $op) {
+            try {
+                $n = 1;
+                match ($op) {
+                    '<<' => 1 << -1,
+                    '>>' => 1 >> -1,
+                    '<<=' => $n <<= -1,
+                    '>>=' => $n >>= -1,
+                };
+                echo $op, " no error\n";
+            } catch (ArithmeticError $e) {
+                echo $op, " ", get_class($e), ": ", $e->getMessage(), "\n";
+            }
+        }
+    "#,
+    )
+    .expect("Execution failed");
+    assert_eq!(
+        output,
+        "<< ArithmeticError: Bit shift by negative number\n\
+         >> ArithmeticError: Bit shift by negative number\n\
+         <<= ArithmeticError: Bit shift by negative number\n\
+         >>= ArithmeticError: Bit shift by negative number\n"
+    );
+}
+
+#[test]
+fn test_shift_by_64_or_more() {
+    let (_val, output) = run_code_capture_output(
+        "<?php $n = 5; $n <<= 70; var_dump(1 << 64, -1 >> 64, 7 >> 100, $n, 1 << 63);",
+    )
+    .expect("Execution failed");
+    assert_eq!(
+        output,
+        "int(0)\nint(-1)\nint(0)\nint(0)\nint(-9223372036854775808)\n"
+    );
+}
+
+#[test]
+fn test_integer_overflow_promotes_to_float() {
+    let (_val, output) = run_code_capture_output(
+        r#"<?php
+        var_dump(PHP_INT_MAX * 2);
+        var_dump(PHP_INT_MAX + 1 === 9223372036854775808.0);
+        var_dump(PHP_INT_MIN - 1 === -9223372036854775808.0);
+        $n = PHP_INT_MAX;
+        $n *= 3;
+        var_dump($n);
+        var_dump(PHP_INT_MIN % -1);
+    "#,
+    )
+    .expect("Execution failed");
+    assert_eq!(
+        output,
+        "float(1.8446744073709552E+19)\nbool(true)\nbool(true)\n\
+         float(2.7670116110564327E+19)\nint(0)\n"
+    );
+}
+
+#[test]
+fn test_factorial_loop_promotes_to_float() {
+    let (_val, output) = run_code_capture_output(
+        r#"<?php
+        $a = 1;
+        $b = 1;
+        for ($i = 2; $i <= 25; $i++) {
+            $a *= $i;
+            $b = $b * $i;
+        }
+        var_dump($a, $b === $a);
+        $n = PHP_INT_MAX;
+        $n++;
+        $m = PHP_INT_MIN;
+        $m--;
+        var_dump($n === 9223372036854775808.0, $m === -9223372036854775808.0);
+        var_dump(abs(PHP_INT_MIN) === 9223372036854775808.0);
+    "#,
+    )
+    .expect("Execution failed");
+    assert_eq!(
+        output,
+        "float(1.5511210043330986E+25)\nbool(true)\nbool(true)\nbool(true)\nbool(true)\n"
+    );
+}
+
+#[test]
+fn test_integer_pow_stays_int_until_overflow() {
+    let (_val, output) = run_code_capture_output(
+        r#"<?php
+        var_dump(2 ** 3, 2 ** 62, (-2) ** 63, 2 ** -1);
+        var_dump(is_float(2 ** 63), 2 ** 63 === 9223372036854775808.0);
+        $p = 3;
+        $p **= 2;
+        $q = 2;
+        $q **= 64;
+        var_dump($p, is_float($q));
+    "#,
+    )
+    .expect("Execution failed");
+    assert_eq!(
+        output,
+        "int(8)\nint(4611686018427387904)\nint(-9223372036854775808)\nfloat(0.5)\n\
+         bool(true)\nbool(true)\nint(9)\nbool(true)\n"
+    );
+}
+
+#[test]
+fn test_intdiv() {
+    let (_val, output) = run_code_capture_output(
+        r#"<?php
+        var_dump(intdiv(7, 2), intdiv(-7, 2));
+        try {
+            intdiv(1, 0);
+        } catch (ArithmeticError $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+        try {
+            intdiv(PHP_INT_MIN, -1);
+        } catch (ArithmeticError $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+    "#,
+    )
+    .expect("Execution failed");
+    assert_eq!(
+        output,
+        "int(3)\nint(-3)\nDivisionByZeroError: Division by zero\n\
+         ArithmeticError: Division of PHP_INT_MIN by -1 is not an integer\n"
+    );
+}
@@ -12,3 +12,44 @@ fn test_is_callable_with_array() {
     let (_val, output) = run_code_capture_output(code).expect("Execution failed");
     assert!(output.contains("bool(true)"));
 }
+
+#[test]
+fn test_is_callable_syntax_only_skips_method_lookup() {
+    let code = r#"<?php
+        class Foo { public function bar() {} }
+        $obj = new Foo();
+        var_dump(is_callable([$obj, 'missing'], true));
+        var_dump(is_callable([$obj, 'missing'], false));
+        var_dump(is_callable(['Foo', 'missing'], true));
+        var_dump(is_callable([$obj], true));
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(output, "bool(true)\nbool(false)\nbool(true)\nbool(false)\n");
+}
+
+#[test]
+fn test_is_callable_sets_callable_name() {
+    let code = r#"<?php
+        class Foo {
+            public function bar() {}
+            public static function baz() {}
+        }
+        is_callable([new Foo(), 'bar'], false, $name);
+        echo $name, "\n";
+        is_callable(['Foo', 'missing'], true, $name);
+        echo $name, "\n";
+        var_dump(is_callable('Foo::baz', false, $name));
+        echo $name, "\n";
+        var_dump(is_callable(function () {}, false, $name));
+        echo $name, "\n";
+        is_callable('strlen', false, $name);
+        echo $name, "\n";
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "Foo::bar\nFoo::missing\nbool(true)\nFoo::baz\nbool(true)\nClosure::__invoke\nstrlen\n"
+    );
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+const PRELUDE: &str = r#"<?php
+function takes_iterable(iterable $x) { return 'iterable'; }
+function takes_traversable(Traversable $x) { return 'traversable'; }
+function takes_iterator(Iterator $x) { return 'iterator'; }
+function takes_generator(Generator $x) { return 'generator'; }
+function gen() { yield 1; }
+class Agg implements IteratorAggregate {
+    public function getIterator(): Iterator { return gen(); }
+}
+"#;
+
+fn run(body: &str) -> Val {
+    run_code(&format!("{}{}", PRELUDE, body))
+}
+
+fn expect_type_error(body: &str, expected_msg: &str) {
+    let caught = run(&format!(
+        "try {{ {} }} catch (TypeError $e) {{ return get_class($e) . ': ' . $e->getMessage(); }}",
+        body
+    ));
+    let Val::String(caught) = caught else {
+        panic!(
+            "Expected TypeError containing '{}', got: {:?}",
+            expected_msg, caught
+        );
+    };
+    let caught = String::from_utf8_lossy(&caught);
+    assert!(
+        caught.starts_with("TypeError: ") && caught.contains(expected_msg),
+        "Expected TypeError containing '{}', got: {}",
+        expected_msg,
+        caught
+    );
+}
+
+fn parse_errors(source: &str) -> Vec<&'static str> {
+    let arena = bumpalo::Bump::new();
+    let lexer = php_rs::parser::lexer::Lexer::new(source.as_bytes());
+    let mut parser = php_rs::parser::parser::Parser::new(lexer, &arena);
+    let program = parser.parse_program();
+    program.errors.iter().map(|e| e.message).collect()
+}
+
+#[test]
+fn iterable_accepts_arrays_and_traversables() {
+    assert_eq!(
+        run("return takes_iterable([1, 2]) . takes_iterable(gen()) . takes_iterable(new Agg);"),
+        Val::String(b"iterableiterableiterable".to_vec().into())
+    );
+}
+
+#[test]
+fn iterable_rejects_scalars_even_in_weak_mode() {
+    expect_type_error(
+        "return takes_iterable('abc');",
+        "takes_iterable(): Argument #1 ($x) must be of type iterable, string given",
+    );
+}
+
+#[test]
+fn traversable_rejects_arrays() {
+    expect_type_error(
+        "return takes_traversable([1]);",
+        "takes_traversable(): Argument #1 ($x) must be of type Traversable, array given",
+    );
+}
+
+#[test]
+fn generator_satisfies_every_iteration_type() {
+    assert_eq!(
+        run("return takes_traversable(gen()) . takes_iterator(gen()) . takes_generator(gen());"),
+        Val::String(b"traversableiteratorgenerator".to_vec().into())
+    );
+}
+
+#[test]
+fn iterator_aggregate_is_traversable_but_not_an_iterator() {
+    assert_eq!(
+        run("return takes_traversable(new Agg);"),
+        Val::String(b"traversable".to_vec().into())
+    );
+    expect_type_error(
+        "return takes_iterator(new Agg);",
+        "must be of type Iterator, Agg given",
+    );
+}
+
+#[test]
+fn generator_return_types_must_admit_generator() {
+    for ty in [
+        "iterable",
+        "Traversable",
+        "Iterator",
+        "\\Generator",
+        "mixed",
+        "object",
+        "?Generator",
+        "int|Iterator",
+    ] {
+        let src = format!("<?php function g(): {} {{ yield 1; }}", ty);
+        assert!(parse_errors(&src).is_empty(), "{}", ty);
+    }
+    for (src, declared) in [
+        ("<?php function g(): array { yield 1; }", "array"),
+        (
+            "<?php class C { public function g(): int { yield 1; } }",
+            "int",
+        ),
+        ("<?php $f = function (): ?string { yield; };", "?string"),
+        (
+            "<?php $f = fn(): int|Countable => yield 1;",
+            "int|Countable",
+        ),
+    ] {
+        let message = format!(
+            "Generator return type must be a declared supertype of Generator, {} given",
+            declared
+        );
+        assert_eq!(parse_errors(src), vec![message.as_str()], "{}", src);
+    }
+    // The error points at the declared return type
+    let src = "<?php function g(): array { yield 1; }";
+    let arena = bumpalo::Bump::new();
+    let lexer = php_rs::parser::lexer::Lexer::new(src.as_bytes());
+    let mut parser = php_rs::parser::parser::Parser::new(lexer, &arena);
+    let program = parser.parse_program();
+    let span = program.errors[0].span;
+    assert_eq!(&src[span.start..span.end], "array");
+    // Only the function whose own body yields is a generator
+    assert!(
+        parse_errors("<?php function f(): int { $g = function () { yield 1; }; return 1; }")
+            .is_empty()
+    );
+}
+
+#[test]
+fn generator_with_iterable_return_type_runs() {
+    assert_eq!(
+        run("function g(): iterable { yield 1; yield 2; }
+            $sum = 0;
+            foreach (g() as $v) { $sum += $v; }
+            return $sum;"),
+        Val::Int(3)
+    );
+}
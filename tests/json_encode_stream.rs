@@ -0,0 +1,135 @@
+mod common;
+
+use common::run_code_with_vm;
+use php_rs::core::value::Val;
+
+#[test]
+fn test_json_encode_stream_matches_json_encode() {
+    let src = r#"<?php
+        $data = [];
+        for ($i = 0; $i < 2000; $i++) {
+            $data[] = ['id' => $i, 'name' => "item/$i", 'tags' => ['a', 'é'], 'ratio' => $i / 3];
+        }
+
+        $stream = fopen('php://memory', 'w+');
+        $written = json_encode_stream($stream, $data);
+        rewind($stream);
+        $streamed = stream_get_contents($stream);
+
+        $expected = json_encode($data);
+        return $written === strlen($expected) && $streamed === $expected;
+    "#;
+
+    let (result, _vm) = run_code_with_vm(src).unwrap();
+    assert_eq!(result, Val::Bool(true));
+}
+
+#[test]
+fn test_json_encode_stream_pretty_print() {
+    let src = r#"<?php
+        $data = ['list' => [1, 2], 'empty' => [], 'object' => new stdClass];
+        $stream = fopen('php://memory', 'w+');
+        json_encode_stream($stream, $data, JSON_PRETTY_PRINT);
+        rewind($stream);
+        return stream_get_contents($stream) === json_encode($data, JSON_PRETTY_PRINT);
+    "#;
+
+    let (result, _vm) = run_code_with_vm(src).unwrap();
+    assert_eq!(result, Val::Bool(true));
+}
+
+#[test]
+fn test_json_encode_stream_error_sets_last_error() {
+    let src = r#"<?php
+        $stream = fopen('php://memory', 'w+');
+        $result = json_encode_stream($stream, [1, NAN]);
+        return [$result, json_last_error() === JSON_ERROR_INF_OR_NAN];
+    "#;
+
+    let (result, vm) = run_code_with_vm(src).unwrap();
+    let Val::Array(arr) = result else {
+        panic!("expected array, got {:?}", result);
+    };
+    let values: Vec<Val> = arr
+        .map
+        .values()
+        .map(|h| vm.arena.get(*h).value.clone())
+        .collect();
+    assert_eq!(values, vec![Val::Bool(false), Val::Bool(true)]);
+}
+
+#[test]
+fn test_json_encode_stream_iterates_generators() {
+    let src = r#"<?php
+        function rows($n) {
+            for ($i = 0; $i < $n; $i++) {
+                yield ['id' => $i];
+            }
+        }
+        function none() {
+            yield from [];
+        }
+        function pairs() {
+            yield 'a' => 1;
+            yield 'b' => rows(2);
+        }
+        class Cursor implements Iterator {
+            private $i = 0;
+            private $items;
+            public function __construct(array $items) { $this->items = $items; }
+            public function rewind(): void { $this->i = 0; }
+            public function valid(): bool { return $this->i < count($this->items); }
+            public function current(): mixed { return $this->items[$this->i]; }
+            public function key(): mixed { return $this->i; }
+            public function next(): void { $this->i++; }
+        }
+        class Numbers implements IteratorAggregate {
+            public function getIterator(): Iterator {
+                return new Cursor([10, 20]);
+            }
+        }
+
+        $stream = fopen('php://memory', 'w+');
+        $written = json_encode_stream($stream, ['rows' => rows(3), 'pairs' => pairs(), 'agg' => new Numbers]);
+        json_encode_stream($stream, none());
+        rewind($stream);
+        return [$written, stream_get_contents($stream)];
+    "#;
+
+    let (result, vm) = run_code_with_vm(src).unwrap();
+    let Val::Array(arr) = result else {
+        panic!("expected array, got {:?}", result);
+    };
+    let values: Vec<Val> = arr
+        .map
+        .values()
+        .map(|h| vm.arena.get(*h).value.clone())
+        .collect();
+    let expected = r#"{"rows":[{"id":0},{"id":1},{"id":2}],"pairs":{"a":1,"b":[{"id":0},{"id":1}]},"agg":[10,20]}[]"#;
+    assert_eq!(
+        values,
+        vec![
+            Val::Int(expected.len() as i64 - 2),
+            Val::String(expected.as_bytes().to_vec().into()),
+        ]
+    );
+}
+
+#[test]
+fn test_json_encode_stream_propagates_generator_exception() {
+    let src = r#"<?php
+        function failing() {
+            yield 1;
+            throw new RuntimeException("boom");
+        }
+        $stream = fopen('php://memory', 'w+');
+        try {
+            json_encode_stream($stream, failing());
+        } catch (RuntimeException $e) {
+            return $e->getMessage();
+        }
+    "#;
+
+    let (result, _vm) = run_code_with_vm(src).unwrap();
+    assert_eq!(result, Val::String(b"boom".to_vec().into()));
+}
@@ -0,0 +1,246 @@
+//! Compares the lexer's token stream with golden files in PHP's
+//! `token_get_all` naming. Each `tests/lexer_fixtures/cases/*.php` has a
+//! sibling `.tokens` file holding one `NAME text` pair per line, as printed
+//! by `tests/lexer_fixtures/dump_tokens.php`.
+//!
+//! The checked-in `.tokens` files were derived by hand from PHP 8's
+//! `token_get_all` rules, not produced by running `dump_tokens.php`; no PHP
+//! binary was available when they were written. Regenerate them with the
+//! script and review the diff before trusting a mismatch as a lexer bug.
+//! `strings.tokens` deliberately keeps `"$obj?->prop"` as literal text,
+//! which the lexer does not interpolate as a nullsafe fetch.
+
+use php_rs::parser::lexer::Lexer;
+use php_rs::parser::lexer::token::TokenKind;
+use std::path::Path;
+
+fn php_token_name(kind: TokenKind) -> &'static str {
+    use TokenKind::*;
+    match kind {
+        Function => "T_FUNCTION",
+        Class => "T_CLASS",
+        Interface => "T_INTERFACE",
+        Trait => "T_TRAIT",
+        Extends => "T_EXTENDS",
+        Implements => "T_IMPLEMENTS",
+        Enum => "T_ENUM",
+        If => "T_IF",
+        Else => "T_ELSE",
+        ElseIf => "T_ELSEIF",
+        EndIf => "T_ENDIF",
+        Return => "T_RETURN",
+        Echo => "T_ECHO",
+        Print => "T_PRINT",
+        While => "T_WHILE",
+        Do => "T_DO",
+        For => "T_FOR",
+        Foreach => "T_FOREACH",
+        EndWhile => "T_ENDWHILE",
+        EndFor => "T_ENDFOR",
+        EndForeach => "T_ENDFOREACH",
+        As => "T_AS",
+        Switch => "T_SWITCH",
+        EndSwitch => "T_ENDSWITCH",
+        Case => "T_CASE",
+        Default => "T_DEFAULT",
+        Break => "T_BREAK",
+        Continue => "T_CONTINUE",
+        Goto => "T_GOTO",
+        Insteadof => "T_INSTEADOF",
+        Try => "T_TRY",
+        Catch => "T_CATCH",
+        Finally => "T_FINALLY",
+        Throw => "T_THROW",
+        Public => "T_PUBLIC",
+        Protected => "T_PROTECTED",
+        Private => "T_PRIVATE",
+        Static => "T_STATIC",
+        Abstract => "T_ABSTRACT",
+        Final => "T_FINAL",
+        Readonly => "T_READONLY",
+        PublicSet => "T_PUBLIC_SET",
+        ProtectedSet => "T_PROTECTED_SET",
+        PrivateSet => "T_PRIVATE_SET",
+        Namespace => "T_NAMESPACE",
+        Use => "T_USE",
+        Global => "T_GLOBAL",
+        New => "T_NEW",
+        Clone => "T_CLONE",
+        InstanceOf => "T_INSTANCEOF",
+        Array => "T_ARRAY",
+        Const => "T_CONST",
+        Include => "T_INCLUDE",
+        IncludeOnce => "T_INCLUDE_ONCE",
+        Require => "T_REQUIRE",
+        RequireOnce => "T_REQUIRE_ONCE",
+        Eval => "T_EVAL",
+        Exit | Die => "T_EXIT",
+        Empty => "T_EMPTY",
+        Isset => "T_ISSET",
+        Unset => "T_UNSET",
+        List => "T_LIST",
+        Yield => "T_YIELD",
+        YieldFrom => "T_YIELD_FROM",
+        Declare => "T_DECLARE",
+        EndDeclare => "T_ENDDECLARE",
+        Match => "T_MATCH",
+        Fn => "T_FN",
+        HaltCompiler => "T_HALT_COMPILER",
+        Attribute => "T_ATTRIBUTE",
+        Line => "T_LINE",
+        File => "T_FILE",
+        Dir => "T_DIR",
+        ClassC => "T_CLASS_C",
+        TraitC => "T_TRAIT_C",
+        MethodC => "T_METHOD_C",
+        FuncC => "T_FUNC_C",
+        NsC => "T_NS_C",
+        PropertyC => "T_PROPERTY_C",
+        TypeCallable => "T_CALLABLE",
+        // Other type names are plain identifiers to PHP's scanner
+        TypeBool | TypeInt | TypeFloat | TypeString | TypeObject | TypeVoid | TypeIterable
+        | TypeMixed | TypeNever | TypeNull | TypeFalse | TypeTrue | Identifier => "T_STRING",
+        IntCast => "T_INT_CAST",
+        FloatCast => "T_DOUBLE_CAST",
+        StringCast => "T_STRING_CAST",
+        ArrayCast => "T_ARRAY_CAST",
+        ObjectCast => "T_OBJECT_CAST",
+        BoolCast => "T_BOOL_CAST",
+        UnsetCast => "T_UNSET_CAST",
+        VoidCast => "T_VOID_CAST",
+        LNumber => "T_LNUMBER",
+        DNumber => "T_DNUMBER",
+        StringLiteral => "T_CONSTANT_ENCAPSED_STRING",
+        NumString => "T_NUM_STRING",
+        StringVarname => "T_STRING_VARNAME",
+        Variable => "T_VARIABLE",
+        InlineHtml => "T_INLINE_HTML",
+        EncapsedAndWhitespace => "T_ENCAPSED_AND_WHITESPACE",
+        DollarOpenCurlyBraces => "T_DOLLAR_OPEN_CURLY_BRACES",
+        CurlyOpen => "T_CURLY_OPEN",
+        StartHeredoc => "T_START_HEREDOC",
+        EndHeredoc => "T_END_HEREDOC",
+        NsSeparator => "T_NS_SEPARATOR",
+        Comment => "T_COMMENT",
+        DocComment => "T_DOC_COMMENT",
+        Arrow => "T_OBJECT_OPERATOR",
+        NullSafeArrow => "T_NULLSAFE_OBJECT_OPERATOR",
+        DoubleArrow => "T_DOUBLE_ARROW",
+        DoubleColon => "T_DOUBLE_COLON",
+        Ellipsis => "T_ELLIPSIS",
+        Pow => "T_POW",
+        Inc => "T_INC",
+        Dec => "T_DEC",
+        PlusEq => "T_PLUS_EQUAL",
+        MinusEq => "T_MINUS_EQUAL",
+        MulEq => "T_MUL_EQUAL",
+        DivEq => "T_DIV_EQUAL",
+        ModEq => "T_MOD_EQUAL",
+        ConcatEq => "T_CONCAT_EQUAL",
+        PowEq => "T_POW_EQUAL",
+        AndEq => "T_AND_EQUAL",
+        OrEq => "T_OR_EQUAL",
+        XorEq => "T_XOR_EQUAL",
+        SlEq => "T_SL_EQUAL",
+        SrEq => "T_SR_EQUAL",
+        CoalesceEq => "T_COALESCE_EQUAL",
+        EqEq => "T_IS_EQUAL",
+        EqEqEq => "T_IS_IDENTICAL",
+        BangEq => "T_IS_NOT_EQUAL",
+        BangEqEq => "T_IS_NOT_IDENTICAL",
+        LtEq => "T_IS_SMALLER_OR_EQUAL",
+        GtEq => "T_IS_GREATER_OR_EQUAL",
+        Spaceship => "T_SPACESHIP",
+        AmpersandFollowedByVarOrVararg | AmpersandFollowedByVar => {
+            "T_AMPERSAND_FOLLOWED_BY_VAR_OR_VARARG"
+        }
+        AmpersandNotFollowedByVarOrVararg | AmpersandNotFollowedByVar => {
+            "T_AMPERSAND_NOT_FOLLOWED_BY_VAR_OR_VARARG"
+        }
+        Sl => "T_SL",
+        Sr => "T_SR",
+        AmpersandAmpersand => "T_BOOLEAN_AND",
+        PipePipe => "T_BOOLEAN_OR",
+        LogicalAnd => "T_LOGICAL_AND",
+        LogicalOr => "T_LOGICAL_OR",
+        LogicalXor => "T_LOGICAL_XOR",
+        Coalesce => "T_COALESCE",
+        OpenTag => "T_OPEN_TAG",
+        OpenTagEcho => "T_OPEN_TAG_WITH_ECHO",
+        CloseTag => "T_CLOSE_TAG",
+        Backtick | DoubleQuote | Dollar | Plus | Minus | Asterisk | Slash | Percent | Dot | Eq
+        | Bang | Lt | Gt | Ampersand | Pipe | Caret | BitNot | Question | At | SemiColon
+        | Colon | Comma | OpenBrace | CloseBrace | OpenParen | CloseParen | OpenBracket
+        | CloseBracket => "CHAR",
+        Eof | Error => "ERROR",
+    }
+}
+
+fn escape(text: &[u8]) -> String {
+    let mut out = String::new();
+    for c in String::from_utf8_lossy(text).chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The token stream in golden-file form, one `NAME text` line per token
+fn dump_tokens(source: &[u8]) -> String {
+    let mut out = String::new();
+    for token in Lexer::new(source) {
+        if token.kind == TokenKind::Eof {
+            break;
+        }
+        let mut text = token.text(source);
+        if token.kind == TokenKind::OpenTag {
+            text = text.trim_ascii_end();
+        }
+        out.push_str(php_token_name(token.kind));
+        out.push(' ');
+        out.push_str(&escape(text));
+        out.push('\n');
+    }
+    out
+}
+
+#[test]
+fn token_streams_match_php_golden_files() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lexer_fixtures/cases");
+    let mut fixtures: Vec<_> = std::fs::read_dir(&dir)
+        .expect("fixture directory")
+        .map(|entry| entry.expect("fixture entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "php"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no fixtures in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let source = std::fs::read(fixture).expect("read fixture");
+        let golden_path = fixture.with_extension("tokens");
+        let golden = std::fs::read_to_string(&golden_path)
+            .unwrap_or_else(|_| panic!("missing golden file {}", golden_path.display()));
+        let actual = dump_tokens(&source);
+        if actual != golden {
+            let line = actual
+                .lines()
+                .zip(golden.lines())
+                .position(|(a, g)| a != g)
+                .unwrap_or_else(|| actual.lines().count().min(golden.lines().count()));
+            failures.push(format!(
+                "{}: first difference at token {}\n  lexer:  {:?}\n  golden: {:?}",
+                fixture.display(),
+                line + 1,
+                actual.lines().nth(line),
+                golden.lines().nth(line)
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,11 @@
+<?php
+$a = (int)$b;
+$a = ( integer )$b . (bool) $c . (boolean)$c;
+$a = (float)$b . (double)$b . (real)$b;
+$a = (string)$b . (binary)$b . (BINARY)$b;
+$a = (array)$b . (object)$b . (unset)$b;
+$a = (	int	)$b . ( 	Float )$b;
+$a = (int /*x*/)$b;
+$a = (int
+)$b;
+$a = (foo)$b;
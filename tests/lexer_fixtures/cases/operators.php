@@ -0,0 +1,11 @@
+<?php
+$a <> $b; $a != $b; $a !== $b; $a <=> $b;
+$a ??= $b ?? $c;
+$a **= 2 ** 3;
+$a <<= 1 >> 2;
+$a?->b?->c();
+fn&($x) => $x;
+function &f(&$x, &...$y) {}
+$a & $b;
+#[Attr]
+class A { public function __construct(public readonly int $x) {} }
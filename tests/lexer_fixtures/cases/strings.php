@@ -0,0 +1,11 @@
+<?php
+$a = 'plain' . "plain" . b'bin' . B"bin";
+$b = "x $obj?->prop y";
+$c = "x $obj->prop $arr[0] $arr[key] {$obj?->p} ${name}";
+$d = b<<<EOT
+  heredoc $v
+  EOT;
+$e = B<<<'EOT'
+nowdoc $v
+EOT;
+$f = b"interp $v";
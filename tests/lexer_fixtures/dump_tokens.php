@@ -0,0 +1,17 @@
+<?php
+// Regenerates a golden file from PHP's own lexer:
+//   cd tests/lexer_fixtures/cases && php ../dump_tokens.php casts.php > casts.tokens
+// Whitespace tokens are dropped and the open tag loses its trailing
+// whitespace, matching how tests/lexer_conformance.rs compares streams.
+foreach (token_get_all(file_get_contents($argv[1])) as $token) {
+    [$name, $text] = is_array($token)
+        ? [token_name($token[0]), $token[1]]
+        : ['CHAR', $token];
+    if ($name === 'T_WHITESPACE') {
+        continue;
+    }
+    if ($name === 'T_OPEN_TAG') {
+        $text = rtrim($text);
+    }
+    echo $name, ' ', strtr($text, ["\\" => '\\\\', "\n" => '\n', "\r" => '\r', "\t" => '\t']), "\n";
+}
@@ -0,0 +1,107 @@
+use php_rs::parser::lexer::Lexer;
+use php_rs::parser::lexer::token::TokenKind;
+use php_rs::parser::lexer::trivia::lex_with_trivia;
+use php_rs::parser::span::Span;
+
+fn reconstruct(source: &[u8]) -> Vec<u8> {
+    let slice = |span: Span| &source[span.start..span.end];
+    let mut out = Vec::with_capacity(source.len());
+    for item in lex_with_trivia(source) {
+        for span in &item.leading_trivia {
+            let text = slice(*span);
+            assert!(
+                text.iter().all(u8::is_ascii_whitespace)
+                    || [b"//".as_slice(), b"#", b"/*"]
+                        .iter()
+                        .any(|p| text.starts_with(p)),
+                "unexpected trivia {:?}",
+                String::from_utf8_lossy(text)
+            );
+            out.extend_from_slice(text);
+        }
+        out.extend_from_slice(slice(item.token.span));
+    }
+    out
+}
+
+fn assert_round_trip(name: &str, source: &[u8]) {
+    assert_eq!(
+        String::from_utf8_lossy(&reconstruct(source)),
+        String::from_utf8_lossy(source),
+        "{}",
+        name
+    );
+}
+
+#[test]
+fn round_trips_php_files_in_repo() {
+    let mut count = 0;
+    for dir in ["tests", "benchmarks/src"] {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(dir);
+        for entry in std::fs::read_dir(path).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "php") {
+                assert_round_trip(&path.display().to_string(), &std::fs::read(&path).unwrap());
+                count += 1;
+            }
+        }
+    }
+    assert!(count > 0);
+}
+
+#[test]
+fn round_trips_tricky_constructs() {
+    let cases = [
+        "",
+        "plain html only",
+        "<html>\n<?php echo 1; ?>\n<p><?= $x ?></p>\n",
+        "<?php\n\n  /** doc */\n  function f( int $a ) : int { return ( int ) $a; } // tail\n",
+        "<?php # hash comment\n#[Attr] class A { /* block */ }\n\t\r\n",
+        "<?php $s = \"a $b {$c['d']} ${e} \\\" x\"; $t = `ls $dir`;",
+        "<?php echo <<<EOT\n  line $x\n  {$y->z}\n  EOT;\necho <<<'N'\nraw $x\nN;\n",
+        "<?php $o -> prop ?-> other :: CONST;",
+        "#!/usr/bin/env php\n<?php echo 1;",
+        "<?php echo 1;\n__halt_compiler();\nbinary \x00 data",
+        "<?php /* unterminated",
+    ];
+    for source in cases {
+        assert_round_trip(source, source.as_bytes());
+    }
+}
+
+#[test]
+fn comments_and_whitespace_attach_to_following_token() {
+    let source = b"<?php  // note\n/** doc */ $x;";
+    let items: Vec<_> = lex_with_trivia(source).collect();
+    let kinds: Vec<_> = items.iter().map(|item| item.token.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::OpenTag,
+            TokenKind::Variable,
+            TokenKind::SemiColon,
+            TokenKind::Eof
+        ]
+    );
+    let trivia: Vec<&[u8]> = items[1]
+        .leading_trivia
+        .iter()
+        .map(|span| &source[span.start..span.end])
+        .collect();
+    assert_eq!(
+        trivia,
+        [b" ".as_slice(), b"// note", b"\n", b"/** doc */", b" "]
+    );
+}
+
+#[test]
+fn trivia_mode_keeps_lexer_configuration() {
+    let source = b"<? echo 1; ?>";
+    let kinds: Vec<_> = Lexer::new(source)
+        .with_short_open_tag(true)
+        .with_trivia()
+        .map(|item| item.token.kind)
+        .collect();
+    assert_eq!(kinds[0], TokenKind::OpenTag);
+    assert_eq!(kinds.last(), Some(&TokenKind::Eof));
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+use php_rs::parser::span::LineIndex;
+use php_rs::vm::engine::VmError;
+use php_rs::vm::executor::execute_code;
+
+fn runtime_error(code: &str) -> String {
+    match execute_code(code) {
+        Err(VmError::RuntimeError(message)) => message,
+        other => panic!("expected a runtime error, got {:?}", other.map(|r| r.value)),
+    }
+}
+
+#[test]
+fn line_index_maps_offsets_to_line_and_column() {
+    let index = LineIndex::new(b"<?php\necho 1;\n\n$x;");
+    assert_eq!(index.line_col(0), (1, 1));
+    assert_eq!(index.line_col(5), (1, 6));
+    assert_eq!(index.line_col(6), (2, 1));
+    assert_eq!(index.line_col(11), (2, 6));
+    assert_eq!(index.line_col(14), (3, 1));
+    assert_eq!(index.line_col(15), (4, 1));
+    assert_eq!(index.line(18), 4);
+}
+
+#[test]
+fn syntax_error_reports_its_line() {
+    let message = runtime_error("<?php\necho 1;\n$x = ;\necho 2;\n");
+    assert!(message.contains("on line 3"), "{}", message);
+}
+
+#[test]
+fn eval_syntax_error_reports_line_within_evaluated_code() {
+    let message = runtime_error("<?php\n\neval(\"echo 1;\\n\\necho 2 +;\");\n");
+    assert!(
+        message.ends_with("(3) : eval()'d code on line 3"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn line_magic_constant_tracks_each_line() {
+    let code = r#"<?php
+$lines = [__LINE__];
+function f() {
+
+    return __LINE__;
+}
+class C {
+    public function m() { return __LINE__; }
+}
+$lines[] = f();
+$lines[] = (new C)->m();
+$lines[] = (fn() =>
+    __LINE__)();
+$lines[] = __LINE__;
+return implode(',', $lines);
+"#;
+    assert_eq!(run_code(code), Val::String(b"2,5,8,13,14".to_vec().into()));
+}
@@ -1,6 +1,6 @@
 mod common;
 
-use common::run_code;
+use common::{run_code, run_code_with_vm};
 use php_rs::core::value::Val;
 
 #[test]
@@ -36,3 +36,18 @@ fn mb_get_info_has_keys() {
     );
     assert_eq!(val, Val::Bool(true));
 }
+
+#[test]
+fn mb_internal_encoding_drives_default_encoding() {
+    let val = run_code(
+        "<?php $s = \"h\u{e9}llo\"; $before = mb_strlen($s); mb_internal_encoding('latin1'); return implode(',', [$before, mb_strlen($s), mb_strlen($s, null), mb_strlen($s, 'UTF-8'), mb_internal_encoding()]);",
+    );
+    assert_eq!(val, Val::String(b"5,6,6,5,ISO-8859-1".to_vec().into()));
+}
+
+#[test]
+fn mb_internal_encoding_rejects_unknown_encoding() {
+    let result = run_code_with_vm("<?php mb_internal_encoding('NOT-AN-ENCODING');");
+    let err = format!("{:?}", result.err().expect("expected error"));
+    assert!(err.contains("must be a valid encoding"), "{}", err);
+}
@@ -0,0 +1,182 @@
+mod common;
+use common::run_code_capture_output;
+
+#[test]
+fn test_unpack_string_keys_bind_by_name() {
+    let code = r#"<?php
+        function foo($name, $verbose = false, $x = 3) {
+            echo $name, "|", var_export($verbose, true), "|", $x, "\n";
+        }
+        foo(...['name' => 'bob', 'verbose' => true]);
+        foo(...['x' => 7, 'name' => 'amy']);
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(output, "bob|true|3\namy|false|7\n");
+}
+
+#[test]
+fn test_unpack_mixed_int_and_string_keys() {
+    let code = r#"<?php
+        function foo($a, $b = 'b', $c = 'c') {
+            echo $a, $b, $c, "\n";
+        }
+        foo(...[1, 'c' => 9]);
+        $f = function ($a, $b = 0) { echo $a + $b, "\n"; };
+        $f(...['b' => 2, 'a' => 40]);
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(output, "1b9\n42\n");
+}
+
+#[test]
+fn test_unpack_unknown_named_parameter_errors() {
+    let code = r#"<?php
+        function foo($a) {}
+        try {
+            foo(...['nope' => 1]);
+        } catch (Error $e) {
+            echo get_class($e), ": ", $e->getMessage();
+        }
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(output, "Error: Unknown named parameter $nope");
+}
+
+#[test]
+fn test_unpack_named_overwrites_positional_errors() {
+    let code = r#"<?php
+        function foo($a, $b = 2) {}
+        try {
+            foo(...[1, 'a' => 2]);
+        } catch (Error $e) {
+            echo get_class($e), ": ", $e->getMessage();
+        }
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "Error: Named parameter $a overwrites previous argument"
+    );
+}
+
+#[test]
+fn test_unpack_positional_after_named_errors() {
+    let code = r#"<?php
+        function foo($a, $b = 2) {}
+        try {
+            foo(...['a' => 1], ...[2]);
+        } catch (Error $e) {
+            echo get_class($e), ": ", $e->getMessage();
+        }
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "Error: Cannot use positional argument after named argument during unpacking"
+    );
+}
+
+#[test]
+fn test_unpack_string_keys_collected_by_variadic() {
+    let code = r#"<?php
+        function v($first, ...$rest) {
+            echo $first, ":", json_encode($rest), "\n";
+        }
+        v(...[0, 1, 'a' => 2, 'b' => 3]);
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(output, "0:{\"0\":1,\"a\":2,\"b\":3}\n");
+}
+
+#[test]
+fn test_named_argument_errors_are_catchable() {
+    let code = r#"<?php
+        function foo($a, $b = 2) {}
+        function v(...$rest) {}
+        $calls = [
+            fn () => foo(1, c: 3),
+            fn () => foo(1, a: 2),
+            fn () => v(...['x' => 1], ...['x' => 2]),
+        ];
+        foreach ($calls as $call) {
+            try {
+                $call();
+            } catch (Error $e) {
+                echo get_class($e), ": ", $e->getMessage(), "\n";
+            }
+        }
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "Error: Unknown named parameter $c\n\
+         Error: Named parameter $a overwrites previous argument\n\
+         Error: Named parameter $x overwrites previous argument\n"
+    );
+}
+
+#[test]
+fn test_named_arguments_to_builtins_are_rejected() {
+    let code = r#"<?php
+        try {
+            echo strlen(...['string' => 'abc']);
+        } catch (Error $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+        try {
+            echo str_pad("a", 5, pad_type: STR_PAD_LEFT);
+        } catch (Error $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "Error: Unknown named parameter $string\n\
+         Error: Unknown named parameter $pad_type\n"
+    );
+}
+
+#[test]
+fn test_method_call_binds_unpacked_and_named_arguments() {
+    let code = r#"<?php
+        class Foo {
+            public function m($x = 1, $a = 2) { echo $x, "|", $a, "\n"; }
+            public static function s($x = 1, $a = 2) { echo $x, "|", $a, "\n"; }
+        }
+        $obj = new Foo();
+        $obj->m(...['a' => 7]);
+        $obj->m(5, a: 9);
+        $name = 'm';
+        $obj->$name(...[3, 'a' => 4]);
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(output, "1|7\n5|9\n3|4\n");
+}
+
+#[test]
+fn test_skipped_required_parameter_throws_argument_count_error() {
+    let code = r#"<?php
+        function foo($a, $b, $c = 3) {}
+        try {
+            foo(1, c: 2);
+        } catch (ArgumentCountError $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "ArgumentCountError: foo(): Argument #2 ($b) not passed\n"
+    );
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn parse_errors(source: &str) -> Vec<&'static str> {
+    let arena = bumpalo::Bump::new();
+    let lexer = php_rs::parser::lexer::Lexer::new(source.as_bytes());
+    let mut parser = php_rs::parser::parser::Parser::new(lexer, &arena);
+    let program = parser.parse_program();
+    program.errors.iter().map(|e| e.message).collect()
+}
+
+#[test]
+fn unparenthesized_nested_ternary_is_rejected() {
+    assert_eq!(
+        parse_errors("<?php echo 1 ? 2 : 3 ? 4 : 5;"),
+        vec![
+            "Unparenthesized `a ? b : c ? d : e` is not supported. Use either `(a ? b : c) ? d : e` or `a ? b : (c ? d : e)`"
+        ]
+    );
+    assert_eq!(
+        parse_errors("<?php echo 1 ? 2 : 3 ?: 4;"),
+        vec![
+            "Unparenthesized `a ? b : c ?: d` is not supported. Use either `(a ? b : c) ?: d` or `a ? b : (c ?: d)`"
+        ]
+    );
+    assert_eq!(
+        parse_errors("<?php echo 0 ?: 1 ? 2 : 3;"),
+        vec![
+            "Unparenthesized `a ?: b ? c : d` is not supported. Use either `(a ?: b) ? c : d` or `a ?: (b ? c : d)`"
+        ]
+    );
+}
+
+#[test]
+fn parenthesized_nested_ternary_is_accepted() {
+    assert!(parse_errors("<?php echo (1 ? 2 : 3) ? 4 : 5;").is_empty());
+    assert!(parse_errors("<?php echo 1 ? 2 : (3 ? 4 : 5);").is_empty());
+    assert!(parse_errors("<?php echo 1 ? 2 ? 3 : 4 : 5;").is_empty());
+    assert_eq!(
+        run_code("<?php return (false ? 'a' : '') ? 'b' : (false ? 'c' : 'd');"),
+        Val::String(b"d".to_vec().into())
+    );
+}
+
+#[test]
+fn chained_short_ternary_is_accepted() {
+    assert!(parse_errors("<?php echo $a ?: $b ?: 'c';").is_empty());
+    assert_eq!(
+        run_code("<?php $a = 0; $b = ''; return $a ?: $b ?: 'fallback';"),
+        Val::String(b"fallback".to_vec().into())
+    );
+}
@@ -40,6 +40,7 @@ fn run_fetch(op: OpCode) -> (VM, i64) {
         constants: Vec::new(),
         lines: Vec::new(),
         catch_table: Vec::new(),
+        compile_warnings: Vec::new(),
         file_path: None,
     };
 
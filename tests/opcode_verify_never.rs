@@ -32,6 +32,7 @@ fn verify_never_type_errors_on_return() {
         constants: vec![php_rs::core::value::Val::Null],
         lines: vec![],
         catch_table: vec![],
+        compile_warnings: vec![],
         file_path: None,
     };
     let result = vm.run(Rc::new(chunk));
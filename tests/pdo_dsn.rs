@@ -0,0 +1,81 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn bytes(s: &[u8]) -> Val {
+    Val::String(s.to_vec().into())
+}
+
+#[test]
+fn sqlite_memory_dsn_connects() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->exec("CREATE TABLE t (v TEXT)");
+$pdo->exec("INSERT INTO t VALUES ('in memory')");
+return $pdo->query("SELECT v FROM t")->fetch(PDO::FETCH_NUM)[0];
+"#
+        ),
+        bytes(b"in memory")
+    );
+}
+
+#[test]
+fn sqlite_file_dsn_persists_between_connections() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("test.db");
+    let code = format!(
+        r#"<?php
+$first = new PDO('sqlite:{path}');
+$first->exec("CREATE TABLE t (v INTEGER)");
+$first->exec("INSERT INTO t VALUES (42)");
+$second = new PDO('sqlite:{path}');
+return $second->query("SELECT v FROM t")->fetch(PDO::FETCH_NUM)[0];
+"#,
+        path = path.display()
+    );
+    assert_eq!(run_code(&code), Val::Int(42));
+    assert!(path.exists());
+}
+
+#[test]
+fn uri_dsn_and_ini_alias_are_resolved() {
+    let dir = tempfile::tempdir().unwrap();
+    let dsn_file = dir.path().join("dsn.txt");
+    std::fs::write(&dsn_file, "sqlite::memory:\n").unwrap();
+    let code = format!(
+        r#"<?php
+$via_uri = new PDO('uri:file://{dsn_file}');
+ini_set('pdo.dsn.scratch', 'sqlite::memory:');
+$via_alias = new PDO('scratch');
+return $via_uri->query("SELECT 1")->fetch(PDO::FETCH_NUM)[0] . "|" . $via_alias->query("SELECT 2")->fetch(PDO::FETCH_NUM)[0];
+"#,
+        dsn_file = dsn_file.display()
+    );
+    assert_eq!(run_code(&code), bytes(b"1|2"));
+}
+
+#[test]
+fn malformed_dsn_throws_pdo_exception() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$messages = [];
+foreach (['nocolon', ':memory:', 'uri:file:///nonexistent/dsn', 'nosuchdriver:foo'] as $dsn) {
+    try {
+        new PDO($dsn);
+        $messages[] = "connected";
+    } catch (PDOException $e) {
+        $messages[] = $e->getMessage();
+    }
+}
+return implode("|", $messages);
+"#
+        ),
+        bytes(
+            b"invalid data source name|invalid data source name|invalid data source URI|could not find driver"
+        )
+    );
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn bytes(s: &[u8]) -> Val {
+    Val::String(s.to_vec().into())
+}
+
+#[test]
+fn errmode_exception_is_default_and_throws_pdo_exception() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+try {
+    $pdo->exec("SELECT * FROM missing");
+    return "no exception";
+} catch (PDOException $e) {
+    return implode("|", [
+        $pdo->getAttribute(PDO::ATTR_ERRMODE),
+        $e instanceof RuntimeException ? "runtime" : "other",
+        $e->getCode(),
+        $e->getMessage(),
+        implode(",", $e->errorInfo),
+    ]);
+}
+"#
+        ),
+        bytes(
+            b"2|runtime|HY000|SQLSTATE[HY000]: General error: 1 no such table: missing|HY000,1,no such table: missing"
+        )
+    );
+}
+
+#[test]
+fn errmode_silent_returns_false_and_sets_error_info() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:', null, null, [PDO::ATTR_ERRMODE => PDO::ERRMODE_SILENT]);
+$result = $pdo->query("SELECT * FROM missing");
+return var_export($result, true) . "|" . $pdo->errorCode() . "|" . implode(",", $pdo->errorInfo());
+"#
+        ),
+        bytes(b"false|HY000|HY000,1,no such table: missing")
+    );
+}
+
+#[test]
+fn errmode_warning_raises_warning_and_returns_false() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->setAttribute(PDO::ATTR_ERRMODE, PDO::ERRMODE_WARNING);
+$warnings = [];
+set_error_handler(function ($errno, $errstr) use (&$warnings) {
+    $warnings[] = ($errno === E_WARNING ? "W:" : "?:") . $errstr;
+    return true;
+});
+$result = $pdo->exec("SELECT * FROM missing");
+return var_export($result, true) . "|" . implode(";", $warnings);
+"#
+        ),
+        bytes(b"false|W:PDO::exec(): SQLSTATE[HY000]: General error: 1 no such table: missing")
+    );
+}
+
+#[test]
+fn statement_execute_follows_connection_errmode() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->exec("CREATE TABLE t (id INTEGER PRIMARY KEY)");
+$stmt = $pdo->prepare("INSERT INTO t VALUES (1)");
+$stmt->execute();
+
+$pdo->setAttribute(PDO::ATTR_ERRMODE, PDO::ERRMODE_SILENT);
+$silent = var_export($stmt->execute(), true) . ":" . implode(",", $stmt->errorInfo());
+
+$pdo->setAttribute(PDO::ATTR_ERRMODE, PDO::ERRMODE_EXCEPTION);
+try {
+    $stmt->execute();
+    $thrown = "no exception";
+} catch (PDOException $e) {
+    $thrown = $e->getCode() . ":" . $e->errorInfo[1];
+}
+return $silent . "|" . $thrown;
+"#
+        ),
+        bytes(b"false:23000,19,UNIQUE constraint failed: t.id|23000:19")
+    );
+}
+
+#[test]
+fn error_info_has_three_elements_without_error() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$info = $pdo->errorInfo();
+return count($info) . "|" . $info[0] . "|" . var_export($info[1], true);
+"#
+        ),
+        bytes(b"3|00000|NULL")
+    );
+}
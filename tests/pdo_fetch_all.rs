@@ -0,0 +1,71 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn fetch_all_json(query: &str, mode: &str) -> Val {
+    run_code(&format!(
+        r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->exec("CREATE TABLE t (grp TEXT, id INTEGER, name TEXT)");
+$pdo->exec("INSERT INTO t VALUES ('a', 1, 'x'), ('b', 2, 'y'), ('a', 3, 'z')");
+return json_encode($pdo->query("{query}")->fetchAll({mode}));
+"#
+    ))
+}
+
+fn json(s: &str) -> Val {
+    Val::String(s.as_bytes().to_vec().into())
+}
+
+#[test]
+fn fetch_all_column() {
+    assert_eq!(
+        fetch_all_json("SELECT * FROM t", "PDO::FETCH_COLUMN"),
+        json(r#"["a","b","a"]"#)
+    );
+    assert_eq!(
+        fetch_all_json("SELECT * FROM t", "PDO::FETCH_COLUMN, 2"),
+        json(r#"["x","y","z"]"#)
+    );
+}
+
+#[test]
+fn fetch_all_key_pair() {
+    assert_eq!(
+        fetch_all_json("SELECT id, name FROM t", "PDO::FETCH_KEY_PAIR"),
+        json(r#"{"1":"x","2":"y","3":"z"}"#)
+    );
+}
+
+#[test]
+fn fetch_all_group_assoc() {
+    assert_eq!(
+        fetch_all_json("SELECT * FROM t", "PDO::FETCH_GROUP | PDO::FETCH_ASSOC"),
+        json(r#"{"a":[{"id":1,"name":"x"},{"id":3,"name":"z"}],"b":[{"id":2,"name":"y"}]}"#)
+    );
+}
+
+#[test]
+fn fetch_all_unique_assoc() {
+    assert_eq!(
+        fetch_all_json(
+            "SELECT id, grp, name FROM t",
+            "PDO::FETCH_UNIQUE | PDO::FETCH_ASSOC"
+        ),
+        json(
+            r#"{"1":{"grp":"a","name":"x"},"2":{"grp":"b","name":"y"},"3":{"grp":"a","name":"z"}}"#
+        )
+    );
+}
+
+#[test]
+fn fetch_all_group_column() {
+    assert_eq!(
+        fetch_all_json(
+            "SELECT grp, name FROM t",
+            "PDO::FETCH_GROUP | PDO::FETCH_COLUMN"
+        ),
+        json(r#"{"a":["x","z"],"b":["y"]}"#)
+    );
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn bytes(s: &[u8]) -> Val {
+    Val::String(s.to_vec().into())
+}
+
+#[test]
+fn stringify_fetches_controls_native_types() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$types = [gettype($pdo->query("SELECT 1+1")->fetch(PDO::FETCH_NUM)[0])];
+$pdo->setAttribute(PDO::ATTR_STRINGIFY_FETCHES, true);
+$row = $pdo->query("SELECT 1+1, 2.5, NULL")->fetch(PDO::FETCH_NUM);
+$types[] = gettype($row[0]) . ":" . $row[0];
+$types[] = gettype($row[1]) . ":" . $row[1];
+$types[] = gettype($row[2]);
+$types[] = var_export($pdo->getAttribute(PDO::ATTR_STRINGIFY_FETCHES), true);
+return implode("|", $types);
+"#
+        ),
+        bytes(b"integer|string:2|string:2.5|NULL|true")
+    );
+}
+
+#[test]
+fn stringify_fetches_from_constructor_options() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:', null, null, [PDO::ATTR_STRINGIFY_FETCHES => true]);
+$rows = $pdo->query("SELECT 1+1 AS n")->fetchAll(PDO::FETCH_ASSOC);
+return gettype($rows[0]['n']);
+"#
+        ),
+        bytes(b"string")
+    );
+}
+
+#[test]
+fn oracle_nulls_converts_empty_strings_and_nulls() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->setAttribute(PDO::ATTR_ORACLE_NULLS, PDO::NULL_EMPTY_STRING);
+$row = $pdo->query("SELECT '', NULL")->fetch(PDO::FETCH_NUM);
+$out = [gettype($row[0]), gettype($row[1])];
+$pdo->setAttribute(PDO::ATTR_ORACLE_NULLS, PDO::NULL_TO_STRING);
+$row = $pdo->query("SELECT '', NULL")->fetch(PDO::FETCH_NUM);
+$out[] = gettype($row[0]);
+$out[] = gettype($row[1]);
+$out[] = $pdo->getAttribute(PDO::ATTR_ORACLE_NULLS);
+return implode("|", $out);
+"#
+        ),
+        bytes(b"NULL|NULL|string|string|2")
+    );
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn bytes(s: &[u8]) -> Val {
+    Val::String(s.to_vec().into())
+}
+
+#[test]
+fn foreach_walks_rows_with_default_fetch_mode_and_second_pass_is_empty() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->exec("CREATE TABLE t (id INTEGER, name TEXT)");
+$pdo->exec("INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'c')");
+$pdo->setAttribute(PDO::ATTR_DEFAULT_FETCH_MODE, PDO::FETCH_ASSOC);
+$stmt = $pdo->query("SELECT id, name FROM t ORDER BY id");
+
+$first = [];
+foreach ($stmt as $key => $row) {
+    $first[] = $key . "=" . json_encode($row);
+}
+$second = 0;
+foreach ($stmt as $row) {
+    $second++;
+}
+return implode(";", $first) . "|" . $second . "|" . ($stmt instanceof Traversable ? "traversable" : "-");
+"#
+        ),
+        bytes(
+            br#"0={"id":1,"name":"a"};1={"id":2,"name":"b"};2={"id":3,"name":"c"}|0|traversable"#
+        )
+    );
+}
+
+#[test]
+fn foreach_respects_set_fetch_mode() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->exec("CREATE TABLE t (id INTEGER, name TEXT)");
+$pdo->exec("INSERT INTO t VALUES (1, 'a'), (2, 'b')");
+$stmt = $pdo->prepare("SELECT id, name FROM t ORDER BY id");
+$stmt->execute();
+$stmt->setFetchMode(PDO::FETCH_NUM);
+$rows = [];
+foreach ($stmt as $row) {
+    $rows[] = json_encode($row);
+}
+return implode(";", $rows);
+"#
+        ),
+        bytes(br#"[1,"a"];[2,"b"]"#)
+    );
+}
+
+#[test]
+fn foreach_uses_set_fetch_mode_arguments() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+class Item {
+    public $id;
+    public $name;
+    public $label;
+    public function __construct($prefix) {
+        $this->label = $prefix . $this->name;
+    }
+}
+$pdo = new PDO('sqlite::memory:');
+$pdo->exec("CREATE TABLE t (id INTEGER, name TEXT)");
+$pdo->exec("INSERT INTO t VALUES (1, 'a'), (2, 'b')");
+
+$stmt = $pdo->query("SELECT id, name FROM t ORDER BY id");
+$stmt->setFetchMode(PDO::FETCH_COLUMN, 1);
+$names = [];
+foreach ($stmt as $name) {
+    $names[] = $name;
+}
+
+$stmt = $pdo->query("SELECT id, name FROM t ORDER BY id");
+$stmt->setFetchMode(PDO::FETCH_CLASS, 'Item', ['item-']);
+$labels = [];
+foreach ($stmt as $item) {
+    $labels[] = get_class($item) . ":" . $item->id . ":" . $item->label;
+}
+return implode(",", $names) . "|" . implode(",", $labels);
+"#
+        ),
+        bytes(b"a,b|Item:1:item-a,Item:2:item-b")
+    );
+}
+
+#[test]
+fn statement_is_iterator_aggregate() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$stmt = $pdo->query("SELECT 1 AS one");
+$iterator = $stmt->getIterator();
+return implode(",", [
+    $stmt instanceof IteratorAggregate ? "aggregate" : "-",
+    $stmt instanceof Iterator ? "iterator" : "-",
+    method_exists($stmt, 'current') ? "current" : "-",
+    $iterator instanceof Iterator ? get_class($iterator) : "-",
+]);
+"#
+        ),
+        bytes(b"aggregate,-,-,InternalIterator")
+    );
+}
@@ -0,0 +1,92 @@
+//! PDO pgsql driver against a live server.
+//!
+//! These run only when PDO_PGSQL_TEST_DSN names a server to use, e.g.
+//! `PDO_PGSQL_TEST_DSN="pgsql:host=localhost;dbname=test;user=postgres"`.
+#![cfg(feature = "pdo-pgsql")]
+
+mod common;
+
+use common::run_code_capture_output;
+
+fn run_pgsql(body: &str) -> Option<String> {
+    let Ok(dsn) = std::env::var("PDO_PGSQL_TEST_DSN") else {
+        eprintln!("PDO_PGSQL_TEST_DSN not set, skipping");
+        return None;
+    };
+    let code = format!(
+        "<?php\n$pdo = new PDO('{}');\n{}",
+        dsn.replace('\'', "\\'"),
+        body
+    );
+    let (_val, output) = run_code_capture_output(&code).expect("Execution failed");
+    Some(output)
+}
+
+#[test]
+fn pgsql_binds_and_returns_typed_values() {
+    let Some(output) = run_pgsql(
+        r#"
+$pdo->exec("CREATE TEMP TABLE items (id serial PRIMARY KEY, n int4, f float8, ok bool, data bytea, name text, price numeric(8,2), day date)");
+$stmt = $pdo->prepare("INSERT INTO items (n, f, ok, data, name, price, day) VALUES (?, ?, ?, ?, ?, ?, ?)");
+$stmt->execute([42, 1.5, true, "a\0b", "it's", "12.30", "2024-02-29"]);
+$stmt = $pdo->prepare("SELECT n, f, ok, data, name, price, day, n::text AS t FROM items WHERE name = :name AND n > :min");
+$stmt->execute([':name' => "it's", ':min' => 1]);
+$row = $stmt->fetch(PDO::FETCH_ASSOC);
+echo bin2hex($row['data']), "\n";
+unset($row['data']);
+var_export($row);
+"#,
+    ) else {
+        return;
+    };
+    assert_eq!(
+        output,
+        "610062\narray (\n  'n' => 42,\n  'f' => 1.5,\n  'ok' => true,\n  'name' => 'it\\'s',\n  'price' => '12.30',\n  'day' => '2024-02-29',\n  't' => '42',\n)"
+    );
+}
+
+#[test]
+fn pgsql_transactions_and_last_insert_id() {
+    let Some(output) = run_pgsql(
+        r#"
+$pdo->exec("CREATE TEMP TABLE seqs (id serial PRIMARY KEY, v text)");
+$pdo->beginTransaction();
+$pdo->exec("INSERT INTO seqs (v) VALUES ('rolled back')");
+$pdo->rollBack();
+$pdo->beginTransaction();
+$pdo->prepare("INSERT INTO seqs (v) VALUES (?)")->execute(['kept']);
+$pdo->commit();
+echo $pdo->lastInsertId(), " ", $pdo->lastInsertId('seqs_id_seq'), " ";
+echo $pdo->query("SELECT count(*) FROM seqs")->fetch(PDO::FETCH_NUM)[0], " ";
+echo $pdo->getAttribute(PDO::ATTR_SERVER_VERSION) !== '' ? "version" : "none";
+"#,
+    ) else {
+        return;
+    };
+    assert_eq!(output, "2 2 1 version");
+}
+
+#[test]
+fn pgsql_error_info_carries_server_details() {
+    let Some(output) = run_pgsql(
+        r#"
+$pdo->setAttribute(PDO::ATTR_ERRMODE, PDO::ERRMODE_SILENT);
+var_dump($pdo->exec("SELECT * FROM missing_table"));
+$info = $pdo->errorInfo();
+echo $info[0], " ", $info[1], " ", $info[2], "\n";
+$pdo->setAttribute(PDO::ATTR_ERRMODE, PDO::ERRMODE_EXCEPTION);
+try {
+    $pdo->query("SELECT nope FROM (SELECT 1) t");
+} catch (PDOException $e) {
+    echo $e->getMessage();
+}
+"#,
+    ) else {
+        return;
+    };
+    assert_eq!(
+        output,
+        "bool(false)\n42P01 7 ERROR:  relation \"missing_table\" does not exist\n\
+         SQLSTATE[42703]: Undefined column: 7 ERROR:  column \"nope\" does not exist"
+    );
+}
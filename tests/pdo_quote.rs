@@ -0,0 +1,69 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn bytes(s: &[u8]) -> Val {
+    Val::String(s.to_vec().into())
+}
+
+#[test]
+fn quote_sqlite_doubles_single_quotes() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+return $pdo->quote("it's a \\ test");
+"#
+        ),
+        bytes(b"'it''s a \\ test'")
+    );
+}
+
+#[test]
+fn quote_sqlite_round_trips_through_exec() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->exec("CREATE TABLE t (v TEXT)");
+$values = ["O'Reilly", "back\\slash", "nul\0byte", "'; DROP TABLE t; --"];
+foreach ($values as $v) {
+    $pdo->exec("INSERT INTO t VALUES (" . $pdo->quote($v) . ")");
+}
+return implode("|", $pdo->query("SELECT v FROM t")->fetchAll(PDO::FETCH_COLUMN));
+"#
+        ),
+        bytes(b"O'Reilly|back\\slash|nul\0byte|'; DROP TABLE t; --")
+    );
+}
+
+#[test]
+fn quote_sqlite_nul_bytes_use_blob_literal() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+return $pdo->quote("a\0b");
+"#
+        ),
+        bytes(b"x'610062'")
+    );
+}
+
+#[test]
+fn quote_param_int_casts_numerics() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+return implode(",", [
+    $pdo->quote("42", PDO::PARAM_INT),
+    $pdo->quote(" 7 ", PDO::PARAM_INT),
+    $pdo->quote("abc", PDO::PARAM_INT),
+]);
+"#
+        ),
+        bytes(b"42,7,'abc'")
+    );
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn bytes(s: &[u8]) -> Val {
+    Val::String(s.to_vec().into())
+}
+
+#[test]
+fn sqlite_create_function_is_callable_from_select() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$registered = $pdo->sqliteCreateFunction('rot13', 'str_rot13', 1);
+$pdo->exec("CREATE TABLE t (name TEXT)");
+$pdo->exec("INSERT INTO t VALUES ('apple'), ('cherry')");
+$rows = $pdo->query("SELECT rot13(name) FROM t ORDER BY name")->fetchAll(PDO::FETCH_COLUMN);
+return var_export($registered, true) . "|" . implode(",", $rows);
+"#
+        ),
+        bytes(b"true|nccyr,pureel")
+    );
+}
+
+#[test]
+fn sqlite_create_function_supports_regexp_operator() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->sqliteCreateFunction('regexp', function ($pattern, $value) {
+    return preg_match('/' . $pattern . '/', $value);
+}, 2, PDO::SQLITE_DETERMINISTIC);
+$pdo->exec("CREATE TABLE t (name TEXT)");
+$pdo->exec("INSERT INTO t VALUES ('apple'), ('banana'), ('cherry')");
+$stmt = $pdo->prepare("SELECT name FROM t WHERE name REGEXP ? ORDER BY name");
+$stmt->execute(['^(a|c)']);
+return implode(",", $stmt->fetchAll(PDO::FETCH_COLUMN));
+"#
+        ),
+        bytes(b"apple,cherry")
+    );
+}
+
+#[test]
+fn sqlite_create_function_converts_argument_and_return_types() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->sqliteCreateFunction('describe', function (...$args) {
+    return implode(",", array_map('gettype', $args));
+});
+$pdo->sqliteCreateFunction('twice', fn ($n) => $n * 2, 1);
+$row = $pdo->query("SELECT describe(1, 2.5, 'x', NULL), twice(1.25), twice(2)")->fetch(PDO::FETCH_NUM);
+return implode("|", array_map(fn ($v) => gettype($v) . ":" . $v, $row));
+"#
+        ),
+        bytes(b"string:integer,double,string,NULL|double:2.5|integer:4")
+    );
+}
+
+#[test]
+fn sqlite_create_function_rethrows_callback_exception() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->sqliteCreateFunction('boom', function () {
+    throw new RuntimeException('boom!');
+});
+try {
+    $pdo->query("SELECT boom()");
+    $result = "no exception";
+} catch (RuntimeException $e) {
+    $result = get_class($e) . ": " . $e->getMessage();
+}
+return $result . "|" . implode(",", $pdo->query("SELECT 1")->fetchAll(PDO::FETCH_COLUMN));
+"#
+        ),
+        bytes(b"RuntimeException: boom!|1")
+    );
+}
+
+#[test]
+fn sqlite_create_function_enforces_argument_count() {
+    assert_eq!(
+        run_code(
+            r#"<?php
+$pdo = new PDO('sqlite::memory:');
+$pdo->sqliteCreateFunction('rot13', 'str_rot13', 1);
+try {
+    $pdo->query("SELECT rot13('a', 'b')");
+    return "no exception";
+} catch (PDOException $e) {
+    return $e->getCode();
+}
+"#
+        ),
+        bytes(b"HY000")
+    );
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::run_code_capture_output;
+use php_rs::core::value::Val;
+
+#[test]
+fn test_print_returns_one() {
+    let (val, output) = run_code_capture_output(
+        r#"<?php
+        $x = print "hi";
+        return $x;
+    "#,
+    )
+    .unwrap();
+    assert_eq!(output, "hi");
+    assert_eq!(val, Val::Int(1));
+}
+
+#[test]
+fn test_print_in_arithmetic_expression() {
+    let (val, output) = run_code_capture_output(
+        r#"<?php
+        return 5 + (print "b");
+    "#,
+    )
+    .unwrap();
+    assert_eq!(output, "b");
+    assert_eq!(val, Val::Int(6));
+}
+
+#[test]
+fn test_print_operand_binds_logical_and() {
+    // print has lower precedence than &&, so the whole `$msg && false`
+    // is printed and the assignment still receives 1.
+    let (val, output) = run_code_capture_output(
+        r#"<?php
+        $msg = "a";
+        $ok = print($msg) && false;
+        return $ok;
+    "#,
+    )
+    .unwrap();
+    assert_eq!(output, "");
+    assert_eq!(val, Val::Int(1));
+}
+
+#[test]
+fn test_print_in_ternary_branches() {
+    let (_, output) = run_code_capture_output(
+        r#"<?php
+        $a = false;
+        $a ? print "A" : print "B";
+        print $a ? "x" : "y";
+    "#,
+    )
+    .unwrap();
+    assert_eq!(output, "By");
+}
+
+#[test]
+fn test_print_with_logical_and_keyword() {
+    let (val, output) = run_code_capture_output(
+        r#"<?php
+        $r = print "a" and print "b";
+        return $r;
+    "#,
+    )
+    .unwrap();
+    assert_eq!(output, "ab");
+    assert_eq!(val, Val::Int(1));
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{FailingRng, run_code};
+use php_rs::core::value::Val;
+
+#[test]
+fn test_random_bytes_length() {
+    let src = "<?php return strlen(random_bytes(32)) + (random_bytes(16) !== random_bytes(16));";
+    let result = run_code(src);
+    assert_eq!(result, Val::Int(33));
+}
+
+#[test]
+fn test_random_int_range() {
+    let src = "<?php
+        $ok = random_int(1, 1) === 1 && random_int(-3, -3) === -3;
+        for ($i = 0; $i < 200; $i++) {
+            $n = random_int(-5, 10);
+            $ok = $ok && $n >= -5 && $n <= 10;
+        }
+        return $ok && is_int(random_int(PHP_INT_MIN, PHP_INT_MAX));";
+    let result = run_code(src);
+    assert_eq!(result, Val::Bool(true));
+}
+
+#[test]
+fn test_random_bytes_oversized_length_is_an_error() {
+    let err = php_rs::vm::executor::execute_code("<?php random_bytes(PHP_INT_MAX);")
+        .expect_err("random_bytes(PHP_INT_MAX) should fail");
+    assert!(
+        format!("{:?}", err).contains("Possible integer overflow in memory allocation"),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn test_random_invalid_arguments_throw_value_error() {
+    let src = r#"<?php
+        $out = [];
+        foreach ([fn () => random_bytes(0), fn () => random_int(5, 1)] as $call) {
+            try {
+                $call();
+                $out[] = "no exception";
+            } catch (ValueError $e) {
+                $out[] = get_class($e) . ": " . $e->getMessage();
+            }
+        }
+        return implode("\n", $out);"#;
+    let result = run_code(src);
+    assert_eq!(
+        result,
+        Val::String(
+            b"ValueError: random_bytes(): Argument #1 ($length) must be greater than 0\n\
+              ValueError: random_int(): Argument #1 ($min) must be less than or equal to argument #2 ($max)"
+                .to_vec()
+                .into()
+        )
+    );
+}
+
+#[test]
+fn test_random_source_failure_throws_exception() {
+    let src = r#"<?php
+        $out = [];
+        foreach ([fn () => random_bytes(16), fn () => random_int(1, 10)] as $call) {
+            try {
+                $call();
+                $out[] = "no exception";
+            } catch (Exception $e) {
+                $out[] = get_class($e) . ": " . $e->getMessage();
+            }
+        }
+        return implode("\n", $out);"#;
+    php_rs::builtins::math::set_secure_rng(Some(Box::new(FailingRng)));
+    let result = run_code(src);
+    php_rs::builtins::math::set_secure_rng(None);
+    assert_eq!(
+        result,
+        Val::String(
+            b"Exception: Could not gather sufficient random data\n\
+              Exception: Could not gather sufficient random data"
+                .to_vec()
+                .into()
+        )
+    );
+}
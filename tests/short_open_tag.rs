@@ -0,0 +1,93 @@
+use php_rs::parser::lexer::Lexer;
+use php_rs::parser::lexer::token::TokenKind;
+use php_rs::vm::executor::{ExecutionConfig, execute_code_with_config};
+
+fn token_kinds(source: &str, short_open_tag: bool) -> Vec<TokenKind> {
+    let mut lexer = Lexer::new(source.as_bytes()).with_short_open_tag(short_open_tag);
+    let mut kinds = Vec::new();
+    loop {
+        let token = lexer.next().expect("lexer ended without Eof");
+        if token.kind == TokenKind::Eof {
+            return kinds;
+        }
+        kinds.push(token.kind);
+    }
+}
+
+fn run_template(source: &str, short_open_tag: &str) -> String {
+    let mut config = ExecutionConfig::default();
+    config
+        .ini_settings
+        .insert("short_open_tag".to_string(), short_open_tag.to_string());
+    execute_code_with_config(source, config)
+        .expect("execution failed")
+        .stdout
+}
+
+#[test]
+fn short_tag_is_inline_html_when_disabled() {
+    assert_eq!(
+        token_kinds("<?xml version=\"1.0\"?>\n<? echo 1; ?>", false),
+        vec![TokenKind::InlineHtml]
+    );
+}
+
+#[test]
+fn short_tag_opens_php_when_enabled() {
+    assert_eq!(
+        token_kinds("a<? echo 1 ?>b<?php echo 2; ?><?= 3 ?>", true),
+        vec![
+            TokenKind::InlineHtml,
+            TokenKind::OpenTag,
+            TokenKind::Echo,
+            TokenKind::LNumber,
+            TokenKind::CloseTag,
+            TokenKind::InlineHtml,
+            TokenKind::OpenTag,
+            TokenKind::Echo,
+            TokenKind::LNumber,
+            TokenKind::SemiColon,
+            TokenKind::CloseTag,
+            TokenKind::OpenTagEcho,
+            TokenKind::LNumber,
+            TokenKind::CloseTag,
+        ]
+    );
+}
+
+#[test]
+fn short_tag_right_after_close_tag() {
+    assert_eq!(
+        token_kinds("<? $a = 1; ?><? echo $a;", true),
+        vec![
+            TokenKind::OpenTag,
+            TokenKind::Variable,
+            TokenKind::Eq,
+            TokenKind::LNumber,
+            TokenKind::SemiColon,
+            TokenKind::CloseTag,
+            TokenKind::OpenTag,
+            TokenKind::Echo,
+            TokenKind::Variable,
+            TokenKind::SemiColon,
+        ]
+    );
+}
+
+#[test]
+fn short_tag_template_executes() {
+    let template = "<? $items = ['a', 'b']; ?><ul><? foreach ($items as $i) : ?><li><?= $i ?></li><? endforeach; ?></ul><? if (count($items) > 1) : ?>many<? else : ?>few<? endif ?>";
+    assert_eq!(
+        run_template(template, "1"),
+        "<ul><li>a</li><li>b</li></ul>many"
+    );
+}
+
+#[test]
+fn short_tag_template_is_output_verbatim_when_disabled() {
+    let template = "<?xml version=\"1.0\"?><? echo 'x'; ?><?= 1 + 1 ?>";
+    assert_eq!(
+        run_template(template, "0"),
+        "<?xml version=\"1.0\"?><? echo 'x'; ?>2"
+    );
+}
@@ -0,0 +1,95 @@
+mod common;
+use common::run_code_capture_output;
+
+fn temp_file(contents: &str) -> (tempfile::TempDir, String) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("input.txt");
+    std::fs::write(&path, contents).unwrap();
+    (dir, path.to_string_lossy().into_owned())
+}
+
+#[test]
+fn test_spl_file_object_iterates_lines_with_drop_new_line() {
+    let (_dir, path) = temp_file("first\nsecond\n\nfourth\n");
+    let code = format!(
+        r#"<?php
+        $file = new SplFileObject('{path}');
+        foreach ($file as $n => $line) {{
+            echo $n, "=", json_encode($line), " ";
+        }}
+        echo "\n";
+        $file->setFlags(SplFileObject::DROP_NEW_LINE);
+        foreach ($file as $n => $line) {{
+            echo $n, "=", json_encode($line), " ";
+        }}
+        echo "\n";
+        $file->setFlags(SplFileObject::DROP_NEW_LINE | SplFileObject::SKIP_EMPTY | SplFileObject::READ_AHEAD);
+        foreach ($file as $n => $line) {{
+            echo $n, "=", $line, " ";
+        }}
+        echo "\n";
+    "#
+    );
+
+    let (_val, output) = run_code_capture_output(&code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "0=\"first\\n\" 1=\"second\\n\" 2=\"\\n\" 3=\"fourth\\n\" 4=\"\" \n\
+         0=\"first\" 1=\"second\" 2=\"\" 3=\"fourth\" 4=\"\" \n\
+         0=first 1=second 3=fourth \n"
+    );
+}
+
+#[test]
+fn test_spl_file_object_reads_csv_rows() {
+    let (_dir, path) = temp_file("name,qty\n\"Smith, J\",3\n\n\"two\nlines\",4\n");
+    let code = format!(
+        r#"<?php
+        $file = new SplFileObject('{path}');
+        $file->setFlags(SplFileObject::READ_CSV | SplFileObject::SKIP_EMPTY | SplFileObject::READ_AHEAD);
+        foreach ($file as $row) {{
+            echo json_encode($row), "\n";
+        }}
+        $file->setFlags(0);
+        $file->rewind();
+        echo json_encode($file->fgetcsv()), "\n";
+    "#
+    );
+
+    let (_val, output) = run_code_capture_output(&code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "[\"name\",\"qty\"]\n[\"Smith, J\",\"3\"]\n[\"two\\nlines\",\"4\"]\n[\"name\",\"qty\"]\n"
+    );
+}
+
+#[test]
+fn test_spl_file_object_read_write_and_seek() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("out.txt").to_string_lossy().into_owned();
+    let code = format!(
+        r#"<?php
+        $file = new SplFileObject('{path}', 'w+');
+        echo $file->fwrite("hello\nworld\n"), " ";
+        $file->fseek(6);
+        echo $file->fread(5), " ", $file->ftell(), " ";
+        $file->rewind();
+        echo trim($file->fgets()), " ", var_export($file->eof(), true), " ";
+        $file->fseek(0, SEEK_END);
+        echo json_encode($file->fgets()), " ", var_export($file->eof(), true), " ";
+        try {{
+            $file->fgets();
+        }} catch (RuntimeException $e) {{
+            echo "caught";
+        }}
+        try {{
+            new SplFileObject('{path}.missing');
+        }} catch (RuntimeException $e) {{
+            echo " ", str_contains($e->getMessage(), "Failed to open stream") ? "missing" : "?";
+        }}
+    "#
+    );
+
+    let (_val, output) = run_code_capture_output(&code).expect("Execution failed");
+    assert_eq!(output, "12 world 11 hello false \"\" true caught missing");
+}
@@ -0,0 +1,103 @@
+mod common;
+use common::run_code_capture_output;
+
+#[test]
+fn test_spl_fixed_array_get_set_in_range() {
+    let code = r#"<?php
+        $a = new SplFixedArray(3);
+        $a[0] = "zero";
+        $a[2] = 2;
+        var_dump($a[0], $a[1], $a[2], count($a));
+        var_dump(isset($a[0]), isset($a[1]), isset($a[5]));
+        unset($a[0]);
+        var_dump($a[0]);
+        foreach ($a as $k => $v) {
+            echo $k, "=", var_export($v, true), " ";
+        }
+        echo "\n";
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "string(4) \"zero\"\nNULL\nint(2)\nint(3)\nbool(true)\nbool(false)\nbool(false)\nNULL\n0=NULL 1=NULL 2=2 \n"
+    );
+}
+
+#[test]
+fn test_spl_fixed_array_out_of_range_errors() {
+    let code = r#"<?php
+        $a = new SplFixedArray(2);
+        try {
+            $a[2] = "x";
+        } catch (RuntimeException $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+        try {
+            echo $a[-1];
+        } catch (RuntimeException $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+        try {
+            $a->offsetGet(5);
+        } catch (RuntimeException $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "RuntimeException: Index invalid or out of range\n\
+         RuntimeException: Index invalid or out of range\n\
+         RuntimeException: Index invalid or out of range\n"
+    );
+}
+
+#[test]
+fn test_spl_fixed_array_negative_size_throws_value_error() {
+    let code = r#"<?php
+        try {
+            new SplFixedArray(-1);
+        } catch (ValueError $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+        $a = new SplFixedArray(2);
+        try {
+            $a->setSize(-1);
+        } catch (ValueError $e) {
+            echo get_class($e), ": ", $e->getMessage(), "\n";
+        }
+        echo $a->getSize(), "\n";
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "ValueError: SplFixedArray::__construct(): Argument #1 ($size) must be greater than or equal to 0\n\
+         ValueError: SplFixedArray::setSize(): Argument #1 ($size) must be greater than or equal to 0\n\
+         2\n"
+    );
+}
+
+#[test]
+fn test_spl_fixed_array_set_size_grows_and_shrinks() {
+    let code = r#"<?php
+        $a = SplFixedArray::fromArray([1, 2, 3]);
+        $a->setSize(5);
+        $a[4] = 5;
+        echo json_encode($a->toArray()), " ", $a->getSize(), "\n";
+        $a->setSize(2);
+        echo json_encode($a->toArray()), " ", count($a), "\n";
+        $b = SplFixedArray::fromArray([3 => 'd', 1 => 'b']);
+        echo json_encode($b->toArray()), "\n";
+        $c = SplFixedArray::fromArray([3 => 'd', 1 => 'b'], false);
+        echo json_encode($c->toArray()), "\n";
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "[1,2,3,null,5] 5\n[1,2] 2\n[null,\"b\",null,\"d\"]\n[\"d\",\"b\"]\n"
+    );
+}
@@ -0,0 +1,116 @@
+mod common;
+use common::run_code_capture_output;
+
+#[test]
+fn test_spl_min_heap_extracts_in_ascending_order() {
+    let code = r#"<?php
+        $h = new SplMinHeap();
+        foreach ([5, 1, 8, 3, 9, 2] as $n) {
+            $h->insert($n);
+        }
+        echo count($h), ":", $h->top(), "\n";
+        $out = [];
+        while (!$h->isEmpty()) {
+            $out[] = $h->extract();
+        }
+        echo implode(",", $out), "\n";
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(output, "6:1\n1,2,3,5,8,9\n");
+}
+
+#[test]
+fn test_spl_max_heap_foreach_consumes_heap() {
+    let code = r#"<?php
+        $h = new SplMaxHeap();
+        foreach (["b", "d", "a", "c"] as $s) {
+            $h->insert($s);
+        }
+        foreach ($h as $k => $v) {
+            echo $k, "=", $v, " ";
+        }
+        echo "\n", count($h), "\n";
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(output, "3=d 2=c 1=b 0=a \n0\n");
+}
+
+#[test]
+fn test_spl_priority_queue_order_and_equal_priorities() {
+    let code = r#"<?php
+        $q = new SplPriorityQueue();
+        $q->insert("low", 1);
+        $q->insert("first-high", 10);
+        $q->insert("mid", 5);
+        $q->insert("second-high", 10);
+        $q->insert("third-high", 10);
+        $out = [];
+        while ($q->valid()) {
+            $out[] = $q->extract();
+        }
+        echo implode(",", $out), "\n";
+
+        $q->insert("x", 3);
+        $q->setExtractFlags(SplPriorityQueue::EXTR_BOTH);
+        echo json_encode($q->top()), "\n";
+        $q->setExtractFlags(SplPriorityQueue::EXTR_PRIORITY);
+        echo $q->extract(), "\n";
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "first-high,second-high,third-high,mid,low\n{\"data\":\"x\",\"priority\":3}\n3\n"
+    );
+}
+
+#[test]
+fn test_spl_heap_user_compare() {
+    let code = r#"<?php
+        class LengthHeap extends SplHeap {
+            protected function compare($a, $b): int {
+                return strlen($a) - strlen($b);
+            }
+        }
+        $h = new LengthHeap();
+        foreach (["aa", "a", "aaaa", "aaa"] as $s) {
+            $h->insert($s);
+        }
+        foreach ($h as $v) {
+            echo $v, " ";
+        }
+        echo "\n";
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(output, "aaaa aaa aa a \n");
+}
+
+#[test]
+fn test_spl_heap_extract_from_empty_errors() {
+    let code = r#"<?php
+        foreach ([new SplMinHeap(), new SplPriorityQueue()] as $h) {
+            try {
+                $h->extract();
+            } catch (RuntimeException $e) {
+                echo get_class($e), ": ", $e->getMessage(), "\n";
+            }
+            try {
+                $h->top();
+            } catch (RuntimeException $e) {
+                echo get_class($e), ": ", $e->getMessage(), "\n";
+            }
+        }
+    "#;
+
+    let (_val, output) = run_code_capture_output(code).expect("Execution failed");
+    assert_eq!(
+        output,
+        "RuntimeException: Can't extract from an empty heap\n\
+         RuntimeException: Can't peek at an empty heap\n\
+         RuntimeException: Can't extract from an empty heap\n\
+         RuntimeException: Can't peek at an empty heap\n"
+    );
+}
@@ -1,28 +1,17 @@
 mod common;
 
-use common::{run_code, run_code_with_vm};
+use common::{run_code, run_code_uncaught_exception};
 use php_rs::core::value::Val;
-use php_rs::vm::engine::VmError;
 
 fn expect_type_error(src: &str, expected_msg: &str) {
-    match run_code_with_vm(src) {
-        Err(VmError::RuntimeError(msg)) => {
-            assert!(
-                msg.contains(expected_msg),
-                "Expected error containing '{}', got: {}",
-                expected_msg,
-                msg
-            );
-        }
-        Err(e) => panic!(
-            "Expected RuntimeError with '{}', got: {:?}",
-            expected_msg, e
-        ),
-        Ok(_) => panic!(
-            "Expected error containing '{}', but code succeeded",
-            expected_msg
-        ),
-    }
+    let (class, msg) = run_code_uncaught_exception(src);
+    assert_eq!(class, "TypeError", "Unexpected exception: {}", msg);
+    assert!(
+        msg.contains(expected_msg),
+        "Expected error containing '{}', got: {}",
+        expected_msg,
+        msg
+    );
 }
 
 // ========================================
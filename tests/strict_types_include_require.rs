@@ -1,8 +1,10 @@
+mod common;
+
 use php_rs::compiler::emitter::Emitter;
 use php_rs::parser::lexer::Lexer;
 use php_rs::parser::parser::Parser;
 use php_rs::runtime::context::EngineBuilder;
-use php_rs::vm::engine::VM;
+use php_rs::vm::engine::{VM, VmError};
 use std::fs;
 use std::path::PathBuf;
 use std::rc::Rc;
@@ -46,6 +48,10 @@ fn compile_and_run(code: &str) -> Result<(), String> {
 
     match vm.run(Rc::new(chunk)) {
         Ok(_) => Ok(()),
+        Err(VmError::Exception(ex_handle)) => {
+            let (class, message) = common::exception_class_and_message(&vm, ex_handle);
+            Err(format!("Uncaught {}: {}", class, message))
+        }
         Err(e) => Err(format!("{:?}", e)),
     }
 }
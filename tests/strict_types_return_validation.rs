@@ -1,28 +1,17 @@
 mod common;
 
-use common::{run_code, run_code_with_vm};
+use common::{run_code, run_code_uncaught_exception};
 use php_rs::core::value::Val;
-use php_rs::vm::engine::VmError;
 
 fn expect_type_error(src: &str, expected_msg: &str) {
-    match run_code_with_vm(src) {
-        Err(VmError::RuntimeError(msg)) => {
-            assert!(
-                msg.contains(expected_msg),
-                "Expected error containing '{}', got: {}",
-                expected_msg,
-                msg
-            );
-        }
-        Err(e) => panic!(
-            "Expected RuntimeError with '{}', got: {:?}",
-            expected_msg, e
-        ),
-        Ok(_) => panic!(
-            "Expected error containing '{}', but code succeeded",
-            expected_msg
-        ),
-    }
+    let (class, msg) = run_code_uncaught_exception(src);
+    assert_eq!(class, "TypeError", "Unexpected exception: {}", msg);
+    assert!(
+        msg.contains(expected_msg),
+        "Expected error containing '{}', got: {}",
+        expected_msg,
+        msg
+    );
 }
 
 #[test]
@@ -199,3 +188,30 @@ return getFloat();
         _ => panic!("Expected Float, got {:?}", val),
     }
 }
+
+#[test]
+fn test_return_type_mismatch_is_catchable_type_error() {
+    let src = r#"<?php
+declare(strict_types=1);
+
+function getIterator(): Iterator {
+    return [1];
+}
+
+try {
+    getIterator();
+    return "no error";
+} catch (TypeError $e) {
+    return $e->getMessage();
+}
+"#;
+    let val = run_code(src);
+    assert_eq!(
+        val,
+        Val::String(
+            b"getIterator(): Return value must be of type Iterator, array returned"
+                .to_vec()
+                .into()
+        )
+    );
+}
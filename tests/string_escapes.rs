@@ -0,0 +1,110 @@
+mod common;
+
+use common::run_code;
+use php_rs::core::value::Val;
+
+fn bytes(s: &[u8]) -> Val {
+    Val::String(s.to_vec().into())
+}
+
+fn parse_errors(source: &str) -> Vec<&'static str> {
+    let arena = bumpalo::Bump::new();
+    let lexer = php_rs::parser::lexer::Lexer::new(source.as_bytes());
+    let mut parser = php_rs::parser::parser::Parser::new(lexer, &arena);
+    let program = parser.parse_program();
+    program.errors.iter().map(|e| e.message).collect()
+}
+
+#[test]
+fn double_quoted_escape_battery() {
+    let cases: &[(&str, &[u8])] = &[
+        (r#""\n\t\r\v\e\f""#, b"\n\t\r\x0B\x1B\x0C"),
+        (r#""\\ \$ \"""#, b"\\ $ \""),
+        (r#""\0\12\101\1234""#, b"\0\nA\x534"),
+        (r#""\8\9""#, b"\\8\\9"),
+        (r#""\x41\x4\x4g\xZ""#, b"A\x04\x04g\\xZ"),
+        (r#""\u{41}\u{e9}\u{20AC}\u{1F600}""#, "Aé€😀".as_bytes()),
+        (r#""\u{0000041}\u0041""#, b"A\\u0041"),
+        (r#""\'\q\ x""#, b"\\'\\q\\ x"),
+        (r#"b"\x7f""#, b"\x7f"),
+    ];
+    for (literal, expected) in cases {
+        assert_eq!(
+            run_code(&format!("<?php return {};", literal)),
+            bytes(expected),
+            "{}",
+            literal
+        );
+    }
+}
+
+#[test]
+fn single_quoted_keeps_only_backslash_and_quote_escapes() {
+    assert_eq!(
+        run_code(r"<?php return 'a\'b\\c\n\t\x41\u{41}\101\$';"),
+        bytes(br"a'b\c\n\t\x41\u{41}\101\$")
+    );
+}
+
+#[test]
+fn heredoc_decodes_escapes_but_keeps_quotes() {
+    assert_eq!(
+        run_code("<?php\n$x = 1;\nreturn <<<EOT\n\"hi\" \\\"q\\\" \\t$x\\u{41}\\x42\nEOT;\n"),
+        bytes(b"\"hi\" \\\"q\\\" \t1AB")
+    );
+}
+
+#[test]
+fn nowdoc_decodes_nothing() {
+    assert_eq!(
+        run_code("<?php return <<<'EOT'\nA\\tB \\\\ \"q\" \\u{41}\nEOT;\n"),
+        bytes(b"A\\tB \\\\ \"q\" \\u{41}")
+    );
+}
+
+#[test]
+fn interpolated_string_decodes_around_variables() {
+    assert_eq!(
+        run_code("<?php $v = 'x'; return \"\\101$v\\u{42}{$v}\\\"\";"),
+        bytes(b"AxBx\"")
+    );
+}
+
+#[test]
+fn octal_overflow_wraps_and_warns() {
+    assert_eq!(
+        run_code(
+            r#"<?php $s = "\400\777"; return bin2hex($s) . '|' . error_get_last()['message'];"#
+        ),
+        bytes(b"00ff|Octal escape sequence overflow \\777 is greater than \\377")
+    );
+}
+
+#[test]
+fn invalid_codepoint_escapes_are_parse_errors() {
+    assert_eq!(
+        parse_errors(r#"<?php echo "\u{}";"#),
+        vec!["Invalid UTF-8 codepoint escape sequence"]
+    );
+    assert_eq!(
+        parse_errors(r#"<?php echo "\u{12g}";"#),
+        vec!["Invalid UTF-8 codepoint escape sequence"]
+    );
+    assert_eq!(
+        parse_errors(r#"<?php echo "$a \u{110000}";"#),
+        vec!["Invalid UTF-8 codepoint escape sequence: Codepoint too large"]
+    );
+    assert_eq!(
+        parse_errors("<?php echo <<<EOT\n\\u{FFFFFFFFF}\nEOT;\n"),
+        vec!["Invalid UTF-8 codepoint escape sequence: Codepoint too large"]
+    );
+    assert!(
+        parse_errors(
+            r#"<?php echo '\u{}', <<<'EOT'
+\u{zz}
+EOT;
+"#
+        )
+        .is_empty()
+    );
+}
@@ -497,6 +497,14 @@ fn test_str_shuffle_basic() {
     assert_eq!(result, Val::Int(5));
 }
 
+#[test]
+fn test_openssl_random_pseudo_bytes_reports_strong_source() {
+    let src = "<?php $strong = false; $b = openssl_random_pseudo_bytes(8, $strong);
+        return strlen($b) === 8 && $strong === true;";
+    let (result, _, _) = run_code(src);
+    assert_eq!(result, Val::Bool(true));
+}
+
 #[test]
 fn test_str_split_basic() {
     let src = "<?php return str_split('hello', 2);";
@@ -917,6 +925,23 @@ fn test_vprintf_vsprintf_basic() {
     assert_eq!(result, Val::Int(4));
 }
 
+#[test]
+fn test_printf_returns_byte_count_through_output_buffer() {
+    let src = r#"<?php
+        ob_start();
+        $n = printf('%s=%03d;', 'é', 7);
+        $m = vprintf('[%s]', ['ü']);
+        $out = ob_get_clean();
+        $s = sprintf('%s=%03d;', 'é', 7);
+        return $n . '|' . $m . '|' . $out . '|' . $s;
+    "#;
+    let (result, _, _) = run_code(src);
+    assert_eq!(
+        result,
+        Val::String("7|4|é=007;[ü]|é=007;".as_bytes().to_vec().into())
+    );
+}
+
 #[test]
 fn test_fprintf_vfprintf_basic() {
     let src = "<?php
@@ -52,6 +52,16 @@ echo "$a\n$b\n";
     assert_eq!(output, "foo\nbar\n");
 }
 
+#[test]
+fn test_nullsafe_arrow_stays_literal_in_string() {
+    let code = r#"<?php
+$v = "a";
+echo "$v?->b\n";
+"#;
+    let (_, output) = run_code_capture_output(code).unwrap();
+    assert_eq!(output, "a?->b\n");
+}
+
 #[test]
 fn test_unset_property_array_element() {
     let code = r#"<?php
@@ -123,3 +123,57 @@ fn test_match_error() {
     ";
     run_code_vm_only(source);
 }
+
+#[test]
+fn test_match_arm_calls_function() {
+    let source = "<?php
+        function label($n) { return 'n=' . $n; }
+        $i = 3;
+        return match (true) {
+            $i < 2 => label($i * 10),
+            $i < 5 => label($i + 1),
+            default => 'big',
+        };
+    ";
+
+    let vm = run_code_vm_only(source);
+    let ret = get_return_value(&vm);
+    assert_eq!(ret, Val::String(b"n=4".to_vec().into()));
+}
+
+#[test]
+fn test_match_default_arm_checked_last() {
+    let source = "<?php
+        $res = match (1) {
+            default => 'default',
+            1 => 'one',
+        };
+        return $res;
+    ";
+
+    let vm = run_code_vm_only(source);
+    let ret = get_return_value(&vm);
+    assert_eq!(ret, Val::String(b"one".to_vec().into()));
+}
+
+#[test]
+fn test_match_arm_throws() {
+    let source = "<?php
+        function check($v) {
+            return match ($v) {
+                'a' => 1,
+                default => throw new InvalidArgumentException('bad ' . $v),
+            };
+        }
+        try {
+            check('z');
+            return 'no throw';
+        } catch (InvalidArgumentException $e) {
+            return check('a') . ':' . $e->getMessage();
+        }
+    ";
+
+    let vm = run_code_vm_only(source);
+    let ret = get_return_value(&vm);
+    assert_eq!(ret, Val::String(b"1:bad z".to_vec().into()));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::run_code_capture_output;
+
+/// Object ids depend on allocation order, so compare without them
+fn strip_object_ids(output: &str) -> String {
+    let mut out = String::new();
+    let mut chars = output.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '#' {
+            while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+                chars.next();
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[test]
+fn test_var_dump_uses_debug_info() {
+    let (_val, output) = run_code_capture_output(
+        r#"<?php
+        class Credentials {
+            private $password = 'hunter2';
+            public $user = 'bob';
+            public function __debugInfo() {
+                return ['user' => $this->user, 'password' => '***'];
+            }
+        }
+        class Holder {
+            public $credentials;
+            public $count = 1;
+        }
+        $holder = new Holder();
+        $holder->credentials = new Credentials();
+        var_dump(new Credentials());
+        var_dump($holder);
+    "#,
+    )
+    .expect("Execution failed");
+    assert!(!output.contains("hunter2"));
+    assert_eq!(
+        strip_object_ids(&output),
+        "object(Credentials) (2) {\n  [\"user\"]=>\n  string(3) \"bob\"\n  [\"password\"]=>\n  string(3) \"***\"\n}\n\
+         object(Holder) (2) {\n  [\"credentials\"]=>\n  object(Credentials) (2) {\n    [\"user\"]=>\n    string(3) \"bob\"\n    [\"password\"]=>\n    string(3) \"***\"\n  }\n  [\"count\"]=>\n  int(1)\n}\n"
+    );
+}
+
+#[test]
+fn test_var_dump_without_debug_info_lists_properties() {
+    let (_val, output) = run_code_capture_output(
+        r#"<?php
+        class Point {
+            public $x = 1;
+            public $y = 2;
+        }
+        var_dump(new Point());
+    "#,
+    )
+    .expect("Execution failed");
+    assert_eq!(
+        strip_object_ids(&output),
+        "object(Point) (2) {\n  [\"x\"]=>\n  int(1)\n  [\"y\"]=>\n  int(2)\n}\n"
+    );
+}
@@ -1,3 +1,6 @@
+mod common;
+
+use common::run_code_capture_output;
 use indexmap::IndexMap;
 use php_rs::compiler::chunk::CodeChunk;
 use php_rs::core::value::{ObjectData, Val};
@@ -380,3 +383,787 @@ fn test_zip_archive_extract_to() {
     php_rs::builtins::zip::php_zip_archive_close(&mut vm, &[]).unwrap();
     vm.frames.pop();
 }
+
+fn php_string_literal(path: &std::path::Path) -> String {
+    format!(
+        "'{}'",
+        path.to_str()
+            .unwrap()
+            .replace('\\', "\\\\")
+            .replace('\'', "\\'")
+    )
+}
+
+#[test]
+fn test_zip_archive_open_not_a_zip_reports_status() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let bogus = temp_dir.path().join("bogus.zip");
+    fs::write(&bogus, b"this is not a zip archive").unwrap();
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $res = $zip->open({});
+        var_dump($res === ZipArchive::ER_NOZIP);
+        var_dump($zip->status);
+        echo $zip->getStatusString();
+    "#,
+        php_string_literal(&bogus)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(output, "bool(true)\nint(19)\nNot a zip archive");
+}
+
+#[test]
+fn test_zip_archive_open_error_codes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let missing = temp_dir.path().join("missing.zip");
+    let existing = temp_dir.path().join("existing.zip");
+    {
+        let file = fs::File::create(&existing).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        zip.start_file("a.txt", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.finish().unwrap();
+    }
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        echo $zip->getStatusString(), "\n";
+        var_dump($zip->open({missing}) === ZipArchive::ER_NOENT);
+        echo $zip->getStatusString(), "\n";
+        var_dump($zip->open({existing}, ZipArchive::CREATE | ZipArchive::EXCL) === ZipArchive::ER_EXISTS);
+        echo $zip->getStatusString(), "\n";
+        var_dump($zip->open({existing}, ZipArchive::CHECKCONS));
+        var_dump($zip->status, $zip->statusSys);
+        echo $zip->getStatusString();
+    "#,
+        missing = php_string_literal(&missing),
+        existing = php_string_literal(&existing)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "No error\nbool(true)\nNo such file\nbool(true)\nFile already exists\nbool(true)\nint(0)\nint(0)\nNo error"
+    );
+}
+
+#[test]
+fn test_zip_archive_set_archive_comment() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("comment.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("a.txt", "a");
+        var_dump($zip->setArchiveComment("archive comment"));
+        var_dump($zip->getArchiveComment(), $zip->comment);
+        var_dump($zip->getArchiveComment(ZipArchive::FL_UNCHANGED));
+        $zip->close();
+
+        $zip->open({path});
+        var_dump($zip->getArchiveComment());
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\nstring(15) \"archive comment\"\nstring(15) \"archive comment\"\nstring(0) \"\"\nstring(15) \"archive comment\"\n"
+    );
+
+    let archive = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+    assert_eq!(archive.comment(), b"archive comment");
+}
+
+#[test]
+fn test_zip_archive_extract_to_with_entries_filter() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("filter.zip");
+    let dest = temp_dir.path().join("out");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("a.txt", "a");
+        $zip->addFromString("b.txt", "b");
+        $zip->addFromString("sub/c.txt", "c");
+        $zip->close();
+
+        $zip->open({path});
+        $zip->addFromString("d.txt", "pending");
+        var_dump($zip->extractTo({dest}, ["a.txt", "sub/c.txt"]));
+        var_dump($zip->extractTo({dest}, "d.txt"));
+        var_dump($zip->extractTo({dest}, ["a.txt", "missing.txt"]));
+    "#,
+        path = php_string_literal(&zip_path),
+        dest = php_string_literal(&dest)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(output, "bool(true)\nbool(true)\nbool(false)\n");
+
+    assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "a");
+    assert_eq!(fs::read_to_string(dest.join("sub/c.txt")).unwrap(), "c");
+    assert_eq!(fs::read_to_string(dest.join("d.txt")).unwrap(), "pending");
+    assert!(!dest.join("b.txt").exists());
+}
+
+#[test]
+fn test_zip_archive_extract_to_refuses_escaping_entry() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("evil.zip");
+    let dest = temp_dir.path().join("out");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("safe.txt", "ok");
+        $zip->addFromString("../evil", "pwned");
+        var_dump($zip->extractTo({dest}));
+    "#,
+        path = php_string_literal(&zip_path),
+        dest = php_string_literal(&dest)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(output, "bool(false)\n");
+
+    assert!(!temp_dir.path().join("evil").exists());
+    assert!(!dest.join("safe.txt").exists());
+}
+
+#[test]
+fn test_zip_archive_extract_to_filter_skips_escaping_entry() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("mixed.zip");
+    let dest = temp_dir.path().join("out");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("one.txt", "1");
+        $zip->addFromString("two.txt", "2");
+        $zip->addFromString("../evil", "pwned");
+        $zip->close();
+
+        $zip->open({path});
+        var_dump($zip->extractTo({dest}, ["one.txt", "two.txt"]));
+        var_dump($zip->extractTo({dest}, ["one.txt", "../evil"]));
+    "#,
+        path = php_string_literal(&zip_path),
+        dest = php_string_literal(&dest)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(output, "bool(true)\nbool(false)\n");
+
+    assert_eq!(fs::read_to_string(dest.join("one.txt")).unwrap(), "1");
+    assert_eq!(fs::read_to_string(dest.join("two.txt")).unwrap(), "2");
+    assert!(!temp_dir.path().join("evil").exists());
+}
+
+#[test]
+fn test_zip_archive_get_stream_reads_in_chunks() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("stream.zip");
+
+    let code = format!(
+        r#"<?php
+        $parts = [];
+        for ($i = 0; $i < 256; $i++) {{
+            $parts[] = str_repeat(md5($i, true), 256);
+        }}
+        $data = implode('', $parts);
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("big.bin", $data);
+        $zip->close();
+
+        $zip->open({path});
+        $zip->addFromString("pending.txt", "line one\nline two\n");
+
+        $fp = $zip->getStream("big.bin");
+        $read = '';
+        $chunks = 0;
+        while (!feof($fp)) {{
+            $read .= fread($fp, 4096);
+            $chunks++;
+        }}
+        fclose($fp);
+        var_dump(strlen($read), $read === $zip->getFromName("big.bin"), $chunks > 256);
+
+        $fp = $zip->getStreamIndex(1);
+        var_dump(fgets($fp), fgets($fp), fgets($fp));
+        var_dump($zip->getStream("missing.bin"));
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "int(1048576)\nbool(true)\nbool(true)\nstring(9) \"line one\n\"\nstring(9) \"line two\n\"\nbool(false)\nbool(false)\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_stat_flags_and_pending_additions() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("stat.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("README.TXT", str_repeat("readme ", 100));
+        $zip->close();
+
+        $zip->open({path});
+        var_dump($zip->statName("readme.txt"));
+        $stat = $zip->statName("readme.txt", ZipArchive::FL_NOCASE);
+        var_dump($stat["name"], $stat["size"], $stat["comp_method"] === ZipArchive::CM_DEFLATE);
+        var_dump($stat["encryption_method"]);
+        var_dump($zip->locateName("readme.txt", ZipArchive::FL_NOCASE));
+
+        $before = time();
+        $zip->addFromString("new.txt", "hello");
+        $stat = $zip->statName("new.txt");
+        var_dump($stat["index"], $stat["size"], $stat["crc"] === crc32("hello"));
+        var_dump($stat["mtime"] >= $before && $stat["mtime"] <= time());
+        var_dump($zip->statIndex(1, ZipArchive::FL_UNCHANGED));
+
+        $zip->deleteName("README.TXT");
+        var_dump($zip->statIndex(0));
+        var_dump($zip->statIndex(0, ZipArchive::FL_UNCHANGED)["name"]);
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(false)\nstring(10) \"README.TXT\"\nint(700)\nbool(true)\nint(0)\nint(0)\n\
+         int(1)\nint(5)\nbool(true)\nbool(true)\nbool(false)\n\
+         bool(false)\nstring(10) \"README.TXT\"\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_stat_reports_mtime_and_compression() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("mtime.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("deflated.txt", str_repeat("deflate me ", 100));
+        $zip->close();
+
+        $zip->open({path});
+        $stat = $zip->statName("deflated.txt");
+        var_dump($stat["mtime"] > 0, abs($stat["mtime"] - time()) < 120);
+        var_dump($stat["comp_method"] === ZipArchive::CM_DEFLATE);
+        var_dump($zip->statIndex(0)["mtime"] === $stat["mtime"]);
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(output, "bool(true)\nbool(true)\nbool(true)\nbool(true)\n");
+}
+
+#[test]
+fn test_zip_procedural_read_in_chunks() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("chunks.zip");
+
+    {
+        let file = fs::File::create(&zip_path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        use std::io::Write;
+        zip.start_file("a.txt", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"abcdefghijklmnopqrstuvwxyz").unwrap();
+        zip.start_file(
+            "b.txt",
+            zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored),
+        )
+        .unwrap();
+        zip.write_all(b"0123456789").unwrap();
+        zip.finish().unwrap();
+    }
+
+    let code = format!(
+        r#"<?php
+        $zip = zip_open({path});
+        while (($entry = zip_read($zip)) !== false) {{
+            echo zip_entry_name($entry), " ", zip_entry_compressionmethod($entry), ":";
+            var_dump(zip_entry_open($zip, $entry, "rb"));
+            while (($chunk = zip_entry_read($entry, 10)) !== "") {{
+                echo "[", $chunk, "]";
+            }}
+            echo "\n";
+            zip_entry_close($entry);
+            echo zip_entry_read($entry), "\n";
+        }}
+        var_dump(zip_read($zip));
+        zip_close($zip);
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "a.txt deflated:bool(true)\n[abcdefghij][klmnopqrst][uvwxyz]\nabcdefghijklmnopqrstuvwxyz\n\
+         b.txt stored:bool(true)\n[0123456789]\n0123456789\n\
+         bool(false)\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_add_glob_and_pattern() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let tree = temp_dir.path().join("tree");
+    fs::create_dir_all(tree.join("src/nested")).unwrap();
+    fs::write(tree.join("src/lib.rs"), "lib").unwrap();
+    fs::write(tree.join("src/main.rs"), "main").unwrap();
+    fs::write(tree.join("src/notes.txt"), "notes").unwrap();
+    fs::write(tree.join("src/nested/deep.rs"), "deep").unwrap();
+    let zip_path = temp_dir.path().join("glob.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $added = $zip->addGlob({tree} . "/src/*.rs", 0, ['add_path' => 'code/', 'remove_path' => {tree}]);
+        echo count($added), " ", $zip->numFiles, "\n";
+        $zip->addPattern('/\.txt$/', {tree} . "/src", ['remove_all_path' => true]);
+        echo $zip->numFiles, "\n";
+        $zip->close();
+
+        $zip->open({path});
+        for ($i = 0; $i < $zip->numFiles; $i++) {{
+            echo $zip->getNameIndex($i), "\n";
+        }}
+    "#,
+        path = php_string_literal(&zip_path),
+        tree = php_string_literal(&tree)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "2 2\n3\ncode/src/lib.rs\ncode/src/main.rs\nnotes.txt\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_replace_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("replace.zip");
+    let source = temp_dir.path().join("source.txt");
+    fs::write(&source, "replacement").unwrap();
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("a.txt", "original a");
+        $zip->addFromString("b.txt", "original b");
+        $zip->close();
+
+        $zip->open({path});
+        var_dump($zip->replaceFile({source}, 1));
+        var_dump($zip->replaceFile({source}, 5));
+        echo $zip->numFiles, "\n";
+        $zip->close();
+
+        $zip->open({path});
+        echo $zip->numFiles, " ", $zip->getFromName("a.txt"), " ", $zip->getFromName("b.txt"), "\n";
+    "#,
+        path = php_string_literal(&zip_path),
+        source = php_string_literal(&source)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\nbool(false)\n2\n2 original a replacement\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_count_on_fresh_archive() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("fresh.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        var_dump(count($zip));
+        $zip->addFromString("a.txt", "a");
+        $zip->addFromString("b.txt", "b");
+        var_dump(count($zip) === 2);
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(output, "int(0)\nbool(true)\n");
+}
+
+#[test]
+fn test_zip_archive_count_tracks_pending_changes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("count.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("a.txt", "a");
+        $zip->addFromString("b.txt", "b");
+        $zip->close();
+
+        $zip->open({path});
+        var_dump($zip instanceof Countable);
+        $zip->addFromString("c.txt", "c");
+        var_dump($zip->deleteIndex(0));
+        var_dump($zip->deleteName("missing.txt"));
+        var_dump($zip->deleteName("a.txt"));
+        echo $zip->numFiles, " ", count($zip), " ", $zip->count(), "\n";
+        $zip->close();
+
+        $zip->open({path});
+        echo $zip->numFiles, " ", count($zip), "\n";
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\nbool(true)\nbool(false)\nbool(false)\n2 2 2\n2 2\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_status_string_after_failures() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let gone_dir = temp_dir.path().join("gone");
+    fs::create_dir(&gone_dir).unwrap();
+    let zip_path = gone_dir.join("out.zip");
+    let missing = temp_dir.path().join("missing.txt");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        var_dump($zip->open({missing_zip}) === ZipArchive::ER_NOENT);
+        echo $zip->getStatusString(), "\n";
+
+        $zip->open({path}, ZipArchive::CREATE);
+        var_dump($zip->addFile({missing}, "missing.txt"));
+        var_dump($zip->status === ZipArchive::ER_NOENT);
+        echo $zip->getStatusString(), "\n";
+
+        $zip->addFromString("a.txt", "a");
+        rmdir({gone});
+        var_dump(@$zip->close());
+        var_dump($zip->status === ZipArchive::ER_TMPOPEN);
+        echo $zip->getStatusString(), "\n";
+    "#,
+        missing_zip = php_string_literal(&missing.with_extension("zip")),
+        path = php_string_literal(&zip_path),
+        missing = php_string_literal(&missing),
+        gone = php_string_literal(&gone_dir)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\nNo such file\nbool(false)\nbool(true)\nNo such file\nbool(false)\nbool(true)\n\
+         Failure to create temporary file: No such file or directory\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_set_compression() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("methods.zip");
+
+    let code = format!(
+        r#"<?php
+        $data = str_repeat("compressible text ", 200);
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("stored.txt", $data);
+        $zip->addFromString("deflated.txt", $data);
+        $zip->addFromString("bzipped.txt", $data);
+        var_dump($zip->setCompressionName("stored.txt", ZipArchive::CM_STORE));
+        var_dump($zip->setCompressionIndex(1, ZipArchive::CM_DEFLATE, 9));
+        var_dump($zip->setCompressionName("bzipped.txt", ZipArchive::CM_BZIP2));
+        var_dump($zip->setCompressionName("missing.txt", ZipArchive::CM_STORE));
+        var_dump($zip->setCompressionIndex(7, ZipArchive::CM_STORE));
+        var_dump($zip->setCompressionName("stored.txt", ZipArchive::CM_SHRINK));
+        $zip->close();
+
+        $zip->open({path});
+        foreach (["stored.txt", "deflated.txt", "bzipped.txt"] as $name) {{
+            $stat = $zip->statName($name);
+            echo $name, " ", $stat["comp_method"], " ",
+                $stat["comp_size"] < $stat["size"] ? "smaller" : "same", " ",
+                $zip->getFromName($name) === $data ? "ok" : "corrupt", "\n";
+        }}
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\nbool(true)\nbool(true)\nbool(false)\nbool(false)\nbool(false)\n\
+         stored.txt 0 same ok\ndeflated.txt 8 smaller ok\nbzipped.txt 12 smaller ok\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_set_encryption() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("encrypted.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("secret.txt", "top secret");
+        $zip->addFromString("plain.txt", "public");
+        var_dump($zip->setEncryptionName("secret.txt", ZipArchive::EM_AES_256, "hunter2"));
+        var_dump($zip->setEncryptionName("missing.txt", ZipArchive::EM_AES_256, "hunter2"));
+        var_dump($zip->setEncryptionIndex(1, ZipArchive::EM_TRAD_PKWARE, "hunter2"));
+        var_dump($zip->close());
+
+        $zip->open({path});
+        echo $zip->statName("secret.txt")["encryption_method"], " ",
+            $zip->statName("plain.txt")["encryption_method"], "\n";
+        var_dump($zip->getFromName("secret.txt"));
+        echo $zip->getStatusString(), "\n";
+        $zip->setPassword("wrong");
+        var_dump($zip->getFromName("secret.txt"));
+        echo $zip->getStatusString(), "\n";
+        $zip->setPassword("hunter2");
+        var_dump($zip->getFromName("secret.txt"), $zip->getFromName("plain.txt"));
+
+        // EM_NONE stores the entry decrypted on the next close()
+        $zip->setEncryptionName("secret.txt", ZipArchive::EM_NONE);
+        $zip->close();
+        $zip->open({path});
+        var_dump($zip->getFromName("secret.txt"));
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\nbool(false)\nbool(false)\nbool(true)\n259 0\n\
+         bool(false)\nNo password provided\nbool(false)\nWrong password provided\n\
+         string(10) \"top secret\"\nstring(6) \"public\"\nstring(10) \"top secret\"\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_set_mtime() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("mtime.zip");
+
+    // MS-DOS timestamps have a two second resolution
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("fixed.txt", "fixed");
+        $zip->addFromString("fresh.txt", "fresh");
+        var_dump($zip->setMtimeName("fixed.txt", 1700000000));
+        var_dump($zip->setMtimeName("missing.txt", 1700000000));
+        var_dump($zip->statName("fixed.txt")["mtime"]);
+        $before = time() - 2;
+        $zip->close();
+
+        $zip->open({path});
+        var_dump($zip->statName("fixed.txt")["mtime"]);
+        $fresh = $zip->statName("fresh.txt")["mtime"];
+        var_dump($fresh >= $before && $fresh <= time());
+        var_dump($zip->setMtimeIndex(1, 1600000000, 0));
+        $zip->close();
+
+        $zip->open({path});
+        var_dump($zip->statIndex(0)["mtime"], $zip->statIndex(1)["mtime"]);
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\nbool(false)\nint(1700000000)\nint(1700000000)\nbool(true)\n\
+         bool(true)\nint(1700000000)\nint(1600000000)\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_open_overwrite_replaces_archive() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("overwrite.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("old1.txt", "old");
+        $zip->addFromString("old2.txt", "old");
+        $zip->close();
+
+        var_dump($zip->open({path}, ZipArchive::OVERWRITE));
+        var_dump($zip->numFiles, filesize({path}));
+        $zip->addFromString("new.txt", "new");
+        var_dump($zip->close());
+
+        $zip->open({path});
+        var_dump($zip->numFiles, $zip->getNameIndex(0), $zip->getFromName("new.txt"));
+        $zip->close();
+
+        var_dump($zip->open({path}, ZipArchive::CREATE | ZipArchive::OVERWRITE));
+        var_dump($zip->close());
+        $zip->open({path});
+        var_dump($zip->numFiles);
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\nint(0)\nint(0)\nbool(true)\n\
+         int(1)\nstring(7) \"new.txt\"\nstring(3) \"new\"\n\
+         bool(true)\nbool(true)\nint(0)\n"
+    );
+}
+
+#[test]
+fn test_zip_open_returns_error_codes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("ok.zip");
+    let not_zip = temp_dir.path().join("plain.txt");
+    fs::write(&not_zip, "just text").unwrap();
+
+    {
+        let file = fs::File::create(&zip_path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        use std::io::Write;
+        zip.start_file("hello.txt", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap();
+    }
+
+    let code = format!(
+        r#"<?php
+        var_dump(zip_open({missing}));
+        var_dump(zip_open({not_zip}));
+        var_dump(zip_open({dir}));
+        var_dump(zip_open({path}, ZipArchive::EXCL));
+        $zip = zip_open({path}, ZipArchive::CHECKCONS);
+        var_dump(is_resource($zip));
+        $entry = zip_read($zip);
+        echo zip_entry_name($entry), "=", zip_entry_read($entry), "\n";
+        var_dump(zip_close($zip));
+        var_dump(zip_close($zip));
+    "#,
+        missing = php_string_literal(&temp_dir.path().join("missing.zip")),
+        not_zip = php_string_literal(&not_zip),
+        dir = php_string_literal(temp_dir.path()),
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "int(9)\nint(19)\nint(5)\nint(10)\nbool(true)\nhello.txt=hello\nNULL\nbool(false)\n"
+    );
+}
+
+#[test]
+fn test_zip_archive_entry_comments() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("comments.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("a.txt", "A");
+        $zip->addFromString("b.txt", "B");
+        var_dump($zip->setArchiveComment("archive note"));
+        var_dump($zip->setCommentName("a.txt", "first entry"));
+        var_dump($zip->setCommentIndex(1, "second entry"));
+        var_dump($zip->setCommentName("missing.txt", "x"));
+        var_dump($zip->getCommentName("a.txt"), $zip->getCommentIndex(1, ZipArchive::FL_UNCHANGED));
+        $zip->close();
+
+        $zip->open({path});
+        var_dump($zip->getArchiveComment(), $zip->getCommentName("a.txt"), $zip->getCommentIndex(1));
+        $zip->setCommentName("b.txt", "changed");
+        $zip->addFromString("c.txt", "C");
+        var_dump($zip->getCommentName("b.txt", ZipArchive::FL_UNCHANGED), $zip->getCommentName("c.txt"));
+        var_dump($zip->getCommentIndex(9));
+        $zip->close();
+
+        $zip->open({path});
+        var_dump($zip->getCommentName("a.txt"), $zip->getCommentName("b.txt"), $zip->getArchiveComment());
+        $zip->close();
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\nbool(true)\nbool(true)\nbool(false)\n\
+         string(11) \"first entry\"\nstring(0) \"\"\n\
+         string(12) \"archive note\"\nstring(11) \"first entry\"\nstring(12) \"second entry\"\n\
+         string(12) \"second entry\"\nstring(0) \"\"\nbool(false)\n\
+         string(11) \"first entry\"\nstring(7) \"changed\"\nstring(12) \"archive note\"\n"
+    );
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+    assert_eq!(archive.comment(), b"archive note");
+    assert_eq!(archive.by_name("b.txt").unwrap().comment(), "changed");
+    assert_eq!(archive.by_name("c.txt").unwrap().comment(), "");
+    let mut content = String::new();
+    std::io::Read::read_to_string(&mut archive.by_name("c.txt").unwrap(), &mut content).unwrap();
+    assert_eq!(content, "C");
+}
+
+#[test]
+fn test_zip_archive_get_stream_sixteen_byte_chunks() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let zip_path = temp_dir.path().join("chunks16.zip");
+
+    let code = format!(
+        r#"<?php
+        $zip = new ZipArchive();
+        $zip->open({path}, ZipArchive::CREATE);
+        $zip->addFromString("alphabet.txt", "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMN");
+        $zip->close();
+
+        $zip->open({path});
+        $fp = $zip->getStream("alphabet.txt");
+        var_dump(is_resource($fp));
+        while (!feof($fp)) {{
+            $chunk = fread($fp, 16);
+            echo strlen($chunk), ":", $chunk, "\n";
+        }}
+        var_dump(fclose($fp));
+        $zip->close();
+    "#,
+        path = php_string_literal(&zip_path)
+    );
+    let (_, output) = run_code_capture_output(&code).unwrap();
+    assert_eq!(
+        output,
+        "bool(true)\n16:abcdefghijklmnop\n16:qrstuvwxyzABCDEF\n8:GHIJKLMN\nbool(true)\n"
+    );
+}
@@ -336,3 +336,325 @@ fn test_gzgets_gzfile() {
     // Cleanup
     let _ = std::fs::remove_file(filename);
 }
+
+#[test]
+fn test_gz_stream_mode_mismatch_warns_and_continues() {
+    let path = std::env::temp_dir().join("php_rs_gz_mode_mismatch.gz");
+    let code = format!(
+        r#"<?php
+        $path = '{}';
+        $w = gzopen($path, 'wb');
+        gzwrite($w, "line\n");
+        $results = [gzread($w, 4), gzgets($w)];
+        gzclose($w);
+
+        $r = gzopen($path, 'rb');
+        $results[] = gzwrite($r, 'more');
+        $results[] = error_get_last()['message'];
+        $results[] = gzgets($r);
+        gzclose($r);
+        $results[] = @gzopen($path . '.missing/x', 'rb');
+        return json_encode($results);
+        "#,
+        path.display()
+    );
+    let result = php_rs::vm::executor::execute_code(&code).expect("script should keep running");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(
+        result.value,
+        Val::String(Rc::new(
+            br#"[false,false,false,"gzwrite(): File opened for reading","line\n",false]"#.to_vec()
+        ))
+    );
+}
+
+#[test]
+fn test_gzopen_use_include_path() {
+    let dir = std::env::temp_dir().join("php_rs_gz_include_path");
+    std::fs::create_dir_all(&dir).unwrap();
+    let code = format!(
+        r#"<?php
+        $dir = '{}';
+        $w = gzopen($dir . '/only_in_include_path.gz', 'wb');
+        gzwrite($w, "first\nsecond\n");
+        gzclose($w);
+
+        ini_set('include_path', '/nonexistent' . PATH_SEPARATOR . $dir);
+        $r = gzopen('only_in_include_path.gz', 'rb', 1);
+        $results = [is_resource($r), gzgets($r)];
+        gzclose($r);
+        $results[] = @gzopen('only_in_include_path.gz', 'rb', 0);
+        $results[] = gzfile('only_in_include_path.gz', 1);
+        return json_encode($results);
+        "#,
+        dir.display()
+    );
+    let result = php_rs::vm::executor::execute_code(&code).expect("script should run");
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(
+        result.value,
+        Val::String(Rc::new(
+            br#"[true,"first\n",false,["first\n","second\n"]]"#.to_vec()
+        ))
+    );
+}
+
+/// Run `code` through a full engine, returning the raw bytes the SAPI
+/// received and the VM (for inspecting headers)
+fn run_with_raw_output(code: &str, ini: &[(&str, &str)]) -> (Vec<u8>, VM) {
+    use php_rs::compiler::emitter::Emitter;
+    use php_rs::runtime::context::RequestContext;
+    use php_rs::vm::engine::CapturingOutputWriter;
+    use std::cell::RefCell;
+
+    let arena = bumpalo::Bump::new();
+    let lexer = php_rs::parser::lexer::Lexer::new(code.as_bytes());
+    let mut parser = php_rs::parser::parser::Parser::new(lexer, &arena);
+    let program = parser.parse_program();
+    assert!(program.errors.is_empty(), "{:?}", program.errors);
+
+    let engine = EngineBuilder::new()
+        .with_core_extensions()
+        .build()
+        .expect("Failed to build engine");
+    let mut request_context = RequestContext::new(engine);
+    for (name, value) in ini {
+        request_context
+            .config
+            .ini_settings
+            .insert(name.to_string(), value.to_string());
+    }
+    let emitter = Emitter::new(code.as_bytes(), &mut request_context.interner);
+    let (chunk, _) = emitter.compile(program.statements);
+
+    let output = Rc::new(RefCell::new(Vec::new()));
+    let sink = output.clone();
+    let mut vm = VM::new_with_context(request_context);
+    vm.set_output_writer(Box::new(CapturingOutputWriter::new(
+        move |bytes: &[u8]| sink.borrow_mut().extend_from_slice(bytes),
+    )));
+    vm.run(Rc::new(chunk)).expect("script should run");
+    php_rs::builtins::output_control::flush_all_output_buffers(&mut vm).unwrap();
+
+    let bytes = output.borrow().clone();
+    (bytes, vm)
+}
+
+#[test]
+fn test_output_compression_gzips_script_output() {
+    use std::io::Read;
+
+    let code = r#"<?php
+        echo "coding: ", var_export(zlib_get_coding_type(), true), "\n";
+        ob_start();
+        for ($i = 0; $i < 500; $i++) {
+            echo "line $i\n";
+        }
+        ob_end_flush();
+        print str_repeat("abc", 1000);
+        exit("\nbye\n");
+        echo "not reached";
+    "#;
+
+    let (plain, _) = run_with_raw_output(code, &[]);
+    let (compressed, vm) = run_with_raw_output(code, &[("zlib.output_compression", "1")]);
+
+    assert!(compressed.starts_with(&[0x1f, 0x8b]));
+    assert!(compressed.len() < plain.len());
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(&compressed[..])
+        .read_to_end(&mut decoded)
+        .expect("output should be a complete gzip stream");
+
+    let expected = String::from_utf8(plain)
+        .unwrap()
+        .replacen("coding: false", "coding: 'gzip'", 1);
+    assert_eq!(String::from_utf8(decoded).unwrap(), expected);
+    assert!(
+        vm.context
+            .headers
+            .iter()
+            .any(|h| h.line == b"Content-Encoding: gzip")
+    );
+}
+
+#[test]
+fn test_output_compression_rejects_ob_gzhandler() {
+    use std::io::Read;
+
+    let code = r#"<?php
+        $started = @ob_start('ob_gzhandler');
+        $error = error_get_last();
+        echo json_encode([$started, $error ? $error['message'] : null]);
+    "#;
+
+    let (compressed, vm) = run_with_raw_output(code, &[("zlib.output_compression", "On")]);
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&compressed[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, r#"[false,"ob_start(): Failed to create buffer"]"#);
+    assert!(vm.output_buffers.is_empty());
+
+    // Without the setting the handler is accepted and nothing is compressed
+    let (plain, _) = run_with_raw_output(code, &[]);
+    assert_eq!(String::from_utf8(plain).unwrap(), "[true,null]");
+}
+
+#[test]
+fn test_decompress_max_length_and_corrupt_input() {
+    let (output, _) = run_with_raw_output(
+        r#"<?php
+        $data = str_repeat("Hello world! ", 50);
+        $encoded = [
+            "gzuncompress" => gzcompress($data),
+            "gzinflate" => gzdeflate($data),
+            "gzdecode" => gzencode($data),
+            "zlib_decode" => zlib_encode($data, ZLIB_ENCODING_GZIP),
+        ];
+        foreach ($encoded as $func => $compressed) {
+            $short = $func($compressed, 12);
+            $exact = $func($compressed, strlen($data));
+            $long = $func($compressed, strlen($data) + 100);
+            echo $func, ": ", var_export($short, true), " ",
+                $exact === $data ? "exact" : "wrong", " ",
+                $long === $data ? "long" : "wrong", "\n";
+        }
+
+        $compressed = gzcompress($data);
+        $corrupt = substr($compressed, 0, 10) . str_repeat("\xff", 8) . substr($compressed, 18);
+        var_dump(gzuncompress($corrupt));
+        echo error_get_last()["message"], "\n";
+        var_dump(gzuncompress(substr($compressed, 0, -6)));
+        echo error_get_last()["message"], "\n";
+        // Flip a bit of the CRC-32 in the gzip trailer
+        $gzip = gzencode($data);
+        $crc = substr($gzip, -8, 1);
+        var_dump(gzdecode(substr_replace($gzip, chr(ord($crc) ^ 1), -8, 1)));
+        echo error_get_last()["message"], "\n";
+    "#,
+        &[],
+    );
+    let output = String::from_utf8(output).unwrap();
+    for func in ["gzuncompress", "gzinflate", "gzdecode", "zlib_decode"] {
+        assert!(
+            output.contains(&format!("{}: 'Hello world!' exact long\n", func)),
+            "{}",
+            output
+        );
+    }
+    assert!(
+        output.ends_with(
+            "bool(false)\ngzuncompress(): data error\n\
+             bool(false)\ngzuncompress(): data error\n\
+             bool(false)\ngzdecode(): data error\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_zlib_functions_accept_symbolic_constants() {
+    let (output, _) = run_with_raw_output(
+        r#"<?php
+        $data = str_repeat("The quick brown fox jumps over the lazy dog. ", 20);
+        echo ZLIB_ENCODING_RAW, " ", ZLIB_ENCODING_DEFLATE, " ", ZLIB_ENCODING_GZIP, " ",
+            FORCE_GZIP, " ", FORCE_DEFLATE, "\n";
+        echo ZLIB_NO_FLUSH, ZLIB_PARTIAL_FLUSH, ZLIB_SYNC_FLUSH, ZLIB_FULL_FLUSH,
+            ZLIB_BLOCK, ZLIB_FINISH, " ", ZLIB_OK, ZLIB_STREAM_END, ZLIB_BUF_ERROR, " ",
+            ZLIB_DEFAULT_STRATEGY, ZLIB_FILTERED, ZLIB_HUFFMAN_ONLY, ZLIB_RLE, ZLIB_FIXED, "\n";
+
+        // Each encoding matches the dedicated one-shot function
+        $pairs = [
+            "raw" => [zlib_encode($data, ZLIB_ENCODING_RAW), "gzinflate"],
+            "deflate" => [zlib_encode($data, ZLIB_ENCODING_DEFLATE), "gzuncompress"],
+            "gzip" => [zlib_encode($data, ZLIB_ENCODING_GZIP), "gzdecode"],
+            "gzcompress" => [gzcompress($data, 6, ZLIB_ENCODING_GZIP), "gzdecode"],
+            "gzdeflate" => [gzdeflate($data, 6, ZLIB_ENCODING_DEFLATE), "gzuncompress"],
+            "gzencode" => [gzencode($data, 6, FORCE_DEFLATE), "gzuncompress"],
+        ];
+        foreach ($pairs as $name => $pair) {
+            $encoded = $pair[0];
+            $decode = $pair[1];
+            echo $name, " ", $decode($encoded) === $data ? "ok" : "bad", " ",
+                zlib_decode($encoded) === $data ? "ok" : "bad", "\n";
+        }
+
+        foreach ([ZLIB_ENCODING_RAW, ZLIB_ENCODING_DEFLATE, ZLIB_ENCODING_GZIP] as $encoding) {
+            $deflate = deflate_init($encoding, ["level" => 9]);
+            $stream = deflate_add($deflate, substr($data, 0, 100), ZLIB_NO_FLUSH)
+                . deflate_add($deflate, substr($data, 100, 200), ZLIB_SYNC_FLUSH)
+                . deflate_add($deflate, substr($data, 300), ZLIB_FINISH);
+            $inflate = inflate_init($encoding);
+            $decoded = "";
+            foreach (str_split($stream, 7) as $chunk) {
+                $decoded .= inflate_add($inflate, $chunk, ZLIB_SYNC_FLUSH);
+            }
+            echo $encoding, " ", $decoded === $data ? "ok" : "bad", " ",
+                inflate_get_status($inflate) === ZLIB_STREAM_END ? "end" : "open", "\n";
+        }
+        echo bin2hex(substr(deflate_add(deflate_init(ZLIB_ENCODING_GZIP), "", ZLIB_FINISH), 0, 3)), "\n";
+    "#,
+        &[],
+    );
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "-15 15 31 31 15\n012354 01-5 01234\n\
+         raw ok ok\ndeflate ok ok\ngzip ok ok\n\
+         gzcompress ok ok\ngzdeflate ok ok\ngzencode ok ok\n\
+         -15 ok end\n15 ok end\n31 ok end\n1f8b08\n"
+    );
+}
+
+#[test]
+fn test_gz_stream_zero_and_negative_lengths() {
+    let path = std::env::temp_dir().join("php_rs_gz_zero_lengths.gz");
+    {
+        use std::io::Write;
+        let file = std::fs::File::create(&path).unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        encoder.write_all(b"ab\ncd").unwrap();
+        encoder.finish().unwrap();
+    }
+
+    let (output, _) = run_with_raw_output(
+        &format!(
+            r#"<?php
+        $r = gzopen('{}', 'rb');
+        var_dump(gzgets($r, 1), gztell($r), gzeof($r));
+        echo json_encode([gzgets($r, 2), gzgets($r, 3), gzread($r, 1), gztell($r)]), "\n";
+        echo json_encode([gzread($r, 10), gzgetc($r), gztell($r), gzgetc($r), gztell($r), gzeof($r)]), "\n";
+        gzclose($r);
+    "#,
+            path.display()
+        ),
+        &[],
+    );
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "bool(false)\nint(0)\nbool(false)\n[\"a\",\"b\\n\",\"c\",4]\n[\"d\",false,5,false,5,true]\n"
+    );
+
+    let mut vm = create_test_vm();
+    let path_handle = vm.arena.alloc(Val::String(Rc::new(
+        path.to_str().unwrap().as_bytes().to_vec(),
+    )));
+    let mode_handle = vm.arena.alloc(Val::String(Rc::new(b"rb".to_vec())));
+    let gz = php_rs::builtins::zlib::php_gzopen(&mut vm, &[path_handle, mode_handle]).unwrap();
+    for length in [0, -1] {
+        let length_handle = vm.arena.alloc(Val::Int(length));
+        assert_eq!(
+            php_rs::builtins::zlib::php_gzread(&mut vm, &[gz, length_handle]).unwrap_err(),
+            "gzread(): Argument #2 ($length) must be greater than 0"
+        );
+        assert_eq!(
+            php_rs::builtins::zlib::php_gzgets(&mut vm, &[gz, length_handle]).unwrap_err(),
+            "gzgets(): Argument #2 ($length) must be greater than 0"
+        );
+    }
+    let tell = php_rs::builtins::zlib::php_gztell(&mut vm, &[gz]).unwrap();
+    assert_eq!(vm.arena.get(tell).value, Val::Int(0));
+
+    std::fs::remove_file(&path).ok();
+}